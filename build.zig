@@ -21,6 +21,16 @@ const pkgs = struct {
     };
 };
 
+/// Each of lib/gale/features.zig's Features, as `zig build -DNAME=false`
+/// leaves it out of the CLI.
+const FEATURES = .{
+    .{ "readline", "Include the REPL's debugger and Ctrl-C handling (default: true)" },
+    .{ "regex", "Include the regex word group (default: true)" },
+    .{ "json", "Include the JSON word group (default: true)" },
+    .{ "net", "Include the TCP and HTTP word groups (default: true)" },
+    .{ "crypto", "Include the digest word group (default: true)" },
+};
+
 pub fn build(b: *std.build.Builder) void {
     // Standard target options allows the person running `zig build` to choose
     // what target to build for. Here we do not override the defaults, which
@@ -32,14 +42,34 @@ pub fn build(b: *std.build.Builder) void {
     // between Debug, ReleaseSafe, ReleaseFast, and ReleaseSmall.
     const mode = b.standardReleaseOptions();
 
+    // Word groups (and REPL niceties) to compile in; see
+    // lib/gale/features.zig. Only the CLI is built with these: libgale on its
+    // own leaves the choice to whatever links it.
+    const build_options = b.addOptions();
+    inline for (FEATURES) |feature| {
+        const enabled = b.option(bool, feature[0], feature[1]) orelse true;
+        build_options.addOption(bool, feature[0], enabled);
+    }
+
     const lib = b.addStaticLibrary("gale", "lib/gale/gale.zig");
     lib.setBuildMode(mode);
     lib.install();
 
+    // The nucleus must remain buildable without an OS underneath it (see
+    // helpers.HOSTED), which this step keeps honest. It isn't part of the
+    // default install.
+    const freestanding_lib = b.addStaticLibrary("gale-freestanding", "lib/gale/gale.zig");
+    freestanding_lib.setTarget(.{ .cpu_arch = .wasm32, .os_tag = .freestanding });
+    freestanding_lib.setBuildMode(mode);
+
+    const freestanding_step = b.step("freestanding", "Build libgale for wasm32-freestanding");
+    freestanding_step.dependOn(&freestanding_lib.step);
+
     const exe = b.addExecutable("gale", "src/gale/main.zig");
     exe.setTarget(target);
     exe.setBuildMode(mode);
     exe.addPackage(pkgs.gale);
+    exe.addOptions("build_options", build_options);
     exe.install();
 
     const run_cmd = exe.run();
@@ -60,6 +90,8 @@ pub fn build(b: *std.build.Builder) void {
     const exe_tests = b.addTest("src/gale/main.zig");
     exe_tests.setTarget(target);
     exe_tests.setBuildMode(mode);
+    exe_tests.addPackage(pkgs.gale);
+    exe_tests.addOptions("build_options", build_options);
 
     // End-to-end tests of the protolang
     const protolang_tests = b.addTest("tests/test_protolang.zig");
@@ -0,0 +1,389 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectApproxEqAbs = std.testing.expectApproxEqAbs;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every arithmetic word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@ADD", ADD },
+    .{ "@SUB", SUB },
+    .{ "@MUL", MUL },
+    .{ "@ADD-WRAP", ADD_WRAP },
+    .{ "@SUB-WRAP", SUB_WRAP },
+    .{ "@MUL-WRAP", MUL_WRAP },
+    .{ "@ADD-SAT", ADD_SAT },
+    .{ "@SUB-SAT", SUB_SAT },
+    .{ "@MUL-SAT", MUL_SAT },
+    .{ "@MIN", MIN },
+    .{ "@MAX", MAX },
+    .{ "@ABS", ABS },
+    .{ "@NEG", NEG },
+    .{ "@SIGN", SIGN },
+};
+
+/// Define every arithmetic word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+// Each word takes two numbers of the same kind (a TypeError otherwise), the
+// one further down the Stack first: `5 3 @SUB` is 2. Integers are as wide as
+// the target's usize, and what happens when a result doesn't fit is up to
+// the word: @ADD and friends call it a ValueError, the -WRAP words wrap
+// around (two's complement, for SignedInts), and the -SAT words stop at the
+// nearest bound. Only the first kind take Floats, which overflow to infinity
+// as floats do. Either way, failing leaves the Stack untouched.
+
+const Op = enum { Add, Sub, Mul };
+
+const Overflow = enum { Checked, Wrapping, Saturating };
+
+/// @ADD ( @1 @1 <- @1 )
+pub fn ADD(runtime: *Runtime) anyerror!void {
+    try arithmetic(runtime, .Add, .Checked);
+}
+
+/// @SUB ( @1 @1 <- @1 )
+pub fn SUB(runtime: *Runtime) anyerror!void {
+    try arithmetic(runtime, .Sub, .Checked);
+}
+
+/// @MUL ( @1 @1 <- @1 )
+pub fn MUL(runtime: *Runtime) anyerror!void {
+    try arithmetic(runtime, .Mul, .Checked);
+}
+
+/// @ADD-WRAP ( @1 @1 <- @1 )
+pub fn ADD_WRAP(runtime: *Runtime) anyerror!void {
+    try arithmetic(runtime, .Add, .Wrapping);
+}
+
+/// @SUB-WRAP ( @1 @1 <- @1 )
+pub fn SUB_WRAP(runtime: *Runtime) anyerror!void {
+    try arithmetic(runtime, .Sub, .Wrapping);
+}
+
+/// @MUL-WRAP ( @1 @1 <- @1 )
+pub fn MUL_WRAP(runtime: *Runtime) anyerror!void {
+    try arithmetic(runtime, .Mul, .Wrapping);
+}
+
+/// @ADD-SAT ( @1 @1 <- @1 )
+pub fn ADD_SAT(runtime: *Runtime) anyerror!void {
+    try arithmetic(runtime, .Add, .Saturating);
+}
+
+/// @SUB-SAT ( @1 @1 <- @1 )
+pub fn SUB_SAT(runtime: *Runtime) anyerror!void {
+    try arithmetic(runtime, .Sub, .Saturating);
+}
+
+/// @MUL-SAT ( @1 @1 <- @1 )
+pub fn MUL_SAT(runtime: *Runtime) anyerror!void {
+    try arithmetic(runtime, .Mul, .Saturating);
+}
+
+// The rest follow @EQ's lead as far as kinds go: @MIN and @MAX only compare
+// numbers of the same kind, and none of these words turn one kind of number
+// into another. That leaves a few results with nowhere to go, which are
+// ValueErrors as above: the absolute value or negation of the most negative
+// SignedInt, and the negation of any UnsignedInt but 0.
+
+/// @MIN ( @1 @1 <- @1 )
+///
+/// The lesser of two numbers, the lower one on a tie. A NaN on either side
+/// makes for a NaN.
+pub fn MIN(runtime: *Runtime) anyerror!void {
+    try extreme(runtime, .Min);
+}
+
+/// @MAX ( @1 @1 <- @1 )
+///
+/// The greater of two numbers, the lower one on a tie. A NaN on either side
+/// makes for a NaN.
+pub fn MAX(runtime: *Runtime) anyerror!void {
+    try extreme(runtime, .Max);
+}
+
+/// @ABS ( @1 <- @1 )
+pub fn ABS(runtime: *Runtime) anyerror!void {
+    const top = try runtime.stack_peek();
+    switch (top.*) {
+        .UnsignedInt => {},
+        .SignedInt => |value| {
+            const result = std.math.absInt(value) catch return InternalError.ValueError;
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_sint(result);
+        },
+        .Float => |value| {
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_float(@fabs(value));
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+/// @NEG ( @1 <- @1 )
+pub fn NEG(runtime: *Runtime) anyerror!void {
+    const top = try runtime.stack_peek();
+    switch (top.*) {
+        .UnsignedInt => |value| if (value != 0) return InternalError.ValueError,
+        .SignedInt => |value| {
+            const result = std.math.negate(value) catch return InternalError.ValueError;
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_sint(result);
+        },
+        .Float => |value| {
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_float(-value);
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+/// @SIGN ( @1 <- @1 )
+///
+/// -1, 0, or 1, of the same kind as the number. Zeroes and NaNs are their
+/// own sign, so `-0.0 @SIGN` is -0.0.
+pub fn SIGN(runtime: *Runtime) anyerror!void {
+    const top = try runtime.stack_peek();
+    switch (top.*) {
+        .UnsignedInt => |value| {
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_uint(@boolToInt(value != 0));
+        },
+        .SignedInt => |value| {
+            const result: isize = if (value > 0) 1 else if (value < 0) -1 else 0;
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_sint(result);
+        },
+        .Float => |value| {
+            const result = if (value > 0) @as(f64, 1.0) else if (value < 0) @as(f64, -1.0) else value;
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_float(result);
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+const Extreme = enum { Min, Max };
+
+fn extreme(runtime: *Runtime, comptime which: Extreme) !void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    _ = try peek.near.assert_same_kind_as(far);
+
+    switch (peek.near.*) {
+        .UnsignedInt => |rhs| {
+            const result = pick(usize, which, far.UnsignedInt, rhs);
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_uint(result);
+        },
+        .SignedInt => |rhs| {
+            const result = pick(isize, which, far.SignedInt, rhs);
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_sint(result);
+        },
+        .Float => |rhs| {
+            const lhs = far.Float;
+            const result = if (std.math.isNan(lhs) or std.math.isNan(rhs))
+                std.math.nan(f64)
+            else
+                pick(f64, which, lhs, rhs);
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_float(result);
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+fn pick(comptime T: type, comptime which: Extreme, lhs: T, rhs: T) T {
+    return switch (which) {
+        .Min => if (rhs < lhs) rhs else lhs,
+        .Max => if (rhs > lhs) rhs else lhs,
+    };
+}
+
+fn arithmetic(runtime: *Runtime, comptime op: Op, comptime overflow: Overflow) !void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    _ = try peek.near.assert_same_kind_as(far);
+
+    switch (peek.near.*) {
+        .UnsignedInt => |rhs| {
+            const result = try apply(usize, op, overflow, far.UnsignedInt, rhs);
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_uint(result);
+        },
+        .SignedInt => |rhs| {
+            const result = try apply(isize, op, overflow, far.SignedInt, rhs);
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_sint(result);
+        },
+        .Float => |rhs| {
+            if (overflow != .Checked) return InternalError.TypeError;
+            const lhs = far.Float;
+            const result = switch (op) {
+                .Add => lhs + rhs,
+                .Sub => lhs - rhs,
+                .Mul => lhs * rhs,
+            };
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_float(result);
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+fn apply(comptime T: type, comptime op: Op, comptime overflow: Overflow, lhs: T, rhs: T) InternalError!T {
+    return switch (overflow) {
+        .Checked => switch (op) {
+            .Add => std.math.add(T, lhs, rhs),
+            .Sub => std.math.sub(T, lhs, rhs),
+            .Mul => std.math.mul(T, lhs, rhs),
+        } catch InternalError.ValueError,
+        .Wrapping => switch (op) {
+            .Add => lhs +% rhs,
+            .Sub => lhs -% rhs,
+            .Mul => lhs *% rhs,
+        },
+        .Saturating => switch (op) {
+            .Add => lhs +| rhs,
+            .Sub => lhs -| rhs,
+            .Mul => lhs *| rhs,
+        },
+    };
+}
+
+test "checked" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("5 3 @SUB");
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+    try runtime.eval("-5 +3 @ADD");
+    try expectEqual(@as(isize, -2), try runtime.stack_pop_value(isize));
+    try runtime.eval("6 7 @MUL");
+    try expectEqual(@as(usize, 42), try runtime.stack_pop_value(usize));
+    try runtime.eval("1.5 2.0 @MUL");
+    try expectApproxEqAbs(@as(f64, 3.0), try runtime.stack_pop_value(f64), 0.0001);
+
+    try expectError(InternalError.ValueError, runtime.eval("3 5 @SUB"));
+    try expectEqual(@as(usize, 2), runtime.stack.depth());
+    try runtime.eval("@DROP @DROP");
+    try expectError(InternalError.TypeError, runtime.eval("-1 1 @ADD"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test "wrapping and saturating" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("3 5 @SUB-WRAP");
+    try expectEqual(@as(usize, std.math.maxInt(usize) - 1), try runtime.stack_pop_value(usize));
+    try runtime.eval("3 5 @SUB-SAT");
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+
+    try runtime.stack_push_sint(std.math.maxInt(isize));
+    try runtime.eval("+1 @ADD-WRAP");
+    try expectEqual(@as(isize, std.math.minInt(isize)), try runtime.stack_pop_value(isize));
+    try runtime.stack_push_sint(std.math.minInt(isize));
+    try runtime.eval("+2 @MUL-SAT");
+    try expectEqual(@as(isize, std.math.minInt(isize)), try runtime.stack_pop_value(isize));
+    try runtime.stack_push_uint(std.math.maxInt(usize));
+    try runtime.eval("2 @MUL-WRAP");
+    try expectEqual(@as(usize, std.math.maxInt(usize) - 1), try runtime.stack_pop_value(usize));
+    try runtime.stack_push_uint(std.math.maxInt(usize));
+    try runtime.eval("1 @ADD-SAT");
+    try expectEqual(@as(usize, std.math.maxInt(usize)), try runtime.stack_pop_value(usize));
+
+    try expectError(InternalError.TypeError, runtime.eval("1.5 2.5 @ADD-WRAP"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test "MIN and MAX" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("3 5 @MIN");
+    try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+    try runtime.eval("-3 +5 @MAX");
+    try expectEqual(@as(isize, 5), try runtime.stack_pop_value(isize));
+    try runtime.eval("-0.5 1.5 @MIN");
+    try expectEqual(@as(f64, -0.5), try runtime.stack_pop_value(f64));
+    try runtime.eval("1.5 -1.0 @SQRT @MAX");
+    try expect(std.math.isNan(try runtime.stack_pop_value(f64)));
+
+    try expectError(InternalError.TypeError, runtime.eval("3 +5 @MIN"));
+    try runtime.eval("@DROP @DROP");
+    try expectError(InternalError.TypeError, runtime.eval("3 5.0 @MAX"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test "ABS, NEG, and SIGN" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("-7 @ABS");
+    try expectEqual(@as(isize, 7), try runtime.stack_pop_value(isize));
+    try runtime.eval("7 @ABS");
+    try expectEqual(@as(usize, 7), try runtime.stack_pop_value(usize));
+    try runtime.eval("-2.5 @ABS");
+    try expectEqual(@as(f64, 2.5), try runtime.stack_pop_value(f64));
+    try runtime.eval("+7 @NEG");
+    try expectEqual(@as(isize, -7), try runtime.stack_pop_value(isize));
+    try runtime.eval("0 @NEG");
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+    try runtime.eval("2.5 @NEG");
+    try expectEqual(@as(f64, -2.5), try runtime.stack_pop_value(f64));
+
+    // The most negative SignedInt has no positive counterpart to become.
+    try runtime.stack_push_sint(std.math.minInt(isize));
+    try expectError(InternalError.ValueError, runtime.eval("@ABS"));
+    try expectError(InternalError.ValueError, runtime.eval("@NEG"));
+    try expectEqual(@as(isize, std.math.minInt(isize)), try runtime.stack_pop_value(isize));
+    try runtime.stack_push_sint(std.math.maxInt(isize));
+    try runtime.eval("@NEG");
+    try expectEqual(@as(isize, -std.math.maxInt(isize)), try runtime.stack_pop_value(isize));
+    try expectError(InternalError.ValueError, runtime.eval("1 @NEG"));
+    try runtime.eval("@DROP");
+
+    try runtime.eval("-4 @SIGN");
+    try expectEqual(@as(isize, -1), try runtime.stack_pop_value(isize));
+    try runtime.eval("+0 @SIGN");
+    try expectEqual(@as(isize, 0), try runtime.stack_pop_value(isize));
+    try runtime.eval("4 @SIGN");
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+    try runtime.eval("0 @SIGN");
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+    try runtime.eval("-0.25 @SIGN");
+    try expectEqual(@as(f64, -1.0), try runtime.stack_pop_value(f64));
+
+    try expectError(InternalError.TypeError, runtime.eval("\"hi\" @ABS"));
+    try runtime.eval("@DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
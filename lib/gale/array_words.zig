@@ -0,0 +1,420 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+// Running a Word across each member of an Array, in order, all up front (see
+// iter_words.zig for doing so a value at a time). The Word runs on the
+// Runtime's own Stack, with the member pushed beforehand and neither the
+// Array nor the Word on the Stack meanwhile, so it can reach for whatever's
+// beneath: `0 arr &@ADD @ARRAY-EACH` sums without reaching for
+// @ARRAY-REDUCE. Members a Word adds to the Array as it goes are run
+// through as well.
+//
+// Should the Word fail partway through, the error carries on up, and the
+// Stack is rolled back to just as it was before (Array, Word, and all, see
+// Runtime.stack_snapshot), as is only fair to whatever's catching it: no
+// half-mapped Arrays or half-reduced accumulators. Anything else the Word
+// did along the way (printing, setting Cells, and so on) stays done.
+
+/// Every Array word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@ARRAY-EACH", ARRAY_EACH },
+    .{ "@ARRAY-FILTER", ARRAY_FILTER },
+    .{ "@ARRAY-MAP", ARRAY_MAP },
+    .{ "@ARRAY-MAX", ARRAY_MAX },
+    .{ "@ARRAY-MIN", ARRAY_MIN },
+    .{ "@ARRAY-REDUCE", ARRAY_REDUCE },
+    .{ "@ARRAY-SORT", ARRAY_SORT },
+    .{ "@ARRAY-SORT-BY", ARRAY_SORT_BY },
+};
+
+/// Define every Array word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @ARRAY-EACH ( Array Word <- )
+///
+/// Run the Word ( @1 -> ) once for each member of the Array.
+pub fn ARRAY_EACH(runtime: *Runtime) anyerror!void {
+    try rolling_back(runtime, each);
+}
+
+/// @ARRAY-MAP ( Array Word <- Array )
+///
+/// A new Array of what the Word ( @1 -> @2 ) leaves on top of the Stack for
+/// each member of the Array.
+pub fn ARRAY_MAP(runtime: *Runtime) anyerror!void {
+    try rolling_back(runtime, map);
+}
+
+/// @ARRAY-FILTER ( Array Word <- Array )
+///
+/// A new Array of only those members of the Array for which the Word
+/// ( @1 -> Boolean ) is true. Anything but a Boolean is a TypeError.
+pub fn ARRAY_FILTER(runtime: *Runtime) anyerror!void {
+    try rolling_back(runtime, filter);
+}
+
+/// @ARRAY-REDUCE ( Array @1 Word <- @1 )
+///
+/// Fold the Array into the accumulator (@1, its starting value) from left to
+/// right: the Word ( @1 @2 -> @1 ) is run with the accumulator beneath each
+/// member, and leaves the next accumulator in their place. `arr 0 &@ADD
+/// @ARRAY-REDUCE` is the sum of the Array, and an empty Array reduces to the
+/// starting value.
+pub fn ARRAY_REDUCE(runtime: *Runtime) anyerror!void {
+    try rolling_back(runtime, reduce);
+}
+
+// Ordering, by way of Object.order: Arrays of things which can't be ordered
+// against one another (a mix of kinds, say, or NaNs) are whatever error that
+// gives. Sorts are stable, so members that order the same stay as they were.
+
+/// @ARRAY-SORT ( Array <- Array )
+///
+/// A new Array of the same members, least first.
+pub fn ARRAY_SORT(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Array) return InternalError.TypeError;
+    const members = peeked.Array.value.?.items;
+
+    const sorted = try runtime.new_array(members.len);
+    errdefer runtime.release_array(sorted);
+    for (members) |member| sorted.value.?.appendAssumeCapacity(try member.ref());
+    try stable_sort(Object, sorted.value.?.items, itself);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_array(sorted);
+}
+
+/// @ARRAY-SORT-BY ( Array Word <- Array )
+///
+/// A new Array of the same members, ordered by the key the Word ( @1 -> @2 )
+/// gives for each, least first. The Word is run once per member, rather
+/// than once per comparison.
+pub fn ARRAY_SORT_BY(runtime: *Runtime) anyerror!void {
+    try rolling_back(runtime, sort_by);
+}
+
+/// @ARRAY-MIN ( Array <- @1 )
+///
+/// The least member of the Array, the first of them on a tie. An empty
+/// Array is a ValueError.
+pub fn ARRAY_MIN(runtime: *Runtime) anyerror!void {
+    try extreme_of(runtime, .lt);
+}
+
+/// @ARRAY-MAX ( Array <- @1 )
+///
+/// The greatest member of the Array, the first of them on a tie. An empty
+/// Array is a ValueError.
+pub fn ARRAY_MAX(runtime: *Runtime) anyerror!void {
+    try extreme_of(runtime, .gt);
+}
+
+fn itself(obj: Object) Object {
+    return obj;
+}
+
+/// A member of an Array being sorted by @ARRAY-SORT-BY, alongside its key.
+/// Both references are owned.
+const Keyed = struct {
+    key: Object,
+    member: Object,
+
+    fn key_of(keyed: Keyed) Object {
+        return keyed.key;
+    }
+};
+
+/// Sort `items` by the Objects `key_of` picks out of them, least first,
+/// keeping those with equal keys in their original order.
+fn stable_sort(comptime T: type, items: []T, comptime key_of: fn (T) Object) InternalError!void {
+    const Context = struct {
+        // std.sort can't be told to stop, so the first failure to order two
+        // keys is kept for afterwards, the rest of the sort being garbage.
+        failure: ?InternalError = null,
+
+        fn less_than(context: *@This(), lhs: T, rhs: T) bool {
+            const ordering = key_of(lhs).order(key_of(rhs)) catch |err| {
+                if (context.failure == null) context.failure = err;
+                return false;
+            };
+            return ordering == .lt;
+        }
+    };
+
+    var context = Context{};
+    std.sort.sort(T, items, &context, Context.less_than);
+    if (context.failure) |err| return err;
+}
+
+fn sort_by(runtime: *Runtime) anyerror!void {
+    var pair = try pop_array_and_word(runtime);
+    defer runtime.release_heaped_object_reference(&pair.near);
+    defer runtime.release_heaped_object_reference(&pair.far);
+
+    var keyed = std.ArrayList(Keyed).init(runtime.alloc);
+    defer {
+        for (keyed.items) |*each_keyed| {
+            runtime.release_heaped_object_reference(&each_keyed.key);
+            runtime.release_heaped_object_reference(&each_keyed.member);
+        }
+        keyed.deinit();
+    }
+
+    var idx: usize = 0;
+    while (try push_member(runtime, pair.far.Array, idx)) : (idx += 1) {
+        try runtime.run_word(pair.near.Word);
+        var entry = Keyed{ .key = try runtime.stack_pop(), .member = undefined };
+        entry.member = pair.far.Array.value.?.items[idx].ref() catch |err| {
+            runtime.release_heaped_object_reference(&entry.key);
+            return err;
+        };
+        keyed.append(entry) catch |err| {
+            runtime.release_heaped_object_reference(&entry.key);
+            runtime.release_heaped_object_reference(&entry.member);
+            return err;
+        };
+    }
+
+    try stable_sort(Keyed, keyed.items, Keyed.key_of);
+
+    const sorted = try runtime.new_array(keyed.items.len);
+    errdefer runtime.release_array(sorted);
+    for (keyed.items) |entry| sorted.value.?.appendAssumeCapacity(try entry.member.ref());
+
+    try runtime.stack_push_array(sorted);
+}
+
+fn extreme_of(runtime: *Runtime, comptime wanted: std.math.Order) !void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Array) return InternalError.TypeError;
+    const members = peeked.Array.value.?.items;
+    if (members.len == 0) return InternalError.ValueError;
+
+    var best = members[0];
+    for (members[1..]) |member| {
+        if ((try member.order(best)) == wanted) best = member;
+    }
+
+    const result = try best.ref();
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(result);
+}
+
+fn rolling_back(runtime: *Runtime, comptime body: fn (*Runtime) anyerror!void) !void {
+    const snap = try runtime.stack_snapshot();
+    body(runtime) catch |err| {
+        runtime.stack_restore(snap);
+        return err;
+    };
+    snap.discard();
+}
+
+/// Pop the Array and Word on top of the Stack, whose references the caller
+/// then owns.
+fn pop_array_and_word(runtime: *Runtime) !Types.PopPairExternal {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Word or far.* != .Array) return InternalError.TypeError;
+    return runtime.stack_pop_pair();
+}
+
+/// Push a new reference to member `idx` of `array`, if it has one.
+fn push_member(runtime: *Runtime, array: *Types.HeapedArray, idx: usize) !bool {
+    const items = array.value.?.items;
+    if (idx >= items.len) return false;
+    try runtime.stack_push_object(try items[idx].ref());
+    return true;
+}
+
+fn each(runtime: *Runtime) anyerror!void {
+    var pair = try pop_array_and_word(runtime);
+    defer runtime.release_heaped_object_reference(&pair.near);
+    defer runtime.release_heaped_object_reference(&pair.far);
+
+    var idx: usize = 0;
+    while (try push_member(runtime, pair.far.Array, idx)) : (idx += 1) {
+        try runtime.run_word(pair.near.Word);
+    }
+}
+
+fn map(runtime: *Runtime) anyerror!void {
+    var pair = try pop_array_and_word(runtime);
+    defer runtime.release_heaped_object_reference(&pair.near);
+    defer runtime.release_heaped_object_reference(&pair.far);
+
+    const results = try runtime.new_array(pair.far.Array.value.?.items.len);
+    errdefer runtime.release_array(results);
+
+    var idx: usize = 0;
+    while (try push_member(runtime, pair.far.Array, idx)) : (idx += 1) {
+        try runtime.run_word(pair.near.Word);
+        var result = try runtime.stack_pop();
+        results.value.?.append(result) catch |err| {
+            runtime.release_heaped_object_reference(&result);
+            return err;
+        };
+    }
+
+    try runtime.stack_push_array(results);
+}
+
+fn filter(runtime: *Runtime) anyerror!void {
+    var pair = try pop_array_and_word(runtime);
+    defer runtime.release_heaped_object_reference(&pair.near);
+    defer runtime.release_heaped_object_reference(&pair.far);
+
+    const kept = try runtime.new_array(0);
+    errdefer runtime.release_array(kept);
+
+    var idx: usize = 0;
+    while (try push_member(runtime, pair.far.Array, idx)) : (idx += 1) {
+        try runtime.run_word(pair.near.Word);
+        const verdict = try runtime.stack_peek();
+        if (verdict.* != .Boolean) return InternalError.TypeError;
+        if (!(try runtime.stack_pop()).Boolean) continue;
+
+        var member = try pair.far.Array.value.?.items[idx].ref();
+        kept.value.?.append(member) catch |err| {
+            runtime.release_heaped_object_reference(&member);
+            return err;
+        };
+    }
+
+    try runtime.stack_push_array(kept);
+}
+
+fn reduce(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_trio();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Word or far.* != .Array) return InternalError.TypeError;
+
+    var word = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&word);
+    // The accumulator's reference goes straight back onto the Stack, from
+    // beneath which the Array is plucked.
+    const accumulator = try runtime.stack_pop();
+    var array = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&array);
+    try runtime.stack_push_object(accumulator);
+
+    var idx: usize = 0;
+    while (try push_member(runtime, array.Array, idx)) : (idx += 1) {
+        try runtime.run_word(word.Word);
+    }
+}
+
+fn expect_members(runtime: *Runtime, source: []const u8, expected: []const usize) !void {
+    try runtime.eval(source);
+    var result = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&result);
+
+    const members = result.Array.value.?.items;
+    try expectEqual(expected.len, members.len);
+    for (expected) |value, idx| try expectEqual(value, members[idx].UnsignedInt);
+}
+
+test "@ARRAY-EACH, @ARRAY-MAP, and @ARRAY-FILTER" {
+    const Words = struct {
+        fn is_even(rt: *Runtime) anyerror!void {
+            try rt.stack_push_bool(try rt.stack_pop_value(usize) % 2 == 0);
+        }
+    };
+
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+    try runtime.define_word("even?", try runtime.word_from_primitive_impl(&Words.is_even, null));
+
+    try expect_members(&runtime, "0 5 @ITER-RANGE @ITER-COLLECT &even? @ARRAY-FILTER", &.{ 0, 2, 4 });
+    try expect_members(&runtime, "1 4 @ITER-RANGE @ITER-COLLECT &@DUP &@MUL @COMPOSE @ARRAY-MAP", &.{ 1, 4, 9 });
+    try expect_members(&runtime, "0 0 @ITER-RANGE @ITER-COLLECT &@DUP @ARRAY-MAP", &.{});
+
+    try runtime.eval("0 1 4 @ITER-RANGE @ITER-COLLECT &@ADD @ARRAY-EACH");
+    try expectEqual(@as(usize, 6), try runtime.stack_pop_value(usize));
+}
+
+test "@ARRAY-REDUCE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("1 5 @ITER-RANGE @ITER-COLLECT 1 &@MUL @ARRAY-REDUCE");
+    try expectEqual(@as(usize, 24), try runtime.stack_pop_value(usize));
+
+    // The accumulator is beneath each member, so order matters.
+    try runtime.eval("1 3 @ITER-RANGE @ITER-COLLECT 10 &@SUB @ARRAY-REDUCE");
+    try expectEqual(@as(usize, 7), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("0 0 @ITER-RANGE @ITER-COLLECT :empty &@ADD @ARRAY-REDUCE");
+    var untouched = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&untouched);
+    try expect(untouched == .Symbol);
+}
+
+test "sorting" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    // 3, 2, 1, and 0.
+    try runtime.eval("0 4 @ITER-RANGE @ITER-COLLECT 3 @LIT &@BXOR @COMPOSE @ARRAY-MAP");
+    try runtime.eval("@DUP @ARRAY-MIN @SWAP @DUP @ARRAY-MAX @SWAP");
+    try expect_members(&runtime, "@ARRAY-SORT", &.{ 0, 1, 2, 3 });
+    try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+
+    // Keyed by whether each is odd, evens first, and otherwise in order.
+    try expect_members(&runtime, "0 6 @ITER-RANGE @ITER-COLLECT 1 @LIT &@BAND @COMPOSE @ARRAY-SORT-BY", &.{ 0, 2, 4, 1, 3, 5 });
+
+    try expectError(InternalError.ValueError, runtime.eval("0 0 @ITER-RANGE @ITER-COLLECT @ARRAY-MAX"));
+    try expectError(InternalError.TypeError, runtime.eval("@DROP 0 2 @ITER-RANGE @ITER-COLLECT &@DUP @ARRAY-SORT"));
+    try expectEqual(@as(usize, 2), runtime.stack.depth());
+    try runtime.eval("@DROP @DROP");
+}
+
+test "failing partway through leaves the Stack as it was" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    // 3 - 1 - 2 is fine, but - 3 underflows an UnsignedInt.
+    try runtime.eval("1 4 @ITER-RANGE @ITER-COLLECT 3 &@SUB");
+    try expectError(InternalError.ValueError, runtime.eval("@ARRAY-REDUCE"));
+    try expectEqual(@as(usize, 3), runtime.stack.depth());
+    try expect((try runtime.stack_peek()).* == .Word);
+
+    try expectError(InternalError.ValueError, runtime.eval("@DROP @DROP 3 @LIT &@SUB @COMPOSE @ARRAY-MAP"));
+    try expectEqual(@as(usize, 2), runtime.stack.depth());
+
+    try expectError(InternalError.TypeError, runtime.eval("@DROP &@DUP @ARRAY-FILTER"));
+    try expectEqual(@as(usize, 2), runtime.stack.depth());
+
+    try expectError(InternalError.TypeError, runtime.eval("@DROP 5 &@DUP @ARRAY-EACH"));
+    try runtime.eval("@DROP @DROP @DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,233 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every bitwise word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@BAND", BAND },
+    .{ "@BOR", BOR },
+    .{ "@BXOR", BXOR },
+    .{ "@BNOT", BNOT },
+    .{ "@SHL", SHL },
+    .{ "@SHR", SHR },
+};
+
+/// Define every bitwise word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+// Bitwise operations on integers, as wide as the target's usize. SignedInts
+// are taken as two's complement. No word mixes UnsignedInts with SignedInts
+// (a TypeError): converting between the two is the caller's call to make,
+// not something done behind their back.
+
+const Op = enum { And, Or, Xor };
+
+/// @BAND ( @1 @1 <- @1 )
+///
+/// The bitwise AND of two integers of the same kind.
+pub fn BAND(runtime: *Runtime) anyerror!void {
+    try combine(runtime, .And);
+}
+
+/// @BOR ( @1 @1 <- @1 )
+///
+/// The bitwise OR of two integers of the same kind.
+pub fn BOR(runtime: *Runtime) anyerror!void {
+    try combine(runtime, .Or);
+}
+
+/// @BXOR ( @1 @1 <- @1 )
+///
+/// The bitwise exclusive OR of two integers of the same kind.
+pub fn BXOR(runtime: *Runtime) anyerror!void {
+    try combine(runtime, .Xor);
+}
+
+fn combine(runtime: *Runtime, comptime op: Op) !void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    _ = try peek.near.assert_same_kind_as(far);
+
+    switch (peek.near.*) {
+        .UnsignedInt => |near| {
+            const result = apply(usize, op, far.UnsignedInt, near);
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_uint(result);
+        },
+        .SignedInt => |near| {
+            const result = apply(isize, op, far.SignedInt, near);
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_sint(result);
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+fn apply(comptime T: type, comptime op: Op, lhs: T, rhs: T) T {
+    return switch (op) {
+        .And => lhs & rhs,
+        .Or => lhs | rhs,
+        .Xor => lhs ^ rhs,
+    };
+}
+
+/// @BNOT ( @1 <- @1 )
+///
+/// Every bit of an integer flipped: for SignedInts, that's the same as
+/// negating and subtracting one.
+pub fn BNOT(runtime: *Runtime) anyerror!void {
+    const top = try runtime.stack_peek();
+    switch (top.*) {
+        .UnsignedInt => |value| {
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_uint(~value);
+        },
+        .SignedInt => |value| {
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_sint(~value);
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+/// @SHL ( @1 UnsignedInt <- @1 )
+///
+/// An integer shifted left by however many bits are on top, zeroes shifted
+/// in. Bits shifted past the top are lost, sign bit and all, so shifting by
+/// the integer's width or more leaves zero.
+pub fn SHL(runtime: *Runtime) anyerror!void {
+    try shift(runtime, .Left);
+}
+
+/// @SHR ( @1 UnsignedInt <- @1 )
+///
+/// An integer shifted right by however many bits are on top. UnsignedInts
+/// have zeroes shifted in; SignedInts, copies of their sign bit, so that
+/// shifting by the integer's width or more leaves zero or -1.
+pub fn SHR(runtime: *Runtime) anyerror!void {
+    try shift(runtime, .Right);
+}
+
+const Direction = enum { Left, Right };
+
+fn shift(runtime: *Runtime, comptime direction: Direction) !void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt) return InternalError.TypeError;
+    const amount = peek.near.UnsignedInt;
+
+    switch (far.*) {
+        .UnsignedInt => |value| {
+            const result = shifted(usize, direction, value, amount);
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_uint(result);
+        },
+        .SignedInt => |value| {
+            const result = shifted(isize, direction, value, amount);
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_sint(result);
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+fn shifted(comptime T: type, comptime direction: Direction, value: T, amount: usize) T {
+    const bits = @typeInfo(T).Int.bits;
+    const Amount = std.math.Log2Int(T);
+
+    if (amount >= bits) return switch (direction) {
+        .Left => 0,
+        // Arithmetic for SignedInts, logical for UnsignedInts, as >> is.
+        .Right => value >> @intCast(Amount, bits - 1) >> 1,
+    };
+
+    return switch (direction) {
+        .Left => value << @intCast(Amount, amount),
+        .Right => value >> @intCast(Amount, amount),
+    };
+}
+
+test "BAND, BOR, BXOR" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("12 10 @BAND");
+    try expectEqual(@as(usize, 8), try runtime.stack_pop_value(usize));
+    try runtime.eval("12 10 @BOR");
+    try expectEqual(@as(usize, 14), try runtime.stack_pop_value(usize));
+    try runtime.eval("12 10 @BXOR");
+    try expectEqual(@as(usize, 6), try runtime.stack_pop_value(usize));
+    try runtime.eval("-1 +5 @BAND");
+    try expectEqual(@as(isize, 5), try runtime.stack_pop_value(isize));
+    try runtime.eval("-8 +3 @BOR");
+    try expectEqual(@as(isize, -5), try runtime.stack_pop_value(isize));
+
+    try expectError(InternalError.TypeError, runtime.eval("-1 5 @BAND"));
+    try runtime.eval("@DROP @DROP");
+    try expectError(InternalError.TypeError, runtime.eval("1.5 2.5 @BOR"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test "BNOT" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("0 @BNOT");
+    try expectEqual(~@as(usize, 0), try runtime.stack_pop_value(usize));
+    try runtime.eval("+5 @BNOT");
+    try expectEqual(@as(isize, -6), try runtime.stack_pop_value(isize));
+
+    try expectError(InternalError.TypeError, runtime.eval("\"x\" @BNOT"));
+    try runtime.eval("@DROP");
+}
+
+test "SHL and SHR" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("3 4 @SHL");
+    try expectEqual(@as(usize, 48), try runtime.stack_pop_value(usize));
+    try runtime.eval("48 4 @SHR");
+    try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+    try runtime.eval("1 999 @SHL");
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+    try runtime.eval("0 @BNOT 999 @SHR");
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("-16 2 @SHR");
+    try expectEqual(@as(isize, -4), try runtime.stack_pop_value(isize));
+    try runtime.eval("-16 999 @SHR");
+    try expectEqual(@as(isize, -1), try runtime.stack_pop_value(isize));
+    try runtime.eval("+16 999 @SHR");
+    try expectEqual(@as(isize, 0), try runtime.stack_pop_value(isize));
+    try runtime.eval("-1 1 @SHL");
+    try expectEqual(@as(isize, -2), try runtime.stack_pop_value(isize));
+
+    try expectError(InternalError.TypeError, runtime.eval("1 -1 @SHL"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
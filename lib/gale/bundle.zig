@@ -0,0 +1,327 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const helpers = @import("./helpers.zig");
+const lexer = @import("./lexer.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const ParsedWord = @import("./parsed_word.zig").ParsedWord;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// Bundles: an entry script, and every file it (however indirectly) imports
+// or loads, in one file, for running somewhere without a library path or
+// package store (see Runtime.eval_bundle). A bundle is text, each file
+// framed by a line saying what path it was asked for by and how long it is,
+// the entry script last:
+//
+//     // gale bundle 1
+//     //@file strings 12
+//     ...12 bytes of strings.gale...
+//     //@entry main.gale 30
+//     ...30 bytes of main.gale...
+//
+// Only literal paths (`"strings" @IMPORT`, and likewise for @IMPORT-AS,
+// @IMPORT-VERSION, and @LOAD) can be found ahead of time, so only those are
+// bundled: anything else is left to the file system, as ever. Files are
+// filed under the path exactly as written, so two spellings of one path are
+// bundled twice.
+//
+// Bundles can also ride along at the end of an executable (see TRAILER), for
+// hosts like the CLI to find and run in place of whatever they'd normally
+// do.
+
+pub const MAGIC = "// gale bundle 1\n";
+const FILE_HEADER = "//@file ";
+const ENTRY_HEADER = "//@entry ";
+
+/// After a bundle tacked onto the end of an executable: the bundle's length
+/// (as a little-endian u64), then this.
+pub const TRAILER = "GALEBNDL";
+pub const TRAILER_LEN = @sizeOf(u64) + TRAILER.len;
+
+pub const File = struct {
+    /// As the import (or the host, for the entry script) asked for it.
+    path: []const u8,
+    source: []const u8,
+};
+
+/// A bundle's files, sliced from its text, which must outlive it.
+pub const Bundle = struct {
+    const Self = @This();
+
+    alloc: Allocator,
+    files: []File,
+    entry: File,
+
+    pub fn deinit(self: *Self) void {
+        self.alloc.free(self.files);
+    }
+};
+
+/// Read `text` as a bundle, failing with ValueError if it isn't one.
+pub fn parse(alloc: Allocator, text: []const u8) !Bundle {
+    if (!std.mem.startsWith(u8, text, MAGIC)) return InternalError.ValueError;
+
+    var files = std.ArrayList(File).init(alloc);
+    errdefer files.deinit();
+
+    var pos: usize = MAGIC.len;
+    while (pos < text.len) {
+        const line_end = std.mem.indexOfScalarPos(u8, text, pos, helpers.CHAR_NEWLINE) orelse return InternalError.ValueError;
+        const line = text[pos..line_end];
+        const is_entry = std.mem.startsWith(u8, line, ENTRY_HEADER);
+        if (!is_entry and !std.mem.startsWith(u8, line, FILE_HEADER)) return InternalError.ValueError;
+
+        const header = line[(if (is_entry) ENTRY_HEADER.len else FILE_HEADER.len)..];
+        const space = std.mem.lastIndexOfScalar(u8, header, ' ') orelse return InternalError.ValueError;
+        const len = std.fmt.parseUnsigned(usize, header[space + 1 ..], 10) catch return InternalError.ValueError;
+
+        const start = line_end + 1;
+        if (len > text.len - start or text.len - start - len < 1) return InternalError.ValueError;
+        if (text[start + len] != helpers.CHAR_NEWLINE) return InternalError.ValueError;
+
+        const file = File{ .path = header[0..space], .source = text[start .. start + len] };
+        pos = start + len + 1;
+
+        if (is_entry) {
+            if (pos != text.len) return InternalError.ValueError;
+            return Bundle{ .alloc = alloc, .files = files.toOwnedSlice(), .entry = file };
+        }
+        try files.append(file);
+    }
+
+    // No entry script.
+    return InternalError.ValueError;
+}
+
+/// Write `files`, then `entry`, as a bundle.
+pub fn write(writer: anytype, files: []const File, entry: File) !void {
+    try writer.writeAll(MAGIC);
+    for (files) |file| try write_file(writer, FILE_HEADER, file);
+    try write_file(writer, ENTRY_HEADER, entry);
+}
+
+fn write_file(writer: anytype, header: []const u8, file: File) !void {
+    try writer.print("{s}{s} {d}\n", .{ header, file.path, file.source.len });
+    try writer.writeAll(file.source);
+    try writer.writeByte(helpers.CHAR_NEWLINE);
+}
+
+/// The trailer (see TRAILER) for a bundle of `len` bytes.
+pub fn trailer_for(len: usize) [TRAILER_LEN]u8 {
+    var trailer: [TRAILER_LEN]u8 = undefined;
+    std.mem.writeIntLittle(u64, trailer[0..@sizeOf(u64)], len);
+    std.mem.copy(u8, trailer[@sizeOf(u64)..], TRAILER);
+    return trailer;
+}
+
+/// The length of the bundle before `trailer`, the last TRAILER_LEN bytes of
+/// some executable, if it is a trailer.
+pub fn length_from_trailer(trailer: *const [TRAILER_LEN]u8) ?u64 {
+    if (!std.mem.eql(u8, trailer[@sizeOf(u64)..], TRAILER)) return null;
+    return std.mem.readIntLittle(u64, trailer[0..@sizeOf(u64)]);
+}
+
+/// Everything `collect` found, all of it owned by the arena.
+pub const Collected = struct {
+    const Self = @This();
+
+    arena: std.heap.ArenaAllocator,
+    /// In the order they were found, the entry script aside.
+    files: std.ArrayList(File),
+    entry: File,
+
+    pub fn deinit(self: *Self) void {
+        self.files.deinit();
+        self.arena.deinit();
+    }
+};
+
+/// Read the script at `entry_path`, and every file it names with a literal
+/// path (see the top of this file), found just as `rt` would find them (see
+/// Runtime.resolve_source_path).
+pub fn collect(alloc: Allocator, rt: *Runtime, entry_path: []const u8) !Collected {
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    var collected = Collected{
+        .arena = std.heap.ArenaAllocator.init(alloc),
+        .files = std.ArrayList(File).init(alloc),
+        .entry = undefined,
+    };
+    errdefer collected.deinit();
+
+    var seen = std.StringHashMap(void).init(alloc);
+    defer seen.deinit();
+
+    const arena = collected.arena.allocator();
+    const entry_source = try read_source(arena, entry_path);
+    collected.entry = .{ .path = try arena.dupe(u8, entry_path), .source = entry_source };
+    try collect_from(&collected, &seen, rt, entry_source);
+
+    return collected;
+}
+
+fn collect_from(
+    collected: *Collected,
+    seen: *std.StringHashMap(void),
+    rt: *Runtime,
+    source: []const u8,
+) anyerror!void {
+    const arena = collected.arena.allocator();
+
+    var paths = std.ArrayList([]const u8).init(collected.files.allocator);
+    defer paths.deinit();
+    try literal_paths(source, &paths);
+
+    for (paths.items) |operand| {
+        if (seen.contains(operand)) continue;
+        try seen.put(operand, {});
+
+        const resolved = try rt.resolve_source_path(operand);
+        defer rt.alloc.free(resolved);
+        const file_source = try read_source(arena, resolved);
+
+        try collected.files.append(.{ .path = operand, .source = file_source });
+        try collect_from(collected, seen, rt, file_source);
+    }
+}
+
+fn read_source(alloc: Allocator, path: []const u8) ![]u8 {
+    return std.fs.cwd().readFileAlloc(alloc, path, std.math.maxInt(u32)) catch |err| switch (err) {
+        error.FileTooBig => return InternalError.LimitExceeded,
+        else => return err,
+    };
+}
+
+/// The words taking a path, alongside how many values sit between the path
+/// and the word.
+const PATH_WORDS = .{
+    .{ "@IMPORT", 0 },
+    .{ "@LOAD", 0 },
+    .{ "@IMPORT-AS", 1 },
+    .{ "@IMPORT-VERSION", 1 },
+};
+
+/// Append to `paths` every literal path `source` hands to a PATH_WORD,
+/// sliced from `source`.
+pub fn literal_paths(source: []const u8, paths: *std.ArrayList([]const u8)) !void {
+    // The last two Words and Strings before the current one, nearest last.
+    var recent: [2]?[]const u8 = .{ null, null };
+
+    var tokens = lexer.Lexer.init(source);
+    while (try tokens.next()) |token| {
+        switch (token.kind) {
+            .Word, .String => {},
+            .LineComment, .BlockComment, .Newline => continue,
+        }
+
+        inline for (PATH_WORDS) |entry| {
+            if (std.mem.eql(u8, token.raw, entry[0])) {
+                if (recent[1 - entry[1]]) |raw| {
+                    const parsed = ParsedWord.from_input(raw) catch null;
+                    if (parsed != null and parsed.? == .String) try paths.append(parsed.?.String);
+                }
+            }
+        }
+
+        recent[0] = recent[1];
+        recent[1] = token.raw;
+    }
+}
+
+test "parse and write" {
+    var text = std.ArrayList(u8).init(testAllocator);
+    defer text.deinit();
+
+    const files = [_]File{
+        .{ .path = "strings", .source = "\"1.0.0\" @VERSION\n" },
+        .{ .path = "lib/with space.gale", .source = "" },
+    };
+    try write(text.writer(), &files, .{ .path = "main.gale", .source = "\"strings\" @IMPORT" });
+
+    var bundle = try parse(testAllocator, text.items);
+    defer bundle.deinit();
+    try expectEqual(@as(usize, 2), bundle.files.len);
+    try expectEqualStrings("strings", bundle.files[0].path);
+    try expectEqualStrings(files[0].source, bundle.files[0].source);
+    try expectEqualStrings("lib/with space.gale", bundle.files[1].path);
+    try expectEqualStrings("main.gale", bundle.entry.path);
+    try expectEqualStrings("\"strings\" @IMPORT", bundle.entry.source);
+
+    try expectError(InternalError.ValueError, parse(testAllocator, "1 2 3"));
+    try expectError(InternalError.ValueError, parse(testAllocator, MAGIC));
+    try expectError(InternalError.ValueError, parse(testAllocator, MAGIC ++ ENTRY_HEADER ++ "a 99\n1\n"));
+    try expectError(InternalError.ValueError, parse(testAllocator, MAGIC ++ ENTRY_HEADER ++ "a 1\n1\n" ++ FILE_HEADER ++ "b 0\n\n"));
+}
+
+test "trailers" {
+    const trailer = trailer_for(1234);
+    try expectEqual(@as(?u64, 1234), length_from_trailer(&trailer));
+
+    var not_one = trailer;
+    not_one[TRAILER_LEN - 1] = 'X';
+    try expect(length_from_trailer(&not_one) == null);
+}
+
+test "literal_paths" {
+    var paths = std.ArrayList([]const u8).init(testAllocator);
+    defer paths.deinit();
+
+    try literal_paths(
+        \\"strings" @IMPORT // "nope" @IMPORT
+        \\"shapes" :s @IMPORT-AS "geometry" "^1" @IMPORT-VERSION
+        \\some-path @LOAD "x" @DUP "./extra.gale" @LOAD
+    , &paths);
+
+    try expectEqual(@as(usize, 4), paths.items.len);
+    try expectEqualStrings("strings", paths.items[0]);
+    try expectEqualStrings("shapes", paths.items[1]);
+    try expectEqualStrings("geometry", paths.items[2]);
+    try expectEqualStrings("./extra.gale", paths.items[3]);
+}
+
+test "collect" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(testAllocator, ".");
+    defer testAllocator.free(root);
+    const main = try std.fs.path.join(testAllocator, &.{ root, "main.gale" });
+    defer testAllocator.free(main);
+
+    try tmp.dir.writeFile("main.gale", "\"strings\" @IMPORT \"shapes\" @IMPORT");
+    try tmp.dir.writeFile("strings.gale", "1");
+    try tmp.dir.writeFile("shapes.gale", "\"strings\" @IMPORT");
+
+    var rt = try Runtime.init_with_options(testAllocator, .{ .library_path = &.{root} });
+    defer rt.deinit_guard_for_empty_stack();
+
+    var collected = try collect(testAllocator, &rt, main);
+    defer collected.deinit();
+    try expectEqualStrings(main, collected.entry.path);
+    try expectEqual(@as(usize, 2), collected.files.items.len);
+    try expectEqualStrings("strings", collected.files.items[0].path);
+    try expectEqualStrings("1", collected.files.items[0].source);
+    try expectEqualStrings("shapes", collected.files.items[1].path);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,227 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+// Proleptic Gregorian calendar arithmetic on UNIX timestamps, always in UTC:
+// time zones are a rabbit hole best left to whoever's hosting us. Unlike
+// std.time.epoch, this handles timestamps from before 1970, too.
+//
+// Patterns, for both formatting and parsing, are strftime-ish:
+//
+// - %Y: the year, at least four digits (and a leading - if need be)
+// - %m, %d, %H, %M, %S: the month, day, hour, minute, and second, as two
+//   digits each
+// - %%: a literal %
+//
+// Anything else in a pattern stands for itself, and any other %-sequence is
+// a ValueError.
+
+const SECONDS_PER_DAY = 24 * 60 * 60;
+
+pub const DateTime = struct {
+    year: i64,
+    /// 1 through 12.
+    month: u8,
+    /// 1 through 31.
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+
+    pub fn from_timestamp(timestamp: i64) DateTime {
+        const days = @divFloor(timestamp, SECONDS_PER_DAY);
+        const seconds = @intCast(u32, @mod(timestamp, SECONDS_PER_DAY));
+        const date = civil_from_days(days);
+
+        return .{
+            .year = date.year,
+            .month = date.month,
+            .day = date.day,
+            .hour = @intCast(u8, seconds / 3600),
+            .minute = @intCast(u8, seconds / 60 % 60),
+            .second = @intCast(u8, seconds % 60),
+        };
+    }
+
+    pub fn to_timestamp(self: DateTime) i64 {
+        return days_from_civil(self.year, self.month, self.day) * SECONDS_PER_DAY +
+            @as(i64, self.hour) * 3600 + @as(i64, self.minute) * 60 + self.second;
+    }
+};
+
+const Date = struct { year: i64, month: u8, day: u8 };
+
+// These two are Howard Hinnant's algorithms, which treat March as the first
+// month of the year so that leap days fall at the very end of it. See
+// http://howardhinnant.github.io/date_algorithms.html for the derivation.
+
+fn civil_from_days(days_since_epoch: i64) Date {
+    const z = days_since_epoch + 719468;
+    const era = @divFloor(z, 146097);
+    const day_of_era = z - era * 146097;
+    const year_of_era = @divFloor(day_of_era - @divFloor(day_of_era, 1460) + @divFloor(day_of_era, 36524) - @divFloor(day_of_era, 146096), 365);
+    const day_of_year = day_of_era - (365 * year_of_era + @divFloor(year_of_era, 4) - @divFloor(year_of_era, 100));
+    const shifted_month = @divFloor(5 * day_of_year + 2, 153);
+    const day = day_of_year - @divFloor(153 * shifted_month + 2, 5) + 1;
+    const month = if (shifted_month < 10) shifted_month + 3 else shifted_month - 9;
+
+    return .{
+        .year = year_of_era + era * 400 + @boolToInt(month <= 2),
+        .month = @intCast(u8, month),
+        .day = @intCast(u8, day),
+    };
+}
+
+fn days_from_civil(year: i64, month: u8, day: u8) i64 {
+    const y = if (month <= 2) year - 1 else year;
+    const era = @divFloor(y, 400);
+    const year_of_era = y - era * 400;
+    const shifted_month: i64 = if (month > 2) month - 3 else month + 9;
+    const day_of_year = @divFloor(153 * shifted_month + 2, 5) + day - 1;
+    const day_of_era = year_of_era * 365 + @divFloor(year_of_era, 4) - @divFloor(year_of_era, 100) + day_of_year;
+    return era * 146097 + day_of_era - 719468;
+}
+
+fn days_in_month(year: i64, month: u8) u8 {
+    return switch (month) {
+        2 => if (@mod(year, 4) == 0 and (@mod(year, 100) != 0 or @mod(year, 400) == 0)) 29 else 28,
+        4, 6, 9, 11 => 30,
+        else => 31,
+    };
+}
+
+pub fn format(writer: anytype, timestamp: i64, pattern: []const u8) !void {
+    const dt = DateTime.from_timestamp(timestamp);
+
+    var idx: usize = 0;
+    while (idx < pattern.len) : (idx += 1) {
+        if (pattern[idx] != '%') {
+            try writer.writeByte(pattern[idx]);
+            continue;
+        }
+
+        idx += 1;
+        if (idx == pattern.len) return InternalError.ValueError;
+
+        switch (pattern[idx]) {
+            'Y' => if (dt.year < 0)
+                try writer.print("-{d:0>4}", .{@intCast(u64, -dt.year)})
+            else
+                try writer.print("{d:0>4}", .{@intCast(u64, dt.year)}),
+            'm' => try writer.print("{d:0>2}", .{dt.month}),
+            'd' => try writer.print("{d:0>2}", .{dt.day}),
+            'H' => try writer.print("{d:0>2}", .{dt.hour}),
+            'M' => try writer.print("{d:0>2}", .{dt.minute}),
+            'S' => try writer.print("{d:0>2}", .{dt.second}),
+            '%' => try writer.writeByte('%'),
+            else => return InternalError.ValueError,
+        }
+    }
+}
+
+/// The timestamp `text` describes, according to `pattern`. Fields the
+/// pattern doesn't mention default to the start of the epoch (so `%Y` alone
+/// is midnight on January 1st), and anything not matching the pattern, or
+/// describing a date which doesn't exist, is a ValueError.
+pub fn parse(text: []const u8, pattern: []const u8) InternalError!i64 {
+    var dt = DateTime{ .year = 1970, .month = 1, .day = 1, .hour = 0, .minute = 0, .second = 0 };
+    var pos: usize = 0;
+
+    var idx: usize = 0;
+    while (idx < pattern.len) : (idx += 1) {
+        if (pattern[idx] != '%' or (idx + 1 < pattern.len and pattern[idx + 1] == '%')) {
+            if (pattern[idx] == '%') idx += 1;
+            if (pos >= text.len or text[pos] != pattern[idx]) return InternalError.ValueError;
+            pos += 1;
+            continue;
+        }
+
+        idx += 1;
+        if (idx == pattern.len) return InternalError.ValueError;
+
+        switch (pattern[idx]) {
+            'Y' => {
+                const negative = pos < text.len and text[pos] == '-';
+                if (negative) pos += 1;
+                const year = try take_digits(text, &pos, 4, 18);
+                dt.year = if (negative) -@intCast(i64, year) else @intCast(i64, year);
+            },
+            'm' => dt.month = try take_field(text, &pos, 1, 12),
+            'd' => dt.day = try take_field(text, &pos, 1, 31),
+            'H' => dt.hour = try take_field(text, &pos, 0, 23),
+            'M' => dt.minute = try take_field(text, &pos, 0, 59),
+            // Leap seconds are not a thing UNIX time acknowledges.
+            'S' => dt.second = try take_field(text, &pos, 0, 59),
+            else => return InternalError.ValueError,
+        }
+    }
+
+    if (pos != text.len) return InternalError.ValueError;
+    if (dt.day > days_in_month(dt.year, dt.month)) return InternalError.ValueError;
+
+    return dt.to_timestamp();
+}
+
+fn take_field(text: []const u8, pos: *usize, min: u8, max: u8) InternalError!u8 {
+    const value = try take_digits(text, pos, 2, 2);
+    if (value < min or value > max) return InternalError.ValueError;
+    return @intCast(u8, value);
+}
+
+fn take_digits(text: []const u8, pos: *usize, min_len: usize, max_len: usize) InternalError!u64 {
+    const start = pos.*;
+    while (pos.* < text.len and pos.* - start < max_len and std.ascii.isDigit(text[pos.*])) pos.* += 1;
+    if (pos.* - start < min_len) return InternalError.ValueError;
+
+    return std.fmt.parseInt(u64, text[start..pos.*], 10) catch InternalError.ValueError;
+}
+
+test "timestamps round trip through dates" {
+    const stamps = [_]i64{ 0, 951782400, 1700000000, -1, -86400 * 365 * 100, 253402300799 };
+    for (stamps) |stamp| try expectEqual(stamp, DateTime.from_timestamp(stamp).to_timestamp());
+
+    // 2000 was a leap year, which 1900 was not.
+    const leap_day = DateTime.from_timestamp(951782400);
+    try expectEqual(@as(i64, 2000), leap_day.year);
+    try expectEqual(@as(u8, 2), leap_day.month);
+    try expectEqual(@as(u8, 29), leap_day.day);
+}
+
+test "format and parse" {
+    var buf: [64]u8 = undefined;
+    var stream = std.io.fixedBufferStream(&buf);
+
+    try format(stream.writer(), 1700000000, "%Y-%m-%d %H:%M:%S 100%%");
+    try expectEqualStrings("2023-11-14 22:13:20 100%", stream.getWritten());
+    try expectEqual(@as(i64, 1700000000), try parse("2023-11-14 22:13:20 100%", "%Y-%m-%d %H:%M:%S 100%%"));
+
+    stream.reset();
+    try format(stream.writer(), -1, "%Y-%m-%dT%H:%M:%S");
+    try expectEqualStrings("1969-12-31T23:59:59", stream.getWritten());
+
+    try expectEqual(@as(i64, 0), try parse("1970", "%Y"));
+    try expectError(InternalError.ValueError, parse("1900-02-29", "%Y-%m-%d"));
+    try expectError(InternalError.ValueError, parse("2023-13-01", "%Y-%m-%d"));
+    try expectError(InternalError.ValueError, parse("2023-11-14 and then some", "%Y-%m-%d"));
+    try expectError(InternalError.ValueError, format(stream.writer(), 0, "%Q"));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,74 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const expect = std.testing.expect;
+
+/// Anything a gale program can do to the world outside of its own Runtime is
+/// gated behind one of these, which must be granted explicitly by whoever
+/// constructs the Runtime (an embedder, or the CLI by way of its --allow-*
+/// flags). Words needing a capability they haven't been granted fail with
+/// InternalError.CapabilityDenied rather than doing anything at all.
+///
+/// The default is to grant nothing: gale code is assumed to be untrusted until
+/// someone says otherwise.
+pub const Capability = enum {
+    /// Reading files and directory listings.
+    Read,
+    /// Creating, writing, moving, and deleting files and directories.
+    Write,
+    /// Opening (or listening on) network sockets.
+    Net,
+    /// Reading and writing environment variables.
+    Env,
+    /// Spawning subprocesses. This is effectively every other capability
+    /// rolled into one, so grant it with care.
+    Run,
+};
+
+pub const Capabilities = std.EnumSet(Capability);
+
+pub const NONE = Capabilities.initEmpty();
+pub const ALL = Capabilities.initFull();
+
+/// Map a CLI flag like `--allow-read` to the Capabilities it grants, or null
+/// if it's not a capability flag at all. `--allow-all` grants, well, all.
+pub fn from_flag(flag: []const u8) ?Capabilities {
+    const prefix = "--allow-";
+    if (!std.mem.startsWith(u8, flag, prefix)) return null;
+    const name = flag[prefix.len..];
+
+    if (std.mem.eql(u8, name, "all")) return ALL;
+
+    inline for (comptime std.enums.values(Capability)) |cap| {
+        if (std.ascii.eqlIgnoreCase(name, @tagName(cap))) {
+            var granted = NONE;
+            granted.insert(cap);
+            return granted;
+        }
+    }
+
+    return null;
+}
+
+test "from_flag" {
+    try expect(from_flag("--allow-read").?.contains(.Read));
+    try expect(!from_flag("--allow-read").?.contains(.Write));
+    try expect(from_flag("--allow-all").?.contains(.Run));
+    try expect(from_flag("--allow-nonsense") == null);
+    try expect(from_flag("--read") == null);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
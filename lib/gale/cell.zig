@@ -0,0 +1,142 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const sync = @import("./sync.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Mutex = sync.Mutex;
+const Object = @import("./object.zig").Object;
+
+/// A box holding exactly one Object, which can be swapped out from under
+/// everyone else holding the same Cell. This is the sanctioned way for Tasks
+/// (or, when sync.THREADED, Runtimes on different threads) to share mutable
+/// state, rather than, say, redefining words at each other.
+///
+/// Every operation is atomic with respect to every other, but only by way of
+/// a lock: there's no lock-free trickery here.
+pub const Cell = struct {
+    const Self = @This();
+
+    mutex: Mutex,
+    /// We hold a reference to this.
+    value: Object,
+
+    /// Takes over `value`'s reference.
+    pub fn init(value: Object) Self {
+        return Self{ .mutex = .{}, .value = value };
+    }
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        self.value.deinit(alloc);
+    }
+
+    /// Return the current value, with a new reference for the caller.
+    pub fn load(self: *Self) !Object {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+
+        return try self.value.ref();
+    }
+
+    /// Replace the value with `new` (taking over its reference), and return
+    /// the old one (whose reference is now the caller's).
+    pub fn swap(self: *Self, new: Object) Object {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+
+        const old = self.value;
+        self.value = new;
+        return old;
+    }
+
+    /// If the current value is equal (see Object.eql) to `expected`, swap in
+    /// `new` as with `swap`, and return the old value. Otherwise, return null
+    /// and leave `new`'s reference with the caller.
+    pub fn compare_and_swap(self: *Self, expected: Object, new: Object) ?Object {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+
+        if (!self.value.eql(expected)) return null;
+
+        const old = self.value;
+        self.value = new;
+        return old;
+    }
+
+    /// Add `delta` to the value, returning the value from before. Both must
+    /// be integers of the same kind (a TypeError otherwise), and overflowing
+    /// is a ValueError, either of which leaves the Cell untouched.
+    pub fn fetch_add(self: *Self, delta: Object) InternalError!Object {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+
+        const old = self.value;
+
+        self.value = switch (old) {
+            .SignedInt => |val| switch (delta) {
+                .SignedInt => |by| .{ .SignedInt = std.math.add(isize, val, by) catch return InternalError.ValueError },
+                else => return InternalError.TypeError,
+            },
+            .UnsignedInt => |val| switch (delta) {
+                .UnsignedInt => |by| .{ .UnsignedInt = std.math.add(usize, val, by) catch return InternalError.ValueError },
+                else => return InternalError.TypeError,
+            },
+            else => return InternalError.TypeError,
+        };
+
+        return old;
+    }
+
+    test "swaps and adds" {
+        var cell = Self.init(.{ .UnsignedInt = 1 });
+        defer cell.deinit(std.testing.allocator);
+
+        try expect(cell.compare_and_swap(.{ .UnsignedInt = 2 }, .{ .UnsignedInt = 3 }) == null);
+        try expectEqual(@as(usize, 1), cell.compare_and_swap(.{ .UnsignedInt = 1 }, .{ .UnsignedInt = 3 }).?.UnsignedInt);
+
+        try expectEqual(@as(usize, 3), (try cell.fetch_add(.{ .UnsignedInt = 2 })).UnsignedInt);
+        try expectError(InternalError.TypeError, cell.fetch_add(.{ .SignedInt = 2 }));
+        try expectError(InternalError.ValueError, cell.fetch_add(.{ .UnsignedInt = std.math.maxInt(usize) }));
+        try expectEqual(@as(usize, 5), (try cell.load()).UnsignedInt);
+    }
+
+    test "between threads" {
+        if (comptime !sync.THREADED) return error.SkipZigTest;
+
+        const Adder = struct {
+            fn run(cell: *Self) void {
+                var i: usize = 0;
+                while (i < 1000) : (i += 1) _ = cell.fetch_add(.{ .UnsignedInt = 1 }) catch unreachable;
+            }
+        };
+
+        var cell = Self.init(.{ .UnsignedInt = 0 });
+        defer cell.deinit(std.testing.allocator);
+
+        var threads: [4]std.Thread = undefined;
+        for (threads) |*thread| thread.* = try std.Thread.spawn(.{}, Adder.run, .{&cell});
+        for (threads) |thread| thread.join();
+
+        try expectEqual(@as(usize, 4000), (try cell.load()).UnsignedInt);
+    }
+};
+
+test {
+    std.testing.refAllDecls(@This());
+}
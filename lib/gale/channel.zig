@@ -0,0 +1,227 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+
+const sync = @import("./sync.zig");
+
+const Condition = sync.Condition;
+const Mutex = sync.Mutex;
+const Object = @import("./object.zig").Object;
+const THREADED = sync.THREADED;
+
+/// A FIFO queue of Objects, for Tasks (see task.zig), or Runtimes on
+/// different host threads, to hand work to one another. Channels are
+/// bounded (senders wait while `capacity` Objects are already queued) or
+/// unbounded (they don't), and can be closed, after which sends fail and
+/// receives drain whatever is left.
+///
+/// This struct only ever tries: it's up to callers to decide how to wait,
+/// since that depends on whether there are Tasks to run in the meantime.
+pub const Channel = struct {
+    const Self = @This();
+    const Buffer = std.fifo.LinearFifo(Object, .Dynamic);
+
+    pub const SendResult = enum {
+        Sent,
+        Full,
+        Closed,
+    };
+
+    pub const ReceiveResult = union(enum) {
+        /// The receiver now owns this Object's reference.
+        Received: Object,
+        Empty,
+        /// Closed, and with nothing left to drain.
+        Closed,
+    };
+
+    pub const Readiness = enum {
+        Send,
+        Receive,
+    };
+
+    buffer: Buffer,
+    capacity: ?usize,
+    closed: bool,
+    mutex: Mutex,
+    activity: Condition,
+    /// How many host threads, beyond whichever is waiting on it, have said
+    /// (see share) they may yet send to or receive from this Channel.
+    sharers: std.atomic.Atomic(usize),
+
+    pub fn init(alloc: Allocator, capacity: ?usize) Self {
+        return Self{
+            .buffer = Buffer.init(alloc),
+            .capacity = capacity,
+            .closed = false,
+            .mutex = .{},
+            .activity = .{},
+            .sharers = std.atomic.Atomic(usize).init(0),
+        };
+    }
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        while (self.buffer.readItem()) |_obj| {
+            var obj = _obj;
+            obj.deinit(alloc);
+        }
+
+        self.buffer.deinit();
+    }
+
+    /// Queue `obj`, taking over its reference, unless the Channel is full or
+    /// closed, in which case the reference remains the caller's.
+    pub fn try_send(self: *Self, obj: Object) !SendResult {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+
+        if (self.closed) return .Closed;
+        if (!self.can_send()) return .Full;
+
+        try self.buffer.writeItem(obj);
+        self.activity.broadcast();
+
+        return .Sent;
+    }
+
+    pub fn try_receive(self: *Self) ReceiveResult {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+
+        if (self.buffer.readItem()) |obj| {
+            self.activity.broadcast();
+            return .{ .Received = obj };
+        }
+
+        return if (self.closed) .Closed else .Empty;
+    }
+
+    /// Closing an already-closed Channel is a no-op.
+    pub fn close(self: *Self) void {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+
+        self.closed = true;
+        self.activity.broadcast();
+    }
+
+    /// Note that another host thread (with a Runtime of its own, or none at
+    /// all) may send to or receive from this Channel, until it unshares it.
+    /// Hosts handing a Channel to another thread must say so, or a Runtime
+    /// with nothing else to do will take waiting on it for a deadlock.
+    pub fn share(self: *Self) void {
+        _ = self.sharers.fetchAdd(1, .AcqRel);
+    }
+
+    pub fn unshare(self: *Self) void {
+        _ = self.sharers.fetchSub(1, .AcqRel);
+
+        // Whoever's waiting may now be waiting on nobody.
+        self.mutex.lock();
+        defer self.mutex.unlock();
+        self.activity.broadcast();
+    }
+
+    pub fn is_shared(self: *const Self) bool {
+        return self.sharers.load(.Acquire) > 0;
+    }
+
+    /// Block the calling thread until a try_send or try_receive (as chosen by
+    /// `readiness`) would no longer come back Full or Empty, respectively, or
+    /// until `timeout_ns` have passed, if given. Only meaningful when
+    /// THREADED: otherwise, nobody could ever wake us, and so this panics.
+    pub fn wait_until(self: *Self, readiness: Readiness, timeout_ns: ?u64) void {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+
+        while (!self.closed and !self.ready_for(readiness)) {
+            if (timeout_ns) |timeout| {
+                self.activity.timedWait(&self.mutex, timeout) catch return;
+            } else {
+                self.activity.wait(&self.mutex);
+            }
+        }
+    }
+
+    fn ready_for(self: *const Self, readiness: Readiness) bool {
+        return switch (readiness) {
+            .Send => self.can_send(),
+            .Receive => self.buffer.count > 0,
+        };
+    }
+
+    fn can_send(self: *const Self) bool {
+        const capacity = self.capacity orelse return true;
+        return self.buffer.count < capacity;
+    }
+
+    test "bounded channels fill up, and closing drains" {
+        var chan = Self.init(testAllocator, 1);
+        defer chan.deinit(testAllocator);
+
+        try expectEqual(SendResult.Sent, try chan.try_send(.{ .UnsignedInt = 1 }));
+        try expectEqual(SendResult.Full, try chan.try_send(.{ .UnsignedInt = 2 }));
+
+        chan.close();
+        try expectEqual(SendResult.Closed, try chan.try_send(.{ .UnsignedInt = 3 }));
+        try expectEqual(@as(usize, 1), chan.try_receive().Received.UnsignedInt);
+        try expect(chan.try_receive() == .Closed);
+    }
+
+    test "between threads" {
+        if (comptime !THREADED) return error.SkipZigTest;
+
+        const Producer = struct {
+            fn run(chan: *Self) void {
+                var i: usize = 0;
+                while (i < 100) {
+                    switch (chan.try_send(.{ .UnsignedInt = i }) catch unreachable) {
+                        .Sent => i += 1,
+                        .Full => chan.wait_until(.Send, null),
+                        .Closed => unreachable,
+                    }
+                }
+                chan.close();
+            }
+        };
+
+        var chan = Self.init(testAllocator, 4);
+        defer chan.deinit(testAllocator);
+
+        const producer = try std.Thread.spawn(.{}, Producer.run, .{&chan});
+        defer producer.join();
+
+        var expected: usize = 0;
+        while (true) {
+            switch (chan.try_receive()) {
+                .Received => |obj| {
+                    try expectEqual(expected, obj.UnsignedInt);
+                    expected += 1;
+                },
+                .Empty => chan.wait_until(.Receive, null),
+                .Closed => break,
+            }
+        }
+
+        try expectEqual(@as(usize, 100), expected);
+    }
+};
+
+test {
+    std.testing.refAllDecls(@This());
+}
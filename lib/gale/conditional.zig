@@ -0,0 +1,189 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const builtin = @import("builtin");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+// Conditional compilation, of a sort: directives which eval understands
+// itself (see Runtime.dispatch_word_by_input), including or skipping
+// everything up to the matching `#else` or `#end`, so that one source can
+// serve several targets:
+//
+//     #if-target:wasm   ...   #else   ...   #end
+//     #unless-flag:debug   ...   #end
+//     #if-word:@TCP-CONNECT   ...   #end
+//
+// Targets are those of Target, flags are whatever the host has defined (see
+// Runtime.define_flag), and words are available if find_word finds them.
+// Blocks nest, and must be closed within the source (or eval) that opened
+// them. Skipped words are never looked up, and skipped conditions never
+// checked.
+
+pub const Target = enum {
+    /// Anything with an OS underneath it, or at least not one of the below.
+    native,
+    wasm,
+    /// There's no uxn port yet, so this is never the current target.
+    uxn,
+
+    pub fn current() Target {
+        return if (builtin.cpu.arch.isWasm()) .wasm else .native;
+    }
+};
+
+pub const Condition = union(enum) {
+    Target: Target,
+    Flag: []const u8,
+    Word: []const u8,
+};
+
+pub const Directive = union(enum) {
+    If: Condition,
+    Unless: Condition,
+    Else,
+    End,
+
+    /// The directive `input` spells, if any. Words starting with `#` which
+    /// aren't directives are left alone, but `#if-` and `#unless-` with
+    /// anything but a known condition after them are ValueErrors.
+    pub fn parse(input: []const u8) InternalError!?Directive {
+        if (input.len == 0 or input[0] != '#') return null;
+        if (std.mem.eql(u8, input, "#else")) return .Else;
+        if (std.mem.eql(u8, input, "#end")) return .End;
+
+        if (std.mem.startsWith(u8, input, "#if-")) return Directive{ .If = try parse_condition(input["#if-".len..]) };
+        if (std.mem.startsWith(u8, input, "#unless-")) return Directive{ .Unless = try parse_condition(input["#unless-".len..]) };
+        return null;
+    }
+};
+
+fn parse_condition(text: []const u8) InternalError!Condition {
+    const colon = std.mem.indexOfScalar(u8, text, ':') orelse return InternalError.ValueError;
+    const kind = text[0..colon];
+    const value = text[colon + 1 ..];
+    if (value.len == 0) return InternalError.ValueError;
+
+    if (std.mem.eql(u8, kind, "target")) {
+        return Condition{ .Target = std.meta.stringToEnum(Target, value) orelse return InternalError.ValueError };
+    }
+    if (std.mem.eql(u8, kind, "flag")) return Condition{ .Flag = value };
+    if (std.mem.eql(u8, kind, "word")) return Condition{ .Word = value };
+    return InternalError.ValueError;
+}
+
+/// The blocks open at any given point in eval, innermost last.
+pub const Conditionals = struct {
+    const Self = @This();
+
+    const Branch = struct {
+        /// Whether whatever the block is in is being included.
+        outer_active: bool,
+        /// Whether the block's condition held (or for `#unless`, didn't).
+        holds: bool,
+        in_else: bool,
+
+        fn active(self: Branch) bool {
+            return self.outer_active and (self.holds != self.in_else);
+        }
+    };
+
+    branches: std.ArrayListUnmanaged(Branch) = .{},
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        self.branches.deinit(alloc);
+    }
+
+    /// Whether words here are to be run, rather than skipped.
+    pub fn active(self: *const Self) bool {
+        const items = self.branches.items;
+        return items.len == 0 or items[items.len - 1].active();
+    }
+
+    pub fn depth(self: *const Self) usize {
+        return self.branches.items.len;
+    }
+
+    /// Open a block, whose condition only matters if active() already.
+    pub fn open(self: *Self, alloc: Allocator, holds: bool) !void {
+        try self.branches.append(alloc, .{ .outer_active = self.active(), .holds = holds, .in_else = false });
+    }
+
+    /// Move on to the innermost block's `#else`. Without an open block, or
+    /// for a second `#else`, ValueError.
+    pub fn flip(self: *Self) InternalError!void {
+        const items = self.branches.items;
+        if (items.len == 0 or items[items.len - 1].in_else) return InternalError.ValueError;
+        items[items.len - 1].in_else = true;
+    }
+
+    /// Close the innermost block, which must exist.
+    pub fn close(self: *Self) InternalError!void {
+        if (self.branches.items.len == 0) return InternalError.ValueError;
+        _ = self.branches.pop();
+    }
+
+    /// Close every block opened since depth() was `to`.
+    pub fn truncate(self: *Self, to: usize) void {
+        self.branches.shrinkRetainingCapacity(to);
+    }
+};
+
+test "Directive.parse" {
+    try expect((try Directive.parse("#tag")) == null);
+    try expect((try Directive.parse("if-target:wasm")) == null);
+    try expect((try Directive.parse("#else")).? == .Else);
+    try expect((try Directive.parse("#end")).? == .End);
+
+    try expectEqual(Target.uxn, (try Directive.parse("#if-target:uxn")).?.If.Target);
+    try expectEqualStrings("debug", (try Directive.parse("#unless-flag:debug")).?.Unless.Flag);
+    try expectEqualStrings("@TCP-CONNECT", (try Directive.parse("#if-word:@TCP-CONNECT")).?.If.Word);
+
+    try expectError(InternalError.ValueError, Directive.parse("#if-target:amiga"));
+    try expectError(InternalError.ValueError, Directive.parse("#if-flag:"));
+    try expectError(InternalError.ValueError, Directive.parse("#if-weather:sunny"));
+}
+
+test "Conditionals" {
+    var conditionals = Conditionals{};
+    defer conditionals.deinit(testAllocator);
+
+    try conditionals.open(testAllocator, false);
+    try expect(!conditionals.active());
+    // Nothing within an inactive block is active, whatever its condition.
+    try conditionals.open(testAllocator, true);
+    try expect(!conditionals.active());
+    try conditionals.flip();
+    try expect(!conditionals.active());
+    try conditionals.close();
+
+    try conditionals.flip();
+    try expect(conditionals.active());
+    try expectError(InternalError.ValueError, conditionals.flip());
+    try conditionals.close();
+    try expect(conditionals.active());
+
+    try expectError(InternalError.ValueError, conditionals.close());
+    try expectError(InternalError.ValueError, conditionals.flip());
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,331 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const Types = @import("./types.zig");
+
+// Marshalling between Zig values and Objects, so that embedders and primitive
+// authors needn't hand-write a switch over every Object kind each time they
+// want to hand a value to (or get one from) gale. Everything is dispatched on
+// @typeInfo at comptime, so unsupported types are compile errors rather than
+// runtime surprises.
+//
+// Supported: bool, integers (any width, range-checked), floats, strings
+// ([]const u8 and string literals), slices and arrays of any supported type
+// (as Arrays), std.StringHashMaps and std.StringArrayHashMaps of any
+// supported type (as Maps), and optionals of any supported type (with null
+// represented as the Symbol named by NULL_SYMBOL).
+
+/// gale has no null, so optionals' nulls become this Symbol (`:null` in
+/// source) on the way in, and this Symbol becomes null on the way out.
+pub const NULL_SYMBOL = "null";
+
+/// Build an Object from a Zig value, allocating heap storage for strings and
+/// arrays as needed. As with anything fresh off the heap in gale, the result
+/// holds no references until it's placed somewhere (usually the Stack, see
+/// Runtime.stack_push_value).
+pub fn to_object(rt: *Runtime, value: anytype) !Object {
+    const T = @TypeOf(value);
+
+    switch (@typeInfo(T)) {
+        .Bool => return Object{ .Boolean = value },
+        .ComptimeInt => return if (value < 0)
+            Object{ .SignedInt = value }
+        else
+            Object{ .UnsignedInt = value },
+        .Int => |int| return if (int.signedness == .signed)
+            Object{ .SignedInt = std.math.cast(isize, value) orelse return InternalError.ValueError }
+        else
+            Object{ .UnsignedInt = std.math.cast(usize, value) orelse return InternalError.ValueError },
+        .ComptimeFloat => return Object{ .Float = @as(f64, value) },
+        .Float => return Object{ .Float = @floatCast(f64, value) },
+        .Optional => {
+            if (value) |inner| return try to_object(rt, inner);
+            return Object{ .Symbol = (try rt.get_or_put_symbol(NULL_SYMBOL)).value_ptr };
+        },
+        .Pointer => |ptr| switch (ptr.size) {
+            .Slice => {
+                if (ptr.child == u8) return Object{ .String = (try rt.get_or_put_string(value)).value_ptr };
+                return try array_to_object(rt, value);
+            },
+            // Pointers to arrays are, more often than not, string literals.
+            .One => switch (@typeInfo(ptr.child)) {
+                .Array => |arr| {
+                    if (arr.child == u8) return Object{ .String = (try rt.get_or_put_string(value)).value_ptr };
+                    return try array_to_object(rt, value);
+                },
+                else => @compileError(unsupported(T)),
+            },
+            else => @compileError(unsupported(T)),
+        },
+        .Array => return try array_to_object(rt, &value),
+        .Struct => {
+            if (comptime string_map_value(T) == null) @compileError(unsupported(T));
+            return try map_to_object(rt, value);
+        },
+        else => @compileError(unsupported(T)),
+    }
+}
+
+fn array_to_object(rt: *Runtime, items: anytype) !Object {
+    var list = Types.ObjectArray.init(rt.alloc);
+    errdefer {
+        for (list.items) |*it| it.deinit(rt.alloc);
+        list.deinit();
+    }
+
+    try list.ensureTotalCapacity(items.len);
+    // The array holds a reference to each of its members.
+    for (items) |item| list.appendAssumeCapacity(try (try to_object(rt, item)).ref());
+
+    const heaped = try rt.alloc.create(Types.HeapedArray);
+    heaped.* = Types.HeapedArray.init(list);
+    return Object{ .Array = heaped };
+}
+
+fn map_to_object(rt: *Runtime, entries: anytype) !Object {
+    const target = try rt.new_map();
+    errdefer rt.release_map(target);
+
+    var iter = entries.iterator();
+    while (iter.next()) |entry| try put_value(rt, target, entry.key_ptr.*, entry.value_ptr.*);
+
+    return Object{ .Map = target };
+}
+
+/// File a Zig value (converted as with to_object) into a Map under `key`,
+/// for words building up Maps to hand back.
+pub fn put_value(rt: *Runtime, target: *Types.HeapedMap, key: []const u8, value: anytype) !void {
+    var obj = try (try to_object(rt, value)).ref();
+    errdefer obj.deinit(rt.alloc);
+    try target.value.?.put(rt.alloc, key, obj);
+}
+
+/// Extract a Zig value of type T from an Object, failing with
+/// InternalError.TypeError if the Object is of the wrong kind, or
+/// InternalError.ValueError if it's the right kind but doesn't fit (an
+/// out-of-range integer, an Array of the wrong length for a Zig array, etc.).
+///
+/// Strings and slices are copied into memory from `alloc`, so the result
+/// never borrows from the Object, which can be safely released immediately.
+/// Use free_value to clean up after such types.
+pub fn from_object(comptime T: type, alloc: Allocator, obj: Object) !T {
+    switch (@typeInfo(T)) {
+        .Bool => return switch (obj) {
+            .Boolean => |val| val,
+            else => InternalError.TypeError,
+        },
+        .Int => return switch (obj) {
+            .SignedInt => |val| std.math.cast(T, val) orelse InternalError.ValueError,
+            .UnsignedInt => |val| std.math.cast(T, val) orelse InternalError.ValueError,
+            else => InternalError.TypeError,
+        },
+        .Float => return switch (obj) {
+            .Float => |val| @floatCast(T, val),
+            else => InternalError.TypeError,
+        },
+        .Optional => |opt| {
+            if (is_null_symbol(obj)) return null;
+            return try from_object(opt.child, alloc, obj);
+        },
+        .Pointer => |ptr| {
+            if (ptr.size != .Slice) @compileError(unsupported(T));
+
+            if (ptr.child == u8) return switch (obj) {
+                .String => |str| try alloc.dupe(u8, str.value.?),
+                else => InternalError.TypeError,
+            };
+
+            const items = switch (obj) {
+                .Array => |arr| arr.value.?.items,
+                else => return InternalError.TypeError,
+            };
+
+            const result = try alloc.alloc(ptr.child, items.len);
+            var converted: usize = 0;
+            errdefer {
+                for (result[0..converted]) |it| free_value(ptr.child, alloc, it);
+                alloc.free(result);
+            }
+
+            for (items) |item, idx| {
+                result[idx] = try from_object(ptr.child, alloc, item);
+                converted += 1;
+            }
+
+            return result;
+        },
+        .Array => |arr| {
+            const items = switch (obj) {
+                .Array => |heaped| heaped.value.?.items,
+                else => return InternalError.TypeError,
+            };
+            if (items.len != arr.len) return InternalError.ValueError;
+
+            var result: T = undefined;
+            var converted: usize = 0;
+            errdefer for (result[0..converted]) |it| free_value(arr.child, alloc, it);
+
+            for (items) |item, idx| {
+                result[idx] = try from_object(arr.child, alloc, item);
+                converted += 1;
+            }
+
+            return result;
+        },
+        .Struct => {
+            const V = comptime string_map_value(T) orelse @compileError(unsupported(T));
+            const source = switch (obj) {
+                .Map => |heaped| &heaped.value.?,
+                else => return InternalError.TypeError,
+            };
+
+            var result = T.init(alloc);
+            errdefer free_value(T, alloc, result);
+
+            for (source.keys()) |key| {
+                const owned_key = try alloc.dupe(u8, key);
+                errdefer alloc.free(owned_key);
+                const value = try from_object(V, alloc, source.get(key).?);
+                errdefer free_value(V, alloc, value);
+                try result.put(owned_key, value);
+            }
+
+            return result;
+        },
+        else => @compileError(unsupported(T)),
+    }
+}
+
+/// Free anything from_object allocated while building `value`.
+pub fn free_value(comptime T: type, alloc: Allocator, value: T) void {
+    switch (@typeInfo(T)) {
+        .Optional => |opt| if (value) |inner| free_value(opt.child, alloc, inner),
+        .Pointer => |ptr| {
+            if (ptr.child != u8) {
+                for (value) |it| free_value(ptr.child, alloc, it);
+            }
+
+            alloc.free(value);
+        },
+        .Array => |arr| for (value) |it| free_value(arr.child, alloc, it),
+        .Struct => if (comptime string_map_value(T)) |V| {
+            // The keys are ours too, as from_object copied them.
+            var entries = value;
+            var iter = entries.iterator();
+            while (iter.next()) |entry| {
+                alloc.free(entry.key_ptr.*);
+                free_value(V, alloc, entry.value_ptr.*);
+            }
+            entries.deinit();
+        },
+        else => {},
+    }
+}
+
+/// The value type of T if it's a std.StringHashMap or std.StringArrayHashMap,
+/// or null for anything else.
+fn string_map_value(comptime T: type) ?type {
+    if (@typeInfo(T) != .Struct or !@hasDecl(T, "KV")) return null;
+    const V = std.meta.fieldInfo(T.KV, .value).field_type;
+    if (T == std.StringHashMap(V) or T == std.StringArrayHashMap(V)) return V;
+    return null;
+}
+
+fn is_null_symbol(obj: Object) bool {
+    return switch (obj) {
+        .Symbol => |sym| std.mem.eql(u8, NULL_SYMBOL, sym.value.?),
+        else => false,
+    };
+}
+
+fn unsupported(comptime T: type) []const u8 {
+    return "gale can't (yet) convert " ++ @typeName(T) ++ " to or from an Object";
+}
+
+test "round trips through the stack" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try rt.stack_push_value(@as(u8, 200));
+    try expectEqual(@as(u8, 200), try rt.stack_pop_value(u8));
+
+    try rt.stack_push_value(@as(i32, -7));
+    try expectEqual(@as(i64, -7), try rt.stack_pop_value(i64));
+
+    try rt.stack_push_value(@as(f32, 1.5));
+    try expectEqual(@as(f64, 1.5), try rt.stack_pop_value(f64));
+
+    try rt.stack_push_value("hello");
+    const hello = try rt.stack_pop_value([]const u8);
+    defer free_value([]const u8, testAllocator, hello);
+    try expectEqualStrings("hello", hello);
+
+    const maybe: ?bool = null;
+    try rt.stack_push_value(maybe);
+    try expectEqual(@as(?bool, null), try rt.stack_pop_value(?bool));
+
+    try rt.stack_push_value([_][]const u8{ "a", "bc" });
+    const strs = try rt.stack_pop_value([]const []const u8);
+    defer free_value([]const []const u8, testAllocator, strs);
+    try expectEqual(@as(usize, 2), strs.len);
+    try expectEqualStrings("bc", strs[1]);
+}
+
+test "round trips of maps" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var ages = std.StringHashMap(u8).init(testAllocator);
+    defer ages.deinit();
+    try ages.put("ada", 36);
+    try ages.put("alan", 41);
+
+    try rt.stack_push_value(ages);
+    const back = try rt.stack_pop_value(std.StringArrayHashMap(u8));
+    defer free_value(std.StringArrayHashMap(u8), testAllocator, back);
+    try expectEqual(@as(usize, 2), back.count());
+    try expectEqual(@as(u8, 36), back.get("ada").?);
+    try expectEqual(@as(u8, 41), back.get("alan").?);
+
+    // Values convert as anything else would, so must fit the map's type.
+    var names = std.StringArrayHashMap([]const []const u8).init(testAllocator);
+    defer names.deinit();
+    try names.put("first", &.{ "Ada", "Augusta" });
+    try rt.stack_push_value(names);
+    try expectError(InternalError.TypeError, rt.stack_pop_value(std.StringHashMap(u8)));
+    const names_back = try rt.stack_pop_value(std.StringHashMap([]const []const u8));
+    defer free_value(std.StringHashMap([]const []const u8), testAllocator, names_back);
+    try expectEqualStrings("Augusta", names_back.get("first").?[1]);
+}
+
+test "from_object: mismatches" {
+    try expectError(InternalError.TypeError, from_object(bool, testAllocator, .{ .UnsignedInt = 1 }));
+    try expectError(InternalError.ValueError, from_object(u8, testAllocator, .{ .UnsignedInt = 256 }));
+    try expectError(InternalError.ValueError, from_object(u8, testAllocator, .{ .SignedInt = -1 }));
+    try expectError(InternalError.TypeError, from_object(std.StringHashMap(u8), testAllocator, .{ .UnsignedInt = 1 }));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
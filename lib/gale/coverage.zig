@@ -0,0 +1,179 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const prelude = @import("./prelude.zig");
+const vocabulary = @import("./vocabulary.zig");
+
+const Object = @import("./object.zig").Object;
+const Observer = @import("./observer.zig").Observer;
+const ObserverEvent = @import("./observer.zig").Event;
+const Runtime = @import("./runtime.zig").Runtime;
+const Types = @import("./types.zig");
+
+/// An Observer counting how many times each word has been run, for reporting
+/// (see write_report) which of the words defined in a Runtime were never
+/// exercised. Since it's only an Observer, it costs nothing until installed
+/// with Runtime.add_observer(coverage.observer()); as with any Observer, it
+/// must outlive its installation.
+///
+/// Gale has no conditional words yet, so there are no branches to cover:
+/// once it does, they'll want events of their own.
+pub const Coverage = struct {
+    const Self = @This();
+
+    /// Each counted word holds a reference, so that a word freed mid-run can't
+    /// have its address (and count) inherited by a later one.
+    hits: std.AutoArrayHashMap(*Types.HeapedWord, usize),
+
+    pub fn init(alloc: Allocator) Self {
+        return .{ .hits = std.AutoArrayHashMap(*Types.HeapedWord, usize).init(alloc) };
+    }
+
+    pub fn deinit(self: *Self, rt: *Runtime) void {
+        for (self.hits.keys()) |word| {
+            var obj = Object{ .Word = word };
+            rt.release_heaped_object_reference(&obj);
+        }
+        self.hits.deinit();
+    }
+
+    pub fn observer(self: *Self) Observer {
+        return .{ .context = self, .on_event = &on_event };
+    }
+
+    /// How many times `word` has started running since installation.
+    pub fn hits_of(self: *const Self, word: *Types.HeapedWord) usize {
+        return self.hits.get(word) orelse 0;
+    }
+
+    fn on_event(context: ?*anyopaque, _: *Runtime, event: ObserverEvent) void {
+        const self = @ptrCast(*Self, @alignCast(@alignOf(Self), context.?));
+        switch (event) {
+            // Observers can't fail whatever they're observing, so a word we
+            // can't find room to count simply goes uncounted.
+            .WordStart => |word| self.record(word) catch {},
+            else => {},
+        }
+    }
+
+    fn record(self: *Self, word: *Types.HeapedWord) !void {
+        const entry = try self.hits.getOrPut(word);
+        if (!entry.found_existing) {
+            word.increment() catch |err| {
+                self.hits.swapRemoveAt(entry.index);
+                return err;
+            };
+            entry.value_ptr.* = 0;
+        }
+        entry.value_ptr.* += 1;
+    }
+
+    /// One line per word defined in `rt` (and its bases), sorted by name,
+    /// giving its run count, then a line totting up how many were run at
+    /// all. Primitives (and the prelude, see prelude.zig) are left out:
+    /// they're covered by libgale's own tests, not by whatever's being
+    /// measured here.
+    pub fn write_report(self: *const Self, rt: *const Runtime, writer: anytype) !void {
+        var lines = std.ArrayList(Line).init(self.hits.allocator);
+        defer lines.deinit();
+
+        var current: ?*const Runtime = rt;
+        while (current) |runtime| : (current = runtime.base) {
+            var dictionary_iter = runtime.dictionary.iterator();
+            while (dictionary_iter.next()) |entry| {
+                for (entry.value_ptr.items()) |word| {
+                    const inner = word.value orelse continue;
+                    if (inner.impl == .Primitive) continue;
+                    const name = entry.key_ptr.*.value.?;
+                    if (is_prelude_word(name)) continue;
+
+                    try lines.append(.{
+                        .name = name,
+                        .hits = self.hits_of(word),
+                    });
+                }
+            }
+        }
+
+        std.sort.sort(Line, lines.items, {}, Line.less_than);
+
+        var covered: usize = 0;
+        for (lines.items) |line| {
+            if (line.hits > 0) covered += 1;
+            try writer.print("{d:>6} {s}\n", .{ line.hits, line.name });
+        }
+
+        const percent = if (lines.items.len == 0) 100 else covered * 100 / lines.items.len;
+        try writer.print("{d} of {d} defined words run ({d}%)\n", .{ covered, lines.items.len, percent });
+    }
+};
+
+fn is_prelude_word(name: []const u8) bool {
+    const vocab = vocabulary.vocabulary_of(name) orelse return false;
+    return std.mem.eql(u8, vocab, prelude.VOCABULARY);
+}
+
+const Line = struct {
+    name: []const u8,
+    hits: usize,
+
+    fn less_than(_: void, lhs: Line, rhs: Line) bool {
+        return std.mem.lessThan(u8, lhs.name, rhs.name);
+    }
+};
+
+fn define_compound(rt: *Runtime, name: []const u8, inner_names: []const []const u8) !void {
+    const inner = try rt.alloc.alloc(*Types.HeapedWord, inner_names.len);
+    for (inner_names) |inner_name, idx| {
+        inner[idx] = (try rt.find_word(inner_name)).?;
+        try inner[idx].increment();
+    }
+    try rt.define_word(name, try rt.word_from_compound_impl(inner, null));
+}
+
+test "Coverage" {
+    var report = std.ArrayList(u8).init(testAllocator);
+    defer report.deinit();
+
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var coverage = Coverage.init(testAllocator);
+    defer coverage.deinit(&rt);
+    try rt.add_observer(coverage.observer());
+
+    try define_compound(&rt, "dupdrop", &.{ "@DUP", "@DROP" });
+    try define_compound(&rt, "unused", &.{"@DUP"});
+
+    try rt.eval("1 dupdrop dupdrop @DROP");
+    try expectEqual(@as(usize, 2), coverage.hits_of((try rt.find_word("dupdrop")).?));
+    try expectEqual(@as(usize, 2), coverage.hits_of((try rt.find_word("@DUP")).?));
+
+    try coverage.write_report(&rt, report.writer());
+    try expectEqualStrings(
+        \\     2 dupdrop
+        \\     0 unused
+        \\1 of 2 defined words run (50%)
+        \\
+    , report.items);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,286 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const Types = @import("./types.zig");
+
+// Reading and writing delimiter-separated text in the manner of RFC 4180:
+// fields containing the delimiter, the quote character, or a line break are
+// quoted, and quote characters within them are doubled. Records may end in
+// either \n or \r\n when reading, and end in \n when writing. Every field
+// read is a String: guessing at which ones "look like" numbers is left to
+// the caller, who actually knows.
+
+/// Which characters separate fields and quote them.
+pub const Dialect = struct {
+    const Self = @This();
+
+    delimiter: u8 = ',',
+    quote: u8 = '"',
+
+    /// A Dialect from its one- or two-character spelling, as the words take
+    /// them: the delimiter, optionally followed by the quote character.
+    pub fn from_spec(spec: []const u8) InternalError!Self {
+        const dialect = switch (spec.len) {
+            1 => Self{ .delimiter = spec[0] },
+            2 => Self{ .delimiter = spec[0], .quote = spec[1] },
+            else => return InternalError.ValueError,
+        };
+
+        if (dialect.delimiter == dialect.quote) return InternalError.ValueError;
+        if (is_line_break(dialect.delimiter) or is_line_break(dialect.quote)) return InternalError.ValueError;
+        return dialect;
+    }
+};
+
+fn is_line_break(chr: u8) bool {
+    return chr == '\n' or chr == '\r';
+}
+
+/// Parse `text` into an Array of records, each an Array of Strings. As with
+/// anything fresh off the heap, the result holds no references yet.
+pub fn parse(rt: *Runtime, text: []const u8, dialect: Dialect) !Object {
+    const records = try rt.new_array(0);
+    errdefer rt.release_array(records);
+
+    var field = std.ArrayList(u8).init(rt.alloc);
+    defer field.deinit();
+
+    var pos: usize = 0;
+    while (pos < text.len) {
+        var record = try (Object{ .Array = try rt.new_array(0) }).ref();
+        errdefer record.deinit(rt.alloc);
+
+        pos = try read_record(rt, text, pos, dialect, record.Array, &field);
+        try records.value.?.append(record);
+    }
+
+    return Object{ .Array = records };
+}
+
+/// Parse `text` as with `parse`, but treating its first record as a header
+/// naming each field, and producing an Array of Maps from those names to the
+/// fields of each following record. Records must have exactly as many fields
+/// as the header, whose names must be unique.
+pub fn parse_headed(rt: *Runtime, text: []const u8, dialect: Dialect) !Object {
+    var table = try (try parse(rt, text, dialect)).ref();
+    defer rt.release_heaped_object_reference(&table);
+    const all_records = table.Array.value.?.items;
+
+    const records = try rt.new_array(if (all_records.len > 0) all_records.len - 1 else 0);
+    errdefer rt.release_array(records);
+    if (all_records.len == 0) return Object{ .Array = records };
+
+    const header = all_records[0].Array.value.?.items;
+    for (all_records[1..]) |raw| {
+        const fields = raw.Array.value.?.items;
+        if (fields.len != header.len) return InternalError.ValueError;
+
+        var record = try (Object{ .Map = try rt.new_map() }).ref();
+        errdefer record.deinit(rt.alloc);
+
+        for (header) |name, idx| {
+            const key = name.String.value.?;
+            if (record.Map.value.?.get(key) != null) return InternalError.ValueError;
+
+            var value = try fields[idx].ref();
+            errdefer value.deinit(rt.alloc);
+            try record.Map.value.?.put(rt.alloc, key, value);
+        }
+
+        try records.value.?.append(record);
+    }
+
+    return Object{ .Array = records };
+}
+
+/// Read the record starting at `start` into `record`, returning where the
+/// next one starts. `field` is scratch space.
+fn read_record(
+    rt: *Runtime,
+    text: []const u8,
+    start: usize,
+    dialect: Dialect,
+    record: *Types.HeapedArray,
+    field: *std.ArrayList(u8),
+) !usize {
+    var pos = start;
+
+    while (true) {
+        field.clearRetainingCapacity();
+
+        if (pos < text.len and text[pos] == dialect.quote) {
+            pos += 1;
+            while (true) {
+                if (pos >= text.len) return InternalError.ValueError;
+                if (text[pos] == dialect.quote) {
+                    if (pos + 1 < text.len and text[pos + 1] == dialect.quote) {
+                        try field.append(dialect.quote);
+                        pos += 2;
+                        continue;
+                    }
+                    pos += 1;
+                    break;
+                }
+                try field.append(text[pos]);
+                pos += 1;
+            }
+        } else {
+            while (pos < text.len and text[pos] != dialect.delimiter and !is_line_break(text[pos])) : (pos += 1) {
+                try field.append(text[pos]);
+            }
+        }
+
+        const str = try rt.get_or_put_string(field.items);
+        var obj = try (Object{ .String = str.value_ptr }).ref();
+        errdefer obj.deinit(rt.alloc);
+        try record.value.?.append(obj);
+
+        if (pos >= text.len) return pos;
+
+        if (text[pos] == dialect.delimiter) {
+            pos += 1;
+            continue;
+        }
+        return switch (text[pos]) {
+            '\r' => if (pos + 1 < text.len and text[pos + 1] == '\n') pos + 2 else pos + 1,
+            '\n' => pos + 1,
+            // Something other than a delimiter or line break after the
+            // closing quote of a field.
+            else => InternalError.ValueError,
+        };
+    }
+}
+
+/// Write `records`, an Array of either Arrays of fields or Maps from names
+/// to fields, to `writer`. In the latter case, a header is written first,
+/// taken from the first Map's keys in the order they were filed; every Map
+/// must then have each of them. Fields may be Strings, Booleans, or numbers;
+/// anything else is a TypeError.
+pub fn stringify(writer: anytype, records: Object, dialect: Dialect) !void {
+    if (records != .Array) return InternalError.TypeError;
+    const items = records.Array.value.?.items;
+    if (items.len == 0) return;
+
+    if (items[0] == .Map) {
+        const header = items[0].Map.value.?.keys();
+        for (header) |name, idx| {
+            if (idx > 0) try writer.writeByte(dialect.delimiter);
+            try write_field(writer, name, dialect);
+        }
+        try writer.writeByte('\n');
+
+        for (items) |record| {
+            if (record != .Map) return InternalError.TypeError;
+            for (header) |name, idx| {
+                if (idx > 0) try writer.writeByte(dialect.delimiter);
+                const value = record.Map.value.?.get(name) orelse return InternalError.ValueError;
+                try write_object_field(writer, value, dialect);
+            }
+            try writer.writeByte('\n');
+        }
+        return;
+    }
+
+    for (items) |record| {
+        if (record != .Array) return InternalError.TypeError;
+        for (record.Array.value.?.items) |value, idx| {
+            if (idx > 0) try writer.writeByte(dialect.delimiter);
+            try write_object_field(writer, value, dialect);
+        }
+        try writer.writeByte('\n');
+    }
+}
+
+fn write_object_field(writer: anytype, value: Object, dialect: Dialect) !void {
+    switch (value) {
+        .String => |str| try write_field(writer, str.value.?, dialect),
+        .Boolean, .Float, .SignedInt, .UnsignedInt => {
+            var buf: [64]u8 = undefined;
+            const formatted = std.fmt.bufPrint(&buf, "{}", .{value}) catch return InternalError.ValueError;
+            try write_field(writer, formatted, dialect);
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+fn write_field(writer: anytype, bytes: []const u8, dialect: Dialect) !void {
+    const special = [_]u8{ dialect.delimiter, dialect.quote, '\r', '\n' };
+    if (std.mem.indexOfAny(u8, bytes, &special) == null) return try writer.writeAll(bytes);
+
+    try writer.writeByte(dialect.quote);
+    for (bytes) |chr| {
+        if (chr == dialect.quote) try writer.writeByte(dialect.quote);
+        try writer.writeByte(chr);
+    }
+    try writer.writeByte(dialect.quote);
+}
+
+test "parse and stringify" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    const text = "name,quote\r\nmo,\"says \"\"hi\"\", twice\"\nbo,\"multi\nline\"\n";
+
+    var records = try (try parse(&rt, text, .{})).ref();
+    defer rt.release_heaped_object_reference(&records);
+    const items = records.Array.value.?.items;
+    try expectEqual(@as(usize, 3), items.len);
+    try expectEqualStrings("says \"hi\", twice", items[1].Array.value.?.items[1].String.value.?);
+    try expectEqualStrings("multi\nline", items[2].Array.value.?.items[1].String.value.?);
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+    try stringify(out.writer(), records, .{});
+    try expectEqualStrings("name,quote\nmo,\"says \"\"hi\"\", twice\"\nbo,\"multi\nline\"\n", out.items);
+
+    var headed = try (try parse_headed(&rt, text, .{})).ref();
+    defer rt.release_heaped_object_reference(&headed);
+    const first = &headed.Array.value.?.items[0].Map.value.?;
+    try expectEqualStrings("mo", first.get("name").?.String.value.?);
+
+    out.clearRetainingCapacity();
+    try stringify(out.writer(), headed, .{});
+    try expectEqualStrings("name,quote\nmo,\"says \"\"hi\"\", twice\"\nbo,\"multi\nline\"\n", out.items);
+}
+
+test "parse: dialects and malformed input" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var records = try (try parse(&rt, "a;'b;c';", try Dialect.from_spec(";'"))).ref();
+    defer rt.release_heaped_object_reference(&records);
+    const fields = records.Array.value.?.items[0].Array.value.?.items;
+    try expectEqual(@as(usize, 3), fields.len);
+    try expectEqualStrings("b;c", fields[1].String.value.?);
+    try expectEqualStrings("", fields[2].String.value.?);
+
+    try expectError(InternalError.ValueError, parse(&rt, "\"open", .{}));
+    try expectError(InternalError.ValueError, parse(&rt, "\"a\"b", .{}));
+    try expectError(InternalError.ValueError, parse_headed(&rt, "a,b\n1", .{}));
+    try expectError(InternalError.ValueError, parse_headed(&rt, "a,a\n1,2", .{}));
+    try expectError(InternalError.ValueError, Dialect.from_spec(",,"));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
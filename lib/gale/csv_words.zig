@@ -0,0 +1,126 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const csv = @import("./csv.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every CSV word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@CSV-PARSE", CSV_PARSE },
+    .{ "@CSV-PARSE-HEADED", CSV_PARSE_HEADED },
+    .{ "@CSV-STRINGIFY", CSV_STRINGIFY },
+};
+
+/// Define every CSV word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @CSV-PARSE ( String String <- Array )
+///
+/// The records of the text (the further String), each an Array of String
+/// fields. The nearer String spells the dialect: the delimiter, optionally
+/// followed by the quote character (which is otherwise "), so "," is
+/// classic CSV. Malformed text or dialects are a ValueError.
+pub fn CSV_PARSE(runtime: *Runtime) anyerror!void {
+    try parse_top(runtime, csv.parse);
+}
+
+/// @CSV-PARSE-HEADED ( String String <- Array )
+///
+/// As @CSV-PARSE, but treating the first record as a header naming the
+/// fields, and producing a Map from those names to the fields of each later
+/// record. Records whose lengths don't match the header's are a ValueError.
+pub fn CSV_PARSE_HEADED(runtime: *Runtime) anyerror!void {
+    try parse_top(runtime, csv.parse_headed);
+}
+
+fn parse_top(runtime: *Runtime, comptime parse_fn: anytype) !void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .String) return InternalError.TypeError;
+
+    const dialect = try csv.Dialect.from_spec(peek.near.String.value.?);
+    const parsed = try parse_fn(runtime, far.String.value.?, dialect);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(parsed);
+}
+
+/// @CSV-STRINGIFY ( Array String <- String )
+///
+/// The records of the Array as text in the dialect the String spells (see
+/// @CSV-PARSE). Records may be Arrays of fields, or Maps as @CSV-PARSE-HEADED
+/// produces, in which case a header is written first. Fields may be Strings,
+/// Booleans, or numbers; anything else is a TypeError.
+pub fn CSV_STRINGIFY(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .Array) return InternalError.TypeError;
+
+    const dialect = try csv.Dialect.from_spec(peek.near.String.value.?);
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+    try csv.stringify(out.writer(), far.*, dialect);
+    const str = try runtime.get_or_put_string(out.items);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+test "@CSV-PARSE and @CSV-STRINGIFY" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"a;b\" \";\" @CSV-PARSE @DUP \",\" @CSV-STRINGIFY");
+    var text = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&text);
+    try expectEqualStrings("a,b\n", text.String.value.?);
+
+    var parsed = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&parsed);
+    try expectEqual(@as(usize, 2), parsed.Array.value.?.items[0].Array.value.?.items.len);
+
+    try expectError(InternalError.ValueError, runtime.eval("\"a\" \";;\" @CSV-PARSE"));
+    var dialect = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&dialect);
+    var source = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&source);
+}
+
+test "@CSV-PARSE-HEADED" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"k\nv\" \",\" @CSV-PARSE-HEADED");
+    var parsed = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&parsed);
+    try expectEqualStrings("v", parsed.Array.value.?.items[0].Map.value.?.get("k").?.String.value.?);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
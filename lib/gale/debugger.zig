@@ -0,0 +1,324 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const helpers = @import("./helpers.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const ParsedWord = @import("./parsed_word.zig").ParsedWord;
+const Runtime = @import("./runtime.zig").Runtime;
+const Types = @import("./types.zig");
+
+/// Breakpoints and stepping over a Runtime's single-step API (see
+/// Runtime.begin and Runtime.step), for hosts (the CLI's REPL among them)
+/// that want to pause gale code, poke at the Stack, and carry on.
+///
+/// Breakpoints are by word name, and are checked before each inner word of a
+/// Compound is run, which is where everything handed to `begin_source` lives.
+/// Nothing stops the host from using the Stack (or even eval) while paused:
+/// pending words pick up wherever the Stack is left.
+pub const Debugger = struct {
+    const Self = @This();
+
+    pub const Stop = union(enum) {
+        /// Everything pending has run.
+        Finished,
+        /// The next word to run is one with a breakpoint on it, by this name.
+        Breakpoint: []const u8,
+    };
+
+    rt: *Runtime,
+    /// Owned copies of the names breakpoints are set on.
+    breakpoints: std.StringArrayHashMap(void),
+    /// Compiled by begin_source, to which we hold a reference until it's
+    /// finished (or abandoned).
+    program: ?*Types.HeapedWord,
+    /// Set when stopped at a breakpoint, so that carrying on doesn't stop
+    /// right back at it.
+    at_breakpoint: bool,
+
+    pub fn init(rt: *Runtime) Self {
+        return .{
+            .rt = rt,
+            .breakpoints = std.StringArrayHashMap(void).init(rt.alloc),
+            .program = null,
+            .at_breakpoint = false,
+        };
+    }
+
+    /// Abandons anything still pending, too.
+    pub fn deinit(self: *Self) void {
+        if (self.running()) self.abandon();
+        for (self.breakpoints.keys()) |name| self.rt.alloc.free(name);
+        self.breakpoints.deinit();
+    }
+
+    pub fn add_breakpoint(self: *Self, name: []const u8) !void {
+        if (self.breakpoints.contains(name)) return;
+
+        const owned = try self.rt.alloc.dupe(u8, name);
+        errdefer self.rt.alloc.free(owned);
+        try self.breakpoints.put(owned, {});
+    }
+
+    /// Returns whether there was a breakpoint on `name` to remove.
+    pub fn remove_breakpoint(self: *Self, name: []const u8) bool {
+        const removed = self.breakpoints.fetchOrderedRemove(name) orelse return false;
+        self.rt.alloc.free(removed.key);
+        return true;
+    }
+
+    /// Queue up `word` to be debugged. Nothing runs until `step` or
+    /// `run_until_break`.
+    pub fn begin(self: *Self, word: *Types.HeapedWord) !void {
+        try self.rt.begin(word);
+    }
+
+    /// Queue up `source`, read just as eval would, to be debugged. Words are
+    /// looked up as they're reached, not now, so unknown words are only an
+    /// error once (and if) execution gets to them.
+    pub fn begin_source(self: *Self, source: []const u8) !void {
+        const program = try compile(self.rt, source);
+        try program.increment();
+        errdefer release_word(self.rt, program);
+
+        try self.rt.begin(program);
+        if (self.program) |older| release_word(self.rt, older);
+        self.program = program;
+    }
+
+    /// Whether anything is pending.
+    pub fn running(self: *const Self) bool {
+        return self.rt.frames.items.len > 0;
+    }
+
+    /// Run exactly one step (see Runtime.step), breakpoints or not.
+    pub fn step(self: *Self) !Runtime.Step {
+        self.at_breakpoint = false;
+
+        const result = self.rt.step() catch |err| {
+            self.finish();
+            return err;
+        };
+
+        if (!self.running()) self.finish();
+        return result;
+    }
+
+    /// Step until everything pending has finished, or a breakpoint is hit.
+    pub fn run_until_break(self: *Self) !Stop {
+        while (self.running()) {
+            if (!self.at_breakpoint) {
+                if (try self.breakpoint_ahead()) |name| {
+                    self.at_breakpoint = true;
+                    return Stop{ .Breakpoint = name };
+                }
+            }
+
+            _ = try self.step();
+        }
+
+        return .Finished;
+    }
+
+    /// Throw away everything pending without running it (see
+    /// Runtime.abandon).
+    pub fn abandon(self: *Self) void {
+        self.rt.abandon();
+        self.finish();
+    }
+
+    fn finish(self: *Self) void {
+        self.at_breakpoint = false;
+        if (self.program) |program| release_word(self.rt, program);
+        self.program = null;
+    }
+
+    /// The name of the breakpoint on whatever the innermost Compound will run
+    /// next, if there is one.
+    fn breakpoint_ahead(self: *Self) !?[]const u8 {
+        if (self.breakpoints.count() == 0) return null;
+
+        const frame = self.rt.frames.items[self.rt.frames.items.len - 1];
+        const impl = (frame.word.value orelse return null).impl;
+        if (impl != .Compound or frame.next_step == impl.Compound.len) return null;
+
+        const upcoming = impl.Compound[frame.next_step];
+        const upcoming_impl = (upcoming.value orelse return null).impl;
+
+        for (self.breakpoints.keys()) |name| {
+            // Call sites are judged by name, so breakpoints catch words not
+            // yet (or no longer) defined as whatever the name means now.
+            if (upcoming_impl == .CallSite) {
+                if (std.mem.eql(u8, upcoming_impl.CallSite.name.value.?, name)) return name;
+            } else if ((try self.rt.find_word(name)) == upcoming) {
+                return name;
+            }
+        }
+
+        return null;
+    }
+
+    /// Write the Stack, bottom to top, Forth .S style: its depth between
+    /// angle brackets, then every Object.
+    pub fn write_stack(self: *Self, writer: anytype) !void {
+        var idx = self.rt.stack.depth();
+        try writer.print("<{d}>", .{idx});
+
+        while (idx > 0) {
+            idx -= 1;
+            try writer.print(" {}", .{self.rt.stack.nth_from_top(idx).?.*});
+        }
+    }
+};
+
+fn release_word(rt: *Runtime, word: *Types.HeapedWord) void {
+    var obj = Object{ .Word = word };
+    rt.release_heaped_object_reference(&obj);
+}
+
+/// Build a Compound out of `source`, one inner word per word of source:
+/// call sites for words, and literal-pushing words for everything else.
+fn compile(rt: *Runtime, source: []const u8) !*Types.HeapedWord {
+    var inner = std.ArrayList(*Types.HeapedWord).init(rt.alloc);
+    defer inner.deinit();
+    errdefer {
+        for (inner.items) |word| release_word(rt, word);
+    }
+
+    var pos: usize = 0;
+    while (try next_token(source, &pos)) |token| {
+        try inner.ensureUnusedCapacity(1);
+        const word = try compile_word(rt, token);
+        // Compound words hold a reference to each of their inner words.
+        try word.increment();
+        inner.appendAssumeCapacity(word);
+    }
+
+    const body = try rt.alloc.dupe(*Types.HeapedWord, inner.items);
+    errdefer rt.alloc.free(body);
+    const program = try rt.word_from_compound_impl(body, null);
+    // The Compound owns those references now.
+    inner.clearRetainingCapacity();
+    return program;
+}
+
+fn compile_word(rt: *Runtime, token: []const u8) !*Types.HeapedWord {
+    return switch (try ParsedWord.from_input(token)) {
+        // TODO: honor stash/hoist semantics (as eval doesn't yet, either)
+        .Simple => |simple| try rt.word_from_call_site(simple.name),
+        .Ref => |name| try lit_word(rt, .{ .Word = try rt.word_from_call_site(name) }),
+        .String => |str| try lit_word(rt, .{ .String = (try rt.get_or_put_string(str)).value_ptr }),
+        .Symbol => |sym| try lit_word(rt, .{ .Symbol = (try rt.get_or_put_symbol(sym)).value_ptr }),
+        .NumFloat => |num| try lit_word(rt, .{ .Float = num }),
+        .SignedInt => |num| try lit_word(rt, .{ .SignedInt = num }),
+        .UnsignedInt => |num| try lit_word(rt, .{ .UnsignedInt = num }),
+    };
+}
+
+/// A word pushing `obj`, to which it takes a reference, as @LIT would.
+fn lit_word(rt: *Runtime, obj: Object) !*Types.HeapedWord {
+    const heaped = try rt.alloc.create(Object);
+    errdefer rt.alloc.destroy(heaped);
+    heaped.* = try obj.ref();
+    errdefer rt.release_heaped_object_reference(heaped);
+
+    return try rt.word_from_heaplit_impl(heaped, null);
+}
+
+/// The next word of `source` from `pos.*` onward, split as eval splits them,
+/// or null if there are none left.
+fn next_token(source: []const u8, pos: *usize) !?[]const u8 {
+    while (pos.* < source.len and is_word_splitting(source[pos.*])) pos.* += 1;
+    if (pos.* == source.len) return null;
+
+    const start = pos.*;
+    if (source[start] == helpers.CHAR_QUOTE_DBL) {
+        pos.* = if (std.mem.indexOfScalarPos(u8, source, start + 1, helpers.CHAR_QUOTE_DBL)) |end| end + 1 else source.len;
+    }
+
+    while (pos.* < source.len and !is_word_splitting(source[pos.*])) : (pos.* += 1) {
+        if (source[pos.*] == helpers.CHAR_QUOTE_DBL) return InternalError.InvalidWordName;
+    }
+
+    return source[start..pos.*];
+}
+
+fn is_word_splitting(chr: u8) bool {
+    return chr == helpers.CHAR_SPACE or chr == helpers.CHAR_TAB or chr == helpers.CHAR_NEWLINE;
+}
+
+test "breakpoints and stepping" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var debugger = Debugger.init(&rt);
+    defer debugger.deinit();
+
+    try debugger.add_breakpoint("@DUP");
+    try debugger.begin_source("1 \"two\" @SWAP @DUP");
+
+    const stop = try debugger.run_until_break();
+    try expectEqualStrings("@DUP", stop.Breakpoint);
+    try expectEqual(@as(usize, 2), rt.stack.depth());
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+    try debugger.write_stack(out.writer());
+    try expectEqualStrings("<2> two 1", out.items);
+
+    // Whatever the Stack looks like when we carry on is what @DUP gets.
+    try rt.eval("@DROP");
+    _ = try debugger.step();
+    try expect(debugger.running());
+    try expectEqual(Stop.Finished, try debugger.run_until_break());
+    try expect(!debugger.running());
+
+    var pair = try rt.stack_pop_pair();
+    defer rt.release_heaped_object_reference(&pair.near);
+    defer rt.release_heaped_object_reference(&pair.far);
+    try expectEqualStrings("two", pair.near.String.value.?);
+    try expectEqual(pair.near.String, pair.far.String);
+}
+
+test "abandoning leaves the rest unrun" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var debugger = Debugger.init(&rt);
+    defer debugger.deinit();
+
+    try debugger.add_breakpoint("@DROP");
+    try expect(debugger.remove_breakpoint("@DROP"));
+    try expect(!debugger.remove_breakpoint("@DROP"));
+    try debugger.add_breakpoint("@SWAP");
+
+    try debugger.begin_source("1 2 @SWAP @DROP");
+    _ = try debugger.run_until_break();
+    debugger.abandon();
+    try expect(!debugger.running());
+
+    try expectEqual(@as(usize, 2), (try rt.stack_pop()).UnsignedInt);
+    try expectEqual(@as(usize, 1), (try rt.stack_pop()).UnsignedInt);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
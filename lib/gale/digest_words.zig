@@ -0,0 +1,116 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// Digests of bytes, which are Strings for now, since that's the only Object
+// which holds them. Hashes come back as lowercase hex, the form they're
+// published and compared in; @HEX-DECODE gets at the raw bytes.
+// TODO: take Opaques too, once there's a way to make byte buffers of them.
+
+/// Every digest word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@CRC32", CRC32 },
+    .{ "@SHA1", SHA1 },
+    .{ "@SHA256", SHA256 },
+};
+
+/// Define every digest word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @SHA256 ( String <- String )
+///
+/// The SHA-256 digest of the String's bytes, as 64 hex digits.
+pub fn SHA256(runtime: *Runtime) anyerror!void {
+    try hex_digest(runtime, std.crypto.hash.sha2.Sha256);
+}
+
+/// @SHA1 ( String <- String )
+///
+/// The SHA-1 digest of the String's bytes, as 40 hex digits. SHA-1 is
+/// broken for anything adversarial: use it to check against published SHA-1
+/// sums, not to fingerprint anything anyone might want to forge.
+pub fn SHA1(runtime: *Runtime) anyerror!void {
+    try hex_digest(runtime, std.crypto.hash.Sha1);
+}
+
+/// @CRC32 ( String <- UnsignedInt )
+///
+/// The CRC-32 (IEEE, as in zlib and PNG) checksum of the String's bytes.
+pub fn CRC32(runtime: *Runtime) anyerror!void {
+    const input = try peek_bytes(runtime);
+    const checksum = std.hash.Crc32.hash(input);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_uint(checksum);
+}
+
+fn hex_digest(runtime: *Runtime, comptime Hash: type) !void {
+    const input = try peek_bytes(runtime);
+
+    var digest: [Hash.digest_length]u8 = undefined;
+    Hash.hash(input, &digest, .{});
+
+    var hex: [Hash.digest_length * 2]u8 = undefined;
+    _ = std.fmt.bufPrint(&hex, "{}", .{std.fmt.fmtSliceHexLower(&digest)}) catch unreachable;
+    const str = try runtime.get_or_put_string(&hex);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+fn peek_bytes(runtime: *Runtime) ![]const u8 {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+    return peeked.String.value.?;
+}
+
+fn expect_digest(runtime: *Runtime, source: []const u8, expected: []const u8) !void {
+    try runtime.eval(source);
+    var result = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&result);
+    try expectEqualStrings(expected, result.String.value.?);
+}
+
+test "@SHA256 and @SHA1" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_digest(&runtime, "\"abc\" @SHA256", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    try expect_digest(&runtime, "\"abc\" @SHA1", "a9993e364706816aba3e25717850c26c9cd0d89d");
+
+    try expectError(InternalError.TypeError, runtime.eval("1 @SHA256"));
+    _ = try runtime.stack_pop();
+}
+
+test "@CRC32" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"abc\" @CRC32");
+    try expectEqual(@as(usize, 891568578), (try runtime.stack_pop()).UnsignedInt);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
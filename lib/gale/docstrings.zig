@@ -0,0 +1,197 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const Lexer = @import("./lexer.zig").Lexer;
+const tags = @import("./tags.zig");
+
+// Docstrings (`{{ ... }}`, see lexer.zig) document whatever's defined next:
+// a word, a shape, or a shape's member, as tags.zig finds them. As far as
+// the Lexer's concerned they're just block comments, so this is the pass that
+// gives each one its owner, for Runtime.document_source (and thus @SEE and
+// @HELP) and any other tooling to look up by name.
+//
+// Only the nearest docstring before a definition is its own; any others
+// before it (and any after the last definition) document nothing.
+
+pub const Docstring = struct {
+    /// The definition's name, as tags.zig gives it.
+    name: []const u8,
+    kind: tags.Kind,
+    /// See text_of.
+    text: []const u8,
+    /// 1-based, as with Tokens, of the docstring's start.
+    line: usize,
+};
+
+/// Every Docstring in one source, in the order they were found.
+pub const Docstrings = struct {
+    const Self = @This();
+
+    arena: std.heap.ArenaAllocator,
+    items: []Docstring,
+
+    pub fn deinit(self: *Self) void {
+        self.arena.deinit();
+    }
+};
+
+/// Whether `raw`, a block comment's text, is a docstring: a block comment
+/// whose body is a single block comment.
+pub fn is_docstring(raw: []const u8) bool {
+    if (raw.len < 4 or !std.mem.startsWith(u8, raw, "{{") or !std.mem.endsWith(u8, raw, "}}")) return false;
+
+    // `{{ a } { b }}` opens with two braces, but the first closes early.
+    var depth: usize = 0;
+    for (raw[1 .. raw.len - 1]) |chr, idx| {
+        switch (chr) {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if (depth == 0) return idx == raw.len - 3;
+            },
+            else => {},
+        }
+    }
+    return false;
+}
+
+/// The body of docstring `raw`, without its braces, each line trimmed of
+/// surrounding whitespace and blank lines dropped from either end. The
+/// caller owns the result.
+pub fn text_of(alloc: Allocator, raw: []const u8) ![]u8 {
+    var text = std.ArrayList(u8).init(alloc);
+    errdefer text.deinit();
+
+    var blank_lines: usize = 0;
+    var lines = std.mem.split(u8, raw[2 .. raw.len - 2], "\n");
+    while (lines.next()) |line| {
+        const trimmed = std.mem.trim(u8, line, " \t\r");
+        if (trimmed.len == 0) {
+            blank_lines += 1;
+            continue;
+        }
+
+        if (text.items.len > 0) try text.appendNTimes('\n', blank_lines + 1);
+        blank_lines = 0;
+        try text.appendSlice(trimmed);
+    }
+
+    return text.toOwnedSlice();
+}
+
+/// Find each docstring in `source` and the definition it documents.
+/// Unterminated Strings and block comments are a ValueError, as they are to
+/// the Lexer.
+pub fn associate(alloc: Allocator, source: []const u8) !Docstrings {
+    var arena = std.heap.ArenaAllocator.init(alloc);
+    errdefer arena.deinit();
+
+    var definitions = try tags.scan(alloc, source);
+    defer definitions.deinit();
+
+    var found = std.ArrayList(Docstring).init(arena.allocator());
+    // The first definition past the last docstring seen, and which one the
+    // last docstring found went to, if any.
+    var next_definition: usize = 0;
+    var last_documented: ?usize = null;
+
+    var lexer = Lexer.init(source);
+    while (try lexer.next()) |token| {
+        if (token.kind != .BlockComment or !is_docstring(token.raw)) continue;
+
+        const end = token.offset + token.raw.len;
+        while (next_definition < definitions.items.len) : (next_definition += 1) {
+            const definition = definitions.items[next_definition];
+            // Vocabularies aren't defined so much as implied by their words.
+            if (definition.kind != .Vocabulary and offset_of(definition) >= end) break;
+        }
+        if (next_definition == definitions.items.len) break;
+
+        const definition = definitions.items[next_definition];
+        const docstring = Docstring{
+            .name = try arena.allocator().dupe(u8, definition.name),
+            .kind = definition.kind,
+            .text = try text_of(arena.allocator(), token.raw),
+            .line = token.line,
+        };
+
+        if (last_documented != null and last_documented.? == next_definition) {
+            found.items[found.items.len - 1] = docstring;
+        } else {
+            try found.append(docstring);
+        }
+        last_documented = next_definition;
+    }
+
+    return Docstrings{ .arena = arena, .items = found.toOwnedSlice() };
+}
+
+/// Where `tag`'s name starts in its source.
+fn offset_of(tag: tags.Tag) usize {
+    return tag.line_offset + tag.column - 1;
+}
+
+test "is_docstring" {
+    try expect(is_docstring("{{ Push a one. }}"));
+    try expect(is_docstring("{{ Nested { braces } are fine. }}"));
+    try expect(!is_docstring("{ Just a comment. }"));
+    try expect(!is_docstring("{{ a } { b }}"));
+    try expect(!is_docstring("{{}"));
+}
+
+test "text_of" {
+    const text = try text_of(testAllocator, "{{\n    Push a one.\n\n    The long way round.\n}}");
+    defer testAllocator.free(text);
+    try expectEqualStrings("Push a one.\n\nThe long way round.", text);
+}
+
+test "associate" {
+    var found = try associate(testAllocator,
+        \\{{ Orphaned by the next. }}
+        \\{{ Something printable. }}
+        \\$ Printable
+        \\    {{ Show it. }}
+        \\    $: repr ( Self <- String ) ;
+        \\;
+        \\
+        \\{ Not a docstring. }
+        \\: greet "hi" println ;
+        \\
+        \\{{ Two of them. }}
+        \\&@DUP :twin @DEFINE
+        \\{{ Documenting nothing. }}
+    );
+    defer found.deinit();
+
+    const expected = [_]struct { name: []const u8, kind: tags.Kind, text: []const u8, line: usize }{
+        .{ .name = "Printable", .kind = .Shape, .text = "Something printable.", .line = 2 },
+        .{ .name = "Printable/repr", .kind = .Member, .text = "Show it.", .line = 4 },
+        .{ .name = "twin", .kind = .Word, .text = "Two of them.", .line = 11 },
+    };
+
+    try expectEqual(expected.len, found.items.len);
+    for (expected) |want, idx| {
+        const docstring = found.items[idx];
+        try expectEqualStrings(want.name, docstring.name);
+        try expectEqual(want.kind, docstring.kind);
+        try expectEqualStrings(want.text, docstring.text);
+        try expectEqual(want.line, docstring.line);
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// Bytes in and out of text-safe encodings. Bytes are Strings for now, since
+// that's the only Object which holds them.
+// TODO: take (and produce) Opaques too, once there's a way to make byte
+// buffers of them.
+
+/// Every encoding word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@BASE64-DECODE", BASE64_DECODE },
+    .{ "@BASE64-ENCODE", BASE64_ENCODE },
+    .{ "@HEX-DECODE", HEX_DECODE },
+    .{ "@HEX-ENCODE", HEX_ENCODE },
+};
+
+/// Define every encoding word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @BASE64-ENCODE ( String <- String )
+///
+/// The String's bytes in standard (RFC 4648, padded) base64.
+pub fn BASE64_ENCODE(runtime: *Runtime) anyerror!void {
+    const input = try peek_bytes(runtime);
+    const encoder = std.base64.standard.Encoder;
+
+    const out = try runtime.alloc.alloc(u8, encoder.calcSize(input.len));
+    _ = encoder.encode(out, input);
+    try replace_top(runtime, out);
+}
+
+/// @BASE64-DECODE ( String <- String )
+///
+/// The bytes the String spells in standard (RFC 4648, padded) base64.
+/// Anything else is a ValueError.
+pub fn BASE64_DECODE(runtime: *Runtime) anyerror!void {
+    const input = try peek_bytes(runtime);
+    const decoder = std.base64.standard.Decoder;
+
+    const len = decoder.calcSizeForSlice(input) catch return InternalError.ValueError;
+    const out = try runtime.alloc.alloc(u8, len);
+    errdefer runtime.alloc.free(out);
+    decoder.decode(out, input) catch return InternalError.ValueError;
+    try replace_top(runtime, out);
+}
+
+/// @HEX-ENCODE ( String <- String )
+///
+/// The String's bytes as lowercase hexadecimal, two digits apiece.
+pub fn HEX_ENCODE(runtime: *Runtime) anyerror!void {
+    const input = try peek_bytes(runtime);
+
+    const out = try std.fmt.allocPrint(runtime.alloc, "{}", .{std.fmt.fmtSliceHexLower(input)});
+    try replace_top(runtime, out);
+}
+
+/// @HEX-DECODE ( String <- String )
+///
+/// The bytes the String spells in hexadecimal, in either case. Odd lengths
+/// and non-hex digits are a ValueError.
+pub fn HEX_DECODE(runtime: *Runtime) anyerror!void {
+    const input = try peek_bytes(runtime);
+    if (input.len % 2 != 0) return InternalError.ValueError;
+
+    const out = try runtime.alloc.alloc(u8, input.len / 2);
+    errdefer runtime.alloc.free(out);
+    _ = std.fmt.hexToBytes(out, input) catch return InternalError.ValueError;
+    try replace_top(runtime, out);
+}
+
+fn peek_bytes(runtime: *Runtime) ![]const u8 {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+    return peeked.String.value.?;
+}
+
+/// Swap the String on top of the Stack for one holding `owned`, which must
+/// have come from the Runtime's allocator.
+fn replace_top(runtime: *Runtime, owned: []u8) !void {
+    const str = runtime.new_string_owned(owned) catch |err| {
+        runtime.alloc.free(owned);
+        return err;
+    };
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str);
+}
+
+fn expect_round_trip(runtime: *Runtime, source: []const u8, expected: []const u8) !void {
+    try runtime.eval(source);
+    var result = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&result);
+    try expectEqualStrings(expected, result.String.value.?);
+}
+
+test "@BASE64-ENCODE and @BASE64-DECODE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_round_trip(&runtime, "\"hi!?\" @BASE64-ENCODE", "aGkhPw==");
+    try expect_round_trip(&runtime, "\"aGkhPw==\" @BASE64-DECODE", "hi!?");
+
+    try expectError(InternalError.ValueError, runtime.eval("\"a*==\" @BASE64-DECODE"));
+    var bad = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&bad);
+}
+
+test "@HEX-ENCODE and @HEX-DECODE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_round_trip(&runtime, "\"hi\" @HEX-ENCODE", "6869");
+    try expect_round_trip(&runtime, "\"6A6b\" @HEX-DECODE", "jk");
+
+    try expectError(InternalError.ValueError, runtime.eval("\"686\" @HEX-DECODE"));
+    var odd = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&odd);
+
+    try expectError(InternalError.ValueError, runtime.eval("\"zz\" @HEX-DECODE"));
+    var bad = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&bad);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,174 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const builtin = @import("builtin");
+
+const capabilities = @import("./capabilities.zig");
+const conversion = @import("./conversion.zig");
+const helpers = @import("./helpers.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every environment word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@CWD", CWD },
+    .{ "@ENV-GET", ENV_GET },
+    .{ "@ENV-SET", ENV_SET },
+    .{ "@PID", PID },
+};
+
+/// Define every environment word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @ENV-GET ( String <- String | Symbol )
+///
+/// The value of the named environment variable, or (as with any other
+/// "optional" value in gale, see conversion.NULL_SYMBOL) `:null` if it isn't
+/// set. Needs the Env capability.
+pub fn ENV_GET(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Env);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+    const found = (try runtime.env_map()).get(peeked.String.value.?);
+
+    // The value may be freed by a later @ENV-SET, so the String gets its own
+    // copy.
+    const value = try conversion.to_object(runtime, found);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(value);
+}
+
+/// @ENV-SET ( String String <- )
+///
+/// Set the environment variable named on top of the Stack to the value
+/// beneath it. This only changes this Runtime's view of the environment
+/// (and that of any subprocesses it starts), never that of the process
+/// hosting it. Needs the Env capability.
+pub fn ENV_SET(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Env);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .String) return InternalError.TypeError;
+
+    // EnvMap copies both, so the Strings are free to go right after.
+    try (try runtime.env_map()).put(peek.near.String.value.?, far.String.value.?);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @CWD ( <- String )
+///
+/// The absolute path of the process's working directory, which is what
+/// every relative path (see fs_words.zig) is relative to. Needs the Read
+/// capability.
+pub fn CWD(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Read);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const cwd = if (try runtime.recording.replayed(.Cwd)) |recorded|
+        try runtime.alloc.dupe(u8, recorded)
+    else
+        try std.process.getCwdAlloc(runtime.alloc);
+    runtime.recording.record(.{ .Cwd = cwd }) catch |err| {
+        runtime.alloc.free(cwd);
+        return err;
+    };
+
+    const str = runtime.new_string_owned(cwd) catch |err| {
+        runtime.alloc.free(cwd);
+        return err;
+    };
+    try runtime.stack_push_string(str);
+}
+
+/// @PID ( <- UnsignedInt )
+///
+/// The ID of the process hosting this Runtime. Raises
+/// InternalError.Unimplemented on systems without processes (or without a
+/// way we know of to ask).
+pub fn PID(runtime: *Runtime) anyerror!void {
+    const found = if (try runtime.recording.replayed(.Pid)) |recorded| recorded else current_pid();
+    try runtime.recording.record(.{ .Pid = found });
+
+    const pid = found orelse return InternalError.Unimplemented;
+    try runtime.stack_push_uint(pid);
+}
+
+fn current_pid() ?usize {
+    if (comptime !helpers.HOSTED) return null;
+
+    return switch (comptime builtin.os.tag) {
+        .linux => @intCast(usize, std.os.linux.getpid()),
+        .windows => @as(usize, std.os.windows.kernel32.GetCurrentProcessId()),
+        else => if (comptime builtin.link_libc) @intCast(usize, std.c.getpid()) else null,
+    };
+}
+
+test "@ENV-SET and @ENV-GET" {
+    var granted = capabilities.NONE;
+    granted.insert(.Env);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"bar\" \"GALE_TEST_FOO\" @ENV-SET \"GALE_TEST_FOO\" @ENV-GET");
+    var found = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&found);
+    try expectEqualStrings("bar", found.String.value.?);
+
+    try runtime.eval("\"GALE_TEST_SURELY_UNSET\" @ENV-GET");
+    var missing = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&missing);
+    try expectEqualStrings(conversion.NULL_SYMBOL, missing.Symbol.value.?);
+
+    // The process's own environment is none the wiser.
+    try expect(std.os.getenv("GALE_TEST_FOO") == null);
+}
+
+test "@CWD and @PID" {
+    var granted = capabilities.NONE;
+    granted.insert(.Read);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("@CWD");
+    var cwd = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&cwd);
+    try expect(std.fs.path.isAbsolute(cwd.String.value.?));
+
+    try runtime.eval("@PID");
+    try expect((try runtime.stack_pop_value(usize)) > 0);
+
+    try expectError(InternalError.CapabilityDenied, runtime.eval("\"HOME\" @ENV-GET"));
+    var name = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&name);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,206 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const helpers = @import("./helpers.zig");
+const io = @import("./io.zig");
+const json = @import("./json.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Observer = @import("./observer.zig").Observer;
+const ObserverEvent = @import("./observer.zig").Event;
+const OutputHandle = io.OutputHandle;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+// A log of the things worth knowing about a long-running Runtime, as opposed
+// to the Tracer's account of every word run: what got defined, which
+// vocabularies came to be, which sources were loaded, and what failed. It's
+// an Observer like any other (install it with Runtime.add_observer), so
+// costs nothing until it's installed.
+
+/// Something an EventLog records. As with Observer Events, slices are only
+/// valid for the duration of the callback.
+pub const Record = union(enum) {
+    /// A word was defined, under this (fully qualified) name.
+    Defined: []const u8,
+    /// A vocabulary got its first word.
+    VocabularyCreated: []const u8,
+    /// A word failed, as of the word that raised it: the words it unwinds
+    /// through aren't recorded again. Failures outside of any word (an
+    /// UnknownWord at the top level, say) are the caller's to see.
+    Error: anyerror,
+    /// A source, by path, was loaded without failing.
+    Loaded: []const u8,
+
+    /// What write_record calls this kind of Record.
+    pub fn event_name(self: Record) []const u8 {
+        return switch (self) {
+            .Defined => "defined",
+            .VocabularyCreated => "vocabulary-created",
+            .Error => "error",
+            .Loaded => "loaded",
+        };
+    }
+};
+
+pub const RecordFn = fn (context: ?*anyopaque, rt: *Runtime, record: Record) void;
+
+/// Where an EventLog's Records go.
+pub const Sink = union(enum) {
+    /// Written out a line at a time (see write_record) to stderr
+    /// (io.stderr()), a file (io.file_output), or wherever else.
+    Output: OutputHandle,
+    /// Handed to the host as they happen, for hosts with logging of their
+    /// own. As with Observers, `context` must outlive the EventLog's
+    /// installation.
+    Callback: struct {
+        context: ?*anyopaque,
+        on_record: *const RecordFn,
+    },
+};
+
+pub const EventLog = struct {
+    const Self = @This();
+
+    sink: Sink,
+    /// Whether written lines lead with when they were written. Freestanding
+    /// targets, having no clock, never do.
+    timestamps: bool = true,
+    /// Whether the last Event was an Error, such that the next is just the
+    /// same one unwinding.
+    unwinding: bool = false,
+
+    pub fn init(sink: Sink) Self {
+        return .{ .sink = sink };
+    }
+
+    pub fn observer(self: *Self) Observer {
+        return .{ .context = self, .on_event = &on_event };
+    }
+
+    fn on_event(context: ?*anyopaque, rt: *Runtime, event: ObserverEvent) void {
+        const self = @ptrCast(*Self, @alignCast(@alignOf(Self), context.?));
+        const unwinding = self.unwinding;
+        self.unwinding = event == .Error;
+
+        const record: Record = switch (event) {
+            .Defined => |definition| .{ .Defined = definition.name },
+            .VocabularyCreated => |name| .{ .VocabularyCreated = name },
+            .Loaded => |path| .{ .Loaded = path },
+            .Error => |err| if (unwinding) return else Record{ .Error = err },
+            .WordStart, .WordEnd, .Push, .Pop => return,
+        };
+
+        switch (self.sink) {
+            // Failing to write a log line is no reason to fail whatever's
+            // logged.
+            .Output => |output| write_record(output.writer(), record, self.now()) catch {},
+            .Callback => |callback| callback.on_record(callback.context, rt, record),
+        }
+    }
+
+    fn now(self: *const Self) ?i64 {
+        if (comptime !helpers.HOSTED) return null;
+        return if (self.timestamps) std.time.milliTimestamp() else null;
+    }
+};
+
+/// `record` as a line of JSON, `{"event":"defined","name":"std/nip"}` and the
+/// like, led by `"at":` milliseconds since the UNIX epoch, if given.
+pub fn write_record(writer: anytype, record: Record, at: ?i64) !void {
+    try writer.writeByte('{');
+    if (at) |millis| try writer.print("\"at\":{d},", .{millis});
+    try writer.print("\"event\":\"{s}\",", .{record.event_name()});
+
+    switch (record) {
+        .Defined, .VocabularyCreated => |name| {
+            try writer.writeAll("\"name\":");
+            try json.write_string(writer, name);
+        },
+        .Loaded => |path| {
+            try writer.writeAll("\"path\":");
+            try json.write_string(writer, path);
+        },
+        .Error => |err| try writer.print("\"error\":\"{s}\"", .{@errorName(err)}),
+    }
+
+    try writer.writeAll("}\n");
+}
+
+test "EventLog" {
+    var log = std.ArrayList(u8).init(testAllocator);
+    defer log.deinit();
+
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var events = EventLog.init(.{ .Output = io.buffer_output(&log) });
+    events.timestamps = false;
+    try rt.add_observer(events.observer());
+
+    try rt.define_word("app/twin", (try rt.find_word("@DUP")).?);
+    try rt.define_word("app/\"quoted\"", (try rt.find_word("@DROP")).?);
+    try rt.eval_source("main.gale", "1 app/twin @DROP @DROP");
+    try expectError(InternalError.TypeError, rt.eval("1 \"a\" @ADD"));
+    try rt.eval("@DROP @DROP");
+
+    try expectEqualStrings(
+        \\{"event":"vocabulary-created","name":"app"}
+        \\{"event":"defined","name":"app/twin"}
+        \\{"event":"defined","name":"app/\"quoted\""}
+        \\{"event":"loaded","path":"main.gale"}
+        \\{"event":"error","error":"TypeError"}
+        \\
+    , log.items);
+}
+
+test "EventLog: callbacks" {
+    const Tally = struct {
+        defined: usize = 0,
+        errors: usize = 0,
+
+        fn on_record(context: ?*anyopaque, _: *Runtime, record: Record) void {
+            const tally = @ptrCast(*@This(), @alignCast(@alignOf(@This()), context.?));
+            switch (record) {
+                .Defined => tally.defined += 1,
+                .Error => tally.errors += 1,
+                .VocabularyCreated, .Loaded => {},
+            }
+        }
+    };
+
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var tally = Tally{};
+    var events = EventLog.init(.{ .Callback = .{ .context = &tally, .on_record = &Tally.on_record } });
+    try rt.add_observer(events.observer());
+
+    try rt.eval("&@DUP &@DROP @COMPOSE :wobble @DEFINE");
+    try expectEqual(@as(usize, 1), tally.defined);
+
+    // Unwinding through wobble is still the one error.
+    try expectError(StackManipulationError.Underflow, rt.eval("wobble"));
+    try expectEqual(@as(usize, 1), tally.errors);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,48 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const root = @import("root");
+
+// Word groups which can be left out of a build altogether, rather than just
+// not installed (see Runtime.PrimitiveGroups), for hosts tight on space: a
+// group compiled out is never referenced, so never compiled in, and turning
+// it back on in PrimitiveGroups does nothing. A build picks its Features by
+// declaring
+//
+//     pub const gale_features = gale.Features{ .regex = false };
+//
+// in its root source file, as the CLI (src/gale/main.zig) does from
+// `zig build -Dregex=false` and friends. Without one, everything is in.
+
+pub const Features = struct {
+    /// The REPL's interactive niceties: its debugger (see debugger.zig) and
+    /// Ctrl-C interrupting whatever's running. Only the CLI looks at this,
+    /// libgale itself having no REPL.
+    readline: bool = true,
+    /// regex.zig and regex_words.zig.
+    regex: bool = true,
+    /// json_words.zig. (json.zig itself is left to whatever else uses it.)
+    json: bool = true,
+    /// net_words.zig and http_words.zig.
+    net: bool = true,
+    /// digest_words.zig.
+    crypto: bool = true,
+};
+
+/// The Features this build was made with.
+pub const enabled: Features = if (@hasDecl(root, "gale_features")) root.gale_features else .{};
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,455 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const unicode = @import("./unicode.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+// Templates, as @FORMAT fills them in. Each `{}` in a template is replaced by
+// the next value, written as @PRINT would write it, and `{{` and `}}` stand
+// for literal braces. A placeholder may carry a spec after a colon:
+//
+//     {:[[fill]align][0][width][.precision]}
+//
+// - align is `<`, `>`, or `^` (left, right, or centered within the width),
+//   padded with fill, which defaults to a space. Numbers are right-aligned
+//   unless told otherwise, and everything else left-aligned.
+// - 0 pads numbers with zeroes after their sign, in place of fill and align.
+// - width is the least number of graphemes (see unicode.zig) to write.
+// - precision is how many digits of a Float to write after its point, and
+//   isn't meaningful for anything else.
+//
+// Anything else within braces, or a stray brace, is a ValueError.
+
+/// Every formatting word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@FORMAT", FORMAT },
+    .{ "@TO-STRING-RADIX", TO_STRING_RADIX },
+    .{ "@PARSE-INT-RADIX", PARSE_INT_RADIX },
+    .{ "@GROUP-DIGITS", GROUP_DIGITS },
+};
+
+/// Define every formatting word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+pub const Alignment = enum { Left, Right, Center };
+
+/// The instructions within a single placeholder's braces.
+pub const Spec = struct {
+    const Self = @This();
+
+    fill: u8 = ' ',
+    alignment: ?Alignment = null,
+    zero_padded: bool = false,
+    width: usize = 0,
+    precision: ?usize = null,
+
+    /// Parse what follows a placeholder's colon.
+    pub fn parse(text: []const u8) InternalError!Self {
+        var spec = Self{};
+        var idx: usize = 0;
+
+        if (text.len >= 2 and alignment_of(text[1]) != null) {
+            spec.fill = text[0];
+            spec.alignment = alignment_of(text[1]);
+            idx = 2;
+        } else if (text.len >= 1 and alignment_of(text[0]) != null) {
+            spec.alignment = alignment_of(text[0]);
+            idx = 1;
+        }
+
+        if (idx < text.len and text[idx] == '0') {
+            if (spec.alignment != null) return InternalError.ValueError;
+            spec.zero_padded = true;
+            idx += 1;
+        }
+
+        if (digits_at(text, &idx)) |digits| {
+            spec.width = std.fmt.parseInt(usize, digits, 10) catch return InternalError.ValueError;
+        }
+
+        if (idx < text.len and text[idx] == '.') {
+            idx += 1;
+            const digits = digits_at(text, &idx) orelse return InternalError.ValueError;
+            spec.precision = std.fmt.parseInt(usize, digits, 10) catch return InternalError.ValueError;
+        }
+
+        if (idx != text.len) return InternalError.ValueError;
+        return spec;
+    }
+
+    fn alignment_of(chr: u8) ?Alignment {
+        return switch (chr) {
+            '<' => .Left,
+            '>' => .Right,
+            '^' => .Center,
+            else => null,
+        };
+    }
+
+    fn digits_at(text: []const u8, idx: *usize) ?[]const u8 {
+        const start = idx.*;
+        while (idx.* < text.len and std.ascii.isDigit(text[idx.*])) idx.* += 1;
+        return if (idx.* > start) text[start..idx.*] else null;
+    }
+
+    test "parse" {
+        try expectEqual(Self{}, try Self.parse(""));
+        try expectEqual(Self{ .alignment = .Left, .width = 4 }, try Self.parse("<4"));
+        try expectEqual(Self{ .fill = '*', .alignment = .Center, .width = 5 }, try Self.parse("*^5"));
+        try expectEqual(Self{ .zero_padded = true, .width = 8, .precision = 3 }, try Self.parse("08.3"));
+        try expectEqual(Self{ .precision = 2 }, try Self.parse(".2"));
+        try expectError(InternalError.ValueError, Self.parse(">08"));
+        try expectError(InternalError.ValueError, Self.parse("4."));
+        try expectError(InternalError.ValueError, Self.parse("4x"));
+    }
+};
+
+/// A template, in pieces: runs of literal text (braces unescaped), and
+/// placeholders.
+pub const Piece = union(enum) {
+    Literal: []const u8,
+    Placeholder: Spec,
+};
+
+pub const Pieces = struct {
+    const Self = @This();
+
+    template: []const u8,
+    pos: usize = 0,
+
+    pub fn init(template: []const u8) Self {
+        return .{ .template = template };
+    }
+
+    pub fn next(self: *Self) InternalError!?Piece {
+        if (self.pos >= self.template.len) return null;
+        const rest = self.template[self.pos..];
+
+        switch (rest[0]) {
+            '{' => {
+                if (rest.len > 1 and rest[1] == '{') {
+                    self.pos += 2;
+                    return Piece{ .Literal = rest[0..1] };
+                }
+
+                const close = std.mem.indexOfScalar(u8, rest, '}') orelse return InternalError.ValueError;
+                const inner = rest[1..close];
+                self.pos += close + 1;
+
+                if (inner.len == 0) return Piece{ .Placeholder = .{} };
+                if (inner[0] != ':') return InternalError.ValueError;
+                return Piece{ .Placeholder = try Spec.parse(inner[1..]) };
+            },
+            '}' => {
+                if (rest.len < 2 or rest[1] != '}') return InternalError.ValueError;
+                self.pos += 2;
+                return Piece{ .Literal = rest[0..1] };
+            },
+            else => {
+                const end = std.mem.indexOfAny(u8, rest, "{}") orelse rest.len;
+                self.pos += end;
+                return Piece{ .Literal = rest[0..end] };
+            },
+        }
+    }
+
+    /// How many placeholders are in `template`, which is thus checked from
+    /// end to end.
+    pub fn count_placeholders(template: []const u8) InternalError!usize {
+        var pieces = Self.init(template);
+        var count: usize = 0;
+        while (try pieces.next()) |piece| {
+            if (piece == .Placeholder) count += 1;
+        }
+        return count;
+    }
+};
+
+/// Write `obj` to `writer` as `spec` says to.
+pub fn write_formatted(alloc: Allocator, writer: anytype, obj: Object, spec: Spec) !void {
+    var rendered = std.ArrayList(u8).init(alloc);
+    defer rendered.deinit();
+
+    if (spec.precision) |precision| {
+        if (obj != .Float) return InternalError.TypeError;
+        try std.fmt.formatFloatDecimal(obj.Float, .{ .precision = precision }, rendered.writer());
+    } else {
+        try rendered.writer().print("{}", .{obj});
+    }
+
+    const numeric = switch (obj) {
+        .Float, .SignedInt, .UnsignedInt => true,
+        else => false,
+    };
+    const padding = spec.width -| unicode.grapheme_count(rendered.items);
+
+    if (spec.zero_padded) {
+        if (!numeric) return InternalError.TypeError;
+        const signed = rendered.items.len > 0 and rendered.items[0] == '-';
+        if (signed) try writer.writeByte('-');
+        try writer.writeByteNTimes('0', padding);
+        try writer.writeAll(rendered.items[@boolToInt(signed)..]);
+        return;
+    }
+
+    const alignment = spec.alignment orelse if (numeric) Alignment.Right else Alignment.Left;
+    const before = switch (alignment) {
+        .Left => 0,
+        .Right => padding,
+        .Center => padding / 2,
+    };
+    try writer.writeByteNTimes(spec.fill, before);
+    try writer.writeAll(rendered.items);
+    try writer.writeByteNTimes(spec.fill, padding - before);
+}
+
+/// @FORMAT ( @n ... @1 String <- String )
+///
+/// The String, as a template (see above), filled in with as many values as
+/// it has placeholders, @n first: `1 2 3 "{} + {} = {}" @FORMAT` is
+/// "1 + 2 = 3", and `3.14159 "{:>8.2}" @FORMAT` is "    3.14". A precision
+/// or zero padding for something other than a number is a TypeError.
+pub fn FORMAT(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+    const template = peeked.String.value.?;
+
+    const count = try Pieces.count_placeholders(template);
+    if (runtime.stack.depth() - 1 < count) return StackManipulationError.Underflow;
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+
+    var pieces = Pieces.init(template);
+    var filled: usize = 0;
+    while (try pieces.next()) |piece| switch (piece) {
+        .Literal => |text| try out.appendSlice(text),
+        .Placeholder => |spec| {
+            const value = runtime.stack.nth_from_top(count - filled).?.*;
+            try write_formatted(runtime.alloc, out.writer(), value, spec);
+            filled += 1;
+        },
+    };
+
+    const str = try runtime.get_or_put_string(out.items);
+    var dropped: usize = 0;
+    while (dropped < count + 1) : (dropped += 1) try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+// Radices, as @TO-STRING-RADIX and @PARSE-INT-RADIX take them, run from 2 to
+// 36, digits past 9 being the letters of the alphabet: lowercase when
+// writing, either case when parsing. Anything else is a ValueError. There's
+// no prefix (`0x` and so on) either way, since the radix is already spelled
+// out.
+
+pub const MIN_RADIX = 2;
+pub const MAX_RADIX = 36;
+
+fn peek_radix(runtime: *Runtime) !u8 {
+    const peek = try runtime.stack_peek_pair();
+    if (peek.far == null) return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt) return InternalError.TypeError;
+    const radix = peek.near.UnsignedInt;
+    if (radix < MIN_RADIX or radix > MAX_RADIX) return InternalError.ValueError;
+    return @intCast(u8, radix);
+}
+
+/// Write an integer `obj` to `writer` in `radix`, with a leading `-` if it's
+/// negative. Anything but an integer is a TypeError.
+pub fn write_radix(writer: anytype, obj: Object, radix: u8) !void {
+    switch (obj) {
+        .UnsignedInt => |value| try std.fmt.formatInt(value, radix, .lower, .{}, writer),
+        .SignedInt => |value| try std.fmt.formatInt(value, radix, .lower, .{}, writer),
+        else => return InternalError.TypeError,
+    }
+}
+
+/// Parse `text` as an integer in `radix`: a SignedInt if it starts with `+`
+/// or `-`, as in source, and an UnsignedInt otherwise. Stray characters, of
+/// which the empty String is made, and numbers too big to hold are
+/// ValueErrors.
+pub fn parse_radix(text: []const u8, radix: u8) InternalError!Object {
+    const signed = text.len > 0 and (text[0] == '+' or text[0] == '-');
+    if (signed) {
+        const value = std.fmt.parseInt(isize, text, radix) catch return InternalError.ValueError;
+        return Object{ .SignedInt = value };
+    }
+    const value = std.fmt.parseUnsigned(usize, text, radix) catch return InternalError.ValueError;
+    return Object{ .UnsignedInt = value };
+}
+
+/// Write an integer `obj` to `writer` in decimal, with `separator` between
+/// each group of three digits, counting from the right. Anything but an
+/// integer is a TypeError.
+pub fn write_grouped(alloc: Allocator, writer: anytype, obj: Object, separator: []const u8) !void {
+    var digits = std.ArrayList(u8).init(alloc);
+    defer digits.deinit();
+    try write_radix(digits.writer(), obj, 10);
+
+    const signed = digits.items[0] == '-';
+    if (signed) try writer.writeByte('-');
+    const unsigned = digits.items[@boolToInt(signed)..];
+    for (unsigned) |digit, idx| {
+        if (idx > 0 and (unsigned.len - idx) % 3 == 0) try writer.writeAll(separator);
+        try writer.writeByte(digit);
+    }
+}
+
+/// @TO-STRING-RADIX ( SignedInt|UnsignedInt UnsignedInt <- String )
+///
+/// The integer, written in the radix on top of it: `255 16
+/// @TO-STRING-RADIX` is "ff", and `-5 2 @TO-STRING-RADIX` is "-101".
+pub fn TO_STRING_RADIX(runtime: *Runtime) anyerror!void {
+    const radix = try peek_radix(runtime);
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+    try write_radix(out.writer(), (try runtime.stack_peek_pair()).far.?.*, radix);
+
+    const str = try runtime.get_or_put_string(out.items);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+/// @PARSE-INT-RADIX ( String UnsignedInt <- SignedInt|UnsignedInt )
+///
+/// The String, read as an integer in the radix on top of it (see
+/// parse_radix): `"ff" 16 @PARSE-INT-RADIX` is 255, and `"-101" 2
+/// @PARSE-INT-RADIX` is -5.
+pub fn PARSE_INT_RADIX(runtime: *Runtime) anyerror!void {
+    const radix = try peek_radix(runtime);
+    const text = (try runtime.stack_peek_pair()).far.?;
+    if (text.* != .String) return InternalError.TypeError;
+
+    const parsed = try parse_radix(text.String.value.?, radix);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    switch (parsed) {
+        .SignedInt => |value| try runtime.stack_push_sint(value),
+        .UnsignedInt => |value| try runtime.stack_push_uint(value),
+        else => unreachable,
+    }
+}
+
+/// @GROUP-DIGITS ( SignedInt|UnsignedInt String <- String )
+///
+/// The integer, written in decimal with the String between each group of
+/// three digits: `-1234567 "," @GROUP-DIGITS` is "-1,234,567".
+pub fn GROUP_DIGITS(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String) return InternalError.TypeError;
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+    try write_grouped(runtime.alloc, out.writer(), far.*, peek.near.String.value.?);
+
+    const str = try runtime.get_or_put_string(out.items);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+fn expect_formatted(runtime: *Runtime, source: []const u8, expected: []const u8) !void {
+    try runtime.eval(source);
+    try expectEqual(@as(usize, 1), runtime.stack.depth());
+    var result = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&result);
+    try expectEqualStrings(expected, result.String.value.?);
+}
+
+test "@FORMAT" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_formatted(&runtime, "1 2 3 \"{} + {} = {}\" @FORMAT", "1 + 2 = 3");
+    try expect_formatted(&runtime, "\"{{}}\" @FORMAT", "{}");
+    try expect_formatted(&runtime, "\"pi\" 3.14159 \"{:<4}|{:.2}|\" @FORMAT", "pi  |3.14|");
+    try expect_formatted(&runtime, "42 :x \"{:6}{:>3}\" @FORMAT", "    42 :x");
+    try expect_formatted(&runtime, "\"x\" \"{:*^6}\" @FORMAT", "**x***");
+    try expect_formatted(&runtime, "7 -7 \"{:03} {:04}\" @FORMAT", "007 -007");
+    try expect_formatted(&runtime, "2.5 \"{:07.3}\" @FORMAT", "002.500");
+
+    try expectError(InternalError.ValueError, runtime.eval("\"{\" @FORMAT"));
+    try expectError(InternalError.ValueError, runtime.eval("@DROP \"}\" @FORMAT"));
+    try expectError(InternalError.ValueError, runtime.eval("@DROP \"{0}\" @FORMAT"));
+    try expectError(StackManipulationError.Underflow, runtime.eval("@DROP \"{}\" @FORMAT"));
+    try expectError(InternalError.TypeError, runtime.eval("@DROP \"a\" \"{:.2}\" @FORMAT"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test "radices" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_formatted(&runtime, "255 16 @TO-STRING-RADIX", "ff");
+    try expect_formatted(&runtime, "-5 2 @TO-STRING-RADIX", "-101");
+    try expect_formatted(&runtime, "+0 8 @TO-STRING-RADIX", "0");
+    try expect_formatted(&runtime, "35 36 @TO-STRING-RADIX", "z");
+
+    try runtime.eval("\"FF\" 16 @PARSE-INT-RADIX");
+    try expectEqual(@as(usize, 255), try runtime.stack_pop_value(usize));
+    try runtime.eval("\"-101\" 2 @PARSE-INT-RADIX");
+    try expectEqual(@as(isize, -5), try runtime.stack_pop_value(isize));
+    try runtime.eval("\"+z\" 36 @PARSE-INT-RADIX");
+    try expectEqual(@as(isize, 35), try runtime.stack_pop_value(isize));
+
+    // Round trips hold at the very edges, too.
+    try runtime.stack_push_sint(std.math.minInt(isize));
+    try runtime.eval("7 @TO-STRING-RADIX 7 @PARSE-INT-RADIX");
+    try expectEqual(@as(isize, std.math.minInt(isize)), try runtime.stack_pop_value(isize));
+    try runtime.stack_push_uint(std.math.maxInt(usize));
+    try runtime.eval("3 @TO-STRING-RADIX 3 @PARSE-INT-RADIX");
+    try expectEqual(@as(usize, std.math.maxInt(usize)), try runtime.stack_pop_value(usize));
+
+    try expectError(InternalError.ValueError, runtime.eval("\"12\" 2 @PARSE-INT-RADIX"));
+    try expectError(InternalError.ValueError, runtime.eval("@DROP @DROP \"\" 2 @PARSE-INT-RADIX"));
+    try expectError(InternalError.ValueError, runtime.eval("@DROP @DROP \"0x10\" 16 @PARSE-INT-RADIX"));
+    try expectError(InternalError.ValueError, runtime.eval("@DROP @DROP 10 37 @TO-STRING-RADIX"));
+    try expectError(InternalError.ValueError, runtime.eval("@DROP 1 @TO-STRING-RADIX"));
+    try expectError(InternalError.TypeError, runtime.eval("@DROP @DROP 1.5 2 @TO-STRING-RADIX"));
+    try expectError(InternalError.TypeError, runtime.eval("@DROP @DROP 12 2 @PARSE-INT-RADIX"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test "@GROUP-DIGITS" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_formatted(&runtime, "1234567 \",\" @GROUP-DIGITS", "1,234,567");
+    try expect_formatted(&runtime, "-123456 \"_\" @GROUP-DIGITS", "-123_456");
+    try expect_formatted(&runtime, "999 \",\" @GROUP-DIGITS", "999");
+    try expect_formatted(&runtime, "+0 \",\" @GROUP-DIGITS", "0");
+
+    try expectError(InternalError.TypeError, runtime.eval("1.5 \",\" @GROUP-DIGITS"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,243 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const unicode = @import("./unicode.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Lexer = @import("./lexer.zig").Lexer;
+
+// The canonical layout of gale source, as `gale fmt` writes it. Formatting
+// never changes what source means, only where its whitespace goes:
+//
+// - Words within a line are separated by exactly one space, and lines have
+//   no trailing whitespace.
+// - Lines are indented with tabs, one per level. Levels are taken from the
+//   source as written, whether it was indented with tabs or with spaces (in
+//   which case the narrowest indentation in the file is taken as one level).
+// - Runs of blank lines collapse to one, and there are none at the start
+//   or end of the file, which ends in exactly one newline.
+// - Trailing // comments on consecutive, equally-indented lines are lined up
+//   one space past the longest of those lines.
+//
+// Strings and { block comments } are left exactly as they were written,
+// even when they span lines: reflowing prose is the author's business.
+// Source with an unterminated string or block comment is a ValueError.
+
+/// How wide a level of space indentation is taken to be when nothing in the
+/// file says otherwise.
+pub const DEFAULT_INDENT_WIDTH = 4;
+
+const Line = struct {
+    indent: usize,
+    words: []const []const u8,
+    /// A // comment after the words (or on its own, if there are none).
+    comment: ?[]const u8,
+    /// Whether any of the words (strings or block comments) spans lines,
+    /// which rules the line out of comment alignment.
+    multiline: bool,
+
+    fn is_blank(self: Line) bool {
+        return self.words.len == 0 and self.comment == null;
+    }
+
+    fn width(self: Line) usize {
+        var total: usize = 0;
+        for (self.words) |word, idx| {
+            if (idx > 0) total += 1;
+            total += unicode.grapheme_count(word);
+        }
+        return total;
+    }
+};
+
+/// `source`, formatted. The caller owns the result.
+pub fn format(alloc: Allocator, source: []const u8) ![]u8 {
+    var arena = std.heap.ArenaAllocator.init(alloc);
+    defer arena.deinit();
+
+    const lines = try split_lines(arena.allocator(), source);
+
+    var out = std.ArrayList(u8).init(alloc);
+    errdefer out.deinit();
+
+    var pending_blank = false;
+    var idx: usize = 0;
+    while (idx < lines.len) {
+        const line = lines[idx];
+        if (line.is_blank()) {
+            pending_blank = true;
+            idx += 1;
+            continue;
+        }
+
+        if (pending_blank and out.items.len > 0) try out.append('\n');
+        pending_blank = false;
+
+        if (!aligns(line)) {
+            try write_line(&out, line, null);
+            idx += 1;
+            continue;
+        }
+
+        var end = idx + 1;
+        var column = line.width();
+        while (end < lines.len and aligns(lines[end]) and lines[end].indent == line.indent) : (end += 1) {
+            column = std.math.max(column, lines[end].width());
+        }
+
+        for (lines[idx..end]) |member| try write_line(&out, member, column + 1);
+        idx = end;
+    }
+
+    return out.toOwnedSlice();
+}
+
+/// Whether `source` is already formatted, as `gale fmt --check` asks.
+pub fn is_formatted(alloc: Allocator, source: []const u8) !bool {
+    const formatted = try format(alloc, source);
+    defer alloc.free(formatted);
+    return std.mem.eql(u8, formatted, source);
+}
+
+fn aligns(line: Line) bool {
+    return line.words.len > 0 and line.comment != null and !line.multiline;
+}
+
+/// Write `line`, placing its comment (if any) at `comment_column`, counted
+/// in graphemes from the end of the indentation, or just after the words if
+/// that's null.
+fn write_line(out: *std.ArrayList(u8), line: Line, comment_column: ?usize) !void {
+    try out.appendNTimes('\t', line.indent);
+
+    for (line.words) |word, idx| {
+        if (idx > 0) try out.append(' ');
+        try out.appendSlice(word);
+    }
+
+    if (line.comment) |comment| {
+        if (line.words.len > 0) {
+            const padding = if (comment_column) |column| column - line.width() else 1;
+            try out.appendNTimes(' ', padding);
+        }
+        try out.appendSlice(comment);
+    }
+
+    try out.append('\n');
+}
+
+const RawLine = struct {
+    tabs: usize,
+    spaces: usize,
+    line: Line,
+};
+
+fn split_lines(alloc: Allocator, source: []const u8) ![]const Line {
+    var raw = std.ArrayList(RawLine).init(alloc);
+    var indent_width: ?usize = null;
+
+    var lexer = Lexer.init(source);
+    // Past any byte order mark, which isn't indentation.
+    var line_start: usize = lexer.line_start;
+    var indentation: []const u8 = "";
+    var words = std.ArrayList([]const u8).init(alloc);
+    var comment: ?[]const u8 = null;
+    var multiline = false;
+
+    while (true) {
+        const next = try lexer.next();
+
+        if (next) |token| {
+            if (token.kind != .Newline) {
+                if (words.items.len == 0 and comment == null) indentation = source[line_start..token.offset];
+
+                switch (token.kind) {
+                    .LineComment => comment = std.mem.trimRight(u8, token.raw, " \t\r"),
+                    else => {
+                        if (std.mem.indexOfScalar(u8, token.raw, '\n') != null) multiline = true;
+                        try words.append(token.raw);
+                    },
+                }
+                continue;
+            }
+        }
+
+        const line = Line{ .indent = 0, .words = words.toOwnedSlice(), .comment = comment, .multiline = multiline };
+        const tabs = std.mem.count(u8, indentation, "\t");
+        const spaces = std.mem.count(u8, indentation, " ");
+        if (!line.is_blank() and spaces > 0) {
+            indent_width = if (indent_width) |narrowest| std.math.min(narrowest, spaces) else spaces;
+        }
+        try raw.append(.{ .tabs = tabs, .spaces = spaces, .line = line });
+
+        const newline = next orelse break;
+        line_start = newline.offset + 1;
+        indentation = "";
+        comment = null;
+        multiline = false;
+    }
+
+    const width = indent_width orelse DEFAULT_INDENT_WIDTH;
+    const lines = try alloc.alloc(Line, raw.items.len);
+    for (raw.items) |entry, idx| {
+        lines[idx] = entry.line;
+        lines[idx].indent = if (entry.line.is_blank()) 0 else entry.tabs + entry.spaces / width;
+    }
+
+    return lines;
+}
+
+fn expect_formatted(source: []const u8, expected: []const u8) !void {
+    const formatted = try format(testAllocator, source);
+    defer testAllocator.free(formatted);
+    try expectEqualStrings(expected, formatted);
+
+    // Formatting formatted source changes nothing.
+    try expect(try is_formatted(testAllocator, formatted));
+}
+
+test "format: spacing and blank lines" {
+    try expect_formatted("\n\n1   2\t@DUP   \n\n\n\n\"a  b\"  :sym\n\n", "1 2 @DUP\n\n\"a  b\" :sym\n");
+    try expect_formatted("1\r\n2", "1\n2\n");
+    try expect_formatted("\xEF\xBB\xBF1\r\n2", "1\n2\n");
+    try expect_formatted("", "");
+}
+
+test "format: indentation" {
+    try expect_formatted("$ Track\n  $> num UnsignedInt ;\n    deeper\n;\n", "$ Track\n\t$> num UnsignedInt ;\n\t\tdeeper\n;\n");
+    try expect_formatted("$ Track\n\t\t$> num ;\n", "$ Track\n\t\t$> num ;\n");
+}
+
+test "format: comments" {
+    try expect_formatted(
+        "1 // one\n100 200 // two hundred\n\t3 // three\n// alone\n",
+        "1       // one\n100 200 // two hundred\n\t3 // three\n// alone\n",
+    );
+    try expect_formatted("{ a {nested}\n   block   comment }   1\n", "{ a {nested}\n   block   comment } 1\n");
+    try expect_formatted("{{ docs }}  $ Thing", "{{ docs }} $ Thing\n");
+}
+
+test "format: malformed" {
+    try expectError(InternalError.ValueError, format(testAllocator, "\"open"));
+    try expectError(InternalError.ValueError, format(testAllocator, "{ open"));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,573 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const capabilities = @import("./capabilities.zig");
+const helpers = @import("./helpers.zig");
+const image = @import("./image.zig");
+const io = @import("./io.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const LineCursor = @import("./line_cursor.zig").LineCursor;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// How big of a file @FILE-READ is willing to slurp into memory.
+// TODO: configurable in build.zig
+pub const MAX_FILE_LEN = 64 * 1024 * 1024;
+
+/// Every file system word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@DIR-LIST", DIR_LIST },
+    .{ "@FILE-APPEND", FILE_APPEND },
+    .{ "@FILE-DELETE", FILE_DELETE },
+    .{ "@FILE-EACH-LINE", FILE_EACH_LINE },
+    .{ "@FILE-EXISTS?", FILE_EXISTS },
+    .{ "@FILE-LINES", FILE_LINES },
+    .{ "@FILE-READ", FILE_READ },
+    .{ "@FILE-WRITE", FILE_WRITE },
+    .{ "@LINES-CLOSE", LINES_CLOSE },
+    .{ "@LINES-NEXT", LINES_NEXT },
+    .{ "@LOAD", LOAD },
+    .{ "@SAVE-IMAGE", SAVE_IMAGE },
+};
+
+/// Define every file system word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+// Paths are relative to the process's working directory, and every word
+// leaves the Stack untouched if the file system says no, so that whatever
+// std.fs error came back (FileNotFound, AccessDenied, and so on) can be dealt
+// with by the caller without having lost track of what they asked for.
+//
+// TODO: Opaque contents, once Opaques are something other than a TODO.
+
+/// @FILE-READ ( String <- String )
+///
+/// The entire contents of the file at the given path. Files bigger than
+/// MAX_FILE_LEN are a LimitExceeded. Needs the Read capability.
+pub fn FILE_READ(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Read);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const path = try peek_string(runtime);
+    const contents = std.fs.cwd().readFileAlloc(runtime.alloc, path, MAX_FILE_LEN) catch |err| switch (err) {
+        error.FileTooBig => return InternalError.LimitExceeded,
+        else => return err,
+    };
+    const str = runtime.new_string_owned(contents) catch |err| {
+        runtime.alloc.free(contents);
+        return err;
+    };
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str);
+}
+
+/// @FILE-WRITE ( String String <- )
+///
+/// Write the contents (further down the Stack) to the file at the path (on
+/// top), creating it if need be and replacing whatever was there if not.
+/// Needs the Write capability.
+pub fn FILE_WRITE(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Write);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const args = try peek_path_and_contents(runtime);
+    try std.fs.cwd().writeFile(args.path, args.contents);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @FILE-APPEND ( String String <- )
+///
+/// As @FILE-WRITE, but tacks the contents onto the end of any existing file
+/// rather than replacing it. Needs the Write capability.
+pub fn FILE_APPEND(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Write);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const args = try peek_path_and_contents(runtime);
+    const file = try std.fs.cwd().createFile(args.path, .{ .truncate = false });
+    defer file.close();
+    try file.seekFromEnd(0);
+    try file.writeAll(args.contents);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @FILE-EXISTS? ( String <- Boolean )
+///
+/// Whether anything (file, directory, or otherwise) exists at the given
+/// path. Needs the Read capability.
+pub fn FILE_EXISTS(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Read);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const path = try peek_string(runtime);
+    const exists = if (std.fs.cwd().access(path, .{})) true else |err| switch (err) {
+        error.FileNotFound => false,
+        else => return err,
+    };
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_bool(exists);
+}
+
+/// @DIR-LIST ( String <- Array )
+///
+/// The names (not full paths) of everything in the directory at the given
+/// path, sorted bytewise so that the same directory always lists the same
+/// way. Needs the Read capability.
+pub fn DIR_LIST(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Read);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const path = try peek_string(runtime);
+    var dir = try std.fs.cwd().openIterableDir(path, .{});
+    defer dir.close();
+
+    const names = try runtime.new_array(0);
+    errdefer runtime.release_array(names);
+
+    var entries = dir.iterate();
+    while (try entries.next()) |entry| {
+        const name = (try runtime.get_or_put_string(entry.name)).value_ptr;
+        try names.value.?.append(try (Object{ .String = name }).ref());
+    }
+    std.sort.sort(Object, names.value.?.items, {}, name_less_than);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_array(names);
+}
+
+fn name_less_than(_: void, lhs: Object, rhs: Object) bool {
+    return std.mem.lessThan(u8, lhs.String.value.?, rhs.String.value.?);
+}
+
+/// @FILE-DELETE ( String <- )
+///
+/// Delete the file at the given path. Directories are left alone (and are
+/// an IsDir error). Needs the Write capability.
+pub fn FILE_DELETE(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Write);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    try std.fs.cwd().deleteFile(try peek_string(runtime));
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @FILE-EACH-LINE ( String Word <- )
+///
+/// Run the Word once for each line of the file at the given path, with the
+/// line (as with @LINES-NEXT) pushed beforehand, reading no more of the file
+/// than it takes to do so. Neither the path nor the Word are on the Stack
+/// while the Word runs. Should the Word fail, the error is passed along, the
+/// file is closed, and whatever the Word did to the Stack stays done. Needs
+/// the Read capability.
+pub fn FILE_EACH_LINE(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Read);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Word or far.* != .String) return InternalError.TypeError;
+
+    var cursor = LineCursor.init(runtime.alloc, try std.fs.cwd().openFile(far.String.value.?, .{}));
+    defer cursor.deinit();
+
+    // Our reference keeps the Word alive until we're done with it.
+    var word = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&word);
+    try runtime.stack_wrangle(.DropTopObject);
+
+    while (try cursor.next()) |line| {
+        const str = try runtime.get_or_put_string(line);
+        try runtime.stack_push_string(str.value_ptr);
+        try runtime.run_word(word.Word);
+    }
+}
+
+/// @FILE-LINES ( String <- UnsignedInt )
+///
+/// Open the file at the given path for reading a line at a time (see
+/// @LINES-NEXT), producing the cursor's ID. Cursors stay open until
+/// @LINES-CLOSE, or until the Runtime goes away. Needs the Read capability.
+pub fn FILE_LINES(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Read);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    var cursor = LineCursor.init(runtime.alloc, try std.fs.cwd().openFile(try peek_string(runtime), .{}));
+    errdefer cursor.deinit();
+
+    const id = runtime.next_line_cursor_id;
+    try runtime.line_cursors.put(id, cursor);
+    runtime.next_line_cursor_id += 1;
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_uint(id);
+}
+
+/// @LINES-NEXT ( UnsignedInt <- String Boolean )
+///
+/// The cursor's next line (without its line ending) and true, or just false
+/// once the file has run dry. Lines longer than io.MAX_LINE_LEN are a
+/// LimitExceeded. Needs the Read capability.
+pub fn LINES_NEXT(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Read);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const cursor = try peek_line_cursor(runtime);
+    const line = (try cursor.next()) orelse {
+        _ = try runtime.stack_pop();
+        return runtime.stack_push_bool(false);
+    };
+
+    const str = try runtime.get_or_put_string(line);
+    _ = try runtime.stack_pop();
+    try runtime.stack_push_string(str.value_ptr);
+    try runtime.stack_push_bool(true);
+}
+
+/// @LINES-CLOSE ( UnsignedInt <- )
+///
+/// Close a cursor, after which its ID means nothing. Needs the Read
+/// capability.
+pub fn LINES_CLOSE(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Read);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const cursor = try peek_line_cursor(runtime);
+    cursor.deinit();
+    _ = runtime.line_cursors.orderedRemove((try runtime.stack_pop()).UnsignedInt);
+}
+
+/// @LOAD ( String <- )
+///
+/// Evaluate the file at the given path into this Runtime, as though it had
+/// been typed in right here, so that whatever it defines stays defined. The
+/// path is gone from the Stack by the time the file starts. Paths which don't
+/// say where they are (`strings`, rather than `./strings.gale`) are looked
+/// for along the library path: see Runtime.resolve_source_path. Loads can
+/// nest up to Runtime.MAX_LOAD_DEPTH deep, and when one fails,
+/// Runtime.load_trace says where (see Runtime.eval_source). Needs the Read
+/// capability, unless the file is bundled (see Runtime.add_bundled_file).
+pub fn LOAD(runtime: *Runtime) anyerror!void {
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const operand = try peek_string(runtime);
+    try runtime.require_source_access(operand);
+
+    // Our copy of the path outlives the String, which may well be freed by
+    // the time the file's done.
+    const path = try runtime.resolve_source_path(operand);
+    defer runtime.alloc.free(path);
+
+    // Bundled sources live as long as the Runtime.
+    const read = if (runtime.bundled.get(path) == null) std.fs.cwd().readFileAlloc(runtime.alloc, path, MAX_FILE_LEN) catch |err| switch (err) {
+        error.FileTooBig => return InternalError.LimitExceeded,
+        else => return err,
+    } else null;
+    defer if (read) |owned| runtime.alloc.free(owned);
+    const contents = runtime.bundled.get(path) orelse read.?;
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.eval_source(path, contents);
+}
+
+/// @SAVE-IMAGE ( String <- )
+///
+/// Save this Runtime (see image.zig), as it stands once the path is gone from
+/// the Stack, as an image at the given path, creating it if need be and
+/// replacing whatever was there if not. Hosts pick images back up with
+/// image.load, between evals (the CLI's --image=PATH, say): there's no word
+/// for it, since nothing can be running while the dictionary changes
+/// underneath. Needs the Write capability.
+pub fn SAVE_IMAGE(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Write);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+    _ = try peek_string(runtime);
+
+    var path = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&path);
+
+    save_image_to(runtime, path.String.value.?) catch |err| {
+        var restored = try path.ref();
+        runtime.stack_push_object(restored) catch {
+            runtime.release_heaped_object_reference(&restored);
+        };
+        return err;
+    };
+}
+
+fn save_image_to(runtime: *Runtime, path: []const u8) !void {
+    var bytes = std.ArrayList(u8).init(runtime.alloc);
+    defer bytes.deinit();
+    try image.save(runtime, bytes.writer());
+    try std.fs.cwd().writeFile(path, bytes.items);
+}
+
+/// Look up the cursor whose ID is on top of the Stack. Unknown (or closed)
+/// IDs are a ValueError.
+fn peek_line_cursor(runtime: *Runtime) !*LineCursor {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+    return runtime.line_cursors.getPtr(peeked.UnsignedInt) orelse return InternalError.ValueError;
+}
+
+fn peek_string(runtime: *Runtime) ![]const u8 {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+    return peeked.String.value.?;
+}
+
+const PathAndContents = struct {
+    path: []const u8,
+    contents: []const u8,
+};
+
+fn peek_path_and_contents(runtime: *Runtime) !PathAndContents {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .String) return InternalError.TypeError;
+
+    return .{
+        .path = peek.near.String.value.?,
+        .contents = far.String.value.?,
+    };
+}
+
+fn test_push_string(runtime: *Runtime, str: []const u8) !void {
+    try runtime.stack_push_string((try runtime.get_or_put_string(str)).value_ptr);
+}
+
+fn test_pop_string(runtime: *Runtime, expected: []const u8) !void {
+    var popped = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&popped);
+    try expectEqualStrings(expected, popped.String.value.?);
+}
+
+test "file system words" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(testAllocator, ".");
+    defer testAllocator.free(root);
+    const path = try std.fs.path.join(testAllocator, &.{ root, "notes.txt" });
+    defer testAllocator.free(path);
+
+    var granted = capabilities.NONE;
+    granted.insert(.Read);
+    granted.insert(.Write);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"hello\"");
+    try test_push_string(&runtime, path);
+    try runtime.eval("@FILE-WRITE \"-world\"");
+    try test_push_string(&runtime, path);
+    try runtime.eval("@FILE-APPEND");
+
+    try test_push_string(&runtime, path);
+    try runtime.eval("@FILE-READ");
+    try test_pop_string(&runtime, "hello-world");
+
+    try test_push_string(&runtime, path);
+    try runtime.eval("@FILE-EXISTS?");
+    try expect(try runtime.stack_pop_value(bool));
+
+    try test_push_string(&runtime, root);
+    try runtime.eval("@DIR-LIST");
+    var listing = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&listing);
+    try expectEqual(@as(usize, 1), listing.Array.value.?.items.len);
+    try expectEqualStrings("notes.txt", listing.Array.value.?.items[0].String.value.?);
+
+    try test_push_string(&runtime, path);
+    try runtime.eval("@FILE-DELETE");
+    try test_push_string(&runtime, path);
+    try runtime.eval("@FILE-EXISTS?");
+    try expect(!(try runtime.stack_pop_value(bool)));
+
+    // Failures leave the path right where it was.
+    try test_push_string(&runtime, path);
+    try expectError(error.FileNotFound, runtime.eval("@FILE-READ"));
+    try test_pop_string(&runtime, path);
+}
+
+test "line-at-a-time words" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile("log.txt", "one\ntwo\r\nthree\n");
+    const path = try tmp.dir.realpathAlloc(testAllocator, "log.txt");
+    defer testAllocator.free(path);
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    var granted = capabilities.NONE;
+    granted.insert(.Read);
+    var runtime = try Runtime.init_with_options(testAllocator, .{
+        .capabilities = granted,
+        .stdout = io.buffer_output(&out),
+    });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try test_push_string(&runtime, path);
+    try runtime.eval("&@PRINTLN @FILE-EACH-LINE");
+    try expectEqualStrings("one\ntwo\nthree\n", out.items);
+
+    try test_push_string(&runtime, path);
+    try runtime.eval("@FILE-LINES");
+    const cursor = (try runtime.stack_peek()).UnsignedInt;
+    try runtime.eval("@LINES-NEXT");
+    try expect(try runtime.stack_pop_value(bool));
+    try test_pop_string(&runtime, "one");
+
+    try runtime.stack_push_uint(cursor);
+    try runtime.eval("@LINES-CLOSE");
+    try runtime.stack_push_uint(cursor);
+    try expectError(InternalError.ValueError, runtime.eval("@LINES-NEXT"));
+    _ = try runtime.stack_pop();
+}
+
+test "LOAD" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(testAllocator, ".");
+    defer testAllocator.free(root);
+    const inner = try std.fs.path.join(testAllocator, &.{ root, "inner.gale" });
+    defer testAllocator.free(inner);
+    const outer = try std.fs.path.join(testAllocator, &.{ root, "outer.gale" });
+    defer testAllocator.free(outer);
+
+    var granted = capabilities.NONE;
+    granted.insert(.Read);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try tmp.dir.writeFile("inner.gale", "2 @DUP");
+    const outer_source = try std.fmt.allocPrint(testAllocator, "1\n\"{s}\" @LOAD", .{inner});
+    defer testAllocator.free(outer_source);
+    try tmp.dir.writeFile("outer.gale", outer_source);
+
+    try test_push_string(&runtime, outer);
+    try runtime.eval("@LOAD");
+    try expectEqual(@as(usize, 3), runtime.stack.depth());
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+
+    // Failures are traced back through each file, innermost first.
+    try tmp.dir.writeFile("inner.gale", "@NOPE");
+    try test_push_string(&runtime, outer);
+    try expectError(InternalError.UnknownWord, runtime.eval("@LOAD"));
+
+    const trace = runtime.load_trace.items;
+    try expectEqual(@as(usize, 2), trace.len);
+    try expectEqualStrings(inner, trace[0].path);
+    try expectEqual(@as(usize, 1), trace[0].line);
+    try expectEqualStrings(outer, trace[1].path);
+    try expectEqual(@as(usize, 2), trace[1].line);
+    try expectEqual(inner.len + 4, trace[1].column);
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+
+    // As is loading oneself forever.
+    const recursive_source = try std.fmt.allocPrint(testAllocator, "\"{s}\" @LOAD", .{inner});
+    defer testAllocator.free(recursive_source);
+    try tmp.dir.writeFile("inner.gale", recursive_source);
+    try test_push_string(&runtime, inner);
+    try expectError(InternalError.LimitExceeded, runtime.eval("@LOAD"));
+    try expectEqual(Runtime.MAX_LOAD_DEPTH, runtime.load_trace.items.len);
+}
+
+test "LOAD: library path" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(testAllocator, ".");
+    defer testAllocator.free(root);
+
+    var granted = capabilities.NONE;
+    granted.insert(.Read);
+    var runtime = try Runtime.init_with_options(testAllocator, .{
+        .capabilities = granted,
+        .library_path = &.{root},
+    });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try tmp.dir.writeFile("seven.gale", "7");
+    try runtime.eval("\"seven\" @LOAD");
+    try expectEqual(@as(usize, 7), try runtime.stack_pop_value(usize));
+
+    // Names not found anywhere stay put, with everywhere tried to show for it.
+    try runtime.eval("\"eight\"");
+    try expectError(error.FileNotFound, runtime.eval("@LOAD"));
+    try expectEqual(@as(usize, 2), runtime.resolution_attempts.items.len);
+    var missing = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&missing);
+}
+
+test "SAVE-IMAGE" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(testAllocator, ".");
+    defer testAllocator.free(root);
+    const path = try std.fs.path.join(testAllocator, &.{ root, "workspace.image" });
+    defer testAllocator.free(path);
+
+    var granted = capabilities.NONE;
+    granted.insert(.Write);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("5 @LIT :five @DEFINE 1");
+    try test_push_string(&runtime, path);
+    try runtime.eval("@SAVE-IMAGE");
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+
+    const bytes = try tmp.dir.readFileAlloc(testAllocator, "workspace.image", MAX_FILE_LEN);
+    defer testAllocator.free(bytes);
+    var resumed = try Runtime.init(testAllocator);
+    defer resumed.deinit_guard_for_empty_stack();
+    try image.load(&resumed, bytes);
+    try resumed.eval("five");
+    try expectEqual(@as(usize, 5), try resumed.stack_pop_value(usize));
+    try expectEqual(@as(usize, 1), try resumed.stack_pop_value(usize));
+}
+
+test "file system words need capabilities" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"notes.txt\"");
+    try expectError(InternalError.CapabilityDenied, runtime.eval("@FILE-READ"));
+    try expectError(InternalError.CapabilityDenied, runtime.eval("@FILE-DELETE"));
+    try expectError(InternalError.CapabilityDenied, runtime.eval("@SAVE-IMAGE"));
+    try test_pop_string(&runtime, "notes.txt");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
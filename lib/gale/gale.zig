@@ -11,6 +11,103 @@
 // OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
 // PERFORMANCE OF THIS SOFTWARE.
 
-pub const InternalError = @import("./internal_error.zig");
+// This file is the public face of libgale: anything embedding gale should be
+// able to get by with only what's exported here, and anything exported here
+// should be considered stable-ish API (insofar as anything in a pre-1.0
+// language is stable). Reaching past this file into the individual modules
+// works, but you're on your own when things move around.
+
+const _stack = @import("./stack.zig");
+const _word = @import("./word.zig");
+const _vocabulary = @import("./vocabulary.zig");
+
+// The Runtime is the entrypoint to just about everything.
 pub const Runtime = @import("./runtime.zig").Runtime;
+
+// What's compiled in. See features.zig for how a build chooses.
+pub const features = @import("./features.zig");
+pub const Features = features.Features;
+
+// Things that live on (or get moved on and off of) the Stack, and ways to
+// translate between them and plain Zig values.
+pub const conversion = @import("./conversion.zig");
+pub const Object = @import("./object.zig").Object;
+pub const Stack = _stack.Stack;
 pub const Types = @import("./types.zig");
+
+// Words, and the machinery needed to define them from the host.
+pub const HostClosureFn = _word.HostClosureFn;
+pub const PrimitiveWord = _word.PrimitiveWord;
+pub const Word = _word.Word;
+pub const WordSignature = @import("./word_signature.zig").WordSignature;
+pub const Shape = @import("./shape.zig").Shape;
+
+// Instrumentation.
+pub const Coverage = @import("./coverage.zig").Coverage;
+pub const Debugger = @import("./debugger.zig").Debugger;
+pub const event_log = @import("./event_log.zig");
+pub const EventLog = event_log.EventLog;
+pub const Observer = @import("./observer.zig").Observer;
+pub const ObserverEvent = @import("./observer.zig").Event;
+pub const recording = @import("./recording.zig");
+pub const Stats = @import("./stats.zig").Stats;
+pub const Tracer = @import("./tracer.zig").Tracer;
+pub const Visualizer = @import("./visualizer.zig").Visualizer;
+
+// Maps and Sets.
+pub const Map = @import("./map.zig").Map;
+pub const Set = @import("./set.zig").Set;
+
+// Tasks, channels, and cells.
+pub const Cell = @import("./cell.zig").Cell;
+pub const Channel = @import("./channel.zig").Channel;
+pub const Task = @import("./task.zig").Task;
+
+// Console I/O.
+pub const io = @import("./io.zig");
+pub const InputHandle = io.InputHandle;
+pub const OutputHandle = io.OutputHandle;
+
+// Driving a Runtime from another process, a line of JSON at a time, as
+// notebooks do.
+pub const notebook = @import("./notebook.zig");
+
+// Wire formats, for talking to other runtimes (or anything else) without
+// gale source in the middle.
+pub const msgpack = @import("./msgpack.zig");
+
+// Saving a Runtime's state, to pick up where it left off later.
+pub const image = @import("./image.zig");
+
+// Sandboxing.
+pub const capabilities = @import("./capabilities.zig");
+pub const Capability = capabilities.Capability;
+pub const Capabilities = capabilities.Capabilities;
+
+// Vocabularies.
+pub const LazyVocabulary = _vocabulary.LazyVocabulary;
+pub const VOCABULARY_SEPARATOR = _vocabulary.VOCABULARY_SEPARATOR;
+pub const library_path = @import("./library_path.zig");
+pub const package = @import("./package.zig");
+pub const bundle = @import("./bundle.zig");
+
+// Source input, for those building their own frontends.
+pub const unicode = @import("./unicode.zig");
+pub const lexer = @import("./lexer.zig");
+pub const hashbang = @import("./hashbang.zig");
+pub const source_encoding = @import("./source_encoding.zig");
+pub const conditional = @import("./conditional.zig");
+pub const pipeline = @import("./pipeline.zig");
+pub const docstrings = @import("./docstrings.zig");
+pub const formatter = @import("./formatter.zig");
+pub const linter = @import("./linter.zig");
+pub const tags = @import("./tags.zig");
+pub const semantic_tokens = @import("./semantic_tokens.zig");
+pub const snippet = @import("./snippet.zig");
+pub const project = @import("./project.zig");
+pub const test_runner = @import("./test_runner.zig");
+pub const ParsedWord = @import("./parsed_word.zig").ParsedWord;
+
+// Errors which can bubble out of the above.
+pub const InternalError = @import("./internal_error.zig").InternalError;
+pub const StackManipulationError = _stack.StackManipulationError;
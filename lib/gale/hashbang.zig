@@ -0,0 +1,113 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const helpers = @import("./helpers.zig");
+
+// Hashbang lines (`#!/usr/bin/env -S gale run --flag=debug`), which let a
+// script be run as a program of its own. Only a source's very first line can
+// be one. The Lexer takes it for a LineComment, and eval_source skips over
+// it, so that it means nothing to gale itself; `gale run` reads the flags
+// it recognizes (see FLAG_PREFIXES) back out of it.
+
+pub const PREFIX = "#!";
+
+/// Arguments in a hashbang which `gale run` honors even when it's been run
+/// directly, rather than by way of the hashbang: those saying which library
+/// path and conditional flags (see conditional.zig) the script expects.
+/// Capabilities aren't among them, as a script can't be allowed to grant
+/// itself whatever it likes.
+pub const FLAG_PREFIXES = [_][]const u8{ "--lib=", "--flag=" };
+
+pub const Hashbang = struct {
+    const Self = @This();
+
+    /// Everything after PREFIX, up to the end of the line.
+    line: []const u8,
+    /// The program the line names, as the kernel would run it.
+    interpreter: []const u8,
+    /// Whatever follows the interpreter, leading whitespace aside.
+    rest: []const u8,
+
+    /// The arguments after the interpreter, split on whitespace as `env -S`
+    /// would split them (quoting aside).
+    pub fn arguments(self: Self) std.mem.TokenIterator(u8) {
+        return std.mem.tokenize(u8, self.rest, " \t");
+    }
+
+    /// The next of the arguments which is one of FLAG_PREFIXES, if any.
+    pub fn next_gale_flag(args: *std.mem.TokenIterator(u8)) ?[]const u8 {
+        while (args.next()) |arg| {
+            for (FLAG_PREFIXES) |prefix| {
+                if (std.mem.startsWith(u8, arg, prefix)) return arg;
+            }
+        }
+        return null;
+    }
+};
+
+/// `source`'s hashbang, if it starts with one.
+pub fn parse(source: []const u8) ?Hashbang {
+    const len = line_len(source);
+    if (len == 0) return null;
+
+    const line = std.mem.trimRight(u8, source[PREFIX.len..len], "\r");
+    const trimmed = std.mem.trimLeft(u8, line, " \t");
+    const interpreter_end = std.mem.indexOfAny(u8, trimmed, " \t") orelse trimmed.len;
+
+    return Hashbang{
+        .line = line,
+        .interpreter = trimmed[0..interpreter_end],
+        .rest = std.mem.trim(u8, trimmed[interpreter_end..], " \t"),
+    };
+}
+
+/// How long `source`'s hashbang is, up to (but not including) the line break
+/// after it, or 0 if it has none.
+pub fn line_len(source: []const u8) usize {
+    if (!std.mem.startsWith(u8, source, PREFIX)) return 0;
+    return std.mem.indexOfScalar(u8, source, helpers.CHAR_NEWLINE) orelse source.len;
+}
+
+test "parse" {
+    const hashbang = parse("#!/usr/bin/env -S gale run --flag=debug  --allow-read --lib=vendor\r\n1 2").?;
+    try expectEqualStrings("/usr/bin/env -S gale run --flag=debug  --allow-read --lib=vendor", hashbang.line);
+    try expectEqualStrings("/usr/bin/env", hashbang.interpreter);
+
+    var args = hashbang.arguments();
+    const expected = [_][]const u8{ "-S", "gale", "run", "--flag=debug", "--allow-read", "--lib=vendor" };
+    for (expected) |want| try expectEqualStrings(want, args.next().?);
+    try expect(args.next() == null);
+
+    var flags = hashbang.arguments();
+    try expectEqualStrings("--flag=debug", Hashbang.next_gale_flag(&flags).?);
+    try expectEqualStrings("--lib=vendor", Hashbang.next_gale_flag(&flags).?);
+    try expect(Hashbang.next_gale_flag(&flags) == null);
+
+    const bare = parse("#! /usr/local/bin/gale").?;
+    try expectEqualStrings("/usr/local/bin/gale", bare.interpreter);
+    try expectEqualStrings("", bare.rest);
+
+    try expect(parse("1 2\n#!/usr/bin/env gale") == null);
+    try expectEqual(@as(usize, 0), line_len("#if-flag:debug"));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
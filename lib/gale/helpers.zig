@@ -14,6 +14,16 @@
 const std = @import("std");
 const expect = std.testing.expect;
 
+const builtin = @import("builtin");
+
+/// Whether we're being built for a target with an operating system under us.
+/// The nucleus (Runtime, Stack, Objects, Words) needs nothing more than an
+/// Allocator, and so builds for freestanding targets (see `zig build
+/// freestanding`), but anything touching files, clocks, environment
+/// variables, and the like must be gated behind this at comptime, so that
+/// freestanding builds never even analyze it.
+pub const HOSTED = builtin.os.tag != .freestanding;
+
 // Just silly stuff that's nice to access by name
 pub const CHAR_AMPER = '&';
 pub const CHAR_COLON = ':';
@@ -56,8 +66,9 @@ test "bool_from_human_str" {
 
 /// Pluck common boolean representations from an environment variable `name` as
 /// an actual boolean. 1, true, TRUE, yes, and YES are accepted truthy values,
-/// anything else is false.
+/// anything else is false. Always false on freestanding targets.
 pub fn getenv_boolean(name: []const u8) bool {
+    if (comptime !HOSTED) return false;
     return bool_from_human_str(std.os.getenv(name) orelse "");
 }
 
@@ -0,0 +1,275 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+// Just enough HTTP/1.1 to be a client for the likes of @HTTP-GET: one
+// request per connection (we always ask for `Connection: close`, and read
+// until the server hangs up), no TLS, no redirects, no keepalive. Anything
+// fancier is what @RUN and curl are for.
+
+/// How big of a response (headers and all) we're willing to buffer.
+// TODO: configurable in build.zig
+pub const MAX_RESPONSE_LEN = 64 * 1024 * 1024;
+
+/// The port http URLs without one of their own go to.
+pub const DEFAULT_PORT = 80;
+
+pub const Url = struct {
+    /// Without the brackets IPv6 literals are written between in URLs.
+    host: []const u8,
+    port: u16,
+    /// Always starts with a slash, and includes any query string.
+    path: []const u8,
+};
+
+/// Pick apart an `http://host[:port][/path]` URL, where the host may be an
+/// IPv6 literal between brackets (`http://[::1]:8080/`). Anything else
+/// (including, for now, https) is Unimplemented, and anything which isn't a
+/// URL at all is a ValueError.
+pub fn parse_url(url: []const u8) InternalError!Url {
+    const scheme_end = std.mem.indexOf(u8, url, "://") orelse return InternalError.ValueError;
+    if (!std.ascii.eqlIgnoreCase(url[0..scheme_end], "http")) return InternalError.Unimplemented;
+
+    const rest = url[scheme_end + 3 ..];
+    const path_start = std.mem.indexOfAny(u8, rest, "/?") orelse rest.len;
+    const authority = rest[0..path_start];
+    if (authority.len == 0) return InternalError.ValueError;
+
+    var parsed = Url{
+        .host = authority,
+        .port = DEFAULT_PORT,
+        .path = if (path_start == rest.len) "/" else rest[path_start..],
+    };
+
+    // Where the host ends, and any port starts: IPv6 literals are full of
+    // colons of their own, hence the brackets.
+    var host_end = authority.len;
+    if (authority[0] == '[') {
+        const close = std.mem.indexOfScalar(u8, authority, ']') orelse return InternalError.ValueError;
+        if (close == 1) return InternalError.ValueError;
+        parsed.host = authority[1..close];
+        host_end = close + 1;
+        if (host_end < authority.len and authority[host_end] != ':') return InternalError.ValueError;
+    } else if (std.mem.lastIndexOfScalar(u8, authority, ':')) |colon| {
+        parsed.host = authority[0..colon];
+        host_end = colon;
+    }
+
+    if (host_end < authority.len) {
+        parsed.port = std.fmt.parseInt(u16, authority[host_end + 1 ..], 10) catch return InternalError.ValueError;
+    }
+
+    return parsed;
+}
+
+pub fn write_request(writer: anytype, method: []const u8, url: Url, body: []const u8) !void {
+    try writer.print("{s} {s} HTTP/1.1\r\n", .{ method, url.path });
+    // Servers hosting several sites need the port as well as the host,
+    // unless it's the default (RFC 9110, section 7.2).
+    try writer.writeAll("Host: ");
+    if (std.mem.indexOfScalar(u8, url.host, ':') != null) {
+        try writer.print("[{s}]", .{url.host});
+    } else {
+        try writer.writeAll(url.host);
+    }
+    if (url.port != DEFAULT_PORT) try writer.print(":{d}", .{url.port});
+    try writer.writeAll("\r\n");
+    try writer.writeAll("Connection: close\r\n");
+    try writer.print("Content-Length: {d}\r\n\r\n", .{body.len});
+    try writer.writeAll(body);
+}
+
+pub const Header = struct {
+    /// Lowercased, since header names are case-insensitive.
+    name: []const u8,
+    value: []const u8,
+};
+
+pub const Response = struct {
+    const Self = @This();
+
+    /// Everything the server sent, which everything else points into.
+    raw: []u8,
+    /// When the body had to be decoded (say, because it was chunked),
+    /// this is where the decoded copy lives.
+    decoded: ?[]u8,
+    status: u16,
+    headers: std.ArrayList(Header),
+    body: []const u8,
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        self.headers.deinit();
+        if (self.decoded) |decoded| alloc.free(decoded);
+        alloc.free(self.raw);
+    }
+
+    pub fn header(self: *const Self, name: []const u8) ?[]const u8 {
+        for (self.headers.items) |it| {
+            if (std.ascii.eqlIgnoreCase(it.name, name)) return it.value;
+        }
+
+        return null;
+    }
+};
+
+/// Read a response from `reader` until EOF, then parse it. Malformed
+/// responses are a ValueError.
+pub fn read_response(alloc: Allocator, reader: anytype) !Response {
+    const raw = reader.readAllAlloc(alloc, MAX_RESPONSE_LEN) catch |err| switch (err) {
+        error.StreamTooLong => return InternalError.LimitExceeded,
+        else => return err,
+    };
+    errdefer alloc.free(raw);
+
+    return try parse_response(alloc, raw);
+}
+
+/// Parse a full response, taking ownership of `raw` on success.
+pub fn parse_response(alloc: Allocator, raw: []u8) !Response {
+    const head_end = std.mem.indexOf(u8, raw, "\r\n\r\n") orelse return InternalError.ValueError;
+    var lines = std.mem.split(u8, raw[0..head_end], "\r\n");
+
+    // HTTP/1.1 200 OK
+    const status_line = lines.next() orelse return InternalError.ValueError;
+    var status_parts = std.mem.tokenize(u8, status_line, " ");
+    const version = status_parts.next() orelse return InternalError.ValueError;
+    if (!std.mem.startsWith(u8, version, "HTTP/")) return InternalError.ValueError;
+    const status_text = status_parts.next() orelse return InternalError.ValueError;
+    const status = std.fmt.parseInt(u16, status_text, 10) catch return InternalError.ValueError;
+
+    var response = Response{
+        .raw = raw,
+        .decoded = null,
+        .status = status,
+        .headers = std.ArrayList(Header).init(alloc),
+        .body = raw[head_end + 4 ..],
+    };
+    errdefer response.headers.deinit();
+
+    while (lines.next()) |line| {
+        const colon = std.mem.indexOfScalar(u8, line, ':') orelse return InternalError.ValueError;
+        // The split iterator hands out const slices, but they're windows
+        // into `raw`, which is ours to scribble on.
+        const name = raw[@ptrToInt(line.ptr) - @ptrToInt(raw.ptr) ..][0..colon];
+        _ = std.ascii.lowerString(name, name);
+        try response.headers.append(.{
+            .name = name,
+            .value = std.mem.trim(u8, line[colon + 1 ..], " \t"),
+        });
+    }
+
+    if (response.header("transfer-encoding")) |encoding| {
+        if (std.ascii.eqlIgnoreCase(encoding, "chunked")) {
+            response.decoded = try dechunk(alloc, response.body);
+            response.body = response.decoded.?;
+        }
+    } else if (response.header("content-length")) |length_text| {
+        const length = std.fmt.parseInt(usize, length_text, 10) catch return InternalError.ValueError;
+        if (length > response.body.len) return InternalError.ValueError;
+        response.body = response.body[0..length];
+    }
+
+    return response;
+}
+
+fn dechunk(alloc: Allocator, encoded: []const u8) ![]u8 {
+    var decoded = std.ArrayList(u8).init(alloc);
+    errdefer decoded.deinit();
+
+    var rest = encoded;
+    while (true) {
+        const size_end = std.mem.indexOf(u8, rest, "\r\n") orelse return InternalError.ValueError;
+        // Chunk extensions (;name=value) are allowed, and ignored.
+        const size_text = std.mem.sliceTo(rest[0..size_end], ';');
+        const size = std.fmt.parseInt(usize, std.mem.trim(u8, size_text, " "), 16) catch return InternalError.ValueError;
+        rest = rest[size_end + 2 ..];
+
+        if (size == 0) break;
+        if (rest.len < size + 2) return InternalError.ValueError;
+        try decoded.appendSlice(rest[0..size]);
+        rest = rest[size + 2 ..];
+    }
+
+    return decoded.toOwnedSlice();
+}
+
+test "parse_url" {
+    const full = try parse_url("http://example.com:8080/a/b?c=d");
+    try expectEqualStrings("example.com", full.host);
+    try expectEqual(@as(u16, 8080), full.port);
+    try expectEqualStrings("/a/b?c=d", full.path);
+
+    const bare = try parse_url("HTTP://example.com");
+    try expectEqual(@as(u16, 80), bare.port);
+    try expectEqualStrings("/", bare.path);
+
+    try expectError(InternalError.Unimplemented, parse_url("https://example.com"));
+    try expectError(InternalError.ValueError, parse_url("example.com"));
+    try expectError(InternalError.ValueError, parse_url("http://example.com:http/"));
+
+    const v6 = try parse_url("http://[::1]:8080/");
+    try expectEqualStrings("::1", v6.host);
+    try expectEqual(@as(u16, 8080), v6.port);
+    try expectEqual(@as(u16, 80), (try parse_url("http://[fe80::1]")).port);
+    try expectError(InternalError.ValueError, parse_url("http://[::1/"));
+    try expectError(InternalError.ValueError, parse_url("http://[]/"));
+    try expectError(InternalError.ValueError, parse_url("http://[::1]8080/"));
+}
+
+test "write_request: Host" {
+    const Case = struct { url: []const u8, host: []const u8 };
+    const cases = [_]Case{
+        .{ .url = "http://example.com/", .host = "Host: example.com\r\n" },
+        .{ .url = "http://example.com:8080/", .host = "Host: example.com:8080\r\n" },
+        .{ .url = "http://[::1]:8080/", .host = "Host: [::1]:8080\r\n" },
+        .{ .url = "http://[::1]/", .host = "Host: [::1]\r\n" },
+    };
+
+    for (cases) |case| {
+        var written = std.ArrayList(u8).init(std.testing.allocator);
+        defer written.deinit();
+        try write_request(written.writer(), "GET", try parse_url(case.url), "");
+        try expect(std.mem.indexOf(u8, written.items, case.host) != null);
+    }
+}
+
+test "parse_response" {
+    const alloc = std.testing.allocator;
+
+    var plain = try parse_response(alloc, try alloc.dupe(u8, "HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: 4\r\n\r\nnopeEXTRA"));
+    defer plain.deinit(alloc);
+    try expectEqual(@as(u16, 404), plain.status);
+    try expectEqualStrings("text/plain", plain.header("Content-Type").?);
+    try expectEqualStrings("content-type", plain.headers.items[0].name);
+    try expectEqualStrings("nope", plain.body);
+
+    var chunked = try parse_response(alloc, try alloc.dupe(u8, "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2;x=y\r\nde\r\n0\r\n\r\n"));
+    defer chunked.deinit(alloc);
+    try expectEqualStrings("abcde", chunked.body);
+
+    const garbage = try alloc.dupe(u8, "SMTP is not HTTP\r\n\r\n");
+    defer alloc.free(garbage);
+    try expectError(InternalError.ValueError, parse_response(alloc, garbage));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
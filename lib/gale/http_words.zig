@@ -0,0 +1,187 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const capabilities = @import("./capabilities.zig");
+const conversion = @import("./conversion.zig");
+const helpers = @import("./helpers.zig");
+const http = @import("./http.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+/// Every HTTP word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@HTTP-GET", HTTP_GET },
+    .{ "@HTTP-REQUEST", HTTP_REQUEST },
+};
+
+/// Define every HTTP word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+// Both words hand back a Map of:
+//
+// - `status`: the response's status code, as an UnsignedInt
+// - `headers`: a Map of the response's headers, by lowercased name (should a
+//   header appear more than once, the last one wins)
+// - `body`: the response's body (de-chunked, if need be), as a String
+//
+// Responses are what they are: a 404 is not an error, just a Map with a
+// `status` of 404. Not being able to connect at all, or getting something
+// back which isn't HTTP, is. Only http:// URLs are supported (see http.zig),
+// and, as with the TCP words, the whole Runtime waits on the response.
+
+/// @HTTP-GET ( String <- Map )
+///
+/// GET the given URL. Needs the Net capability.
+pub fn HTTP_GET(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Net);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+
+    const result = try fetch(runtime, "GET", peeked.String.value.?, "");
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_map(result);
+}
+
+/// @HTTP-REQUEST ( String String String <- Map )
+///
+/// Send the body (bottom) to the URL (top) with the given method (middle),
+/// say `"{}" "POST" "http://localhost:8080/things"`. Needs the Net
+/// capability.
+pub fn HTTP_REQUEST(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Net);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peek = try runtime.stack_peek_trio();
+    const method = peek.far orelse return StackManipulationError.Underflow;
+    const body = peek.farther orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or method.* != .String or body.* != .String) return InternalError.TypeError;
+
+    const result = try fetch(runtime, method.String.value.?, peek.near.String.value.?, body.String.value.?);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_map(result);
+}
+
+fn fetch(runtime: *Runtime, method: []const u8, url_text: []const u8, body: []const u8) !*Types.HeapedMap {
+    const url = try http.parse_url(url_text);
+
+    const stream = try std.net.tcpConnectToHost(runtime.alloc, url.host, url.port);
+    defer stream.close();
+
+    var buffered = std.io.bufferedWriter(stream.writer());
+    try http.write_request(buffered.writer(), method, url, body);
+    try buffered.flush();
+
+    var response = try http.read_response(runtime.alloc, stream.reader());
+    defer response.deinit(runtime.alloc);
+
+    const result = try runtime.new_map();
+    errdefer runtime.release_map(result);
+    try conversion.put_value(runtime, result, "status", response.status);
+    try conversion.put_value(runtime, result, "body", response.body);
+
+    const headers = try runtime.new_map();
+    {
+        errdefer runtime.release_map(headers);
+        for (response.headers.items) |it| try conversion.put_value(runtime, headers, it.name, it.value);
+        try result.value.?.put(runtime.alloc, "headers", try (Object{ .Map = headers }).ref());
+    }
+
+    return result;
+}
+
+const TestServer = struct {
+    const Self = @This();
+
+    listener: std.net.StreamServer,
+    /// What the last request looked like, up to the end of its headers.
+    request_head: [256]u8 = undefined,
+    request_head_len: usize = 0,
+
+    fn serve_once(self: *Self, response: []const u8) void {
+        const conn = self.listener.accept() catch return;
+        defer conn.stream.close();
+
+        const reader = conn.stream.reader();
+        while (self.request_head_len < self.request_head.len) {
+            self.request_head[self.request_head_len] = reader.readByte() catch return;
+            self.request_head_len += 1;
+            if (std.mem.endsWith(u8, self.request_head[0..self.request_head_len], "\r\n\r\n")) break;
+        }
+
+        conn.stream.writer().writeAll(response) catch return;
+    }
+};
+
+test "@HTTP-GET" {
+    var server = TestServer{ .listener = std.net.StreamServer.init(.{ .reuse_address = true }) };
+    defer server.listener.deinit();
+    try server.listener.listen(try std.net.Address.parseIp("127.0.0.1", 0));
+
+    const thread = try std.Thread.spawn(.{}, TestServer.serve_once, .{
+        &server,
+        "HTTP/1.1 201 Created\r\nX-Thing: yes\r\nContent-Length: 2\r\n\r\nhi",
+    });
+
+    var granted = capabilities.NONE;
+    granted.insert(.Net);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    var url_buf: [64]u8 = undefined;
+    const url = try std.fmt.bufPrint(&url_buf, "\"http://127.0.0.1:{d}/hello\"", .{server.listener.listen_address.getPort()});
+    try runtime.eval(url);
+    try runtime.eval("@HTTP-GET");
+    thread.join();
+
+    try expectEqualStrings("GET /hello HTTP/1.1\r\n", server.request_head[0.."GET /hello HTTP/1.1\r\n".len]);
+
+    var result = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&result);
+    const map = &result.Map.value.?;
+    try expectEqual(@as(usize, 201), map.get("status").?.UnsignedInt);
+    try expectEqualStrings("hi", map.get("body").?.String.value.?);
+    try expectEqualStrings("yes", map.get("headers").?.Map.value.?.get("x-thing").?.String.value.?);
+}
+
+test "@HTTP-GET only speaks http://" {
+    var granted = capabilities.NONE;
+    granted.insert(.Net);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"https://example.com\"");
+    try expectError(InternalError.Unimplemented, runtime.eval("@HTTP-GET"));
+    var url = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&url);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
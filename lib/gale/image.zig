@@ -0,0 +1,662 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const nucleus_words = @import("./nucleus_words.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const Types = @import("./types.zig");
+const Word = @import("./word.zig").Word;
+
+// Images: a Runtime's dictionary, docstrings, search order, imported modules,
+// and Stack, saved to be picked back up later (see `load`) in another, the
+// way a Smalltalk or Forth workspace is. Everything else (limits, tasks,
+// observers, and so on) belongs to whoever's hosting the Runtime, and isn't
+// saved.
+//
+// Primitives and host closures can't be written down, so they're saved by
+// the name they were first defined under, and found under that same name
+// (as the oldest such definition) wherever the image is loaded: the loading
+// Runtime needs the same primitive groups, and the same host words, as the
+// one that saved it. Everything else is saved in full, shared words once,
+// though Arrays and Maps are saved by value, and so come back unshared.
+// Signatures aren't saved, but literals' are what @LIT would give them.
+// Cells, Channels, Opaques, and Sets have no saved form, and are a
+// TypeError.
+//
+// The format is this file's own, and only ever read back by it: MAGIC, then
+// the words (each after anything it refers to, so that it can refer back by
+// its index), then the other sections in the order listed above. Integers
+// are little-endian, and lengths are u32s.
+
+pub const MAGIC = "GALEIMG1";
+
+/// How deeply words and Objects may nest, either way, before we give up with
+/// a LimitExceeded rather than risk running out of (Zig) stack.
+// TODO: configurable in build.zig
+pub const MAX_NESTING = 512;
+
+const WordKind = enum(u8) {
+    Native,
+    Compound,
+    HeapLit,
+    CallSite,
+};
+
+const ObjectKind = enum(u8) {
+    Array,
+    Boolean,
+    Float,
+    Map,
+    SignedInt,
+    String,
+    Symbol,
+    UnsignedInt,
+    Word,
+};
+
+/// Write everything `rt` would need to pick up where it left off as an
+/// image.
+pub fn save(rt: *Runtime, writer: anytype) !void {
+    var saver = Saver{
+        .rt = rt,
+        .ids = std.AutoHashMap(*Types.HeapedWord, u32).init(rt.alloc),
+        .natives = std.AutoHashMap(*Types.HeapedWord, []const u8).init(rt.alloc),
+        .words = std.ArrayList(u8).init(rt.alloc),
+    };
+    defer saver.deinit();
+    try saver.name_natives();
+
+    // Every word has to be found before any can be counted, so the rest
+    // goes to one side first.
+    var rest = std.ArrayList(u8).init(rt.alloc);
+    defer rest.deinit();
+    try saver.write_rest(rest.writer());
+
+    try writer.writeAll(MAGIC);
+    try write_int(writer, saver.ids.count());
+    try writer.writeAll(saver.words.items);
+    try writer.writeAll(rest.items);
+}
+
+const Saver = struct {
+    const Self = @This();
+
+    rt: *Runtime,
+    /// Each word's index in the image.
+    ids: std.AutoHashMap(*Types.HeapedWord, u32),
+    /// What each primitive and host closure is saved as. See name_natives.
+    natives: std.AutoHashMap(*Types.HeapedWord, []const u8),
+    /// Every word, as written so far.
+    words: std.ArrayList(u8),
+
+    fn deinit(self: *Self) void {
+        self.ids.deinit();
+        self.natives.deinit();
+        self.words.deinit();
+    }
+
+    /// Name every primitive and host closure after whichever name it's the
+    /// oldest such definition of, here or in our base, as `load` will look
+    /// for it.
+    fn name_natives(self: *Self) !void {
+        var current: ?*const Runtime = self.rt;
+        while (current) |rt| : (current = rt.base) {
+            var dictionary_iter = rt.dictionary.iterator();
+            while (dictionary_iter.next()) |entry| {
+                const native = first_native(entry.value_ptr.items()) orelse continue;
+                const named = try self.natives.getOrPut(native);
+                if (!named.found_existing) named.value_ptr.* = entry.key_ptr.*.value.?;
+            }
+        }
+    }
+
+    fn write_rest(self: *Self, writer: anytype) !void {
+        const rt = self.rt;
+
+        try write_int(writer, rt.dictionary.count());
+        var dictionary_iter = rt.dictionary.iterator();
+        while (dictionary_iter.next()) |entry| {
+            try write_bytes(writer, entry.key_ptr.*.value.?);
+            const words = entry.value_ptr.items();
+            try write_int(writer, words.len);
+            for (words) |heaped| try write_int(writer, try self.id_of(heaped, 0));
+        }
+
+        try write_int(writer, rt.docs.count());
+        var docs_iter = rt.docs.iterator();
+        while (docs_iter.next()) |entry| {
+            try write_int(writer, try self.id_of(entry.key_ptr.*, 0));
+            try write_bytes(writer, entry.value_ptr.*);
+        }
+
+        try write_int(writer, rt.search_order.items.len);
+        for (rt.search_order.items) |entry| {
+            try write_bytes(writer, entry.vocabulary);
+            try writer.writeByte(@boolToInt(entry.prefix != null));
+            if (entry.prefix) |prefix| try write_bytes(writer, prefix);
+        }
+
+        // Modules still being imported (which is to say, saved from within
+        // one) aren't modules yet.
+        var loaded: usize = 0;
+        for (rt.modules.values()) |entry| loaded += @boolToInt(!entry.loading);
+        try write_int(writer, loaded);
+        var modules_iter = rt.modules.iterator();
+        while (modules_iter.next()) |entry| {
+            if (entry.value_ptr.loading) continue;
+            try write_bytes(writer, entry.key_ptr.*);
+            try write_bytes(writer, entry.value_ptr.vocabulary);
+        }
+
+        const depth = rt.stack.depth();
+        try write_int(writer, depth);
+        var idx: usize = 0;
+        while (idx < depth) : (idx += 1) {
+            try self.write_object(writer, rt.stack.nth_from_top(depth - 1 - idx).?.*, 0);
+        }
+    }
+
+    /// `heaped`'s index, writing it (and anything it refers to) first if
+    /// this is the first we've seen of it.
+    fn id_of(self: *Self, heaped: *Types.HeapedWord, depth: usize) anyerror!u32 {
+        if (self.ids.get(heaped)) |id| return id;
+        if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+
+        // Anything this refers to gets written as it's found, so this word
+        // has to wait its turn to the side.
+        var record = std.ArrayList(u8).init(self.rt.alloc);
+        defer record.deinit();
+        const writer = record.writer();
+
+        const inner = heaped.value.?;
+        const kind: WordKind = switch (inner.impl) {
+            .Primitive, .HostClosure => .Native,
+            .Compound => .Compound,
+            .HeapLit => .HeapLit,
+            .CallSite => .CallSite,
+        };
+        try writer.writeByte(@enumToInt(kind));
+        try writer.writeByte(flags_byte(inner.flags));
+        try writer.writeAll(&inner.tags);
+
+        switch (inner.impl) {
+            .Primitive, .HostClosure => {
+                // Never defined under any name, and so with none to be found
+                // by.
+                const name = self.natives.get(heaped) orelse return InternalError.TypeError;
+                try write_bytes(writer, name);
+            },
+            .Compound => |compound| {
+                try write_int(writer, compound.len);
+                for (compound) |member| try write_int(writer, try self.id_of(member, depth + 1));
+            },
+            .HeapLit => |obj| try self.write_object(writer, obj.*, depth + 1),
+            .CallSite => |site| try write_bytes(writer, site.name.value.?),
+        }
+
+        const id = std.math.cast(u32, self.ids.count()) orelse return InternalError.LimitExceeded;
+        try self.ids.put(heaped, id);
+        try self.words.appendSlice(record.items);
+        return id;
+    }
+
+    fn write_object(self: *Self, writer: anytype, obj: Object, depth: usize) anyerror!void {
+        if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+
+        switch (obj) {
+            .Cell, .Channel, .Opaque, .Set => return InternalError.TypeError,
+            else => try writer.writeByte(@enumToInt(std.meta.stringToEnum(ObjectKind, @tagName(obj)).?)),
+        }
+
+        switch (obj) {
+            .Array => |array| {
+                const items = array.value.?.items;
+                try write_int(writer, items.len);
+                for (items) |member| try self.write_object(writer, member, depth + 1);
+            },
+            .Boolean => |val| try writer.writeByte(@boolToInt(val)),
+            .Float => |val| try writer.writeIntLittle(u64, @bitCast(u64, val)),
+            .Map => |map| {
+                const keys = map.value.?.keys();
+                try write_int(writer, keys.len);
+                for (keys) |key| {
+                    try write_bytes(writer, key);
+                    try self.write_object(writer, map.value.?.get(key).?, depth + 1);
+                }
+            },
+            .SignedInt => |val| try writer.writeIntLittle(i64, @intCast(i64, val)),
+            .String => |str| try write_bytes(writer, str.value.?),
+            .Symbol => |sym| try write_bytes(writer, sym.value.?),
+            .UnsignedInt => |val| try writer.writeIntLittle(u64, @intCast(u64, val)),
+            .Word => |heaped| try write_int(writer, try self.id_of(heaped, depth + 1)),
+            .Cell, .Channel, .Opaque, .Set => unreachable,
+        }
+    }
+};
+
+fn flags_byte(flags: Word.Flags) u8 {
+    return @as(u8, @boolToInt(flags.hidden)) |
+        @as(u8, @boolToInt(flags.data)) << 1 |
+        @as(u8, @boolToInt(flags.mutable)) << 2;
+}
+
+fn write_int(writer: anytype, val: usize) !void {
+    const narrowed = std.math.cast(u32, val) orelse return InternalError.LimitExceeded;
+    try writer.writeIntLittle(u32, narrowed);
+}
+
+fn write_bytes(writer: anytype, bytes: []const u8) !void {
+    try write_int(writer, bytes.len);
+    try writer.writeAll(bytes);
+}
+
+/// The oldest primitive or host closure among `words`, if any.
+fn first_native(words: []*Types.HeapedWord) ?*Types.HeapedWord {
+    for (words) |heaped| {
+        switch (heaped.value.?.impl) {
+            .Primitive, .HostClosure => return heaped,
+            else => {},
+        }
+    }
+
+    return null;
+}
+
+/// Pick up where the image in `bytes` left off: replace each name it defines
+/// (see Runtime.replace_definitions) with its words, document them as they
+/// were, replace the search order with its own, add any modules not already
+/// imported here, and finally replace the Stack with its own. Names the
+/// image doesn't define are left be. Malformed images are a ValueError, and
+/// images wanting host words this Runtime doesn't have are an UnknownWord:
+/// either way, nothing has changed yet. Words can't be running while the
+/// dictionary shifts under them, so this is also a ValueError mid-eval: it's
+/// for hosts to call between evals.
+pub fn load(rt: *Runtime, bytes: []const u8) !void {
+    if (rt.frames.items.len > 0) return InternalError.ValueError;
+    if (!std.mem.startsWith(u8, bytes, MAGIC)) return InternalError.ValueError;
+
+    var loader = Loader{
+        .rt = rt,
+        .bytes = bytes,
+        .pos = MAGIC.len,
+        .arena = std.heap.ArenaAllocator.init(rt.alloc),
+        .words = std.ArrayList(*Types.HeapedWord).init(rt.alloc),
+        .stack = std.ArrayList(Object).init(rt.alloc),
+    };
+    defer loader.deinit();
+
+    try loader.read();
+    try loader.apply();
+}
+
+const Definition = struct {
+    name: []const u8,
+    words: []*Types.HeapedWord,
+};
+
+const Doc = struct {
+    word: *Types.HeapedWord,
+    text: []const u8,
+};
+
+const SearchEntry = struct {
+    vocabulary: []const u8,
+    prefix: ?[]const u8,
+};
+
+const Module = struct {
+    path: []const u8,
+    vocabulary: []const u8,
+};
+
+const Loader = struct {
+    const Self = @This();
+
+    rt: *Runtime,
+    bytes: []const u8,
+    pos: usize,
+    /// For everything below, bar the words and Stack, which hold references
+    /// needing releasing.
+    arena: std.heap.ArenaAllocator,
+    /// By index, each holding a reference for as long as we do.
+    words: std.ArrayList(*Types.HeapedWord),
+    definitions: []Definition = undefined,
+    docs: []Doc = undefined,
+    search_order: []SearchEntry = undefined,
+    modules: []Module = undefined,
+    /// Bottom first, each holding a reference.
+    stack: std.ArrayList(Object),
+
+    fn deinit(self: *Self) void {
+        for (self.stack.items) |*obj| self.rt.release_heaped_object_reference(obj);
+        self.stack.deinit();
+        for (self.words.items) |heaped| release_word(self.rt, heaped);
+        self.words.deinit();
+        self.arena.deinit();
+    }
+
+    fn read(self: *Self) !void {
+        const arena = self.arena.allocator();
+
+        var count = try self.int();
+        while (count > 0) : (count -= 1) try self.read_word();
+
+        self.definitions = try arena.alloc(Definition, try self.count_of(8));
+        for (self.definitions) |*definition| {
+            definition.name = try self.read_bytes();
+            definition.words = try arena.alloc(*Types.HeapedWord, try self.count_of(4));
+            for (definition.words) |*heaped| heaped.* = try self.word_at(try self.int());
+        }
+
+        self.docs = try arena.alloc(Doc, try self.count_of(8));
+        for (self.docs) |*doc| {
+            doc.word = try self.word_at(try self.int());
+            doc.text = try self.read_bytes();
+        }
+
+        self.search_order = try arena.alloc(SearchEntry, try self.count_of(5));
+        for (self.search_order) |*entry| {
+            entry.vocabulary = try self.read_bytes();
+            entry.prefix = if ((try self.byte()) != 0) try self.read_bytes() else null;
+        }
+
+        self.modules = try arena.alloc(Module, try self.count_of(8));
+        for (self.modules) |*entry| {
+            entry.path = try self.read_bytes();
+            entry.vocabulary = try self.read_bytes();
+        }
+
+        count = try self.count_of(1);
+        try self.stack.ensureTotalCapacity(count);
+        while (count > 0) : (count -= 1) {
+            self.stack.appendAssumeCapacity(try (try self.object(0)).ref());
+        }
+
+        if (self.pos != self.bytes.len) return InternalError.ValueError;
+    }
+
+    fn apply(self: *Self) !void {
+        const rt = self.rt;
+
+        for (self.definitions) |definition| try rt.replace_definitions(definition.name, definition.words);
+        for (self.docs) |doc| try rt.document_word(doc.word, doc.text);
+
+        for (rt.search_order.items) |*entry| entry.deinit(rt.alloc);
+        rt.search_order.clearRetainingCapacity();
+        rt.dictionary_generation +%= 1;
+        for (self.search_order) |entry| try rt.use_vocabulary(entry.vocabulary, entry.prefix);
+
+        for (self.modules) |entry| {
+            if (rt.modules.contains(entry.path)) continue;
+            const path = try rt.alloc.dupe(u8, entry.path);
+            errdefer rt.alloc.free(path);
+            const vocab_name = try rt.alloc.dupe(u8, entry.vocabulary);
+            errdefer rt.alloc.free(vocab_name);
+            try rt.modules.put(path, .{ .vocabulary = vocab_name, .loading = false });
+        }
+
+        while (rt.stack.depth() > 0) {
+            var dropped = try rt.stack_pop();
+            rt.release_heaped_object_reference(&dropped);
+        }
+        for (self.stack.items) |obj| try rt.stack_push_object(obj);
+    }
+
+    fn read_word(self: *Self) !void {
+        const kind = std.meta.intToEnum(WordKind, try self.byte()) catch return InternalError.ValueError;
+        const flags = try self.byte();
+        const tags = try self.take(Word.TAG_ARRAY_SIZE);
+
+        const heaped = switch (kind) {
+            .Native => try self.native(try self.read_bytes()),
+            .Compound => try self.compound(),
+            .HeapLit => try self.heaplit(),
+            .CallSite => try self.rt.word_from_call_site(try self.read_bytes()),
+        };
+        try heaped.increment();
+        errdefer release_word(self.rt, heaped);
+
+        // Host words are this Runtime's own, and keep their own flags.
+        if (kind != .Native) {
+            heaped.value.?.flags = .{
+                .hidden = flags & 1 != 0,
+                .data = flags & 2 != 0,
+                .mutable = flags & 4 != 0,
+            };
+            std.mem.copy(u8, &heaped.value.?.tags, tags);
+        }
+        try self.words.append(heaped);
+    }
+
+    fn native(self: *Self, name: []const u8) !*Types.HeapedWord {
+        // Which may well be in a lazy vocabulary nothing's asked for yet.
+        _ = try self.rt.find_word(name);
+
+        var current: ?*const Runtime = self.rt;
+        while (current) |rt| : (current = rt.base) {
+            const identifier = rt.symbols.get(name) orelse continue;
+            const words = rt.dictionary.getPtr(identifier) orelse continue;
+            if (first_native(words.items())) |heaped| return heaped;
+        }
+
+        return InternalError.UnknownWord;
+    }
+
+    fn compound(self: *Self) !*Types.HeapedWord {
+        const inner = try self.rt.alloc.alloc(*Types.HeapedWord, try self.count_of(4));
+        var filled: usize = 0;
+        errdefer {
+            for (inner[0..filled]) |member| release_word(self.rt, member);
+            self.rt.alloc.free(inner);
+        }
+
+        while (filled < inner.len) : (filled += 1) {
+            const member = try self.word_at(try self.int());
+            try member.increment();
+            inner[filled] = member;
+        }
+
+        return try self.rt.word_from_compound_impl(inner, null);
+    }
+
+    fn heaplit(self: *Self) !*Types.HeapedWord {
+        var obj = try (try self.object(0)).ref();
+        errdefer self.rt.release_heaped_object_reference(&obj);
+
+        const banished = try self.rt.alloc.create(Object);
+        errdefer self.rt.alloc.destroy(banished);
+        banished.* = obj;
+
+        return try self.rt.word_from_heaplit_impl(banished, nucleus_words.literal_signature(self.rt, obj));
+    }
+
+    fn object(self: *Self, depth: usize) anyerror!Object {
+        if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+
+        const kind = std.meta.intToEnum(ObjectKind, try self.byte()) catch return InternalError.ValueError;
+        switch (kind) {
+            .Array => {
+                const len = try self.count_of(1);
+                const target = try self.rt.new_array(len);
+                errdefer self.rt.release_array(target);
+
+                var idx: usize = 0;
+                while (idx < len) : (idx += 1) {
+                    const member = try (try self.object(depth + 1)).ref();
+                    target.value.?.appendAssumeCapacity(member);
+                }
+
+                return Object{ .Array = target };
+            },
+            .Boolean => return Object{ .Boolean = (try self.byte()) != 0 },
+            .Float => return Object{ .Float = @bitCast(f64, try self.int_of(u64)) },
+            .Map => {
+                const target = try self.rt.new_map();
+                errdefer self.rt.release_map(target);
+
+                var count = try self.count_of(5);
+                while (count > 0) : (count -= 1) {
+                    const key = try self.read_bytes();
+                    var member = try (try self.object(depth + 1)).ref();
+                    errdefer self.rt.release_heaped_object_reference(&member);
+                    try target.value.?.put(self.rt.alloc, key, member);
+                }
+
+                return Object{ .Map = target };
+            },
+            .SignedInt => return Object{ .SignedInt = std.math.cast(isize, try self.int_of(i64)) orelse return InternalError.ValueError },
+            .String => return Object{ .String = (try self.rt.get_or_put_string(try self.read_bytes())).value_ptr },
+            .Symbol => return Object{ .Symbol = (try self.rt.get_or_put_symbol(try self.read_bytes())).value_ptr },
+            .UnsignedInt => return Object{ .UnsignedInt = std.math.cast(usize, try self.int_of(u64)) orelse return InternalError.ValueError },
+            .Word => return Object{ .Word = try self.word_at(try self.int()) },
+        }
+    }
+
+    /// Only words read before this one can be referred to.
+    fn word_at(self: *Self, idx: usize) !*Types.HeapedWord {
+        if (idx >= self.words.items.len) return InternalError.ValueError;
+        return self.words.items[idx];
+    }
+
+    /// A count of things at least `min_len` bytes long each, which stops a
+    /// bogus count from asking for a huge allocation up front.
+    fn count_of(self: *Self, min_len: usize) !usize {
+        const count = try self.int();
+        if (count > (self.bytes.len - self.pos) / min_len) return InternalError.ValueError;
+        return count;
+    }
+
+    fn read_bytes(self: *Self) ![]const u8 {
+        return try self.take(try self.int());
+    }
+
+    fn int(self: *Self) !usize {
+        return try self.int_of(u32);
+    }
+
+    fn int_of(self: *Self, comptime T: type) !T {
+        const raw = try self.take(@sizeOf(T));
+        return std.mem.readIntLittle(T, raw[0..@sizeOf(T)]);
+    }
+
+    fn byte(self: *Self) !u8 {
+        return (try self.take(1))[0];
+    }
+
+    fn take(self: *Self, len: usize) ![]const u8 {
+        if (len > self.bytes.len - self.pos) return InternalError.ValueError;
+        defer self.pos += len;
+        return self.bytes[self.pos .. self.pos + len];
+    }
+};
+
+fn release_word(rt: *Runtime, heaped: *Types.HeapedWord) void {
+    var obj = Object{ .Word = heaped };
+    rt.release_heaped_object_reference(&obj);
+}
+
+test "save and load" {
+    var image = std.ArrayList(u8).init(testAllocator);
+    defer image.deinit();
+
+    {
+        var rt = try Runtime.init(testAllocator);
+        defer rt.deinit();
+
+        try rt.eval("2 @LIT :two @DEFINE &two &two @COMPOSE :twice-two @DEFINE &later :deferred @DEFINE");
+        try rt.document_word((try rt.find_word("two")).?, "Two, always.");
+        try rt.use_vocabulary("shapes", "s");
+        try rt.eval("1 -2 1.5 \"hi\" :sym");
+        try rt.stack_push_raw_word((try rt.find_word("two")).?);
+        try save(&rt, image.writer());
+    }
+
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+    try rt.stack_push_uint(99);
+
+    try load(&rt, image.items);
+    var word = try rt.stack_pop();
+    defer rt.release_heaped_object_reference(&word);
+    // Still the very word that's defined as `two`.
+    try expect(word.Word == (try rt.find_word("two")).?);
+    try expectEqualStrings("Two, always.", rt.doc_of(word.Word).?);
+    var sym = try rt.stack_pop();
+    defer rt.release_heaped_object_reference(&sym);
+    try expectEqualStrings("sym", sym.Symbol.value.?);
+    var str = try rt.stack_pop();
+    defer rt.release_heaped_object_reference(&str);
+    try expectEqualStrings("hi", str.String.value.?);
+    try expectEqual(@as(f64, 1.5), try rt.stack_pop_value(f64));
+    try expectEqual(@as(isize, -2), try rt.stack_pop_value(isize));
+    try expectEqual(@as(usize, 1), try rt.stack_pop_value(usize));
+    // The 99 went with the rest of the old Stack.
+    try expectEqual(@as(usize, 0), rt.stack.depth());
+
+    try rt.eval("twice-two @DROP two @DUP @DROP");
+    try expectEqual(@as(usize, 2), try rt.stack_pop_value(usize));
+    try expectEqual(@as(usize, 2), try rt.stack_pop_value(usize));
+    try expectEqual(@as(usize, 2), rt.search_order.items.len);
+    try expectEqualStrings("s", rt.search_order.items[1].prefix.?);
+
+    // Call sites resolve afresh.
+    try rt.eval("3 @LIT :later @DEFINE deferred");
+    try expectEqual(@as(usize, 3), try rt.stack_pop_value(usize));
+}
+
+test "load: malformed, and mid-eval" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try expectError(InternalError.ValueError, load(&rt, "nope"));
+    try expectError(InternalError.ValueError, load(&rt, MAGIC));
+    try expectError(InternalError.ValueError, load(&rt, MAGIC ++ "\x01\x00\x00\x00\x09"));
+
+    var image = std.ArrayList(u8).init(testAllocator);
+    defer image.deinit();
+    try save(&rt, image.writer());
+    try load(&rt, image.items);
+    try expectError(InternalError.ValueError, load(&rt, image.items[0 .. image.items.len - 1]));
+
+    try rt.frames.append(.{ .word = (try rt.find_word("@DUP")).? });
+    defer _ = rt.frames.pop();
+    try expectError(InternalError.ValueError, load(&rt, image.items));
+}
+
+test "save: no saved form" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var image = std.ArrayList(u8).init(testAllocator);
+    defer image.deinit();
+
+    var cell = Object{ .Cell = try rt.new_cell(.{ .UnsignedInt = 1 }) };
+    try rt.stack_push_object(cell);
+    try expectError(InternalError.TypeError, save(&rt, image.writer()));
+    cell = try rt.stack_pop();
+    rt.release_heaped_object_reference(&cell);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
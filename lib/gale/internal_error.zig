@@ -14,14 +14,40 @@
 const std = @import("std");
 
 pub const InternalError = error{
+    /// Raised by @ASSERT and friends. See test_runner.zig.
+    AssertionFailed,
     AttemptedDestructionOfPopulousRc,
+    AttemptedMutationOfFrozenRuntime,
     AttemptedResurrectionOfExhaustedRc, // me too, buddy
     BoundedShapeWithoutBoundsCheckingWord,
+    CapabilityDenied,
+    ChannelClosed,
+    /// A module (see module.zig) imported itself, however indirectly.
+    CircularImport,
+    Deadlock,
     EmptyWord,
+    /// Two demands on a vocabulary's version (see version.zig) can't both
+    /// be met. See Runtime.version_conflict for which.
+    IncompatibleVersion,
+    Interrupted,
     InvalidWordName,
+    LimitExceeded,
+    /// Something's held more references than a refcount (see rc.zig) can
+    /// count.
+    RcOverflow,
+    /// A replayed run (see recording.zig) asked the world for something
+    /// other than what the recorded run asked for next.
+    ReplayDiverged,
     TypeError,
     Unimplemented,
+    UnknownWord,
+    /// A source wasn't UTF-8. See source_encoding.zig.
+    UnsupportedEncoding,
     ValueError, // TODO: rename???
+    /// Raised by words which can't make progress until some other Task
+    /// does. Within a Task, the word is retried after yielding; anywhere
+    /// else, it's a bug in the word.
+    WouldBlock,
 };
 
 test {
@@ -0,0 +1,281 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const io = @import("./io.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const Stats = @import("./stats.zig").Stats;
+const Types = @import("./types.zig");
+
+/// Every introspection word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@SEE", SEE },
+    .{ "@HELP", HELP },
+    .{ "@INTROSPECT-STATS", INTROSPECT_STATS },
+};
+
+/// Define every introspection word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @SEE ( Word|Symbol -> )
+///
+/// Print the definition of a word (as given by reference, say `&@DUP`) or of
+/// whatever's defined under a Symbol's name (say `:@DUP`) to stdout. See
+/// write_definition for the format. Unknown names are an UnknownWord, and
+/// leave the stack untouched.
+pub fn SEE(runtime: *Runtime) anyerror!void {
+    const target = try target_of(runtime, try runtime.stack_peek());
+    try write_definition(runtime, runtime.stdout.writer(), target.name, target.word);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @HELP ( Word|Symbol -> )
+///
+/// Print a word's name and docstring (see docstrings.zig), found as @SEE
+/// finds it, to stdout. Words without one say as much.
+pub fn HELP(runtime: *Runtime) anyerror!void {
+    const target = try target_of(runtime, try runtime.stack_peek());
+    try write_help(runtime, runtime.stdout.writer(), target.name, target.word);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @INTROSPECT-STATS ( <- Map )
+///
+/// Push a Map of the Runtime's counters (see stats.zig), keyed by their
+/// names with dashes for underscores: `words-executed`, `pushes`, and so on.
+/// Allocations are only counted when the Runtime was built with
+/// Options.count_allocations, and read as zero otherwise.
+pub fn INTROSPECT_STATS(runtime: *Runtime) anyerror!void {
+    const stats = runtime.stats();
+    const result = try runtime.new_map();
+    errdefer runtime.release_map(result);
+    inline for (std.meta.fields(Stats)) |field| {
+        try result.value.?.put(runtime.alloc, comptime dashed(field.name), .{ .UnsignedInt = @field(stats, field.name) });
+    }
+    try runtime.stack_push_map(result);
+}
+
+fn dashed(comptime name: []const u8) []const u8 {
+    comptime {
+        var buf: [name.len]u8 = undefined;
+        for (name) |chr, idx| buf[idx] = if (chr == '_') '-' else chr;
+        const final = buf;
+        return &final;
+    }
+}
+
+const Target = struct {
+    name: []const u8,
+    word: *Types.HeapedWord,
+};
+
+fn target_of(runtime: *Runtime, top: *const Object) !Target {
+    return switch (top.*) {
+        .Symbol => |sym| try named(runtime, sym.value.?),
+        .Word => |word| blk: {
+            const inner = word.value orelse return InternalError.EmptyWord;
+            if (inner.impl == .CallSite) break :blk try named(runtime, inner.impl.CallSite.name.value.?);
+
+            break :blk Target{ .name = runtime.name_of_word(word) orelse "<anonymous>", .word = word };
+        },
+        else => InternalError.TypeError,
+    };
+}
+
+fn named(runtime: *Runtime, name: []const u8) !Target {
+    const word = (try runtime.find_word(name)) orelse return InternalError.UnknownWord;
+    return Target{ .name = name, .word = word };
+}
+
+/// `word`, much as Forth's SEE would show it: a line with `name` and the
+/// word's signature (when it has one), then its docstring (see
+/// Runtime.document_word) and body, each indented four spaces. Compound
+/// words' bodies are their inner words, by name where they have one;
+/// literals' are the value they push (and, for @CONSTANT and @VALUE's, which
+/// of the two it is); everything else is implemented in Zig, and says as
+/// much.
+pub fn write_definition(rt: *Runtime, writer: anytype, name: []const u8, word: *Types.HeapedWord) !void {
+    const inner = word.value orelse return InternalError.EmptyWord;
+
+    try writer.writeAll(name);
+    if (inner.signature) |signature| switch (signature) {
+        .Declared, .Inferred => |sig| try writer.print(" {}", .{sig.*}),
+    };
+    try writer.writeByte('\n');
+
+    if (rt.doc_of(word)) |doc| try write_doc(writer, doc);
+
+    try writer.writeAll("    ");
+    switch (inner.impl) {
+        .Compound => |compound| for (compound) |iword, idx| {
+            if (idx > 0) try writer.writeByte(' ');
+            try write_word(rt, writer, iword);
+        },
+        .HeapLit => |obj| if (inner.flags.data) {
+            try writer.writeAll(if (inner.flags.mutable) "value " else "constant ");
+            try write_literal(rt, writer, obj.*);
+        } else try write_word(rt, writer, word),
+        else => try write_word(rt, writer, word),
+    }
+    try writer.writeByte('\n');
+}
+
+/// `name` on a line of its own, then `word`'s docstring, indented as with
+/// write_definition.
+pub fn write_help(rt: *Runtime, writer: anytype, name: []const u8, word: *Types.HeapedWord) !void {
+    try writer.print("{s}\n", .{name});
+    try write_doc(writer, rt.doc_of(word) orelse "Undocumented.");
+}
+
+fn write_doc(writer: anytype, doc: []const u8) !void {
+    var lines = std.mem.split(u8, doc, "\n");
+    while (lines.next()) |line| try writer.print("    {s}\n", .{line});
+}
+
+/// `word` as it'd appear within a body.
+fn write_word(rt: *Runtime, writer: anytype, word: *Types.HeapedWord) anyerror!void {
+    if (rt.name_of_word(word)) |name| return writer.writeAll(name);
+
+    const inner = word.value orelse return writer.writeAll("<empty>");
+    switch (inner.impl) {
+        .CallSite => unreachable, // by way of name_of_word
+        .Compound => try writer.writeAll("<compound>"),
+        .HeapLit => |obj| try write_literal(rt, writer, obj.*),
+        .HostClosure => try writer.writeAll("<host closure>"),
+        .Primitive => try writer.writeAll("<primitive>"),
+    }
+}
+
+/// `obj` as it'd be written in source, as far as that's possible.
+fn write_literal(rt: *Runtime, writer: anytype, obj: Object) anyerror!void {
+    switch (obj) {
+        .String => |str| try writer.print("\"{s}\"", .{str.value.?}),
+        .Word => |word| {
+            try writer.writeByte('&');
+            try write_word(rt, writer, word);
+        },
+        else => try writer.print("{}", .{obj}),
+    }
+}
+
+test "SEE" {
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .stdout = io.buffer_output(&out) });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("1 @LIT");
+    const one = (try runtime.stack_pop()).Word;
+
+    const inner = try runtime.alloc.alloc(*Types.HeapedWord, 3);
+    inner[0] = one;
+    inner[1] = (try runtime.find_word("@DUP")).?;
+    inner[2] = (try runtime.find_word("@DROP")).?;
+    for (inner[1..]) |word| try word.increment();
+
+    const oneish = try runtime.word_from_compound_impl(inner, null);
+    try runtime.define_word("oneish", oneish);
+    try runtime.document_word(oneish, "Push a one.\nThe long way round.");
+
+    try runtime.eval(":oneish @SEE &oneish @SEE \"two\" @LIT @SEE");
+    try expectEqualStrings(
+        \\oneish
+        \\    Push a one.
+        \\    The long way round.
+        \\    1 @DUP @DROP
+        \\oneish
+        \\    Push a one.
+        \\    The long way round.
+        \\    1 @DUP @DROP
+        \\<anonymous> ( <- String )
+        \\    "two"
+        \\
+    , out.items);
+
+    out.clearRetainingCapacity();
+    try runtime.eval("1.5 :half @CONSTANT 2 :count @VALUE :half @SEE :count @SEE");
+    try expectEqualStrings(
+        \\half ( <- Float )
+        \\    constant 1.5
+        \\count ( <- UnsignedInt )
+        \\    value 2
+        \\
+    , out.items);
+
+    out.clearRetainingCapacity();
+    try runtime.eval(":@DUP @SEE");
+    try expectEqualStrings("@DUP\n    <primitive>\n", out.items);
+
+    try expectError(InternalError.UnknownWord, runtime.eval(":nope @SEE"));
+    try runtime.stack_wrangle(.DropTopObject);
+    try expectError(InternalError.TypeError, runtime.eval("1 @SEE"));
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+test "HELP" {
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .stdout = io.buffer_output(&out) });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("&@DUP :twin @DEFINE");
+    try runtime.document_source("{{ Twice\n  the top. }}\n&@DUP :twin @DEFINE");
+
+    try runtime.eval(":twin @HELP &twin @HELP :@DROP @HELP");
+    try expectEqualStrings(
+        \\twin
+        \\    Twice
+        \\    the top.
+        \\twin
+        \\    Twice
+        \\    the top.
+        \\@DROP
+        \\    Undocumented.
+        \\
+    , out.items);
+
+    try expectError(InternalError.UnknownWord, runtime.eval(":nope @HELP"));
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+test "INTROSPECT-STATS" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("1 @DROP @INTROSPECT-STATS");
+    var stats = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&stats);
+
+    const counters = &stats.Map.value.?;
+    try std.testing.expect(counters.get("words-executed").?.UnsignedInt >= 2);
+    try std.testing.expect(counters.get("pushes").?.UnsignedInt >= 1);
+    try std.testing.expectEqual(@as(usize, 0), counters.get("allocations").?.UnsignedInt);
+    try std.testing.expect(counters.get("call-site-misses") != null);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
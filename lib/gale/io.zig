@@ -0,0 +1,153 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const helpers = @import("./helpers.zig");
+
+// TODO: configurable in build.zig
+pub const MAX_LINE_LEN = 64 * 1024;
+
+pub const WriteFn = *const fn (context: ?*anyopaque, bytes: []const u8) anyerror!usize;
+pub const ReadFn = *const fn (context: ?*anyopaque, buffer: []u8) anyerror!usize;
+
+/// Somewhere for words like @PRINT to send their output. Runtimes default to
+/// the process's stdout and stderr (see Runtime.Options), but embedders can
+/// point these anywhere: a log, a GUI widget, a buffer (see buffer_output).
+pub const OutputHandle = struct {
+    const Self = @This();
+
+    pub const Writer = std.io.Writer(Self, anyerror, write);
+
+    context: ?*anyopaque,
+    write_fn: WriteFn,
+
+    pub fn write(self: Self, bytes: []const u8) anyerror!usize {
+        return self.write_fn(self.context, bytes);
+    }
+
+    pub fn writer(self: Self) Writer {
+        return .{ .context = self };
+    }
+};
+
+/// Where words like @READ-LINE get their input from. As with OutputHandle,
+/// this defaults to the process's stdin, but needn't be.
+pub const InputHandle = struct {
+    const Self = @This();
+
+    pub const Reader = std.io.Reader(Self, anyerror, read);
+
+    context: ?*anyopaque,
+    read_fn: ReadFn,
+
+    pub fn read(self: Self, buffer: []u8) anyerror!usize {
+        return self.read_fn(self.context, buffer);
+    }
+
+    pub fn reader(self: Self) Reader {
+        return .{ .context = self };
+    }
+};
+
+/// Output which goes nowhere, which is the default on freestanding targets.
+pub const DISCARD = OutputHandle{ .context = null, .write_fn = &discard_write };
+
+/// Input which is always at EOF, which is the default on freestanding
+/// targets.
+pub const EMPTY = InputHandle{ .context = null, .read_fn = &empty_read };
+
+fn discard_write(_: ?*anyopaque, bytes: []const u8) anyerror!usize {
+    return bytes.len;
+}
+
+fn empty_read(_: ?*anyopaque, _: []u8) anyerror!usize {
+    return 0;
+}
+
+pub fn stdout() OutputHandle {
+    if (comptime !helpers.HOSTED) return DISCARD;
+    return .{ .context = null, .write_fn = &stdout_write };
+}
+
+pub fn stderr() OutputHandle {
+    if (comptime !helpers.HOSTED) return DISCARD;
+    return .{ .context = null, .write_fn = &stderr_write };
+}
+
+pub fn stdin() InputHandle {
+    if (comptime !helpers.HOSTED) return EMPTY;
+    return .{ .context = null, .read_fn = &stdin_read };
+}
+
+fn stdout_write(_: ?*anyopaque, bytes: []const u8) anyerror!usize {
+    return std.io.getStdOut().write(bytes);
+}
+
+fn stderr_write(_: ?*anyopaque, bytes: []const u8) anyerror!usize {
+    return std.io.getStdErr().write(bytes);
+}
+
+fn stdin_read(_: ?*anyopaque, buffer: []u8) anyerror!usize {
+    return std.io.getStdIn().read(buffer);
+}
+
+/// Output appended to `list`, which must outlive the handle.
+pub fn buffer_output(list: *std.ArrayList(u8)) OutputHandle {
+    return .{ .context = list, .write_fn = &buffer_write };
+}
+
+fn buffer_write(context: ?*anyopaque, bytes: []const u8) anyerror!usize {
+    const list = @ptrCast(*std.ArrayList(u8), @alignCast(@alignOf(std.ArrayList(u8)), context.?));
+    try list.appendSlice(bytes);
+    return bytes.len;
+}
+
+/// Output written to `file`, which must outlive the handle.
+pub fn file_output(file: *std.fs.File) OutputHandle {
+    return .{ .context = file, .write_fn = &file_write };
+}
+
+fn file_write(context: ?*anyopaque, bytes: []const u8) anyerror!usize {
+    const file = @ptrCast(*std.fs.File, @alignCast(@alignOf(std.fs.File), context.?));
+    return file.write(bytes);
+}
+
+/// Input read from `stream`, which must outlive the handle.
+pub fn fixed_input(stream: *std.io.FixedBufferStream([]const u8)) InputHandle {
+    return .{ .context = stream, .read_fn = &fixed_read };
+}
+
+fn fixed_read(context: ?*anyopaque, buffer: []u8) anyerror!usize {
+    const Stream = std.io.FixedBufferStream([]const u8);
+    const stream = @ptrCast(*Stream, @alignCast(@alignOf(Stream), context.?));
+    return stream.read(buffer);
+}
+
+test "buffer_output and fixed_input" {
+    var list = std.ArrayList(u8).init(std.testing.allocator);
+    defer list.deinit();
+    try buffer_output(&list).writer().print("{d} apples", .{3});
+    try expectEqualStrings("3 apples", list.items);
+
+    var stream = std.io.fixedBufferStream(@as([]const u8, "one\ntwo"));
+    var buf: [8]u8 = undefined;
+    const line = (try fixed_input(&stream).reader().readUntilDelimiterOrEof(&buf, '\n')).?;
+    try expectEqualStrings("one", line);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
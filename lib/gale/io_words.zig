@@ -0,0 +1,146 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const io = @import("./io.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+
+/// Every console I/O word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@CR", CR },
+    .{ "@EMIT", EMIT },
+    .{ "@PRINT", PRINT },
+    .{ "@PRINTLN", PRINTLN },
+    .{ "@READ-LINE", READ_LINE },
+};
+
+/// Define every console I/O word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @PRINT ( @1 <- )
+///
+/// Write the top Object (see Object.format for what that looks like) to the
+/// Runtime's stdout, without a trailing newline.
+pub fn PRINT(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    try runtime.stdout.writer().print("{}", .{peeked.*});
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @PRINTLN ( @1 <- )
+///
+/// @PRINT, then @CR.
+pub fn PRINTLN(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    try runtime.stdout.writer().print("{}\n", .{peeked.*});
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @EMIT ( UnsignedInt <- )
+///
+/// Write a single Unicode codepoint, UTF-8 encoded, to the Runtime's stdout.
+/// Anything which isn't a valid codepoint is a ValueError.
+pub fn EMIT(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+
+    const codepoint = std.math.cast(u21, peeked.UnsignedInt) orelse return InternalError.ValueError;
+    var buf: [4]u8 = undefined;
+    const len = std.unicode.utf8Encode(codepoint, &buf) catch return InternalError.ValueError;
+
+    try runtime.stdout.writer().writeAll(buf[0..len]);
+    _ = try runtime.stack_pop();
+}
+
+/// @CR ( <- )
+///
+/// Write a newline to the Runtime's stdout.
+pub fn CR(runtime: *Runtime) anyerror!void {
+    try runtime.stdout.writer().writeByte('\n');
+}
+
+/// @READ-LINE ( <- String Boolean )
+///
+/// Read a line from the Runtime's stdin, pushing it (without its line
+/// ending, be that \n or \r\n) and true, or just false once stdin has run
+/// dry. Lines longer than io.MAX_LINE_LEN are a LimitExceeded.
+pub fn READ_LINE(runtime: *Runtime) anyerror!void {
+    const maybe_line = runtime.stdin.reader().readUntilDelimiterOrEofAlloc(
+        runtime.alloc,
+        '\n',
+        io.MAX_LINE_LEN,
+    ) catch |err| switch (err) {
+        error.StreamTooLong => return InternalError.LimitExceeded,
+        else => return err,
+    };
+
+    const line = maybe_line orelse return runtime.stack_push_bool(false);
+    defer runtime.alloc.free(line);
+
+    const str = try runtime.get_or_put_string(std.mem.trimRight(u8, line, "\r"));
+    try runtime.stack_push_string(str.value_ptr);
+    try runtime.stack_push_bool(true);
+}
+
+test "@PRINT, @PRINTLN, @EMIT, and @CR" {
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .stdout = io.buffer_output(&out) });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"hi\" @PRINT 1 @PRINTLN :two @PRINT @CR 955 @EMIT");
+    try expectEqualStrings("hi1\n:two\nλ", out.items);
+
+    try expectError(InternalError.ValueError, runtime.eval("55296 @EMIT"));
+    _ = try runtime.stack_pop();
+    try expectError(InternalError.TypeError, runtime.eval("-1 @EMIT"));
+    _ = try runtime.stack_pop();
+}
+
+test "@READ-LINE" {
+    var stream = std.io.fixedBufferStream(@as([]const u8, "one\r\ntwo"));
+
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .stdin = io.fixed_input(&stream) });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    for ([_][]const u8{ "one", "two" }) |expected| {
+        try runtime.eval("@READ-LINE");
+        try expect(try runtime.stack_pop_value(bool));
+        try expect_string_popped(&runtime, expected);
+    }
+
+    try runtime.eval("@READ-LINE");
+    try expect(!(try runtime.stack_pop_value(bool)));
+}
+
+fn expect_string_popped(runtime: *Runtime, expected: []const u8) !void {
+    var popped = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&popped);
+    try expectEqualStrings(expected, popped.String.value.?);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
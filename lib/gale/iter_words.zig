@@ -0,0 +1,450 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const nucleus_words = @import("./nucleus_words.zig");
+
+const Cell = @import("./cell.zig").Cell;
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+// Iterators: sequences produced a value at a time, as they're asked for,
+// rather than all up front. An iterator is any Word which, each time it's
+// run, pushes its next value and true, or just false once it's run dry, just
+// as @LINES-NEXT does for a file (so `cursor &@LINES-NEXT 1 @CAPTURE` is an
+// iterator over its lines). Running it is all `next` (in the prelude) does.
+//
+// The adapters here (@ITER-TAKE, @ITER-MAP, and @ITER-FILTER) wrap one
+// iterator in another, pulling from it only as they're pulled from in turn,
+// and @ITER-EACH and @ITER-COLLECT drain them. Each adapter is a Compound of
+// its state (as literals) and a -STEP word doing the actual work, which are
+// words in their own right for the sake of @SEE and images, but not
+// otherwise meant to be called by hand.
+
+/// Every iterator word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@ITER-COLLECT", ITER_COLLECT },
+    .{ "@ITER-EACH", ITER_EACH },
+    .{ "@ITER-FILTER", ITER_FILTER },
+    .{ "@ITER-FILTER-STEP", ITER_FILTER_STEP },
+    .{ "@ITER-MAP", ITER_MAP },
+    .{ "@ITER-MAP-STEP", ITER_MAP_STEP },
+    .{ "@ITER-RANGE", ITER_RANGE },
+    .{ "@ITER-RANGE-BY", ITER_RANGE_BY },
+    .{ "@ITER-RANGE-BY-STEP", ITER_RANGE_BY_STEP },
+    .{ "@ITER-RANGE-STEP", ITER_RANGE_STEP },
+    .{ "@ITER-TAKE", ITER_TAKE },
+    .{ "@ITER-TAKE-STEP", ITER_TAKE_STEP },
+};
+
+/// Define every iterator word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @ITER-RANGE ( UnsignedInt UnsignedInt <- Word )
+///
+/// An iterator over the UnsignedInts from the further (inclusive) to the
+/// nearer (exclusive): `0 3 @ITER-RANGE` produces 0, 1, and 2.
+pub fn ITER_RANGE(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt or far.* != .UnsignedInt) return InternalError.TypeError;
+
+    var counter = try new_counter(runtime, far.*);
+    defer runtime.release_heaped_object_reference(&counter);
+    try replace_with_stepper(runtime, 2, &.{ counter, peek.near.* }, "@ITER-RANGE-STEP");
+}
+
+/// @ITER-RANGE-STEP ( Cell UnsignedInt <- UnsignedInt Boolean | Boolean )
+pub fn ITER_RANGE_STEP(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt or far.* != .Cell) return InternalError.TypeError;
+    const end = peek.near.UnsignedInt;
+    const counter = &far.Cell.value.?;
+
+    const next = try counter_value(counter);
+    var held = try far.ref();
+    defer runtime.release_heaped_object_reference(&held);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+
+    if (next >= end) return runtime.stack_push_bool(false);
+    _ = counter.swap(.{ .UnsignedInt = next + 1 });
+    try runtime.stack_push_uint(next);
+    try runtime.stack_push_bool(true);
+}
+
+/// @ITER-RANGE-BY ( @1 @1 @1 <- Word )
+///
+/// An iterator over the numbers from the furthest (inclusive) towards the
+/// middle one (exclusive), the nearest apart: `0 10 3 @ITER-RANGE-BY`
+/// produces 0, 3, 6, and 9, and `+3 -3 -2 @ITER-RANGE-BY` 3, 1, and -1. All
+/// three must be the same kind of number, and a step of zero (or NaN) is a
+/// ValueError. Each value is worked out afresh from the start, so Floats
+/// don't drift as they would if summed, and the range ends early, rather
+/// than overflowing, should a value not fit.
+pub fn ITER_RANGE_BY(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_trio();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    const mid = peek.mid orelse return StackManipulationError.Underflow;
+    _ = try peek.near.assert_same_kind_as(mid);
+    _ = try mid.assert_same_kind_as(far);
+
+    switch (peek.near.*) {
+        .UnsignedInt => |step| if (step == 0) return InternalError.ValueError,
+        .SignedInt => |step| if (step == 0) return InternalError.ValueError,
+        .Float => |step| if (step == 0 or std.math.isNan(step)) return InternalError.ValueError,
+        else => return InternalError.TypeError,
+    }
+
+    // Numbers are never heaped, so the bounds hold no references of their
+    // own.
+    const bounds = try runtime.new_array(3);
+    for ([_]*Object{ far, mid, peek.near }) |bound| bounds.value.?.appendAssumeCapacity(bound.*);
+    bounds.increment() catch unreachable;
+    var bounds_obj = Object{ .Array = bounds };
+    defer runtime.release_heaped_object_reference(&bounds_obj);
+
+    var counter = try new_counter(runtime, .{ .UnsignedInt = 0 });
+    defer runtime.release_heaped_object_reference(&counter);
+    try replace_with_stepper(runtime, 3, &.{ counter, bounds_obj }, "@ITER-RANGE-BY-STEP");
+}
+
+/// @ITER-RANGE-BY-STEP ( Cell Array <- @1 Boolean | Boolean )
+pub fn ITER_RANGE_BY_STEP(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Array or far.* != .Cell) return InternalError.TypeError;
+    const bounds = peek.near.Array.value.?.items;
+    if (bounds.len != 3) return InternalError.ValueError;
+    const counter = &far.Cell.value.?;
+
+    const idx = try counter_value(counter);
+    const next = nth_in_range(bounds[0], bounds[1], bounds[2], idx);
+    var held = try far.ref();
+    defer runtime.release_heaped_object_reference(&held);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+
+    const value = next orelse return runtime.stack_push_bool(false);
+    _ = counter.swap(.{ .UnsignedInt = idx + 1 });
+    try runtime.stack_push_object(value);
+    try runtime.stack_push_bool(true);
+}
+
+/// Value `idx` of the range from `start` towards `end` by `step`, or null if
+/// it's at or past the end (or doesn't fit).
+fn nth_in_range(start: Object, end: Object, step: Object, idx: usize) ?Object {
+    switch (step) {
+        .UnsignedInt => |by| {
+            const offset = std.math.mul(usize, idx, by) catch return null;
+            const value = std.math.add(usize, start.UnsignedInt, offset) catch return null;
+            return if (value < end.UnsignedInt) Object{ .UnsignedInt = value } else null;
+        },
+        .SignedInt => |by| {
+            const offset = std.math.mul(isize, std.math.cast(isize, idx) orelse return null, by) catch return null;
+            const value = std.math.add(isize, start.SignedInt, offset) catch return null;
+            const within = if (by > 0) value < end.SignedInt else value > end.SignedInt;
+            return if (within) Object{ .SignedInt = value } else null;
+        },
+        .Float => |by| {
+            const value = start.Float + @intToFloat(f64, idx) * by;
+            const within = if (by > 0) value < end.Float else value > end.Float;
+            return if (within) Object{ .Float = value } else null;
+        },
+        else => return null,
+    }
+}
+
+/// @ITER-TAKE ( Word UnsignedInt <- Word )
+///
+/// An iterator over at most the first UnsignedInt many values of the
+/// iterator beneath it, which is never pulled from again after that.
+pub fn ITER_TAKE(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt or far.* != .Word) return InternalError.TypeError;
+
+    var counter = try new_counter(runtime, peek.near.*);
+    defer runtime.release_heaped_object_reference(&counter);
+    try replace_with_stepper(runtime, 2, &.{ far.*, counter }, "@ITER-TAKE-STEP");
+}
+
+/// @ITER-TAKE-STEP ( Word Cell <- @1 Boolean | Boolean )
+pub fn ITER_TAKE_STEP(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Cell or far.* != .Word) return InternalError.TypeError;
+    const counter = &peek.near.Cell.value.?;
+
+    const remaining = try counter_value(counter);
+    var pair = try runtime.stack_pop_pair();
+    defer runtime.release_heaped_object_reference(&pair.near);
+    defer runtime.release_heaped_object_reference(&pair.far);
+
+    if (remaining == 0) return runtime.stack_push_bool(false);
+    _ = counter.swap(.{ .UnsignedInt = remaining - 1 });
+    try runtime.run_word(pair.far.Word);
+}
+
+/// @ITER-MAP ( Word Word <- Word )
+///
+/// An iterator over the further iterator's values, each run through the
+/// nearer Word ( @1 -> @2 ) on its way out.
+pub fn ITER_MAP(runtime: *Runtime) anyerror!void {
+    try replace_with_adapter(runtime, "@ITER-MAP-STEP");
+}
+
+/// @ITER-MAP-STEP ( Word Word <- @1 Boolean | Boolean )
+pub fn ITER_MAP_STEP(runtime: *Runtime) anyerror!void {
+    var pair = try pop_words(runtime);
+    defer runtime.release_heaped_object_reference(&pair.near);
+    defer runtime.release_heaped_object_reference(&pair.far);
+
+    if (!try pull(runtime, pair.far.Word)) return runtime.stack_push_bool(false);
+    try runtime.run_word(pair.near.Word);
+    try runtime.stack_push_bool(true);
+}
+
+/// @ITER-FILTER ( Word Word <- Word )
+///
+/// An iterator over only those of the further iterator's values for which
+/// the nearer Word ( @1 -> Boolean ) is true.
+pub fn ITER_FILTER(runtime: *Runtime) anyerror!void {
+    try replace_with_adapter(runtime, "@ITER-FILTER-STEP");
+}
+
+/// @ITER-FILTER-STEP ( Word Word <- @1 Boolean | Boolean )
+pub fn ITER_FILTER_STEP(runtime: *Runtime) anyerror!void {
+    var pair = try pop_words(runtime);
+    defer runtime.release_heaped_object_reference(&pair.near);
+    defer runtime.release_heaped_object_reference(&pair.far);
+
+    while (try pull(runtime, pair.far.Word)) {
+        // The predicate gets the value to itself, so hang on to it in case
+        // it's a keeper.
+        var value = try (try runtime.stack_peek()).ref();
+        defer runtime.release_heaped_object_reference(&value);
+
+        try runtime.run_word(pair.near.Word);
+        if (try pop_bool(runtime)) {
+            try runtime.stack_push_object(value);
+            return runtime.stack_push_bool(true);
+        }
+    }
+
+    try runtime.stack_push_bool(false);
+}
+
+/// @ITER-EACH ( Word Word <- )
+///
+/// Run the nearer Word once for each of the further iterator's values, with
+/// the value pushed beforehand. Neither Word is on the Stack meanwhile.
+pub fn ITER_EACH(runtime: *Runtime) anyerror!void {
+    var pair = try pop_words(runtime);
+    defer runtime.release_heaped_object_reference(&pair.near);
+    defer runtime.release_heaped_object_reference(&pair.far);
+
+    while (try pull(runtime, pair.far.Word)) try runtime.run_word(pair.near.Word);
+}
+
+/// @ITER-COLLECT ( Word <- Array )
+///
+/// Every (remaining) value of the iterator, in order. Iterators which never
+/// run dry never finish being collected, so @ITER-TAKE those first.
+pub fn ITER_COLLECT(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Word) return InternalError.TypeError;
+
+    var source = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&source);
+
+    const values = try runtime.new_array(0);
+    errdefer runtime.release_array(values);
+    while (try pull(runtime, source.Word)) {
+        var value = try runtime.stack_pop();
+        values.value.?.append(value) catch |err| {
+            runtime.release_heaped_object_reference(&value);
+            return err;
+        };
+    }
+
+    try runtime.stack_push_array(values);
+}
+
+/// Run `source` for its next value, returning whether there was one (in
+/// which case, it's on top of the Stack).
+fn pull(runtime: *Runtime, source: *Types.HeapedWord) !bool {
+    try runtime.run_word(source);
+    return pop_bool(runtime);
+}
+
+fn pop_bool(runtime: *Runtime) !bool {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Boolean) return InternalError.TypeError;
+    return (try runtime.stack_pop()).Boolean;
+}
+
+/// Pop the two Words on top of the Stack, whose references the caller then
+/// owns.
+fn pop_words(runtime: *Runtime) !Types.PopPairExternal {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Word or far.* != .Word) return InternalError.TypeError;
+    return runtime.stack_pop_pair();
+}
+
+fn replace_with_adapter(runtime: *Runtime, step: []const u8) !void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Word or far.* != .Word) return InternalError.TypeError;
+    try replace_with_stepper(runtime, 2, &.{ far.*, peek.near.* }, step);
+}
+
+/// A Cell holding `start`, with a reference the caller owns.
+fn new_counter(runtime: *Runtime, start: Object) !Object {
+    const heaped = try runtime.new_cell(start);
+    heaped.increment() catch unreachable;
+    return Object{ .Cell = heaped };
+}
+
+fn counter_value(counter: *Cell) !usize {
+    const held = try counter.load();
+    // UnsignedInts are never heaped, so there's nothing to release.
+    if (held != .UnsignedInt) return InternalError.TypeError;
+    return held.UnsignedInt;
+}
+
+/// Drop `consumed` Objects, and push a Compound pushing each of `state` (to
+/// which new references are taken) and then running `step`.
+fn replace_with_stepper(runtime: *Runtime, consumed: usize, state: []const Object, step: []const u8) !void {
+    const inner = try runtime.alloc.alloc(*Types.HeapedWord, state.len + 1);
+    var filled: usize = 0;
+    errdefer {
+        for (inner[0..filled]) |member| {
+            var owned = Object{ .Word = member };
+            runtime.release_heaped_object_reference(&owned);
+        }
+        runtime.alloc.free(inner);
+    }
+
+    while (filled < state.len) : (filled += 1) {
+        inner[filled] = try nucleus_words.literal_word(runtime, &state[filled]);
+        inner[filled].increment() catch unreachable;
+    }
+    inner[filled] = (try runtime.find_word(step)) orelse return InternalError.UnknownWord;
+    try inner[filled].increment();
+    filled += 1;
+
+    const word = try runtime.word_from_compound_impl(inner, null);
+
+    var dropped: usize = 0;
+    while (dropped < consumed) : (dropped += 1) try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_raw_word(word);
+}
+
+fn expect_collected(runtime: *Runtime, source: []const u8, expected: []const usize) !void {
+    try runtime.eval(source);
+    var collected = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&collected);
+
+    const members = collected.Array.value.?.items;
+    try expectEqual(expected.len, members.len);
+    for (expected) |value, idx| try expectEqual(value, members[idx].UnsignedInt);
+}
+
+test "iterators" {
+    const Words = struct {
+        fn is_even(rt: *Runtime) anyerror!void {
+            try rt.stack_push_bool(try rt.stack_pop_value(usize) % 2 == 0);
+        }
+    };
+
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+    try runtime.define_word("even?", try runtime.word_from_primitive_impl(&Words.is_even, null));
+
+    try expect_collected(&runtime, "0 4 @ITER-RANGE @ITER-COLLECT", &.{ 0, 1, 2, 3 });
+    try expect_collected(&runtime, "3 1 @ITER-RANGE @ITER-COLLECT", &.{});
+    try expect_collected(&runtime, "0 1000 @ITER-RANGE 3 @ITER-TAKE @ITER-COLLECT", &.{ 0, 1, 2 });
+    try expect_collected(&runtime, "0 10 @ITER-RANGE &even? @ITER-FILTER 2 @ITER-TAKE @ITER-COLLECT", &.{ 0, 2 });
+
+    try runtime.eval("0 3 @ITER-RANGE \"<{}>\" @LIT &@FORMAT @COMPOSE @ITER-MAP @ITER-COLLECT");
+    var mapped = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&mapped);
+    try expectEqual(@as(usize, 3), mapped.Array.value.?.items.len);
+    try expectEqualStrings("<2>", mapped.Array.value.?.items[2].String.value.?);
+
+    // Pulling one at a time, by hand.
+    try runtime.eval("5 7 @ITER-RANGE @DUP @CALL");
+    try expect((try runtime.stack_pop()).Boolean);
+    try expectEqual(@as(usize, 5), try runtime.stack_pop_value(usize));
+    try runtime.eval("@DUP @CALL @DROP @DROP @CALL");
+    try expect(!(try runtime.stack_pop()).Boolean);
+
+    try runtime.eval("0 3 @ITER-RANGE &@DUP @ITER-EACH");
+    try expectEqual(@as(usize, 6), runtime.stack.depth());
+    var idx: usize = 6;
+    while (idx > 0) : (idx -= 1) try expectEqual((idx - 1) / 2, try runtime.stack_pop_value(usize));
+
+    try expectError(InternalError.TypeError, runtime.eval("5 @LIT @ITER-COLLECT"));
+    try runtime.eval("@DROP");
+}
+
+test "@ITER-RANGE-BY" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_collected(&runtime, "0 10 3 @ITER-RANGE-BY @ITER-COLLECT", &.{ 0, 3, 6, 9 });
+    try expect_collected(&runtime, "10 0 3 @ITER-RANGE-BY @ITER-COLLECT", &.{});
+
+    try runtime.eval("+3 -3 -2 @ITER-RANGE-BY @ITER-COLLECT");
+    var down = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&down);
+    try expectEqual(@as(usize, 3), down.Array.value.?.items.len);
+    try expectEqual(@as(isize, -1), down.Array.value.?.items[2].SignedInt);
+
+    try runtime.eval("0.0 1.0 0.25 @ITER-RANGE-BY @ITER-COLLECT");
+    var quarters = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&quarters);
+    try expectEqual(@as(usize, 4), quarters.Array.value.?.items.len);
+    try expectEqual(@as(f64, 0.75), quarters.Array.value.?.items[3].Float);
+
+    // Running out of room ends the range, rather than wrapping around.
+    try runtime.stack_push_uint(std.math.maxInt(usize) - 1);
+    try runtime.stack_push_uint(std.math.maxInt(usize));
+    try runtime.eval("2 @ITER-RANGE-BY @ITER-COLLECT");
+    var edge = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&edge);
+    try expectEqual(@as(usize, 1), edge.Array.value.?.items.len);
+
+    try expectError(InternalError.ValueError, runtime.eval("0 10 0 @ITER-RANGE-BY"));
+    try expectError(InternalError.TypeError, runtime.eval("@DROP @DROP @DROP 0 10 +1 @ITER-RANGE-BY"));
+    try runtime.eval("@DROP @DROP @DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
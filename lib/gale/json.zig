@@ -0,0 +1,210 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const conversion = @import("./conversion.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// Converting between JSON text and Objects. The mapping is:
+//
+// | JSON                 | Object                                          |
+// |----------------------|-------------------------------------------------|
+// | null                 | the Symbol `:null` (see conversion.NULL_SYMBOL) |
+// | true, false          | Boolean                                         |
+// | 0, 1, 2, ...         | UnsignedInt                                     |
+// | -1, -2, ...          | SignedInt                                       |
+// | 1.5, 1e3, and any    | Float                                           |
+// | integer too big for  |                                                 |
+// | the above            |                                                 |
+// | "string"             | String                                          |
+// | [array]              | Array                                           |
+// | {object}             | Map                                             |
+//
+// Going the other way, every Symbol besides `:null` becomes a JSON string of
+// its name (so `:red` is "red", and won't come back as a Symbol), while
+// Words, Cells, Channels, Opaques, and Sets have no JSON form at all and are
+// a TypeError. So are NaNs and infinities, which JSON can't express either.
+
+/// How deeply Arrays and Maps may nest, either way, before we give up with
+/// a LimitExceeded rather than risk running out of (Zig) stack.
+// TODO: configurable in build.zig
+pub const MAX_NESTING = 512;
+
+/// Parse `text` as a single JSON value. Malformed JSON is a ValueError. As
+/// with anything fresh off the heap, the result holds no references yet.
+pub fn parse(rt: *Runtime, text: []const u8) !Object {
+    var parser = std.json.Parser.init(rt.alloc, false);
+    defer parser.deinit();
+
+    var tree = parser.parse(text) catch |err| switch (err) {
+        error.OutOfMemory => return err,
+        else => return InternalError.ValueError,
+    };
+    defer tree.deinit();
+
+    return try value_to_object(rt, tree.root, 0);
+}
+
+fn value_to_object(rt: *Runtime, value: std.json.Value, depth: usize) anyerror!Object {
+    if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+
+    switch (value) {
+        .Null => return Object{ .Symbol = (try rt.get_or_put_symbol(conversion.NULL_SYMBOL)).value_ptr },
+        .Bool => |val| return Object{ .Boolean = val },
+        .Integer => |val| return if (val < 0)
+            Object{ .SignedInt = std.math.cast(isize, val) orelse return InternalError.ValueError }
+        else
+            Object{ .UnsignedInt = std.math.cast(usize, val) orelse return InternalError.ValueError },
+        .Float => |val| return Object{ .Float = val },
+        .NumberString => |text| return Object{
+            .Float = std.fmt.parseFloat(f64, text) catch return InternalError.ValueError,
+        },
+        .String => |text| return try conversion.to_object(rt, text),
+        .Array => |items| {
+            const array = try rt.new_array(items.items.len);
+            errdefer rt.release_array(array);
+
+            for (items.items) |item| {
+                const member = try (try value_to_object(rt, item, depth + 1)).ref();
+                array.value.?.appendAssumeCapacity(member);
+            }
+
+            return Object{ .Array = array };
+        },
+        .Object => |fields| {
+            const map = try rt.new_map();
+            errdefer rt.release_map(map);
+
+            var iter = fields.iterator();
+            while (iter.next()) |field| {
+                var member = try (try value_to_object(rt, field.value_ptr.*, depth + 1)).ref();
+                errdefer member.deinit(rt.alloc);
+                try map.value.?.put(rt.alloc, field.key_ptr.*, member);
+            }
+
+            return Object{ .Map = map };
+        },
+    }
+}
+
+/// Write `obj` as (compact) JSON.
+pub fn stringify(writer: anytype, obj: Object) !void {
+    try write_value(writer, obj, 0);
+}
+
+fn write_value(writer: anytype, obj: Object, depth: usize) anyerror!void {
+    if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+
+    switch (obj) {
+        .Array => |array| {
+            try writer.writeByte('[');
+            for (array.value.?.items) |member, idx| {
+                if (idx > 0) try writer.writeByte(',');
+                try write_value(writer, member, depth + 1);
+            }
+            try writer.writeByte(']');
+        },
+        .Boolean => |val| try writer.writeAll(if (val) "true" else "false"),
+        .Float => |val| {
+            if (std.math.isNan(val) or std.math.isInf(val)) return InternalError.TypeError;
+            try writer.print("{d}", .{val});
+        },
+        .Map => |map| {
+            try writer.writeByte('{');
+            for (map.value.?.keys()) |key, idx| {
+                if (idx > 0) try writer.writeByte(',');
+                try write_string(writer, key);
+                try writer.writeByte(':');
+                try write_value(writer, map.value.?.get(key).?, depth + 1);
+            }
+            try writer.writeByte('}');
+        },
+        .SignedInt => |val| try writer.print("{d}", .{val}),
+        .String => |str| try write_string(writer, str.value.?),
+        .Symbol => |sym| if (std.mem.eql(u8, sym.value.?, conversion.NULL_SYMBOL))
+            try writer.writeAll("null")
+        else
+            try write_string(writer, sym.value.?),
+        .UnsignedInt => |val| try writer.print("{d}", .{val}),
+        .Cell, .Channel, .Opaque, .Set, .Word => return InternalError.TypeError,
+    }
+}
+
+/// `str` as a JSON string, escaped as need be.
+pub fn write_string(writer: anytype, str: []const u8) !void {
+    try writer.writeByte('"');
+    for (str) |chr| switch (chr) {
+        '"' => try writer.writeAll("\\\""),
+        '\\' => try writer.writeAll("\\\\"),
+        '\n' => try writer.writeAll("\\n"),
+        '\r' => try writer.writeAll("\\r"),
+        '\t' => try writer.writeAll("\\t"),
+        0...0x08, 0x0B, 0x0C, 0x0E...0x1F, 0x7F => try writer.print("\\u{x:0>4}", .{chr}),
+        // Anything else, multi-byte UTF-8 included, is fine as-is.
+        else => try writer.writeByte(chr),
+    };
+    try writer.writeByte('"');
+}
+
+fn expect_round_trip(rt: *Runtime, text: []const u8, expected: []const u8) !void {
+    var obj = try (try parse(rt, text)).ref();
+    defer rt.release_heaped_object_reference(&obj);
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+    try stringify(out.writer(), obj);
+    try expectEqualStrings(expected, out.items);
+}
+
+test "round trips" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try expect_round_trip(&rt, "[1, -2, 2.5, true, null, \"a\\\"b\\u0001\"]", "[1,-2,2.5,true,null,\"a\\\"b\\u0001\"]");
+    try expect_round_trip(&rt, "{\"b\": {\"c\": []}, \"a\": \"λ\"}", "{\"b\":{\"c\":[]},\"a\":\"λ\"}");
+}
+
+test "parse" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var obj = try (try parse(&rt, "{\"n\": 3, \"xs\": [null]}")).ref();
+    defer rt.release_heaped_object_reference(&obj);
+    try expectEqual(@as(usize, 3), obj.Map.value.?.get("n").?.UnsignedInt);
+    const xs = obj.Map.value.?.get("xs").?.Array.value.?.items;
+    try expectEqualStrings(conversion.NULL_SYMBOL, xs[0].Symbol.value.?);
+
+    var huge = try (try parse(&rt, "99999999999999999999999")).ref();
+    defer rt.release_heaped_object_reference(&huge);
+    try expect(huge == .Float);
+
+    try expectError(InternalError.ValueError, parse(&rt, "{\"unterminated\": "));
+    try expectError(InternalError.ValueError, parse(&rt, "[1] [2]"));
+
+    const deep = "[" ** (MAX_NESTING + 2) ++ "]" ** (MAX_NESTING + 2);
+    try expectError(InternalError.LimitExceeded, parse(&rt, deep));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const json = @import("./json.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+
+/// Every JSON word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@JSON-PARSE", JSON_PARSE },
+    .{ "@JSON-STRINGIFY", JSON_STRINGIFY },
+};
+
+/// Define every JSON word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @JSON-PARSE ( String <- @1 )
+///
+/// The value the String holds, as JSON. See json.zig for which JSON types
+/// become which Objects. Malformed JSON is a ValueError.
+pub fn JSON_PARSE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+
+    const parsed = try json.parse(runtime, peeked.String.value.?);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(parsed);
+}
+
+/// @JSON-STRINGIFY ( @1 <- String )
+///
+/// The Object, as compact JSON text. Objects with no JSON equivalent (see
+/// json.zig) are a TypeError.
+pub fn JSON_STRINGIFY(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+    try json.stringify(out.writer(), peeked.*);
+    const str = try runtime.get_or_put_string(out.items);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+test "@JSON-PARSE and @JSON-STRINGIFY" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    // Strings in source can't (yet) hold double quotes, so the text goes on
+    // the Stack by hand.
+    try runtime.stack_push_string((try runtime.get_or_put_string("[1,{\"a\":true}]")).value_ptr);
+    try runtime.eval("@JSON-PARSE @DUP @JSON-STRINGIFY");
+    var text = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&text);
+    try expectEqualStrings("[1,{\"a\":true}]", text.String.value.?);
+
+    var parsed = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&parsed);
+    try expectEqual(@as(usize, 2), parsed.Array.value.?.items.len);
+
+    try expectError(InternalError.TypeError, runtime.eval("&@DUP @JSON-STRINGIFY"));
+    var word = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&word);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,543 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const hashbang = @import("./hashbang.zig");
+const helpers = @import("./helpers.zig");
+const source_encoding = @import("./source_encoding.zig");
+const test_helpers = @import("./test_helpers.zig");
+const unicode = @import("./unicode.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+// Source text, split into Tokens for tools (the formatter, the linter) that
+// care about more of it than eval does: comments, line breaks, and where
+// everything is. Words are split wherever eval would split them, and every
+// Token's raw text is exactly as written, so tools can reprint anything they
+// have no opinion on untouched.
+
+pub const Token = struct {
+    pub const Kind = enum {
+        /// Anything eval would look up or read as a literal, besides Strings.
+        Word,
+        /// A word starting with a double quote, running to the next one (line
+        /// breaks and all), and on to the next whitespace after that.
+        String,
+        /// `//` through to (but not including) the end of the line, as is
+        /// a hashbang on the first line (see hashbang.zig).
+        LineComment,
+        /// `{` through to its matching `}`, braces nesting, line breaks and
+        /// all. Docstrings (`{{ ... }}`) are just block comments with a
+        /// block comment for a body; see docstrings.zig for what they
+        /// document.
+        BlockComment,
+        /// A line break outside of any other Token.
+        Newline,
+    };
+
+    kind: Kind,
+    /// Exactly as written.
+    raw: []const u8,
+    /// Where `raw` starts in the source.
+    offset: usize,
+    /// 1-based, in lines and bytes respectively, of the Token's start. See
+    /// columns for other ways of counting the latter.
+    line: usize,
+    column: usize,
+
+    /// Where the Token starts in its line of `source`, the source it was
+    /// lexed from, as editors count columns.
+    pub fn columns(self: Token, source: []const u8, policy: unicode.ColumnPolicy) Columns {
+        return columns_of(source, self.offset, self.column, policy);
+    }
+};
+
+/// A column, counted other than in bytes.
+pub const Columns = struct {
+    /// 1-based, in graphemes.
+    grapheme: usize,
+    /// 1-based, as unicode.display_width counts them.
+    display: usize,
+};
+
+/// The Columns of whatever starts at `offset` in `source`, `column` (in
+/// bytes, as Tokens count them) into its line.
+pub fn columns_of(source: []const u8, offset: usize, column: usize, policy: unicode.ColumnPolicy) Columns {
+    const before = source[offset - (column - 1) .. offset];
+    return .{
+        .grapheme = unicode.grapheme_count(before) + 1,
+        .display = unicode.display_width(before, policy) + 1,
+    };
+}
+
+pub const Lexer = struct {
+    const Self = @This();
+
+    source: []const u8,
+    pos: usize,
+    line: usize,
+    line_start: usize,
+
+    /// A leading byte order mark (see source_encoding.zig) is skipped, and
+    /// columns on the first line count from after it.
+    pub fn init(source: []const u8) Self {
+        const start = source_encoding.bom_len(source);
+        return .{ .source = source, .pos = start, .line = 1, .line_start = start };
+    }
+
+    /// Pick up lexing just after `token`, as though the Lexer had just
+    /// returned it.
+    pub fn resume_after(self: *Self, token: Token) void {
+        self.pos = token.offset;
+        self.line = token.line;
+        self.line_start = token.offset - (token.column - 1);
+        self.pos += token.raw.len;
+        self.count_lines(token.raw, token.offset);
+    }
+
+    /// The next Token, or null at the end of the source. Unterminated
+    /// Strings and block comments are a ValueError.
+    pub fn next(self: *Self) InternalError!?Token {
+        while (self.pos < self.source.len and is_inline_space(self.source[self.pos])) self.pos += 1;
+        if (self.pos == self.source.len) return null;
+
+        const start = self.pos;
+        var token = Token{
+            .kind = .Word,
+            .raw = undefined,
+            .offset = start,
+            .line = self.line,
+            .column = start - self.line_start + 1,
+        };
+
+        const chr = self.source[start];
+        if (chr == helpers.CHAR_NEWLINE) {
+            token.kind = .Newline;
+            self.pos += 1;
+        } else if (std.mem.startsWith(u8, self.source[start..], "//")) {
+            token.kind = .LineComment;
+            self.pos = std.mem.indexOfScalarPos(u8, self.source, start, helpers.CHAR_NEWLINE) orelse self.source.len;
+        } else if (start == source_encoding.bom_len(self.source) and hashbang.line_len(self.source[start..]) > 0) {
+            token.kind = .LineComment;
+            self.pos = start + hashbang.line_len(self.source[start..]);
+        } else if (chr == '{') {
+            token.kind = .BlockComment;
+            self.pos = try self.block_comment_end(start);
+        } else {
+            if (chr == helpers.CHAR_QUOTE_DBL) {
+                token.kind = .String;
+                const close = std.mem.indexOfScalarPos(u8, self.source, start + 1, helpers.CHAR_QUOTE_DBL) orelse return InternalError.ValueError;
+                self.pos = close + 1;
+            }
+
+            while (self.pos < self.source.len and !is_word_splitting(self.source[self.pos])) self.pos += 1;
+        }
+
+        token.raw = self.source[start..self.pos];
+        self.count_lines(token.raw, start);
+        return token;
+    }
+
+    fn block_comment_end(self: *Self, start: usize) InternalError!usize {
+        var depth: usize = 0;
+        var pos = start;
+
+        while (pos < self.source.len) : (pos += 1) {
+            switch (self.source[pos]) {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if (depth == 0) return pos + 1;
+                },
+                else => {},
+            }
+        }
+
+        return InternalError.ValueError;
+    }
+
+    fn count_lines(self: *Self, raw: []const u8, start: usize) void {
+        for (raw) |chr, idx| {
+            if (chr != helpers.CHAR_NEWLINE) continue;
+            self.line += 1;
+            self.line_start = start + idx + 1;
+        }
+    }
+};
+
+/// A change to a source: `old_len` bytes at `start` replaced by `new_len`
+/// others.
+pub const Edit = struct {
+    start: usize,
+    old_len: usize,
+    new_len: usize,
+};
+
+pub const Relexed = struct {
+    /// Owned by the caller.
+    tokens: []Token,
+    /// How many of them were lexed afresh, rather than carried over.
+    lexed: usize,
+};
+
+/// The Tokens of `source`, given `old`, every Token of the source before
+/// `edit` was made to it, for editors which can't afford to lex a whole file
+/// per keystroke. Only the Tokens from the one before the edit up to the
+/// first the Lexer lands on again past its end are lexed afresh; the rest
+/// are carried over, moved to wherever the edit left them. Either way, the
+/// result is exactly what lexing `source` from scratch would give, errors
+/// included.
+pub fn relex(alloc: Allocator, old: []const Token, source: []const u8, edit: Edit) !Relexed {
+    var tokens = std.ArrayList(Token).init(alloc);
+    errdefer tokens.deinit();
+
+    // A Token ending before the edit (the byte that ended it included)
+    // can't have been touched by it.
+    var first: usize = 0;
+    while (first < old.len and old[first].offset + old[first].raw.len < edit.start) first += 1;
+    for (old[0..first]) |token| try tokens.append(moved(token, source, token.offset, token.line, token.column));
+
+    var lexer = Lexer.init(source);
+    if (first > 0) lexer.resume_after(old[first - 1]);
+
+    const edit_end = edit.start + edit.new_len;
+    var next_old = first;
+    var lexed: usize = 0;
+    while (try lexer.next()) |token| {
+        if (token.offset >= edit_end) {
+            // Lexing after the edit went just as it did before once the
+            // Lexer lands where it landed before, unless that's the start of
+            // the source (after any byte order mark), now or before the edit:
+            // only there is a hashbang a LineComment.
+            const old_offset = token.offset - edit.new_len + edit.old_len;
+            const at_start = token.offset == source_encoding.bom_len(source) or old_offset <= source_encoding.UTF8_BOM.len;
+            while (!at_start and next_old < old.len and old[next_old].offset < old_offset) next_old += 1;
+            if (!at_start and next_old < old.len and old[next_old].offset == old_offset) {
+                try carry_over(&tokens, old[next_old..], source, token);
+                break;
+            }
+        }
+
+        try tokens.append(token);
+        lexed += 1;
+    }
+
+    return Relexed{ .tokens = tokens.toOwnedSlice(), .lexed = lexed };
+}
+
+/// Append `rest` to `tokens`, moved so that the first of them is at
+/// `anchor`, as lexed afresh.
+fn carry_over(tokens: *std.ArrayList(Token), rest: []const Token, source: []const u8, anchor: Token) !void {
+    const base = rest[0];
+    // Columns only move until the end of the line the edit was on.
+    var same_line = true;
+
+    for (rest) |token| {
+        const column = if (same_line) anchor.column + token.column - base.column else token.column;
+        try tokens.append(moved(
+            token,
+            source,
+            anchor.offset + (token.offset - base.offset),
+            anchor.line + (token.line - base.line),
+            column,
+        ));
+        if (std.mem.indexOfScalar(u8, token.raw, helpers.CHAR_NEWLINE) != null) same_line = false;
+    }
+}
+
+fn moved(token: Token, source: []const u8, offset: usize, line: usize, column: usize) Token {
+    return .{
+        .kind = token.kind,
+        .raw = source[offset .. offset + token.raw.len],
+        .offset = offset,
+        .line = line,
+        .column = column,
+    };
+}
+
+/// Source which lexes back into `tokens` (their kinds and raw text, at
+/// least, if not where they were), for tools which rewrite source a Token
+/// at a time. Tokens are separated by single spaces, and Newlines are line
+/// breaks, with one added after any LineComment missing its own. The caller
+/// owns the result.
+pub fn render(alloc: Allocator, tokens: []const Token) ![]u8 {
+    var out = std.ArrayList(u8).init(alloc);
+    errdefer out.deinit();
+
+    var at_line_start = true;
+    for (tokens) |token, idx| {
+        if (token.kind == .Newline) {
+            try out.append(helpers.CHAR_NEWLINE);
+            at_line_start = true;
+            continue;
+        }
+
+        if (!at_line_start) try out.append(helpers.CHAR_SPACE);
+        try out.appendSlice(token.raw);
+        at_line_start = false;
+
+        // Anything after a LineComment on the same line would be part of it.
+        const last = idx == tokens.len - 1;
+        if (token.kind == .LineComment and !last and tokens[idx + 1].kind != .Newline) {
+            try out.append(helpers.CHAR_NEWLINE);
+            at_line_start = true;
+        }
+    }
+
+    return out.toOwnedSlice();
+}
+
+/// Whitespace separating Tokens within a line. Carriage returns count, so
+/// that CRLF line endings are just line endings.
+pub fn is_inline_space(chr: u8) bool {
+    return chr == helpers.CHAR_SPACE or chr == helpers.CHAR_TAB or chr == '\r';
+}
+
+fn is_word_splitting(chr: u8) bool {
+    return is_inline_space(chr) or chr == helpers.CHAR_NEWLINE;
+}
+
+test "Lexer" {
+    var lexer = Lexer.init("1 \"a\nb\"  @DUP // hi\r\n\t{ a {b}\n} x");
+
+    const expected = [_]struct { kind: Token.Kind, raw: []const u8, line: usize, column: usize }{
+        .{ .kind = .Word, .raw = "1", .line = 1, .column = 1 },
+        .{ .kind = .String, .raw = "\"a\nb\"", .line = 1, .column = 3 },
+        .{ .kind = .Word, .raw = "@DUP", .line = 2, .column = 5 },
+        .{ .kind = .LineComment, .raw = "// hi\r", .line = 2, .column = 10 },
+        .{ .kind = .Newline, .raw = "\n", .line = 2, .column = 16 },
+        .{ .kind = .BlockComment, .raw = "{ a {b}\n}", .line = 3, .column = 2 },
+        .{ .kind = .Word, .raw = "x", .line = 4, .column = 3 },
+    };
+
+    for (expected) |want| {
+        const token = (try lexer.next()).?;
+        try expectEqual(want.kind, token.kind);
+        try expectEqualStrings(want.raw, token.raw);
+        try expectEqual(want.line, token.line);
+        try expectEqual(want.column, token.column);
+    }
+    try expect((try lexer.next()) == null);
+
+    var script = Lexer.init("#!/usr/bin/env gale\n#!not");
+    try expectEqual(Token.Kind.LineComment, (try script.next()).?.kind);
+    try expectEqual(Token.Kind.Newline, (try script.next()).?.kind);
+    try expectEqual(Token.Kind.Word, (try script.next()).?.kind);
+
+    var bom = Lexer.init("\xEF\xBB\xBF12 x");
+    const first = (try bom.next()).?;
+    try expectEqualStrings("12", first.raw);
+    try expectEqual(@as(usize, 1), first.column);
+    try expectEqual(@as(usize, 4), (try bom.next()).?.column);
+
+    var unterminated = Lexer.init("{ open");
+    try expectError(InternalError.ValueError, unterminated.next());
+}
+
+fn lex_all(alloc: Allocator, source: []const u8) ![]Token {
+    var tokens = std.ArrayList(Token).init(alloc);
+    errdefer tokens.deinit();
+
+    var lexer = Lexer.init(source);
+    while (try lexer.next()) |token| try tokens.append(token);
+    return tokens.toOwnedSlice();
+}
+
+fn expect_round_trip(source: []const u8) !void {
+    const tokens = try lex_all(testAllocator, source);
+    defer testAllocator.free(tokens);
+
+    const rendered = try render(testAllocator, tokens);
+    defer testAllocator.free(rendered);
+
+    const relexed = try lex_all(testAllocator, rendered);
+    defer testAllocator.free(relexed);
+
+    try expectEqual(tokens.len, relexed.len);
+    for (tokens) |token, idx| {
+        try expectEqual(token.kind, relexed[idx].kind);
+        try expectEqualStrings(token.raw, relexed[idx].raw);
+    }
+}
+
+test "render" {
+    const tokens = try lex_all(testAllocator, "  1   \"a b\"\t@DUP // hi\n{ a {b}\n}x");
+    defer testAllocator.free(tokens);
+
+    const rendered = try render(testAllocator, tokens);
+    defer testAllocator.free(rendered);
+    try expectEqualStrings("1 \"a b\" @DUP // hi\n{ a {b}\n} x", rendered);
+
+    // Dropping the Newline mustn't leave { a {b}\n} commented out.
+    const without_newline = [_]Token{ tokens[3], tokens[5] };
+    const kept = try render(testAllocator, &without_newline);
+    defer testAllocator.free(kept);
+    try expectEqualStrings("// hi\n{ a {b}\n}", kept);
+
+    try expect_round_trip("&@DUP :twin @DEFINE\n\n{{ Docs. }}\n: greet \"hi\" println ; // done\n");
+}
+
+fn expect_relexed(before: []const u8, after: []const u8, edit: Edit, lexed: usize) !void {
+    const old = try lex_all(testAllocator, before);
+    defer testAllocator.free(old);
+    const fresh = try lex_all(testAllocator, after);
+    defer testAllocator.free(fresh);
+
+    const relexed = try relex(testAllocator, old, after, edit);
+    defer testAllocator.free(relexed.tokens);
+
+    try expectEqual(fresh.len, relexed.tokens.len);
+    for (fresh) |want, idx| {
+        const got = relexed.tokens[idx];
+        try expectEqual(want.kind, got.kind);
+        try expectEqualStrings(want.raw, got.raw);
+        try expectEqual(want.offset, got.offset);
+        try expectEqual(want.line, got.line);
+        try expectEqual(want.column, got.column);
+    }
+    try expectEqual(lexed, relexed.lexed);
+}
+
+test "relex" {
+    const before = "1 2 @DUP\n\"a\" 3\n4 5\n";
+
+    // Growing a word relexes it, and that's all.
+    try expect_relexed(before, "1 22 @DUP\n\"a\" 3\n4 5\n", .{ .start = 2, .old_len = 1, .new_len = 2 }, 1);
+    // As does adding one, up to where the Lexer lands back on the old 3.
+    try expect_relexed(before, "1 2 @DUP\n\"a\" 9 3\n4 5\n", .{ .start = 13, .old_len = 0, .new_len = 2 }, 1);
+    // Gluing two together relexes them as one.
+    try expect_relexed(before, "1 2 @DUP\n\"a\"3\n4 5\n", .{ .start = 12, .old_len = 1, .new_len = 0 }, 1);
+    // Appending relexes the line break it's appended to, too.
+    try expect_relexed(before, "1 2 @DUP\n\"a\" 3\n4 5\n6", .{ .start = 19, .old_len = 0, .new_len = 1 }, 2);
+
+    const old = try lex_all(testAllocator, before);
+    defer testAllocator.free(old);
+    try expectError(InternalError.ValueError, relex(testAllocator, old, "1 { 2 @DUP\n\"a\" 3\n4 5\n", .{ .start = 2, .old_len = 0, .new_len = 2 }));
+
+    // Whatever's left at the start of a source might now be a hashbang, or
+    // no longer be one.
+    try expect_relexed("a #!x\n", "#!x\n", .{ .start = 0, .old_len = 2, .new_len = 0 }, 1);
+    try expect_relexed("#!x\n", "a #!x\n", .{ .start = 0, .old_len = 0, .new_len = 2 }, 3);
+}
+
+test "relex agrees with lexing from scratch" {
+    var prng = std.rand.DefaultPrng.init(0x6a1e);
+    const random = prng.random();
+
+    var before_buf: [48]u8 = undefined;
+    var after_buf: [64]u8 = undefined;
+    var round: usize = 0;
+    while (round < 1_000) : (round += 1) {
+        const before = before_buf[0..random.uintAtMost(usize, before_buf.len)];
+        test_helpers.fill_arbitrary(random, before);
+
+        const start = random.uintAtMost(usize, before.len);
+        const old_len = random.uintAtMost(usize, before.len - start);
+        const new_len = random.uintAtMost(usize, after_buf.len - (before.len - old_len));
+        const edit = Edit{ .start = start, .old_len = old_len, .new_len = new_len };
+
+        const after = after_buf[0 .. before.len - old_len + new_len];
+        std.mem.copy(u8, after, before[0..start]);
+        test_helpers.fill_arbitrary(random, after[start .. start + new_len]);
+        std.mem.copy(u8, after[start + new_len ..], before[start + old_len ..]);
+
+        const old = lex_all(testAllocator, before) catch continue;
+        defer testAllocator.free(old);
+
+        const fresh = lex_all(testAllocator, after) catch |err| {
+            try expectError(err, relex(testAllocator, old, after, edit));
+            continue;
+        };
+        defer testAllocator.free(fresh);
+
+        const relexed = try relex(testAllocator, old, after, edit);
+        defer testAllocator.free(relexed.tokens);
+        try expectEqual(fresh.len, relexed.tokens.len);
+        for (fresh) |want, idx| {
+            try expectEqual(want.kind, relexed.tokens[idx].kind);
+            try expectEqual(want.offset, relexed.tokens[idx].offset);
+            try expectEqual(want.line, relexed.tokens[idx].line);
+            try expectEqual(want.column, relexed.tokens[idx].column);
+            try expectEqualStrings(want.raw, relexed.tokens[idx].raw);
+        }
+    }
+}
+
+test "render round-trips arbitrary input" {
+    var prng = std.rand.DefaultPrng.init(0x6a1e);
+    const random = prng.random();
+
+    var buf: [64]u8 = undefined;
+    var round: usize = 0;
+    while (round < 1_000) : (round += 1) {
+        const source = buf[0..random.uintAtMost(usize, buf.len)];
+        test_helpers.fill_arbitrary(random, source);
+        expect_round_trip(source) catch |err| switch (err) {
+            // Not every source lexes in the first place.
+            InternalError.ValueError => continue,
+            else => return err,
+        };
+    }
+}
+
+test "Token.columns" {
+    const source = "1\n\t日本 \"é\" x";
+    var lexer = Lexer.init(source);
+    _ = try lexer.next();
+    _ = try lexer.next();
+    _ = try lexer.next();
+    _ = try lexer.next();
+    const x = (try lexer.next()).?;
+
+    try expectEqual(@as(usize, 14), x.column);
+    const plain = x.columns(source, .{});
+    try expectEqual(@as(usize, 9), plain.grapheme);
+    try expectEqual(@as(usize, 16), plain.display);
+    const wide = x.columns(source, .{ .tab_width = 4, .east_asian_width = true });
+    try expectEqual(@as(usize, 9), wide.grapheme);
+    try expectEqual(@as(usize, 14), wide.display);
+}
+
+test "Lexer never panics on arbitrary input" {
+    var prng = std.rand.DefaultPrng.init(0x6a1e);
+    const random = prng.random();
+
+    var buf: [64]u8 = undefined;
+    var round: usize = 0;
+    while (round < 10_000) : (round += 1) {
+        const source = buf[0..random.uintAtMost(usize, buf.len)];
+        test_helpers.fill_arbitrary(random, source);
+
+        // Every Token is a nonempty slice of the source, in order, so the
+        // Lexer always gets somewhere, and stops.
+        var lexer = Lexer.init(source);
+        var pos: usize = 0;
+        while (lexer.next() catch break) |token| {
+            try expect(token.raw.len > 0);
+            try expect(token.offset >= pos);
+            pos = token.offset + token.raw.len;
+            try expect(pos <= source.len);
+        }
+    }
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,159 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const helpers = @import("./helpers.zig");
+
+// Where @LOAD and @IMPORT look for files named without saying where they are
+// (`strings`, say, rather than `./strings.gale`): see
+// Runtime.resolve_source_path, which consults whichever directories the host
+// hands it in Options.library_path. from_environment puts together the usual
+// set, for hosts (like the CLI) that want it.
+
+/// A list of directories, separated as PATH is on this system.
+pub const ENV_VAR = "GLUUMY_PATH";
+
+/// Searched after anything given explicitly or by ENV_VAR.
+pub const DEFAULT_DIRS = [_][]const u8{
+    ".",
+    "/usr/local/share/gluumy/lib",
+    "/usr/share/gluumy/lib",
+};
+
+/// Tried after the name as given, for names without it.
+pub const EXTENSION = ".gale";
+
+/// Whether `operand` says exactly where it is, being absolute, or relative
+/// by way of `.` or `..`, in which case it's never searched for.
+pub fn is_explicit(operand: []const u8) bool {
+    if (std.fs.path.isAbsolute(operand)) return true;
+    if (std.mem.eql(u8, operand, ".") or std.mem.eql(u8, operand, "..")) return true;
+
+    inline for (.{ "./", "../", "." ++ std.fs.path.sep_str, ".." ++ std.fs.path.sep_str }) |prefix| {
+        if (std.mem.startsWith(u8, operand, prefix)) return true;
+    }
+    return false;
+}
+
+/// Where `operand` might be within `dir`, in the order to try them: as
+/// written, then with EXTENSION, unless it already has it. The caller owns
+/// both the slice and the paths within it.
+pub fn candidates(alloc: Allocator, dir: []const u8, operand: []const u8) ![][]u8 {
+    var found = std.ArrayList([]u8).init(alloc);
+    errdefer {
+        for (found.items) |path| alloc.free(path);
+        found.deinit();
+    }
+
+    const as_written = try std.fs.path.join(alloc, &.{ dir, operand });
+    errdefer alloc.free(as_written);
+    try found.append(as_written);
+
+    if (!std.mem.endsWith(u8, operand, EXTENSION)) {
+        const extended = try std.mem.concat(alloc, u8, &.{ as_written, EXTENSION });
+        errdefer alloc.free(extended);
+        try found.append(extended);
+    }
+
+    return found.toOwnedSlice();
+}
+
+/// `flags` (say, from the CLI's --lib), then each directory in ENV_VAR, if
+/// it's set, then DEFAULT_DIRS. Free the result with `free`.
+pub fn from_environment(alloc: Allocator, flags: []const []const u8) ![][]u8 {
+    var dirs = std.ArrayList([]u8).init(alloc);
+    errdefer {
+        for (dirs.items) |dir| alloc.free(dir);
+        dirs.deinit();
+    }
+
+    for (flags) |dir| try append_owned(alloc, &dirs, dir);
+
+    if (comptime helpers.HOSTED) {
+        const from_env = std.process.getEnvVarOwned(alloc, ENV_VAR) catch |err| switch (err) {
+            error.EnvironmentVariableNotFound => null,
+            else => return err,
+        };
+        if (from_env) |value| {
+            defer alloc.free(value);
+            try append_list(alloc, &dirs, value);
+        }
+    }
+
+    for (DEFAULT_DIRS) |dir| try append_owned(alloc, &dirs, dir);
+
+    return dirs.toOwnedSlice();
+}
+
+pub fn free(alloc: Allocator, dirs: [][]u8) void {
+    for (dirs) |dir| alloc.free(dir);
+    alloc.free(dirs);
+}
+
+/// Append each directory in `list` (as ENV_VAR would have it) to `dirs`,
+/// skipping empty entries.
+fn append_list(alloc: Allocator, dirs: *std.ArrayList([]u8), list: []const u8) !void {
+    var entries = std.mem.tokenize(u8, list, &.{std.fs.path.delimiter});
+    while (entries.next()) |dir| try append_owned(alloc, dirs, dir);
+}
+
+fn append_owned(alloc: Allocator, dirs: *std.ArrayList([]u8), dir: []const u8) !void {
+    const owned = try alloc.dupe(u8, dir);
+    errdefer alloc.free(owned);
+    try dirs.append(owned);
+}
+
+test "is_explicit" {
+    try expect(is_explicit("/srv/strings.gale"));
+    try expect(is_explicit("./strings.gale"));
+    try expect(is_explicit("../strings"));
+    try expect(is_explicit(".."));
+    try expect(!is_explicit("strings"));
+    try expect(!is_explicit("text/strings.gale"));
+    try expect(!is_explicit(".hidden.gale"));
+}
+
+test "candidates" {
+    const both = try candidates(testAllocator, "lib", "strings");
+    defer free(testAllocator, both);
+    try expectEqual(@as(usize, 2), both.len);
+    try expectEqualStrings("lib" ++ std.fs.path.sep_str ++ "strings", both[0]);
+    try expectEqualStrings("lib" ++ std.fs.path.sep_str ++ "strings.gale", both[1]);
+
+    const one = try candidates(testAllocator, "lib", "strings.gale");
+    defer free(testAllocator, one);
+    try expectEqual(@as(usize, 1), one.len);
+}
+
+test "append_list" {
+    var dirs = std.ArrayList([]u8).init(testAllocator);
+    defer {
+        for (dirs.items) |dir| testAllocator.free(dir);
+        dirs.deinit();
+    }
+
+    const delimiter = [_]u8{std.fs.path.delimiter};
+    try append_list(testAllocator, &dirs, "a" ++ delimiter ++ delimiter ++ "b");
+    try expectEqual(@as(usize, 2), dirs.items.len);
+    try expectEqualStrings("b", dirs.items[1]);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,83 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const expect = std.testing.expect;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const io = @import("./io.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+/// Reads a file a line at a time, holding no more of it in memory than the
+/// line at hand (and a little buffering), for files too big to reasonably
+/// slurp with @FILE-READ. As with sockets, gale code only ever sees these by
+/// the IDs the Runtime files them under (see Runtime.line_cursors).
+pub const LineCursor = struct {
+    const Self = @This();
+
+    const Reader = std.io.BufferedReader(4096, std.fs.File.Reader);
+
+    file: std.fs.File,
+    reader: Reader,
+    line: std.ArrayList(u8),
+
+    /// Takes ownership of `file`.
+    pub fn init(alloc: Allocator, file: std.fs.File) Self {
+        return Self{
+            .file = file,
+            .reader = .{ .unbuffered_reader = file.reader() },
+            .line = std.ArrayList(u8).init(alloc),
+        };
+    }
+
+    pub fn deinit(self: *Self) void {
+        self.line.deinit();
+        self.file.close();
+    }
+
+    /// The next line (without its line ending, be that \n or \r\n), or null
+    /// once the file has run dry. The line is only valid until the next call.
+    /// Lines longer than io.MAX_LINE_LEN are a LimitExceeded.
+    pub fn next(self: *Self) !?[]const u8 {
+        self.reader.reader().readUntilDelimiterArrayList(&self.line, '\n', io.MAX_LINE_LEN) catch |err| switch (err) {
+            // A final line without a trailing newline is still a line.
+            error.EndOfStream => if (self.line.items.len == 0) return null,
+            error.StreamTooLong => return InternalError.LimitExceeded,
+            else => return err,
+        };
+
+        return std.mem.trimRight(u8, self.line.items, "\r");
+    }
+};
+
+pub const LineCursorMap = std.AutoArrayHashMap(usize, LineCursor);
+
+test "lines" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile("log.txt", "one\r\n\ntwo\nthree");
+
+    var cursor = LineCursor.init(std.testing.allocator, try tmp.dir.openFile("log.txt", .{}));
+    defer cursor.deinit();
+
+    for ([_][]const u8{ "one", "", "two", "three" }) |expected| {
+        try expectEqualStrings(expected, (try cursor.next()).?);
+    }
+    try expect((try cursor.next()) == null);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,482 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const conditional = @import("./conditional.zig");
+
+const ColumnPolicy = @import("./unicode.zig").ColumnPolicy;
+const Columns = @import("./lexer.zig").Columns;
+const Lexer = @import("./lexer.zig").Lexer;
+const columns_of = @import("./lexer.zig").columns_of;
+const ParsedWord = @import("./parsed_word.zig").ParsedWord;
+const Pipeline = @import("./pipeline.zig").Pipeline;
+const Runtime = @import("./runtime.zig").Runtime;
+const Token = @import("./lexer.zig").Token;
+const Types = @import("./types.zig");
+const WordSignature = @import("./word_signature.zig").WordSignature;
+
+// `gale lint`: things about gale source which are probably mistakes, found
+// without running it. Words are judged against a Runtime's dictionary, so
+// lint against one set up just as the source will be run.
+//
+// Stack effects can only be checked as far as the words involved have
+// signatures: the first word without one ends the check for the rest of the
+// source. Words the source defines for itself (`... :name @DEFINE`) are known
+// from there on, though their effects aren't, and defining a name which
+// already means something is a ShadowedName, as is using a name the host
+// has defined more than once. Within `#if-` blocks (see conditional.zig),
+// which may well be meant for some other target, no name is unknown, and
+// past the first, the Stack's depth is anyone's guess.
+
+pub const Rule = enum {
+    /// A word which can't be read at all, like one with a stray quote.
+    InvalidWord,
+    /// A word (or &reference) with no definition.
+    UnknownWord,
+    /// A word which needs more on the Stack than the source above it leaves
+    /// there, assuming it starts out empty.
+    Underflow,
+    /// A word with several definitions, of which only the newest can ever
+    /// be reached.
+    ShadowedName,
+    /// Literals which read as something other than they look: redundant
+    /// leading zeros, negative zero, and integers too big to be integers.
+    SuspiciousLiteral,
+
+    /// The rule's name, as `gale lint` flags (and output) spell it.
+    pub fn name(self: Rule) []const u8 {
+        return switch (self) {
+            .InvalidWord => "invalid-word",
+            .UnknownWord => "unknown-word",
+            .Underflow => "underflow",
+            .ShadowedName => "shadowed-name",
+            .SuspiciousLiteral => "suspicious-literal",
+        };
+    }
+
+    pub fn from_name(rule_name: []const u8) ?Rule {
+        inline for (@typeInfo(Rule).Enum.fields) |field| {
+            const rule = @field(Rule, field.name);
+            if (std.mem.eql(u8, rule.name(), rule_name)) return rule;
+        }
+        return null;
+    }
+
+    pub fn severity(self: Rule) Severity {
+        return switch (self) {
+            .InvalidWord, .UnknownWord => .Error,
+            .Underflow, .ShadowedName, .SuspiciousLiteral => .Warning,
+        };
+    }
+};
+
+/// Each defines the word named by the Symbol before it. See nucleus_words.zig,
+/// and prelude.gale for the lowercase names.
+const DEFINE_WORDS = [_][]const u8{ "@DEFINE", "@CONSTANT", "@VALUE", "constant", "value" };
+
+fn is_define_word(name: []const u8) bool {
+    for (DEFINE_WORDS) |define_word| {
+        if (std.mem.eql(u8, name, define_word)) return true;
+    }
+    return false;
+}
+
+pub const Rules = std.EnumSet(Rule);
+
+/// Every rule, which is what `gale lint` checks unless told otherwise.
+pub const ALL_RULES = Rules.initFull();
+
+pub const Severity = enum {
+    /// Will fail if run.
+    Error,
+    /// Might not.
+    Warning,
+
+    pub fn name(self: Severity) []const u8 {
+        return switch (self) {
+            .Error => "error",
+            .Warning => "warning",
+        };
+    }
+};
+
+pub const Diagnostic = struct {
+    rule: Rule,
+    /// Where in the source the offending word starts (see Token).
+    offset: usize,
+    line: usize,
+    column: usize,
+    /// The word itself, sliced from the source.
+    word: []const u8,
+    message: []const u8,
+
+    /// See Token.columns.
+    pub fn columns(self: Diagnostic, source: []const u8, policy: ColumnPolicy) Columns {
+        return columns_of(source, self.offset, self.column, policy);
+    }
+};
+
+/// Everything `rules` finds wrong with `source`, in source order. The caller
+/// owns the returned slice, but the Diagnostics within it point into
+/// `source`. Source which can't be split into words at all (see Lexer) is
+/// a ValueError.
+pub fn lint(alloc: Allocator, rt: *Runtime, source: []const u8, rules: Rules) ![]Diagnostic {
+    var linter = Linter{
+        .rt = rt,
+        .rules = rules,
+        .found = std.ArrayList(Diagnostic).init(alloc),
+        .defined = std.StringHashMap(void).init(alloc),
+    };
+    errdefer linter.found.deinit();
+    defer linter.defined.deinit();
+
+    var lexer = Lexer.init(source);
+    while (try lexer.next()) |token| {
+        switch (token.kind) {
+            .Word, .String => try linter.check(token),
+            .LineComment, .BlockComment, .Newline => {},
+        }
+    }
+    linter.pipeline.finish() catch try linter.report(.InvalidWord, linter.pipe.?, "has no word after it to pipe into");
+
+    return linter.found.toOwnedSlice();
+}
+
+const Linter = struct {
+    const Self = @This();
+
+    rt: *Runtime,
+    rules: Rules,
+    found: std.ArrayList(Diagnostic),
+    /// Names the source has defined so far, sliced from it.
+    defined: std.StringHashMap(void),
+    /// The previous Word or String, for @DEFINE to find its name in.
+    previous: ?Token = null,
+    /// How deep the Stack is at this point in the source, as far as we can
+    /// tell, which stops being very far at the first word of unknown effect.
+    depth: ?usize = 0,
+    /// How many `#if-` blocks are open.
+    conditional_depth: usize = 0,
+    pipeline: Pipeline = .{},
+    /// The latest `|>`, which eval drops rather than running.
+    pipe: ?Token = null,
+
+    fn report(self: *Self, rule: Rule, token: Token, message: []const u8) !void {
+        if (!self.rules.contains(rule)) return;
+        try self.found.append(.{
+            .rule = rule,
+            .offset = token.offset,
+            .line = token.line,
+            .column = token.column,
+            .word = token.raw,
+            .message = message,
+        });
+    }
+
+    fn check(self: *Self, token: Token) !void {
+        const runs = self.pipeline.feed(token.raw) catch {
+            self.depth = null;
+            return try self.report(.InvalidWord, token, "has no word before it to pipe from");
+        };
+        if (!runs) {
+            self.pipe = token;
+            return;
+        }
+
+        defer self.previous = token;
+
+        if (token.kind == .Word and std.mem.indexOfScalar(u8, token.raw, '"') != null) {
+            self.depth = null;
+            return try self.report(.InvalidWord, token, "has a stray double quote in it");
+        }
+
+        if (token.kind == .Word) {
+            const directive = conditional.Directive.parse(token.raw) catch {
+                self.depth = null;
+                return try self.report(.InvalidWord, token, "isn't a condition #if- or #unless- knows");
+            };
+            if (directive) |found| return self.check_directive(found);
+        }
+
+        const parsed = ParsedWord.from_input(token.raw) catch {
+            self.depth = null;
+            return try self.report(.InvalidWord, token, "can't be read as a word or a literal");
+        };
+
+        switch (parsed) {
+            .Simple => |simple| {
+                if (looks_numeric(simple.name)) {
+                    self.depth = null;
+                    return try self.report(.SuspiciousLiteral, token, "is too big to be an integer, so it's read as a word");
+                }
+
+                if (is_define_word(simple.name)) try self.check_definition();
+
+                const word = try self.check_name(simple.name, token) orelse {
+                    self.depth = null;
+                    return;
+                };
+
+                if (simple.semantics.stash_before_lookup or simple.semantics.hoist_after_result) {
+                    self.depth = null;
+                } else {
+                    try self.apply_effect(word, token);
+                }
+            },
+            .Ref => |name| {
+                _ = try self.check_name(name, token);
+                self.push();
+            },
+            .SignedInt => |num| {
+                if (num == 0 and token.raw[0] == '-') try self.report(.SuspiciousLiteral, token, "is negative zero, which is just zero");
+                if (has_leading_zeros(token.raw)) try self.report(.SuspiciousLiteral, token, "has leading zeros, which don't make it octal");
+                self.push();
+            },
+            .UnsignedInt => {
+                if (has_leading_zeros(token.raw)) try self.report(.SuspiciousLiteral, token, "has leading zeros, which don't make it octal");
+                self.push();
+            },
+            .NumFloat => |num| {
+                if (num == 0 and std.math.signbit(num)) try self.report(.SuspiciousLiteral, token, "is negative zero");
+                self.push();
+            },
+            .String, .Symbol => self.push(),
+        }
+    }
+
+    fn check_directive(self: *Self, directive: conditional.Directive) void {
+        switch (directive) {
+            .If, .Unless => {
+                self.conditional_depth += 1;
+                self.depth = null;
+            },
+            .Else => {},
+            .End => self.conditional_depth -|= 1,
+        }
+    }
+
+    /// Look up `name`, reporting it if it's unknown or shadowed. Names the
+    /// source defines are known, but have no word to show for it yet.
+    fn check_name(self: *Self, name: []const u8, token: Token) !?*Types.HeapedWord {
+        const word = try self.rt.find_word(name) orelse {
+            if (!self.defined.contains(name) and self.conditional_depth == 0) {
+                try self.report(.UnknownWord, token, "isn't defined");
+            }
+            return null;
+        };
+
+        if (self.rt.definition_count(name) > 1) {
+            try self.report(.ShadowedName, token, "has several definitions, of which only the newest is ever used");
+        }
+
+        return word;
+    }
+
+    /// Note the name @DEFINE is about to define, if it's written right before
+    /// it as a Symbol, reporting it if it already means something.
+    fn check_definition(self: *Self) !void {
+        const symbol = self.previous orelse return;
+        if (symbol.kind != .Word or symbol.raw.len < 2 or symbol.raw[0] != ':') return;
+
+        const name = symbol.raw[1..];
+        if (self.defined.contains(name) or (try self.rt.find_word(name)) != null) {
+            try self.report(.ShadowedName, symbol, "is already defined, and this shadows it");
+        }
+        try self.defined.put(name, {});
+    }
+
+    fn push(self: *Self) void {
+        if (self.depth) |depth| self.depth = depth + 1;
+    }
+
+    fn apply_effect(self: *Self, word: *Types.HeapedWord, token: Token) !void {
+        const depth = self.depth orelse return;
+        const effect = effect_of(word) orelse {
+            self.depth = null;
+            return;
+        };
+
+        if (effect.needs > depth) {
+            self.depth = null;
+            return try self.report(.Underflow, token, "needs more on the stack than there is by now");
+        }
+
+        self.depth = depth - effect.takes + effect.gives;
+    }
+};
+
+const Effect = struct {
+    /// How many Objects must be on the Stack beforehand...
+    needs: usize,
+    /// ...how many of those are gone afterwards...
+    takes: usize,
+    /// ...and how many are added on top.
+    gives: usize,
+};
+
+/// What `word` does to the depth of the Stack, if its signature says, and if
+/// it ever returns at all.
+fn effect_of(word: *Types.HeapedWord) ?Effect {
+    const state = (word.value orelse return null).signature orelse return null;
+    const sig: *WordSignature = switch (state) {
+        .Declared, .Inferred => |sig| sig,
+    };
+
+    return switch (sig.*) {
+        .SideEffectary => Effect{ .needs = 0, .takes = 0, .gives = 0 },
+        .Nullary => |gives| Effect{ .needs = 0, .takes = 0, .gives = gives.len },
+        .NullarySingle => Effect{ .needs = 0, .takes = 0, .gives = 1 },
+        .PurelyConsuming => |takes| Effect{ .needs = takes.len, .takes = takes.len, .gives = 0 },
+        .PurelyAdditive => |additive| Effect{ .needs = additive.expects.len, .takes = 0, .gives = additive.gives.len },
+        .Mutative => |mutative| Effect{ .needs = mutative.before.len, .takes = mutative.before.len, .gives = mutative.after.len },
+        .NullaryTerminal, .ConsumingTerminal => null,
+    };
+}
+
+fn looks_numeric(name: []const u8) bool {
+    const digits = if (name.len > 1 and (name[0] == '-' or name[0] == '+')) name[1..] else name;
+    if (digits.len == 0) return false;
+    for (digits) |chr| if (!std.ascii.isDigit(chr)) return false;
+    return true;
+}
+
+fn has_leading_zeros(raw: []const u8) bool {
+    const digits = if (raw[0] == '-' or raw[0] == '+') raw[1..] else raw;
+    return digits.len > 1 and digits[0] == '0';
+}
+
+test "lint" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    // Shadow @SWAP with itself.
+    try rt.define_word("@SWAP", (try rt.find_word("@SWAP")).?);
+
+    const found = try lint(testAllocator, &rt,
+        \\{ Some words. } 007 -0 @SWAP
+        \\mystery &@DUP 99999999999999999999999 // and a comment
+    , ALL_RULES);
+    defer testAllocator.free(found);
+
+    const expected = [_]struct { rule: Rule, word: []const u8, line: usize, column: usize }{
+        .{ .rule = .SuspiciousLiteral, .word = "007", .line = 1, .column = 17 },
+        .{ .rule = .SuspiciousLiteral, .word = "-0", .line = 1, .column = 21 },
+        .{ .rule = .ShadowedName, .word = "@SWAP", .line = 1, .column = 24 },
+        .{ .rule = .UnknownWord, .word = "mystery", .line = 2, .column = 1 },
+        .{ .rule = .SuspiciousLiteral, .word = "99999999999999999999999", .line = 2, .column = 15 },
+    };
+
+    try expectEqual(expected.len, found.len);
+    for (expected) |want, idx| {
+        try expectEqual(want.rule, found[idx].rule);
+        try expectEqualStrings(want.word, found[idx].word);
+        try expectEqual(want.line, found[idx].line);
+        try expectEqual(want.column, found[idx].column);
+    }
+
+    const quiet = try lint(testAllocator, &rt, "mystery 007", Rules.init(.{ .UnknownWord = true }));
+    defer testAllocator.free(quiet);
+    try expectEqual(@as(usize, 1), quiet.len);
+    try expectEqual(Rule.UnknownWord, quiet[0].rule);
+}
+
+test "Diagnostic.columns" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    const source = "\t\"日本\" mystery";
+    const found = try lint(testAllocator, &rt, source, ALL_RULES);
+    defer testAllocator.free(found);
+
+    try expectEqual(@as(usize, 1), found.len);
+    try expectEqual(@as(usize, 11), found[0].column);
+    const columns = found[0].columns(source, .{ .tab_width = 4, .east_asian_width = true });
+    try expectEqual(@as(usize, 7), columns.grapheme);
+    try expectEqual(@as(usize, 12), columns.display);
+}
+
+test "lint: definitions" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    const found = try lint(testAllocator, &rt, "&@DUP :twin @DEFINE 1 twin\n&@DROP :@DUP @DEFINE", ALL_RULES);
+    defer testAllocator.free(found);
+
+    try expectEqual(@as(usize, 1), found.len);
+    try expectEqual(Rule.ShadowedName, found[0].rule);
+    try expectEqualStrings(":@DUP", found[0].word);
+    try expectEqual(@as(usize, 2), found[0].line);
+}
+
+test "lint: conditionals" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    const found = try lint(testAllocator, &rt, "#if-target:uxn uxn/beep #end mystery #if-weather:fine", ALL_RULES);
+    defer testAllocator.free(found);
+
+    try expectEqual(@as(usize, 2), found.len);
+    try expectEqual(Rule.UnknownWord, found[0].rule);
+    try expectEqualStrings("mystery", found[0].word);
+    try expectEqual(Rule.InvalidWord, found[1].rule);
+}
+
+test "lint: pipelines" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    const found = try lint(testAllocator, &rt, "|> 1 |> 2 |> @SWAP |> |> @DROP |>", ALL_RULES);
+    defer testAllocator.free(found);
+
+    const columns = [_]usize{ 1, 23, 32 };
+    try expectEqual(columns.len, found.len);
+    for (columns) |column, idx| {
+        try expectEqual(Rule.InvalidWord, found[idx].rule);
+        try expectEqualStrings("|>", found[idx].word);
+        try expectEqual(column, found[idx].column);
+    }
+}
+
+test "lint: underflow, as far as signatures go" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    // @LIT hands out words with signatures, which is the easiest way to come
+    // by some.
+    try rt.eval("1 @LIT");
+    var lit = try rt.stack_pop();
+    defer rt.release_heaped_object_reference(&lit);
+    try rt.define_word("one", lit.Word);
+
+    const found = try lint(testAllocator, &rt, "one one", ALL_RULES);
+    defer testAllocator.free(found);
+    try expectEqual(@as(usize, 0), found.len);
+
+    try expect(effect_of(lit.Word).?.gives == 1);
+    try expect(effect_of((try rt.find_word("@DUP")).?) == null);
+}
+
+test "Rule names" {
+    try expectEqual(Rule.ShadowedName, Rule.from_name("shadowed-name").?);
+    try expect(Rule.from_name("nope") == null);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,304 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const nucleus_words = @import("./nucleus_words.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+// Named locals, for the words where keeping track of what's where on the
+// Stack gets in the way of what the word is doing:
+//
+//     :x :y 2  &y &x @COMPOSE &x @COMPOSE  @LOCALS :yxx @DEFINE
+//     1 2 yxx   // 2 1 1
+//
+// @LOCALS takes a body, and gives back a word which binds the values beneath
+// it (@n to the first name given, through @1 to the last) for as long as the
+// body runs, and in which each call site naming one of them (`&x`) has been
+// swapped out for a fetch of that local. Call sites are only swapped out
+// within the body itself, and the Compounds it's directly made of, so locals
+// are invisible to the words it calls by name, and to any word pushed (by
+// @LIT, say) rather than run. Names which aren't locals are looked up as
+// ever.
+//
+// Bound values live off the Stack in the Runtime's Locals, one run of values
+// per @WITH-LOCALS being run, innermost last, which is what the fetches read
+// from: @LOCALS compiles down to @WITH-LOCALS and @LOCAL.
+
+/// Every locals word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@LOCAL", LOCAL },
+    .{ "@LOCALS", LOCALS },
+    .{ "@WITH-LOCALS", WITH_LOCALS },
+};
+
+/// Define every locals word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// The values bound by every @WITH-LOCALS still running.
+pub const Locals = struct {
+    const Self = @This();
+
+    /// Each holds a reference.
+    values: std.ArrayListUnmanaged(Object) = .{},
+    /// Where in `values` each binding's run starts, innermost last.
+    bases: std.ArrayListUnmanaged(usize) = .{},
+
+    /// Bindings only last as long as the words they're for run, so by the
+    /// time a Runtime is torn down, there's nothing left to release.
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        self.values.deinit(alloc);
+        self.bases.deinit(alloc);
+    }
+};
+
+/// @LOCALS ( Symbol ... Symbol UnsignedInt Word -> Word )
+///
+/// The Word, with the UnsignedInt many names beneath it bound as locals (see
+/// above). Naming the same local twice is a ValueError.
+pub fn LOCALS(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Word or far.* != .UnsignedInt) return InternalError.TypeError;
+    const count = far.UnsignedInt;
+
+    const depth = runtime.stack.depth();
+    if (depth - 2 < count) return StackManipulationError.Underflow;
+
+    const names = try runtime.alloc.alloc(*Types.HeapedSymbol, count);
+    defer runtime.alloc.free(names);
+    for (names) |*name, idx| {
+        const obj = runtime.stack.nth_from_top(count + 1 - idx).?;
+        if (obj.* != .Symbol) return InternalError.TypeError;
+        // Symbols are interned, so alike names are one and the same.
+        for (names[0..idx]) |earlier| {
+            if (earlier == obj.Symbol) return InternalError.ValueError;
+        }
+        name.* = obj.Symbol;
+    }
+
+    const body = try bind_names(runtime, peek.near.Word, names);
+    defer release_word(runtime, body);
+    const word = try binding_word(runtime, body, count);
+
+    var idx: usize = 0;
+    while (idx < count + 2) : (idx += 1) try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_raw_word(word);
+}
+
+/// @WITH-LOCALS ( @n ... @1 Word UnsignedInt -> * )
+///
+/// Bind the UnsignedInt many values beneath the Word, and run it. Within it
+/// (and nowhere else), @LOCAL reads them back. The values are released once
+/// the Word is done, whether or not it succeeded.
+pub fn WITH_LOCALS(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt or far.* != .Word) return InternalError.TypeError;
+    const count = peek.near.UnsignedInt;
+    if (runtime.stack.depth() - 2 < count) return StackManipulationError.Underflow;
+
+    const locals = &runtime.locals;
+    const base = locals.values.items.len;
+    try locals.values.ensureUnusedCapacity(runtime.alloc, count);
+    try locals.bases.append(runtime.alloc, base);
+    defer {
+        for (locals.values.items[base..]) |*value| runtime.release_heaped_object_reference(value);
+        locals.values.shrinkRetainingCapacity(base);
+        _ = locals.bases.pop();
+    }
+
+    _ = try runtime.stack_pop();
+    var body = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&body);
+
+    // Popped @1 first, and so filled in from the end.
+    locals.values.items.len += count;
+    var unfilled = count;
+    while (unfilled > 0) : (unfilled -= 1) {
+        // The depth was checked above, so there's no running out here.
+        locals.values.items[base + unfilled - 1] = runtime.stack_pop() catch unreachable;
+    }
+
+    try runtime.run_word(body.Word);
+}
+
+/// @LOCAL ( UnsignedInt -> @1 )
+///
+/// The value bound at that index (counting from 0, the first name) by the
+/// innermost running @WITH-LOCALS. Indexes past its locals, or there being
+/// no locals at all, are a ValueError.
+pub fn LOCAL(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+
+    const locals = &runtime.locals;
+    if (locals.bases.items.len == 0) return InternalError.ValueError;
+    const base = locals.bases.items[locals.bases.items.len - 1];
+    if (peeked.UnsignedInt >= locals.values.items.len - base) return InternalError.ValueError;
+
+    var value = try locals.values.items[base + peeked.UnsignedInt].ref();
+    defer runtime.release_heaped_object_reference(&value);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(value);
+}
+
+/// `word`, with every call site naming one of `names` swapped out for a
+/// fetch of that local, returned with a reference the caller then owns.
+/// Words with nothing to swap out come back as they were.
+fn bind_names(runtime: *Runtime, word: *Types.HeapedWord, names: []const *Types.HeapedSymbol) anyerror!*Types.HeapedWord {
+    if (word.value) |inner| switch (inner.impl) {
+        .CallSite => |site| for (names) |name, idx| {
+            if (site.name == name) return fetch_word(runtime, idx);
+        },
+        .Compound => |compound| return bind_compound(runtime, word, compound, names),
+        else => {},
+    };
+
+    try word.increment();
+    return word;
+}
+
+fn bind_compound(
+    runtime: *Runtime,
+    word: *Types.HeapedWord,
+    compound: []*Types.HeapedWord,
+    names: []const *Types.HeapedSymbol,
+) anyerror!*Types.HeapedWord {
+    const inner = try runtime.alloc.alloc(*Types.HeapedWord, compound.len);
+    var filled: usize = 0;
+    errdefer {
+        for (inner[0..filled]) |member| release_word(runtime, member);
+        runtime.alloc.free(inner);
+    }
+
+    var changed = false;
+    while (filled < compound.len) : (filled += 1) {
+        inner[filled] = try bind_names(runtime, compound[filled], names);
+        if (inner[filled] != compound[filled]) changed = true;
+    }
+
+    if (!changed) {
+        for (inner) |member| release_word(runtime, member);
+        runtime.alloc.free(inner);
+        try word.increment();
+        return word;
+    }
+
+    const bound = try runtime.word_from_compound_impl(inner, null);
+    bound.increment() catch unreachable;
+    return bound;
+}
+
+/// `idx @LOCAL`, as a word.
+fn fetch_word(runtime: *Runtime, idx: usize) !*Types.HeapedWord {
+    const index = Object{ .UnsignedInt = idx };
+    return compiled_word(runtime, &index, "@LOCAL");
+}
+
+/// `body count @WITH-LOCALS`, as a word.
+fn binding_word(runtime: *Runtime, body: *Types.HeapedWord, count: usize) !*Types.HeapedWord {
+    const wrapped = Object{ .Word = body };
+    const inner = try runtime.alloc.alloc(*Types.HeapedWord, 3);
+    var filled: usize = 0;
+    errdefer {
+        for (inner[0..filled]) |member| release_word(runtime, member);
+        runtime.alloc.free(inner);
+    }
+
+    inner[0] = try nucleus_words.literal_word(runtime, &wrapped);
+    inner[0].increment() catch unreachable;
+    filled = 1;
+    const counted = Object{ .UnsignedInt = count };
+    inner[1] = try nucleus_words.literal_word(runtime, &counted);
+    inner[1].increment() catch unreachable;
+    filled = 2;
+    inner[2] = try installed_word(runtime, "@WITH-LOCALS");
+    filled = 3;
+
+    return try runtime.word_from_compound_impl(inner, null);
+}
+
+/// `literal primitive`, as a word, returned with a reference the caller then
+/// owns.
+fn compiled_word(runtime: *Runtime, literal: *const Object, primitive: []const u8) !*Types.HeapedWord {
+    const inner = try runtime.alloc.alloc(*Types.HeapedWord, 2);
+    var filled: usize = 0;
+    errdefer {
+        for (inner[0..filled]) |member| release_word(runtime, member);
+        runtime.alloc.free(inner);
+    }
+
+    inner[0] = try nucleus_words.literal_word(runtime, literal);
+    inner[0].increment() catch unreachable;
+    filled = 1;
+    inner[1] = try installed_word(runtime, primitive);
+    filled = 2;
+
+    const word = try runtime.word_from_compound_impl(inner, null);
+    word.increment() catch unreachable;
+    return word;
+}
+
+/// The word installed as `name`, referenced for the caller, so that what
+/// @LOCALS compiles to reads (to @SEE, and to images) as it would have been
+/// written by hand.
+fn installed_word(runtime: *Runtime, name: []const u8) !*Types.HeapedWord {
+    const word = (try runtime.find_word(name)) orelse return InternalError.UnknownWord;
+    try word.increment();
+    return word;
+}
+
+fn release_word(runtime: *Runtime, word: *Types.HeapedWord) void {
+    var owned = Object{ .Word = word };
+    runtime.release_heaped_object_reference(&owned);
+}
+
+test "@LOCALS" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval(":x :y 2 &y &x @COMPOSE &x @COMPOSE @LOCALS :yxx @DEFINE");
+    try runtime.eval("1 2 yxx");
+    try expectEqual(@as(usize, 3), runtime.stack.depth());
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+
+    // Words called by name don't see their caller's locals, and running out
+    // of locals (here, by failing) unbinds them all the same.
+    try runtime.eval("&x :peek @DEFINE :x 1 &peek @LOCALS");
+    try expectError(InternalError.UnknownWord, runtime.eval("5 @SWAP @CALL"));
+    try expectEqual(@as(usize, 0), runtime.locals.values.items.len);
+    try expectEqual(@as(usize, 0), runtime.locals.bases.items.len);
+
+    try expectError(InternalError.ValueError, runtime.eval(":x :x 2 &x @LOCALS"));
+    try runtime.eval("@DROP @DROP @DROP @DROP");
+    try expectError(InternalError.ValueError, runtime.eval("0 @LOCAL"));
+    try runtime.eval("@DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
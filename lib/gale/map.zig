@@ -0,0 +1,114 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+
+const Object = @import("./object.zig").Object;
+
+/// A collection of Objects, each filed under a (byte)string key, which
+/// remembers the order its keys were first put in. This is what words
+/// handing back "records" (say, a subprocess's output and exit status) use,
+/// rather than inventing a Shape for each.
+///
+/// Maps aren't synchronized: like Arrays, they're built up before being
+/// placed on a Stack, and are best treated as read-only after.
+pub const Map = struct {
+    const Self = @This();
+
+    /// We own the keys, and hold a reference to each of the values.
+    entries: std.StringArrayHashMapUnmanaged(Object),
+
+    pub fn init() Self {
+        return Self{ .entries = .{} };
+    }
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        var iter = self.entries.iterator();
+        while (iter.next()) |entry| {
+            alloc.free(entry.key_ptr.*);
+            entry.value_ptr.deinit(alloc);
+        }
+        self.entries.deinit(alloc);
+    }
+
+    pub fn count(self: *const Self) usize {
+        return self.entries.count();
+    }
+
+    /// The value filed under `key`, if any, without a new reference.
+    pub fn get(self: *const Self, key: []const u8) ?Object {
+        return self.entries.get(key);
+    }
+
+    pub fn keys(self: *const Self) []const []const u8 {
+        return self.entries.keys();
+    }
+
+    /// File `value` (taking over its reference) under a copy of `key`,
+    /// releasing whatever was there before.
+    pub fn put(self: *Self, alloc: Allocator, key: []const u8, value: Object) !void {
+        const result = try self.entries.getOrPut(alloc, key);
+        if (result.found_existing) {
+            result.value_ptr.deinit(alloc);
+        } else {
+            result.key_ptr.* = alloc.dupe(u8, key) catch |err| {
+                _ = self.entries.pop();
+                return err;
+            };
+        }
+        result.value_ptr.* = value;
+    }
+
+    /// Whether both Maps hold equal (see Object.eql) values under the same
+    /// keys, regardless of the order they were put in.
+    pub fn eql(self: *const Self, other: *const Self) bool {
+        if (self.count() != other.count()) return false;
+
+        var iter = self.entries.iterator();
+        while (iter.next()) |entry| {
+            const theirs = other.get(entry.key_ptr.*) orelse return false;
+            if (!entry.value_ptr.eql(theirs)) return false;
+        }
+
+        return true;
+    }
+
+    test "put, get, and eql" {
+        const alloc = std.testing.allocator;
+
+        var map = Self.init();
+        defer map.deinit(alloc);
+        try map.put(alloc, "status", .{ .UnsignedInt = 1 });
+        try map.put(alloc, "status", .{ .UnsignedInt = 0 });
+        try map.put(alloc, "ok", .{ .Boolean = true });
+
+        try expectEqual(@as(usize, 2), map.count());
+        try expectEqual(@as(usize, 0), map.get("status").?.UnsignedInt);
+        try expect(map.get("nope") == null);
+        try expect(std.mem.eql(u8, "status", map.keys()[0]));
+
+        var other = Self.init();
+        defer other.deinit(alloc);
+        try other.put(alloc, "ok", .{ .Boolean = true });
+        try expect(!map.eql(&other));
+        try other.put(alloc, "status", .{ .UnsignedInt = 0 });
+        try expect(map.eql(&other));
+    }
+};
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,103 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const conversion = @import("./conversion.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every Map word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@MAP-GET", MAP_GET },
+    .{ "@MAP-KEYS", MAP_KEYS },
+};
+
+/// Define every Map word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @MAP-GET ( Map String <- @1 )
+///
+/// Whatever's filed under the given key, or `:null` (see
+/// conversion.NULL_SYMBOL) if nothing is.
+pub fn MAP_GET(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .Map) return InternalError.TypeError;
+
+    const found = far.Map.value.?.get(peek.near.String.value.?) orelse
+        Object{ .Symbol = (try runtime.get_or_put_symbol(conversion.NULL_SYMBOL)).value_ptr };
+
+    // Held on to until it's pushed, lest dropping the Map take it with it.
+    var value = try found.ref();
+    defer runtime.release_heaped_object_reference(&value);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(value);
+}
+
+/// @MAP-KEYS ( Map <- Array )
+///
+/// The Map's keys, as Strings, in the order they were first put in.
+pub fn MAP_KEYS(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Map) return InternalError.TypeError;
+
+    const keys = try conversion.to_object(runtime, peeked.Map.value.?.keys());
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(keys);
+}
+
+test "@MAP-GET and @MAP-KEYS" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    const map = try runtime.new_map();
+    try map.value.?.put(runtime.alloc, "one", .{ .UnsignedInt = 1 });
+    try map.value.?.put(runtime.alloc, "two", .{ .UnsignedInt = 2 });
+    try runtime.stack_push_map(map);
+
+    try runtime.eval("@DUP @MAP-KEYS");
+    var keys = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&keys);
+    try expectEqual(@as(usize, 2), keys.Array.value.?.items.len);
+    try expectEqualStrings("two", keys.Array.value.?.items[1].String.value.?);
+
+    try runtime.eval("@DUP \"two\" @MAP-GET");
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("\"three\" @MAP-GET");
+    var missing = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&missing);
+    try expectEqualStrings(conversion.NULL_SYMBOL, missing.Symbol.value.?);
+
+    try expectError(InternalError.TypeError, runtime.eval("1 \"one\" @MAP-GET"));
+    var key = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&key);
+    _ = try runtime.stack_pop();
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,272 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectApproxEqAbs = std.testing.expectApproxEqAbs;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every floating point math word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@SQRT", SQRT },
+    .{ "@POW", POW },
+    .{ "@LN", LN },
+    .{ "@EXP", EXP },
+    .{ "@SIN", SIN },
+    .{ "@COS", COS },
+    .{ "@TAN", TAN },
+    .{ "@ASIN", ASIN },
+    .{ "@ACOS", ACOS },
+    .{ "@ATAN", ATAN },
+    .{ "@ATAN2", ATAN2 },
+    .{ "@FLOOR", FLOOR },
+    .{ "@CEIL", CEIL },
+    .{ "@ROUND", ROUND },
+    .{ "@NAN?", IS_NAN },
+    .{ "@INF?", IS_INF },
+};
+
+/// Define every floating point math word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+// Floats are Zig's f64s, and these follow IEEE 754 wherever a result is
+// undefined: `-1.0 @SQRT` is NaN, not an error, and `0.0 @LN` is negative
+// infinity. @NAN? and @INF? are there to catch those afterwards. Anything
+// but a Float is a TypeError: integers have to be converted first. Angles
+// are in radians.
+
+/// @SQRT ( Float <- Float )
+pub fn SQRT(runtime: *Runtime) anyerror!void {
+    try unary(runtime, sqrt);
+}
+
+/// @POW ( Float Float <- Float )
+///
+/// The lower Float raised to the power of the upper: `2.0 10.0 @POW` is
+/// 1024.0.
+pub fn POW(runtime: *Runtime) anyerror!void {
+    try binary(runtime, pow);
+}
+
+/// @LN ( Float <- Float )
+///
+/// The natural logarithm.
+pub fn LN(runtime: *Runtime) anyerror!void {
+    try unary(runtime, ln);
+}
+
+/// @EXP ( Float <- Float )
+///
+/// e raised to the power of the Float.
+pub fn EXP(runtime: *Runtime) anyerror!void {
+    try unary(runtime, exp);
+}
+
+/// @SIN ( Float <- Float )
+pub fn SIN(runtime: *Runtime) anyerror!void {
+    try unary(runtime, sin);
+}
+
+/// @COS ( Float <- Float )
+pub fn COS(runtime: *Runtime) anyerror!void {
+    try unary(runtime, cos);
+}
+
+/// @TAN ( Float <- Float )
+pub fn TAN(runtime: *Runtime) anyerror!void {
+    try unary(runtime, tan);
+}
+
+/// @ASIN ( Float <- Float )
+pub fn ASIN(runtime: *Runtime) anyerror!void {
+    try unary(runtime, asin);
+}
+
+/// @ACOS ( Float <- Float )
+pub fn ACOS(runtime: *Runtime) anyerror!void {
+    try unary(runtime, acos);
+}
+
+/// @ATAN ( Float <- Float )
+pub fn ATAN(runtime: *Runtime) anyerror!void {
+    try unary(runtime, atan);
+}
+
+/// @ATAN2 ( Float Float <- Float )
+///
+/// The angle of the point (x, y), y being the lower Float and x the upper,
+/// from the positive x axis, between -pi and pi.
+pub fn ATAN2(runtime: *Runtime) anyerror!void {
+    try binary(runtime, atan2);
+}
+
+/// @FLOOR ( Float <- Float )
+pub fn FLOOR(runtime: *Runtime) anyerror!void {
+    try unary(runtime, floor);
+}
+
+/// @CEIL ( Float <- Float )
+pub fn CEIL(runtime: *Runtime) anyerror!void {
+    try unary(runtime, ceil);
+}
+
+/// @ROUND ( Float <- Float )
+///
+/// The nearest whole Float, halves rounded away from zero.
+pub fn ROUND(runtime: *Runtime) anyerror!void {
+    try unary(runtime, round);
+}
+
+/// @NAN? ( Float <- Boolean )
+pub fn IS_NAN(runtime: *Runtime) anyerror!void {
+    try predicate(runtime, is_nan);
+}
+
+/// @INF? ( Float <- Boolean )
+///
+/// Whether the Float is infinite, either way.
+pub fn IS_INF(runtime: *Runtime) anyerror!void {
+    try predicate(runtime, is_inf);
+}
+
+// Builtins and std.math's generic functions can't be passed around as
+// `fn (f64) f64`s, so these stand in for them.
+fn sqrt(x: f64) f64 {
+    return @sqrt(x);
+}
+fn ln(x: f64) f64 {
+    return @log(x);
+}
+fn exp(x: f64) f64 {
+    return @exp(x);
+}
+fn sin(x: f64) f64 {
+    return @sin(x);
+}
+fn cos(x: f64) f64 {
+    return @cos(x);
+}
+fn tan(x: f64) f64 {
+    return @tan(x);
+}
+fn floor(x: f64) f64 {
+    return @floor(x);
+}
+fn ceil(x: f64) f64 {
+    return @ceil(x);
+}
+fn round(x: f64) f64 {
+    return @round(x);
+}
+fn asin(x: f64) f64 {
+    return std.math.asin(x);
+}
+fn acos(x: f64) f64 {
+    return std.math.acos(x);
+}
+fn atan(x: f64) f64 {
+    return std.math.atan(x);
+}
+fn is_nan(x: f64) bool {
+    return std.math.isNan(x);
+}
+fn is_inf(x: f64) bool {
+    return std.math.isInf(x);
+}
+fn pow(x: f64, y: f64) f64 {
+    return std.math.pow(f64, x, y);
+}
+fn atan2(y: f64, x: f64) f64 {
+    return std.math.atan2(f64, y, x);
+}
+
+fn peek_float(runtime: *Runtime) !f64 {
+    const top = try runtime.stack_peek();
+    if (top.* != .Float) return InternalError.TypeError;
+    return top.Float;
+}
+
+fn unary(runtime: *Runtime, comptime f: fn (f64) f64) !void {
+    const result = f(try peek_float(runtime));
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_float(result);
+}
+
+fn binary(runtime: *Runtime, comptime f: fn (f64, f64) f64) !void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Float or far.* != .Float) return InternalError.TypeError;
+
+    const result = f(far.Float, peek.near.Float);
+    _ = try runtime.stack_pop_pair();
+    try runtime.stack_push_float(result);
+}
+
+fn predicate(runtime: *Runtime, comptime f: fn (f64) bool) !void {
+    const result = f(try peek_float(runtime));
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_bool(result);
+}
+
+fn expect_float(runtime: *Runtime, source: []const u8, expected: f64) !void {
+    try runtime.eval(source);
+    try expectApproxEqAbs(expected, try runtime.stack_pop_value(f64), 0.000001);
+}
+
+test "math" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_float(&runtime, "2.0 @SQRT", std.math.sqrt2);
+    try expect_float(&runtime, "2.0 10.0 @POW", 1024.0);
+    try expect_float(&runtime, "1.0 @EXP @LN", 1.0);
+    try expect_float(&runtime, "0.0 @COS", 1.0);
+    try expect_float(&runtime, "1.0 @ATAN 4.0 @MUL", std.math.pi);
+    try expect_float(&runtime, "-1.0 0.0 @ATAN2", -std.math.pi / 2.0);
+    try expect_float(&runtime, "1.0 @ASIN @SIN", 1.0);
+    try expect_float(&runtime, "-2.5 @FLOOR", -3.0);
+    try expect_float(&runtime, "-2.5 @CEIL", -2.0);
+    try expect_float(&runtime, "-2.5 @ROUND", -3.0);
+
+    try expectError(InternalError.TypeError, runtime.eval("4 @SQRT"));
+    try runtime.eval("@DROP");
+    try expectError(InternalError.TypeError, runtime.eval("2.0 3 @POW"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test "NAN? and INF?" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("-1.0 @SQRT @NAN?");
+    try expect((try runtime.stack_pop()).Boolean);
+    try runtime.eval("0.0 @LN @INF?");
+    try expect((try runtime.stack_pop()).Boolean);
+    try runtime.eval("1.0 @LN @INF?");
+    try expect(!(try runtime.stack_pop()).Boolean);
+    try runtime.eval("1.0 @NAN?");
+    try expect(!(try runtime.stack_pop()).Boolean);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
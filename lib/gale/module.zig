@@ -0,0 +1,71 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+/// A file imported with Runtime.import_module (or @IMPORT), whose unqualified
+/// definitions land in `vocabulary` (see Runtime.define_word_here). Several
+/// files can share a vocabulary, each extending it.
+pub const Module = struct {
+    /// Owned by the Runtime.
+    vocabulary: []u8,
+    /// Set for as long as the file is being evaluated, so that importing it
+    /// again in the meantime can be caught as the circular import it is.
+    loading: bool,
+};
+
+/// Modules, by their files' canonical (real) paths, which the Runtime owns.
+pub const ModuleMap = std.StringArrayHashMap(Module);
+
+/// One vocabulary in a Runtime's search order: unqualified names which
+/// aren't defined as-is are looked up within it, or, if it was imported under
+/// a prefix, names qualified by that prefix are looked up within it instead.
+/// Both strings are owned by the Runtime.
+pub const SearchEntry = struct {
+    const Self = @This();
+
+    vocabulary: []u8,
+    prefix: ?[]u8,
+
+    pub fn init(alloc: Allocator, vocabulary: []const u8, prefix: ?[]const u8) !Self {
+        const owned_vocabulary = try alloc.dupe(u8, vocabulary);
+        errdefer alloc.free(owned_vocabulary);
+
+        return Self{
+            .vocabulary = owned_vocabulary,
+            .prefix = if (prefix) |given| try alloc.dupe(u8, given) else null,
+        };
+    }
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        alloc.free(self.vocabulary);
+        if (self.prefix) |prefix| alloc.free(prefix);
+    }
+};
+
+/// The vocabulary a module at `path` defines into: its file name, sans
+/// extension, so `lib/strings.gale` defines `strings/whatever`.
+pub fn vocabulary_for_path(path: []const u8) []const u8 {
+    return std.fs.path.stem(path);
+}
+
+test "vocabulary_for_path" {
+    try expectEqualStrings("strings", vocabulary_for_path("lib/strings.gale"));
+    try expectEqualStrings("strings", vocabulary_for_path("/srv/gale/strings"));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,284 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const capabilities = @import("./capabilities.zig");
+const helpers = @import("./helpers.zig");
+const module = @import("./module.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every module word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@IMPORT", IMPORT },
+    .{ "@IMPORT-AS", IMPORT_AS },
+    .{ "@IMPORT-VERSION", IMPORT_VERSION },
+    .{ "@REQUIRE-VERSION", REQUIRE_VERSION },
+    .{ "@USE", USE },
+    .{ "@VERSION", VERSION },
+};
+
+/// Define every module word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+// See Runtime.import_module for what importing means. Paths are found as
+// @LOAD finds them (relative to the process's working directory, or along the
+// library path: see Runtime.resolve_source_path), and are gone from the Stack
+// by the time the module starts.
+
+/// @IMPORT ( String <- )
+///
+/// Import the module at the given path, so that its words can be called
+/// without their vocabulary's name: importing `lib/strings.gale`, say, lets
+/// `strings/trim` be called as `trim`. Needs the Read capability.
+pub fn IMPORT(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+    try runtime.require_source_access(peeked.String.value.?);
+
+    try import_popped_path(runtime, null);
+}
+
+/// @IMPORT-AS ( String Symbol <- )
+///
+/// As @IMPORT, but under a prefix: importing `lib/strings.gale` as `:s`
+/// lets `strings/trim` be called as `s/trim`, and no other way but its own
+/// name. Needs the Read capability.
+pub fn IMPORT_AS(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Symbol or far.* != .String) return InternalError.TypeError;
+    try runtime.require_source_access(far.String.value.?);
+
+    var prefix = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&prefix);
+
+    import_popped_path(runtime, prefix.Symbol.value.?) catch |err| {
+        // Put back what we took, so the Stack is as it was (less the path,
+        // if the error came from the module itself).
+        var restored = try prefix.ref();
+        runtime.stack_push_object(restored) catch {
+            runtime.release_heaped_object_reference(&restored);
+        };
+        return err;
+    };
+}
+
+/// @IMPORT-VERSION ( String String <- )
+///
+/// As @IMPORT, but demanding (see @REQUIRE-VERSION) that the module's
+/// vocabulary be within the range given last: `"strings" "^1.2"
+/// @IMPORT-VERSION`. Demands which can't be met are refused before the
+/// module is ever evaluated, where possible. Needs the Read capability.
+pub fn IMPORT_VERSION(runtime: *Runtime) anyerror!void {
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .String) return InternalError.TypeError;
+    try runtime.require_source_access(far.String.value.?);
+
+    const path = try runtime.resolve_source_path(far.String.value.?);
+    defer runtime.alloc.free(path);
+    try runtime.require_version(module.vocabulary_for_path(path), peek.near.String.value.?);
+
+    var range = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&range);
+
+    import_popped_path(runtime, null) catch |err| {
+        var restored = try range.ref();
+        runtime.stack_push_object(restored) catch {
+            runtime.release_heaped_object_reference(&restored);
+        };
+        return err;
+    };
+}
+
+/// @REQUIRE-VERSION ( Symbol String -> nothing )
+///
+/// Demand that the vocabulary named by the Symbol be at a version within the
+/// range (see version.zig) given by the String, on behalf of whichever
+/// module is being imported: `:strings "^1.2" @REQUIRE-VERSION`. Ranges which
+/// rule out the vocabulary's own version (see @VERSION), or what anything
+/// else has demanded of it, are an IncompatibleVersion, as described by
+/// Runtime.version_conflict.
+pub fn REQUIRE_VERSION(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .Symbol) return InternalError.TypeError;
+
+    try runtime.require_version(far.Symbol.value.?, peek.near.String.value.?);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @USE ( Symbol <- )
+///
+/// Add the vocabulary named by the Symbol to the search order, as @IMPORT
+/// does for the modules it imports, whether or not any module defines it:
+/// `:host @USE` lets a host's `host/answer` be called as `answer`.
+pub fn USE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Symbol) return InternalError.TypeError;
+
+    try runtime.use_vocabulary(peeked.Symbol.value.?, null);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @VERSION ( String <- )
+///
+/// Declare the vocabulary of the module being imported to be at the given
+/// MAJOR.MINOR.PATCH version, for @REQUIRE-VERSION and @IMPORT-VERSION to
+/// check against. Outside of a module, there's no vocabulary to declare
+/// for, which is a ValueError.
+pub fn VERSION(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+    const vocab_name = runtime.current_vocabulary orelse return InternalError.ValueError;
+
+    try runtime.declare_version(vocab_name, peeked.String.value.?);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// Import the module whose path is the String on top of the Stack, dropping
+/// the path once it's certain the module can be found.
+fn import_popped_path(runtime: *Runtime, prefix: ?[]const u8) !void {
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peeked = try runtime.stack_peek();
+    const path = try runtime.resolve_source_path(peeked.String.value.?);
+    defer runtime.alloc.free(path);
+
+    // Missing files leave the path where it was, as with the fs words.
+    if (!runtime.bundled.contains(path)) try std.fs.cwd().access(path, .{});
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.import_module(path, prefix);
+}
+
+fn test_push_string(runtime: *Runtime, str: []const u8) !void {
+    try runtime.stack_push_string((try runtime.get_or_put_string(str)).value_ptr);
+}
+
+test "IMPORT, IMPORT-AS, and USE" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(testAllocator, ".");
+    defer testAllocator.free(root);
+
+    var granted = capabilities.NONE;
+    granted.insert(.Read);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    // Within the module, its own words are callable unqualified.
+    try tmp.dir.writeFile("shapes.gale", "4 @LIT :sides @DEFINE &sides :corners @DEFINE");
+    const shapes = try std.fs.path.join(testAllocator, &.{ root, "shapes.gale" });
+    defer testAllocator.free(shapes);
+
+    try test_push_string(&runtime, shapes);
+    try runtime.eval("@IMPORT corners shapes/sides");
+    try expectEqual(@as(usize, 4), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 4), try runtime.stack_pop_value(usize));
+
+    // Importing again doesn't evaluate the module again.
+    try test_push_string(&runtime, shapes);
+    try test_push_string(&runtime, shapes);
+    try runtime.eval(":sh @IMPORT-AS @IMPORT sh/sides");
+    try expectEqual(@as(usize, 4), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 1), runtime.definition_count("shapes/sides"));
+
+    try runtime.define_primitive_word("host", "answer", &Words.answer);
+    try expectError(InternalError.UnknownWord, runtime.eval("answer"));
+    try runtime.eval(":host @USE answer");
+    try expectEqual(@as(usize, 42), try runtime.stack_pop_value(usize));
+
+    // Circular imports are caught, however roundabout.
+    const ping = try std.fs.path.join(testAllocator, &.{ root, "ping.gale" });
+    defer testAllocator.free(ping);
+    const pong = try std.fs.path.join(testAllocator, &.{ root, "pong.gale" });
+    defer testAllocator.free(pong);
+
+    const ping_source = try std.fmt.allocPrint(testAllocator, "\"{s}\" @IMPORT", .{pong});
+    defer testAllocator.free(ping_source);
+    try tmp.dir.writeFile("ping.gale", ping_source);
+    const pong_source = try std.fmt.allocPrint(testAllocator, "\"{s}\" @IMPORT", .{ping});
+    defer testAllocator.free(pong_source);
+    try tmp.dir.writeFile("pong.gale", pong_source);
+
+    try test_push_string(&runtime, ping);
+    try expectError(InternalError.CircularImport, runtime.eval("@IMPORT"));
+    try expectEqual(@as(usize, 2), runtime.load_trace.items.len);
+}
+
+test "VERSION, REQUIRE-VERSION, and IMPORT-VERSION" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(testAllocator, ".");
+    defer testAllocator.free(root);
+
+    var granted = capabilities.NONE;
+    granted.insert(.Read);
+    var runtime = try Runtime.init_with_options(testAllocator, .{
+        .capabilities = granted,
+        .library_path = &.{root},
+    });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try tmp.dir.writeFile("strings.gale", "\"1.5.0\" @VERSION");
+    try tmp.dir.writeFile("shapes.gale", ":strings \"^2\" @REQUIRE-VERSION");
+
+    try runtime.eval("\"strings\" \"^1.2\" @IMPORT-VERSION");
+    try expectEqual(@as(usize, 0), runtime.stack.depth());
+
+    // Refused before anything's dropped, let alone evaluated.
+    try expectError(InternalError.IncompatibleVersion, runtime.eval("\"strings\" \"~1.4\" @IMPORT-VERSION"));
+    try expectEqual(@as(usize, 2), runtime.stack.depth());
+    test_release_popped(&runtime, 2);
+
+    // As are demands made within a module, which leave what they demanded
+    // behind.
+    try expectError(InternalError.IncompatibleVersion, runtime.eval("\"shapes\" @IMPORT"));
+    try expectEqualStrings("strings 1.5.0 doesn't satisfy ^2 (required by shapes)", runtime.version_conflict.?);
+    test_release_popped(&runtime, 2);
+
+    try expectError(InternalError.ValueError, runtime.eval("\"1.0.0\" @VERSION"));
+    test_release_popped(&runtime, 1);
+}
+
+fn test_release_popped(runtime: *Runtime, count: usize) void {
+    var idx: usize = 0;
+    while (idx < count) : (idx += 1) {
+        var popped = runtime.stack_pop() catch unreachable;
+        runtime.release_heaped_object_reference(&popped);
+    }
+}
+
+const Words = struct {
+    fn answer(rt: *Runtime) anyerror!void {
+        try rt.stack_push_uint(42);
+    }
+};
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,363 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualSlices = std.testing.expectEqualSlices;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const conversion = @import("./conversion.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// Converting between MessagePack (https://msgpack.org) and Objects, both for
+// the words in msgpack_words.zig and for hosts wanting to speak to runtimes
+// (or anything else with a MessagePack library) without gale source in the
+// middle. The mapping is:
+//
+// | MessagePack          | Object                                          |
+// |----------------------|-------------------------------------------------|
+// | nil                  | the Symbol `:null` (see conversion.NULL_SYMBOL) |
+// | true, false          | Boolean                                         |
+// | non-negative ints    | UnsignedInt                                     |
+// | negative ints        | SignedInt                                       |
+// | float 32, float 64   | Float                                           |
+// | str, bin             | String                                          |
+// | array                | Array                                           |
+// | map (with str keys)  | Map                                             |
+//
+// Going the other way, every Symbol besides `:null` becomes a str of its
+// name, and Floats are always written as float 64. Integers are written in
+// the smallest form that fits. Words, Cells, Channels, Opaques, and Sets have
+// no MessagePack form and are a TypeError. Extension types, and maps with keys
+// which aren't strs, are a ValueError on the way in.
+
+/// How deeply Arrays and Maps may nest, either way, before we give up with
+/// a LimitExceeded rather than risk running out of (Zig) stack.
+// TODO: configurable in build.zig
+pub const MAX_NESTING = 512;
+
+/// Decode `bytes`, which must hold exactly one MessagePack value. Truncated
+/// or otherwise malformed input is a ValueError. As with anything fresh off
+/// the heap, the result holds no references yet.
+pub fn decode(rt: *Runtime, bytes: []const u8) !Object {
+    const decoded = try decode_prefix(rt, bytes);
+    if (decoded.len != bytes.len) {
+        var obj = try decoded.object.ref();
+        rt.release_heaped_object_reference(&obj);
+        return InternalError.ValueError;
+    }
+
+    return decoded.object;
+}
+
+pub const Decoded = struct {
+    object: Object,
+    /// How many bytes the value took up.
+    len: usize,
+};
+
+/// Decode the first MessagePack value in `bytes`, for when several are
+/// strung together (as on a socket or pipe). Returns the value alongside
+/// how many bytes it took up, so the caller knows where the next starts.
+pub fn decode_prefix(rt: *Runtime, bytes: []const u8) !Decoded {
+    var decoder = Decoder{ .rt = rt, .bytes = bytes, .pos = 0 };
+    const object = try decoder.value(0);
+    return Decoded{ .object = object, .len = decoder.pos };
+}
+
+const Decoder = struct {
+    const Self = @This();
+
+    rt: *Runtime,
+    bytes: []const u8,
+    pos: usize,
+
+    fn value(self: *Self, depth: usize) anyerror!Object {
+        if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+
+        const tag = try self.byte();
+        switch (tag) {
+            0x00...0x7f => return Object{ .UnsignedInt = tag },
+            0x80...0x8f => return try self.map(tag & 0x0f, depth),
+            0x90...0x9f => return try self.array(tag & 0x0f, depth),
+            0xa0...0xbf => return try self.string(tag & 0x1f),
+            0xc0 => return Object{ .Symbol = (try self.rt.get_or_put_symbol(conversion.NULL_SYMBOL)).value_ptr },
+            0xc2 => return Object{ .Boolean = false },
+            0xc3 => return Object{ .Boolean = true },
+            0xc4, 0xd9 => return try self.string(try self.int(u8)),
+            0xc5, 0xda => return try self.string(try self.int(u16)),
+            0xc6, 0xdb => return try self.string(try self.int(u32)),
+            0xca => return Object{ .Float = @bitCast(f32, try self.int(u32)) },
+            0xcb => return Object{ .Float = @bitCast(f64, try self.int(u64)) },
+            0xcc => return Object{ .UnsignedInt = try self.int(u8) },
+            0xcd => return Object{ .UnsignedInt = try self.int(u16) },
+            0xce => return Object{ .UnsignedInt = try self.int(u32) },
+            0xcf => return Object{ .UnsignedInt = std.math.cast(usize, try self.int(u64)) orelse return InternalError.ValueError },
+            0xd0 => return try signed(try self.int(i8)),
+            0xd1 => return try signed(try self.int(i16)),
+            0xd2 => return try signed(try self.int(i32)),
+            0xd3 => return try signed(try self.int(i64)),
+            0xdc => return try self.array(try self.int(u16), depth),
+            0xdd => return try self.array(try self.int(u32), depth),
+            0xde => return try self.map(try self.int(u16), depth),
+            0xdf => return try self.map(try self.int(u32), depth),
+            0xe0...0xff => return Object{ .SignedInt = @bitCast(i8, tag) },
+            // 0xc1 is reserved, and everything else is an extension type.
+            else => return InternalError.ValueError,
+        }
+    }
+
+    fn array(self: *Self, len: usize, depth: usize) !Object {
+        // Every member takes at least a byte, which stops a bogus length
+        // from asking for a huge allocation up front.
+        if (len > self.bytes.len - self.pos) return InternalError.ValueError;
+
+        const target = try self.rt.new_array(len);
+        errdefer self.rt.release_array(target);
+
+        var idx: usize = 0;
+        while (idx < len) : (idx += 1) {
+            const member = try (try self.value(depth + 1)).ref();
+            target.value.?.appendAssumeCapacity(member);
+        }
+
+        return Object{ .Array = target };
+    }
+
+    fn map(self: *Self, len: usize, depth: usize) !Object {
+        const target = try self.rt.new_map();
+        errdefer self.rt.release_map(target);
+
+        var idx: usize = 0;
+        while (idx < len) : (idx += 1) {
+            const tag = try self.byte();
+            const key_len: usize = switch (tag) {
+                0xa0...0xbf => tag & 0x1f,
+                0xd9 => try self.int(u8),
+                0xda => try self.int(u16),
+                0xdb => try self.int(u32),
+                else => return InternalError.ValueError,
+            };
+            const key = try self.take(key_len);
+
+            var member = try (try self.value(depth + 1)).ref();
+            errdefer member.deinit(self.rt.alloc);
+            try target.value.?.put(self.rt.alloc, key, member);
+        }
+
+        return Object{ .Map = target };
+    }
+
+    fn string(self: *Self, len: usize) !Object {
+        return Object{ .String = (try self.rt.get_or_put_string(try self.take(len))).value_ptr };
+    }
+
+    fn signed(val: anytype) !Object {
+        return if (val < 0)
+            Object{ .SignedInt = std.math.cast(isize, val) orelse return InternalError.ValueError }
+        else
+            Object{ .UnsignedInt = @intCast(usize, val) };
+    }
+
+    fn int(self: *Self, comptime T: type) !T {
+        const raw = try self.take(@sizeOf(T));
+        return std.mem.readIntBig(T, raw[0..@sizeOf(T)]);
+    }
+
+    fn byte(self: *Self) !u8 {
+        return (try self.take(1))[0];
+    }
+
+    fn take(self: *Self, len: usize) ![]const u8 {
+        if (len > self.bytes.len - self.pos) return InternalError.ValueError;
+        defer self.pos += len;
+        return self.bytes[self.pos .. self.pos + len];
+    }
+};
+
+/// Write `obj` as MessagePack.
+pub fn encode(writer: anytype, obj: Object) !void {
+    try write_value(writer, obj, 0);
+}
+
+fn write_value(writer: anytype, obj: Object, depth: usize) anyerror!void {
+    if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+
+    switch (obj) {
+        .Array => |array| {
+            const items = array.value.?.items;
+            try write_length(writer, items.len, 0x90, 0xdc);
+            for (items) |member| try write_value(writer, member, depth + 1);
+        },
+        .Boolean => |val| try writer.writeByte(if (val) 0xc3 else 0xc2),
+        .Float => |val| {
+            try writer.writeByte(0xcb);
+            try writer.writeIntBig(u64, @bitCast(u64, val));
+        },
+        .Map => |map| {
+            const keys = map.value.?.keys();
+            try write_length(writer, keys.len, 0x80, 0xde);
+            for (keys) |key| {
+                try write_string(writer, key);
+                try write_value(writer, map.value.?.get(key).?, depth + 1);
+            }
+        },
+        .SignedInt => |val| {
+            if (val >= 0) return try write_unsigned(writer, @intCast(u64, val));
+            if (val >= -32) {
+                try writer.writeByte(@bitCast(u8, @intCast(i8, val)));
+            } else if (val >= std.math.minInt(i8)) {
+                try writer.writeByte(0xd0);
+                try writer.writeIntBig(i8, @intCast(i8, val));
+            } else if (val >= std.math.minInt(i16)) {
+                try writer.writeByte(0xd1);
+                try writer.writeIntBig(i16, @intCast(i16, val));
+            } else if (val >= std.math.minInt(i32)) {
+                try writer.writeByte(0xd2);
+                try writer.writeIntBig(i32, @intCast(i32, val));
+            } else {
+                try writer.writeByte(0xd3);
+                try writer.writeIntBig(i64, @intCast(i64, val));
+            }
+        },
+        .String => |str| try write_string(writer, str.value.?),
+        .Symbol => |sym| if (std.mem.eql(u8, sym.value.?, conversion.NULL_SYMBOL))
+            try writer.writeByte(0xc0)
+        else
+            try write_string(writer, sym.value.?),
+        .UnsignedInt => |val| try write_unsigned(writer, @intCast(u64, val)),
+        .Cell, .Channel, .Opaque, .Set, .Word => return InternalError.TypeError,
+    }
+}
+
+fn write_unsigned(writer: anytype, val: u64) !void {
+    if (val <= 0x7f) {
+        try writer.writeByte(@intCast(u8, val));
+    } else if (val <= std.math.maxInt(u8)) {
+        try writer.writeByte(0xcc);
+        try writer.writeIntBig(u8, @intCast(u8, val));
+    } else if (val <= std.math.maxInt(u16)) {
+        try writer.writeByte(0xcd);
+        try writer.writeIntBig(u16, @intCast(u16, val));
+    } else if (val <= std.math.maxInt(u32)) {
+        try writer.writeByte(0xce);
+        try writer.writeIntBig(u32, @intCast(u32, val));
+    } else {
+        try writer.writeByte(0xcf);
+        try writer.writeIntBig(u64, val);
+    }
+}
+
+fn write_string(writer: anytype, bytes: []const u8) !void {
+    if (bytes.len <= 31) {
+        try writer.writeByte(0xa0 | @intCast(u8, bytes.len));
+    } else if (bytes.len <= std.math.maxInt(u8)) {
+        try writer.writeByte(0xd9);
+        try writer.writeIntBig(u8, @intCast(u8, bytes.len));
+    } else if (bytes.len <= std.math.maxInt(u16)) {
+        try writer.writeByte(0xda);
+        try writer.writeIntBig(u16, @intCast(u16, bytes.len));
+    } else if (bytes.len <= std.math.maxInt(u32)) {
+        try writer.writeByte(0xdb);
+        try writer.writeIntBig(u32, @intCast(u32, bytes.len));
+    } else {
+        return InternalError.LimitExceeded;
+    }
+
+    try writer.writeAll(bytes);
+}
+
+/// Arrays and maps share a layout: a fix form for up to 15 members, then
+/// 16- and 32-bit lengths.
+fn write_length(writer: anytype, len: usize, fix_tag: u8, tag16: u8) !void {
+    if (len <= 15) {
+        try writer.writeByte(fix_tag | @intCast(u8, len));
+    } else if (len <= std.math.maxInt(u16)) {
+        try writer.writeByte(tag16);
+        try writer.writeIntBig(u16, @intCast(u16, len));
+    } else if (len <= std.math.maxInt(u32)) {
+        try writer.writeByte(tag16 + 1);
+        try writer.writeIntBig(u32, @intCast(u32, len));
+    } else {
+        return InternalError.LimitExceeded;
+    }
+}
+
+test "encode" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    try encode(out.writer(), Object{ .UnsignedInt = 300 });
+    try encode(out.writer(), Object{ .SignedInt = -1 });
+    try encode(out.writer(), Object{ .SignedInt = -200 });
+    try encode(out.writer(), Object{ .Boolean = true });
+    try encode(out.writer(), Object{ .Symbol = (try rt.get_or_put_symbol(conversion.NULL_SYMBOL)).value_ptr });
+
+    try expectEqualSlices(u8, &[_]u8{ 0xcd, 0x01, 0x2c, 0xff, 0xd1, 0xff, 0x38, 0xc3, 0xc0 }, out.items);
+}
+
+test "decode and round-trip" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    // {"a": [1, -2, "hi"], "b": 1.5}
+    const bytes = [_]u8{
+        0x82,
+        0xa1, 'a', 0x93, 0x01, 0xfe, 0xa2, 'h', 'i',
+        0xa1, 'b', 0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    };
+
+    var obj = try (try decode(&rt, &bytes)).ref();
+    defer rt.release_heaped_object_reference(&obj);
+
+    const items = obj.Map.value.?.get("a").?.Array.value.?.items;
+    try expectEqual(@as(usize, 1), items[0].UnsignedInt);
+    try expectEqual(@as(isize, -2), items[1].SignedInt);
+    try expectEqualStrings("hi", items[2].String.value.?);
+    try expectEqual(@as(f64, 1.5), obj.Map.value.?.get("b").?.Float);
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+    try encode(out.writer(), obj);
+    try expectEqualSlices(u8, &bytes, out.items);
+}
+
+test "decode: malformed" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try expectError(InternalError.ValueError, decode(&rt, &[_]u8{}));
+    try expectError(InternalError.ValueError, decode(&rt, &[_]u8{ 0xa3, 'h', 'i' }));
+    try expectError(InternalError.ValueError, decode(&rt, &[_]u8{ 0x92, 0x01 }));
+    try expectError(InternalError.ValueError, decode(&rt, &[_]u8{ 0x81, 0x01, 0x01 }));
+    try expectError(InternalError.ValueError, decode(&rt, &[_]u8{ 0xc1 }));
+    try expectError(InternalError.ValueError, decode(&rt, &[_]u8{ 0xa1, 'x', 0x00 }));
+
+    const prefixed = try decode_prefix(&rt, &[_]u8{ 0x05, 0x06 });
+    try expectEqual(@as(usize, 1), prefixed.len);
+    try expectEqual(@as(usize, 5), prefixed.object.UnsignedInt);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,87 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const msgpack = @import("./msgpack.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+
+/// Every MessagePack word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@MSGPACK-DECODE", MSGPACK_DECODE },
+    .{ "@MSGPACK-ENCODE", MSGPACK_ENCODE },
+};
+
+/// Define every MessagePack word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @MSGPACK-DECODE ( String <- @1 )
+///
+/// The value the String's bytes hold, as MessagePack. See msgpack.zig for
+/// which MessagePack types become which Objects. Malformed input, or bytes
+/// left over after the value, are a ValueError.
+// TODO: take Opaques too, once there's a way to make byte buffers of them.
+pub fn MSGPACK_DECODE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+
+    const decoded = try msgpack.decode(runtime, peeked.String.value.?);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(decoded);
+}
+
+/// @MSGPACK-ENCODE ( @1 <- String )
+///
+/// The Object, as MessagePack bytes. Objects with no MessagePack equivalent
+/// (see msgpack.zig) are a TypeError.
+pub fn MSGPACK_ENCODE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+    try msgpack.encode(out.writer(), peeked.*);
+    const str = try runtime.get_or_put_string(out.items);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+test "@MSGPACK-ENCODE and @MSGPACK-DECODE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("-300 @MSGPACK-ENCODE @MSGPACK-DECODE");
+    try expectEqual(@as(isize, -300), (try runtime.stack_pop()).SignedInt);
+
+    // "ab" is a whole (positive fixint) value followed by another byte.
+    try expectError(InternalError.ValueError, runtime.eval("\"ab\" @MSGPACK-DECODE"));
+    var bytes = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&bytes);
+
+    try expectError(InternalError.TypeError, runtime.eval("&@DUP @MSGPACK-ENCODE"));
+    var word = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&word);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,39 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+
+/// An open TCP connection or listener. gale code never sees these directly,
+/// only the UnsignedInt IDs the Runtime files them under (see
+/// Runtime.sockets), much as with Tasks: there's no sharing them between
+/// Runtimes, and whatever's left open when the Runtime goes away is closed
+/// along with it.
+pub const Socket = union(enum) {
+    const Self = @This();
+
+    Stream: std.net.Stream,
+    Listener: std.net.StreamServer,
+
+    pub fn deinit(self: *Self) void {
+        switch (self.*) {
+            .Stream => |stream| stream.close(),
+            .Listener => |*listener| listener.deinit(),
+        }
+    }
+};
+
+pub const SocketMap = std.AutoArrayHashMap(usize, Socket);
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,287 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const capabilities = @import("./capabilities.zig");
+const helpers = @import("./helpers.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const Socket = @import("./net.zig").Socket;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// The most @TCP-READ will hand back in one go, no matter how much it was
+/// asked for.
+// TODO: configurable in build.zig
+pub const MAX_READ_LEN = 1024 * 1024;
+
+/// Every TCP word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@TCP-ACCEPT", TCP_ACCEPT },
+    .{ "@TCP-CLOSE", TCP_CLOSE },
+    .{ "@TCP-CONNECT", TCP_CONNECT },
+    .{ "@TCP-LISTEN", TCP_LISTEN },
+    .{ "@TCP-PORT", TCP_PORT },
+    .{ "@TCP-READ", TCP_READ },
+    .{ "@TCP-WRITE", TCP_WRITE },
+};
+
+/// Define every TCP word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+// Connections and listeners are both referred to by UnsignedInt IDs, which
+// are only meaningful to the Runtime which handed them out. Using a
+// connection's ID where a listener's is wanted (or vice versa), or one which
+// has been closed, is a ValueError.
+//
+// Everything here blocks the whole Runtime (Tasks included) until the OS is
+// done with it.
+//
+// TODO: let Tasks get a slice in while we wait, as Channels do.
+// TODO: Opaque payloads, once Opaques are something other than a TODO.
+
+/// @TCP-CONNECT ( String UnsignedInt <- UnsignedInt )
+///
+/// Connect to the given host (a name to be resolved, or an IP address) and
+/// port, producing the connection's ID. Needs the Net capability.
+pub fn TCP_CONNECT(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Net);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const args = try peek_host_and_port(runtime);
+    const stream = try std.net.tcpConnectToHost(runtime.alloc, args.host, args.port);
+    errdefer stream.close();
+
+    const id = try file_socket(runtime, .{ .Stream = stream });
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_uint(id);
+}
+
+/// @TCP-LISTEN ( String UnsignedInt <- UnsignedInt )
+///
+/// Listen on the given IP address and port (0 for "any free one", see
+/// @TCP-PORT), producing the listener's ID. Needs the Net capability.
+pub fn TCP_LISTEN(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Net);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const args = try peek_host_and_port(runtime);
+    const address = std.net.Address.parseIp(args.host, args.port) catch return InternalError.ValueError;
+
+    var listener = std.net.StreamServer.init(.{ .reuse_address = true });
+    errdefer listener.deinit();
+    try listener.listen(address);
+
+    const id = try file_socket(runtime, .{ .Listener = listener });
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_uint(id);
+}
+
+/// @TCP-PORT ( UnsignedInt <- UnsignedInt )
+///
+/// The port a listener is actually listening on. Needs the Net capability.
+pub fn TCP_PORT(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Net);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const socket = try peek_socket(runtime, 0);
+    if (socket.* != .Listener) return InternalError.ValueError;
+
+    const port = socket.Listener.listen_address.getPort();
+    _ = try runtime.stack_pop();
+    try runtime.stack_push_uint(port);
+}
+
+/// @TCP-ACCEPT ( UnsignedInt <- UnsignedInt )
+///
+/// Wait for, and accept, the next connection to a listener, producing the
+/// new connection's ID. Needs the Net capability.
+pub fn TCP_ACCEPT(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Net);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const socket = try peek_socket(runtime, 0);
+    if (socket.* != .Listener) return InternalError.ValueError;
+
+    const conn = try socket.Listener.accept();
+    errdefer conn.stream.close();
+
+    const id = try file_socket(runtime, .{ .Stream = conn.stream });
+    _ = try runtime.stack_pop();
+    try runtime.stack_push_uint(id);
+}
+
+/// @TCP-READ ( UnsignedInt UnsignedInt <- String )
+///
+/// Read up to the given number of bytes (but no more than MAX_READ_LEN)
+/// from a connection, waiting for at least one to arrive. An empty String
+/// means the other end has hung up. Needs the Net capability.
+pub fn TCP_READ(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Net);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+    const wanted = std.math.min(peeked.UnsignedInt, MAX_READ_LEN);
+
+    const socket = try peek_socket(runtime, 1);
+    if (socket.* != .Stream) return InternalError.ValueError;
+
+    const buf = try runtime.alloc.alloc(u8, wanted);
+    defer runtime.alloc.free(buf);
+    const len = try socket.Stream.read(buf);
+    const str = try runtime.get_or_put_string(buf[0..len]);
+
+    _ = try runtime.stack_pop_pair();
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+/// @TCP-WRITE ( UnsignedInt String <- )
+///
+/// Write all of the String to a connection. Needs the Net capability.
+pub fn TCP_WRITE(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Net);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+
+    const socket = try peek_socket(runtime, 1);
+    if (socket.* != .Stream) return InternalError.ValueError;
+
+    try socket.Stream.writeAll(peeked.String.value.?);
+    try runtime.stack_wrangle(.DropTopObject);
+    _ = try runtime.stack_pop();
+}
+
+/// @TCP-CLOSE ( UnsignedInt <- )
+///
+/// Close a connection or listener, after which its ID means nothing. Needs
+/// the Net capability.
+pub fn TCP_CLOSE(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Net);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const socket = try peek_socket(runtime, 0);
+    socket.deinit();
+    _ = runtime.sockets.orderedRemove((try runtime.stack_pop()).UnsignedInt);
+}
+
+fn file_socket(runtime: *Runtime, socket: Socket) !usize {
+    const id = runtime.next_socket_id;
+    try runtime.sockets.put(id, socket);
+    runtime.next_socket_id += 1;
+    return id;
+}
+
+/// Look up the socket whose ID is `depth` Objects down the Stack (0 being
+/// the top).
+fn peek_socket(runtime: *Runtime, depth: usize) !*Socket {
+    const peek = try runtime.stack_peek_pair();
+    const target = switch (depth) {
+        0 => peek.near,
+        1 => peek.far orelse return StackManipulationError.Underflow,
+        else => unreachable,
+    };
+    if (target.* != .UnsignedInt) return InternalError.TypeError;
+
+    return runtime.sockets.getPtr(target.UnsignedInt) orelse return InternalError.ValueError;
+}
+
+const HostAndPort = struct {
+    host: []const u8,
+    port: u16,
+};
+
+fn peek_host_and_port(runtime: *Runtime) !HostAndPort {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt or far.* != .String) return InternalError.TypeError;
+
+    return .{
+        .host = far.String.value.?,
+        .port = std.math.cast(u16, peek.near.UnsignedInt) orelse return InternalError.ValueError,
+    };
+}
+
+fn test_pop_string(runtime: *Runtime, expected: []const u8) !void {
+    var popped = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&popped);
+    try expectEqualStrings(expected, popped.String.value.?);
+}
+
+test "TCP round trip" {
+    var granted = capabilities.NONE;
+    granted.insert(.Net);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"127.0.0.1\" 0 @TCP-LISTEN");
+    const listener = (try runtime.stack_peek()).UnsignedInt;
+    try runtime.eval("@TCP-PORT");
+    const port = try runtime.stack_pop_value(usize);
+
+    // The listener's backlog lets us connect before anyone's accepted.
+    try runtime.eval("\"127.0.0.1\"");
+    try runtime.stack_push_uint(port);
+    try runtime.eval("@TCP-CONNECT");
+    const client = try runtime.stack_pop_value(usize);
+
+    try runtime.stack_push_uint(listener);
+    try runtime.eval("@TCP-ACCEPT");
+    const server = try runtime.stack_pop_value(usize);
+
+    try runtime.stack_push_uint(client);
+    try runtime.eval("\"ping\" @TCP-WRITE");
+    try runtime.stack_push_uint(server);
+    try runtime.eval("16 @TCP-READ");
+    try test_pop_string(&runtime, "ping");
+
+    // Hanging up is seen as an empty read on the other end.
+    try runtime.stack_push_uint(client);
+    try runtime.eval("@TCP-CLOSE");
+    try runtime.stack_push_uint(server);
+    try runtime.eval("16 @TCP-READ");
+    try test_pop_string(&runtime, "");
+
+    try runtime.stack_push_uint(client);
+    try expectError(InternalError.ValueError, runtime.eval("16 @TCP-READ"));
+    _ = try runtime.stack_pop_pair();
+
+    // The listener and the server's end of the connection are left for
+    // deinit to close.
+    try expectEqual(@as(usize, 2), runtime.sockets.count());
+}
+
+test "TCP words need the Net capability" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("1");
+    try expectError(InternalError.CapabilityDenied, runtime.eval("@TCP-CLOSE"));
+    _ = try runtime.stack_pop();
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
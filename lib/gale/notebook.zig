@@ -0,0 +1,218 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const io = @import("./io.zig");
+const json = @import("./json.zig");
+
+const OutputHandle = io.OutputHandle;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// A line-at-a-time evaluation protocol, for notebooks and anything else that
+// would rather drive a Runtime from another process than embed one. Each
+// request is a line of JSON:
+//
+//     {"id":1,"code":"2 3 @DUP"}
+//
+// and each gets a line of JSON back, echoing the `id` (which can be any JSON
+// at all, or left out), saying how it went, and showing what the Stack holds
+// afterwards, bottom to top, both as data and as display data ready for a
+// notebook's front end:
+//
+//     {"id":1,"status":"ok","stdout":"","stack":[{"kind":"UnsignedInt",
+//      "text":"2"},...],"data":{"text/plain":"<3> 2 3 3","text/html":"..."}}
+//
+// (all on one line). Failures have a "status" of "error", with an "error"
+// naming it, and leave the Stack just as it was before the request. Lines
+// that aren't requests at all get an "error" of "MalformedRequest".
+//
+// This is deliberately no more than a Jupyter kernel needs under the hood:
+// the kernel messaging spec proper (ZeroMQ sockets, signed messages) is best
+// left to a thin wrapper kernel translating execute_request into this.
+
+pub const Kernel = struct {
+    const Self = @This();
+
+    alloc: Allocator,
+    /// What's been printed while evaluating the current request.
+    captured: std.ArrayList(u8),
+
+    pub fn init(alloc: Allocator) Self {
+        return .{ .alloc = alloc, .captured = std.ArrayList(u8).init(alloc) };
+    }
+
+    pub fn deinit(self: *Self) void {
+        self.captured.deinit();
+    }
+
+    /// Hand this to Runtime.Options.stdout (and stderr, if that should be
+    /// captured too), so that what's printed makes it into the replies. The
+    /// Kernel mustn't move while the Runtime's around.
+    pub fn output(self: *Self) OutputHandle {
+        return io.buffer_output(&self.captured);
+    }
+
+    /// Evaluate the request on `line` against `rt`, writing the reply (and
+    /// its trailing newline) to `writer`.
+    pub fn handle(self: *Self, rt: *Runtime, line: []const u8, writer: anytype) !void {
+        self.captured.clearRetainingCapacity();
+
+        var parser = std.json.Parser.init(self.alloc, false);
+        defer parser.deinit();
+
+        var tree = parser.parse(line) catch |err| switch (err) {
+            error.OutOfMemory => return err,
+            else => return writer.writeAll(MALFORMED_REPLY),
+        };
+        defer tree.deinit();
+
+        if (tree.root != .Object) return writer.writeAll(MALFORMED_REPLY);
+        const code = tree.root.Object.get("code") orelse return writer.writeAll(MALFORMED_REPLY);
+        if (code != .String) return writer.writeAll(MALFORMED_REPLY);
+        const id = tree.root.Object.get("id") orelse std.json.Value.Null;
+
+        const evaluated = eval_atomically(rt, code.String);
+
+        try writer.writeAll("{\"id\":");
+        try id.jsonStringify(.{}, writer);
+        if (evaluated) |_| {
+            try writer.writeAll(",\"status\":\"ok\"");
+        } else |err| {
+            try writer.print(",\"status\":\"error\",\"error\":\"{s}\"", .{@errorName(err)});
+        }
+
+        try writer.writeAll(",\"stdout\":");
+        try json.write_string(writer, self.captured.items);
+        try writer.writeAll(",\"stack\":");
+        try self.write_stack(rt, writer);
+        try writer.writeAll("}\n");
+    }
+
+    /// The Stack as JSON, then the "data" field, sharing the text of each
+    /// Object between them.
+    fn write_stack(self: *Self, rt: *Runtime, writer: anytype) !void {
+        const depth = rt.stack.depth();
+        const texts = try self.alloc.alloc([]u8, depth);
+        var made: usize = 0;
+        defer {
+            for (texts[0..made]) |text| self.alloc.free(text);
+            self.alloc.free(texts);
+        }
+
+        // Bottom to top, as everywhere else.
+        while (made < depth) : (made += 1) {
+            texts[made] = try std.fmt.allocPrint(self.alloc, "{}", .{rt.stack.nth_from_top(depth - 1 - made).?.*});
+        }
+
+        try writer.writeByte('[');
+        for (texts) |text, idx| {
+            if (idx > 0) try writer.writeByte(',');
+            try writer.writeAll("{\"kind\":");
+            try json.write_string(writer, @tagName(rt.stack.nth_from_top(depth - 1 - idx).?.*));
+            try writer.writeAll(",\"text\":");
+            try json.write_string(writer, text);
+            try writer.writeByte('}');
+        }
+        try writer.writeByte(']');
+
+        var display = std.ArrayList(u8).init(self.alloc);
+        defer display.deinit();
+
+        try display.writer().print("<{d}>", .{depth});
+        for (texts) |text| try display.writer().print(" {s}", .{text});
+        try writer.writeAll(",\"data\":{\"text/plain\":");
+        try json.write_string(writer, display.items);
+
+        // Top first, which reads best as a table.
+        display.clearRetainingCapacity();
+        try display.appendSlice("<table><tr><th>Kind</th><th>Value</th></tr>");
+        var idx = depth;
+        while (idx > 0) {
+            idx -= 1;
+            try display.writer().print("<tr><td>{s}</td><td>", .{@tagName(rt.stack.nth_from_top(depth - 1 - idx).?.*)});
+            try write_html_escaped(display.writer(), texts[idx]);
+            try display.appendSlice("</td></tr>");
+        }
+        try display.appendSlice("</table>");
+        try writer.writeAll(",\"text/html\":");
+        try json.write_string(writer, display.items);
+        try writer.writeByte('}');
+    }
+};
+
+const MALFORMED_REPLY = "{\"id\":null,\"status\":\"error\",\"error\":\"MalformedRequest\"}\n";
+
+/// Evaluate `code`, rolling the Stack back if it fails.
+fn eval_atomically(rt: *Runtime, code: []const u8) !void {
+    const snap = try rt.stack_snapshot();
+    rt.eval(code) catch |err| {
+        rt.stack_restore(snap);
+        return err;
+    };
+    snap.discard();
+}
+
+fn write_html_escaped(writer: anytype, text: []const u8) !void {
+    for (text) |chr| switch (chr) {
+        '&' => try writer.writeAll("&amp;"),
+        '<' => try writer.writeAll("&lt;"),
+        '>' => try writer.writeAll("&gt;"),
+        '"' => try writer.writeAll("&quot;"),
+        else => try writer.writeByte(chr),
+    };
+}
+
+test "Kernel" {
+    var kernel = Kernel.init(testAllocator);
+    defer kernel.deinit();
+
+    var rt = try Runtime.init_with_options(testAllocator, .{ .stdout = kernel.output() });
+    defer rt.deinit_guard_for_empty_stack();
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    try kernel.handle(&rt, "{\"id\":1,\"code\":\"1 2 @DUP \\\"hi\\\" @PRINT\"}", out.writer());
+    try expectEqualStrings(
+        "{\"id\":1,\"status\":\"ok\",\"stdout\":\"hi\",\"stack\":[" ++
+            "{\"kind\":\"UnsignedInt\",\"text\":\"1\"}," ++
+            "{\"kind\":\"UnsignedInt\",\"text\":\"2\"}," ++
+            "{\"kind\":\"UnsignedInt\",\"text\":\"2\"}]," ++
+            "\"data\":{\"text/plain\":\"<3> 1 2 2\",\"text/html\":\"<table><tr><th>Kind</th><th>Value</th></tr>" ++
+            "<tr><td>UnsignedInt</td><td>2</td></tr>" ++
+            "<tr><td>UnsignedInt</td><td>2</td></tr>" ++
+            "<tr><td>UnsignedInt</td><td>1</td></tr></table>\"}}\n",
+        out.items,
+    );
+
+    out.clearRetainingCapacity();
+    try kernel.handle(&rt, "{\"id\":\"b\",\"code\":\"@DROP bogus\"}", out.writer());
+    try std.testing.expect(std.mem.startsWith(u8, out.items, "{\"id\":\"b\",\"status\":\"error\",\"error\":\"UnknownWord\",\"stdout\":\"\""));
+    try expectEqual(@as(usize, 3), rt.stack.depth());
+
+    out.clearRetainingCapacity();
+    try kernel.handle(&rt, "{\"code\":4}", out.writer());
+    try kernel.handle(&rt, "nope", out.writer());
+    try expectEqualStrings(MALFORMED_REPLY ++ MALFORMED_REPLY, out.items);
+
+    try rt.eval("@DROP @DROP @DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
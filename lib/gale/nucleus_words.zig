@@ -22,11 +22,35 @@ const _stack = @import("./stack.zig");
 const test_helpers = @import("./test_helpers.zig");
 
 const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
 const Runtime = @import("./runtime.zig").Runtime;
 const StackManipulationError = _stack.StackManipulationError;
+const Types = @import("./types.zig");
 const Word = @import("./word.zig").Word;
 const WordSignature = @import("./word_signature.zig").WordSignature;
 
+/// Every nucleus word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@EQ", EQ },
+    .{ "@DROP", DROP },
+    .{ "@DUP", DUP },
+    .{ "@DEFINE", DEFINE },
+    .{ "@CALL", CALL },
+    .{ "@CAPTURE", CAPTURE },
+    .{ "@COMPOSE", COMPOSE },
+    .{ "@CONSTANT", CONSTANT },
+    .{ "@2DUPSHUF", TWODUPSHUF },
+    .{ "@LIT", LIT },
+    .{ "@SWAP", SWAP },
+    .{ "@TO", TO },
+    .{ "@VALUE", VALUE },
+};
+
+/// Define every nucleus word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
 // As a general rule, only write tests for methods in this file that actually
 // do something noteworthy of their own. Some of these words call directly into
 // Stack.whatever() without meaningful (if any) handling, duplicating those
@@ -34,15 +58,15 @@ const WordSignature = @import("./word_signature.zig").WordSignature;
 
 /// @EQ ( @2 @1 <- Boolean )
 ///
-/// Non-destructive equality check of the top two items of the stack. At this
-/// low a level, there is no type system, so checking equality of disparate
-/// primitive types will panic.
-pub fn EQ(runtime: *Runtime) !void {
+/// Non-destructive equality check (see Object.eql) of the top two items of
+/// the stack. At this low a level, there is no type system, so checking
+/// equality of disparate primitive types raises a TypeError.
+pub fn EQ(runtime: *Runtime) anyerror!void {
     const peek = try runtime.stack_peek_pair();
 
     if (peek.far) |bottom| {
         _ = try peek.near.assert_same_kind_as(bottom);
-        try runtime.stack_push_bool(true);
+        try runtime.stack_push_bool(peek.near.eql(bottom.*));
         return;
     }
 
@@ -61,20 +85,25 @@ test "EQ" {
     try expect((try runtime.stack_peek_pair()).near.*.Boolean);
     // Now compare that boolean to the UnsignedInt... or don't, preferably.
     try expectError(InternalError.TypeError, EQ(&runtime));
+    _ = try runtime.stack_pop();
+    // 1 != 2, equally revelatory.
+    try runtime.stack_push_uint(2);
+    try EQ(&runtime);
+    try expect(!(try runtime.stack_peek_pair()).near.*.Boolean);
 }
 
 /// @DROP ( @1 -> nothing )
-pub fn DROP(runtime: *Runtime) !void {
+pub fn DROP(runtime: *Runtime) anyerror!void {
     try runtime.stack_wrangle(.DropTopObject);
 }
 
 /// @DUP ( @1 -> @1 )
-pub fn DUP(runtime: *Runtime) !void {
+pub fn DUP(runtime: *Runtime) anyerror!void {
     try runtime.stack_wrangle(.DuplicateTopObject);
 }
 
 /// @2DUPSHUF ( @2 @1 -> @2 @1 @2 @1 )
-pub fn TWODUPSHUF(runtime: *Runtime) !void {
+pub fn TWODUPSHUF(runtime: *Runtime) anyerror!void {
     try runtime.stack_wrangle(.DuplicateTopTwoObjectsShuffled);
 }
 
@@ -86,15 +115,25 @@ pub fn TWODUPSHUF(runtime: *Runtime) !void {
 ///
 /// Used to be called @HEAPWRAP, which might hint at why it's implemented the
 /// way it is.
-pub fn LIT(runtime: *Runtime) !void {
+pub fn LIT(runtime: *Runtime) anyerror!void {
+    const banished = try runtime.stack_pop_to_heap();
+    const word = try runtime.word_from_heaplit_impl(banished, literal_signature(runtime, banished.*));
+    try runtime.stack_push_raw_word(word);
+}
+
+/// The signature of a word pushing `obj`, as @LIT makes them.
+pub fn literal_signature(runtime: *Runtime, obj: Object) ?Word.SignatureState {
     // TODO: Should these return Bounded versions instead, since we inherently
     // already know the word's return value?
-    const banished = try runtime.stack_pop_to_heap();
-    const word = try runtime.word_from_heaplit_impl(banished, switch (banished.*) {
+    return switch (obj) {
         // TODO, maybe this should be unreachable, since opaques are meant more
         // for things like FFI storage rather than raw, Gale-side bit access.
         .Opaque => @panic("unimplemented"),
 
+        // TODO: Cells, Channels, Maps, and Sets have no Shape yet, and thus
+        // no well-known signature to hand out.
+        .Cell, .Channel, .Map, .Set => null,
+
         .Array => .{ .Declared = runtime.get_well_known_word_signature(.NullarySingleUnboundedArray) },
         .Boolean => .{ .Declared = runtime.get_well_known_word_signature(.NullarySingleUnboundedBoolean) },
         .Float => .{ .Declared = runtime.get_well_known_word_signature(.NullarySingleUnboundedFloat) },
@@ -103,8 +142,7 @@ pub fn LIT(runtime: *Runtime) !void {
         .String => .{ .Declared = runtime.get_well_known_word_signature(.NullarySingleUnboundedString) },
         .Symbol => .{ .Declared = runtime.get_well_known_word_signature(.NullarySingleUnboundedSymbol) },
         .Word => .{ .Declared = runtime.get_well_known_word_signature(.NullarySingleUnboundedWord) },
-    });
-    try runtime.stack_push_raw_word(word);
+    };
 }
 
 test "LIT" {
@@ -135,8 +173,288 @@ test "LIT" {
     try expectEqual(@as(usize, 1), top_three.farther.UnsignedInt);
 }
 
+/// @DEFINE ( Word Symbol -> nothing )
+///
+/// Define the Word under the Symbol's name, shadowing (not replacing) any
+/// definition already there. While a module is being imported, unqualified
+/// names are defined within its vocabulary: see Runtime.define_word_here.
+/// References (`&name`) define as late-bound call sites, so `&@DUP :twin
+/// @DEFINE` makes `twin` call whatever `@DUP` means at the time.
+pub fn DEFINE(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Symbol or far.* != .Word) return InternalError.TypeError;
+
+    try runtime.define_word_here(peek.near.Symbol.value.?, far.Word);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+test "DEFINE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("&@DUP :twin @DEFINE 2 @LIT :two @DEFINE two twin");
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+
+    try expectError(InternalError.TypeError, runtime.eval(":two :twin @DEFINE"));
+    var symbols = try runtime.stack_pop_pair();
+    runtime.release_heaped_object_reference(&symbols.near);
+    runtime.release_heaped_object_reference(&symbols.far);
+}
+
+/// @CALL ( Word -> * )
+///
+/// Run the Word, which is gone from the Stack by the time it starts, so that
+/// what it sees is whatever was beneath it.
+pub fn CALL(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Word) return InternalError.TypeError;
+
+    var word = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&word);
+    try runtime.run_word(word.Word);
+}
+
+/// @COMPOSE ( Word Word -> Word )
+///
+/// Build a compound word which runs the lower Word, then the upper one, so
+/// `&@SWAP &@DROP @COMPOSE :nip @DEFINE` defines `nip`. This (with @LIT and
+/// @DEFINE) is how gale code defines words of its own, for now: see
+/// prelude.gale.
+pub fn COMPOSE(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Word or far.* != .Word) return InternalError.TypeError;
+
+    const inner = try runtime.alloc.alloc(*Types.HeapedWord, 2);
+    inner[0] = far.Word;
+    inner[1] = peek.near.Word;
+    const word = runtime.word_from_compound_impl(inner, null) catch |err| {
+        runtime.alloc.free(inner);
+        return err;
+    };
+
+    // Compound words hold a reference to each of their inner words, which
+    // these are, having been the Stack's.
+    _ = try runtime.stack_pop_pair();
+    try runtime.stack_push_raw_word(word);
+}
+
+test "CALL and COMPOSE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("1 2 &@SWAP &@DROP @COMPOSE @CALL");
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("3 &@DUP &@DUP @COMPOSE @DUP @COMPOSE @CALL");
+    try expectEqual(@as(usize, 5), runtime.stack.depth());
+    var idx: usize = 0;
+    while (idx < 5) : (idx += 1) try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+
+    try expectError(InternalError.TypeError, runtime.eval("4 @CALL"));
+    try expectError(InternalError.TypeError, runtime.eval("&@DUP @COMPOSE"));
+    var word = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&word);
+    try expectEqual(@as(usize, 4), try runtime.stack_pop_value(usize));
+}
+
+/// @CAPTURE ( @n ... @1 Word UnsignedInt -> Word )
+///
+/// Close the Word over the UnsignedInt many values beneath it, returning a
+/// word which pushes them (@n first, so that they land as they were) and then
+/// runs the Word: `10 20 &@SWAP 2 @CAPTURE` is a word leaving `20 10`. The
+/// values are wrapped as @LIT would wrap them, so Opaques are a TypeError.
+pub fn CAPTURE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+    const count = peeked.UnsignedInt;
+
+    const depth = runtime.stack.depth();
+    if (depth < 2 or depth - 2 < count) return StackManipulationError.Underflow;
+    const body = runtime.stack.nth_from_top(1).?;
+    if (body.* != .Word) return InternalError.TypeError;
+
+    var idx: usize = 0;
+    while (idx < count) : (idx += 1) {
+        if (runtime.stack.nth_from_top(2 + idx).?.* == .Opaque) return InternalError.TypeError;
+    }
+
+    const word = try closure_word(runtime, count, body.Word);
+
+    idx = 0;
+    while (idx < count + 2) : (idx += 1) try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_raw_word(word);
+}
+
+fn closure_word(runtime: *Runtime, count: usize, body: *Types.HeapedWord) !*Types.HeapedWord {
+    const inner = try runtime.alloc.alloc(*Types.HeapedWord, count + 1);
+    var filled: usize = 0;
+    errdefer {
+        for (inner[0..filled]) |member| {
+            var owned = Object{ .Word = member };
+            runtime.release_heaped_object_reference(&owned);
+        }
+        runtime.alloc.free(inner);
+    }
+
+    // Compound words hold a reference to each of their inner words, which,
+    // unlike @COMPOSE's, are still the Stack's, and so need one of their own.
+    while (filled < count) : (filled += 1) {
+        const literal = try literal_word(runtime, runtime.stack.nth_from_top(count + 1 - filled).?);
+        literal.increment() catch unreachable;
+        inner[filled] = literal;
+    }
+    try body.increment();
+    inner[count] = body;
+    filled = count + 1;
+
+    return try runtime.word_from_compound_impl(inner, null);
+}
+
+/// A fresh word pushing `obj`, as @LIT makes them, but holding a reference of
+/// its own rather than taking over the Stack's.
+pub fn literal_word(runtime: *Runtime, obj: *const Object) !*Types.HeapedWord {
+    const banished = try runtime.alloc.create(Object);
+    banished.* = obj.ref() catch |err| {
+        runtime.alloc.destroy(banished);
+        return err;
+    };
+    return runtime.word_from_heaplit_impl(banished, literal_signature(runtime, banished.*)) catch |err| {
+        runtime.release_heaped_object_reference(banished);
+        runtime.alloc.destroy(banished);
+        return err;
+    };
+}
+
+test "CAPTURE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("10 20 &@SWAP 2 @CAPTURE");
+    try expectEqual(@as(usize, 1), runtime.stack.depth());
+    try runtime.eval("@DUP @CALL @CALL");
+    try expectEqual(@as(usize, 4), runtime.stack.depth());
+    try expectEqual(@as(usize, 10), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 20), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 10), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 20), try runtime.stack_pop_value(usize));
+
+    // Captured heaped values are shared, not copied, and outlive the Stack's
+    // own references to them.
+    try runtime.eval("\"over\" &@DUP 1 @CAPTURE @CALL");
+    try expectEqual(@as(usize, 2), runtime.stack.depth());
+    var idx: usize = 0;
+    while (idx < 2) : (idx += 1) {
+        var str = try runtime.stack_pop();
+        try std.testing.expectEqualStrings("over", str.String.value.?);
+        runtime.release_heaped_object_reference(&str);
+    }
+
+    try runtime.eval("3 &@DUP 0 @CAPTURE @CALL");
+    try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+
+    try expectError(StackManipulationError.Underflow, runtime.eval("1 &@DUP 2 @CAPTURE"));
+    try expectError(InternalError.TypeError, runtime.eval("@DROP 2 1 @CAPTURE"));
+    try runtime.eval("@DROP @DROP @DROP @DROP");
+}
+
+/// @CONSTANT ( @1 Symbol -> nothing )
+///
+/// Define a word under the Symbol's name (as @DEFINE would, module
+/// vocabularies and all) which pushes @1, and is marked as data rather than
+/// code (see Word.Flags): `3.14159 :pi @CONSTANT`.
+pub fn CONSTANT(runtime: *Runtime) anyerror!void {
+    try define_data(runtime, false);
+}
+
+/// @VALUE ( @1 Symbol -> nothing )
+///
+/// As @CONSTANT, but the value can later be changed with @TO: `0 :count
+/// @VALUE`.
+pub fn VALUE(runtime: *Runtime) anyerror!void {
+    try define_data(runtime, true);
+}
+
+fn define_data(runtime: *Runtime, mutable: bool) !void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Symbol) return InternalError.TypeError;
+    // As with @LIT.
+    if (far.* == .Opaque) return InternalError.TypeError;
+
+    const word = try literal_word(runtime, far);
+    word.value.?.flags.data = true;
+    word.value.?.flags.mutable = mutable;
+
+    runtime.define_word_here(peek.near.Symbol.value.?, word) catch |err| {
+        // Object.deinit expects to be dropping a reference, so give it one.
+        word.increment() catch unreachable;
+        var unowned = Object{ .Word = word };
+        runtime.release_heaped_object_reference(&unowned);
+        return err;
+    };
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @TO ( @1 Symbol -> nothing )
+///
+/// Change the value defined (by @VALUE) under the Symbol's name to @1, which
+/// must be of the same kind as the value it replaces (lest the word's
+/// signature stop telling the truth), or else it's a TypeError. Names which
+/// aren't values, constants included, are a ValueError.
+pub fn TO(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Symbol) return InternalError.TypeError;
+
+    const word = (try runtime.find_word(peek.near.Symbol.value.?)) orelse return InternalError.UnknownWord;
+    const inner = &word.value.?;
+    if (!inner.flags.mutable) return InternalError.ValueError;
+    const slot = inner.impl.HeapLit;
+    if (std.meta.activeTag(slot.*) != std.meta.activeTag(far.*)) return InternalError.TypeError;
+
+    var popped = try runtime.stack_pop_pair();
+    runtime.release_heaped_object_reference(&popped.near);
+    // The slot takes over the Stack's reference to the new value.
+    var old = slot.*;
+    slot.* = popped.far;
+    runtime.release_heaped_object_reference(&old);
+}
+
+test "CONSTANT, VALUE, and TO" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("3 :three @CONSTANT \"hi\" :greeting @VALUE three greeting");
+    var greeting = try runtime.stack_pop();
+    try std.testing.expectEqualStrings("hi", greeting.String.value.?);
+    runtime.release_heaped_object_reference(&greeting);
+    try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+
+    const three = (try runtime.find_word("three")).?.value.?;
+    try expect(three.flags.data and !three.flags.mutable);
+    try expect((try runtime.find_word("greeting")).?.value.?.flags.mutable);
+
+    try runtime.eval("\"bye\" :greeting @TO greeting");
+    greeting = try runtime.stack_pop();
+    try std.testing.expectEqualStrings("bye", greeting.String.value.?);
+    runtime.release_heaped_object_reference(&greeting);
+
+    try expectError(InternalError.ValueError, runtime.eval("4 :three @TO"));
+    try runtime.eval("@DROP @DROP");
+    try expectError(InternalError.TypeError, runtime.eval("4 :greeting @TO"));
+    try runtime.eval("@DROP @DROP");
+    try expectError(InternalError.UnknownWord, runtime.eval("4 :nope @TO"));
+    try runtime.eval("@DROP @DROP");
+}
+
 /// @SWAP ( @2 @1 -> @2 @1 )
-pub fn SWAP(runtime: *Runtime) !void {
+pub fn SWAP(runtime: *Runtime) anyerror!void {
     try runtime.stack_wrangle(.SwapTopTwoObjects);
 }
 
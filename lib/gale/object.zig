@@ -28,10 +28,19 @@ pub const Object = union(enum) {
 
     Array: *Types.HeapedArray,
     Boolean: bool,
+    /// A mutable, shareable box holding a single Object. See cell.zig.
+    Cell: *Types.HeapedCell,
+    /// A queue for passing Objects between Tasks, or threads. See
+    /// channel.zig.
+    Channel: *Types.HeapedChannel,
     Float: f64,
+    /// Objects filed under string keys. See map.zig.
+    Map: *Types.HeapedMap,
     /// Opaque represents a blob of memory that is left to userspace to manage
     /// manually. TODO more docs here.
     Opaque: *Types.HeapedOpaque,
+    /// Distinct Objects, in the order they were first added. See set.zig.
+    Set: *Types.HeapedSet,
     SignedInt: isize,
     String: *Types.HeapedString,
     Symbol: *Types.HeapedSymbol,
@@ -41,24 +50,41 @@ pub const Object = union(enum) {
     pub fn deinit(self: *Self, alloc: Allocator) void {
         switch (self.*) {
             .Array => |inner| {
-                // First, we need to deref and kill all the objects stored
-                // in this array, garbage collecting the inner contents as
-                // necessary.
+                // If (and only if) ours is the last reference to this array,
+                // we need to deref and kill all the objects stored in it,
+                // garbage collecting the inner contents as necessary. Anyone
+                // else still holding the array still needs its contents.
                 //
                 // Using ? here because if we have an Rc with no contents at
                 // this point, something has gone horribly, horribly wrong, and
                 // panicking the thread is appropriate.
-                for (inner.value.?.items) |_it| {
-                    var it = _it;
-                    it.deinit(alloc);
+                if (inner.strong_count.load(.Acquire) == 1) {
+                    for (inner.value.?.items) |_it| {
+                        var it = _it;
+                        it.deinit(alloc);
+                    }
                 }
 
-                // Now we can toss this Object and the ArrayList stored within.
-                // Passing alloc here is necessary by type signature only; it
-                // won't be used (since ArrayList is a ManagedStruct).
-                _ = inner.decrement_and_prune(.DeinitInner, alloc);
+                // Now we can toss this Object and the ArrayList stored within,
+                // along with the (heaped) Rc itself.
+                _ = inner.decrement_and_prune(.DeinitInnerDestroySelf, alloc);
             },
             .Boolean, .Float, .SignedInt, .UnsignedInt => {},
+            .Cell => |inner| {
+                _ = inner.decrement_and_prune(.DeinitInnerWithAllocDestroySelf, alloc);
+            },
+            // Anything still queued is torn down along with the Channel.
+            .Channel => |inner| {
+                _ = inner.decrement_and_prune(.DeinitInnerWithAllocDestroySelf, alloc);
+            },
+            // As with Arrays, the values go when the last reference does,
+            // which Map.deinit takes care of.
+            .Map => |inner| {
+                _ = inner.decrement_and_prune(.DeinitInnerWithAllocDestroySelf, alloc);
+            },
+            .Set => |inner| {
+                _ = inner.decrement_and_prune(.DeinitInnerWithAllocDestroySelf, alloc);
+            },
             .String, .Symbol => |inner| {
                 _ = inner.decrement_and_prune(.FreeInnerDestroySelf, alloc);
             },
@@ -79,6 +105,10 @@ pub const Object = union(enum) {
         switch (self) {
             .Array => |rc| try rc.increment(),
             .Boolean, .Float, .SignedInt, .UnsignedInt => {},
+            .Cell => |rc| try rc.increment(),
+            .Channel => |rc| try rc.increment(),
+            .Map => |rc| try rc.increment(),
+            .Set => |rc| try rc.increment(),
             .String => |rc| try rc.increment(),
             .Symbol => |rc| try rc.increment(),
             .Opaque => |rc| try rc.increment(),
@@ -88,6 +118,168 @@ pub const Object = union(enum) {
         return self;
     }
 
+    /// Whether two Objects are the same kind, and hold the same value. Strings
+    /// and Symbols compare by contents, and Arrays, Maps, and Sets by their
+    /// members, while everything else on the heap (Words, Cells, etc.) is
+    /// only ever equal to itself.
+    pub fn eql(self: Self, other: Self) bool {
+        if (std.meta.activeTag(self) != std.meta.activeTag(other)) return false;
+
+        return switch (self) {
+            .Array => |arr| arr == other.Array or members_eql(arr.value.?.items, other.Array.value.?.items),
+            .Boolean => |val| val == other.Boolean,
+            .Cell => |cell| cell == other.Cell,
+            .Channel => |chan| chan == other.Channel,
+            .Float => |val| val == other.Float,
+            .Map => |map| map == other.Map or map.value.?.eql(&other.Map.value.?),
+            .Opaque => |val| val == other.Opaque,
+            .Set => |set| set == other.Set or set.value.?.eql(&other.Set.value.?),
+            .SignedInt => |val| val == other.SignedInt,
+            .String => |str| str == other.String or std.mem.eql(u8, str.value.?, other.String.value.?),
+            .Symbol => |sym| sym == other.Symbol or std.mem.eql(u8, sym.value.?, other.Symbol.value.?),
+            .UnsignedInt => |val| val == other.UnsignedInt,
+            .Word => |word| word == other.Word,
+        };
+    }
+
+    fn members_eql(ours: []const Self, theirs: []const Self) bool {
+        if (ours.len != theirs.len) return false;
+
+        for (ours) |item, idx| {
+            if (!item.eql(theirs[idx])) return false;
+        }
+
+        return true;
+    }
+
+    test "eql" {
+        var one = "one".*;
+        var also_one = "one".*;
+        var one_str = Types.HeapedString.init(one[0..]);
+        var also_one_str = Types.HeapedString.init(also_one[0..]);
+
+        try std.testing.expect((Self{ .String = &one_str }).eql(.{ .String = &also_one_str }));
+        try std.testing.expect((Self{ .UnsignedInt = 1 }).eql(.{ .UnsignedInt = 1 }));
+        try std.testing.expect(!(Self{ .UnsignedInt = 1 }).eql(.{ .UnsignedInt = 2 }));
+        try std.testing.expect(!(Self{ .UnsignedInt = 1 }).eql(.{ .SignedInt = 1 }));
+    }
+
+    /// How this Object sorts against `other`, which must be of the same kind
+    /// (a TypeError otherwise, as with @EQ). Numbers sort numerically,
+    /// Booleans false first, Strings and Symbols bytewise (so, by codepoint),
+    /// and Arrays member by member, with any Array sorting before those it's
+    /// the start of. Floats sort against everything but NaN, which is a
+    /// ValueError. Nothing else on the heap (Words, Cells, etc.) has an
+    /// order, and so is a TypeError.
+    pub fn order(self: Self, other: Self) InternalError!std.math.Order {
+        if (std.meta.activeTag(self) != std.meta.activeTag(other)) return InternalError.TypeError;
+
+        return switch (self) {
+            .Array => |arr| members_order(arr.value.?.items, other.Array.value.?.items),
+            .Boolean => |val| std.math.order(@boolToInt(val), @boolToInt(other.Boolean)),
+            .Float => |val| if (std.math.isNan(val) or std.math.isNan(other.Float))
+                InternalError.ValueError
+            else
+                std.math.order(val, other.Float),
+            .SignedInt => |val| std.math.order(val, other.SignedInt),
+            .String => |str| std.mem.order(u8, str.value.?, other.String.value.?),
+            .Symbol => |sym| std.mem.order(u8, sym.value.?, other.Symbol.value.?),
+            .UnsignedInt => |val| std.math.order(val, other.UnsignedInt),
+            .Cell, .Channel, .Map, .Opaque, .Set, .Word => InternalError.TypeError,
+        };
+    }
+
+    fn members_order(ours: []const Self, theirs: []const Self) InternalError!std.math.Order {
+        for (ours) |item, idx| {
+            if (idx >= theirs.len) return .gt;
+            const ordering = try item.order(theirs[idx]);
+            if (ordering != .eq) return ordering;
+        }
+
+        return std.math.order(ours.len, theirs.len);
+    }
+
+    test "order" {
+        var ab = "ab".*;
+        var abc = "abc".*;
+        var ab_str = Types.HeapedString.init(ab[0..]);
+        var abc_str = Types.HeapedString.init(abc[0..]);
+
+        try std.testing.expectEqual(std.math.Order.lt, try (Self{ .String = &ab_str }).order(.{ .String = &abc_str }));
+        try std.testing.expectEqual(std.math.Order.gt, try (Self{ .SignedInt = 1 }).order(.{ .SignedInt = -1 }));
+        try std.testing.expectEqual(std.math.Order.eq, try (Self{ .Float = 0.0 }).order(.{ .Float = -0.0 }));
+        try std.testing.expectEqual(std.math.Order.lt, try (Self{ .Boolean = false }).order(.{ .Boolean = true }));
+        try std.testing.expectError(InternalError.TypeError, (Self{ .UnsignedInt = 1 }).order(.{ .SignedInt = 1 }));
+        try std.testing.expectError(InternalError.ValueError, (Self{ .Float = std.math.nan(f64) }).order(.{ .Float = 1.0 }));
+    }
+
+    /// Render this Object for humans (by way of @PRINT, the REPL, and so on)
+    /// through the usual std.fmt machinery, so `{}` does the trick. Strings
+    /// are written raw, Symbols get their leading colon back, and Arrays are
+    /// written member-by-member between brackets (and Maps between braces,
+    /// and Sets between `#{` and `}`).
+    /// Everything else on the heap has no sensible textual form, and so just
+    /// gets its kind.
+    pub fn format(
+        self: Self,
+        comptime fmt: []const u8,
+        options: std.fmt.FormatOptions,
+        writer: anytype,
+    ) @TypeOf(writer).Error!void {
+        _ = fmt;
+        _ = options;
+
+        switch (self) {
+            .Array => |arr| {
+                try writer.writeByte('[');
+                for (arr.value.?.items) |item, idx| {
+                    if (idx > 0) try writer.writeByte(' ');
+                    try item.format("", .{}, writer);
+                }
+                try writer.writeByte(']');
+            },
+            .Boolean => |val| try writer.writeAll(if (val) "true" else "false"),
+            .Cell => try writer.writeAll("<cell>"),
+            .Channel => try writer.writeAll("<channel>"),
+            .Float => |val| try writer.print("{d}", .{val}),
+            .Map => |map| {
+                try writer.writeByte('{');
+                for (map.value.?.keys()) |key, idx| {
+                    if (idx > 0) try writer.writeAll(", ");
+                    try writer.print("{s}: ", .{key});
+                    try map.value.?.get(key).?.format("", .{}, writer);
+                }
+                try writer.writeByte('}');
+            },
+            .Opaque => try writer.writeAll("<opaque>"),
+            .Set => |set| {
+                try writer.writeAll("#{");
+                for (set.value.?.items()) |member, idx| {
+                    if (idx > 0) try writer.writeByte(' ');
+                    try member.format("", .{}, writer);
+                }
+                try writer.writeByte('}');
+            },
+            .SignedInt => |val| try writer.print("{d}", .{val}),
+            .String => |str| try writer.writeAll(str.value.?),
+            .Symbol => |sym| try writer.print(":{s}", .{sym.value.?}),
+            .UnsignedInt => |val| try writer.print("{d}", .{val}),
+            .Word => try writer.writeAll("<word>"),
+        }
+    }
+
+    test "format" {
+        var hi = "hi".*;
+        var hi_str = Types.HeapedString.init(hi[0..]);
+        var hi_sym = Types.HeapedSymbol.init(hi[0..]);
+
+        var buf: [32]u8 = undefined;
+        try std.testing.expectEqualStrings("hi", try std.fmt.bufPrint(&buf, "{}", .{Self{ .String = &hi_str }}));
+        try std.testing.expectEqualStrings(":hi", try std.fmt.bufPrint(&buf, "{}", .{Self{ .Symbol = &hi_sym }}));
+        try std.testing.expectEqualStrings("-3", try std.fmt.bufPrint(&buf, "{}", .{Self{ .SignedInt = -3 }}));
+        try std.testing.expectEqualStrings("true", try std.fmt.bufPrint(&buf, "{}", .{Self{ .Boolean = true }}));
+    }
+
     /// Raise an `InternalError.TypeError` if this object is not the same primitive
     /// kind as `other`.
     ///
@@ -0,0 +1,69 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const Types = @import("./types.zig");
+
+/// Things an Observer can be told about. Pointers handed out here are only
+/// valid for the duration of the callback: copy (and, for heaped Objects,
+/// ref()) anything you need to hold onto.
+pub const Event = union(enum) {
+    /// A word is about to run.
+    WordStart: *Types.HeapedWord,
+    /// A word ran to completion.
+    WordEnd: *Types.HeapedWord,
+    /// An Object was pushed onto the stack, whether by a literal, a word, or
+    /// the host. Words pushing several at once (say, @2DUPSHUF) send one of
+    /// these for each, in the order they sit on the stack.
+    Push: *const Object,
+    /// An Object was taken off the stack, whether to be used or dropped.
+    Pop: *const Object,
+    /// A word failed. Since errors unwind through every word between the
+    /// failure and the top level, expect one of these per word unwound.
+    Error: anyerror,
+    /// A word was added to the dictionary (see Runtime.define_word).
+    Defined: Definition,
+    /// The first word was defined into a vocabulary (see vocabulary.zig),
+    /// just before its Defined.
+    VocabularyCreated: []const u8,
+    /// eval_source (and thus @LOAD, @IMPORT, and the like) got through a
+    /// source, given by path, without failing.
+    Loaded: []const u8,
+
+    pub const Definition = struct {
+        /// Fully qualified.
+        name: []const u8,
+        word: *Types.HeapedWord,
+    };
+};
+
+pub const ObserverFn = fn (context: ?*anyopaque, rt: *Runtime, event: Event) void;
+
+/// A host-installed set of eyes on the interpreter, for debuggers, profilers,
+/// and other instrumentation that would rather not fork the Runtime. As with
+/// HostClosure, `context` belongs to the host, and must outlive the
+/// Observer's installation.
+///
+/// Observers are called synchronously and in installation order, and must not
+/// install or remove Observers themselves.
+pub const Observer = struct {
+    context: ?*anyopaque,
+    on_event: *const ObserverFn,
+};
+
+test {
+    std.testing.refAllDecls(@This());
+}
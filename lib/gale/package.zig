@@ -0,0 +1,406 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const helpers = @import("./helpers.zig");
+const library_path = @import("./library_path.zig");
+const toml = @import("./toml.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Map = @import("./map.zig").Map;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// Packages: a directory of gale source with a manifest (MANIFEST_NAME, in
+// TOML) at its root, saying what it's called and what it depends on:
+//
+//     name = "shapes"
+//     version = "0.1.0"
+//
+//     [dependencies]
+//     strings = { git = "https://example.com/strings.git", rev = "v1.2.0" }
+//     geometry = { path = "../geometry" }
+//
+// `fetch` clones git dependencies into a store (DEFAULT_STORE, relative to
+// the manifest, unless told otherwise), and `roots` says where each
+// dependency ended up, for the library path (see library_path.zig): once
+// fetched, `"strings" @IMPORT` finds the dependency's own strings.gale.
+//
+// Only the top-level manifest is read: dependencies' own dependencies are
+// theirs to bring along, for now.
+
+pub const MANIFEST_NAME = "gale.toml";
+
+/// Where fetched dependencies live, relative to their manifest's directory.
+pub const DEFAULT_STORE = ".gale" ++ std.fs.path.sep_str ++ "deps";
+
+// TODO: configurable in build.zig
+pub const MAX_MANIFEST_SIZE = 1024 * 1024;
+
+pub const PackageError = error{
+    /// `git` (or whatever the Fetcher ran) didn't succeed.
+    FetchFailed,
+};
+
+pub const Source = union(enum) {
+    /// Cloned into the store, and checked out at `rev` (any commit, tag, or
+    /// branch), if given.
+    Git: struct {
+        url: []const u8,
+        rev: ?[]const u8,
+    },
+    /// A directory, relative to the manifest, used right where it is.
+    Path: []const u8,
+};
+
+pub const Dependency = struct {
+    name: []const u8,
+    source: Source,
+};
+
+/// Everything in it belongs to its arena.
+pub const Manifest = struct {
+    const Self = @This();
+
+    arena: std.heap.ArenaAllocator,
+    name: []const u8,
+    version: []const u8,
+    /// In the order the manifest gives them.
+    dependencies: []const Dependency,
+
+    pub fn deinit(self: *Self) void {
+        self.arena.deinit();
+    }
+};
+
+/// Parse `text` as a manifest. Missing names or versions, dependencies with
+/// neither (or both) a `git` or `path` source, dependency names which
+/// wouldn't make a directory name (see is_valid_name), and git URLs or revs
+/// which git would take for options (starting with `-`) are ValueErrors, as
+/// is anything which isn't TOML.
+pub fn parse_manifest(alloc: Allocator, text: []const u8) !Manifest {
+    // TOML parses into Objects, and thus needs a Runtime to put them in,
+    // though not a single word.
+    var rt = try Runtime.init_with_options(alloc, .{
+        .dictionary_capacity = 0,
+        .primitives = Runtime.PrimitiveGroups.NONE,
+    });
+    defer rt.deinit();
+
+    var parsed = try (try toml.parse(&rt, text)).ref();
+    defer rt.release_heaped_object_reference(&parsed);
+    const root = &parsed.Map.value.?;
+
+    var manifest = Manifest{
+        .arena = std.heap.ArenaAllocator.init(alloc),
+        .name = undefined,
+        .version = undefined,
+        .dependencies = &.{},
+    };
+    errdefer manifest.deinit();
+    const arena = manifest.arena.allocator();
+
+    manifest.name = try arena.dupe(u8, try required_string(root, "name"));
+    manifest.version = try arena.dupe(u8, try required_string(root, "version"));
+    if (!is_valid_name(manifest.name)) return InternalError.ValueError;
+
+    const deps_obj = root.get("dependencies") orelse return manifest;
+    if (deps_obj != .Map) return InternalError.ValueError;
+    const deps = &deps_obj.Map.value.?;
+
+    const dependencies = try arena.alloc(Dependency, deps.count());
+    for (deps.keys()) |name, idx| {
+        if (!is_valid_name(name)) return InternalError.ValueError;
+
+        const spec_obj = deps.get(name).?;
+        if (spec_obj != .Map) return InternalError.ValueError;
+        const spec = &spec_obj.Map.value.?;
+
+        const git = try optional_string(spec, "git");
+        const path = try optional_string(spec, "path");
+        const rev = try optional_string(spec, "rev");
+        if (looks_like_option(git) or looks_like_option(rev)) return InternalError.ValueError;
+        const source: Source = if (git != null and path == null) .{ .Git = .{
+            .url = try arena.dupe(u8, git.?),
+            .rev = if (rev) |given| try arena.dupe(u8, given) else null,
+        } } else if (path != null and git == null and spec.get("rev") == null)
+            .{ .Path = try arena.dupe(u8, path.?) }
+        else
+            return InternalError.ValueError;
+
+        dependencies[idx] = .{ .name = try arena.dupe(u8, name), .source = source };
+    }
+
+    manifest.dependencies = dependencies;
+    return manifest;
+}
+
+fn looks_like_option(arg: ?[]const u8) bool {
+    const given = arg orelse return false;
+    return given.len > 0 and given[0] == '-';
+}
+
+/// Read the manifest in the directory `dir_path`.
+pub fn read_manifest(alloc: Allocator, dir_path: []const u8) !Manifest {
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const path = try std.fs.path.join(alloc, &.{ dir_path, MANIFEST_NAME });
+    defer alloc.free(path);
+    const text = std.fs.cwd().readFileAlloc(alloc, path, MAX_MANIFEST_SIZE) catch |err| switch (err) {
+        error.FileTooBig => return InternalError.LimitExceeded,
+        else => return err,
+    };
+    defer alloc.free(text);
+
+    return try parse_manifest(alloc, text);
+}
+
+fn required_string(table: *const Map, key: []const u8) ![]const u8 {
+    return (try optional_string(table, key)) orelse return InternalError.ValueError;
+}
+
+fn optional_string(table: *const Map, key: []const u8) !?[]const u8 {
+    const value = table.get(key) orelse return null;
+    if (value != .String) return InternalError.ValueError;
+    return value.String.value.?;
+}
+
+/// Whether `name` can name a dependency, and thus a directory in the store:
+/// neither empty nor `.` or `..`, and free of path separators.
+pub fn is_valid_name(name: []const u8) bool {
+    if (name.len == 0) return false;
+    if (std.mem.eql(u8, name, ".") or std.mem.eql(u8, name, "..")) return false;
+    for (name) |char| {
+        if (char == '/' or char == std.fs.path.sep) return false;
+    }
+    return true;
+}
+
+/// Where `dep` lives (or, for git dependencies not yet fetched, will),
+/// given the directory its manifest is in and the store (see DEFAULT_STORE,
+/// which is relative to that directory). The caller owns the result.
+pub fn root_of(alloc: Allocator, dep: Dependency, manifest_dir: []const u8, store: []const u8) ![]u8 {
+    return switch (dep.source) {
+        .Git => try std.fs.path.join(alloc, &.{ manifest_dir, store, dep.name }),
+        .Path => |path| if (std.fs.path.isAbsolute(path))
+            try alloc.dupe(u8, path)
+        else
+            try std.fs.path.join(alloc, &.{ manifest_dir, path }),
+    };
+}
+
+/// Every dependency's root (see root_of), in manifest order, for
+/// Runtime.Options.library_path. Free the result with library_path.free.
+pub fn roots(alloc: Allocator, manifest: *const Manifest, manifest_dir: []const u8, store: []const u8) ![][]u8 {
+    var found = std.ArrayList([]u8).init(alloc);
+    errdefer {
+        for (found.items) |root| alloc.free(root);
+        found.deinit();
+    }
+
+    for (manifest.dependencies) |dep| {
+        const root = try root_of(alloc, dep, manifest_dir, store);
+        errdefer alloc.free(root);
+        try found.append(root);
+    }
+
+    return found.toOwnedSlice();
+}
+
+/// Runs a command (`git`, as far as fetch is concerned) to completion,
+/// returning whether it succeeded. Swappable so that hosts (and tests) can
+/// fetch some other way.
+pub const Fetcher = struct {
+    ctx: ?*anyopaque = null,
+    run: *const fn (ctx: ?*anyopaque, alloc: Allocator, argv: []const []const u8) anyerror!bool = &run_child,
+};
+
+fn run_child(ctx: ?*anyopaque, alloc: Allocator, argv: []const []const u8) anyerror!bool {
+    _ = ctx;
+    var child = std.ChildProcess.init(argv, alloc);
+    const term = try child.spawnAndWait();
+    return switch (term) {
+        .Exited => |code| code == 0,
+        else => false,
+    };
+}
+
+/// Make every dependency available at its root (see root_of): git
+/// dependencies not already in the store are cloned there (and checked out
+/// at their `rev`, if any), and path dependencies are checked for. Anything
+/// already in the store is left as it is: delete it to fetch it anew. Says
+/// what it's doing, a line per dependency, to `writer`.
+pub fn fetch(
+    alloc: Allocator,
+    manifest: *const Manifest,
+    manifest_dir: []const u8,
+    store: []const u8,
+    fetcher: Fetcher,
+    writer: anytype,
+) !void {
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    for (manifest.dependencies) |dep| {
+        const root = try root_of(alloc, dep, manifest_dir, store);
+        defer alloc.free(root);
+
+        const present = if (std.fs.cwd().access(root, .{})) |_| true else |_| false;
+
+        switch (dep.source) {
+            .Path => {
+                if (!present) return error.FileNotFound;
+                try writer.print("using {s} at {s}\n", .{ dep.name, root });
+            },
+            .Git => |git| {
+                if (present) {
+                    try writer.print("using {s} at {s}\n", .{ dep.name, root });
+                    continue;
+                }
+
+                try writer.print("fetching {s} from {s}\n", .{ dep.name, git.url });
+                if (std.fs.path.dirname(root)) |parent| try std.fs.cwd().makePath(parent);
+
+                // parse_manifest turns away URLs and revs starting with `-`,
+                // but git is told where its options end regardless.
+                if (!try fetcher.run(fetcher.ctx, alloc, &.{ "git", "clone", "--quiet", "--", git.url, root })) {
+                    return PackageError.FetchFailed;
+                }
+                if (git.rev) |rev| {
+                    // For checkout, `--` would mean that paths follow.
+                    if (!try fetcher.run(fetcher.ctx, alloc, &.{ "git", "-C", root, "checkout", "--quiet", "--end-of-options", rev })) {
+                        // Leave nothing half-fetched for next time to mistake
+                        // for the real thing.
+                        std.fs.cwd().deleteTree(root) catch {};
+                        return PackageError.FetchFailed;
+                    }
+                }
+            },
+        }
+    }
+}
+
+const EXAMPLE =
+    \\name = "shapes"
+    \\version = "0.1.0"
+    \\
+    \\[dependencies]
+    \\strings = { git = "https://example.com/strings.git", rev = "v1.2.0" }
+    \\geometry = { path = "../geometry" }
+;
+
+test "parse_manifest" {
+    var manifest = try parse_manifest(testAllocator, EXAMPLE);
+    defer manifest.deinit();
+
+    try expectEqualStrings("shapes", manifest.name);
+    try expectEqualStrings("0.1.0", manifest.version);
+    try expectEqual(@as(usize, 2), manifest.dependencies.len);
+
+    const strings = manifest.dependencies[0];
+    try expectEqualStrings("strings", strings.name);
+    try expectEqualStrings("https://example.com/strings.git", strings.source.Git.url);
+    try expectEqualStrings("v1.2.0", strings.source.Git.rev.?);
+    try expectEqualStrings("../geometry", manifest.dependencies[1].source.Path);
+
+    var bare = try parse_manifest(testAllocator, "name = \"bare\"\nversion = \"1\"");
+    defer bare.deinit();
+    try expectEqual(@as(usize, 0), bare.dependencies.len);
+
+    try expectError(InternalError.ValueError, parse_manifest(testAllocator, "name = \"nameless\""));
+    try expectError(InternalError.ValueError, parse_manifest(
+        testAllocator,
+        "name = \"a\"\nversion = \"1\"\n[dependencies]\nb = { git = \"x\", path = \"y\" }",
+    ));
+    try expectError(InternalError.ValueError, parse_manifest(
+        testAllocator,
+        "name = \"a\"\nversion = \"1\"\n[dependencies]\n\"..\" = { path = \"y\" }",
+    ));
+    try expectError(InternalError.ValueError, parse_manifest(
+        testAllocator,
+        "name = \"a\"\nversion = \"1\"\n[dependencies]\nb = { git = \"--upload-pack=touch /tmp/pwned\" }",
+    ));
+    try expectError(InternalError.ValueError, parse_manifest(
+        testAllocator,
+        "name = \"a\"\nversion = \"1\"\n[dependencies]\nb = { git = \"https://example.com/b.git\", rev = \"-p\" }",
+    ));
+}
+
+test "roots" {
+    var manifest = try parse_manifest(testAllocator, EXAMPLE);
+    defer manifest.deinit();
+
+    const found = try roots(testAllocator, &manifest, "app", DEFAULT_STORE);
+    defer library_path.free(testAllocator, found);
+
+    const sep = std.fs.path.sep_str;
+    try expectEqualStrings("app" ++ sep ++ DEFAULT_STORE ++ sep ++ "strings", found[0]);
+    try expectEqualStrings("app" ++ sep ++ ".." ++ sep ++ "geometry", found[1]);
+}
+
+const RecordingFetcher = struct {
+    commands: usize = 0,
+
+    fn run(ctx: ?*anyopaque, alloc: Allocator, argv: []const []const u8) anyerror!bool {
+        _ = alloc;
+        const self = @ptrCast(*RecordingFetcher, @alignCast(@alignOf(RecordingFetcher), ctx.?));
+        self.commands += 1;
+        try expectEqualStrings("git", argv[0]);
+        // Whatever came from the manifest follows the end of git's options.
+        for (argv) |arg| {
+            if (std.mem.eql(u8, arg, "--") or std.mem.eql(u8, arg, "--end-of-options")) return true;
+        }
+        return error.TestUnexpectedResult;
+    }
+};
+
+test "fetch" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(testAllocator, ".");
+    defer testAllocator.free(root);
+    try tmp.dir.makeDir("geometry");
+
+    const text = "name = \"a\"\nversion = \"1\"\n[dependencies]\n" ++
+        "strings = { git = \"https://example.com/strings.git\", rev = \"v1\" }\n" ++
+        "geometry = { path = \"geometry\" }";
+    var manifest = try parse_manifest(testAllocator, text);
+    defer manifest.deinit();
+
+    var recorder = RecordingFetcher{};
+    const fetcher = Fetcher{ .ctx = &recorder, .run = &RecordingFetcher.run };
+    var log = std.ArrayList(u8).init(testAllocator);
+    defer log.deinit();
+
+    // A clone and a checkout, though our fake git leaves nothing behind.
+    try fetch(testAllocator, &manifest, root, DEFAULT_STORE, fetcher, log.writer());
+    try expectEqual(@as(usize, 2), recorder.commands);
+    try expect(std.mem.startsWith(u8, log.items, "fetching strings from https://example.com/strings.git\n"));
+
+    // Once in the store, that's that.
+    try tmp.dir.makePath(DEFAULT_STORE ++ std.fs.path.sep_str ++ "strings");
+    try fetch(testAllocator, &manifest, root, DEFAULT_STORE, fetcher, log.writer());
+    try expectEqual(@as(usize, 2), recorder.commands);
+
+    try tmp.dir.deleteDir("geometry");
+    try expectError(error.FileNotFound, fetch(testAllocator, &manifest, root, DEFAULT_STORE, fetcher, log.writer()));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,259 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const conversion = @import("./conversion.zig");
+const sync = @import("./sync.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const THREADED = sync.THREADED;
+const Types = @import("./types.zig");
+
+/// Every parallel word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@PMAP", PMAP },
+};
+
+/// Define every parallel word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// How each element of a @PMAP went.
+const Outcome = union(enum) {
+    /// Not yet run, or already moved into the results.
+    Pending,
+    /// Owned by whoever holds the Outcome.
+    Value: Object,
+    Failure: anyerror,
+
+    fn of(result: anyerror!Object) Outcome {
+        return if (result) |value| .{ .Value = value } else |err| .{ .Failure = err };
+    }
+};
+
+/// Everything a worker thread needs to chew through its share of a @PMAP.
+/// Workers claim elements one at a time, so that a few slow elements don't
+/// hold everyone else up.
+const Job = struct {
+    alloc: Allocator,
+    options: Runtime.Options,
+    word: *Types.HeapedWord,
+    items: []const Object,
+    outcomes: []Outcome,
+    next: std.atomic.Atomic(usize),
+
+    fn claim(self: *Job) ?usize {
+        const idx = self.next.fetchAdd(1, .Monotonic);
+        return if (idx < self.items.len) idx else null;
+    }
+
+    /// Runs on each worker thread (and the calling thread, too), each with a
+    /// Runtime of its own layered over the shared base.
+    fn work(self: *Job) void {
+        var worker = Runtime.init_with_options(self.alloc, self.options) catch |err| {
+            // Someone has to account for these elements, and it may as well
+            // be us: they'll each fail with our setup error.
+            while (self.claim()) |idx| self.outcomes[idx] = .{ .Failure = err };
+            return;
+        };
+        defer worker.deinit();
+
+        while (self.claim()) |idx| {
+            self.outcomes[idx] = Outcome.of(worker.run_word_isolated(self.word, self.items[idx]));
+        }
+    }
+};
+
+/// @PMAP ( Array Word <- Array Array )
+///
+/// Run a Word once per element of an Array, each on its own Stack holding
+/// only that element, from which the Word must leave exactly one Object.
+/// Returns two Arrays of the same length as the input, in the same order as
+/// it, regardless of which finished first: the results, and the names (as
+/// Symbols) of any errors raised. Elements which failed have a :null result,
+/// and those which didn't have a :null error.
+///
+/// Runs across the Runtime's worker threads (see Runtime.Options) if it has
+/// any, and a frozen base, in which case the Word can only make use of words
+/// from that base, as each thread gets a Runtime of its own on top of it.
+/// Otherwise, elements are simply run one after another.
+pub fn PMAP(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Word or far.* != .Array) return InternalError.TypeError;
+
+    const word = peek.near.Word;
+    const items = far.Array.value.?.items;
+
+    const outcomes = try runtime.alloc.alloc(Outcome, items.len);
+    defer {
+        for (outcomes) |*outcome| switch (outcome.*) {
+            .Value => |*value| runtime.release_heaped_object_reference(value),
+            .Pending, .Failure => {},
+        };
+        runtime.alloc.free(outcomes);
+    }
+    std.mem.set(Outcome, outcomes, .Pending);
+
+    const parallel = THREADED and runtime.worker_threads > 1 and runtime.base != null and items.len > 1;
+
+    if (comptime THREADED) {
+        if (parallel) try run_in_parallel(runtime, word, items, outcomes);
+    }
+
+    if (!parallel) {
+        for (items) |item, idx| outcomes[idx] = Outcome.of(runtime.run_word_isolated(word, item));
+    }
+
+    const null_symbol = Object{ .Symbol = (try runtime.get_or_put_symbol(conversion.NULL_SYMBOL)).value_ptr };
+    const results = try runtime.new_array(items.len);
+    errdefer runtime.release_array(results);
+    const failures = try runtime.new_array(items.len);
+    errdefer runtime.release_array(failures);
+
+    // Arrays hold a reference to each of their members: the results take
+    // over the ones held by the Outcomes.
+    for (outcomes) |*outcome| switch (outcome.*) {
+        .Value => |value| {
+            results.value.?.appendAssumeCapacity(value);
+            outcome.* = .Pending;
+            failures.value.?.appendAssumeCapacity(try null_symbol.ref());
+        },
+        .Failure => |err| {
+            results.value.?.appendAssumeCapacity(try null_symbol.ref());
+            const name = (try runtime.get_or_put_symbol(@errorName(err))).value_ptr;
+            failures.value.?.appendAssumeCapacity(try (Object{ .Symbol = name }).ref());
+        },
+        .Pending => unreachable,
+    };
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_array(results);
+    try runtime.stack_push_array(failures);
+}
+
+fn run_in_parallel(runtime: *Runtime, word: *Types.HeapedWord, items: []const Object, outcomes: []Outcome) !void {
+    var job = Job{
+        .alloc = runtime.alloc,
+        .options = .{
+            // Workers are short-lived and don't define much of anything.
+            .dictionary_capacity = 0,
+            .symbol_pool_capacity = 64,
+            .signature_pool_capacity = 64,
+            .limits = runtime.limits,
+            .capabilities = runtime.capabilities,
+            .base = runtime.base,
+            .worker_threads = 0,
+        },
+        .word = word,
+        .items = items,
+        .outcomes = outcomes,
+        .next = std.atomic.Atomic(usize).init(0),
+    };
+
+    // The calling thread pulls its weight, too, so one fewer is spawned.
+    const wanted = std.math.min(runtime.worker_threads, items.len) - 1;
+    const threads = try runtime.alloc.alloc(std.Thread, wanted);
+    defer runtime.alloc.free(threads);
+
+    var spawned: usize = 0;
+    defer for (threads[0..spawned]) |thread| thread.join();
+
+    // Should we fail to spawn some (or all) of the threads we'd like, those
+    // we do have (including ours) will simply have more to chew through.
+    while (spawned < wanted) : (spawned += 1) {
+        threads[spawned] = std.Thread.spawn(.{}, Job.work, .{&job}) catch break;
+    }
+
+    job.work();
+}
+
+const TestWords = struct {
+    fn double(rt: *Runtime) anyerror!void {
+        const value = try rt.stack_pop_value(usize);
+        try rt.stack_push_uint(value * 2);
+    }
+};
+
+fn expect_pmap_results(rt: *Runtime, expected: []const usize, expected_failure: ?[]const u8) !void {
+    var failures = try rt.stack_pop();
+    defer rt.release_heaped_object_reference(&failures);
+    var results = try rt.stack_pop();
+    defer rt.release_heaped_object_reference(&results);
+
+    for (results.Array.value.?.items) |result, idx| {
+        const failure = failures.Array.value.?.items[idx].Symbol.value.?;
+
+        if (expected_failure) |name| {
+            try expectEqualStrings(conversion.NULL_SYMBOL, result.Symbol.value.?);
+            try expectEqualStrings(name, failure);
+        } else {
+            try expectEqual(expected[idx], result.UnsignedInt);
+            try expectEqualStrings(conversion.NULL_SYMBOL, failure);
+        }
+    }
+
+    try expectEqual(expected.len, results.Array.value.?.items.len);
+}
+
+test "@PMAP: sequentially" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+    try rt.define_primitive_word(null, "double", &TestWords.double);
+
+    try rt.stack_push_value(&[_]usize{ 1, 2, 3 });
+    try rt.eval("&double @PMAP");
+    try expect_pmap_results(&rt, &.{ 2, 4, 6 }, null);
+
+    // Each element must leave exactly one Object behind.
+    try rt.stack_push_value(&[_]usize{ 1, 2 });
+    try rt.eval("&@DROP @PMAP");
+    try expect_pmap_results(&rt, &.{ 0, 0 }, "ValueError");
+}
+
+test "@PMAP: across threads" {
+    if (!THREADED) return error.SkipZigTest;
+
+    var base = try Runtime.init(testAllocator);
+    defer base.deinit_guard_for_empty_stack();
+    try base.define_primitive_word(null, "double", &TestWords.double);
+    try base.freeze();
+
+    var rt = try Runtime.init_with_options(testAllocator, .{ .base = &base, .worker_threads = 4 });
+    defer rt.deinit_guard_for_empty_stack();
+
+    var input: [64]usize = undefined;
+    var expected: [64]usize = undefined;
+    for (input) |*it, idx| {
+        it.* = idx;
+        expected[idx] = idx * 2;
+    }
+
+    try rt.stack_push_value(&input);
+    try rt.eval("&double @PMAP");
+    try expect_pmap_results(&rt, &expected, null);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
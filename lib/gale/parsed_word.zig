@@ -20,6 +20,7 @@ const expectError = std.testing.expectError;
 
 const InternalError = @import("./internal_error.zig").InternalError;
 const helpers = @import("./helpers.zig");
+const test_helpers = @import("./test_helpers.zig");
 
 /// Commas can be placed before and/or after simple word lookups to modify the
 /// behavior of the stack. These convenience modifiers serve to alleviate
@@ -143,6 +144,11 @@ pub const ParsedWord = union(enum) {
         // TODO: This presumes that string quote handling actually happens a
         // level above (read: that the word splitter understands that "these
         // are all one word"), which probably isn't the cleanest design
+        if (input[0] == STRING_WORD_DELIMITER) {
+            // A lone quote both opens and closes nothing at all.
+            if (input.len == 1) return InternalError.InvalidWordName;
+        }
+
         if ((input[0] == STRING_WORD_DELIMITER) and
             (input[input.len - 1] == STRING_WORD_DELIMITER))
         {
@@ -268,6 +274,24 @@ pub const ParsedWord = union(enum) {
         );
     }
 
+    test "errors on lone quotes" {
+        try expectError(InternalError.InvalidWordName, from_input("\""));
+    }
+
+    test "never panics on arbitrary input" {
+        var prng = std.rand.DefaultPrng.init(0x6a1e);
+        const random = prng.random();
+
+        var buf: [16]u8 = undefined;
+        var round: usize = 0;
+        while (round < 10_000) : (round += 1) {
+            const input = buf[0..random.uintAtMost(usize, buf.len)];
+            test_helpers.fill_arbitrary(random, input);
+            // Whatever it parses as (or doesn't), so long as it returns.
+            _ = from_input(input) catch continue;
+        }
+    }
+
     test "parses ints: bare" {
         try expectEqual(@as(usize, 420), (try from_input("420")).UnsignedInt);
     }
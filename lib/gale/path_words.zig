@@ -0,0 +1,194 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const builtin = @import("builtin");
+
+const conversion = @import("./conversion.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+const path = std.fs.path;
+
+/// Every path word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@PATH-EXT", PATH_EXT },
+    .{ "@PATH-FILENAME", PATH_FILENAME },
+    .{ "@PATH-JOIN", PATH_JOIN },
+    .{ "@PATH-NORMALIZE", PATH_NORMALIZE },
+    .{ "@PATH-PARENT", PATH_PARENT },
+};
+
+/// Define every path word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+// These are all purely lexical: nothing here so much as looks at the file
+// system (and so none of them need the Read capability), which means
+// symlinks are none of their concern. Separators are whatever the host
+// platform uses (though Windows' std.fs.path functions accept either).
+
+/// @PATH-JOIN ( String String <- String )
+///
+/// The path on top, relative to the one beneath it, with exactly one
+/// separator between the two.
+pub fn PATH_JOIN(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .String) return InternalError.TypeError;
+
+    const joined = try path.join(runtime.alloc, &.{ far.String.value.?, peek.near.String.value.? });
+    try replace_top(runtime, 2, joined);
+}
+
+/// @PATH-PARENT ( String <- String )
+///
+/// Everything but the final component of the path, or `:null` (see
+/// conversion.NULL_SYMBOL) if it has only the one, like `notes.txt` or `/`.
+pub fn PATH_PARENT(runtime: *Runtime) anyerror!void {
+    const given = try peek_path(runtime);
+    const parent = try conversion.to_object(runtime, path.dirname(given));
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(parent);
+}
+
+/// @PATH-FILENAME ( String <- String )
+///
+/// The final component of the path, which may well be a directory's name.
+/// Empty for paths with no components at all, like `/`.
+pub fn PATH_FILENAME(runtime: *Runtime) anyerror!void {
+    const given = try peek_path(runtime);
+    try replace_top(runtime, 1, try runtime.alloc.dupe(u8, path.basename(given)));
+}
+
+/// @PATH-EXT ( String <- String )
+///
+/// The extension of the path's final component, including its leading dot
+/// (so `.gz` for `notes.tar.gz`), or an empty String if there isn't one.
+/// Dotfiles like `.profile` have no extension.
+pub fn PATH_EXT(runtime: *Runtime) anyerror!void {
+    const given = try peek_path(runtime);
+    try replace_top(runtime, 1, try runtime.alloc.dupe(u8, path.extension(given)));
+}
+
+/// @PATH-NORMALIZE ( String <- String )
+///
+/// Tidy a path up, without changing where it points (symlinks aside):
+/// repeated separators are collapsed, `.` components are dropped, and `..`
+/// components cancel out whatever comes before them. Leading `..`s in
+/// relative paths are kept, in absolute paths they're dropped (the parent of
+/// the root is the root). An empty result is `.`.
+pub fn PATH_NORMALIZE(runtime: *Runtime) anyerror!void {
+    const given = try peek_path(runtime);
+    try replace_top(runtime, 1, try normalize(runtime.alloc, given));
+}
+
+/// See PATH_NORMALIZE. Windows drive letters are treated as just another
+/// component, which they are, mostly.
+fn normalize(alloc: Allocator, given: []const u8) ![]u8 {
+    const absolute = path.isAbsolute(given);
+
+    var components = std.ArrayList([]const u8).init(alloc);
+    defer components.deinit();
+
+    var iter = std.mem.tokenize(u8, given, if (comptime builtin.os.tag == .windows) "/\\" else "/");
+    while (iter.next()) |component| {
+        if (std.mem.eql(u8, component, ".")) continue;
+
+        if (std.mem.eql(u8, component, "..")) {
+            const top = components.items.len;
+            if (top > 0 and !std.mem.eql(u8, components.items[top - 1], "..")) {
+                _ = components.pop();
+                continue;
+            }
+            if (absolute) continue;
+        }
+
+        try components.append(component);
+    }
+
+    const sep = [_]u8{path.sep};
+    const body = try std.mem.join(alloc, &sep, components.items);
+    if (!absolute and body.len > 0) return body;
+    defer alloc.free(body);
+
+    return if (absolute)
+        std.mem.concat(alloc, u8, &[_][]const u8{ &sep, body })
+    else
+        alloc.dupe(u8, ".");
+}
+
+fn peek_path(runtime: *Runtime) ![]const u8 {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+    return peeked.String.value.?;
+}
+
+/// Drop `consumed` Objects, then push `result` (which must have come from
+/// the Runtime's allocator) as a String.
+fn replace_top(runtime: *Runtime, consumed: usize, result: []u8) !void {
+    const str = runtime.new_string_owned(result) catch |err| {
+        runtime.alloc.free(result);
+        return err;
+    };
+
+    var dropped: usize = 0;
+    while (dropped < consumed) : (dropped += 1) try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str);
+}
+
+fn expect_word_result(runtime: *Runtime, input: []const u8, expected: []const u8) !void {
+    try runtime.eval(input);
+    var result = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&result);
+
+    switch (result) {
+        .String => |str| try expectEqualStrings(expected, str.value.?),
+        .Symbol => |sym| try expectEqualStrings(expected, sym.value.?),
+        else => return error.TestUnexpectedResult,
+    }
+}
+
+test "path words" {
+    if (comptime builtin.os.tag == .windows) return error.SkipZigTest;
+
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_word_result(&runtime, "\"/usr/\" \"lib\" @PATH-JOIN", "/usr/lib");
+    try expect_word_result(&runtime, "\"/usr/lib/libc.so\" @PATH-PARENT", "/usr/lib");
+    try expect_word_result(&runtime, "\"libc.so\" @PATH-PARENT", conversion.NULL_SYMBOL);
+    try expect_word_result(&runtime, "\"/usr/lib/\" @PATH-FILENAME", "lib");
+    try expect_word_result(&runtime, "\"notes.tar.gz\" @PATH-EXT", ".gz");
+    try expect_word_result(&runtime, "\".profile\" @PATH-EXT", "");
+    try expect_word_result(&runtime, "\"/a//b/./c/../../d/\" @PATH-NORMALIZE", "/a/d");
+    try expect_word_result(&runtime, "\"/../a\" @PATH-NORMALIZE", "/a");
+    try expect_word_result(&runtime, "\"../a/../../b\" @PATH-NORMALIZE", "../../b");
+    try expect_word_result(&runtime, "\"a/..\" @PATH-NORMALIZE", ".");
+
+    try expectError(InternalError.TypeError, runtime.eval("1 @PATH-EXT"));
+    _ = try runtime.stack_pop();
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const expect = std.testing.expect;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+// Pipelines, which eval understands itself (see Runtime.eval_words): `x |> f
+// |> g` is sugar for `x f g`, which is already the order a concatenative
+// language calls things in, for those who'd rather the chaining read as
+// such. Each `|>` must have a word on either side of it within the same
+// source (or eval), so a leading, trailing, or doubled-up `|>` is a
+// ValueError rather than being quietly dropped. The operator is syntax, not a
+// word: it's never looked up, and can't be defined or shadowed.
+
+pub const OPERATOR = "|>";
+
+/// Where eval is within a (possible) pipeline, fed every word in turn.
+pub const Pipeline = struct {
+    const Self = @This();
+
+    state: enum { Start, AfterWord, AfterOperator } = .Start,
+
+    /// Whether `word` is to be run, which is to say it isn't OPERATOR. An
+    /// OPERATOR with no word before it is a ValueError.
+    pub fn feed(self: *Self, word: []const u8) InternalError!bool {
+        if (!std.mem.eql(u8, word, OPERATOR)) {
+            self.state = .AfterWord;
+            return true;
+        }
+
+        if (self.state != .AfterWord) return InternalError.ValueError;
+        self.state = .AfterOperator;
+        return false;
+    }
+
+    /// Check that the input didn't end on an OPERATOR, with nothing to pipe
+    /// into.
+    pub fn finish(self: Self) InternalError!void {
+        if (self.state == .AfterOperator) return InternalError.ValueError;
+    }
+};
+
+test "Pipeline" {
+    var pipeline = Pipeline{};
+    try expect(try pipeline.feed("3"));
+    try expect(!try pipeline.feed(OPERATOR));
+    try expect(try pipeline.feed("dup"));
+    try expect(!try pipeline.feed(OPERATOR));
+    try expect(try pipeline.feed("2drop"));
+    try pipeline.finish();
+
+    // Only whole words are operators.
+    pipeline = Pipeline{};
+    try expect(try pipeline.feed("|>>"));
+    try expect(try pipeline.feed("\"|>\""));
+    try pipeline.finish();
+}
+
+test "Pipeline: misplaced operators" {
+    var leading = Pipeline{};
+    try expectError(InternalError.ValueError, leading.feed(OPERATOR));
+
+    var doubled = Pipeline{};
+    _ = try doubled.feed("3");
+    _ = try doubled.feed(OPERATOR);
+    try expectError(InternalError.ValueError, doubled.feed(OPERATOR));
+
+    var trailing = Pipeline{};
+    _ = try trailing.feed("3");
+    _ = try trailing.feed(OPERATOR);
+    try expectError(InternalError.ValueError, trailing.finish());
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,228 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const helpers = @import("./helpers.zig");
+const lexer = @import("./lexer.zig");
+
+const Runtime = @import("./runtime.zig").Runtime;
+
+/// The prelude's source, baked into libgale so that it's there even without
+/// a file system. See prelude.gale.
+pub const SOURCE = @embedFile("./prelude.gale");
+
+/// Which vocabulary the prelude defines into.
+pub const VOCABULARY = "std";
+
+/// What eval_source (and thus Runtime.load_trace) calls the prelude.
+pub const PATH = "<prelude>";
+
+/// Evaluate the prelude into VOCABULARY, and add that to the search order.
+pub fn install(runtime: *Runtime) !void {
+    // Eval knows nothing of comments, so they're blanked out beforehand,
+    // which leaves every word right where it was for load_trace's sake.
+    const source = try blank_comments(runtime.alloc, SOURCE);
+    defer runtime.alloc.free(source);
+
+    const outer_vocabulary = runtime.current_vocabulary;
+    runtime.current_vocabulary = VOCABULARY;
+    defer runtime.current_vocabulary = outer_vocabulary;
+
+    // Limits are for the host's code, not ours.
+    const limits = runtime.limits;
+    runtime.limits = .{};
+    defer runtime.limits = limits;
+
+    try runtime.eval_source(PATH, source);
+    // Whereas docstrings are only found in the original.
+    try runtime.document_source(SOURCE);
+    try runtime.use_vocabulary(VOCABULARY, null);
+}
+
+/// A copy of `source` with every comment (see lexer.zig) overwritten by
+/// spaces, line breaks aside. The caller owns the result.
+pub fn blank_comments(alloc: Allocator, source: []const u8) ![]u8 {
+    const blanked = try alloc.dupe(u8, source);
+    errdefer alloc.free(blanked);
+
+    var tokens = lexer.Lexer.init(source);
+    while (try tokens.next()) |token| {
+        if (token.kind != .LineComment and token.kind != .BlockComment) continue;
+
+        for (blanked[token.offset .. token.offset + token.raw.len]) |*chr| {
+            if (chr.* != helpers.CHAR_NEWLINE) chr.* = helpers.CHAR_SPACE;
+        }
+    }
+
+    return blanked;
+}
+
+test "blank_comments" {
+    const blanked = try blank_comments(testAllocator, "1 // one\n{ two\n} 2");
+    defer testAllocator.free(blanked);
+    try expectEqualStrings("1       \n     \n  2", blanked);
+}
+
+test "prelude: stack shuffles" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect((try runtime.find_word(VOCABULARY ++ "/nip")) != null);
+
+    try runtime.eval("1 2 nip");
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("1 2 tuck");
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("1 2 over 2drop");
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+}
+
+test "prelude: combinators" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("3 &dup curry twice call");
+    try expectEqual(@as(usize, 4), runtime.stack.depth());
+    var idx: usize = 0;
+    while (idx < 4) : (idx += 1) try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("1 2 &@SWAP 2 capture call");
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("1 2 &@SWAP partial &@DROP compose call");
+    try expectEqual(@as(usize, 1), runtime.stack.depth());
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("20 10 &nip with call");
+    try expectEqual(@as(usize, 1), runtime.stack.depth());
+    try expectEqual(@as(usize, 20), try runtime.stack_pop_value(usize));
+}
+
+test "prelude: dataflow combinators" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("1 2 &@DUP dip");
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("7 &@DROP keep");
+    try expectEqual(@as(usize, 1), runtime.stack.depth());
+    try expectEqual(@as(usize, 7), try runtime.stack_pop_value(usize));
+
+    // Had the second Word run on the first's results rather than on 3, one
+    // 3 would be left, rather than two.
+    try runtime.eval("3 &@DUP &@DROP bi");
+    try expectEqual(@as(usize, 2), runtime.stack.depth());
+    try runtime.eval("2drop");
+
+    try runtime.eval("3 &@DUP &@DROP &@DUP tri");
+    try expectEqual(@as(usize, 4), runtime.stack.depth());
+    var idx: usize = 0;
+    while (idx < 4) : (idx += 1) try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+}
+
+test "prelude: iterators" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("+10 +0 -5 range-by to-list min-of");
+    try expectEqual(@as(isize, 5), try runtime.stack_pop_value(isize));
+}
+
+test "prelude: arrays" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("0 4 range collect 1 @LIT &add @COMPOSE array-map 0 &add array-reduce");
+    try expectEqual(@as(usize, 10), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("0 4 range collect 3 @LIT &@BXOR @COMPOSE sort-by sort min-of");
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+}
+
+test "prelude: sets" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("0 4 range collect to-set set 2 set-add 9 set-add difference set-count");
+    try expectEqual(@as(usize, 3), try runtime.stack_pop_value(usize));
+}
+
+test "prelude: arithmetic" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("2 3 add 4 mul 5 sub-sat 30 sub-sat");
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("-3 neg +8 min -2 abs max sign");
+    try expectEqual(@as(isize, 1), try runtime.stack_pop_value(isize));
+}
+
+test "prelude: floating point math" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("2.0 sqrt 2.0 pow floor");
+    try expectEqual(@as(f64, 2.0), try runtime.stack_pop_value(f64));
+    try runtime.eval("0.0 ln inf?");
+    try expect((try runtime.stack_pop()).Boolean);
+}
+
+test "prelude: comparisons" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("4 4 =");
+    try expect((try runtime.stack_pop()).Boolean);
+    try expectEqual(@as(usize, 4), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 4), try runtime.stack_pop_value(usize));
+}
+
+test "prelude: strings" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"7f\" 16 parse-int-radix 2 to-string-radix 2 parse-int-radix");
+    try expectEqual(@as(usize, 127), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("\"ab\" 2 repeat \"c\" concat \"b\" \"-\" replace length");
+    try expectEqual(@as(usize, 5), try runtime.stack_pop_value(usize));
+    try runtime.eval("\"a-c\" \"-\" index-of");
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+}
+
+test "prelude: docstrings" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    const help = (try runtime.find_word("help")).?;
+    try expectEqualStrings("Print the docstring of a word, given by reference or by Symbol.", runtime.doc_of(help).?);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,233 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const builtin = @import("builtin");
+
+const capabilities = @import("./capabilities.zig");
+const conversion = @import("./conversion.zig");
+const helpers = @import("./helpers.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// How much of each of a subprocess's stdout and stderr @RUN is willing to
+/// hang on to.
+// TODO: configurable in build.zig
+pub const MAX_OUTPUT_LEN = 64 * 1024 * 1024;
+
+/// Every subprocess word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@RUN", RUN },
+};
+
+/// Define every subprocess word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @RUN ( Array String <- Map )
+///
+/// Run the command named by the Array's first String, with the rest as its
+/// arguments (no shell is involved, so there's no quoting to get wrong),
+/// feeding it the String on top of the Stack as stdin and waiting for it to
+/// finish. The result is a Map of:
+///
+/// - `stdout` and `stderr`: everything the command wrote to each, as Strings
+/// - `status`: its exit code, or `:null` if it was killed by a signal
+/// - `signal`: the signal which killed it, or `:null` if it exited normally
+///
+/// Commands which couldn't be started at all (FileNotFound, say) raise
+/// whatever error the OS gave, leaving the Stack untouched. The command sees
+/// this Runtime's view of the environment (see @ENV-SET), and its working
+/// directory. Needs the Run capability.
+pub fn RUN(runtime: *Runtime) anyerror!void {
+    try runtime.require_capability(.Run);
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .Array) return InternalError.TypeError;
+
+    const members = far.Array.value.?.items;
+    if (members.len == 0) return InternalError.ValueError;
+
+    const argv = try runtime.alloc.alloc([]const u8, members.len);
+    defer runtime.alloc.free(argv);
+    for (members) |member, idx| {
+        if (member != .String) return InternalError.TypeError;
+        argv[idx] = member.String.value.?;
+    }
+
+    const output = try run_command(runtime.alloc, argv, peek.near.String.value.?, try runtime.env_map());
+    defer output.deinit(runtime.alloc);
+
+    const result = try runtime.new_map();
+    errdefer runtime.release_map(result);
+    try conversion.put_value(runtime, result, "stdout", output.stdout);
+    try conversion.put_value(runtime, result, "stderr", output.stderr);
+    try conversion.put_value(runtime, result, "status", switch (output.term) {
+        .Exited => |code| @as(?u32, code),
+        else => null,
+    });
+    try conversion.put_value(runtime, result, "signal", switch (output.term) {
+        .Signal => |sig| @as(?u32, sig),
+        else => null,
+    });
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_map(result);
+}
+
+const Output = struct {
+    stdout: []u8,
+    stderr: []u8,
+    term: std.ChildProcess.Term,
+
+    fn deinit(self: Output, alloc: Allocator) void {
+        alloc.free(self.stdout);
+        alloc.free(self.stderr);
+    }
+};
+
+fn run_command(
+    alloc: Allocator,
+    argv: []const []const u8,
+    input: []const u8,
+    env: *const std.process.EnvMap,
+) !Output {
+    var child = std.ChildProcess.init(argv, alloc);
+    child.env_map = env;
+    child.stdin_behavior = .Pipe;
+    child.stdout_behavior = .Pipe;
+    child.stderr_behavior = .Pipe;
+    try child.spawn();
+
+    // We close stdin ourselves (so the child sees EOF), rather than leaving
+    // it for wait() to do.
+    const stdin = child.stdin.?;
+    child.stdin = null;
+
+    // With threads to spare, stdin and stderr each get one, so no matter
+    // which pipe the child blocks on, someone's there to service it.
+    // Without, we do one after another, which deadlocks should the child
+    // fill a pipe we're not yet reading from: fine for small inputs and
+    // outputs, and no worse than the single-threaded target deserves.
+    var stderr: anyerror![]u8 = error.Unexpected;
+    var stdout: anyerror![]u8 = error.Unexpected;
+
+    if (comptime builtin.single_threaded) {
+        feed(stdin, input);
+        drain(alloc, child.stdout.?, &stdout);
+        drain(alloc, child.stderr.?, &stderr);
+    } else {
+        const feeder = std.Thread.spawn(.{}, feed, .{ stdin, input }) catch |err| {
+            stdin.close();
+            abandon(&child);
+            return err;
+        };
+        const drainer = std.Thread.spawn(.{}, drain, .{ alloc, child.stderr.?, &stderr }) catch |err| {
+            abandon(&child);
+            feeder.join();
+            return err;
+        };
+        drain(alloc, child.stdout.?, &stdout);
+        feeder.join();
+        drainer.join();
+    }
+
+    const term = child.wait() catch |err| {
+        if (stdout) |bytes| alloc.free(bytes) else |_| {}
+        if (stderr) |bytes| alloc.free(bytes) else |_| {}
+        return err;
+    };
+
+    const stdout_bytes = stdout catch |err| {
+        if (stderr) |bytes| alloc.free(bytes) else |_| {}
+        return err;
+    };
+    errdefer alloc.free(stdout_bytes);
+
+    return Output{ .stdout = stdout_bytes, .stderr = try stderr, .term = term };
+}
+
+/// Be rid of a child we've given up on: killed, waited on (so it doesn't
+/// linger as a zombie), and with its pipes closed. Ours are closed first, so
+/// that a child blocked writing to one fails rather than waiting forever.
+fn abandon(child: *std.ChildProcess) void {
+    if (child.stdout) |file| file.close();
+    child.stdout = null;
+    if (child.stderr) |file| file.close();
+    child.stderr = null;
+
+    _ = child.kill() catch {};
+    _ = child.wait() catch {};
+}
+
+fn feed(file: std.fs.File, input: []const u8) void {
+    defer file.close();
+    // A child which exits without reading all of its stdin is its own
+    // business, not an error of ours.
+    file.writeAll(input) catch {};
+}
+
+fn drain(alloc: Allocator, file: std.fs.File, out: *anyerror![]u8) void {
+    out.* = file.reader().readAllAlloc(alloc, MAX_OUTPUT_LEN) catch |err| switch (err) {
+        error.StreamTooLong => InternalError.LimitExceeded,
+        else => err,
+    };
+}
+
+test "@RUN" {
+    if (comptime builtin.os.tag == .windows) return error.SkipZigTest;
+
+    var granted = capabilities.NONE;
+    granted.insert(.Run);
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .capabilities = granted });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    const argv = [_][]const u8{ "sh", "-c", "cat; echo oops >&2; exit 3" };
+    try runtime.stack_push_object(try conversion.to_object(&runtime, argv[0..]));
+    try runtime.eval("\"hello\" @RUN");
+
+    var result = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&result);
+    const map = &result.Map.value.?;
+    try expectEqualStrings("hello", map.get("stdout").?.String.value.?);
+    try expectEqualStrings("oops\n", map.get("stderr").?.String.value.?);
+    try expectEqual(@as(usize, 3), map.get("status").?.UnsignedInt);
+    try expectEqualStrings(conversion.NULL_SYMBOL, map.get("signal").?.Symbol.value.?);
+}
+
+test "@RUN needs the Run capability" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"hello\"");
+    try expectError(InternalError.CapabilityDenied, runtime.eval("@RUN"));
+    var input = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&input);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,155 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const docstrings = @import("./docstrings.zig");
+const tags = @import("./tags.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+// Many sources, scanned as one: every definition tags.zig finds in each,
+// alongside the file it came from and its docstring (see docstrings.zig), for
+// tools that need a view of a whole project rather than of one file at a
+// time. A source that can't be scanned (an unterminated String, say) is
+// recorded as a Failure and otherwise left out, rather than costing the rest.
+//
+// Reading the files is the caller's business, so that this works just as
+// well for bundled sources (see bundle.zig) as for those on disk.
+
+pub const Source = struct {
+    path: []const u8,
+    text: []const u8,
+};
+
+pub const Definition = struct {
+    path: []const u8,
+    tag: tags.Tag,
+    doc: ?[]const u8,
+};
+
+pub const Failure = struct {
+    path: []const u8,
+    err: InternalError,
+};
+
+pub const Project = struct {
+    const Self = @This();
+
+    arena: std.heap.ArenaAllocator,
+    /// Every scanned source's tags, in the order the sources were given, as
+    /// tags.write_ctags and tags.write_etags want them.
+    files: []tags.File,
+    /// Every definition in `files`, in the same order.
+    definitions: []Definition,
+    failures: []Failure,
+
+    pub fn deinit(self: *Self) void {
+        self.arena.deinit();
+    }
+
+    /// The first definition of `name`, by the order the sources were given
+    /// in, if there's any at all.
+    pub fn find(self: *const Self, name: []const u8) ?*const Definition {
+        for (self.definitions) |*definition| {
+            if (std.mem.eql(u8, definition.tag.name, name)) return definition;
+        }
+        return null;
+    }
+};
+
+/// Scan every one of `sources`. The Project refers to (but doesn't copy)
+/// their paths and text, which must outlive it.
+pub fn scan(alloc: Allocator, sources: []const Source) !Project {
+    var arena = std.heap.ArenaAllocator.init(alloc);
+    errdefer arena.deinit();
+    const arena_alloc = arena.allocator();
+
+    var files = std.ArrayList(tags.File).init(arena_alloc);
+    var definitions = std.ArrayList(Definition).init(arena_alloc);
+    var failures = std.ArrayList(Failure).init(arena_alloc);
+
+    for (sources) |source| {
+        // Both own arenas of their own, atop ours, so they go when it does.
+        const found = tags.scan(arena_alloc, source.text) catch |err| switch (err) {
+            error.OutOfMemory => return err,
+            else => |scan_err| {
+                try failures.append(.{ .path = source.path, .err = scan_err });
+                continue;
+            },
+        };
+        const documented = try docstrings.associate(arena_alloc, source.text);
+
+        try files.append(.{ .path = source.path, .tags = found.items });
+        for (found.items) |tag| {
+            try definitions.append(.{
+                .path = source.path,
+                .tag = tag,
+                .doc = doc_for(documented.items, tag),
+            });
+        }
+    }
+
+    return Project{
+        .arena = arena,
+        .files = files.toOwnedSlice(),
+        .definitions = definitions.toOwnedSlice(),
+        .failures = failures.toOwnedSlice(),
+    };
+}
+
+fn doc_for(found: []const docstrings.Docstring, tag: tags.Tag) ?[]const u8 {
+    if (tag.kind == .Vocabulary) return null;
+    for (found) |docstring| {
+        if (docstring.kind == tag.kind and std.mem.eql(u8, docstring.name, tag.name)) return docstring.text;
+    }
+    return null;
+}
+
+test "scan" {
+    const sources = [_]Source{
+        .{ .path = "a.gale", .text = "{{ Twice over. }}\n&@DUP :twin @DEFINE\n" },
+        .{ .path = "broken.gale", .text = "\"never closed" },
+        .{ .path = "b.gale", .text = ": greet \"hi\" println ;\n&@DROP :twin @DEFINE\n" },
+    };
+
+    var project = try scan(testAllocator, &sources);
+    defer project.deinit();
+
+    try expectEqual(@as(usize, 2), project.files.len);
+    try expectEqualStrings("b.gale", project.files[1].path);
+    try expectEqual(@as(usize, 3), project.definitions.len);
+
+    const twin = project.find("twin").?;
+    try expectEqualStrings("a.gale", twin.path);
+    try expectEqualStrings("Twice over.", twin.doc.?);
+    try expectEqual(@as(usize, 2), twin.tag.line);
+
+    const greet = project.find("greet").?;
+    try expectEqualStrings("b.gale", greet.path);
+    try expect(greet.doc == null);
+    try expect(project.find("nope") == null);
+
+    try expectEqual(@as(usize, 1), project.failures.len);
+    try expectEqualStrings("broken.gale", project.failures[0].path);
+    try expectEqual(InternalError.ValueError, project.failures[0].err);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
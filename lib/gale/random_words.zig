@@ -0,0 +1,193 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const helpers = @import("./helpers.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every random number word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@RANDOM", RANDOM },
+    .{ "@RANDOM-RANGE", RANDOM_RANGE },
+    .{ "@RANDOM-SEED", RANDOM_SEED },
+    .{ "@UUID-V4", UUID_V4 },
+    .{ "@UUID-V7", UUID_V7 },
+};
+
+/// Define every random number word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+// Every Runtime has a PRNG of its own, so two Runtimes seeded alike (see
+// Runtime.Options.random_seed, or @RANDOM-SEED) produce the same numbers in
+// the same order, no matter what any other Runtime is up to. None of this is
+// fit for cryptography.
+
+/// @RANDOM ( <- UnsignedInt )
+///
+/// Any UnsignedInt at all, each equally likely.
+pub fn RANDOM(runtime: *Runtime) anyerror!void {
+    try runtime.stack_push_uint(runtime.prng.random().int(usize));
+}
+
+/// @RANDOM-RANGE ( @1 @1 <- @1 )
+///
+/// An integer at least as big as the lower bound (further down the Stack),
+/// but smaller than the upper bound (on top). Both bounds must be the same
+/// kind of integer (a TypeError otherwise), and the range mustn't be empty
+/// (a ValueError otherwise).
+pub fn RANDOM_RANGE(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    _ = try peek.near.assert_same_kind_as(far);
+
+    const random = runtime.prng.random();
+    switch (peek.near.*) {
+        .UnsignedInt => |upper| {
+            const lower = far.UnsignedInt;
+            if (lower >= upper) return InternalError.ValueError;
+
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_uint(random.intRangeLessThan(usize, lower, upper));
+        },
+        .SignedInt => |upper| {
+            const lower = far.SignedInt;
+            if (lower >= upper) return InternalError.ValueError;
+
+            _ = try runtime.stack_pop_pair();
+            try runtime.stack_push_sint(random.intRangeLessThan(isize, lower, upper));
+        },
+        else => return InternalError.TypeError,
+    }
+}
+
+/// @RANDOM-SEED ( UnsignedInt <- )
+///
+/// Start this Runtime's PRNG over from the given seed.
+pub fn RANDOM_SEED(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+
+    runtime.prng = std.rand.DefaultPrng.init(@intCast(u64, peeked.UnsignedInt));
+    _ = try runtime.stack_pop();
+}
+
+/// @UUID-V4 ( <- String )
+///
+/// A random (version 4) UUID, in the usual hyphenated lowercase hex form.
+/// These come from the same PRNG as @RANDOM, so are just as reproducible
+/// under a fixed seed, and just as unfit for anything needing to be
+/// unguessable.
+pub fn UUID_V4(runtime: *Runtime) anyerror!void {
+    var bytes: [16]u8 = undefined;
+    runtime.prng.random().bytes(&bytes);
+    try push_uuid(runtime, bytes, 4);
+}
+
+/// @UUID-V7 ( <- String )
+///
+/// A time-ordered (version 7) UUID: the current Unix time in milliseconds,
+/// followed by random bits (as with @UUID-V4). Those made later sort after
+/// those made earlier (to the millisecond), which keeps them friendly to
+/// database indices and the like.
+pub fn UUID_V7(runtime: *Runtime) anyerror!void {
+    const millis = std.math.cast(u48, try runtime.read_wall_clock_ms()) orelse return InternalError.ValueError;
+
+    var bytes: [16]u8 = undefined;
+    runtime.prng.random().bytes(&bytes);
+    std.mem.writeIntBig(u48, bytes[0..6], millis);
+    try push_uuid(runtime, bytes, 7);
+}
+
+/// Stamp `version` and the RFC 4122 variant onto `bytes`, and push the
+/// result as a String.
+fn push_uuid(runtime: *Runtime, bytes: [16]u8, version: u4) !void {
+    var stamped = bytes;
+    stamped[6] = (stamped[6] & 0x0f) | (@as(u8, version) << 4);
+    stamped[8] = (stamped[8] & 0x3f) | 0x80;
+
+    var text: [36]u8 = undefined;
+    _ = std.fmt.bufPrint(&text, "{}-{}-{}-{}-{}", .{
+        std.fmt.fmtSliceHexLower(stamped[0..4]),
+        std.fmt.fmtSliceHexLower(stamped[4..6]),
+        std.fmt.fmtSliceHexLower(stamped[6..8]),
+        std.fmt.fmtSliceHexLower(stamped[8..10]),
+        std.fmt.fmtSliceHexLower(stamped[10..16]),
+    }) catch unreachable;
+
+    const str = try runtime.get_or_put_string(&text);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+test "seeded Runtimes agree" {
+    var first = try Runtime.init_with_options(testAllocator, .{ .random_seed = 42 });
+    defer first.deinit_guard_for_empty_stack();
+    var second = try Runtime.init(testAllocator);
+    defer second.deinit_guard_for_empty_stack();
+
+    try first.eval("@RANDOM");
+    try second.eval("42 @RANDOM-SEED @RANDOM");
+    try expectEqual(try first.stack_pop_value(usize), try second.stack_pop_value(usize));
+}
+
+test "@RANDOM-RANGE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    var i: usize = 0;
+    while (i < 100) : (i += 1) {
+        try runtime.eval("-3 +3 @RANDOM-RANGE");
+        const rolled = try runtime.stack_pop_value(isize);
+        try expect(rolled >= -3 and rolled < 3);
+    }
+
+    try expectError(InternalError.ValueError, runtime.eval("5 5 @RANDOM-RANGE"));
+    _ = try runtime.stack_pop_pair();
+    try expectError(InternalError.TypeError, runtime.eval("-5 5 @RANDOM-RANGE"));
+    _ = try runtime.stack_pop_pair();
+}
+
+fn expect_uuid(runtime: *Runtime, source: []const u8, version: u8) !void {
+    try runtime.eval(source);
+    var uuid = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&uuid);
+
+    const text = uuid.String.value.?;
+    try expectEqual(@as(usize, 36), text.len);
+    try expectEqual(version, text[14]);
+    try expect(std.mem.indexOfScalar(u8, "89ab", text[19]) != null);
+    for ([_]usize{ 8, 13, 18, 23 }) |idx| try expectEqual(@as(u8, '-'), text[idx]);
+}
+
+test "@UUID-V4 and @UUID-V7" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_uuid(&runtime, "@UUID-V4", '4');
+    if (helpers.HOSTED) try expect_uuid(&runtime, "@UUID-V7", '7');
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
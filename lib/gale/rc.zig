@@ -24,6 +24,58 @@ const Types = @import("./types.zig");
 // TODO: allow override in build.zig
 const RC_ALLOW_INIT_GT0: bool = false;
 
+/// Refcounts are atomic by default, which is what allows Runtimes (and the
+/// Objects within them) to be handed between threads, or to share a frozen
+/// base Runtime across a thread pool. Single-threaded builds
+/// (-fsingle-threaded) can't make use of any of that, and so get cheaper,
+/// plain integers instead.
+pub const RC_THREAD_SAFE: bool = !builtin.single_threaded;
+
+/// Just enough of std.atomic.Atomic's interface for Rc, minus the atomics.
+/// Arithmetic wraps, as it does there, so that neither kind of count panics
+/// where the other wouldn't: Rc.increment checks for overflow either way.
+fn UnsynchronizedCount(comptime T: type) type {
+    return struct {
+        const Self = @This();
+
+        value: T,
+
+        pub fn init(value: T) Self {
+            return Self{ .value = value };
+        }
+
+        pub fn load(self: *const Self, comptime _: std.atomic.Ordering) T {
+            return self.value;
+        }
+
+        pub fn fetchAdd(self: *Self, operand: T, comptime _: std.atomic.Ordering) T {
+            const old = self.value;
+            self.value +%= operand;
+            return old;
+        }
+
+        pub fn fetchSub(self: *Self, operand: T, comptime _: std.atomic.Ordering) T {
+            const old = self.value;
+            self.value -%= operand;
+            return old;
+        }
+
+        pub fn tryCompareAndSwap(
+            self: *Self,
+            expected: T,
+            new_value: T,
+            comptime _: std.atomic.Ordering,
+            comptime _: std.atomic.Ordering,
+        ) ?T {
+            if (self.value != expected) return self.value;
+            self.value = new_value;
+            return null;
+        }
+
+        pub fn fence(_: *Self, comptime _: std.atomic.Ordering) void {}
+    };
+}
+
 pub fn Rc(comptime T: type) type {
     return struct {
         const no_valid_innerkind_msg = "Could not determine an InnerKind for value's type: " ++ @typeName(T);
@@ -59,12 +111,15 @@ pub fn Rc(comptime T: type) type {
                 DeinitInnerWithAlloc,
                 DeinitInnerWithAllocDestroySelf,
             },
-            .ManagedStruct => enum { DeinitInner },
+            .ManagedStruct => enum {
+                DeinitInner,
+                DeinitInnerDestroySelf,
+            },
             else => @compileError("No valid PruneModes exist for value's type: " ++ @typeName(T)),
         };
 
         const Self = @This();
-        const RefCount = std.atomic.Atomic(u16);
+        const RefCount = if (RC_THREAD_SAFE) std.atomic.Atomic(u16) else UnsynchronizedCount(u16);
 
         strong_count: RefCount,
         value: ?T,
@@ -115,10 +170,17 @@ pub fn Rc(comptime T: type) type {
             return no_refs and no_data;
         }
 
-        /// Increment the number of references to this Rc.
+        /// Increment the number of references to this Rc. A count which
+        /// is already as high as it goes is an RcOverflow, and left as it
+        /// was, rather than wrapping around to zero.
         pub fn increment(self: *Self) InternalError!void {
             if (self.value == null) return InternalError.AttemptedResurrectionOfExhaustedRc;
-            _ = self.strong_count.fetchAdd(1, .Monotonic);
+
+            var count = self.strong_count.load(.Monotonic);
+            while (true) {
+                if (count == std.math.maxInt(u16)) return InternalError.RcOverflow;
+                count = self.strong_count.tryCompareAndSwap(count, count + 1, .Monotonic, .Monotonic) orelse return;
+            }
         }
 
         /// Returned boolean reflects the livelihood of the object after this
@@ -200,6 +262,10 @@ pub fn Rc(comptime T: type) type {
                 },
                 .ManagedStruct => switch (prune_mode) {
                     .DeinitInner => inner.deinit(),
+                    .DeinitInnerDestroySelf => {
+                        inner.deinit();
+                        alloc.destroy(self);
+                    },
                 },
                 else => unreachable,
             };
@@ -234,6 +300,37 @@ test "Rc(u8): simple set, increments, decrements, and prune" {
     try expect(shared_str.decrement_and_prune(.FreeInnerDestroySelf, testAllocator));
 }
 
+test "Rc: concurrent increments and decrements" {
+    if (!RC_THREAD_SAFE) return error.SkipZigTest;
+
+    const Hammer = struct {
+        fn run(rc: *Types.HeapedString) void {
+            var i: usize = 0;
+            while (i < 1000) : (i += 1) rc.increment() catch unreachable;
+            i = 0;
+            while (i < 500) : (i += 1) _ = rc.decrement();
+        }
+    };
+
+    var str = "shared".*;
+    var shared_str = Types.HeapedString.init_referenced(str[0..]);
+
+    var threads: [4]std.Thread = undefined;
+    for (threads) |*thread| thread.* = try std.Thread.spawn(.{}, Hammer.run, .{&shared_str});
+    for (threads) |thread| thread.join();
+
+    try expect(shared_str.strong_count.load(.Acquire) == 1 + 4 * 500);
+}
+
+test "Rc: increments refuse to overflow, atomic or not" {
+    var str = "full".*;
+    var shared_str = Types.HeapedString.init_referenced(str[0..]);
+    shared_str.strong_count = @TypeOf(shared_str.strong_count).init(std.math.maxInt(u16));
+
+    try std.testing.expectError(InternalError.RcOverflow, shared_str.increment());
+    try expect(shared_str.strong_count.load(.Acquire) == std.math.maxInt(u16));
+}
+
 test {
     std.testing.refAllDeclsRecursive(@This());
 }
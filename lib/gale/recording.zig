@@ -0,0 +1,444 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const helpers = @import("./helpers.zig");
+const io = @import("./io.zig");
+const json = @import("./json.zig");
+
+const InputHandle = io.InputHandle;
+const InternalError = @import("./internal_error.zig").InternalError;
+const OutputHandle = io.OutputHandle;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// Recording a run writes down everything it learned from the world which
+// could be different next time: what its PRNG was seeded with, what it read
+// from stdin, what the clocks said, and what the environment looked like.
+// Replaying the recording hands back the same answers in the same order, so
+// the run goes just as it did, which is how a bug hit on someone else's
+// machine can be reproduced on yours. Files, sockets, and subprocesses
+// aren't captured: a replay needs those to be as they were.
+//
+// A recording is a trace of JSON lines, one Input apiece, such as
+// `{"seed":42}` or `{"stdin":"hello\n"}`. Bytes which aren't UTF-8 are
+// written as `{"base64":"..."}` in place of a string.
+
+/// Kinds of Input.
+pub const Kind = enum {
+    Seed,
+    Stdin,
+    Monotonic,
+    WallClockMs,
+    Env,
+    Cwd,
+    Pid,
+
+    /// What a trace calls this kind of Input.
+    pub fn name(self: Kind) []const u8 {
+        return switch (self) {
+            .Seed => "seed",
+            .Stdin => "stdin",
+            .Monotonic => "monotonic",
+            .WallClockMs => "wall-clock-ms",
+            .Env => "env",
+            .Cwd => "cwd",
+            .Pid => "pid",
+        };
+    }
+
+    pub fn from_name(text: []const u8) ?Kind {
+        inline for (comptime std.enums.values(Kind)) |kind| {
+            if (std.mem.eql(u8, text, kind.name())) return kind;
+        }
+        return null;
+    }
+};
+
+pub const EnvVar = struct {
+    name: []const u8,
+    value: []const u8,
+};
+
+/// Something a Runtime learned from the world. Slices only need to live as
+/// long as the call recording them.
+pub const Input = union(Kind) {
+    /// What the PRNG (see random_words.zig) was first seeded with.
+    Seed: u64,
+    /// What one read of stdin gave: nothing at all at its end.
+    Stdin: []const u8,
+    /// Nanoseconds since the Runtime was created, as read by @NOW.
+    Monotonic: u64,
+    /// Milliseconds since 1970-01-01T00:00:00Z, as read by @UNIX-TIME and
+    /// @UUID-V7.
+    WallClockMs: i64,
+    /// The environment, as Runtime.env_map first snapshots it.
+    Env: []const EnvVar,
+    /// The working directory, as read by @CWD.
+    Cwd: []const u8,
+    /// The process's ID, as read by @PID: null where there isn't one.
+    Pid: ?usize,
+};
+
+/// Where a Runtime gets its Inputs from. See Runtime.Options.recording.
+pub const Source = union(enum) {
+    const Self = @This();
+
+    /// Straight from the world, as usual.
+    Live,
+    /// From the world, writing each one down along the way.
+    Record: *Recorder,
+    /// From a trace written down earlier.
+    Replay: *Replay,
+
+    /// When replaying, the next Input, which must be of `kind` (or else
+    /// it's a ReplayDiverged). Otherwise null, and it's up to the caller to
+    /// ask the world instead, and to `record` the answer.
+    pub fn replayed(self: Self, comptime kind: Kind) !?std.meta.TagPayload(Input, kind) {
+        return switch (self) {
+            .Replay => |replay| try replay.next(kind),
+            else => null,
+        };
+    }
+
+    /// Write down `input`, when recording.
+    pub fn record(self: Self, input: Input) !void {
+        if (self == .Record) try self.Record.write(input);
+    }
+
+    /// What a Runtime which would otherwise read `live` should read as
+    /// stdin instead.
+    pub fn stdin(self: Self, live: InputHandle) InputHandle {
+        return switch (self) {
+            .Live => live,
+            .Record => |recorder| blk: {
+                recorder.live_stdin = live;
+                break :blk recorder.stdin_handle();
+            },
+            .Replay => |replay| replay.stdin_handle(),
+        };
+    }
+};
+
+/// Writes down Inputs as a trace. Must outlive any Runtime recording to it.
+pub const Recorder = struct {
+    const Self = @This();
+
+    out: OutputHandle,
+    /// Where the recorded Runtime's stdin really reads from. Filled in by
+    /// Source.stdin.
+    live_stdin: InputHandle = io.EMPTY,
+
+    pub fn init(out: OutputHandle) Self {
+        return .{ .out = out };
+    }
+
+    pub fn write(self: *Self, input: Input) !void {
+        try write_input(self.out.writer(), input);
+    }
+
+    /// Reads of `live_stdin`, each written down as it's made.
+    pub fn stdin_handle(self: *Self) InputHandle {
+        return .{ .context = self, .read_fn = &recorded_read };
+    }
+};
+
+fn recorded_read(context: ?*anyopaque, buffer: []u8) anyerror!usize {
+    const self = @ptrCast(*Recorder, @alignCast(@alignOf(Recorder), context.?));
+    const len = try self.live_stdin.read(buffer);
+    try self.write(.{ .Stdin = buffer[0..len] });
+    return len;
+}
+
+/// Inputs read back from a trace, in the order they were written down. Must
+/// outlive any Runtime replaying it.
+pub const Replay = struct {
+    const Self = @This();
+
+    arena: std.heap.ArenaAllocator,
+    inputs: []const Input,
+    next_idx: usize,
+    /// What's left of the last Stdin Input, when it didn't all fit in the
+    /// buffer it was read into.
+    unread: []const u8,
+
+    /// Parse `trace`, which needn't outlive the Replay. A malformed trace is
+    /// a ValueError.
+    pub fn init(alloc: Allocator, trace: []const u8) !Self {
+        var arena = std.heap.ArenaAllocator.init(alloc);
+        errdefer arena.deinit();
+
+        var inputs = std.ArrayList(Input).init(arena.allocator());
+        var lines = std.mem.tokenize(u8, trace, "\n");
+        while (lines.next()) |line| try inputs.append(try parse_input(arena.allocator(), line));
+
+        return Self{
+            .arena = arena,
+            .inputs = inputs.items,
+            .next_idx = 0,
+            .unread = "",
+        };
+    }
+
+    pub fn deinit(self: *Self) void {
+        self.arena.deinit();
+    }
+
+    /// The next Input, which must be of `kind`: anything else (or nothing at
+    /// all) means this run has gone differently than the recorded one, a
+    /// ReplayDiverged.
+    pub fn next(self: *Self, comptime kind: Kind) !std.meta.TagPayload(Input, kind) {
+        if (self.next_idx >= self.inputs.len) return InternalError.ReplayDiverged;
+
+        const found = self.inputs[self.next_idx];
+        if (found != kind) return InternalError.ReplayDiverged;
+
+        self.next_idx += 1;
+        return @field(found, @tagName(kind));
+    }
+
+    /// Whether every Input has been handed back, as they all should have
+    /// been by the end of a faithful replay.
+    pub fn finished(self: Self) bool {
+        return self.next_idx == self.inputs.len and self.unread.len == 0;
+    }
+
+    /// Stdin, as it was read while recording.
+    pub fn stdin_handle(self: *Self) InputHandle {
+        return .{ .context = self, .read_fn = &replayed_read };
+    }
+};
+
+fn replayed_read(context: ?*anyopaque, buffer: []u8) anyerror!usize {
+    const self = @ptrCast(*Replay, @alignCast(@alignOf(Replay), context.?));
+    if (self.unread.len == 0) self.unread = try self.next(.Stdin);
+
+    const len = std.math.min(buffer.len, self.unread.len);
+    std.mem.copy(u8, buffer, self.unread[0..len]);
+    self.unread = self.unread[len..];
+    return len;
+}
+
+/// `input` as a line of a trace.
+pub fn write_input(writer: anytype, input: Input) !void {
+    try writer.writeByte('{');
+    try json.write_string(writer, @as(Kind, input).name());
+    try writer.writeByte(':');
+
+    switch (input) {
+        .Seed, .Monotonic => |val| try writer.print("{d}", .{val}),
+        .WallClockMs => |val| try writer.print("{d}", .{val}),
+        .Stdin, .Cwd => |bytes| try write_bytes(writer, bytes),
+        .Env => |vars| {
+            try writer.writeByte('[');
+            for (vars) |env_var, idx| {
+                if (idx > 0) try writer.writeByte(',');
+                try writer.writeByte('[');
+                try write_bytes(writer, env_var.name);
+                try writer.writeByte(',');
+                try write_bytes(writer, env_var.value);
+                try writer.writeByte(']');
+            }
+            try writer.writeByte(']');
+        },
+        .Pid => |maybe_pid| if (maybe_pid) |pid| try writer.print("{d}", .{pid}) else try writer.writeAll("null"),
+    }
+
+    try writer.writeAll("}\n");
+}
+
+fn write_bytes(writer: anytype, bytes: []const u8) !void {
+    if (std.unicode.utf8ValidateSlice(bytes)) return json.write_string(writer, bytes);
+
+    // Three bytes make four characters, so chunks of a multiple of three
+    // don't need padding until the last of them.
+    const encoder = std.base64.standard.Encoder;
+    var buf: [256]u8 = undefined;
+    var rest = bytes;
+
+    try writer.writeAll("{\"base64\":\"");
+    while (rest.len > 0) {
+        const chunk = rest[0..std.math.min(rest.len, 3 * 64)];
+        try writer.writeAll(encoder.encode(&buf, chunk));
+        rest = rest[chunk.len..];
+    }
+    try writer.writeAll("\"}");
+}
+
+fn parse_input(alloc: Allocator, line: []const u8) !Input {
+    var parser = std.json.Parser.init(alloc, false);
+    defer parser.deinit();
+
+    var tree = parser.parse(line) catch |err| switch (err) {
+        error.OutOfMemory => return err,
+        else => return InternalError.ValueError,
+    };
+    defer tree.deinit();
+
+    if (tree.root != .Object or tree.root.Object.count() != 1) return InternalError.ValueError;
+    var fields = tree.root.Object.iterator();
+    const field = fields.next().?;
+    const value = field.value_ptr.*;
+
+    return switch (Kind.from_name(field.key_ptr.*) orelse return InternalError.ValueError) {
+        .Seed => Input{ .Seed = try int_of(u64, value) },
+        .Stdin => Input{ .Stdin = try bytes_of(alloc, value) },
+        .Monotonic => Input{ .Monotonic = try int_of(u64, value) },
+        .WallClockMs => Input{ .WallClockMs = try int_of(i64, value) },
+        .Env => Input{ .Env = try env_of(alloc, value) },
+        .Cwd => Input{ .Cwd = try bytes_of(alloc, value) },
+        .Pid => Input{ .Pid = if (value == .Null) null else try int_of(usize, value) },
+    };
+}
+
+fn int_of(comptime T: type, value: std.json.Value) !T {
+    switch (value) {
+        .Integer => |val| return std.math.cast(T, val) orelse return InternalError.ValueError,
+        // What std.json makes of integers too big for an i64, such as most
+        // seeds.
+        .NumberString => |text| return std.fmt.parseInt(T, text, 10) catch return InternalError.ValueError,
+        else => return InternalError.ValueError,
+    }
+}
+
+fn bytes_of(alloc: Allocator, value: std.json.Value) ![]const u8 {
+    switch (value) {
+        .String => |text| return try alloc.dupe(u8, text),
+        .Object => |fields| {
+            const encoded = fields.get("base64") orelse return InternalError.ValueError;
+            if (fields.count() != 1 or encoded != .String) return InternalError.ValueError;
+
+            const decoder = std.base64.standard.Decoder;
+            const bytes = try alloc.alloc(u8, decoder.calcSizeForSlice(encoded.String) catch return InternalError.ValueError);
+            decoder.decode(bytes, encoded.String) catch return InternalError.ValueError;
+            return bytes;
+        },
+        else => return InternalError.ValueError,
+    }
+}
+
+fn env_of(alloc: Allocator, value: std.json.Value) ![]const EnvVar {
+    if (value != .Array) return InternalError.ValueError;
+
+    const vars = try alloc.alloc(EnvVar, value.Array.items.len);
+    for (value.Array.items) |pair, idx| {
+        if (pair != .Array or pair.Array.items.len != 2) return InternalError.ValueError;
+        vars[idx] = .{
+            .name = try bytes_of(alloc, pair.Array.items[0]),
+            .value = try bytes_of(alloc, pair.Array.items[1]),
+        };
+    }
+    return vars;
+}
+
+test "write_input and Replay round trip" {
+    var trace = std.ArrayList(u8).init(testAllocator);
+    defer trace.deinit();
+
+    const inputs = [_]Input{
+        .{ .Seed = std.math.maxInt(u64) },
+        .{ .Stdin = "hello\n" },
+        .{ .Stdin = "\xff\xfe" },
+        .{ .Monotonic = 1234 },
+        .{ .WallClockMs = -5 },
+        .{ .Env = &.{ .{ .name = "HOME", .value = "/home/\"me\"" }, .{ .name = "EMPTY", .value = "" } } },
+        .{ .Cwd = "/tmp" },
+        .{ .Pid = null },
+    };
+    for (inputs) |input| try write_input(trace.writer(), input);
+
+    try expect(std.mem.startsWith(u8, trace.items, "{\"seed\":18446744073709551615}\n{\"stdin\":\"hello\\n\"}\n{\"stdin\":{\"base64\":\"//4=\"}}\n"));
+
+    var replay = try Replay.init(testAllocator, trace.items);
+    defer replay.deinit();
+
+    try expectEqual(@as(u64, std.math.maxInt(u64)), try replay.next(.Seed));
+    try expectEqualStrings("hello\n", try replay.next(.Stdin));
+    try expectEqualStrings("\xff\xfe", try replay.next(.Stdin));
+    try expectError(InternalError.ReplayDiverged, replay.next(.Cwd));
+    try expectEqual(@as(u64, 1234), try replay.next(.Monotonic));
+    try expectEqual(@as(i64, -5), try replay.next(.WallClockMs));
+
+    const env = try replay.next(.Env);
+    try expectEqual(@as(usize, 2), env.len);
+    try expectEqualStrings("/home/\"me\"", env[0].value);
+    try expectEqualStrings("EMPTY", env[1].name);
+
+    try expectEqualStrings("/tmp", try replay.next(.Cwd));
+    try expectEqual(@as(?usize, null), try replay.next(.Pid));
+    try expect(replay.finished());
+    try expectError(InternalError.ReplayDiverged, replay.next(.Pid));
+
+    try expectError(InternalError.ValueError, Replay.init(testAllocator, "{\"seed\":-1}\n"));
+    try expectError(InternalError.ValueError, Replay.init(testAllocator, "{\"weather\":\"fine\"}\n"));
+}
+
+test "a recorded run replays alike" {
+    if (comptime !helpers.HOSTED) return error.SkipZigTest;
+
+    var trace = std.ArrayList(u8).init(testAllocator);
+    defer trace.deinit();
+    const program = "@READ-LINE @DROP @RANDOM @NOW @UNIX-TIME";
+
+    var unix_time: isize = undefined;
+    var now: usize = undefined;
+    var random: usize = undefined;
+    {
+        var stream = std.io.fixedBufferStream(@as([]const u8, "typed\n"));
+        var recorder = Recorder.init(io.buffer_output(&trace));
+        var runtime = try Runtime.init_with_options(testAllocator, .{
+            .stdin = io.fixed_input(&stream),
+            .recording = .{ .Record = &recorder },
+        });
+        defer runtime.deinit_guard_for_empty_stack();
+
+        try runtime.eval(program);
+        unix_time = try runtime.stack_pop_value(isize);
+        now = try runtime.stack_pop_value(usize);
+        random = try runtime.stack_pop_value(usize);
+        var line = try runtime.stack_pop();
+        runtime.release_heaped_object_reference(&line);
+    }
+
+    var replay = try Replay.init(testAllocator, trace.items);
+    defer replay.deinit();
+
+    // Nothing to read from but the trace.
+    var runtime = try Runtime.init_with_options(testAllocator, .{
+        .stdin = io.EMPTY,
+        .recording = .{ .Replay = &replay },
+    });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval(program);
+    try expectEqual(unix_time, try runtime.stack_pop_value(isize));
+    try expectEqual(now, try runtime.stack_pop_value(usize));
+    try expectEqual(random, try runtime.stack_pop_value(usize));
+    var line = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&line);
+    try expectEqualStrings("typed", line.String.value.?);
+    try expect(replay.finished());
+
+    // The recording never read the clock a second time.
+    try expectError(InternalError.ReplayDiverged, runtime.eval("@NOW"));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,845 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+// A small regular expression engine, for the words in regex_words.zig.
+// Patterns are compiled to a program for a backtracking matcher, which
+// remembers every (instruction, position) pair it has already tried and
+// failed from, so no pattern can take more than (program length) * (text
+// length) steps: there's no catastrophic backtracking to be had, at the cost
+// of a bit per such pair.
+//
+// Supported syntax, matching UTF-8 encoded codepoints (invalid sequences
+// match as individual bytes):
+//
+// - literals, and `\` before any non-alphanumeric character to match it
+//   literally; `\n`, `\r`, `\t`, `\f`, and `\v`
+// - `.` (anything but a newline), `[classes]`, `[^negated classes]`,
+//   `\d`, `\w`, `\s`, and their negations `\D`, `\W`, and `\S` (ASCII only)
+// - `^` and `$` (the start and end of the text), `\b` and `\B`
+// - `(capturing)`, `(?:non-capturing)`, and `(?<named>)` or `(?P<named>)`
+//   groups
+// - `*`, `+`, `?`, `{n}`, `{n,}`, and `{n,m}`, each followed by an optional
+//   `?` to match as few times as possible rather than as many
+// - `|` alternation
+//
+// Matching is leftmost-first, as in Perl and most everything since.
+
+/// How long compiled programs may be; counted repetition, in particular,
+/// copies its operand once per count.
+// TODO: configurable in build.zig
+pub const MAX_PROGRAM_LEN = 1 << 16;
+
+/// How many (instruction, position) pairs a single search may track, which
+/// bounds the memory a search takes to an eighth of this many bytes.
+// TODO: configurable in build.zig
+pub const MAX_VISITED = 1 << 28;
+
+/// How deeply groups may nest.
+pub const MAX_NESTING = 256;
+
+/// The largest count {n,m} repetition accepts.
+pub const MAX_REPEAT = 1000;
+
+const Assertion = enum {
+    Start,
+    End,
+    WordBoundary,
+    NotWordBoundary,
+};
+
+const Range = struct {
+    lo: u21,
+    hi: u21,
+};
+
+const Class = struct {
+    ranges: []const Range,
+    negated: bool,
+
+    fn matches(self: Class, cp: u21) bool {
+        for (self.ranges) |range| {
+            if (cp >= range.lo and cp <= range.hi) return !self.negated;
+        }
+        return self.negated;
+    }
+};
+
+const Inst = union(enum) {
+    Char: u21,
+    Any,
+    Class: usize,
+    Assert: Assertion,
+    /// Try the first target, then (if that fails) the second.
+    Split: [2]usize,
+    Jump: usize,
+    /// Record the current position in a capture slot.
+    Save: usize,
+    Match,
+};
+
+const Node = union(enum) {
+    Empty,
+    Char: u21,
+    Any,
+    Class: usize,
+    Assert: Assertion,
+    Concat: []const usize,
+    Alternate: [2]usize,
+    Group: struct { child: usize, index: ?usize },
+    Repeat: struct { child: usize, min: u32, max: ?u32, greedy: bool },
+};
+
+pub const GroupName = struct {
+    name: []const u8,
+    index: usize,
+};
+
+pub const Regex = struct {
+    const Self = @This();
+
+    arena: std.heap.ArenaAllocator,
+    program: []const Inst,
+    classes: []const Class,
+    /// How many capture groups the pattern has, not counting the implicit
+    /// group 0 (the whole match).
+    group_count: usize,
+    names: []const GroupName,
+
+    /// Compile `pattern`. Malformed patterns are a ValueError, and those
+    /// compiling to something unreasonably large are a LimitExceeded.
+    pub fn compile(alloc: Allocator, pattern: []const u8) !Self {
+        var arena = std.heap.ArenaAllocator.init(alloc);
+        errdefer arena.deinit();
+
+        var parser = Parser{
+            .alloc = arena.allocator(),
+            .pattern = pattern,
+            .pos = 0,
+            .depth = 0,
+            .nodes = std.ArrayList(Node).init(arena.allocator()),
+            .classes = std.ArrayList(Class).init(arena.allocator()),
+            .names = std.ArrayList(GroupName).init(arena.allocator()),
+            .group_count = 0,
+        };
+        const root = try parser.alternation();
+        // The only way to stop short of the end is an unbalanced ).
+        if (parser.pos != pattern.len) return InternalError.ValueError;
+
+        var compiler = Compiler{
+            .nodes = parser.nodes.items,
+            .program = std.ArrayList(Inst).init(arena.allocator()),
+        };
+        _ = try compiler.emit(.{ .Save = 0 });
+        try compiler.generate(root);
+        _ = try compiler.emit(.{ .Save = 1 });
+        _ = try compiler.emit(.Match);
+
+        return Self{
+            .arena = arena,
+            .program = compiler.program.items,
+            .classes = parser.classes.items,
+            .group_count = parser.group_count,
+            .names = parser.names.items,
+        };
+    }
+
+    pub fn deinit(self: *Self) void {
+        self.arena.deinit();
+    }
+
+    /// The number of the group called `name`, if any.
+    pub fn group_index(self: *const Self, name: []const u8) ?usize {
+        for (self.names) |entry| {
+            if (std.mem.eql(u8, entry.name, name)) return entry.index;
+        }
+        return null;
+    }
+
+    /// The leftmost match in `text` starting at or after byte offset
+    /// `start`, if any. The caller owns the Match.
+    pub fn search(self: *const Self, alloc: Allocator, text: []const u8, start: usize) !?Match {
+        std.debug.assert(start <= text.len);
+
+        const positions = text.len - start + 1;
+        const bits = std.math.mul(usize, self.program.len, positions) catch return InternalError.LimitExceeded;
+        if (bits > MAX_VISITED) return InternalError.LimitExceeded;
+
+        var visited = try std.DynamicBitSetUnmanaged.initEmpty(alloc, bits);
+        defer visited.deinit(alloc);
+
+        var stack = std.ArrayList(Frame).init(alloc);
+        defer stack.deinit();
+
+        const slots = try alloc.alloc(?usize, 2 * (self.group_count + 1));
+        errdefer alloc.free(slots);
+
+        var from = start;
+        while (true) {
+            std.mem.set(?usize, slots, null);
+            var run = Run{
+                .regex = self,
+                .text = text,
+                .base = start,
+                .slots = slots,
+                .visited = &visited,
+                .stack = &stack,
+            };
+            if (try run.attempt(from)) return Match{ .slots = slots };
+
+            if (from == text.len) break;
+            from += decode_at(text, from).len;
+        }
+
+        alloc.free(slots);
+        return null;
+    }
+
+    /// Write `template` to `writer`, with $0 through $9, ${number}, and
+    /// ${name} replaced by what the corresponding group of `match` matched
+    /// (or nothing, for groups which didn't participate), and $$ by a lone
+    /// $. References to groups which don't exist are a ValueError.
+    pub fn expand(self: *const Self, writer: anytype, text: []const u8, match: Match, template: []const u8) !void {
+        var idx: usize = 0;
+        while (idx < template.len) {
+            const chr = template[idx];
+            if (chr != '$') {
+                try writer.writeByte(chr);
+                idx += 1;
+                continue;
+            }
+
+            if (idx + 1 >= template.len) return InternalError.ValueError;
+            const next = template[idx + 1];
+
+            const group = if (next == '$') {
+                try writer.writeByte('$');
+                idx += 2;
+                continue;
+            } else if (std.ascii.isDigit(next)) digit: {
+                idx += 2;
+                break :digit @as(usize, next - '0');
+            } else if (next == '{') braced: {
+                const close = std.mem.indexOfScalarPos(u8, template, idx + 2, '}') orelse return InternalError.ValueError;
+                const reference = template[idx + 2 .. close];
+                idx = close + 1;
+                break :braced std.fmt.parseInt(usize, reference, 10) catch
+                    (self.group_index(reference) orelse return InternalError.ValueError);
+            } else return InternalError.ValueError;
+
+            if (group > self.group_count) return InternalError.ValueError;
+            if (match.group(text, group)) |captured| try writer.writeAll(captured);
+        }
+    }
+};
+
+/// Where in the text a Regex matched, and where each of its groups did.
+pub const Match = struct {
+    const Self = @This();
+
+    /// Start and end offsets of each group, in pairs.
+    slots: []?usize,
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        alloc.free(self.slots);
+    }
+
+    pub fn start(self: Self) usize {
+        return self.slots[0].?;
+    }
+
+    pub fn end(self: Self) usize {
+        return self.slots[1].?;
+    }
+
+    /// Where a search for the next match should start, so that matches don't
+    /// overlap, and empty matches don't repeat forever. Null once there's no
+    /// text left to search.
+    pub fn resume_at(self: Self, text: []const u8) ?usize {
+        if (self.end() > self.start()) return self.end();
+        if (self.end() == text.len) return null;
+        return self.end() + decode_at(text, self.end()).len;
+    }
+
+    /// What group `index` matched, or null if it didn't participate.
+    pub fn group(self: Self, text: []const u8, index: usize) ?[]const u8 {
+        const from = self.slots[2 * index] orelse return null;
+        const to = self.slots[2 * index + 1] orelse return null;
+        return text[from..to];
+    }
+};
+
+const Frame = union(enum) {
+    Try: struct { pc: usize, pos: usize },
+    Restore: struct { slot: usize, value: ?usize },
+};
+
+const Run = struct {
+    const Self = @This();
+
+    regex: *const Regex,
+    text: []const u8,
+    /// The earliest position this search can visit, which the visited set
+    /// is indexed from.
+    base: usize,
+    slots: []?usize,
+    visited: *std.DynamicBitSetUnmanaged,
+    stack: *std.ArrayList(Frame),
+
+    fn attempt(self: *Self, from: usize) !bool {
+        const positions = self.text.len - self.base + 1;
+
+        self.stack.clearRetainingCapacity();
+        try self.stack.append(.{ .Try = .{ .pc = 0, .pos = from } });
+
+        while (self.stack.popOrNull()) |frame| {
+            var pc: usize = undefined;
+            var pos: usize = undefined;
+            switch (frame) {
+                .Restore => |restore| {
+                    self.slots[restore.slot] = restore.value;
+                    continue;
+                },
+                .Try => |state| {
+                    pc = state.pc;
+                    pos = state.pos;
+                },
+            }
+
+            while (true) {
+                // Having been here before and failed, we'd only fail again.
+                const bit = pc * positions + (pos - self.base);
+                if (self.visited.isSet(bit)) break;
+                self.visited.set(bit);
+
+                switch (self.regex.program[pc]) {
+                    .Char => |want| {
+                        if (pos >= self.text.len) break;
+                        const decoded = decode_at(self.text, pos);
+                        if (decoded.cp != want) break;
+                        pos += decoded.len;
+                        pc += 1;
+                    },
+                    .Any => {
+                        if (pos >= self.text.len) break;
+                        const decoded = decode_at(self.text, pos);
+                        if (decoded.cp == '\n') break;
+                        pos += decoded.len;
+                        pc += 1;
+                    },
+                    .Class => |idx| {
+                        if (pos >= self.text.len) break;
+                        const decoded = decode_at(self.text, pos);
+                        if (!self.regex.classes[idx].matches(decoded.cp)) break;
+                        pos += decoded.len;
+                        pc += 1;
+                    },
+                    .Assert => |kind| {
+                        if (!assertion_holds(kind, self.text, pos)) break;
+                        pc += 1;
+                    },
+                    .Split => |targets| {
+                        try self.stack.append(.{ .Try = .{ .pc = targets[1], .pos = pos } });
+                        pc = targets[0];
+                    },
+                    .Jump => |target| pc = target,
+                    .Save => |slot| {
+                        try self.stack.append(.{ .Restore = .{ .slot = slot, .value = self.slots[slot] } });
+                        self.slots[slot] = pos;
+                        pc += 1;
+                    },
+                    .Match => return true,
+                }
+            }
+        }
+
+        return false;
+    }
+};
+
+fn assertion_holds(kind: Assertion, text: []const u8, pos: usize) bool {
+    return switch (kind) {
+        .Start => pos == 0,
+        .End => pos == text.len,
+        .WordBoundary, .NotWordBoundary => boundary: {
+            const before = pos > 0 and is_word_byte(text[pos - 1]);
+            const after = pos < text.len and is_word_byte(text[pos]);
+            break :boundary (before != after) == (kind == .WordBoundary);
+        },
+    };
+}
+
+fn is_word_byte(chr: u8) bool {
+    return std.ascii.isAlNum(chr) or chr == '_';
+}
+
+const Decoded = struct {
+    cp: u21,
+    len: usize,
+};
+
+fn decode_at(text: []const u8, pos: usize) Decoded {
+    const as_byte = Decoded{ .cp = text[pos], .len = 1 };
+    const len = std.unicode.utf8ByteSequenceLength(text[pos]) catch return as_byte;
+    if (pos + len > text.len) return as_byte;
+    const cp = std.unicode.utf8Decode(text[pos .. pos + len]) catch return as_byte;
+    return Decoded{ .cp = cp, .len = len };
+}
+
+const DIGIT_RANGES = [_]Range{.{ .lo = '0', .hi = '9' }};
+const WORD_RANGES = [_]Range{
+    .{ .lo = '0', .hi = '9' },
+    .{ .lo = 'A', .hi = 'Z' },
+    .{ .lo = '_', .hi = '_' },
+    .{ .lo = 'a', .hi = 'z' },
+};
+const SPACE_RANGES = [_]Range{
+    .{ .lo = '\t', .hi = '\r' },
+    .{ .lo = ' ', .hi = ' ' },
+};
+
+const Parser = struct {
+    const Self = @This();
+
+    alloc: Allocator,
+    pattern: []const u8,
+    pos: usize,
+    depth: usize,
+    nodes: std.ArrayList(Node),
+    classes: std.ArrayList(Class),
+    names: std.ArrayList(GroupName),
+    group_count: usize,
+
+    fn add(self: *Self, node: Node) !usize {
+        try self.nodes.append(node);
+        return self.nodes.items.len - 1;
+    }
+
+    fn alternation(self: *Self) anyerror!usize {
+        var left = try self.concatenation();
+        while (self.eat('|')) {
+            const right = try self.concatenation();
+            left = try self.add(.{ .Alternate = .{ left, right } });
+        }
+        return left;
+    }
+
+    fn concatenation(self: *Self) !usize {
+        var children = std.ArrayList(usize).init(self.alloc);
+        while (!self.at_end() and self.peek() != '|' and self.peek() != ')') {
+            try children.append(try self.repetition());
+        }
+
+        return switch (children.items.len) {
+            0 => try self.add(.Empty),
+            1 => children.items[0],
+            else => try self.add(.{ .Concat = children.toOwnedSlice() }),
+        };
+    }
+
+    fn repetition(self: *Self) !usize {
+        var operand = try self.atom();
+
+        while (!self.at_end()) {
+            var min: u32 = undefined;
+            var max: ?u32 = undefined;
+            switch (self.peek()) {
+                '*' => {
+                    min = 0;
+                    max = null;
+                    self.pos += 1;
+                },
+                '+' => {
+                    min = 1;
+                    max = null;
+                    self.pos += 1;
+                },
+                '?' => {
+                    min = 0;
+                    max = 1;
+                    self.pos += 1;
+                },
+                '{' => {
+                    // A { which doesn't start a well-formed count is just a
+                    // {, which the next atom will pick up.
+                    const counts = try self.counted() orelse break;
+                    min = counts.min;
+                    max = counts.max;
+                },
+                else => break,
+            }
+
+            if (self.nodes.items[operand] == .Assert) return InternalError.ValueError;
+            const greedy = !self.eat('?');
+            operand = try self.add(.{ .Repeat = .{ .child = operand, .min = min, .max = max, .greedy = greedy } });
+        }
+
+        return operand;
+    }
+
+    const Counts = struct { min: u32, max: ?u32 };
+
+    fn counted(self: *Self) !?Counts {
+        const close = std.mem.indexOfScalarPos(u8, self.pattern, self.pos, '}') orelse return null;
+        const body = self.pattern[self.pos + 1 .. close];
+
+        var counts: Counts = undefined;
+        if (std.mem.indexOfScalar(u8, body, ',')) |comma| {
+            counts.min = std.fmt.parseInt(u32, body[0..comma], 10) catch return null;
+            counts.max = if (comma + 1 == body.len)
+                null
+            else
+                std.fmt.parseInt(u32, body[comma + 1 ..], 10) catch return null;
+        } else {
+            counts.min = std.fmt.parseInt(u32, body, 10) catch return null;
+            counts.max = counts.min;
+        }
+
+        if (counts.min > MAX_REPEAT) return InternalError.LimitExceeded;
+        if (counts.max) |max| {
+            if (max > MAX_REPEAT) return InternalError.LimitExceeded;
+            if (max < counts.min) return InternalError.ValueError;
+        }
+
+        self.pos = close + 1;
+        return counts;
+    }
+
+    fn atom(self: *Self) !usize {
+        const decoded = decode_at(self.pattern, self.pos);
+        self.pos += decoded.len;
+
+        return switch (decoded.cp) {
+            '(' => try self.group(),
+            '[' => try self.class(),
+            '.' => try self.add(.Any),
+            '^' => try self.add(.{ .Assert = .Start }),
+            '$' => try self.add(.{ .Assert = .End }),
+            '\\' => try self.escape(),
+            // Nothing to repeat.
+            '*', '+', '?' => InternalError.ValueError,
+            else => try self.add(.{ .Char = decoded.cp }),
+        };
+    }
+
+    fn group(self: *Self) !usize {
+        if (self.depth >= MAX_NESTING) return InternalError.LimitExceeded;
+        self.depth += 1;
+        defer self.depth -= 1;
+
+        var index: ?usize = null;
+        if (self.eat_str("?:")) {
+            // Non-capturing.
+        } else if (self.eat_str("?<") or self.eat_str("?P<")) {
+            const close = std.mem.indexOfScalarPos(u8, self.pattern, self.pos, '>') orelse return InternalError.ValueError;
+            const name = self.pattern[self.pos..close];
+            if (name.len == 0) return InternalError.ValueError;
+            for (name) |chr| {
+                if (!is_word_byte(chr)) return InternalError.ValueError;
+            }
+            for (self.names.items) |existing| {
+                if (std.mem.eql(u8, existing.name, name)) return InternalError.ValueError;
+            }
+            self.pos = close + 1;
+
+            self.group_count += 1;
+            index = self.group_count;
+            try self.names.append(.{ .name = name, .index = self.group_count });
+        } else {
+            self.group_count += 1;
+            index = self.group_count;
+        }
+
+        const child = try self.alternation();
+        if (!self.eat(')')) return InternalError.ValueError;
+        return try self.add(.{ .Group = .{ .child = child, .index = index } });
+    }
+
+    fn escape(self: *Self) !usize {
+        if (self.at_end()) return InternalError.ValueError;
+        const chr = self.peek();
+
+        switch (chr) {
+            'b' => {
+                self.pos += 1;
+                return try self.add(.{ .Assert = .WordBoundary });
+            },
+            'B' => {
+                self.pos += 1;
+                return try self.add(.{ .Assert = .NotWordBoundary });
+            },
+            'd', 'D', 'w', 'W', 's', 'S' => {
+                self.pos += 1;
+                var ranges = std.ArrayList(Range).init(self.alloc);
+                try append_shorthand(&ranges, chr);
+                try self.classes.append(.{ .ranges = ranges.toOwnedSlice(), .negated = false });
+                return try self.add(.{ .Class = self.classes.items.len - 1 });
+            },
+            else => return try self.add(.{ .Char = try self.escaped_char() }),
+        }
+    }
+
+    /// The character a (non-class) escape stands for, just past the \.
+    fn escaped_char(self: *Self) !u21 {
+        if (self.at_end()) return InternalError.ValueError;
+        const decoded = decode_at(self.pattern, self.pos);
+        self.pos += decoded.len;
+
+        return switch (decoded.cp) {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'f' => 0x0C,
+            'v' => 0x0B,
+            'a'...'z', 'A'...'Z', '0'...'9' => InternalError.ValueError,
+            else => decoded.cp,
+        };
+    }
+
+    fn class(self: *Self) !usize {
+        const negated = self.eat('^');
+        var ranges = std.ArrayList(Range).init(self.alloc);
+
+        var first = true;
+        while (true) : (first = false) {
+            if (self.at_end()) return InternalError.ValueError;
+            // A ] straight after the [ (or [^) is a literal ].
+            if (self.peek() == ']' and !first) {
+                self.pos += 1;
+                break;
+            }
+
+            const lo = try self.class_member(&ranges) orelse continue;
+
+            const is_range = self.pos + 1 < self.pattern.len and self.peek() == '-' and self.pattern[self.pos + 1] != ']';
+            if (!is_range) {
+                try ranges.append(.{ .lo = lo, .hi = lo });
+                continue;
+            }
+
+            self.pos += 1;
+            const hi = try self.class_member(&ranges) orelse return InternalError.ValueError;
+            if (hi < lo) return InternalError.ValueError;
+            try ranges.append(.{ .lo = lo, .hi = hi });
+        }
+
+        try self.classes.append(.{ .ranges = ranges.toOwnedSlice(), .negated = negated });
+        return try self.add(.{ .Class = self.classes.items.len - 1 });
+    }
+
+    /// A single character within a class, or null if it was a shorthand
+    /// like \d, whose ranges are appended to `ranges` directly.
+    fn class_member(self: *Self, ranges: *std.ArrayList(Range)) !?u21 {
+        if (self.at_end()) return InternalError.ValueError;
+        const decoded = decode_at(self.pattern, self.pos);
+        self.pos += decoded.len;
+        if (decoded.cp != '\\') return decoded.cp;
+
+        if (self.at_end()) return InternalError.ValueError;
+        switch (self.peek()) {
+            'd', 'D', 'w', 'W', 's', 'S' => {
+                try append_shorthand(ranges, self.peek());
+                self.pos += 1;
+                return null;
+            },
+            else => return try self.escaped_char(),
+        }
+    }
+
+    fn at_end(self: *Self) bool {
+        return self.pos >= self.pattern.len;
+    }
+
+    fn peek(self: *Self) u8 {
+        return self.pattern[self.pos];
+    }
+
+    fn eat(self: *Self, chr: u8) bool {
+        if (self.at_end() or self.peek() != chr) return false;
+        self.pos += 1;
+        return true;
+    }
+
+    fn eat_str(self: *Self, str: []const u8) bool {
+        if (!std.mem.startsWith(u8, self.pattern[self.pos..], str)) return false;
+        self.pos += str.len;
+        return true;
+    }
+};
+
+/// Append the ranges \d, \w, \s, or (in upper case) their negations cover.
+fn append_shorthand(ranges: *std.ArrayList(Range), which: u8) !void {
+    const positive: []const Range = switch (std.ascii.toLower(which)) {
+        'd' => &DIGIT_RANGES,
+        'w' => &WORD_RANGES,
+        's' => &SPACE_RANGES,
+        else => unreachable,
+    };
+
+    if (std.ascii.isLower(which)) return try ranges.appendSlice(positive);
+
+    // The complement of a sorted set of ranges is whatever lies between
+    // them.
+    var next: u21 = 0;
+    for (positive) |range| {
+        if (range.lo > next) try ranges.append(.{ .lo = next, .hi = range.lo - 1 });
+        next = range.hi + 1;
+    }
+    try ranges.append(.{ .lo = next, .hi = std.math.maxInt(u21) });
+}
+
+const Compiler = struct {
+    const Self = @This();
+
+    nodes: []const Node,
+    program: std.ArrayList(Inst),
+
+    fn emit(self: *Self, inst: Inst) !usize {
+        if (self.program.items.len >= MAX_PROGRAM_LEN) return InternalError.LimitExceeded;
+        try self.program.append(inst);
+        return self.program.items.len - 1;
+    }
+
+    fn split(first: usize, second: usize, greedy: bool) Inst {
+        return .{ .Split = if (greedy) .{ first, second } else .{ second, first } };
+    }
+
+    fn generate(self: *Self, index: usize) anyerror!void {
+        switch (self.nodes[index]) {
+            .Empty => {},
+            .Char => |cp| _ = try self.emit(.{ .Char = cp }),
+            .Any => _ = try self.emit(.Any),
+            .Class => |idx| _ = try self.emit(.{ .Class = idx }),
+            .Assert => |kind| _ = try self.emit(.{ .Assert = kind }),
+            .Concat => |children| for (children) |child| try self.generate(child),
+            .Alternate => |pair| {
+                const fork = try self.emit(.{ .Split = .{ 0, 0 } });
+                try self.generate(pair[0]);
+                const jump = try self.emit(.{ .Jump = 0 });
+                const right = self.program.items.len;
+                try self.generate(pair[1]);
+
+                self.program.items[fork] = .{ .Split = .{ fork + 1, right } };
+                self.program.items[jump] = .{ .Jump = self.program.items.len };
+            },
+            .Group => |grp| {
+                if (grp.index) |idx| _ = try self.emit(.{ .Save = 2 * idx });
+                try self.generate(grp.child);
+                if (grp.index) |idx| _ = try self.emit(.{ .Save = 2 * idx + 1 });
+            },
+            .Repeat => |rep| {
+                var count: u32 = 0;
+                while (count < rep.min) : (count += 1) try self.generate(rep.child);
+
+                if (rep.max) |max| {
+                    // x{0,2} is x(x)?, roughly: each optional copy may be
+                    // skipped, straight to the end.
+                    var forks = std.ArrayList(usize).init(self.program.allocator);
+                    defer forks.deinit();
+                    while (count < max) : (count += 1) {
+                        try forks.append(try self.emit(.{ .Split = .{ 0, 0 } }));
+                        try self.generate(rep.child);
+                    }
+
+                    const end = self.program.items.len;
+                    for (forks.items) |fork| self.program.items[fork] = split(fork + 1, end, rep.greedy);
+                } else {
+                    const fork = try self.emit(.{ .Split = .{ 0, 0 } });
+                    try self.generate(rep.child);
+                    _ = try self.emit(.{ .Jump = fork });
+                    self.program.items[fork] = split(fork + 1, self.program.items.len, rep.greedy);
+                }
+            },
+        }
+    }
+};
+
+fn expect_match(pattern: []const u8, text: []const u8, expected: ?[]const u8) !void {
+    var re = try Regex.compile(testAllocator, pattern);
+    defer re.deinit();
+
+    var found = try re.search(testAllocator, text, 0);
+    if (expected) |want| {
+        try expect(found != null);
+        defer found.?.deinit(testAllocator);
+        try expectEqualStrings(want, found.?.group(text, 0).?);
+    } else {
+        try expect(found == null);
+    }
+}
+
+test "search" {
+    try expect_match("b+", "abbbc", "bbb");
+    try expect_match("b+?", "abbbc", "b");
+    try expect_match("^b", "abc", null);
+    try expect_match("c$", "abc", "c");
+    try expect_match("a.c", "a\nc", null);
+    try expect_match("[^a-b]+", "abcd", "cd");
+    try expect_match("[]x]+", "x]y", "x]");
+    try expect_match("\\d{2,3}", "a12345", "123");
+    try expect_match("x{2}", "xxx", "xx");
+    try expect_match("cat|dog", "hotdog", "dog");
+    try expect_match("\\bis\\b", "this is", "is");
+    try expect_match("[\\W]+", "ab, cd", ", ");
+    try expect_match("é+", "caféé", "éé");
+    try expect_match("a{,", "a{,", "a{,");
+    // Patterns which would backtrack catastrophically elsewhere.
+    try expect_match("(a*)*b", "a" ** 64, null);
+    try expect_match("(a|a)*c", "a" ** 64, null);
+}
+
+test "captures and expand" {
+    var re = try Regex.compile(testAllocator, "(?<user>\\w+)@(\\w+)?(x)?");
+    defer re.deinit();
+    try expectEqual(@as(usize, 3), re.group_count);
+    try expectEqual(@as(?usize, 1), re.group_index("user"));
+
+    const text = "mail me@host now";
+    var found = (try re.search(testAllocator, text, 0)).?;
+    defer found.deinit(testAllocator);
+    try expectEqual(@as(usize, 5), found.start());
+    try expectEqualStrings("me", found.group(text, 1).?);
+    try expectEqualStrings("host", found.group(text, 2).?);
+    try expect(found.group(text, 3) == null);
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+    try re.expand(out.writer(), text, found, "${user} at $2$3 ($$)");
+    try expectEqualStrings("me at host ($)", out.items);
+
+    try expectError(InternalError.ValueError, re.expand(out.writer(), text, found, "$4"));
+    try expectError(InternalError.ValueError, re.expand(out.writer(), text, found, "${nope}"));
+}
+
+test "compile: malformed" {
+    const bad = [_][]const u8{ "(", ")", "a**b[", "*a", "[a", "a{3,2}", "\\q", "(?<>a)", "(?<a>x)(?<a>y)", "[z-a]", "^*" };
+    for (bad) |pattern| {
+        try expectError(InternalError.ValueError, Regex.compile(testAllocator, pattern));
+    }
+
+    try expectError(InternalError.LimitExceeded, Regex.compile(testAllocator, "a{1001}"));
+    try expectError(InternalError.LimitExceeded, Regex.compile(testAllocator, "(a{1000}){1000}"));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,241 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const conversion = @import("./conversion.zig");
+const regex = @import("./regex.zig");
+const Regex = regex.Regex;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+// Regular expressions over Strings. See regex.zig for the syntax. Patterns
+// are compiled afresh on every call.
+// TODO: cache compiled patterns, perhaps keyed by the pattern String.
+
+/// Every regex word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@REGEX-FIND-ALL", REGEX_FIND_ALL },
+    .{ "@REGEX-MATCH?", REGEX_MATCH_P },
+    .{ "@REGEX-REPLACE", REGEX_REPLACE },
+};
+
+/// Define every regex word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @REGEX-MATCH? ( String String <- Boolean )
+///
+/// Whether the pattern (the nearer String) matches anywhere in the text (the
+/// further one). Anchor it with ^ and $ to match the whole text. Malformed
+/// patterns are a ValueError.
+pub fn REGEX_MATCH_P(runtime: *Runtime) anyerror!void {
+    const args = try peek_text_and_pattern(runtime);
+
+    var re = try Regex.compile(runtime.alloc, args.pattern);
+    defer re.deinit();
+
+    var found = try re.search(runtime.alloc, args.text, 0);
+    defer if (found) |*match| match.deinit(runtime.alloc);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_bool(found != null);
+}
+
+/// @REGEX-FIND-ALL ( String String <- Array )
+///
+/// Every (non-overlapping) match of the pattern (the nearer String) in the
+/// text (the further one), from left to right. Each is a Map of:
+///
+/// - `match`: the String matched
+/// - `start` and `end`: byte offsets of the match within the text
+/// - `groups`: a Map from each capture group's number ("1", "2", ...) and,
+///   for named groups, name, to what it captured, or `:null` if it didn't
+///   participate in the match
+pub fn REGEX_FIND_ALL(runtime: *Runtime) anyerror!void {
+    const args = try peek_text_and_pattern(runtime);
+
+    var re = try Regex.compile(runtime.alloc, args.pattern);
+    defer re.deinit();
+
+    const matches = try runtime.new_array(0);
+    errdefer runtime.release_array(matches);
+
+    var pos: usize = 0;
+    while (true) {
+        var found = (try re.search(runtime.alloc, args.text, pos)) orelse break;
+        defer found.deinit(runtime.alloc);
+
+        var member = try (Object{ .Map = try match_to_map(runtime, &re, args.text, found) }).ref();
+        errdefer member.deinit(runtime.alloc);
+        try matches.value.?.append(member);
+
+        pos = found.resume_at(args.text) orelse break;
+    }
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_array(matches);
+}
+
+/// @REGEX-REPLACE ( String String String <- String )
+///
+/// The text (the furthest String) with every match of the pattern (the
+/// middle one) replaced by the replacement (the nearest one), in which $0
+/// through $9, ${number}, and ${name} stand for what the corresponding group
+/// captured, and $$ for a lone $. References to groups the pattern doesn't
+/// have are a ValueError.
+pub fn REGEX_REPLACE(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_trio();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    const mid = peek.mid orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or mid.* != .String or far.* != .String) return InternalError.TypeError;
+
+    const text = far.String.value.?;
+    const template = peek.near.String.value.?;
+
+    var re = try Regex.compile(runtime.alloc, mid.String.value.?);
+    defer re.deinit();
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+
+    var pos: usize = 0;
+    var copied: usize = 0;
+    while (true) {
+        var found = (try re.search(runtime.alloc, text, pos)) orelse break;
+        defer found.deinit(runtime.alloc);
+
+        try out.appendSlice(text[copied..found.start()]);
+        try re.expand(out.writer(), text, found, template);
+        copied = found.end();
+
+        pos = found.resume_at(text) orelse break;
+    }
+    try out.appendSlice(text[copied..]);
+
+    const str = try runtime.get_or_put_string(out.items);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+const TextAndPattern = struct {
+    text: []const u8,
+    pattern: []const u8,
+};
+
+fn peek_text_and_pattern(runtime: *Runtime) !TextAndPattern {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .String) return InternalError.TypeError;
+
+    return TextAndPattern{ .text = far.String.value.?, .pattern = peek.near.String.value.? };
+}
+
+fn match_to_map(runtime: *Runtime, re: *const Regex, text: []const u8, found: regex.Match) !*Types.HeapedMap {
+    const map = try runtime.new_map();
+    errdefer runtime.release_map(map);
+
+    try conversion.put_value(runtime, map, "match", found.group(text, 0).?);
+    try conversion.put_value(runtime, map, "start", found.start());
+    try conversion.put_value(runtime, map, "end", found.end());
+
+    var groups = try (Object{ .Map = try runtime.new_map() }).ref();
+    var filed = false;
+    defer if (!filed) groups.deinit(runtime.alloc);
+
+    var idx: usize = 1;
+    while (idx <= re.group_count) : (idx += 1) {
+        var buf: [20]u8 = undefined;
+        const key = std.fmt.bufPrint(&buf, "{d}", .{idx}) catch unreachable;
+        try conversion.put_value(runtime, groups.Map, key, found.group(text, idx));
+    }
+    for (re.names) |entry| {
+        try conversion.put_value(runtime, groups.Map, entry.name, found.group(text, entry.index));
+    }
+
+    try map.value.?.put(runtime.alloc, "groups", groups);
+    filed = true;
+
+    return map;
+}
+
+test "@REGEX-MATCH?" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"hello\" \"l+o$\" @REGEX-MATCH?");
+    try expect((try runtime.stack_pop()).Boolean);
+
+    try runtime.eval("\"hello\" \"^l\" @REGEX-MATCH?");
+    try expect(!(try runtime.stack_pop()).Boolean);
+
+    try expectError(InternalError.ValueError, runtime.eval("\"hello\" \"(\" @REGEX-MATCH?"));
+    var pattern = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&pattern);
+    var text = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&text);
+}
+
+test "@REGEX-FIND-ALL" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"a=1,b=22\" \"(?<key>\\w)=(\\d+)\" @REGEX-FIND-ALL");
+    var matches = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&matches);
+
+    const items = matches.Array.value.?.items;
+    try expectEqual(@as(usize, 2), items.len);
+
+    const second = &items[1].Map.value.?;
+    try expectEqualStrings("b=22", second.get("match").?.String.value.?);
+    try expectEqual(@as(usize, 4), second.get("start").?.UnsignedInt);
+    const groups = &second.get("groups").?.Map.value.?;
+    try expectEqualStrings("b", groups.get("key").?.String.value.?);
+    try expectEqualStrings("b", groups.get("1").?.String.value.?);
+    try expectEqualStrings("22", groups.get("2").?.String.value.?);
+}
+
+test "@REGEX-REPLACE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"a=1,b=22\" \"(\\w)=(\\d+)\" \"$2=$1\" @REGEX-REPLACE");
+    var replaced = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&replaced);
+    try expectEqualStrings("1=a,22=b", replaced.String.value.?);
+
+    try runtime.eval("\"abc\" \"x*\" \"-\" @REGEX-REPLACE");
+    var empties = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&empties);
+    try expectEqualStrings("-a-b-c-", empties.String.value.?);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -14,6 +14,7 @@
 const std = @import("std");
 const Allocator = std.mem.Allocator;
 const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
 const expectApproxEqAbs = std.testing.expectApproxEqAbs;
 const expectEqual = std.testing.expectEqual;
 const expectEqualStrings = std.testing.expectEqualStrings;
@@ -22,31 +23,117 @@ const expectError = std.testing.expectError;
 const builtin = @import("builtin");
 
 const _object = @import("./object.zig");
+const _observer = @import("./observer.zig");
 const _stack = @import("./stack.zig");
+const _task = @import("./task.zig");
 const _word = @import("./word.zig");
 
+const arithmetic_words = @import("./arithmetic_words.zig");
+const array_words = @import("./array_words.zig");
+const bitwise_words = @import("./bitwise_words.zig");
+const bundle = @import("./bundle.zig");
+const capabilities = @import("./capabilities.zig");
+const cell = @import("./cell.zig");
+const channel = @import("./channel.zig");
+const conditional = @import("./conditional.zig");
+const conversion = @import("./conversion.zig");
+const docstrings = @import("./docstrings.zig");
+const env_words = @import("./env_words.zig");
+const features = @import("./features.zig");
+const format_words = @import("./format_words.zig");
+const hashbang = @import("./hashbang.zig");
+const fs_words = @import("./fs_words.zig");
+const task_words = @import("./task_words.zig");
+const thunk_words = @import("./thunk_words.zig");
+const time_words = @import("./time_words.zig");
 const helpers = @import("./helpers.zig");
+const http_words = @import("./http_words.zig");
+const image = @import("./image.zig");
+const io = @import("./io.zig");
+const io_words = @import("./io_words.zig");
+const json_words = @import("./json_words.zig");
+const library_path = @import("./library_path.zig");
+const toml_words = @import("./toml_words.zig");
+const csv_words = @import("./csv_words.zig");
+const msgpack_words = @import("./msgpack_words.zig");
+const encoding_words = @import("./encoding_words.zig");
+const regex_words = @import("./regex_words.zig");
+const set = @import("./set.zig");
+const set_words = @import("./set_words.zig");
+const source_encoding = @import("./source_encoding.zig");
+const unicode_words = @import("./unicode_words.zig");
+const digest_words = @import("./digest_words.zig");
+const sexp_words = @import("./sexp_words.zig");
+const introspection_words = @import("./introspection_words.zig");
+const trace_words = @import("./trace_words.zig");
+const test_runner = @import("./test_runner.zig");
+const test_words = @import("./test_words.zig");
+const iter_words = @import("./iter_words.zig");
+const line_cursor = @import("./line_cursor.zig");
+const locals_words = @import("./locals_words.zig");
+const map = @import("./map.zig");
+const map_words = @import("./map_words.zig");
+const math_words = @import("./math_words.zig");
+const module = @import("./module.zig");
+const module_words = @import("./module_words.zig");
+const net = @import("./net.zig");
+const net_words = @import("./net_words.zig");
+const nucleus_words = @import("./nucleus_words.zig");
+const parallel_words = @import("./parallel_words.zig");
+const path_words = @import("./path_words.zig");
+const prelude = @import("./prelude.zig");
+const proc_words = @import("./proc_words.zig");
+const random_words = @import("./random_words.zig");
+const recording = @import("./recording.zig");
+const variant_words = @import("./variant_words.zig");
+const vocabulary = @import("./vocabulary.zig");
 const well_known_entities = @import("./well_known_entities.zig");
+const version = @import("./version.zig");
+const word_map = @import("./word_map.zig");
 
+const CallSite = _word.CallSite;
+const Capabilities = capabilities.Capabilities;
+const Capability = capabilities.Capability;
 const CompoundImplementation = _word.CompoundImplementation;
+const CountingAllocator = @import("./stats.zig").CountingAllocator;
 const HeapLitImplementation = _word.HeapLitImplementation;
+const HostClosure = _word.HostClosure;
+const HostClosureFn = _word.HostClosureFn;
+const InputHandle = io.InputHandle;
 const InternalError = @import("./internal_error.zig").InternalError;
+const LazyVocabulary = vocabulary.LazyVocabulary;
 const Object = _object.Object;
+const Observer = _observer.Observer;
+const OutputHandle = io.OutputHandle;
+const ObserverEvent = _observer.Event;
 const ParsedWord = @import("./parsed_word.zig").ParsedWord;
+const Pipeline = @import("./pipeline.zig").Pipeline;
 const PrimitiveImplementation = _word.PrimitiveImplementation;
 const Shape = @import("./shape.zig").Shape;
+const Frame = _task.Frame;
+const FrameStack = _task.FrameStack;
 const Stack = _stack.Stack;
 const StackManipulationError = _stack.StackManipulationError;
+const Stats = @import("./stats.zig").Stats;
+const Task = _task.Task;
+const TaskMap = _task.TaskMap;
+const Tracer = @import("./tracer.zig").Tracer;
 const Types = @import("./types.zig");
 const Word = _word.Word;
 const WordList = @import("./word_list.zig").WordList;
-const WordMap = @import("./word_map.zig").WordMap;
+const WordMap = word_map.WordMap;
 const WordSignature = @import("./word_signature.zig").WordSignature;
+
+/// Docstrings, by the word they document. Each holds a reference to its word.
+const DocMap = std.AutoHashMap(*Types.HeapedWord, []u8);
 const WellKnownShape = well_known_entities.WellKnownShape;
 const WellKnownShapeStorage = well_known_entities.WellKnownShapeStorage;
 const WellKnownSignature = well_known_entities.WellKnownSignature;
 const WellKnownSignatureStorage = well_known_entities.WellKnownSignatureStorage;
 
+/// Handed out to each Runtime as it's created, see Runtime.id.
+var next_runtime_id = std.atomic.Atomic(usize).init(1);
+
 pub const Runtime = struct {
     const Self = @This();
 
@@ -99,8 +186,22 @@ pub const Runtime = struct {
 
     /// All symbols are interned by their raw "string" contents and stored
     /// behind a typical garbage collection structure (Rc([]u8)) for later
-    /// pulling onto a stack.
-    const SymbolPool = std.StringHashMap(Types.HeapedSymbol);
+    /// pulling onto a stack. The pool holds a reference to each of its
+    /// symbols for the life of the Runtime, and the keys are the symbols' own
+    /// inner slices, so they live exactly as long as their values do.
+    ///
+    /// Since interned symbols are unique by address, the dictionary keys off
+    /// of these pointers (see word_map.zig), and thus this is the only place
+    /// a word's name is ever hashed by contents.
+    const SymbolPool = std.HashMap(
+        []const u8,
+        *Types.HeapedSymbol,
+        word_map.StringContext(word_map.DefaultHasher),
+        std.hash_map.default_max_load_percentage,
+    );
+
+    /// Lazy vocabularies are keyed by their (Runtime-owned) names.
+    const LazyVocabularyMap = std.StringHashMap(LazyVocabulary);
 
     // TODO: use HashSet if https://github.com/ziglang/zig/issues/6919 ever
     // moves
@@ -131,50 +232,631 @@ pub const Runtime = struct {
     }
 
     alloc: Allocator,
+    /// Unique (for the life of the process) to this Runtime.
+    id: usize,
+    base: ?*const Self,
+    /// Frozen Runtimes refuse all changes to their dictionaries, and are thus
+    /// safe to share as a base.
+    frozen: bool,
     dictionary: WordMap,
+    /// Bumped whenever an already-defined name gains a new definition, which
+    /// invalidates every CallSite's cached resolution.
+    dictionary_generation: usize,
+    lazy_vocabularies: LazyVocabularyMap,
+    capabilities: Capabilities,
+    limits: Limits,
+    worker_threads: usize,
+    /// How many times `eval` is currently on the (Zig) call stack: only the
+    /// outermost eval starts a new budget.
+    eval_nesting: usize,
+    /// `#if-` blocks open in whatever's being evaluated. See conditional.zig.
+    conditionals: conditional.Conditionals,
+    /// For `#if-flag:`, with (owned) names from Options.flags and
+    /// define_flag.
+    flags: std.StringArrayHashMap(void),
+    /// Sources being evaluated by eval_source, outermost first.
+    loads: std.ArrayList(Load),
+    /// Where the last failed eval_source failed, innermost source first.
+    load_trace: std.ArrayList(SourcePosition),
+    /// Our own copy of Options.library_path.
+    library_path: [][]u8,
+    /// Everywhere the last failed resolve_source_path looked, in order.
+    resolution_attempts: std.ArrayList([]u8),
+    /// Sources which @LOAD and @IMPORT find here rather than on disk, by
+    /// (owned) path as written. See add_bundled_file.
+    bundled: std.StringHashMap([]u8),
+    /// Every module ever imported, by path. See import_module.
+    modules: module.ModuleMap,
+    /// Consulted, newest first, by find_word when a name isn't defined as-is.
+    search_order: std.ArrayList(module.SearchEntry),
+    /// The vocabulary of the module being imported, if any, which
+    /// define_word_here defines unqualified names into.
+    current_vocabulary: ?[]const u8,
+    /// Vocabularies' versions, and what's been demanded of them, by
+    /// (Runtime-owned) vocabulary name. See declare_version.
+    versions: std.StringArrayHashMap(version.Versioning),
+    /// Why the last IncompatibleVersion was raised, if it was, and there was
+    /// memory to say so.
+    version_conflict: ?[]u8,
+    words_executed: usize,
+    budget_started: ?std.time.Instant,
+    /// What monotonic clock readings (see time_words.zig) are relative to.
+    /// Null on systems without such a clock.
+    clock_epoch: ?std.time.Instant,
+    /// Set (from anywhere, signal handlers included) by `interrupt`.
+    interrupt_requested: std.atomic.Atomic(bool),
+    /// Where words like @PRINT and @READ-LINE do their I/O. See io.zig.
+    stdin: InputHandle,
+    stdout: OutputHandle,
+    stderr: OutputHandle,
+    /// This Runtime's own copy of the process environment, read in on first
+    /// use (see env_map), so that @ENV-SET needn't touch the process's own
+    /// (which isn't threadsafe to change on most systems) and subprocesses
+    /// see whatever gale code has set.
+    env: ?std.process.EnvMap,
+    /// Open TCP connections and listeners, by the IDs handed out to gale
+    /// code. See net_words.zig.
+    sockets: net.SocketMap,
+    next_socket_id: usize,
+    /// Files being read a line at a time, by the IDs handed out to gale
+    /// code. See line_cursor.zig.
+    line_cursors: line_cursor.LineCursorMap,
+    next_line_cursor_id: usize,
+    /// Behind @RANDOM and friends. See Options.random_seed.
+    prng: std.rand.DefaultPrng,
+    /// Whether what this Runtime learns from the world (see recording.zig)
+    /// is being written down, or read back. See Options.recording.
+    recording: recording.Source,
+    observers: std.ArrayList(Observer),
+    /// Installed (as one of the observers) by @TRACE-ON. See trace_words.zig.
+    tracer: ?*Tracer,
+    /// Running totals, allocations aside. See stats.
+    counters: Stats,
+    /// Behind `alloc`, if Options.count_allocations asked for one.
+    allocation_counter: ?*CountingAllocator,
+    /// Registered by @TEST, for test_runner.run.
+    tests: test_runner.Registry,
+    /// See document_word.
+    docs: DocMap,
     private_space: PrivateSpace,
     stack: *Stack,
+    /// Words currently being run, innermost last. See task.zig.
+    frames: FrameStack,
+    /// Values bound by @WITH-LOCALS while its words run. See
+    /// locals_words.zig.
+    locals: locals_words.Locals,
+    /// Spawned, but not yet joined, Tasks.
+    tasks: TaskMap,
+    next_task_id: usize,
+    /// Null when the Runtime's own ("main") Stack and Frames are swapped in.
+    current_task: ?*Task,
+    /// Set by @YIELD within a Task to end its current slice.
+    yield_requested: bool,
+    /// Whether whatever's running could be suspended (mid-word) and resumed
+    /// later, which is only true of a Task's own words: a Task's words being
+    /// run by way of a nested run_word (from a host closure, say) must
+    /// finish before the closure can return.
+    suspendable: bool,
     symbols: SymbolPool,
     signatures: WordSignaturePool,
-    well_known_shapes: WellKnownShapeStorage,
+    /// Heap-allocated so that the addresses of these Shapes (which the
+    /// well-known signatures point to) survive the Runtime being moved.
+    well_known_shapes: *WellKnownShapeStorage,
     well_known_signatures: WellKnownSignatureStorage,
 
+    /// Knobs for Runtime construction, mostly of interest to embedders and
+    /// those targeting constrained systems. Every field has a sane default,
+    /// so `init_with_options(alloc, .{})` is exactly `init(alloc)`.
+    pub const Options = struct {
+        /// How many dictionary entries, interned symbols, and word signatures
+        /// to preallocate space for, respectively. These are hints, not
+        /// limits: each will grow as needed.
+        dictionary_capacity: u32 = DICTIONARY_DEFAULT_SIZE,
+        symbol_pool_capacity: u32 = SYMBOL_POOL_DEFAULT_SIZE,
+        signature_pool_capacity: u32 = SIGNATURE_POOL_DEFAULT_SIZE,
+
+        // There's no Stack capacity alongside these: the Stack grows a
+        // fixed-size segment (see stack.zig) at a time, leaving nothing to
+        // preallocate, and limits.max_stack_depth bounds it. Nor is there a
+        // float width, Floats being f64s throughout (see Object), which no
+        // one Runtime can narrow.
+
+        /// Which groups of built-in words to define at startup.
+        primitives: PrimitiveGroups = .{},
+
+        /// An image (see image.zig) saved by a Runtime fresh from its own
+        /// init, loaded in place of evaluating prelude.gale afresh, for hosts
+        /// starting many Runtimes alike. Primitives can't be saved, only
+        /// referred to by name, so their groups are installed either way, and
+        /// must match those of the Runtime which saved it. Ignored without
+        /// the Prelude, or with a base. Needn't outlive init.
+        prelude_image: ?[]const u8 = null,
+
+        limits: Limits = .{},
+
+        /// What this Runtime may do to the outside world. See
+        /// capabilities.zig: by default, nothing.
+        capabilities: Capabilities = capabilities.NONE,
+
+        /// How many threads (including the calling one) words like @PMAP may
+        /// spread their work across, each with a Runtime of its own layered
+        /// over `base` (without one, there's nothing to share, and so these
+        /// are ignored). 0 and 1 both mean "just this thread". Hosts setting
+        /// this must hand us a thread-safe allocator.
+        worker_threads: usize = 0,
+
+        /// Where words like @PRINT and @READ-LINE do their I/O. Null means
+        /// the process's own stdio (or, on freestanding targets, nowhere at
+        /// all). See io.zig for some alternatives.
+        stdin: ?InputHandle = null,
+        stdout: ?OutputHandle = null,
+        stderr: ?OutputHandle = null,
+
+        /// What to seed this Runtime's PRNG (see random_words.zig) with, for
+        /// reproducible runs. Null means "something different every time",
+        /// where the system can provide it.
+        random_seed: ?u64 = null,
+
+        /// Whether to write down everything this Runtime reads from the
+        /// world which could differ next time (its seed, stdin, clocks, and
+        /// environment), or else to read it all back from such a recording,
+        /// so as to run just as that run did. Whatever's recorded to or
+        /// replayed from must outlive the Runtime. See recording.zig.
+        recording: recording.Source = .Live,
+
+        /// Names `#if-flag:` (see conditional.zig) considers defined. Copied,
+        /// so needn't outlive the Runtime. See also Runtime.define_flag.
+        flags: []const []const u8 = &.{},
+
+        /// Where @LOAD and @IMPORT look for files named without saying where
+        /// they are (see Runtime.resolve_source_path), in order. Empty means
+        /// "right here, in the working directory". Copied, so needn't outlive
+        /// the Runtime. See library_path.from_environment for the usual set.
+        library_path: []const []const u8 = &.{},
+
+        /// A frozen (see Runtime.freeze) Runtime whose words this one can see
+        /// and run, but never modify. Any number of Runtimes can share one
+        /// base (even across threads), each with its own stack and its own
+        /// dictionary layered over the top. The base must outlive all of
+        /// them.
+        base: ?*const Runtime = null,
+
+        /// Whether to count every allocation this Runtime makes (see
+        /// stats), which costs a little on each. Everything holding the
+        /// Runtime's allocator, stack snapshots included, must then be done
+        /// with before deinit, since it takes the counting with it.
+        count_allocations: bool = false,
+    };
+
+    /// Bounds on how much work a single top-level `eval` may do before being
+    /// cut off with an InternalError.LimitExceeded, for embedders running
+    /// untrusted code. Null means unlimited, which is the default for all of
+    /// them. A Runtime which has hit a limit is left in whatever state it
+    /// was in at the time: use stack_snapshot beforehand if that matters.
+    pub const Limits = struct {
+        /// Counts each word run, including those run from within other
+        /// words, but not literals.
+        max_words_executed: ?usize = null,
+        /// Checked before each word and after each literal.
+        max_stack_depth: ?usize = null,
+        /// Checked before each word, so a single long-running primitive can
+        /// overshoot this. Ignored on freestanding targets.
+        max_wall_clock_ns: ?u64 = null,
+    };
+
+    /// Which word groups init_with_options installs. Those compiled out of
+    /// the build altogether (see features.zig) stay out regardless.
+    pub const PrimitiveGroups = struct {
+        /// The @-prefixed words in nucleus_words.zig, upon which everything
+        /// else is built. Turning these off leaves a Runtime which can do
+        /// little more than push literals until the host defines some words.
+        nucleus: bool = true,
+        /// @SPAWN, @YIELD, @JOIN, and Channels. See task_words.zig.
+        tasks: bool = true,
+        /// @DELAY and @FORCE, for lazily-run (and remembered) results. See
+        /// thunk_words.zig.
+        thunks: bool = true,
+        /// Ranges (stepped or not), and taking, mapping, filtering, and
+        /// draining iterators. See iter_words.zig.
+        iterators: bool = true,
+        /// Running Words across Arrays (@ARRAY-MAP, @ARRAY-REDUCE, and the
+        /// like), and sorting them. See array_words.zig.
+        arrays: bool = true,
+        /// @PMAP. See parallel_words.zig.
+        parallel: bool = true,
+        /// Sleeping, clocks, and calendars. See time_words.zig.
+        time: bool = true,
+        /// @PRINT, @READ-LINE, and friends. See io_words.zig.
+        io: bool = true,
+        /// Files and directories, each gated behind the Read or Write
+        /// capability. See fs_words.zig.
+        fs: bool = true,
+        /// Environment variables, the working directory, and the like. See
+        /// env_words.zig.
+        env: bool = true,
+        /// Reading Maps. See map_words.zig.
+        maps: bool = true,
+        /// Building Sets, and their algebra. See set_words.zig.
+        sets: bool = true,
+        /// Running subprocesses, gated behind the Run capability. See
+        /// proc_words.zig.
+        proc: bool = true,
+        /// TCP clients and servers, gated behind the Net capability. See
+        /// net_words.zig.
+        net: bool = true,
+        /// @HTTP-GET and @HTTP-REQUEST, gated behind the Net capability. See
+        /// http_words.zig.
+        http: bool = true,
+        /// @RANDOM and friends, and UUIDs. See random_words.zig.
+        random: bool = true,
+        /// @ADD, @SUB, and @MUL, and their wrapping and saturating variants,
+        /// along with @MIN, @MAX, @ABS, @NEG, and @SIGN. See
+        /// arithmetic_words.zig.
+        arithmetic: bool = true,
+        /// @BAND, @SHL, and the rest of the bitwise operations on integers.
+        /// See bitwise_words.zig.
+        bitwise: bool = true,
+        /// @SQRT, @POW, @SIN, @FLOOR, and the rest of the floating point
+        /// math. See math_words.zig.
+        math: bool = true,
+        /// Splitting, joining, and tidying file system paths. See
+        /// path_words.zig.
+        paths: bool = true,
+        /// @JSON-PARSE and @JSON-STRINGIFY. See json_words.zig.
+        json: bool = true,
+        /// @TOML-PARSE. See toml_words.zig.
+        toml: bool = true,
+        /// @CSV-PARSE, @CSV-PARSE-HEADED, and @CSV-STRINGIFY. See
+        /// csv_words.zig.
+        csv: bool = true,
+        /// @MSGPACK-ENCODE and @MSGPACK-DECODE. See msgpack_words.zig.
+        msgpack: bool = true,
+        /// Base64 and hex, to and from bytes. See encoding_words.zig.
+        encodings: bool = true,
+        /// @REGEX-MATCH?, @REGEX-FIND-ALL, and @REGEX-REPLACE, over the
+        /// engine in regex.zig. See regex_words.zig.
+        regex: bool = true,
+        /// Grapheme-aware counting and slicing, searching, replacing, case
+        /// folding, trimming, splitting, and joining. See unicode_words.zig.
+        unicode: bool = true,
+        /// @FORMAT, filling in templates, and writing and reading integers
+        /// in other radices. See format_words.zig.
+        formatting: bool = true,
+        /// @SHA256, @SHA1, and @CRC32. See digest_words.zig.
+        digests: bool = true,
+        /// @SEXP-PARSE and @SEXP-WRITE. See sexp_words.zig.
+        sexp: bool = true,
+        /// @TRACE-ON and @TRACE-OFF. See trace_words.zig.
+        trace: bool = true,
+        /// @ASSERT, @ASSERT-EQ, @ASSERT-STACK, and @TEST. See test_words.zig.
+        testing: bool = true,
+        /// @IMPORT and friends, gated behind the Read capability, and
+        /// @USE and the versioning words. See module_words.zig.
+        modules: bool = true,
+        /// @SEE, @HELP, and @INTROSPECT-STATS. See introspection_words.zig.
+        introspection: bool = true,
+        /// @LOCALS, binding names to values within a word. See
+        /// locals_words.zig.
+        locals: bool = true,
+        /// @VARIANT and @MATCH, for sum-shaped values. See
+        /// variant_words.zig.
+        variants: bool = true,
+        /// The `std` vocabulary, written in gale (see prelude.gale), and put
+        /// in the search order. Needs the nucleus, without which it's
+        /// skipped. Runtimes with a base use the base's.
+        prelude: bool = true,
+
+        /// Every group turned off, for hosts wanting only a few, or nothing
+        /// more than somewhere to put Objects.
+        pub const NONE = none: {
+            var groups = PrimitiveGroups{};
+            inline for (std.meta.fields(PrimitiveGroups)) |field| @field(groups, field.name) = false;
+            break :none groups;
+        };
+    };
+
+    fn initial_random_seed() u64 {
+        // Freestanding targets have no entropy to ask for, so they get the
+        // same sequence every time unless told otherwise.
+        if (comptime !helpers.HOSTED) return 0;
+
+        var seed: u64 = undefined;
+        std.crypto.random.bytes(std.mem.asBytes(&seed));
+        return seed;
+    }
+
+    fn copy_library_path(alloc: Allocator, dirs: []const []const u8) ![][]u8 {
+        const copied = try alloc.alloc([]u8, dirs.len);
+        var filled: usize = 0;
+        errdefer library_path.free(alloc, copied[0..filled]);
+
+        for (dirs) |dir| {
+            copied[filled] = try alloc.dupe(u8, dir);
+            filled += 1;
+        }
+
+        return copied;
+    }
+
     pub fn init(alloc: Allocator) !Self {
+        return try init_with_options(alloc, .{});
+    }
+
+    pub fn init_with_options(backing_alloc: Allocator, options: Options) !Self {
+        if (options.base) |base| {
+            if (!base.frozen) return InternalError.ValueError;
+        }
+
+        // Once rt exists, below, its deinit cleans up after everything before
+        // it instead.
+        var handed_to_rt = false;
+
+        const allocation_counter = if (options.count_allocations) try CountingAllocator.create(backing_alloc) else null;
+        errdefer if (!handed_to_rt and allocation_counter != null) allocation_counter.?.destroy();
+        const alloc = if (allocation_counter) |counter| counter.allocator() else backing_alloc;
+
         var dictionary = WordMap.init(alloc);
-        try dictionary.ensureTotalCapacity(DICTIONARY_DEFAULT_SIZE);
+        errdefer if (!handed_to_rt) dictionary.deinit();
+        try dictionary.ensureTotalCapacity(options.dictionary_capacity);
 
         var symbol_pool = SymbolPool.init(alloc);
-        try symbol_pool.ensureTotalCapacity(SYMBOL_POOL_DEFAULT_SIZE);
+        errdefer if (!handed_to_rt) symbol_pool.deinit();
+        try symbol_pool.ensureTotalCapacity(options.symbol_pool_capacity);
 
         var signature_pool = WordSignaturePool.init(alloc);
-        try signature_pool.ensureTotalCapacity(SIGNATURE_POOL_DEFAULT_SIZE);
+        errdefer if (!handed_to_rt) signature_pool.deinit();
+        try signature_pool.ensureTotalCapacity(options.signature_pool_capacity);
+
+        const copied_library_path = try copy_library_path(alloc, options.library_path);
+        errdefer if (!handed_to_rt) library_path.free(alloc, copied_library_path);
+
+        const stack = try Stack.init(alloc, null);
+        errdefer if (!handed_to_rt) stack.release();
+
+        // Filled in from the build-time image by populate(), below.
+        const well_known_shapes = try alloc.create(WellKnownShapeStorage);
+        errdefer if (!handed_to_rt) alloc.destroy(well_known_shapes);
+
+        const random_seed = if (try options.recording.replayed(.Seed)) |seed|
+            seed
+        else
+            options.random_seed orelse initial_random_seed();
+        try options.recording.record(.{ .Seed = random_seed });
 
         var rt = Self{
             .alloc = alloc,
+            .id = next_runtime_id.fetchAdd(1, .Monotonic),
+            .base = options.base,
+            .frozen = false,
             .dictionary = dictionary,
+            .dictionary_generation = 0,
+            .lazy_vocabularies = LazyVocabularyMap.init(alloc),
+            .capabilities = options.capabilities,
+            .limits = options.limits,
+            .worker_threads = options.worker_threads,
+            .eval_nesting = 0,
+            .conditionals = .{},
+            .flags = std.StringArrayHashMap(void).init(alloc),
+            .loads = std.ArrayList(Load).init(alloc),
+            .load_trace = std.ArrayList(SourcePosition).init(alloc),
+            .library_path = copied_library_path,
+            .resolution_attempts = std.ArrayList([]u8).init(alloc),
+            .bundled = std.StringHashMap([]u8).init(alloc),
+            .modules = module.ModuleMap.init(alloc),
+            .search_order = std.ArrayList(module.SearchEntry).init(alloc),
+            .current_vocabulary = null,
+            .versions = std.StringArrayHashMap(version.Versioning).init(alloc),
+            .version_conflict = null,
+            .words_executed = 0,
+            .budget_started = null,
+            .clock_epoch = if (comptime helpers.HOSTED) std.time.Instant.now() catch null else null,
+            .interrupt_requested = std.atomic.Atomic(bool).init(false),
+            .stdin = options.recording.stdin(options.stdin orelse io.stdin()),
+            .stdout = options.stdout orelse io.stdout(),
+            .stderr = options.stderr orelse io.stderr(),
+            .env = null,
+            .sockets = net.SocketMap.init(alloc),
+            .next_socket_id = 1,
+            .line_cursors = line_cursor.LineCursorMap.init(alloc),
+            .next_line_cursor_id = 1,
+            .prng = std.rand.DefaultPrng.init(random_seed),
+            .recording = options.recording,
+            .observers = std.ArrayList(Observer).init(alloc),
+            .tracer = null,
+            .counters = .{},
+            .allocation_counter = allocation_counter,
+            .tests = test_runner.Registry.init(alloc),
+            .docs = DocMap.init(alloc),
             .private_space = PrivateSpace.init(),
-            .stack = try Stack.init(alloc, null),
+            .stack = stack,
+            .frames = FrameStack.init(alloc),
+            .locals = .{},
+            .tasks = TaskMap.init(alloc),
+            .next_task_id = 1,
+            .current_task = null,
+            .yield_requested = false,
+            .suspendable = false,
             .symbols = symbol_pool,
             .signatures = signature_pool,
-            .well_known_shapes = well_known_entities.shape_storage(),
+            .well_known_shapes = well_known_shapes,
             .well_known_signatures = well_known_entities.signature_storage(),
         };
+        handed_to_rt = true;
+        errdefer rt.deinit();
 
         try well_known_entities.populate(&rt);
+        for (options.flags) |flag| try rt.define_flag(flag);
+
+        // No sense defining the primitives twice when the base has already
+        // done so for us. Groups compiled out (see features.zig) are left out
+        // whatever the options say.
+        if (options.base == null) {
+            const groups = options.primitives;
+            if (groups.nucleus) try nucleus_words.install(&rt);
+            if (groups.tasks) try task_words.install(&rt);
+            if (groups.thunks) try thunk_words.install(&rt);
+            if (groups.iterators) try iter_words.install(&rt);
+            if (groups.arrays) try array_words.install(&rt);
+            if (groups.parallel) try parallel_words.install(&rt);
+            if (groups.time) try time_words.install(&rt);
+            if (groups.io) try io_words.install(&rt);
+            if (groups.fs) try fs_words.install(&rt);
+            if (groups.env) try env_words.install(&rt);
+            if (groups.maps) try map_words.install(&rt);
+            if (groups.sets) try set_words.install(&rt);
+            if (groups.proc) try proc_words.install(&rt);
+            if (features.enabled.net and groups.net) try net_words.install(&rt);
+            if (features.enabled.net and groups.http) try http_words.install(&rt);
+            if (groups.random) try random_words.install(&rt);
+            if (groups.arithmetic) try arithmetic_words.install(&rt);
+            if (groups.bitwise) try bitwise_words.install(&rt);
+            if (groups.math) try math_words.install(&rt);
+            if (groups.paths) try path_words.install(&rt);
+            if (features.enabled.json and groups.json) try json_words.install(&rt);
+            if (groups.toml) try toml_words.install(&rt);
+            if (groups.csv) try csv_words.install(&rt);
+            if (groups.msgpack) try msgpack_words.install(&rt);
+            if (groups.encodings) try encoding_words.install(&rt);
+            if (features.enabled.regex and groups.regex) try regex_words.install(&rt);
+            if (groups.unicode) try unicode_words.install(&rt);
+            if (groups.formatting) try format_words.install(&rt);
+            if (features.enabled.crypto and groups.digests) try digest_words.install(&rt);
+            if (groups.sexp) try sexp_words.install(&rt);
+            if (groups.trace) try trace_words.install(&rt);
+            if (groups.testing) try test_words.install(&rt);
+            if (groups.modules) try module_words.install(&rt);
+            if (groups.introspection) try introspection_words.install(&rt);
+            if (groups.locals) try locals_words.install(&rt);
+            if (groups.variants) try variant_words.install(&rt);
+        }
+        if (options.primitives.prelude and options.primitives.nucleus) {
+            if (options.base != null) {
+                try rt.use_vocabulary(prelude.VOCABULARY, null);
+            } else if (options.prelude_image) |bytes| {
+                try image.load(&rt, bytes);
+            } else {
+                try prelude.install(&rt);
+            }
+        }
 
         return rt;
     }
 
+    test "init_with_options: primitive groups" {
+        var bare = try Self.init_with_options(testAllocator, .{
+            .dictionary_capacity = 0,
+            .primitives = .{ .nucleus = false },
+        });
+        defer bare.deinit_guard_for_empty_stack();
+        try expectError(InternalError.UnknownWord, bare.eval("@DUP"));
+
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+        try rt.eval("1 @DUP @DROP @DROP");
+    }
+
+    test "init_with_options: prelude_image" {
+        var saved = std.ArrayList(u8).init(testAllocator);
+        defer saved.deinit();
+        {
+            var fresh = try Self.init(testAllocator);
+            defer fresh.deinit_guard_for_empty_stack();
+            try image.save(&fresh, saved.writer());
+        }
+
+        var rt = try Self.init_with_options(testAllocator, .{ .prelude_image = saved.items });
+        defer rt.deinit_guard_for_empty_stack();
+        try rt.eval("1 2 nip");
+        try expectEqual(@as(usize, 2), try rt.stack_pop_value(usize));
+    }
+
+    test "init_with_options: failures leave nothing behind" {
+        // The testing allocator fails the test on any leak.
+        try expectError(InternalError.ValueError, Self.init_with_options(testAllocator, .{ .prelude_image = "nope" }));
+        try expectError(InternalError.ValueError, Self.init_with_options(testAllocator, .{
+            .prelude_image = "nope",
+            .count_allocations = true,
+        }));
+
+        // Whichever allocation fails, save for the Prelude's: it runs to
+        // thousands of them, all by way of eval.
+        const options = Options{
+            .library_path = &.{ "one", "two" },
+            .flags = &.{"fancy"},
+            .primitives = .{ .prelude = false },
+        };
+        var fail_index: usize = 0;
+        while (true) : (fail_index += 1) {
+            var failing = std.testing.FailingAllocator.init(testAllocator, fail_index);
+            var rt = Self.init_with_options(failing.allocator(), options) catch |err| {
+                try expect(err == error.OutOfMemory);
+                continue;
+            };
+            rt.deinit_guard_for_empty_stack();
+            break;
+        }
+    }
+
     pub fn deinit(self: *Self) void {
-        // First, nuke everything on the stack using this horribly named method
-        // (TODO for the love of god find better names for these things).
-        self.stack.deinit_from_bottom();
+        // First, nuke everything on the stack. This walks top-down and respects
+        // copy-on-write sharing, so any outstanding snapshots remain valid
+        // (though they must still be discarded by their owners).
+        self.stack.release();
         self.deinit_shared();
     }
 
     /// The rest of the deinit() sequence, shared between the standard deinit()
     /// and the test-mode-only deinit_guard_for_empty_stack().
     fn deinit_shared(self: *Self) void {
+        // Tasks which were never joined hold references to their entrypoints,
+        // which may well be dictionary words, so these go first.
+        for (self.tasks.values()) |task| task.deinit(self.alloc);
+        self.tasks.deinit();
+        // As do registered tests, and docstrings.
+        self.tests.deinit(self);
+        var docs_iter = self.docs.iterator();
+        while (docs_iter.next()) |entry| {
+            self.alloc.free(entry.value_ptr.*);
+            var documented = Object{ .Word = entry.key_ptr.* };
+            self.release_heaped_object_reference(&documented);
+        }
+        self.docs.deinit();
+        self.frames.deinit();
+        self.locals.deinit(self.alloc);
+        self.conditionals.deinit(self.alloc);
+        for (self.flags.keys()) |flag| self.alloc.free(flag);
+        self.flags.deinit();
+        self.loads.deinit();
+        self.clear_load_trace();
+        self.load_trace.deinit();
+        self.resolution_attempts.deinit();
+        var bundled_iter = self.bundled.iterator();
+        while (bundled_iter.next()) |entry| {
+            self.alloc.free(entry.key_ptr.*);
+            self.alloc.free(entry.value_ptr.*);
+        }
+        self.bundled.deinit();
+        library_path.free(self.alloc, self.library_path);
+        var modules_iter = self.modules.iterator();
+        while (modules_iter.next()) |entry| {
+            self.alloc.free(entry.key_ptr.*);
+            self.alloc.free(entry.value_ptr.vocabulary);
+        }
+        self.modules.deinit();
+        for (self.search_order.items) |*entry| entry.deinit(self.alloc);
+        self.search_order.deinit();
+        var versions_iter = self.versions.iterator();
+        while (versions_iter.next()) |entry| {
+            self.alloc.free(entry.key_ptr.*);
+            entry.value_ptr.deinit(self.alloc);
+        }
+        self.versions.deinit();
+        if (self.env) |*env| env.deinit();
+        for (self.sockets.values()) |*socket| socket.deinit();
+        self.sockets.deinit();
+        for (self.line_cursors.values()) |*cursor| cursor.deinit();
+        self.line_cursors.deinit();
+
         // Now, we need to nuke all defined words, which is a bit fidgety since
         // they're referenced by their symbol identifiers which themselves may
         // need to be garbage collected in this process.
@@ -193,15 +875,37 @@ pub const Runtime = struct {
         self.dictionary.clearAndFree();
         self.dictionary.deinit();
 
+        // Any words materialized from lazy vocabularies went away with the
+        // dictionary above, so all that's left is our own bookkeeping.
+        var lazy_iter = self.lazy_vocabularies.iterator();
+        while (lazy_iter.next()) |entry| {
+            self.alloc.free(entry.key_ptr.*);
+
+            switch (entry.value_ptr.source) {
+                .Path => |path| self.alloc.free(path),
+                .Loader => {},
+            }
+        }
+        self.lazy_vocabularies.deinit();
+
+        // Drop the pool's own reference to each symbol: anything still
+        // referenced elsewhere at this point has leaked, and will be caught
+        // by the testing allocator.
         var symbol_iter = self.symbols.valueIterator();
         while (symbol_iter.next()) |entry| {
-            _ = entry.decrement_and_prune(.FreeInner, self.alloc);
+            _ = entry.*.decrement_and_prune(.FreeInnerDestroySelf, self.alloc);
         }
         self.symbols.clearAndFree();
         self.symbols.deinit();
 
         self.signatures.clearAndFree();
         self.signatures.deinit();
+
+        self.alloc.destroy(self.well_known_shapes);
+        self.observers.deinit();
+        if (self.tracer) |tracer| self.alloc.destroy(tracer);
+        // Last of all, as everything above frees through it.
+        if (self.allocation_counter) |counter| counter.destroy();
     }
 
     /// Deinitialize this Runtime, panicking if anything was left on the stack.
@@ -238,12 +942,27 @@ pub const Runtime = struct {
     /// Run a string of input in this Runtime, splitting into words along the
     /// way. Any number of WORD_SPLITTING_CHARS are used as delimiters to split
     /// the input into potentially-parseable words, which are then passed to
-    /// `dispatch_word_by_input`.
+    /// `dispatch_word_by_input`, save for the `|>` of pipelines (see
+    /// pipeline.zig), which are dropped, or a ValueError if misplaced.
     pub fn eval(self: *Self, input: []const u8) !void {
+        if (self.eval_nesting == 0) self.start_budget();
+        self.eval_nesting += 1;
+        defer self.eval_nesting -= 1;
+
+        // Conditional blocks (see conditional.zig) don't outlive the input
+        // that opened them, however it ends.
+        const conditional_depth = self.conditionals.depth();
+        defer self.conditionals.truncate(conditional_depth);
+        try self.eval_words(input);
+        if (self.conditionals.depth() != conditional_depth) return InternalError.ValueError;
+    }
+
+    fn eval_words(self: *Self, input: []const u8) !void {
         var current_word: []const u8 = undefined;
         var start_idx: usize = 0;
         var in_word = false;
         var in_string = false;
+        var pipeline = Pipeline{};
 
         chars: for (input) |chr, idx| {
             if (in_string and chr != helpers.CHAR_QUOTE_DBL) continue;
@@ -263,7 +982,8 @@ pub const Runtime = struct {
                     if (!in_word) continue :chars;
 
                     current_word = input[start_idx..idx];
-                    try self.dispatch_word_by_input(current_word);
+                    self.note_load_offset(input, start_idx);
+                    if (try pipeline.feed(current_word)) try self.dispatch_word_by_input(current_word);
                     in_word = false;
                     continue :chars;
                 }
@@ -276,17 +996,377 @@ pub const Runtime = struct {
 
             if (idx == input.len - 1) {
                 current_word = input[start_idx..];
-                try self.dispatch_word_by_input(current_word);
+                self.note_load_offset(input, start_idx);
+                if (try pipeline.feed(current_word)) try self.dispatch_word_by_input(current_word);
+            }
+        }
+
+        try pipeline.finish();
+    }
+
+    test "eval: pipelines" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        try rt.eval("3 |> dup |> 2drop");
+        try expectEqual(@as(usize, 0), rt.stack.depth());
+
+        try rt.eval("1 |> 2 |> @SWAP");
+        try expectEqual(@as(usize, 1), try rt.stack_pop_value(usize));
+        try expectEqual(@as(usize, 2), try rt.stack_pop_value(usize));
+
+        // Strings are only ever strings.
+        try rt.eval("\"|>\"");
+        var pushed = try rt.stack_pop();
+        defer rt.release_heaped_object_reference(&pushed);
+        try expectEqualStrings("|>", pushed.String.value.?);
+
+        // A pipe needs something on either side of it, within the same input,
+        // though the words before a misplaced one will already have run.
+        try expectError(InternalError.ValueError, rt.eval("|> 3"));
+        try expectError(InternalError.ValueError, rt.eval("3 |> |> dup"));
+        try expectEqual(@as(usize, 3), try rt.stack_pop_value(usize));
+        try expectError(InternalError.ValueError, rt.eval("3 |>"));
+        try expectEqual(@as(usize, 3), try rt.stack_pop_value(usize));
+    }
+
+    /// How deeply eval_source (and thus @LOAD) may nest, so that a file
+    /// loading itself is a LimitExceeded rather than a Zig stack overflow.
+    // TODO: configurable in build.zig
+    pub const MAX_LOAD_DEPTH = 64;
+
+    /// A source being evaluated by eval_source, and the offset into it of the
+    /// word eval is on.
+    pub const Load = struct {
+        path: []const u8,
+        source: []const u8,
+        offset: usize,
+    };
+
+    /// A place in a source, much as a Lexer Token would give it. The path is
+    /// owned by the Runtime.
+    pub const SourcePosition = struct {
+        path: []u8,
+        line: usize,
+        column: usize,
+    };
+
+    /// Run `source` (as with eval), noting that it came from `path` so that,
+    /// should it fail, load_trace says where: first the word that failed
+    /// (or called whatever did), then the word that loaded its source, and so
+    /// on outwards through any nested eval_sources. The outermost
+    /// eval_source clears load_trace before it starts. Both slices are the
+    /// caller's, and need only outlive the call.
+    pub fn eval_source(self: *Self, path: []const u8, source: []const u8) !void {
+        if (self.loads.items.len >= MAX_LOAD_DEPTH) return InternalError.LimitExceeded;
+        if (self.loads.items.len == 0) self.clear_load_trace();
+
+        // Byte order marks and CRLFs (see source_encoding.zig) are gone
+        // before anything counts positions, so that load_trace's match what
+        // an editor shows.
+        const normalized = try source_encoding.normalize(self.alloc, source);
+        defer if (normalized) |owned| self.alloc.free(owned);
+        const text = normalized orelse source;
+
+        // A hashbang means nothing to gale (see hashbang.zig), but blanking
+        // it out, rather than slicing it off, keeps load_trace's positions
+        // right.
+        const hashbang_len = hashbang.line_len(text);
+        const blanked: ?[]u8 = if (hashbang_len > 0) try self.alloc.dupe(u8, text) else null;
+        defer if (blanked) |owned| self.alloc.free(owned);
+        if (blanked) |owned| std.mem.set(u8, owned[0..hashbang_len], helpers.CHAR_SPACE);
+        const body = blanked orelse text;
+
+        try self.loads.append(.{ .path = path, .source = body, .offset = 0 });
+        defer _ = self.loads.pop();
+
+        self.eval(body) catch |err| {
+            const load = self.loads.items[self.loads.items.len - 1];
+            // Running out of memory here leaves the trace short a source,
+            // which is no reason to lose the original error.
+            self.trace_load_failure(load) catch {};
+            return err;
+        };
+        self.notify(.{ .Loaded = path });
+    }
+
+    /// Have @LOAD and @IMPORT find `source` at `path` (exactly as they're
+    /// given it, library path or not), instead of looking on disk. Bundling
+    /// the same path again replaces what was there. Both slices are copied.
+    pub fn add_bundled_file(self: *Self, path: []const u8, source: []const u8) !void {
+        const owned_source = try self.alloc.dupe(u8, source);
+        errdefer self.alloc.free(owned_source);
+
+        const entry = try self.bundled.getOrPut(path);
+        if (entry.found_existing) {
+            self.alloc.free(entry.value_ptr.*);
+        } else {
+            entry.key_ptr.* = self.alloc.dupe(u8, path) catch |err| {
+                self.bundled.removeByPtr(entry.key_ptr);
+                return err;
+            };
+        }
+        entry.value_ptr.* = owned_source;
+    }
+
+    /// Bundle everything in `text`, a bundle (see bundle.zig), and then run
+    /// its entry script with eval_source.
+    pub fn eval_bundle(self: *Self, text: []const u8) !void {
+        var parsed = try bundle.parse(self.alloc, text);
+        defer parsed.deinit();
+
+        for (parsed.files) |file| try self.add_bundled_file(file.path, file.source);
+        try self.eval_source(parsed.entry.path, parsed.entry.source);
+    }
+
+    test "eval_bundle" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        var text = std.ArrayList(u8).init(testAllocator);
+        defer text.deinit();
+        const files = [_]bundle.File{.{ .path = "numbers", .source = "2 @LIT :two @DEFINE" }};
+        try bundle.write(text.writer(), &files, .{ .path = "main.gale", .source = "\"numbers\" @IMPORT two" });
+
+        try rt.eval_bundle(text.items);
+        try expectEqual(@as(usize, 2), try rt.stack_pop_value(usize));
+
+        try expectError(InternalError.ValueError, rt.eval_bundle("2"));
+    }
+
+    fn trace_load_failure(self: *Self, load: Load) !void {
+        const path = try self.alloc.dupe(u8, load.path);
+        errdefer self.alloc.free(path);
+
+        const before = load.source[0..load.offset];
+        const line_start = if (std.mem.lastIndexOfScalar(u8, before, helpers.CHAR_NEWLINE)) |idx| idx + 1 else 0;
+        try self.load_trace.append(.{
+            .path = path,
+            .line = std.mem.count(u8, before, "\n") + 1,
+            .column = load.offset - line_start + 1,
+        });
+    }
+
+    fn note_load_offset(self: *Self, input: []const u8, offset: usize) void {
+        if (self.loads.items.len == 0) return;
+
+        // Only the source's own eval counts: anything it evals in turn (say,
+        // a String) isn't part of the source.
+        const load = &self.loads.items[self.loads.items.len - 1];
+        if (input.ptr == load.source.ptr) load.offset = offset;
+    }
+
+    /// Forget load_trace, and the rest of what the last failure left to
+    /// explain it (resolution_attempts and version_conflict) with it.
+    pub fn clear_load_trace(self: *Self) void {
+        for (self.load_trace.items) |position| self.alloc.free(position.path);
+        self.load_trace.clearRetainingCapacity();
+        for (self.resolution_attempts.items) |path| self.alloc.free(path);
+        self.resolution_attempts.clearRetainingCapacity();
+        if (self.version_conflict) |conflict| self.alloc.free(conflict);
+        self.version_conflict = null;
+    }
+
+    /// Where the file @LOAD or @IMPORT was given as `operand` lives. Bundled
+    /// paths (see add_bundled_file) are taken as-is, as are explicit
+    /// (see library_path.is_explicit), and everything when there's no
+    /// library_path. Otherwise, this is the first of
+    /// `operand`'s candidates (see library_path.candidates) to exist within
+    /// each library_path directory in turn, failing with FileNotFound (and
+    /// everything tried in resolution_attempts) when none do. Either way,
+    /// the caller owns the result.
+    pub fn resolve_source_path(self: *Self, operand: []const u8) ![]u8 {
+        for (self.resolution_attempts.items) |path| self.alloc.free(path);
+        self.resolution_attempts.clearRetainingCapacity();
+
+        if (self.bundled.contains(operand) or self.library_path.len == 0 or library_path.is_explicit(operand)) {
+            return try self.alloc.dupe(u8, operand);
+        }
+        if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+        for (self.library_path) |dir| {
+            const paths = try library_path.candidates(self.alloc, dir, operand);
+            defer self.alloc.free(paths);
+
+            for (paths) |path, idx| {
+                if (std.fs.cwd().access(path, .{})) |_| {
+                    for (paths[idx + 1 ..]) |rest| self.alloc.free(rest);
+                    return path;
+                } else |_| {}
+
+                self.resolution_attempts.append(path) catch |err| {
+                    for (paths[idx..]) |rest| self.alloc.free(rest);
+                    return err;
+                };
             }
         }
+
+        return error.FileNotFound;
+    }
+
+    test "resolve_source_path" {
+        var tmp = std.testing.tmpDir(.{});
+        defer tmp.cleanup();
+        const root = try tmp.dir.realpathAlloc(testAllocator, ".");
+        defer testAllocator.free(root);
+        const empty = try std.fs.path.join(testAllocator, &.{ root, "empty" });
+        defer testAllocator.free(empty);
+        try tmp.dir.makeDir("empty");
+        try tmp.dir.writeFile("strings.gale", "");
+
+        var rt = try Self.init_with_options(testAllocator, .{
+            .library_path = &.{ empty, root },
+        });
+        defer rt.deinit_guard_for_empty_stack();
+
+        const found = try rt.resolve_source_path("strings");
+        defer testAllocator.free(found);
+        const expected = try std.fs.path.join(testAllocator, &.{ root, "strings.gale" });
+        defer testAllocator.free(expected);
+        try expectEqualStrings(expected, found);
+
+        // Explicit paths are never searched for, found or not.
+        const explicit = try rt.resolve_source_path("./strings");
+        defer testAllocator.free(explicit);
+        try expectEqualStrings("./strings", explicit);
+
+        try expectError(error.FileNotFound, rt.resolve_source_path("nope"));
+        try expectEqual(@as(usize, 4), rt.resolution_attempts.items.len);
+        try expect(std.mem.startsWith(u8, rt.resolution_attempts.items[0], empty));
+    }
+
+    test "eval_source" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        try expectError(InternalError.UnknownWord, rt.eval_source("a.gale", "1 @DROP\n  2 @NOPE"));
+        try expectEqual(@as(usize, 1), rt.load_trace.items.len);
+        try expectEqualStrings("a.gale", rt.load_trace.items[0].path);
+        try expectEqual(@as(usize, 2), rt.load_trace.items[0].line);
+        try expectEqual(@as(usize, 5), rt.load_trace.items[0].column);
+        try expectEqual(@as(usize, 2), try rt.stack_pop_value(usize));
+
+        try rt.eval_source("b.gale", "3 @DROP");
+        try expectEqual(@as(usize, 0), rt.load_trace.items.len);
+
+        try expectError(InternalError.UnknownWord, rt.eval_source("c.gale", "#!/usr/bin/env gale\n4 @DROP @NOPE"));
+        try expectEqual(@as(usize, 2), rt.load_trace.items[0].line);
+        try expectEqual(@as(usize, 9), rt.load_trace.items[0].column);
+
+        try expectError(InternalError.UnknownWord, rt.eval_source("d.gale", "\xEF\xBB\xBF5 @DROP\r\n@NOPE\r\n"));
+        try expectEqual(@as(usize, 2), rt.load_trace.items[0].line);
+        try expectEqual(@as(usize, 1), rt.load_trace.items[0].column);
+        try rt.eval_source("e.gale", "\xEF\xBB\xBF\"a\r\nb\" @DROP\r\n");
+
+        try expectError(InternalError.UnsupportedEncoding, rt.eval_source("f.gale", "\xFF\xFE6\x00"));
+    }
+
+    /// Run `input` (as with eval), and then pop and return everything it left
+    /// on the stack beyond what was already there, ordered bottom to top.
+    /// This is the main way for hosts using gale as a scripting or
+    /// configuration language to get answers back. The caller owns the
+    /// returned Objects (and slice): see `release_objects`.
+    ///
+    /// If `input` consumes more of the stack than it leaves behind, nothing is
+    /// returned. If it fails, the stack is left as-is, as with eval.
+    pub fn eval_collect(self: *Self, input: []const u8) ![]Object {
+        const depth_before = self.stack.depth();
+        try self.eval(input);
+        const depth_after = self.stack.depth();
+
+        const produced = if (depth_after > depth_before) depth_after - depth_before else 0;
+        const results = try self.alloc.alloc(Object, produced);
+        errdefer self.alloc.free(results);
+
+        // Popping can allocate (see Stack.ensure_exclusive), so fail partway
+        // through, by which point everything above idx is ours.
+        var idx = produced;
+        errdefer for (results[idx + 1 ..]) |*result| self.release_heaped_object_reference(result);
+        while (idx > 0) {
+            idx -= 1;
+            results[idx] = try self.stack_pop();
+        }
+
+        return results;
+    }
+
+    /// As eval_collect, but insist that exactly one Object of kind `kind` is
+    /// produced, failing with InternalError.ValueError (wrong count) or
+    /// InternalError.TypeError (wrong kind) otherwise. Anything produced is
+    /// released on failure. The caller owns the returned Object.
+    pub fn eval_expect_one(self: *Self, input: []const u8, kind: std.meta.Tag(Object)) !Object {
+        const results = try self.eval_collect(input);
+        defer self.alloc.free(results);
+
+        if (results.len != 1 or std.meta.activeTag(results[0]) != kind) {
+            for (results) |*result| self.release_heaped_object_reference(result);
+            return if (results.len != 1) InternalError.ValueError else InternalError.TypeError;
+        }
+
+        return results[0];
+    }
+
+    /// As eval_expect_one, but convert the result to a Zig value of type T
+    /// (see conversion.zig for what's supported and how). The result is owned
+    /// by the caller: see conversion.free_value.
+    pub fn eval_as(self: *Self, comptime T: type, input: []const u8) !T {
+        const results = try self.eval_collect(input);
+        defer self.release_objects(results);
+
+        if (results.len != 1) return InternalError.ValueError;
+
+        return try conversion.from_object(T, self.alloc, results[0]);
+    }
+
+    /// Release every Object in `objects` (say, from eval_collect), and then
+    /// the slice itself.
+    pub fn release_objects(self: *Self, objects: []Object) void {
+        for (objects) |*obj| self.release_heaped_object_reference(obj);
+        self.alloc.free(objects);
+    }
+
+    test "eval_collect and eval_expect_one" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        try rt.eval("1");
+        const results = try rt.eval_collect("2 \"three\"");
+        defer rt.release_objects(results);
+        try expectEqual(@as(usize, 2), results.len);
+        try expectEqual(@as(usize, 2), results[0].UnsignedInt);
+        try expectEqualStrings("three", results[1].String.value.?);
+
+        // The pre-existing 1 is neither returned nor disturbed.
+        try expectEqual(@as(usize, 1), (try rt.stack_pop()).UnsignedInt);
+
+        try expectEqual(@as(isize, -4), (try rt.eval_expect_one("-4", .SignedInt)).SignedInt);
+        try expectError(InternalError.TypeError, rt.eval_expect_one("4", .SignedInt));
+        try expectError(InternalError.ValueError, rt.eval_expect_one("4 5", .UnsignedInt));
+
+        try expectEqual(@as(u16, 80), try rt.eval_as(u16, "80"));
+        try expectError(InternalError.ValueError, rt.eval_as(u8, "256"));
     }
 
     /// Pass a single pre-whitespace-trimmed word to ParsedWord.from_input and
     /// either place the literal onto the stack or lookup and run the word (if
     /// it exists), as appropriate.
     pub fn dispatch_word_by_input(self: *Self, input: []const u8) !void {
+        if (try conditional.Directive.parse(input)) |directive| return self.apply_directive(directive);
+        if (!self.conditionals.active()) return;
+
         switch (try ParsedWord.from_input(input)) {
-            .Simple, .Ref => return InternalError.Unimplemented,
+            .Simple => |simple| {
+                // Stashing and hoisting (see ParsedWord) aren't supported yet.
+                if (simple.semantics.stash_before_lookup or simple.semantics.hoist_after_result) {
+                    return InternalError.Unimplemented;
+                }
+
+                const word = (try self.find_word(simple.name)) orelse return InternalError.UnknownWord;
+                try self.run_word(word);
+            },
+            // References are late-bound: the word is looked up when (and
+            // every time, modulo caching) it's run, not when it's pushed.
+            .Ref => |name| try self.stack_push_raw_word(try self.word_from_call_site(name)),
             .String => |str| {
                 const interned_str = try self.get_or_put_string(str);
                 try self.stack_push_string(interned_str.value_ptr);
@@ -299,180 +1379,1946 @@ pub const Runtime = struct {
             .SignedInt => |num| try self.stack_push_sint(num),
             .UnsignedInt => |num| try self.stack_push_uint(num),
         }
-    }
 
-    pub fn run_word(self: *Self, word: *Types.HeapedWord) !void {
-        // TODO: Stack compatibility check against the WordSignature.
+        try self.enforce_stack_depth_limit();
+    }
 
-        if (word.value) |iword| {
-            switch (iword.impl) {
-                .Compound => return InternalError.Unimplemented, // TODO
-                .HeapLit => |lit| self.stack = try self.stack.do_push(lit.*),
-                .Primitive => |impl| try impl(self),
-            }
-        } else {
-            // TODO: determine if there's a better/more concise error to pass
-            // here, perhaps by somehow triggering this and seeing what states
-            // can even leave us here
-            return InternalError.EmptyWord;
+    fn apply_directive(self: *Self, directive: conditional.Directive) !void {
+        switch (directive) {
+            .If, .Unless => |condition| {
+                // Conditions within skipped blocks are never checked, so
+                // that, say, #if-word: doesn't load vocabularies for nothing.
+                const holds = self.conditionals.active() and try self.condition_holds(condition);
+                try self.conditionals.open(self.alloc, if (directive == .If) holds else !holds);
+            },
+            .Else => try self.conditionals.flip(),
+            .End => try self.conditionals.close(),
         }
     }
 
-    pub fn get_or_put_string(self: *Self, str: []const u8) !GetOrPutResult(Types.HeapedString) {
-        // TODO: intern this similarly to symbols
-        const stored = try self.alloc.alloc(u8, str.len);
-        std.mem.copy(u8, stored[0..], str);
-        const heaped = try self.alloc.create(Types.HeapedString);
-        heaped.* = Types.HeapedString.init(stored);
-        return .{
-            .value_ptr = heaped,
-            .found_existing = false,
+    fn condition_holds(self: *Self, condition: conditional.Condition) !bool {
+        return switch (condition) {
+            .Target => |target| target == conditional.Target.current(),
+            .Flag => |name| self.flags.contains(name),
+            .Word => |name| (try self.find_word(name)) != null,
         };
     }
 
-    /// Retrieve the previously-interned Symbol's Rc
-    pub fn get_or_put_symbol(self: *Self, sym: []const u8) !GetOrPutResult(Types.HeapedSymbol) {
-        var entry = try self.symbols.getOrPut(sym);
-        if (!entry.found_existing) {
-            const stored = try self.alloc.alloc(u8, sym.len);
-            std.mem.copy(u8, stored[0..], sym);
-            entry.value_ptr.* = Types.HeapedSymbol.init(stored);
-            // TODO: uncomment this to fix known memory bugs found when
-            // implementing test_protolang 19 Jan 2023
-            // try entry.value_ptr.increment();
-        }
-        return .{
-            .value_ptr = entry.value_ptr,
-            .found_existing = entry.found_existing,
+    /// Define the flag `name`, for `#if-flag:` (see conditional.zig).
+    pub fn define_flag(self: *Self, name: []const u8) !void {
+        const result = try self.flags.getOrPut(name);
+        if (result.found_existing) return;
+        result.key_ptr.* = self.alloc.dupe(u8, name) catch |err| {
+            _ = self.flags.pop();
+            return err;
         };
     }
 
-    /// Take a WordSignature by value and, if it is new to this Runtime, store
-    /// it. Return a GetOrPutResult which will contain a pointer to the stored
-    /// WordSignature. Each unique signature will be stored a maximum of one
-    /// time in this Runtime.
-    ///
-    /// Can fail by way of allocation errors only.
-    pub fn get_or_put_word_signature(self: *Self, sig: WordSignature) !GetOrPutResult(WordSignature) {
-        var entry = try self.signatures.getOrPut(sig);
-        return .{
-            .value_ptr = entry.key_ptr,
-            .found_existing = entry.found_existing,
-        };
-    }
+    test "conditional directives" {
+        var rt = try Self.init_with_options(testAllocator, .{ .flags = &.{"fancy"} });
+        defer rt.deinit_guard_for_empty_stack();
 
-    pub fn get_well_known_shape(self: *Self, req: WellKnownShape) *Shape {
-        return &self.well_known_shapes[@enumToInt(req)];
-    }
+        try rt.eval("#if-flag:fancy 1 #else 2 #end");
+        try expectEqual(@as(usize, 1), try rt.stack_pop_value(usize));
 
-    pub fn get_well_known_word_signature(self: *Self, req: WellKnownSignature) *WordSignature {
-        return self.well_known_signatures[@enumToInt(req)];
+        try rt.eval("#unless-target:native 1 #else 2 #end");
+        try expectEqual(@as(usize, 2), try rt.stack_pop_value(usize));
+
+        // Skipped words are never looked up, however nested.
+        try rt.eval("#if-word:@NOPE @NOPE #if-flag:fancy @NOPE #end #else 3 #end");
+        try expectEqual(@as(usize, 3), try rt.stack_pop_value(usize));
+
+        try rt.define_flag("plain");
+        try rt.eval("#if-word:@DUP #if-flag:plain 4 #end #end");
+        try expectEqual(@as(usize, 4), try rt.stack_pop_value(usize));
+
+        // Blocks are closed within the input which opened them, or not at
+        // all.
+        try expectError(InternalError.ValueError, rt.eval("#if-flag:fancy 5"));
+        try expectEqual(@as(usize, 5), try rt.stack_pop_value(usize));
+        try expectError(InternalError.ValueError, rt.eval("#end"));
+        try expectError(InternalError.ValueError, rt.eval("#if-target:amiga #end"));
+        try expectEqual(@as(usize, 0), rt.conditionals.depth());
     }
 
-    /// Takes a bare Word struct, wraps it in a refcounter, and returns a
-    /// pointer to the resultant memory. Does not wrap it in an Object for
-    /// direct placement on a Stack.
-    fn send_word_to_heap(self: *Self, bare: Word) !*Types.HeapedWord {
-        const heap_space = try self.alloc.create(Types.HeapedWord);
-        heap_space.* = Types.HeapedWord.init(bare);
-        return heap_space;
+    /// Fail with InternalError.CapabilityDenied unless this Runtime has been
+    /// granted `cap`. Every word touching the outside world must call this
+    /// before doing anything observable.
+    pub fn require_capability(self: *Self, cap: Capability) InternalError!void {
+        if (!self.capabilities.contains(cap)) return InternalError.CapabilityDenied;
     }
 
-    /// Heap-wraps a compound word definition.
-    pub fn word_from_compound_impl(
-        self: *Self,
-        impl: CompoundImplementation,
-        sig: ?Word.SignatureState,
-    ) !*Types.HeapedWord {
-        return try self.send_word_to_heap(Word.new_compound_untagged(impl, sig));
+    /// As require_capability(.Read), but for @LOAD and @IMPORT's `operand`,
+    /// which needs no capability at all if it's bundled (see
+    /// add_bundled_file): nothing need be read to get at it.
+    pub fn require_source_access(self: *Self, operand: []const u8) InternalError!void {
+        if (!self.bundled.contains(operand)) try self.require_capability(.Read);
     }
 
-    /// Heap-wraps a heaplit word definition. How meta.
-    pub fn word_from_heaplit_impl(
-        self: *Self,
-        impl: HeapLitImplementation,
-        sig: ?Word.SignatureState,
-    ) !*Types.HeapedWord {
-        return try self.send_word_to_heap(Word.new_heaplit_untagged(impl, sig));
+    test "require_capability" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit();
+        try expectError(InternalError.CapabilityDenied, rt.require_capability(.Read));
+
+        var granted = capabilities.NONE;
+        granted.insert(.Read);
+        var trusted = try Self.init_with_options(testAllocator, .{ .capabilities = granted });
+        defer trusted.deinit();
+        try trusted.require_capability(.Read);
+        try expectError(InternalError.CapabilityDenied, trusted.require_capability(.Net));
     }
 
-    /// Heap-wraps a primitive word definition.
-    pub fn word_from_primitive_impl(
-        self: *Self,
-        impl: PrimitiveImplementation,
-        sig: ?Word.SignatureState,
-    ) !*Types.HeapedWord {
-        return try self.send_word_to_heap(Word.new_primitive_untagged(impl, sig));
+    /// This Runtime's copy of the environment, snapshotted from the process's
+    /// the first time it's asked for. Callers are responsible for checking
+    /// the Env capability.
+    pub fn env_map(self: *Self) !*std.process.EnvMap {
+        if (self.env == null) self.env = try self.snapshot_env();
+        return &self.env.?;
     }
 
-    // Right now, Zig doesn't have a way to narrow `targets` type from anytype,
-    // which is super disappointing, but being brainstormed on:
-    // https://github.com/ziglang/zig/issues/5404
-    pub fn define_word_va(self: *Self, identifier: *Types.HeapedSymbol, targets: anytype) !void {
-        try identifier.increment();
-        var dict_entry = try self.dictionary.getOrPut(identifier);
-        if (!dict_entry.found_existing) {
-            dict_entry.value_ptr.* = WordList.init(self.alloc);
+    fn snapshot_env(self: *Self) !std.process.EnvMap {
+        if (try self.recording.replayed(.Env)) |vars| {
+            var env = std.process.EnvMap.init(self.alloc);
+            errdefer env.deinit();
+            for (vars) |env_var| try env.put(env_var.name, env_var.value);
+            return env;
         }
 
-        const compound_storage = try self.alloc.alloc(*Types.HeapedWord, targets.len);
-        inline for (targets) |target, idx| compound_storage[idx] = target;
+        var env = try std.process.getEnvMap(self.alloc);
+        errdefer env.deinit();
 
-        // TODO WARNING: For now, this always makes invalid words (without a
-        // signature). Need to figure out the correct way to plumb signatures
-        // here given that the runtime will be using a builder pattern to
-        // attach them.
-        var heap_for_word = try self.word_from_compound_impl(compound_storage, null);
+        if (self.recording == .Record) {
+            var vars = std.ArrayList(recording.EnvVar).init(self.alloc);
+            defer vars.deinit();
 
-        // TODO should this increment actually be stashed away in a dictionary
-        // helper method somewhere? should there be a
-        // Runtime.unstacked_word_from_compound_impl that handles the increment
-        // for us (using Rc.init_referenced) since we can't rely on
-        // Stack.do_push's implicit increment?
-        try heap_for_word.increment();
+            var iter = env.iterator();
+            while (iter.next()) |entry| try vars.append(.{ .name = entry.key_ptr.*, .value = entry.value_ptr.* });
+            try self.recording.record(.{ .Env = vars.items });
+        }
 
-        try dict_entry.value_ptr.append(heap_for_word);
+        return env;
     }
 
-    pub fn priv_space_set_byte(self: *Self, member: u8, value: u8) InternalError!void {
-        return switch (member) {
-            0 => self.private_space.interpreter_mode = @intToEnum(InterpreterMode, value),
-            else => InternalError.ValueError,
-        };
+    /// Nanoseconds since this Runtime was created, by the monotonic clock
+    /// (or as recorded: see Options.recording). Unimplemented on systems
+    /// without such a clock.
+    pub fn read_monotonic_clock(self: *Self) !u64 {
+        if (try self.recording.replayed(.Monotonic)) |ns| return ns;
+        if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+        const epoch = self.clock_epoch orelse return InternalError.Unimplemented;
+        const now = std.time.Instant.now() catch return InternalError.Unimplemented;
+        const ns = now.since(epoch);
+        try self.recording.record(.{ .Monotonic = ns });
+        return ns;
     }
 
-    test "priv_space_set_byte" {
-        var rt = try Self.init(testAllocator);
-        defer rt.deinit();
-        try expectEqual(@as(u8, 0), @enumToInt(rt.private_space.interpreter_mode));
-        try rt.priv_space_set_byte(0, 1);
-        try expectEqual(@as(u8, 1), @enumToInt(rt.private_space.interpreter_mode));
+    /// Milliseconds since 1970-01-01T00:00:00Z, by the wall clock (or as
+    /// recorded: see Options.recording). Unimplemented on systems without
+    /// one.
+    pub fn read_wall_clock_ms(self: *Self) !i64 {
+        if (try self.recording.replayed(.WallClockMs)) |ms| return ms;
+        if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+        const ms = std.time.milliTimestamp();
+        try self.recording.record(.{ .WallClockMs = ms });
+        return ms;
     }
 
-    pub fn stack_peek(self: *Self) !*Object {
-        return self.stack.do_peek();
+    fn start_budget(self: *Self) void {
+        self.words_executed = 0;
+        // Freestanding targets have no clock to ask, so wall-clock limits
+        // are simply not enforced there.
+        self.budget_started = if (comptime !helpers.HOSTED or self.limits.max_wall_clock_ns == null)
+            null
+        else
+            // We'd rather run unbounded than not at all on systems without a
+            // usable monotonic clock.
+            std.time.Instant.now() catch null;
     }
 
-    pub fn stack_peek_pair(self: *Self) !Types.PeekPair {
-        return self.stack.do_peek_pair();
+    /// Ask whatever is running to stop, at the start of the next word, with
+    /// an InternalError.Interrupted, which unwinds just like any other error.
+    /// Words already running (say, a primitive blocked on I/O) aren't
+    /// interrupted. This is safe to call from other threads and from signal
+    /// handlers, and is a no-op if an interrupt is already pending.
+    pub fn interrupt(self: *Self) void {
+        self.interrupt_requested.store(true, .Release);
     }
 
-    pub fn stack_peek_trio(self: *Self) !Types.PeekTrio {
-        return self.stack.do_peek_trio();
+    /// Whether an `interrupt` has yet to be noticed.
+    pub fn interrupt_pending(self: *const Self) bool {
+        return self.interrupt_requested.load(.Acquire);
     }
 
-    /// Remove the top item from the stack and return it. If there are no
-    /// contents remaining, a StackManipulationError.Underflow is raised.
-    pub fn stack_pop(self: *Self) !Object {
-        const popped = try self.stack.do_pop();
-        self.stack = popped.now_top_stack;
-        return popped.item;
+    /// Forget about any `interrupt` which has yet to be noticed, which hosts
+    /// should do once whatever they wanted interrupted is over, lest it
+    /// interrupt something else instead.
+    pub fn clear_interrupt(self: *Self) void {
+        self.interrupt_requested.store(false, .Release);
     }
 
-    /// Remove the top item from the stack, move it to the heap, and return the
-    /// new address to the data. Use `release_heaped_object_reference` to later
-    /// drop a reference to this data (and, if applicable, collect the
-    /// garbage). If there are no contents remaining, a
+    /// What this Runtime has done since it was made, or last reset_stats.
+    pub fn stats(self: *const Self) Stats {
+        var result = self.counters;
+        if (self.allocation_counter) |counter| {
+            result.allocations = counter.allocations.load(.Monotonic);
+            result.allocated_bytes = counter.allocated_bytes.load(.Monotonic);
+        }
+        return result;
+    }
+
+    /// Start stats counting from zero again.
+    pub fn reset_stats(self: *Self) void {
+        self.counters = .{};
+        if (self.allocation_counter) |counter| counter.reset();
+    }
+
+    test "stats" {
+        var rt = try Self.init_with_options(testAllocator, .{ .count_allocations = true });
+        defer rt.deinit_guard_for_empty_stack();
+        try rt.eval("&@DUP :twin @DEFINE");
+
+        rt.reset_stats();
+        try rt.eval("1 twin @DROP @DROP");
+        const counted = rt.stats();
+        try expectEqual(@as(usize, 2), counted.pushes);
+        try expectEqual(@as(usize, 2), counted.pops);
+        try expect(counted.words_executed >= 3);
+        try expectEqual(@as(usize, 1), counted.call_site_misses);
+
+        try rt.eval("2 twin @DROP @DROP");
+        try expectEqual(@as(usize, 1), rt.stats().call_site_hits);
+
+        try rt.eval("\"allocates\" @DROP");
+        try expect(rt.stats().allocations > counted.allocations);
+
+        rt.reset_stats();
+        try expectEqual(@as(usize, 0), rt.stats().allocations);
+        try expectEqual(@as(usize, 0), rt.stats().pushes);
+    }
+
+    fn enforce_limits(self: *Self) InternalError!void {
+        if (self.interrupt_requested.swap(false, .AcqRel)) return InternalError.Interrupted;
+
+        self.words_executed += 1;
+        self.counters.words_executed += 1;
+
+        if (self.limits.max_words_executed) |max| {
+            if (self.words_executed > max) return InternalError.LimitExceeded;
+        }
+
+        try self.enforce_stack_depth_limit();
+
+        if (comptime !helpers.HOSTED) return;
+
+        if (self.limits.max_wall_clock_ns) |max| {
+            if (self.budget_started) |started| {
+                const now = std.time.Instant.now() catch return;
+                if (now.since(started) > max) return InternalError.LimitExceeded;
+            }
+        }
+    }
+
+    fn enforce_stack_depth_limit(self: *Self) InternalError!void {
+        if (self.limits.max_stack_depth) |max| {
+            if (self.stack.depth() > max) return InternalError.LimitExceeded;
+        }
+    }
+
+    test "interrupt" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        rt.interrupt();
+        try expect(rt.interrupt_pending());
+        // Literals aren't words, and thus aren't interrupted...
+        try rt.eval("1");
+        // ...but words are, and only the once.
+        try expectError(InternalError.Interrupted, rt.eval("@DROP"));
+        try expect(!rt.interrupt_pending());
+        try rt.eval("@DROP");
+    }
+
+    test "limits" {
+        var rt = try Self.init_with_options(testAllocator, .{ .limits = .{
+            .max_words_executed = 2,
+            .max_stack_depth = 3,
+        } });
+        defer rt.deinit();
+
+        // Budgets are per top-level eval...
+        try rt.eval("1 @DUP @DROP");
+        try rt.eval("@DUP @DROP");
+        try expectError(InternalError.LimitExceeded, rt.eval("@DUP @DROP @DUP"));
+        // ...as is stack depth, more or less: this is four Objects deep.
+        try expectError(InternalError.LimitExceeded, rt.eval("2 3 4"));
+    }
+
+    /// Run `word` to completion (or failure) on the current Stack. This is
+    /// re-entrant, and so primitives and host closures are free to call it.
+    // Explicitly anyerror, as inferred error sets can't be recursive, and
+    // words are very much recursive.
+    pub fn run_word(self: *Self, word: *Types.HeapedWord) anyerror!void {
+        // Anything at or below this depth belongs to whoever called us.
+        const floor = self.frames.items.len;
+
+        const was_suspendable = self.suspendable;
+        self.suspendable = false;
+        defer self.suspendable = was_suspendable;
+
+        try self.enter_word(word);
+
+        self.run_frames_until(floor, false) catch |err| {
+            self.unwind_frames(floor, err);
+            return err;
+        };
+    }
+
+    /// Run `word` (see run_word) on a fresh Stack holding only `input` (to
+    /// which a new reference is taken), and return the one Object it must
+    /// leave behind, which the caller then owns. Leaving anything other than
+    /// exactly one Object is an InternalError.ValueError. Either way, our own
+    /// Stack is left untouched.
+    pub fn run_word_isolated(self: *Self, word: *Types.HeapedWord, input: Object) !Object {
+        const outer = self.stack;
+        self.stack = try Stack.init(self.alloc, null);
+        defer {
+            self.stack.release();
+            self.stack = outer;
+        }
+
+        try self.stack_push_object(input);
+        try self.run_word(word);
+
+        if (self.stack.depth() != 1) return InternalError.ValueError;
+        return try self.stack_pop();
+    }
+
+    fn enter_word(self: *Self, word: *Types.HeapedWord) !void {
+        try self.enforce_limits();
+        try self.frames.append(.{ .word = word });
+        self.notify(.{ .WordStart = word });
+    }
+
+    /// Enter `word` from the very end of the innermost Frame's word, which
+    /// thus has nothing left to do but wait on it. If `word` will itself be
+    /// left pending (a Compound or CallSite), its Frame takes the innermost
+    /// one's place rather than stacking atop it, so that words ending by
+    /// calling themselves (directly or in turn) loop in constant space
+    /// rather than growing the Frames by one per pass. The replaced word is
+    /// left (and observers told so) just before `word` starts. Everything
+    /// else runs in a single step, and gains nothing from this.
+    fn enter_word_from_tail(self: *Self, word: *Types.HeapedWord) !void {
+        const pending = if (word.value) |inner| switch (inner.impl) {
+            .CallSite => true,
+            .Compound => |compound| compound.len > 0,
+            else => false,
+        } else false;
+
+        if (pending) self.leave_word();
+        try self.enter_word(word);
+    }
+
+    test "tail calls" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        try rt.eval("&@DROP &drain @COMPOSE :drain @DEFINE");
+        try rt.eval("&@DROP &pong @COMPOSE :ping @DEFINE");
+        try rt.eval("&@DROP &ping @COMPOSE :pong @DEFINE");
+
+        for ([_][]const u8{ "drain", "ping" }) |name| {
+            var idx: usize = 0;
+            while (idx < 100) : (idx += 1) try rt.stack_push_uint(idx);
+
+            // Each word drops, then calls the next, until there's nothing
+            // left to drop, without the Frames ever getting any deeper.
+            try rt.begin((try rt.find_word(name)).?);
+            var deepest: usize = 0;
+            while (true) {
+                _ = rt.step() catch |err| {
+                    try expectEqual(@as(anyerror, StackManipulationError.Underflow), err);
+                    break;
+                };
+                deepest = std.math.max(deepest, rt.frames.items.len);
+            }
+
+            try expectEqual(@as(usize, 0), rt.stack.depth());
+            try expect(deepest <= 3);
+        }
+    }
+
+    fn leave_word(self: *Self) void {
+        const frame = self.frames.pop();
+        self.notify(.{ .WordEnd = frame.word });
+    }
+
+    /// Throw away every Frame above `floor`, telling observers about each,
+    /// innermost first, just as if the error had bubbled up the Zig stack.
+    fn unwind_frames(self: *Self, floor: usize, err: anyerror) void {
+        while (self.frames.items.len > floor) {
+            _ = self.frames.pop();
+            self.notify(.{ .Error = err });
+        }
+    }
+
+    /// Step until only `floor` Frames are left, or, if `honor_yields` is
+    /// set, until a Task asks to yield.
+    fn run_frames_until(self: *Self, floor: usize, honor_yields: bool) anyerror!void {
+        while (self.frames.items.len > floor) {
+            if (honor_yields and self.yield_requested) return;
+            _ = try self.advance();
+        }
+    }
+
+    /// What a single `step` did, and to which word.
+    pub const Step = union(enum) {
+        /// Nothing was pending, and so nothing happened.
+        Idle,
+        /// Started running a word (an inner word of a Compound, or the word a
+        /// CallSite refers to), which is now the innermost pending one.
+        Entered: *Types.HeapedWord,
+        /// Ran a word which only ever takes a single step (a primitive, a
+        /// host closure, or a literal) from start to finish.
+        Ran: *Types.HeapedWord,
+        /// Finished a word which was Entered (or begun) earlier.
+        Left: *Types.HeapedWord,
+        /// The innermost word can't make progress yet (see yield_to_others),
+        /// and will be run again next time.
+        Blocked: *Types.HeapedWord,
+    };
+
+    /// Queue up `word` to be run by `step`, without running any of it yet.
+    /// Words begun while others are pending run (to completion) before those
+    /// others carry on, just as if they'd been called by them.
+    pub fn begin(self: *Self, word: *Types.HeapedWord) !void {
+        try self.enter_word(word);
+    }
+
+    /// Run exactly one step of whatever is pending (see begin), for the
+    /// benefit of debuggers, visualizers, and test harnesses that want to
+    /// watch execution one tick at a time. `frames` shows what remains. On
+    /// failure, everything pending is unwound, just as run_word would.
+    pub fn step(self: *Self) anyerror!Step {
+        if (self.frames.items.len == 0) return .Idle;
+
+        return self.advance() catch |err| {
+            self.unwind_frames(0, err);
+            return err;
+        };
+    }
+
+    test "begin and step" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        try rt.eval("1 &@DUP");
+        var site = try rt.stack_pop();
+        defer rt.release_heaped_object_reference(&site);
+        const dup = (try rt.find_word("@DUP")).?;
+
+        try rt.begin(site.Word);
+        try expectEqual(Step{ .Entered = dup }, try rt.step());
+        try expectEqual(@as(usize, 1), rt.stack.depth());
+        try expectEqual(Step{ .Ran = dup }, try rt.step());
+        try expectEqual(@as(usize, 2), rt.stack.depth());
+        try expectEqual(Step{ .Left = site.Word }, try rt.step());
+        try expectEqual(Step.Idle, try rt.step());
+
+        _ = try rt.stack_pop_pair();
+    }
+
+    /// Throw away everything pending (see begin) without running any more of
+    /// it, as when a debugger's user has seen enough. Observers are told
+    /// about each word abandoned just as if it had failed with
+    /// InternalError.Interrupted. Whatever already ran stays run.
+    pub fn abandon(self: *Self) void {
+        self.unwind_frames(0, InternalError.Interrupted);
+    }
+
+    /// Advance the innermost Frame by one step: enter one inner word, run
+    /// one primitive, push one literal, or finish up.
+    fn advance(self: *Self) anyerror!Step {
+        // Careful: enter_word can grow (and thus move) the Frames, so this
+        // pointer is only good until then.
+        const frame = &self.frames.items[self.frames.items.len - 1];
+        const word = frame.word;
+
+        // TODO: Stack compatibility check against the WordSignature.
+
+        // TODO: determine if there's a better/more concise error to pass
+        // here, perhaps by somehow triggering this and seeing what states can
+        // even leave us here
+        const iword = word.value orelse return InternalError.EmptyWord;
+
+        switch (iword.impl) {
+            .CallSite => |site| {
+                if (frame.next_step > 0) {
+                    self.leave_word();
+                    return Step{ .Left = word };
+                }
+
+                frame.next_step = 1;
+                const target = try self.resolve_call_site(site);
+                // Nothing's left for a call site to do once its target is
+                // done, so it's always a tail call.
+                try self.enter_word_from_tail(target);
+                return Step{ .Entered = target };
+            },
+            .Compound => |compound| {
+                if (frame.next_step == compound.len) {
+                    self.leave_word();
+                    return Step{ .Left = word };
+                }
+
+                const inner = compound[frame.next_step];
+                frame.next_step += 1;
+                if (frame.next_step == compound.len) {
+                    try self.enter_word_from_tail(inner);
+                } else {
+                    try self.enter_word(inner);
+                }
+                return Step{ .Entered = inner };
+            },
+            .HeapLit => |lit| {
+                self.stack = try self.stack.do_push(lit.*);
+                self.notify_push();
+            },
+            .HostClosure => |closure| closure.call(closure.context, self) catch |err| {
+                try self.maybe_suspend(err);
+                return Step{ .Blocked = word };
+            },
+            .Primitive => |impl| impl(self) catch |err| {
+                try self.maybe_suspend(err);
+                return Step{ .Blocked = word };
+            },
+        }
+
+        self.leave_word();
+        return Step{ .Ran = word };
+    }
+
+    /// A word which would block (see yield_to_others) is left as the
+    /// innermost Frame, to be run again from scratch once this Task is next
+    /// scheduled. Everything else is a real error.
+    fn maybe_suspend(self: *Self, err: anyerror) anyerror!void {
+        if (err != InternalError.WouldBlock or !self.suspendable) return err;
+        self.yield_requested = true;
+    }
+
+    /// For words which can't make progress until some other Task does (as
+    /// when receiving from an empty Channel): let everyone else have a go,
+    /// and return whether anyone did. Words should retry after this returns
+    /// true, and give up, or find another way to wait, otherwise.
+    ///
+    /// Within a Task that can be suspended, this instead fails with
+    /// InternalError.WouldBlock, which the caller must pass along untouched
+    /// after leaving the Stack exactly as it found it: it'll be run again
+    /// later. (Running other Tasks from within this one would work, too, but
+    /// only until one of those needed this one to move along first.)
+    pub fn yield_to_others(self: *Self) !bool {
+        if (self.current_task != null and self.suspendable) return InternalError.WouldBlock;
+        return (try self.run_task_slices()) > 0;
+    }
+
+    /// Create a Task which will run `entrypoint` (a Word Object, whose
+    /// reference the Task takes over) on a Stack of its own, and return its
+    /// id. Nothing is run until someone yields or joins.
+    pub fn spawn_task(self: *Self, entrypoint: Object) !usize {
+        if (entrypoint != .Word) return InternalError.TypeError;
+
+        const id = self.next_task_id;
+        const task = try Task.init(self.alloc, id, entrypoint);
+        errdefer {
+            // The entrypoint's reference remains the caller's on failure.
+            task.stack.release();
+            task.frames.deinit();
+            self.alloc.destroy(task);
+        }
+
+        try self.tasks.put(id, task);
+        self.next_task_id += 1;
+
+        return id;
+    }
+
+    /// Give every Runnable Task one slice, in spawn order, and return how
+    /// many there were.
+    pub fn run_task_slices(self: *Self) !usize {
+        var ran: usize = 0;
+        var idx: usize = 0;
+
+        // Tasks can spawn and join other Tasks from within their slices, so
+        // the map can change from under us: re-check the bounds every pass
+        // (at worst, a Task misses out on this round).
+        while (idx < self.tasks.count()) : (idx += 1) {
+            const task = self.tasks.values()[idx];
+            if (task.state != .Runnable) continue;
+
+            ran += 1;
+            self.run_task_slice(task);
+        }
+
+        return ran;
+    }
+
+    /// Swap `task` in, run it until it finishes, fails, or yields, and swap
+    /// it back out. Failures are stashed in the Task for join_task to find.
+    fn run_task_slice(self: *Self, task: *Task) void {
+        const outer_task = self.current_task;
+
+        std.mem.swap(*Stack, &self.stack, &task.stack);
+        std.mem.swap(FrameStack, &self.frames, &task.frames);
+        const was_suspendable = self.suspendable;
+        self.current_task = task;
+        self.yield_requested = false;
+        self.suspendable = true;
+        task.state = .Running;
+
+        const result: anyerror!void = if (task.started)
+            self.run_frames_until(0, true)
+        else blk: {
+            task.started = true;
+            self.enter_word(task.entrypoint.Word) catch |err| break :blk err;
+            break :blk self.run_frames_until(0, true);
+        };
+
+        if (result) |_| {
+            task.state = if (self.frames.items.len == 0) .Done else .Runnable;
+        } else |err| {
+            self.unwind_frames(0, err);
+            task.failure = err;
+            task.state = .Failed;
+        }
+
+        self.yield_requested = false;
+        self.suspendable = was_suspendable;
+        self.current_task = outer_task;
+        std.mem.swap(*Stack, &self.stack, &task.stack);
+        std.mem.swap(FrameStack, &self.frames, &task.frames);
+    }
+
+    /// Wait (see yield_to_others) for the Task with id `id` to finish or
+    /// fail, without otherwise touching it.
+    ///
+    /// Waiting on ourselves, or on a Task which is (transitively) waiting on
+    /// us when neither can be suspended, can never finish, and fails with
+    /// InternalError.Deadlock.
+    pub fn wait_for_task(self: *Self, id: usize) !void {
+        const task = self.tasks.get(id) orelse return InternalError.ValueError;
+        if (task == self.current_task) return InternalError.Deadlock;
+
+        while (task.state == .Runnable or task.state == .Running) {
+            if (!try self.yield_to_others()) return InternalError.Deadlock;
+        }
+    }
+
+    /// Wait for the Task with id `id` (see wait_for_task), and then move
+    /// whatever it left on its Stack onto ours (in the same order), or
+    /// return its error if it failed. Either way, the Task is then gone.
+    pub fn join_task(self: *Self, id: usize) !void {
+        try self.wait_for_task(id);
+        const task = self.tasks.get(id).?;
+
+        _ = self.tasks.orderedRemove(id);
+        defer task.deinit(self.alloc);
+
+        if (task.failure) |err| return err;
+
+        var results = std.ArrayList(Object).init(self.alloc);
+        defer {
+            for (results.items) |*result| self.release_heaped_object_reference(result);
+            results.deinit();
+        }
+
+        while (true) {
+            const popped = task.stack.do_pop() catch |err| switch (err) {
+                StackManipulationError.Underflow => break,
+                else => return err,
+            };
+            task.stack = popped.now_top_stack;
+            results.append(popped.item) catch |err| {
+                var orphan = popped.item;
+                self.release_heaped_object_reference(&orphan);
+                return err;
+            };
+        }
+
+        // Pushing takes a reference of its own, and the defer above drops
+        // the one we popped.
+        var idx = results.items.len;
+        while (idx > 0) {
+            idx -= 1;
+            try self.stack_push_object(results.items[idx]);
+        }
+
+        try self.enforce_stack_depth_limit();
+    }
+
+    /// Install an Observer, which will be told about everything from here on
+    /// out (see observer.zig for what "everything" means).
+    pub fn add_observer(self: *Self, observer: Observer) !void {
+        try self.observers.append(observer);
+    }
+
+    /// Uninstall every Observer matching `observer` exactly. Removing one that
+    /// was never installed is a no-op.
+    pub fn remove_observer(self: *Self, observer: Observer) void {
+        var idx: usize = 0;
+
+        while (idx < self.observers.items.len) {
+            const candidate = self.observers.items[idx];
+
+            if (candidate.context == observer.context and candidate.on_event == observer.on_event) {
+                _ = self.observers.orderedRemove(idx);
+            } else {
+                idx += 1;
+            }
+        }
+    }
+
+    fn notify(self: *Self, event: ObserverEvent) void {
+        for (self.observers.items) |observer| observer.on_event(observer.context, self, event);
+    }
+
+    fn notify_push(self: *Self) void {
+        self.notify_pushes(1);
+    }
+
+    /// Count the `count` Objects just pushed, telling observers of each in
+    /// the order they were pushed.
+    fn notify_pushes(self: *Self, count: usize) void {
+        self.counters.pushes += count;
+        if (self.observers.items.len == 0) return;
+        // We just pushed them, so they're definitely there to find.
+        var idx = count;
+        while (idx > 0) {
+            idx -= 1;
+            self.notify(.{ .Push = self.stack.nth_from_top(idx).? });
+        }
+    }
+
+    test "observers see words, pushes, pops, and errors" {
+        const Tally = struct {
+            starts: usize = 0,
+            ends: usize = 0,
+            pushes: usize = 0,
+            pops: usize = 0,
+            errors: usize = 0,
+
+            fn on_event(context: ?*anyopaque, _: *Runtime, event: ObserverEvent) void {
+                const tally = @ptrCast(*@This(), @alignCast(@alignOf(@This()), context.?));
+                switch (event) {
+                    .WordStart => tally.starts += 1,
+                    .WordEnd => tally.ends += 1,
+                    .Push => tally.pushes += 1,
+                    .Pop => tally.pops += 1,
+                    .Error => tally.errors += 1,
+                    .Defined, .VocabularyCreated, .Loaded => {},
+                }
+            }
+        };
+
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        var tally = Tally{};
+        const observer = Observer{ .context = &tally, .on_event = &Tally.on_event };
+        try rt.add_observer(observer);
+
+        try rt.eval("1 2 @SWAP");
+        try expectEqual(@as(usize, 2), tally.pushes);
+        try expectEqual(@as(usize, 1), tally.starts);
+        try expectEqual(@as(usize, 1), tally.ends);
+
+        _ = try rt.stack_pop_pair();
+        try expectEqual(@as(usize, 2), tally.pops);
+
+        try expectError(StackManipulationError.Underflow, rt.eval("@DROP"));
+        try expectEqual(@as(usize, 1), tally.errors);
+
+        rt.remove_observer(observer);
+        try rt.eval("3 @DROP");
+        try expectEqual(@as(usize, 2), tally.pushes);
+    }
+
+    test "observers see what words push and drop" {
+        const Tally = struct {
+            pushes: usize = 0,
+            pops: usize = 0,
+
+            fn on_event(context: ?*anyopaque, _: *Runtime, event: ObserverEvent) void {
+                const tally = @ptrCast(*@This(), @alignCast(@alignOf(@This()), context.?));
+                switch (event) {
+                    .Push => tally.pushes += 1,
+                    .Pop => tally.pops += 1,
+                    else => {},
+                }
+            }
+        };
+
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+        try rt.eval("7 @LIT :seven @DEFINE");
+
+        var tally = Tally{};
+        try rt.add_observer(.{ .context = &tally, .on_event = &Tally.on_event });
+
+        try rt.eval("1 @DUP @DROP");
+        try expectEqual(@as(usize, 2), tally.pushes);
+        try expectEqual(@as(usize, 1), tally.pops);
+
+        try rt.eval("2 @2DUPSHUF");
+        try expectEqual(@as(usize, 5), tally.pushes);
+
+        // As do the literals words like @LIT build push.
+        try rt.eval("seven");
+        try expectEqual(@as(usize, 6), tally.pushes);
+        try expectEqual(@as(usize, 7), try rt.stack_pop_value(usize));
+
+        while (rt.stack.depth() > 0) _ = try rt.stack_pop();
+        try expectEqual(@as(usize, 6), tally.pops);
+    }
+
+    /// Return whatever `site` refers to, from its cache if the dictionary
+    /// hasn't been redefined from under it, or by way of find_word (and then
+    /// caching the result) otherwise.
+    fn resolve_call_site(self: *Self, site: *CallSite) !*Types.HeapedWord {
+        // Call sites belonging to someone else (our base's words, say) are
+        // read-only to us: resolve them from scratch, against our own
+        // dictionary (and thus, eventually, the base's).
+        if (site.owner_id != self.id) {
+            self.counters.call_site_misses += 1;
+            return (try self.find_word(site.name.value.?)) orelse InternalError.UnknownWord;
+        }
+
+        if (site.cached) |cached| {
+            if (site.generation == self.dictionary_generation) {
+                self.counters.call_site_hits += 1;
+                return cached;
+            }
+            site.cached = null;
+        }
+        self.counters.call_site_misses += 1;
+
+        const word = (try self.find_word(site.name.value.?)) orelse return InternalError.UnknownWord;
+        site.cached = word;
+        site.generation = self.dictionary_generation;
+
+        return word;
+    }
+
+    test "resolve_call_site: caches until redefinition" {
+        const Words = struct {
+            fn push_one(rt: *Runtime) anyerror!void {
+                try rt.stack_push_uint(1);
+            }
+
+            fn push_two(rt: *Runtime) anyerror!void {
+                try rt.stack_push_uint(2);
+            }
+        };
+
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        const one = try rt.word_from_primitive_impl(&Words.push_one, null);
+        try rt.define_word("number", one);
+
+        try rt.eval("&number");
+        var site_word = try rt.stack_pop();
+        defer rt.release_heaped_object_reference(&site_word);
+        const site = site_word.Word.value.?.impl.CallSite;
+
+        try rt.run_word(site_word.Word);
+        try expectEqual(one, site.cached.?);
+        try rt.run_word(site_word.Word);
+        try expectEqual(@as(usize, 1), (try rt.stack_pop()).UnsignedInt);
+        try expectEqual(@as(usize, 1), (try rt.stack_pop()).UnsignedInt);
+
+        const two = try rt.word_from_primitive_impl(&Words.push_two, null);
+        try rt.define_word("number", two);
+
+        try rt.run_word(site_word.Word);
+        try expectEqual(two, site.cached.?);
+        try expectEqual(@as(usize, 2), (try rt.stack_pop()).UnsignedInt);
+    }
+
+    pub fn get_or_put_string(self: *Self, str: []const u8) !GetOrPutResult(Types.HeapedString) {
+        // TODO: intern this similarly to symbols
+        const stored = try self.alloc.alloc(u8, str.len);
+        std.mem.copy(u8, stored[0..], str);
+        const heaped = try self.alloc.create(Types.HeapedString);
+        heaped.* = Types.HeapedString.init(stored);
+        return .{
+            .value_ptr = heaped,
+            .found_existing = false,
+        };
+    }
+
+    /// Retrieve the previously-interned Symbol's Rc, interning it first if
+    /// this is the first time we've seen it. The returned pointer is stable
+    /// for the life of the Runtime, and is the only valid way to name a word
+    /// in the dictionary.
+    pub fn get_or_put_symbol(self: *Self, sym: []const u8) !GetOrPutResult(Types.HeapedSymbol) {
+        if (self.symbols.get(sym)) |existing| {
+            return .{
+                .value_ptr = existing,
+                .found_existing = true,
+            };
+        }
+
+        const stored = try self.alloc.alloc(u8, sym.len);
+        errdefer self.alloc.free(stored);
+        std.mem.copy(u8, stored[0..], sym);
+
+        const heaped = try self.alloc.create(Types.HeapedSymbol);
+        errdefer self.alloc.destroy(heaped);
+        heaped.* = Types.HeapedSymbol.init(stored);
+
+        // The pool's own reference, released in deinit_shared.
+        try heaped.increment();
+
+        // The key is the symbol's own storage, *not* the caller's slice, which
+        // may well be a window into some input buffer that's about to go away.
+        try self.symbols.put(stored, heaped);
+
+        return .{
+            .value_ptr = heaped,
+            .found_existing = false,
+        };
+    }
+
+    test "get_or_put_symbol: interns by contents" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit();
+
+        var input = "something".*;
+        const first = try rt.get_or_put_symbol(input[0..]);
+        try expectEqual(false, first.found_existing);
+
+        // Scribbling over the caller's buffer must not affect the pool.
+        input[0] = 'n';
+        const second = try rt.get_or_put_symbol("something");
+        try expectEqual(true, second.found_existing);
+        try expectEqual(first.value_ptr, second.value_ptr);
+        try expectEqualStrings("something", second.value_ptr.value.?);
+    }
+
+    /// Take a WordSignature by value and, if it is new to this Runtime, store
+    /// it. Return a GetOrPutResult which will contain a pointer to the stored
+    /// WordSignature. Each unique signature will be stored a maximum of one
+    /// time in this Runtime.
+    ///
+    /// Can fail by way of allocation errors only.
+    pub fn get_or_put_word_signature(self: *Self, sig: WordSignature) !GetOrPutResult(WordSignature) {
+        var entry = try self.signatures.getOrPut(sig);
+        return .{
+            .value_ptr = entry.key_ptr,
+            .found_existing = entry.found_existing,
+        };
+    }
+
+    pub fn get_well_known_shape(self: *Self, req: WellKnownShape) *Shape {
+        return &self.well_known_shapes[@enumToInt(req)];
+    }
+
+    pub fn get_well_known_word_signature(self: *Self, req: WellKnownSignature) *WordSignature {
+        return self.well_known_signatures[@enumToInt(req)];
+    }
+
+    /// Takes a bare Word struct, wraps it in a refcounter, and returns a
+    /// pointer to the resultant memory. Does not wrap it in an Object for
+    /// direct placement on a Stack.
+    fn send_word_to_heap(self: *Self, bare: Word) !*Types.HeapedWord {
+        const heap_space = try self.alloc.create(Types.HeapedWord);
+        heap_space.* = Types.HeapedWord.init(bare);
+        return heap_space;
+    }
+
+    /// Create an empty Array with room for `capacity` members. As with
+    /// words, nothing holds a reference to it yet: push it, or hand it to
+    /// release_array if something goes wrong before then.
+    pub fn new_array(self: *Self, capacity: usize) !*Types.HeapedArray {
+        var list = Types.ObjectArray.init(self.alloc);
+        errdefer list.deinit();
+        try list.ensureTotalCapacity(capacity);
+
+        const heap_space = try self.alloc.create(Types.HeapedArray);
+        heap_space.* = Types.HeapedArray.init(list);
+        return heap_space;
+    }
+
+    /// Tear down an Array (and its members) which never made it onto the
+    /// Stack.
+    pub fn release_array(self: *Self, array: *Types.HeapedArray) void {
+        // Object.deinit expects to be dropping a reference, so give it one.
+        array.increment() catch unreachable;
+        var obj = Object{ .Array = array };
+        self.release_heaped_object_reference(&obj);
+    }
+
+    /// Create an empty Map. As with words, nothing holds a reference to it
+    /// yet: push it, or hand it to release_map if something goes wrong before
+    /// then.
+    pub fn new_map(self: *Self) !*Types.HeapedMap {
+        const heap_space = try self.alloc.create(Types.HeapedMap);
+        heap_space.* = Types.HeapedMap.init(map.Map.init());
+        return heap_space;
+    }
+
+    /// Tear down a Map (and its values) which never made it onto the Stack.
+    pub fn release_map(self: *Self, target: *Types.HeapedMap) void {
+        target.increment() catch unreachable;
+        var obj = Object{ .Map = target };
+        self.release_heaped_object_reference(&obj);
+    }
+
+    /// Create an empty Set, which (as with new_map) nothing holds a reference
+    /// to yet.
+    pub fn new_set(self: *Self) !*Types.HeapedSet {
+        const heap_space = try self.alloc.create(Types.HeapedSet);
+        heap_space.* = Types.HeapedSet.init(set.Set.init());
+        return heap_space;
+    }
+
+    /// Tear down a Set (and its members) which never made it onto the Stack.
+    pub fn release_set(self: *Self, target: *Types.HeapedSet) void {
+        target.increment() catch unreachable;
+        var obj = Object{ .Set = target };
+        self.release_heaped_object_reference(&obj);
+    }
+
+    /// Wrap `bytes`, which must have come from this Runtime's allocator, in
+    /// a String which takes ownership of them. Unlike get_or_put_string, no
+    /// copy is made. As with words, nothing holds a reference to it yet.
+    pub fn new_string_owned(self: *Self, bytes: []u8) !*Types.HeapedString {
+        const heap_space = try self.alloc.create(Types.HeapedString);
+        heap_space.* = Types.HeapedString.init(bytes);
+        return heap_space;
+    }
+
+    /// Create a Cell holding `value`, whose reference it takes over. As with
+    /// words, nothing holds a reference to the Cell itself yet.
+    pub fn new_cell(self: *Self, value: Object) !*Types.HeapedCell {
+        const heap_space = try self.alloc.create(Types.HeapedCell);
+        heap_space.* = Types.HeapedCell.init(cell.Cell.init(value));
+        return heap_space;
+    }
+
+    /// Create an empty Channel, bounded to `capacity` queued Objects if
+    /// non-null. As with words, nothing holds a reference to it yet.
+    pub fn new_channel(self: *Self, capacity: ?usize) !*Types.HeapedChannel {
+        const heap_space = try self.alloc.create(Types.HeapedChannel);
+        heap_space.* = Types.HeapedChannel.init(channel.Channel.init(self.alloc, capacity));
+        return heap_space;
+    }
+
+    /// Heap-wraps a compound word definition.
+    pub fn word_from_compound_impl(
+        self: *Self,
+        impl: CompoundImplementation,
+        sig: ?Word.SignatureState,
+    ) !*Types.HeapedWord {
+        return try self.send_word_to_heap(Word.new_compound_untagged(impl, sig));
+    }
+
+    /// Heap-wraps a heaplit word definition. How meta.
+    pub fn word_from_heaplit_impl(
+        self: *Self,
+        impl: HeapLitImplementation,
+        sig: ?Word.SignatureState,
+    ) !*Types.HeapedWord {
+        return try self.send_word_to_heap(Word.new_heaplit_untagged(impl, sig));
+    }
+
+    /// Heap-wraps a late-bound reference to the word named `name`, interning
+    /// that name along the way.
+    pub fn word_from_call_site(self: *Self, name: []const u8) !*Types.HeapedWord {
+        const identifier = (try self.get_or_put_symbol(name)).value_ptr;
+
+        const site = try self.alloc.create(CallSite);
+        errdefer self.alloc.destroy(site);
+        site.* = .{ .name = identifier, .owner_id = self.id };
+        try identifier.increment();
+        errdefer _ = identifier.decrement_and_prune(.FreeInnerDestroySelf, self.alloc);
+
+        return try self.send_word_to_heap(Word.new_call_site_untagged(site, null));
+    }
+
+    test "word_from_call_site: failures leave nothing behind" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        const alloc = rt.alloc;
+        defer rt.alloc = alloc;
+        var word: *Types.HeapedWord = undefined;
+        var fail_index: usize = 0;
+        while (true) : (fail_index += 1) {
+            var failing = std.testing.FailingAllocator.init(alloc, fail_index);
+            rt.alloc = failing.allocator();
+            word = rt.word_from_call_site("elsewhere") catch |err| {
+                try expect(err == error.OutOfMemory);
+                continue;
+            };
+            break;
+        }
+        rt.alloc = alloc;
+
+        try word.increment();
+        var owned = Object{ .Word = word };
+        rt.release_heaped_object_reference(&owned);
+    }
+
+    /// Heap-wraps a compound word calling each of `names` in turn, each by
+    /// way of a call site of its own (see word_from_call_site), exactly as
+    /// `&a &b @COMPOSE` would build it from gale. Compound words built from
+    /// the words themselves (as word_from_compound_impl and define_word_va
+    /// take them) are bound early, and so never see redefinitions.
+    pub fn word_from_call_sites(self: *Self, names: []const []const u8) !*Types.HeapedWord {
+        const inner = try self.alloc.alloc(*Types.HeapedWord, names.len);
+        var filled: usize = 0;
+        errdefer {
+            for (inner[0..filled]) |member| {
+                var owned = Object{ .Word = member };
+                self.release_heaped_object_reference(&owned);
+            }
+            self.alloc.free(inner);
+        }
+
+        // Compound words hold a reference to each of their inner words.
+        while (filled < names.len) : (filled += 1) {
+            const site = try self.word_from_call_site(names[filled]);
+            site.increment() catch unreachable;
+            inner[filled] = site;
+        }
+
+        return try self.word_from_compound_impl(inner, null);
+    }
+
+    test "word_from_call_sites: compound words cache each call until redefinition" {
+        const Words = struct {
+            fn push_one(rt: *Runtime) anyerror!void {
+                try rt.stack_push_uint(1);
+            }
+
+            fn push_two(rt: *Runtime) anyerror!void {
+                try rt.stack_push_uint(2);
+            }
+        };
+
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        try rt.define_word("number", try rt.word_from_primitive_impl(&Words.push_one, null));
+        try rt.define_word("numbers", try rt.word_from_call_sites(&.{ "number", "number" }));
+
+        rt.reset_stats();
+        try rt.eval("numbers numbers");
+        try expectEqual(@as(usize, 2), rt.stats().call_site_misses);
+        try expectEqual(@as(usize, 2), rt.stats().call_site_hits);
+        var idx: usize = 0;
+        while (idx < 4) : (idx += 1) try expectEqual(@as(usize, 1), try rt.stack_pop_value(usize));
+
+        try rt.define_word("number", try rt.word_from_primitive_impl(&Words.push_two, null));
+        try rt.eval("numbers");
+        try expectEqual(@as(usize, 4), rt.stats().call_site_misses);
+        try expectEqual(@as(usize, 2), try rt.stack_pop_value(usize));
+        try expectEqual(@as(usize, 2), try rt.stack_pop_value(usize));
+    }
+
+    /// Heap-wraps a primitive word definition.
+    pub fn word_from_primitive_impl(
+        self: *Self,
+        impl: PrimitiveImplementation,
+        sig: ?Word.SignatureState,
+    ) !*Types.HeapedWord {
+        return try self.send_word_to_heap(Word.new_primitive_untagged(impl, sig));
+    }
+
+    /// Find the current (most recent) definition of the word named `name`,
+    /// materializing the lazy vocabulary it belongs to if need be. Names
+    /// which aren't defined as-is are then looked for within the vocabulary
+    /// being imported, if any, and then the search order (see
+    /// use_vocabulary). Returns null for words that simply don't exist.
+    // Explicitly anyerror, since lazy loading can recurse back into eval.
+    pub fn find_word(self: *Self, name: []const u8) anyerror!?*Types.HeapedWord {
+        if (try self.find_word_as_named(name)) |word| return word;
+        if (self.current_vocabulary == null and self.search_order.items.len == 0) return null;
+
+        var qualified = std.ArrayList(u8).init(self.alloc);
+        defer qualified.deinit();
+
+        // Prefixed names only ever mean their prefix's vocabulary.
+        if (vocabulary.vocabulary_of(name)) |prefix| {
+            const rest = name[prefix.len + 1 ..];
+
+            var idx = self.search_order.items.len;
+            while (idx > 0) {
+                idx -= 1;
+                const entry = self.search_order.items[idx];
+                const entry_prefix = entry.prefix orelse continue;
+                if (!std.mem.eql(u8, entry_prefix, prefix)) continue;
+
+                if (try self.find_word_within(&qualified, entry.vocabulary, rest)) |word| return word;
+            }
+
+            return null;
+        }
+
+        if (self.current_vocabulary) |current| {
+            if (try self.find_word_within(&qualified, current, name)) |word| return word;
+        }
+
+        var idx = self.search_order.items.len;
+        while (idx > 0) {
+            idx -= 1;
+            const entry = self.search_order.items[idx];
+            if (entry.prefix != null) continue;
+
+            if (try self.find_word_within(&qualified, entry.vocabulary, name)) |word| return word;
+        }
+
+        return null;
+    }
+
+    fn find_word_as_named(self: *Self, name: []const u8) anyerror!?*Types.HeapedWord {
+        if (self.lookup_word(name)) |word| return word;
+
+        const vocab_name = vocabulary.vocabulary_of(name) orelse return null;
+        if (!try self.materialize_vocabulary(vocab_name)) return null;
+
+        return self.lookup_word(name);
+    }
+
+    /// Look up `vocab_name/name`, using `scratch` to spell that out.
+    fn find_word_within(
+        self: *Self,
+        scratch: *std.ArrayList(u8),
+        vocab_name: []const u8,
+        name: []const u8,
+    ) anyerror!?*Types.HeapedWord {
+        scratch.clearRetainingCapacity();
+        try scratch.writer().print("{s}{c}{s}", .{ vocab_name, vocabulary.VOCABULARY_SEPARATOR, name });
+        return self.find_word_as_named(scratch.items);
+    }
+
+    /// Add `vocab_name` to the end (the most-searched end) of the search
+    /// order: unqualified names will be looked for within it, or, given a
+    /// `prefix`, names qualified by `prefix` will be instead. There's only
+    /// the one search order per Runtime, whichever module is doing the
+    /// using.
+    pub fn use_vocabulary(self: *Self, vocab_name: []const u8, prefix: ?[]const u8) !void {
+        var entry = try module.SearchEntry.init(self.alloc, vocab_name, prefix);
+        errdefer entry.deinit(self.alloc);
+        try self.search_order.append(entry);
+
+        // Names may now mean something other than what call sites cached.
+        self.dictionary_generation +%= 1;
+    }
+
+    /// Define `word` as `name`, or, while a module is being imported and
+    /// `name` is unqualified, as `name` within that module's vocabulary.
+    pub fn define_word_here(self: *Self, name: []const u8, word: *Types.HeapedWord) !void {
+        const vocab = if (vocabulary.vocabulary_of(name) == null) self.current_vocabulary else null;
+        try self.define_word_in_vocabulary(vocab, name, word);
+    }
+
+    /// Evaluate the file at `path` as a module, defining its unqualified
+    /// names (see define_word_here) into the vocabulary named after it (see
+    /// module.vocabulary_for_path), and then add that vocabulary to the
+    /// search order (see use_vocabulary). Modules are only ever evaluated
+    /// once, by real path: importing one again just uses it again, unless
+    /// it's still being evaluated, which is a CircularImport. A module which
+    /// fails can be imported again, but keeps whatever it defined before
+    /// failing.
+    pub fn import_module(self: *Self, path: []const u8, prefix: ?[]const u8) !void {
+        if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+        const bundled = self.bundled.get(path);
+
+        // Bundled modules (see add_bundled_file) go by their path as written.
+        const canonical = if (bundled != null)
+            try self.alloc.dupe(u8, path)
+        else
+            try std.fs.cwd().realpathAlloc(self.alloc, path);
+        if (self.modules.get(canonical)) |existing| {
+            self.alloc.free(canonical);
+            if (existing.loading) return InternalError.CircularImport;
+            return self.use_vocabulary(existing.vocabulary, prefix);
+        }
+
+        const read = if (bundled == null) std.fs.cwd().readFileAlloc(self.alloc, canonical, vocabulary.MAX_VOCABULARY_FILE_SIZE) catch |err| {
+            self.alloc.free(canonical);
+            return err;
+        } else null;
+        defer if (read) |owned| self.alloc.free(owned);
+        const contents = bundled orelse read.?;
+
+        const vocab_name = self.alloc.dupe(u8, module.vocabulary_for_path(canonical)) catch |err| {
+            self.alloc.free(canonical);
+            return err;
+        };
+        self.modules.put(canonical, .{ .vocabulary = vocab_name, .loading = true }) catch |err| {
+            self.alloc.free(vocab_name);
+            self.alloc.free(canonical);
+            return err;
+        };
+
+        const outer_vocabulary = self.current_vocabulary;
+        self.current_vocabulary = vocab_name;
+        const evaluated = self.eval_source(path, contents);
+        self.current_vocabulary = outer_vocabulary;
+
+        evaluated catch |err| {
+            // Forget the module, so that it can be fixed and tried again.
+            const removed = self.modules.fetchSwapRemove(canonical).?;
+            self.alloc.free(removed.key);
+            self.alloc.free(removed.value.vocabulary);
+            return err;
+        };
+
+        // Nested imports may well have moved the entry.
+        self.modules.getPtr(canonical).?.loading = false;
+        try self.use_vocabulary(vocab_name, prefix);
+    }
+
+    /// Declare `vocab_name` to be at the version `text` (see version.zig).
+    /// Declaring the same version twice is harmless, but declaring another,
+    /// or one that something's already demanded otherwise (see
+    /// require_version), is an IncompatibleVersion.
+    pub fn declare_version(self: *Self, vocab_name: []const u8, text: []const u8) !void {
+        const declared = try version.parse_version(text);
+        const versioning = try self.versioning_of(vocab_name);
+
+        if (versioning.declared) |existing| {
+            if (existing.order(declared) == .eq) return;
+            return self.raise_version_conflict("{s} is already version {}, so can't be {}", .{ vocab_name, existing, declared });
+        }
+        for (versioning.demands.items) |demand| {
+            if (!demand.range.contains(declared)) {
+                return self.raise_version_conflict("{s} {} doesn't satisfy {s} (required by {s})", .{ vocab_name, declared, demand.text, demand.by });
+            }
+        }
+
+        versioning.declared = declared;
+    }
+
+    /// Demand that `vocab_name` be at a version within the range `text` (see
+    /// version.zig), on behalf of the module being imported (if any). Ranges
+    /// which the vocabulary's declared version (see declare_version), or any
+    /// other demand, rules out are an IncompatibleVersion. Vocabularies which
+    /// have yet to declare a version are held to it when they do.
+    pub fn require_version(self: *Self, vocab_name: []const u8, text: []const u8) !void {
+        const range = try version.parse_range(text);
+        const by = self.current_vocabulary orelse version.TOP_LEVEL;
+        const versioning = try self.versioning_of(vocab_name);
+
+        if (versioning.declared) |declared| {
+            if (!range.contains(declared)) {
+                return self.raise_version_conflict("{s} {} doesn't satisfy {s} (required by {s})", .{ vocab_name, declared, text, by });
+            }
+        }
+        for (versioning.demands.items) |demand| {
+            if (range.intersect(demand.range) == null) {
+                return self.raise_version_conflict(
+                    "{s} can't be both {s} (required by {s}) and {s} (required by {s})",
+                    .{ vocab_name, demand.text, demand.by, text, by },
+                );
+            }
+        }
+
+        const owned_text = try self.alloc.dupe(u8, text);
+        errdefer self.alloc.free(owned_text);
+        const owned_by = try self.alloc.dupe(u8, by);
+        errdefer self.alloc.free(owned_by);
+        try versioning.demands.append(self.alloc, .{ .range = range, .text = owned_text, .by = owned_by });
+    }
+
+    fn versioning_of(self: *Self, vocab_name: []const u8) !*version.Versioning {
+        const result = try self.versions.getOrPut(vocab_name);
+        if (!result.found_existing) {
+            result.key_ptr.* = self.alloc.dupe(u8, vocab_name) catch |err| {
+                _ = self.versions.pop();
+                return err;
+            };
+            result.value_ptr.* = .{};
+        }
+        return result.value_ptr;
+    }
+
+    fn raise_version_conflict(self: *Self, comptime fmt: []const u8, args: anytype) InternalError {
+        if (self.version_conflict) |conflict| self.alloc.free(conflict);
+        self.version_conflict = std.fmt.allocPrint(self.alloc, fmt, args) catch null;
+        return InternalError.IncompatibleVersion;
+    }
+
+    test "declare_version and require_version" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        try rt.require_version("strings", "^1.2");
+        try rt.require_version("strings", ">=1.4");
+        try rt.declare_version("strings", "1.5.0");
+        try rt.declare_version("strings", "1.5.0");
+
+        try expectError(InternalError.IncompatibleVersion, rt.require_version("strings", "^2"));
+        try expectEqualStrings("strings 1.5.0 doesn't satisfy ^2 (required by the top level)", rt.version_conflict.?);
+        try expectError(InternalError.IncompatibleVersion, rt.declare_version("strings", "1.6.0"));
+
+        try rt.require_version("shapes", "~0.3.1");
+        try expectError(InternalError.IncompatibleVersion, rt.require_version("shapes", "0.4"));
+        try expectEqualStrings(
+            "shapes can't be both ~0.3.1 (required by the top level) and 0.4 (required by the top level)",
+            rt.version_conflict.?,
+        );
+        try expectError(InternalError.IncompatibleVersion, rt.declare_version("shapes", "0.2.0"));
+        try expectError(InternalError.ValueError, rt.declare_version("shapes", "zero"));
+
+        rt.clear_load_trace();
+        try expect(rt.version_conflict == null);
+    }
+
+    /// Load the lazy vocabulary named `vocab_name`, if there is one and it
+    /// hasn't been loaded yet, returning whether anything was loaded.
+    fn materialize_vocabulary(self: *Self, vocab_name: []const u8) anyerror!bool {
+        const entry = self.lazy_vocabularies.getPtr(vocab_name) orelse return false;
+        if (entry.loaded) return false;
+
+        entry.loaded = true;
+        // Loaders can register more vocabularies, which may move entry out
+        // from under us, so copy what we need and re-find it on failure.
+        const source = entry.source;
+        LazyVocabulary.load_into(source, self) catch |err| {
+            if (self.lazy_vocabularies.getPtr(vocab_name)) |failed| failed.loaded = false;
+            return err;
+        };
+
+        return true;
+    }
+
+    /// A plain dictionary lookup, with none of find_word's lazy loading, but
+    /// falling back to our base (if any) on a miss. Names which have never
+    /// been interned can't possibly be in a dictionary, so this never interns
+    /// anything itself, and is safe to call on a shared, frozen base.
+    fn lookup_word(self: *const Self, name: []const u8) ?*Types.HeapedWord {
+        if (self.symbols.get(name)) |identifier| {
+            if (self.dictionary.getPtr(identifier)) |words| {
+                if (words.len() > 0) return words.items()[words.len() - 1];
+            }
+        }
+
+        if (self.base) |base| return base.lookup_word(name);
+        return null;
+    }
+
+    /// How many definitions the word named `name` has, here and in our base,
+    /// counting every one shadowed by a newer one. Only the newest is ever
+    /// looked up.
+    pub fn definition_count(self: *const Self, name: []const u8) usize {
+        var count: usize = 0;
+
+        if (self.symbols.get(name)) |identifier| {
+            if (self.dictionary.getPtr(identifier)) |words| count += words.len();
+        }
+
+        if (self.base) |base| count += base.definition_count(name);
+        return count;
+    }
+
+    /// Attach (a copy of) `doc` to `word`, replacing whatever docstring it
+    /// already had, for @SEE and other tooling to show. Documenting a word
+    /// keeps it alive until this Runtime is torn down.
+    pub fn document_word(self: *Self, word: *Types.HeapedWord, doc: []const u8) !void {
+        try self.guard_not_frozen();
+
+        const owned = try self.alloc.dupe(u8, doc);
+        errdefer self.alloc.free(owned);
+
+        const entry = try self.docs.getOrPut(word);
+        if (entry.found_existing) {
+            self.alloc.free(entry.value_ptr.*);
+        } else {
+            word.increment() catch |err| {
+                _ = self.docs.remove(word);
+                return err;
+            };
+        }
+        entry.value_ptr.* = owned;
+    }
+
+    /// `word`'s docstring, here or in our base, if it was ever given one.
+    pub fn doc_of(self: *const Self, word: *Types.HeapedWord) ?[]const u8 {
+        if (self.docs.get(word)) |doc| return doc;
+        if (self.base) |base| return base.doc_of(word);
+        return null;
+    }
+
+    test "document_word" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        const dup = (try rt.find_word("@DUP")).?;
+        try expect(rt.doc_of(dup) == null);
+
+        try rt.document_word(dup, "Duplicate.");
+        try rt.document_word(dup, "Duplicate the top of the Stack.");
+        try expectEqualStrings("Duplicate the top of the Stack.", rt.doc_of(dup).?);
+    }
+
+    /// Attach each docstring in `source` (see docstrings.zig) to the word
+    /// it documents, as found by find_word, having evaluated `source`
+    /// already. Those documenting anything the dictionary doesn't have (a
+    /// shape, say) are skipped.
+    pub fn document_source(self: *Self, source: []const u8) !void {
+        var found = try docstrings.associate(self.alloc, source);
+        defer found.deinit();
+
+        for (found.items) |docstring| {
+            const word = (try self.find_word(docstring.name)) orelse continue;
+            try self.document_word(word, docstring.text);
+        }
+    }
+
+    test "document_source" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        const source =
+            \\{{ Twice the top of the Stack. }}
+            \\&@DUP :twin @DEFINE
+            \\{{ No word of ours. }}
+            \\$ Printable ;
+        ;
+        try rt.eval("&@DUP :twin @DEFINE");
+        try rt.document_source(source);
+        try expectEqualStrings("Twice the top of the Stack.", rt.doc_of((try rt.find_word("twin")).?).?);
+    }
+
+    /// The name `word` goes by, for instrumentation's sake: a CallSite's own
+    /// name, or else whichever name (here or in our base) it's defined under,
+    /// if any. This searches the whole dictionary, so it's no fit for
+    /// anything hot.
+    pub fn name_of_word(self: *const Self, word: *Types.HeapedWord) ?[]const u8 {
+        if (word.value) |inner| {
+            if (inner.impl == .CallSite) return inner.impl.CallSite.name.value.?;
+        }
+
+        var dictionary_iter = self.dictionary.iterator();
+        while (dictionary_iter.next()) |entry| {
+            for (entry.value_ptr.items()) |candidate| {
+                if (candidate == word) return entry.key_ptr.*.value.?;
+            }
+        }
+
+        if (self.base) |base| return base.name_of_word(word);
+        return null;
+    }
+
+    /// Make this Runtime's dictionary read-only, so that it can be used as the
+    /// `base` of any number of other Runtimes. Any lazy vocabularies are
+    /// materialized first, since loading them later would be a mutation.
+    /// There's no unfreezing.
+    ///
+    /// Freezing guards the dictionary, but not the stack or symbol pool: once
+    /// a frozen Runtime is shared, don't eval anything in it directly.
+    pub fn freeze(self: *Self) !void {
+        while (true) {
+            var pending: ?[]const u8 = null;
+            var lazy_iter = self.lazy_vocabularies.iterator();
+
+            while (lazy_iter.next()) |entry| {
+                if (!entry.value_ptr.loaded) {
+                    pending = entry.key_ptr.*;
+                    break;
+                }
+            }
+
+            _ = try self.materialize_vocabulary(pending orelse break);
+        }
+
+        self.frozen = true;
+    }
+
+    fn guard_not_frozen(self: *Self) InternalError!void {
+        if (self.frozen) return InternalError.AttemptedMutationOfFrozenRuntime;
+    }
+
+    test "frozen bases are shared, but not mutated" {
+        const Words = struct {
+            fn push_one(rt: *Runtime) anyerror!void {
+                try rt.stack_push_uint(1);
+            }
+        };
+
+        var base = try Self.init(testAllocator);
+        defer base.deinit_guard_for_empty_stack();
+        try base.define_primitive_word(null, "one", &Words.push_one);
+        try base.freeze();
+        try expectError(
+            InternalError.AttemptedMutationOfFrozenRuntime,
+            base.define_primitive_word(null, "two", &Words.push_one),
+        );
+
+        var left = try Self.init_with_options(testAllocator, .{ .base = &base });
+        defer left.deinit_guard_for_empty_stack();
+        var right = try Self.init_with_options(testAllocator, .{ .base = &base });
+        defer right.deinit_guard_for_empty_stack();
+
+        // Layers over the base are independent of one another...
+        try left.define_primitive_word(null, "mine", &Words.push_one);
+        try expectError(InternalError.UnknownWord, right.eval("mine"));
+
+        // ...but all see the base, nucleus included.
+        try left.eval("one mine @SWAP");
+        try right.eval("one @DUP");
+        _ = try left.stack_pop_pair();
+        _ = try right.stack_pop_pair();
+    }
+
+    test "dispatch_word_by_input: runs the newest definition of simple words" {
+        const Pushers = struct {
+            fn push_one(rt: *Runtime) anyerror!void {
+                try rt.stack_push_uint(1);
+            }
+
+            fn push_two(rt: *Runtime) anyerror!void {
+                try rt.stack_push_uint(2);
+            }
+        };
+
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        try rt.define_word("push", try rt.word_from_primitive_impl(&Pushers.push_one, null));
+        try rt.eval("push");
+        try expectEqual(@as(usize, 1), (try rt.stack_pop()).UnsignedInt);
+
+        try rt.define_word("push", try rt.word_from_primitive_impl(&Pushers.push_two, null));
+        try rt.eval("push");
+        try expectEqual(@as(usize, 2), (try rt.stack_pop()).UnsignedInt);
+
+        try expectError(InternalError.UnknownWord, rt.eval("nope"));
+        try expectError(InternalError.Unimplemented, rt.eval(",push"));
+    }
+
+    /// Register a vocabulary whose words will be defined the first time any
+    /// `name/...` word is looked up (see find_word). Names already registered
+    /// are refused with a ValueError. Path sources are copied.
+    pub fn register_lazy_vocabulary(self: *Self, name: []const u8, source: LazyVocabulary.Source) !void {
+        try self.guard_not_frozen();
+        if (self.lazy_vocabularies.contains(name)) return InternalError.ValueError;
+
+        const stored_name = try self.alloc.dupe(u8, name);
+        errdefer self.alloc.free(stored_name);
+
+        const stored_source: LazyVocabulary.Source = switch (source) {
+            .Loader => source,
+            .Path => |path| .{ .Path = try self.alloc.dupe(u8, path) },
+        };
+        errdefer switch (stored_source) {
+            .Path => |path| self.alloc.free(path),
+            .Loader => {},
+        };
+
+        try self.lazy_vocabularies.put(stored_name, .{ .source = stored_source });
+    }
+
+    test "find_word: lazy vocabularies load once, on first lookup" {
+        const Loader = struct {
+            fn push_one(rt: *Runtime) anyerror!void {
+                try rt.stack_push_uint(1);
+            }
+
+            fn load(context: ?*anyopaque, rt: *Runtime) anyerror!void {
+                const calls = @ptrCast(*usize, @alignCast(@alignOf(usize), context.?));
+                calls.* += 1;
+                try rt.define_word("lazy/one", try rt.word_from_primitive_impl(&push_one, null));
+            }
+        };
+
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        var calls: usize = 0;
+        try rt.register_lazy_vocabulary("lazy", .{ .Loader = .{ .context = &calls, .load = &Loader.load } });
+        try expectEqual(@as(usize, 0), calls);
+
+        try rt.eval("lazy/one lazy/one");
+        try expectEqual(@as(usize, 1), calls);
+        try expectEqual(@as(usize, 1), (try rt.stack_pop()).UnsignedInt);
+        try expectEqual(@as(usize, 1), (try rt.stack_pop()).UnsignedInt);
+
+        // Loaded vocabularies don't get re-run on misses.
+        try expect((try rt.find_word("lazy/two")) == null);
+        try expectEqual(@as(usize, 1), calls);
+        try expectError(InternalError.UnknownWord, rt.eval("nope/one"));
+    }
+
+    /// Add `word` to the dictionary under `name`, shadowing (but not
+    /// replacing) any existing definitions of that name.
+    pub fn define_word(self: *Self, name: []const u8, word: *Types.HeapedWord) !void {
+        try self.guard_not_frozen();
+        const identifier = (try self.get_or_put_symbol(name)).value_ptr;
+        const words = try self.dictionary_entry_for(identifier);
+        const new_name = words.len() == 0;
+        try word.increment();
+        try words.append(word);
+
+        if (self.observers.items.len == 0) return;
+        if (new_name) {
+            if (vocabulary.vocabulary_of(name)) |vocab| {
+                if (!self.has_other_words_in(vocab, identifier)) self.notify(.{ .VocabularyCreated = vocab });
+            }
+        }
+        self.notify(.{ .Defined = .{ .name = name, .word = word } });
+    }
+
+    /// Whether anything but `except` is defined into `vocab` here. This
+    /// walks the whole dictionary, so is only worth asking when observers
+    /// want to know.
+    fn has_other_words_in(self: *Self, vocab: []const u8, except: *Types.HeapedSymbol) bool {
+        var dictionary_iter = self.dictionary.iterator();
+        while (dictionary_iter.next()) |entry| {
+            if (entry.key_ptr.* == except) continue;
+            const other = vocabulary.vocabulary_of(entry.key_ptr.*.value.?) orelse continue;
+            if (std.mem.eql(u8, other, vocab)) return true;
+        }
+        return false;
+    }
+
+    /// Replace every definition of `name` here (though not in our base) with
+    /// `words`, oldest first, as though they'd been defined in that order
+    /// all along. See image.zig.
+    pub fn replace_definitions(self: *Self, name: []const u8, words: []const *Types.HeapedWord) !void {
+        try self.guard_not_frozen();
+        const identifier = (try self.get_or_put_symbol(name)).value_ptr;
+        const existing = try self.dictionary_entry_for(identifier);
+
+        var replacement = WordList.init(self.alloc);
+        errdefer replacement.deinit(self.alloc);
+        try replacement.contents.ensureTotalCapacity(words.len);
+        for (words) |word| {
+            try word.increment();
+            replacement.contents.appendAssumeCapacity(word);
+        }
+
+        // Words in both lists stay alive through the swap, having just been
+        // referenced again.
+        var replaced = existing.*;
+        existing.* = replacement;
+        replaced.deinit(self.alloc);
+        self.dictionary_generation +%= 1;
+    }
+
+    /// Define each of `table`'s `.{ name, function }` pairs as a primitive
+    /// word, as each of the *_words.zig files does with its ALL.
+    // TODO: signatures, once there's a sane way to declare them from Zig.
+    pub fn install_primitives(self: *Self, comptime table: anytype) !void {
+        inline for (table) |entry| {
+            const word = try self.word_from_primitive_impl(&entry[1], null);
+            errdefer self.alloc.destroy(word);
+            try self.define_word(entry[0], word);
+        }
+    }
+
+    /// Define a host (Zig, or anything speaking the C ABI through a Zig shim)
+    /// function as a word named `name`, or `vocab/name` if a vocabulary is
+    /// given. This is the front door for embedders wanting domain-specific
+    /// words.
+    pub fn define_primitive_word(
+        self: *Self,
+        vocab: ?[]const u8,
+        name: []const u8,
+        impl: PrimitiveImplementation,
+    ) !void {
+        // Checked up front so we don't leak a word nobody will ever own.
+        try self.guard_not_frozen();
+        if (name.len == 0) return InternalError.InvalidWordName;
+
+        const word = try self.word_from_primitive_impl(impl, null);
+        // Until it's defined, nothing else knows of the word, which has
+        // nothing of its own to release.
+        errdefer self.alloc.destroy(word);
+        try self.define_word_in_vocabulary(vocab, name, word);
+    }
+
+    /// As with define_primitive_word, but the word carries `context` along
+    /// with it, which is handed back to `call` on each run. See HostClosure
+    /// for the (important!) lifetime caveats.
+    pub fn define_host_closure_word(
+        self: *Self,
+        vocab: ?[]const u8,
+        name: []const u8,
+        context: ?*anyopaque,
+        call: *const HostClosureFn,
+    ) !void {
+        try self.guard_not_frozen();
+        if (name.len == 0) return InternalError.InvalidWordName;
+
+        const word = try self.send_word_to_heap(Word.new_host_closure_untagged(.{
+            .context = context,
+            .call = call,
+        }, null));
+        errdefer self.alloc.destroy(word);
+        try self.define_word_in_vocabulary(vocab, name, word);
+    }
+
+    fn define_word_in_vocabulary(
+        self: *Self,
+        vocab: ?[]const u8,
+        name: []const u8,
+        word: *Types.HeapedWord,
+    ) !void {
+        if (vocab) |vocab_name| {
+            const qualified = try std.fmt.allocPrint(self.alloc, "{s}{c}{s}", .{
+                vocab_name,
+                vocabulary.VOCABULARY_SEPARATOR,
+                name,
+            });
+            // define_word interns (and thus copies) the name.
+            defer self.alloc.free(qualified);
+            return try self.define_word(qualified, word);
+        }
+
+        try self.define_word(name, word);
+    }
+
+    test "define_primitive_word and define_host_closure_word" {
+        const Host = struct {
+            fn answer(rt: *Runtime) anyerror!void {
+                try rt.stack_push_uint(42);
+            }
+
+            fn count(context: ?*anyopaque, rt: *Runtime) anyerror!void {
+                const counter = @ptrCast(*usize, @alignCast(@alignOf(usize), context.?));
+                counter.* += 1;
+                try rt.stack_push_uint(counter.*);
+            }
+        };
+
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit_guard_for_empty_stack();
+
+        var counter: usize = 0;
+        try rt.define_primitive_word("host", "answer", &Host.answer);
+        try rt.define_host_closure_word(null, "count", &counter, &Host.count);
+
+        try rt.eval("host/answer count count");
+        try expectEqual(@as(usize, 2), counter);
+        try expectEqual(@as(usize, 2), (try rt.stack_pop()).UnsignedInt);
+        try expectEqual(@as(usize, 1), (try rt.stack_pop()).UnsignedInt);
+        try expectEqual(@as(usize, 42), (try rt.stack_pop()).UnsignedInt);
+
+        try expectError(InternalError.InvalidWordName, rt.define_primitive_word(null, "", &Host.answer));
+
+        // Words which fail to be defined (here, for want of memory to intern
+        // their names in) are freed again, or the testing allocator would
+        // catch them leaking.
+        const alloc = rt.alloc;
+        defer rt.alloc = alloc;
+        var failing = std.testing.FailingAllocator.init(alloc, 1);
+        rt.alloc = failing.allocator();
+        try expectError(error.OutOfMemory, rt.define_primitive_word(null, "unstored", &Host.answer));
+        failing = std.testing.FailingAllocator.init(alloc, 1);
+        try expectError(error.OutOfMemory, rt.define_host_closure_word(null, "unstored", &counter, &Host.count));
+    }
+
+    /// Find (or create) the WordList for `identifier`, which must be an
+    /// interned symbol. The dictionary takes its own reference to the
+    /// identifier only when the entry is first created.
+    fn dictionary_entry_for(self: *Self, identifier: *Types.HeapedSymbol) !*WordList {
+        try self.guard_not_frozen();
+
+        var dict_entry = try self.dictionary.getOrPut(identifier);
+
+        if (!dict_entry.found_existing) {
+            try identifier.increment();
+            dict_entry.value_ptr.* = WordList.init(self.alloc);
+            // A name new to the dictionary can still shadow whatever call
+            // sites found for it through the search order.
+            if (self.search_order.items.len > 0 or self.current_vocabulary != null) {
+                self.dictionary_generation +%= 1;
+            }
+        } else {
+            // Shadowing an existing definition: any cached call sites may now
+            // be pointing at the wrong thing.
+            self.dictionary_generation +%= 1;
+        }
+
+        return dict_entry.value_ptr;
+    }
+
+    // Right now, Zig doesn't have a way to narrow `targets` type from anytype,
+    // which is super disappointing, but being brainstormed on:
+    // https://github.com/ziglang/zig/issues/5404
+    //
+    // `identifier` must have come from get_or_put_symbol: the dictionary keys
+    // off of interned symbols' addresses.
+    pub fn define_word_va(self: *Self, identifier: *Types.HeapedSymbol, targets: anytype) !void {
+        const words = try self.dictionary_entry_for(identifier);
+
+        const compound_storage = try self.alloc.alloc(*Types.HeapedWord, targets.len);
+        inline for (targets) |target, idx| compound_storage[idx] = target;
+
+        // TODO WARNING: For now, this always makes invalid words (without a
+        // signature). Need to figure out the correct way to plumb signatures
+        // here given that the runtime will be using a builder pattern to
+        // attach them.
+        var heap_for_word = try self.word_from_compound_impl(compound_storage, null);
+
+        // TODO should this increment actually be stashed away in a dictionary
+        // helper method somewhere? should there be a
+        // Runtime.unstacked_word_from_compound_impl that handles the increment
+        // for us (using Rc.init_referenced) since we can't rely on
+        // Stack.do_push's implicit increment?
+        try heap_for_word.increment();
+
+        try words.append(heap_for_word);
+    }
+
+    pub fn priv_space_set_byte(self: *Self, member: u8, value: u8) InternalError!void {
+        return switch (member) {
+            0 => self.private_space.interpreter_mode = @intToEnum(InterpreterMode, value),
+            else => InternalError.ValueError,
+        };
+    }
+
+    test "priv_space_set_byte" {
+        var rt = try Self.init(testAllocator);
+        defer rt.deinit();
+        try expectEqual(@as(u8, 0), @enumToInt(rt.private_space.interpreter_mode));
+        try rt.priv_space_set_byte(0, 1);
+        try expectEqual(@as(u8, 1), @enumToInt(rt.private_space.interpreter_mode));
+    }
+
+    pub fn stack_peek(self: *Self) !*Object {
+        return self.stack.do_peek();
+    }
+
+    pub fn stack_peek_pair(self: *Self) !Types.PeekPair {
+        return self.stack.do_peek_pair();
+    }
+
+    pub fn stack_peek_trio(self: *Self) !Types.PeekTrio {
+        return self.stack.do_peek_trio();
+    }
+
+    /// Remove the top item from the stack and return it. If there are no
+    /// contents remaining, a StackManipulationError.Underflow is raised.
+    pub fn stack_pop(self: *Self) !Object {
+        const popped = try self.stack.do_pop();
+        self.stack = popped.now_top_stack;
+        self.counters.pops += 1;
+        self.notify(.{ .Pop = &popped.item });
+        return popped.item;
+    }
+
+    /// Remove the top item from the stack, move it to the heap, and return the
+    /// new address to the data. Use `release_heaped_object_reference` to later
+    /// drop a reference to this data (and, if applicable, collect the
+    /// garbage). If there are no contents remaining, a
     /// StackManipulationError.Underflow is raised.
     pub fn stack_pop_to_heap(self: *Self) !*Object {
         const popped = try self.stack.do_pop();
@@ -480,6 +3326,8 @@ pub const Runtime = struct {
         errdefer self.alloc.destroy(banish_target);
         banish_target.* = popped.item;
         self.stack = popped.now_top_stack;
+        self.counters.pops += 1;
+        self.notify(.{ .Pop = banish_target });
         return banish_target;
     }
 
@@ -490,6 +3338,9 @@ pub const Runtime = struct {
     pub fn stack_pop_pair(self: *Self) !Types.PopPairExternal {
         const popped = try self.stack.do_pop_pair();
         self.stack = popped.now_top_stack;
+        self.counters.pops += 2;
+        self.notify(.{ .Pop = &popped.near });
+        self.notify(.{ .Pop = &popped.far });
         return Types.PopPairExternal{
             .near = popped.near,
             .far = popped.far,
@@ -503,6 +3354,10 @@ pub const Runtime = struct {
     pub fn stack_pop_trio(self: *Self) !Types.PopTrioExternal {
         const popped = try self.stack.do_pop_trio();
         self.stack = popped.now_top_stack;
+        self.counters.pops += 3;
+        self.notify(.{ .Pop = &popped.near });
+        self.notify(.{ .Pop = &popped.far });
+        self.notify(.{ .Pop = &popped.farther });
         return Types.PopTrioExternal{
             .near = popped.near,
             .far = popped.far,
@@ -510,20 +3365,61 @@ pub const Runtime = struct {
         };
     }
 
+    /// Take an O(1), copy-on-write snapshot of the stack as it stands right
+    /// now, suitable for rolling back to later with `stack_restore`. See
+    /// `Stack.snapshot` for the nitty-gritty.
+    pub fn stack_snapshot(self: *Self) !Stack.Snapshot {
+        return try self.stack.snapshot();
+    }
+
+    /// Throw away the current stack in favour of one previously captured with
+    /// `stack_snapshot`, consuming the snapshot in the process.
+    pub fn stack_restore(self: *Self, snap: Stack.Snapshot) void {
+        self.stack = self.stack.restore(snap);
+    }
+
+    /// Convert a Zig value to an Object (see conversion.zig) and push it.
+    pub fn stack_push_value(self: *Self, value: anytype) !void {
+        const obj = try conversion.to_object(self, value);
+        self.stack = self.stack.do_push(obj) catch |err| {
+            // Nothing holds a reference to obj yet, so take one only to drop
+            // it, freeing whatever to_object built.
+            var owned = obj.ref() catch unreachable;
+            self.release_heaped_object_reference(&owned);
+            return err;
+        };
+        self.notify_push();
+    }
+
+    /// Pop the top Object and convert it to a Zig value of type T (see
+    /// conversion.zig), releasing the Object. If the conversion fails, the
+    /// Object is left on the stack. The result is owned by the caller: see
+    /// conversion.free_value.
+    pub fn stack_pop_value(self: *Self, comptime T: type) !T {
+        const value = try conversion.from_object(T, self.alloc, (try self.stack_peek()).*);
+        var popped = try self.stack_pop();
+        self.release_heaped_object_reference(&popped);
+        return value;
+    }
+
     pub fn stack_push_array(self: *Self, value: *Types.HeapedArray) !void {
         self.stack = try self.stack.do_push_array(value);
+        self.notify_push();
     }
 
     pub fn stack_push_bool(self: *Self, value: bool) !void {
         self.stack = try self.stack.do_push_bool(value);
+        self.notify_push();
     }
 
     pub fn stack_push_float(self: *Self, value: f64) !void {
         self.stack = try self.stack.do_push_float(value);
+        self.notify_push();
     }
 
     pub fn stack_push_sint(self: *Self, value: isize) !void {
         self.stack = try self.stack.do_push_sint(value);
+        self.notify_push();
     }
 
     /// Push a HeapedString to the stack by reference. As this string is
@@ -532,6 +3428,7 @@ pub const Runtime = struct {
     /// reaching this point.
     pub fn stack_push_string(self: *Self, value: *Types.HeapedString) !void {
         self.stack = try self.stack.do_push_string(value);
+        self.notify_push();
     }
 
     /// Push a HeapedSymbol to the stack by reference. As this symbol is
@@ -540,14 +3437,38 @@ pub const Runtime = struct {
     /// reaching this point.
     pub fn stack_push_symbol(self: *Self, value: *Types.HeapedSymbol) !void {
         self.stack = try self.stack.do_push_symbol(value);
+        self.notify_push();
     }
 
     pub fn stack_push_uint(self: *Self, value: usize) !void {
         self.stack = try self.stack.do_push_uint(value);
+        self.notify_push();
+    }
+
+    /// Push an Object of any kind, taking a new reference to it.
+    pub fn stack_push_object(self: *Self, value: Object) !void {
+        self.stack = try self.stack.do_push(value);
+        self.notify_push();
+    }
+
+    pub fn stack_push_channel(self: *Self, value: *Types.HeapedChannel) !void {
+        self.stack = try self.stack.do_push(.{ .Channel = value });
+        self.notify_push();
+    }
+
+    pub fn stack_push_map(self: *Self, value: *Types.HeapedMap) !void {
+        self.stack = try self.stack.do_push(.{ .Map = value });
+        self.notify_push();
+    }
+
+    pub fn stack_push_set(self: *Self, value: *Types.HeapedSet) !void {
+        self.stack = try self.stack.do_push(.{ .Set = value });
+        self.notify_push();
     }
 
     pub fn stack_push_raw_word(self: *Self, value: *Types.HeapedWord) !void {
         self.stack = try self.stack.do_push_word(value);
+        self.notify_push();
     }
 
     pub const StackWranglingOperation = enum {
@@ -562,12 +3483,22 @@ pub const Runtime = struct {
     // TODO: return type?
     pub fn stack_wrangle(self: *Self, operation: StackWranglingOperation) !void {
         switch (operation) {
-            .DropTopObject => self.stack = try self.stack.do_drop(),
+            // By way of stack_pop, so that observers see it go.
+            .DropTopObject => {
+                var dropped = try self.stack_pop();
+                self.release_heaped_object_reference(&dropped);
+            },
 
-            .DuplicateTopObject => self.stack = try self.stack.do_dup(),
-            .DuplicateTopTwoObjectsShuffled => self.stack = try self.stack.do_2dupshuf(),
+            .DuplicateTopObject => {
+                self.stack = try self.stack.do_dup();
+                self.notify_push();
+            },
+            .DuplicateTopTwoObjectsShuffled => {
+                self.stack = try self.stack.do_2dupshuf();
+                self.notify_pushes(2);
+            },
 
-            .SwapTopTwoObjects => try self.stack.do_swap(),
+            .SwapTopTwoObjects => self.stack = try self.stack.do_swap(),
         }
     }
 };
@@ -612,14 +3543,7 @@ test "Runtime.eval: integration" {
     try expectEqualStrings("foo and a bit of bar", foo_str.String.value.?);
 
     var something_symbol = try rt.stack_pop();
-    defer {
-        // TODO: uncomment this once Runtime.get_or_put_symbol is fixed to
-        // increment refcount correctly, this *should* be leaking RAM as-is but
-        // is not, unearthing a whole class of bugs (5 addresses leaking in 1
-        // test in libgale alone)
-        //
-        // rt.release_heaped_object_reference(&something_symbol);
-    }
+    defer rt.release_heaped_object_reference(&something_symbol);
     try expectEqualStrings("something", something_symbol.Symbol.value.?);
 
     var inferunsigned_float_signed = try rt.stack_pop_trio();
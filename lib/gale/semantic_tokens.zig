@@ -0,0 +1,240 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualSlices = std.testing.expectEqualSlices;
+const expectError = std.testing.expectError;
+
+const tags = @import("./tags.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Lexer = @import("./lexer.zig").Lexer;
+const ParsedWord = @import("./parsed_word.zig").ParsedWord;
+const pipeline = @import("./pipeline.zig");
+
+// What each span of a source is, for syntax highlighting: the Lexer's
+// Tokens, sorted into the handful of categories an editor colours
+// differently, with definitions picked out as tags.zig finds them. Words
+// are highlighted as written, not as they'd resolve: telling a word from a
+// typo takes a Runtime, which is the linter's business.
+
+pub const Category = enum {
+    Comment,
+    Number,
+    String,
+    /// Anything else eval would run or push: words, references, Symbols.
+    Word,
+    /// Where a word (or a shape's member) is named by its definition.
+    Definition,
+    /// Where a shape is named by its definition.
+    Shape,
+    /// The `|>` of pipelines, which eval drops rather than runs.
+    Operator,
+
+    pub fn name(self: Category) []const u8 {
+        return switch (self) {
+            .Comment => "comment",
+            .Number => "number",
+            .String => "string",
+            .Word => "word",
+            .Definition => "definition",
+            .Shape => "shape",
+            .Operator => "operator",
+        };
+    }
+
+    /// Where this is in LSP_TOKEN_TYPES.
+    pub fn lsp_type(self: Category) u32 {
+        return switch (self) {
+            .Comment => 0,
+            .Number => 1,
+            .String => 2,
+            .Word, .Definition => 3,
+            .Shape => 4,
+            .Operator => 5,
+        };
+    }
+
+    /// A bitset over LSP_TOKEN_MODIFIERS.
+    pub fn lsp_modifiers(self: Category) u32 {
+        return if (self == .Definition) 1 else 0;
+    }
+};
+
+/// The legend a language server should advertise for encode_lsp's data,
+/// in terms of LSP's predefined token types and modifiers.
+pub const LSP_TOKEN_TYPES = [_][]const u8{ "comment", "number", "string", "function", "type", "operator" };
+pub const LSP_TOKEN_MODIFIERS = [_][]const u8{"definition"};
+
+pub const SemanticToken = struct {
+    category: Category,
+    /// Where the span starts in the source, and how many bytes it runs for.
+    offset: usize,
+    len: usize,
+    /// 1-based, in lines and bytes respectively, as with Tokens.
+    line: usize,
+    column: usize,
+};
+
+/// Every span of `source` but whitespace, categorized, in order. The caller
+/// owns the result. Unterminated Strings and block comments are a
+/// ValueError, as they are to the Lexer.
+pub fn classify(alloc: Allocator, source: []const u8) ![]SemanticToken {
+    var found = std.ArrayList(SemanticToken).init(alloc);
+    errdefer found.deinit();
+
+    var definitions = try tags.scan(alloc, source);
+    defer definitions.deinit();
+
+    // By where they start in the source: Tags and Tokens agree on that much.
+    var defined = std.AutoHashMap(usize, Category).init(alloc);
+    defer defined.deinit();
+    for (definitions.items) |tag| {
+        const category: Category = switch (tag.kind) {
+            .Word, .Member => .Definition,
+            .Shape => .Shape,
+            // Found at its first word, which already has a Tag of its own.
+            .Vocabulary => continue,
+        };
+        try defined.put(tag.line_offset + tag.column - 1, category);
+    }
+
+    var lexer = Lexer.init(source);
+    while (try lexer.next()) |token| {
+        const category: Category = switch (token.kind) {
+            .Newline => continue,
+            .LineComment, .BlockComment => .Comment,
+            .String => .String,
+            .Word => defined.get(token.offset) orelse category_of_word(token.raw),
+        };
+
+        try found.append(.{
+            .category = category,
+            .offset = token.offset,
+            .len = token.raw.len,
+            .line = token.line,
+            .column = token.column,
+        });
+    }
+
+    return found.toOwnedSlice();
+}
+
+fn category_of_word(raw: []const u8) Category {
+    if (std.mem.eql(u8, raw, pipeline.OPERATOR)) return .Operator;
+    const parsed = ParsedWord.from_input(raw) catch return .Word;
+    return switch (parsed) {
+        .NumFloat, .SignedInt, .UnsignedInt => .Number,
+        else => .Word,
+    };
+}
+
+/// `tokens`, classified from `source`, as the LSP's semantic tokens data:
+/// five integers per token (line and start delta, length, type, and
+/// modifiers), with lines counted from 0 and characters in UTF-16 code
+/// units. Tokens spanning lines (block comments, Strings) are split at each
+/// line break, since not every client can take them whole. The caller owns
+/// the result.
+pub fn encode_lsp(alloc: Allocator, source: []const u8, tokens: []const SemanticToken) ![]u32 {
+    var data = std.ArrayList(u32).init(alloc);
+    errdefer data.deinit();
+
+    var prev_line: usize = 0;
+    var prev_start: usize = 0;
+
+    for (tokens) |token| {
+        const end = token.offset + token.len;
+        var line = token.line - 1;
+        var line_start = token.offset - (token.column - 1);
+        var start = token.offset;
+
+        while (start < end) {
+            const piece_end = std.mem.indexOfScalarPos(u8, source[0..end], start, '\n') orelse end;
+
+            if (piece_end > start) {
+                const char = utf16_len(source[line_start..start]);
+                try data.appendSlice(&[_]u32{
+                    @intCast(u32, line - prev_line),
+                    @intCast(u32, if (line == prev_line) char - prev_start else char),
+                    @intCast(u32, utf16_len(source[start..piece_end])),
+                    token.category.lsp_type(),
+                    token.category.lsp_modifiers(),
+                });
+                prev_line = line;
+                prev_start = char;
+            }
+
+            start = piece_end + 1;
+            line_start = start;
+            line += 1;
+        }
+    }
+
+    return data.toOwnedSlice();
+}
+
+/// How long `text` is in UTF-16 code units, or failing that (it isn't
+/// UTF-8), in bytes.
+fn utf16_len(text: []const u8) usize {
+    return std.unicode.calcUtf16LeLen(text) catch text.len;
+}
+
+test "classify" {
+    const source = "// hi\n1 -2.5 \"a b\" :twin @DEFINE\n$ Point $: x ; twin";
+    const tokens = try classify(testAllocator, source);
+    defer testAllocator.free(tokens);
+
+    var categories: [12]Category = undefined;
+    try expectEqual(@as(usize, categories.len), tokens.len);
+    for (tokens) |token, idx| categories[idx] = token.category;
+    try expectEqualSlices(Category, &.{
+        .Comment, .Number, .Number, .String,     .Definition, .Word,
+        .Word,    .Shape,  .Word,   .Definition, .Word,       .Word,
+    }, &categories);
+
+    // :twin, as written.
+    try expectEqual(@as(usize, 2), tokens[4].line);
+    try expectEqual(@as(usize, 14), tokens[4].column);
+    try expectEqual(@as(usize, 5), tokens[4].len);
+
+    const piped = try classify(testAllocator, "3 |> dup");
+    defer testAllocator.free(piped);
+    try expectEqual(Category.Operator, piped[1].category);
+
+    try expectError(InternalError.ValueError, classify(testAllocator, "\"unterminated"));
+}
+
+test "encode_lsp" {
+    const source = "1 { a\nbé } @DUP\n\"ü\" x";
+    const tokens = try classify(testAllocator, source);
+    defer testAllocator.free(tokens);
+
+    const data = try encode_lsp(testAllocator, source, tokens);
+    defer testAllocator.free(data);
+
+    try expectEqualSlices(u32, &.{
+        0, 0, 1, 1, 0, // 1
+        0, 2, 3, 0, 0, // { a
+        1, 0, 4, 0, 0, // bé }, two bytes but one code unit for the é
+        0, 5, 4, 3, 0, // @DUP
+        1, 0, 3, 2, 0, // "ü"
+        0, 4, 1, 3, 0, // x
+    }, data);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
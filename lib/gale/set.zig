@@ -0,0 +1,154 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+
+/// A collection of distinct Objects, which remembers the order they were
+/// first added in, as Maps do their keys. Membership is by Object.eql, and
+/// only Objects which hash dependably can be members: Booleans, integers,
+/// Strings, and Symbols. Floats can't (NaN is never equal even to itself,
+/// and 0.0 is equal to -0.0 for all their bits differ), nor can anything
+/// else on the heap, which either compares by identity or can change from
+/// under the Set. Trying to add them is a TypeError.
+///
+/// As with Maps, Sets aren't synchronized. See set_words.zig for how they're
+/// added to without disturbing anyone else holding them.
+pub const Set = struct {
+    const Self = @This();
+
+    /// We hold a reference to each of the members.
+    members: std.ArrayHashMapUnmanaged(Object, void, Context, true),
+
+    const Context = struct {
+        pub fn hash(_: Context, key: Object) u32 {
+            var hasher = std.hash.Wyhash.init(0);
+            std.hash.autoHash(&hasher, std.meta.activeTag(key));
+            switch (key) {
+                .Boolean => |val| std.hash.autoHash(&hasher, val),
+                .SignedInt => |val| std.hash.autoHash(&hasher, val),
+                .UnsignedInt => |val| std.hash.autoHash(&hasher, val),
+                .String => |str| hasher.update(str.value.?),
+                .Symbol => |sym| hasher.update(sym.value.?),
+                // Never let in to begin with: see is_hashable.
+                else => unreachable,
+            }
+            return @truncate(u32, hasher.final());
+        }
+
+        pub fn eql(_: Context, a: Object, b: Object, _: usize) bool {
+            return a.eql(b);
+        }
+    };
+
+    pub fn init() Self {
+        return Self{ .members = .{} };
+    }
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        for (self.members.keys()) |*member| member.deinit(alloc);
+        self.members.deinit(alloc);
+    }
+
+    /// Whether `obj` is the kind of Object that can be a member at all.
+    pub fn is_hashable(obj: Object) bool {
+        return switch (obj) {
+            .Boolean, .SignedInt, .String, .Symbol, .UnsignedInt => true,
+            else => false,
+        };
+    }
+
+    pub fn count(self: *const Self) usize {
+        return self.members.count();
+    }
+
+    /// The members, in the order they were first added, without new
+    /// references.
+    pub fn items(self: *const Self) []const Object {
+        return self.members.keys();
+    }
+
+    /// Whether `obj` is a member, which it never is if it isn't hashable.
+    pub fn contains(self: *const Self, obj: Object) bool {
+        return is_hashable(obj) and self.members.contains(obj);
+    }
+
+    /// Add `obj`, taking over its reference, which is released straight
+    /// away should an equal member already be there. Should this fail
+    /// (unhashable Objects being a TypeError), the reference is left with
+    /// the caller.
+    pub fn add(self: *Self, alloc: Allocator, obj: Object) !void {
+        if (!is_hashable(obj)) return InternalError.TypeError;
+
+        const result = try self.members.getOrPut(alloc, obj);
+        if (result.found_existing) {
+            var duplicate = obj;
+            duplicate.deinit(alloc);
+        }
+    }
+
+    /// A copy of this Set, holding references of its own.
+    pub fn clone(self: *const Self, alloc: Allocator) !Self {
+        var copy = Self.init();
+        errdefer copy.deinit(alloc);
+
+        try copy.members.ensureTotalCapacity(alloc, self.count());
+        for (self.items()) |member| copy.members.putAssumeCapacity(try member.ref(), {});
+        return copy;
+    }
+
+    /// Whether both Sets hold the same members, regardless of the order they
+    /// were added in.
+    pub fn eql(self: *const Self, other: *const Self) bool {
+        if (self.count() != other.count()) return false;
+
+        for (self.items()) |member| {
+            if (!other.contains(member)) return false;
+        }
+
+        return true;
+    }
+
+    test "add, contains, clone, and eql" {
+        const alloc = std.testing.allocator;
+
+        var set = Self.init();
+        defer set.deinit(alloc);
+        try set.add(alloc, .{ .UnsignedInt = 1 });
+        try set.add(alloc, .{ .SignedInt = 1 });
+        try set.add(alloc, .{ .UnsignedInt = 1 });
+        try expectError(InternalError.TypeError, set.add(alloc, .{ .Float = 1.0 }));
+
+        try expectEqual(@as(usize, 2), set.count());
+        try expect(set.contains(.{ .UnsignedInt = 1 }));
+        try expect(!set.contains(.{ .UnsignedInt = 2 }));
+        try expect(!set.contains(.{ .Float = 1.0 }));
+        try expectEqual(@as(isize, 1), set.items()[1].SignedInt);
+
+        var other = try set.clone(alloc);
+        defer other.deinit(alloc);
+        try expect(set.eql(&other));
+        try other.add(alloc, .{ .Boolean = false });
+        try expect(!set.eql(&other));
+    }
+};
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,254 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const Set = @import("./set.zig").Set;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+// Sets, as set.zig has them: what can be a member, and what happens when
+// something that can't be is added, is up to it. Nothing here changes a Set
+// anyone else can see, a Set being a value like any other: @SET-ADD changes
+// a Set in place only when the Stack holds the one and only reference to it,
+// and otherwise adds to a copy. The algebra words always build a new Set,
+// ordered by the further Set's members first.
+
+/// Every Set word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@SET-ADD", SET_ADD },
+    .{ "@SET-COUNT", SET_COUNT },
+    .{ "@SET-DIFFERENCE", SET_DIFFERENCE },
+    .{ "@SET-FROM", SET_FROM },
+    .{ "@SET-INTERSECT", SET_INTERSECT },
+    .{ "@SET-MEMBER?", SET_MEMBER_P },
+    .{ "@SET-MEMBERS", SET_MEMBERS },
+    .{ "@SET-NEW", SET_NEW },
+    .{ "@SET-UNION", SET_UNION },
+};
+
+/// Define every Set word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @SET-NEW ( <- Set )
+pub fn SET_NEW(runtime: *Runtime) anyerror!void {
+    try runtime.stack_push_set(try runtime.new_set());
+}
+
+/// @SET-FROM ( Array <- Set )
+///
+/// The distinct members of the Array, in the order each first appears.
+pub fn SET_FROM(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Array) return InternalError.TypeError;
+
+    const result = try runtime.new_set();
+    errdefer runtime.release_set(result);
+    for (peeked.Array.value.?.items) |member| try add_ref(runtime, &result.value.?, member);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_set(result);
+}
+
+/// @SET-MEMBERS ( Set <- Array )
+///
+/// The Set's members, in the order they were first added.
+pub fn SET_MEMBERS(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Set) return InternalError.TypeError;
+    const members = peeked.Set.value.?.items();
+
+    const result = try runtime.new_array(members.len);
+    errdefer runtime.release_array(result);
+    for (members) |member| result.value.?.appendAssumeCapacity(try member.ref());
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_array(result);
+}
+
+/// @SET-COUNT ( Set <- UnsignedInt )
+pub fn SET_COUNT(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Set) return InternalError.TypeError;
+    const count = peeked.Set.value.?.count();
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_uint(count);
+}
+
+/// @SET-ADD ( Set @1 <- Set )
+///
+/// The Set, with the Object as a member (if it wasn't already).
+pub fn SET_ADD(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (far.* != .Set or !Set.is_hashable(peek.near.*)) return InternalError.TypeError;
+
+    if (far.Set.strong_count.load(.Acquire) == 1) {
+        try add_ref(runtime, &far.Set.value.?, peek.near.*);
+        return runtime.stack_wrangle(.DropTopObject);
+    }
+
+    const copy = try runtime.new_set();
+    errdefer runtime.release_set(copy);
+    copy.value = try far.Set.value.?.clone(runtime.alloc);
+    try add_ref(runtime, &copy.value.?, peek.near.*);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_set(copy);
+}
+
+/// @SET-MEMBER? ( Set @1 <- Boolean )
+///
+/// Whether the Object is in the Set. The Set being anything but, or the
+/// Object something that could never be a member, is a TypeError.
+pub fn SET_MEMBER_P(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (far.* != .Set or !Set.is_hashable(peek.near.*)) return InternalError.TypeError;
+    const found = far.Set.value.?.contains(peek.near.*);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_bool(found);
+}
+
+/// @SET-UNION ( Set Set <- Set )
+///
+/// Everything in either Set.
+pub fn SET_UNION(runtime: *Runtime) anyerror!void {
+    try combine(runtime, .Union);
+}
+
+/// @SET-INTERSECT ( Set Set <- Set )
+///
+/// Everything in both Sets.
+pub fn SET_INTERSECT(runtime: *Runtime) anyerror!void {
+    try combine(runtime, .Intersection);
+}
+
+/// @SET-DIFFERENCE ( Set Set <- Set )
+///
+/// Everything in the further Set but not the nearer.
+pub fn SET_DIFFERENCE(runtime: *Runtime) anyerror!void {
+    try combine(runtime, .Difference);
+}
+
+const Operation = enum { Union, Intersection, Difference };
+
+fn combine(runtime: *Runtime, comptime operation: Operation) !void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .Set or far.* != .Set) return InternalError.TypeError;
+    const ours = &far.Set.value.?;
+    const theirs = &peek.near.Set.value.?;
+
+    const result = try runtime.new_set();
+    errdefer runtime.release_set(result);
+
+    for (ours.items()) |member| {
+        const keep = switch (operation) {
+            .Union => true,
+            .Intersection => theirs.contains(member),
+            .Difference => !theirs.contains(member),
+        };
+        if (keep) try add_ref(runtime, &result.value.?, member);
+    }
+    if (operation == .Union) {
+        for (theirs.items()) |member| try add_ref(runtime, &result.value.?, member);
+    }
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_set(result);
+}
+
+/// Add a new reference to `member` to `target`.
+fn add_ref(runtime: *Runtime, target: *Set, member: Object) !void {
+    var owned = try member.ref();
+    target.add(runtime.alloc, owned) catch |err| {
+        runtime.release_heaped_object_reference(&owned);
+        return err;
+    };
+}
+
+fn expect_printed(runtime: *Runtime, source: []const u8, expected: []const u8) !void {
+    try runtime.eval(source);
+    var result = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&result);
+
+    var buf: [64]u8 = undefined;
+    try expectEqualStrings(expected, try std.fmt.bufPrint(&buf, "{}", .{result}));
+}
+
+test "building Sets" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_printed(&runtime, "@SET-NEW \"b\" @SET-ADD :a @SET-ADD \"b\" @SET-ADD", "#{b :a}");
+    try expect_printed(&runtime, "0 6 @ITER-RANGE @ITER-COLLECT 1 @LIT &@BAND @COMPOSE @ARRAY-MAP @SET-FROM", "#{0 1}");
+    try expect_printed(&runtime, "@SET-NEW 3 @SET-ADD 4 @SET-ADD @SET-MEMBERS", "[3 4]");
+
+    try runtime.eval("@SET-NEW +1 @SET-ADD @DUP 1 @SET-MEMBER? @SWAP +1 @SET-MEMBER?");
+    try expect((try runtime.stack_pop()).Boolean);
+    try expect(!(try runtime.stack_pop()).Boolean);
+
+    try expectError(InternalError.TypeError, runtime.eval("@SET-NEW 1.5 @SET-ADD"));
+    try expectError(InternalError.TypeError, runtime.eval("@DROP @SET-MEMBERS @SET-MEMBER?"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test "@SET-ADD leaves shared Sets be" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("@SET-NEW 1 @SET-ADD @DUP 2 @SET-ADD @SET-COUNT");
+    try expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+    try runtime.eval("@SET-COUNT");
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+}
+
+test "Set algebra" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("0 4 @ITER-RANGE @ITER-COLLECT @SET-FROM 2 6 @ITER-RANGE @ITER-COLLECT @SET-FROM");
+    try expect_printed(&runtime, "@2DUPSHUF @SET-UNION", "#{0 1 2 3 4 5}");
+    try expect_printed(&runtime, "@2DUPSHUF @SET-INTERSECT", "#{2 3}");
+    try expect_printed(&runtime, "@2DUPSHUF @SWAP @SET-DIFFERENCE", "#{4 5}");
+    try expect_printed(&runtime, "@SET-DIFFERENCE", "#{0 1}");
+
+    // Equal regardless of order.
+    try runtime.eval("@SET-NEW 1 @SET-ADD 2 @SET-ADD @SET-NEW 2 @SET-ADD 1 @SET-ADD @EQ");
+    try expect((try runtime.stack_pop()).Boolean);
+    try runtime.eval("@DROP @DROP");
+
+    try expectError(InternalError.TypeError, runtime.eval("@SET-NEW 1 @SET-UNION"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
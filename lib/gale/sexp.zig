@@ -0,0 +1,311 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// Converting between S-expression text and Objects. The mapping is:
+//
+// | S-expression         | Object                                          |
+// |----------------------|-------------------------------------------------|
+// | (a list)             | Array                                           |
+// | 0, 1, 2, ...         | UnsignedInt                                     |
+// | -1, -2, ...          | SignedInt                                       |
+// | 1.5, -2e3, ...       | Float                                           |
+// | "string"             | String, with \" \\ \n \t and \r escapes         |
+// | #t, #f               | Boolean                                         |
+// | any other atom       | Symbol                                          |
+//
+// Comments run from a ; to the end of the line. There's no quoting (no 'x
+// or `x) and no dotted pairs: this is a notation for data, not a Lisp.
+//
+// Going the other way, Symbols whose names wouldn't read back as Symbols
+// (containing whitespace, say, or looking like a number) are a ValueError,
+// as are NaNs and infinities. Maps, Sets, Words, Cells, Channels, and Opaques
+// have no S-expression form and are a TypeError.
+
+/// How deeply lists may nest, either way, before we give up with a
+/// LimitExceeded rather than risk running out of (Zig) stack.
+// TODO: configurable in build.zig
+pub const MAX_NESTING = 512;
+
+/// Parse `text` as a single S-expression (surrounded by as much whitespace
+/// and as many comments as it likes). Malformed text is a ValueError. As
+/// with anything fresh off the heap, the result holds no references yet.
+pub fn parse(rt: *Runtime, text: []const u8) !Object {
+    var reader = Reader{ .rt = rt, .text = text, .pos = 0 };
+
+    reader.skip_blank();
+    const obj = try reader.datum(0);
+    reader.skip_blank();
+
+    if (reader.pos != text.len) {
+        var owned = try obj.ref();
+        rt.release_heaped_object_reference(&owned);
+        return InternalError.ValueError;
+    }
+
+    return obj;
+}
+
+const Reader = struct {
+    const Self = @This();
+
+    rt: *Runtime,
+    text: []const u8,
+    pos: usize,
+
+    fn datum(self: *Self, depth: usize) anyerror!Object {
+        if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+        if (self.pos >= self.text.len) return InternalError.ValueError;
+
+        return switch (self.text[self.pos]) {
+            '(' => try self.list(depth),
+            '"' => try self.string(),
+            ')' => InternalError.ValueError,
+            else => try self.atom(),
+        };
+    }
+
+    fn list(self: *Self, depth: usize) !Object {
+        self.pos += 1;
+
+        const array = try self.rt.new_array(0);
+        errdefer self.rt.release_array(array);
+
+        while (true) {
+            self.skip_blank();
+            if (self.pos >= self.text.len) return InternalError.ValueError;
+            if (self.text[self.pos] == ')') {
+                self.pos += 1;
+                break;
+            }
+
+            var member = try (try self.datum(depth + 1)).ref();
+            errdefer member.deinit(self.rt.alloc);
+            try array.value.?.append(member);
+        }
+
+        return Object{ .Array = array };
+    }
+
+    fn string(self: *Self) !Object {
+        self.pos += 1;
+
+        var out = std.ArrayList(u8).init(self.rt.alloc);
+        defer out.deinit();
+
+        while (true) {
+            if (self.pos >= self.text.len) return InternalError.ValueError;
+            const chr = self.text[self.pos];
+            self.pos += 1;
+
+            switch (chr) {
+                '"' => break,
+                '\\' => {
+                    if (self.pos >= self.text.len) return InternalError.ValueError;
+                    const escaped = self.text[self.pos];
+                    self.pos += 1;
+                    try out.append(switch (escaped) {
+                        '"', '\\' => escaped,
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        else => return InternalError.ValueError,
+                    });
+                },
+                else => try out.append(chr),
+            }
+        }
+
+        return Object{ .String = (try self.rt.get_or_put_string(out.items)).value_ptr };
+    }
+
+    fn atom(self: *Self) !Object {
+        const start = self.pos;
+        while (self.pos < self.text.len and !is_delimiter(self.text[self.pos])) self.pos += 1;
+        const token = self.text[start..self.pos];
+
+        if (std.mem.eql(u8, token, "#t")) return Object{ .Boolean = true };
+        if (std.mem.eql(u8, token, "#f")) return Object{ .Boolean = false };
+        if (try number(token)) |num| return num;
+
+        return Object{ .Symbol = (try self.rt.get_or_put_symbol(token)).value_ptr };
+    }
+
+    fn skip_blank(self: *Self) void {
+        while (self.pos < self.text.len) {
+            switch (self.text[self.pos]) {
+                ' ', '\t', '\r', '\n' => self.pos += 1,
+                ';' => {
+                    while (self.pos < self.text.len and self.text[self.pos] != '\n') self.pos += 1;
+                },
+                else => return,
+            }
+        }
+    }
+};
+
+fn is_delimiter(chr: u8) bool {
+    return switch (chr) {
+        ' ', '\t', '\r', '\n', '(', ')', '"', ';' => true,
+        else => false,
+    };
+}
+
+/// Whether `token` starts out looking like a number (and so must be one,
+/// rather than a Symbol).
+fn looks_numeric(token: []const u8) bool {
+    const unsigned = if (token.len > 1 and (token[0] == '-' or token[0] == '+')) token[1..] else token;
+    if (unsigned.len == 0) return false;
+    return std.ascii.isDigit(unsigned[0]) or (unsigned[0] == '.' and unsigned.len > 1 and std.ascii.isDigit(unsigned[1]));
+}
+
+fn number(token: []const u8) !?Object {
+    if (!looks_numeric(token)) return null;
+
+    if (std.mem.indexOfAny(u8, token, ".eE") == null) {
+        const value = std.fmt.parseInt(i128, token, 10) catch return InternalError.ValueError;
+        return if (value < 0)
+            Object{ .SignedInt = std.math.cast(isize, value) orelse return InternalError.ValueError }
+        else
+            Object{ .UnsignedInt = std.math.cast(usize, value) orelse return InternalError.ValueError };
+    }
+
+    return Object{ .Float = std.fmt.parseFloat(f64, token) catch return InternalError.ValueError };
+}
+
+/// Write `obj` as an S-expression, with single spaces between list members.
+pub fn write(writer: anytype, obj: Object) !void {
+    try write_datum(writer, obj, 0);
+}
+
+fn write_datum(writer: anytype, obj: Object, depth: usize) anyerror!void {
+    if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+
+    switch (obj) {
+        .Array => |array| {
+            try writer.writeByte('(');
+            for (array.value.?.items) |member, idx| {
+                if (idx > 0) try writer.writeByte(' ');
+                try write_datum(writer, member, depth + 1);
+            }
+            try writer.writeByte(')');
+        },
+        .Boolean => |val| try writer.writeAll(if (val) "#t" else "#f"),
+        .Float => |val| {
+            if (std.math.isNan(val) or std.math.isInf(val)) return InternalError.ValueError;
+
+            // Floats which happen to be whole numbers still need to read
+            // back as Floats.
+            var buf: [64]u8 = undefined;
+            const formatted = std.fmt.bufPrint(&buf, "{d}", .{val}) catch return InternalError.ValueError;
+            try writer.writeAll(formatted);
+            if (std.mem.indexOfAny(u8, formatted, ".eE") == null) try writer.writeAll(".0");
+        },
+        .SignedInt => |val| try writer.print("{d}", .{val}),
+        .UnsignedInt => |val| try writer.print("{d}", .{val}),
+        .String => |str| {
+            try writer.writeByte('"');
+            for (str.value.?) |chr| {
+                switch (chr) {
+                    '"' => try writer.writeAll("\\\""),
+                    '\\' => try writer.writeAll("\\\\"),
+                    '\n' => try writer.writeAll("\\n"),
+                    '\t' => try writer.writeAll("\\t"),
+                    '\r' => try writer.writeAll("\\r"),
+                    else => try writer.writeByte(chr),
+                }
+            }
+            try writer.writeByte('"');
+        },
+        .Symbol => |sym| {
+            const name = sym.value.?;
+            if (name.len == 0 or looks_numeric(name) or std.mem.eql(u8, name, "#t") or std.mem.eql(u8, name, "#f")) {
+                return InternalError.ValueError;
+            }
+            for (name) |chr| {
+                if (is_delimiter(chr)) return InternalError.ValueError;
+            }
+            try writer.writeAll(name);
+        },
+        .Cell, .Channel, .Map, .Opaque, .Set, .Word => return InternalError.TypeError,
+    }
+}
+
+test "parse" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var obj = try (try parse(&rt,
+        \\ ; A comment.
+        \\ (define (point 1 -2 3.5) "say \"hi\"" #t (nested ()))
+    )).ref();
+    defer rt.release_heaped_object_reference(&obj);
+
+    const items = obj.Array.value.?.items;
+    try expectEqual(@as(usize, 5), items.len);
+    try expectEqualStrings("define", items[0].Symbol.value.?);
+
+    const point = items[1].Array.value.?.items;
+    try expectEqualStrings("point", point[0].Symbol.value.?);
+    try expectEqual(@as(usize, 1), point[1].UnsignedInt);
+    try expectEqual(@as(isize, -2), point[2].SignedInt);
+    try expectEqual(@as(f64, 3.5), point[3].Float);
+
+    try expectEqualStrings("say \"hi\"", items[2].String.value.?);
+    try expect(items[3].Boolean);
+    try expectEqual(@as(usize, 0), items[4].Array.value.?.items[1].Array.value.?.items.len);
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+    try write(out.writer(), obj);
+    try expectEqualStrings("(define (point 1 -2 3.5) \"say \\\"hi\\\"\" #t (nested ()))", out.items);
+}
+
+test "parse: malformed" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try expectError(InternalError.ValueError, parse(&rt, "(a b"));
+    try expectError(InternalError.ValueError, parse(&rt, "a)"));
+    try expectError(InternalError.ValueError, parse(&rt, "a b"));
+    try expectError(InternalError.ValueError, parse(&rt, "\"open"));
+    try expectError(InternalError.ValueError, parse(&rt, "12abc"));
+    try expectError(InternalError.ValueError, parse(&rt, ""));
+
+    const deep = "(" ** (MAX_NESTING + 2) ++ ")" ** (MAX_NESTING + 2);
+    try expectError(InternalError.LimitExceeded, parse(&rt, deep));
+}
+
+test "write" {
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    try write(out.writer(), Object{ .Float = 2 });
+    try expectEqualStrings("2.0", out.items);
+    try expectError(InternalError.ValueError, write(out.writer(), Object{ .Float = std.math.nan(f64) }));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,86 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const sexp = @import("./sexp.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+
+/// Every S-expression word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@SEXP-PARSE", SEXP_PARSE },
+    .{ "@SEXP-WRITE", SEXP_WRITE },
+};
+
+/// Define every S-expression word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @SEXP-PARSE ( String <- @1 )
+///
+/// The S-expression the String holds: lists become Arrays, and atoms become
+/// numbers, Strings, Booleans, or Symbols. See sexp.zig for the details.
+/// Malformed text is a ValueError.
+pub fn SEXP_PARSE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+
+    const parsed = try sexp.parse(runtime, peeked.String.value.?);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(parsed);
+}
+
+/// @SEXP-WRITE ( @1 <- String )
+///
+/// The Object, as S-expression text. Objects with no S-expression form (see
+/// sexp.zig) are a TypeError.
+pub fn SEXP_WRITE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+    try sexp.write(out.writer(), peeked.*);
+    const str = try runtime.get_or_put_string(out.items);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+test "@SEXP-PARSE and @SEXP-WRITE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    // Strings in source can't (yet) hold double quotes, so the text goes on
+    // the Stack by hand.
+    try runtime.stack_push_string((try runtime.get_or_put_string("(a(1)#t\"b\"  2.5)")).value_ptr);
+    try runtime.eval("@SEXP-PARSE @SEXP-WRITE");
+    var text = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&text);
+    try expectEqualStrings("(a (1) #t \"b\" 2.5)", text.String.value.?);
+
+    try expectError(InternalError.ValueError, runtime.eval("\"(a\" @SEXP-PARSE"));
+    var bad = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&bad);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
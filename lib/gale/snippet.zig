@@ -0,0 +1,141 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const lexer = @import("./lexer.zig");
+const unicode = @import("./unicode.zig");
+
+// A position in a source, shown as compilers show them these days: the line
+// it's on, and carets under the word starting there.
+//
+//     3 | 1 2 bogus 4
+//       |     ^^^^^
+//
+// With colour, the gutter is dimmed and the carets are bold red. Whether
+// colour's wanted (is this a terminal? is NO_COLOR set?) is the host's call.
+
+/// SGR codes, for Style.paint.
+pub const RED = "1;31";
+pub const BOLD = "1";
+pub const DIM = "2";
+
+pub const Style = struct {
+    const Self = @This();
+
+    /// Whether to colour things in with ANSI escapes.
+    color: bool = false,
+
+    pub fn begin(self: Self, writer: anytype, code: []const u8) !void {
+        if (self.color) try writer.print("\x1b[{s}m", .{code});
+    }
+
+    pub fn end(self: Self, writer: anytype) !void {
+        if (self.color) try writer.writeAll("\x1b[0m");
+    }
+
+    /// `text`, in the colour (or weight) `code` gives, if colour's wanted at
+    /// all.
+    pub fn paint(self: Self, writer: anytype, code: []const u8, text: []const u8) !void {
+        try self.begin(writer, code);
+        try writer.writeAll(text);
+        try self.end(writer);
+    }
+};
+
+/// `source`'s `line`th line, then carets under the word starting `column`
+/// bytes into it (both 1-based, as with Tokens), each row ending in a
+/// newline. Positions outside of the source write nothing at all.
+pub fn write_snippet(writer: anytype, style: Style, source: []const u8, line: usize, column: usize) !void {
+    const text = line_of(source, line) orelse return;
+    if (column == 0 or column > text.len + 1) return;
+
+    var number_buf: [24]u8 = undefined;
+    const number = std.fmt.bufPrint(&number_buf, "{d}", .{line}) catch unreachable;
+
+    try style.paint(writer, DIM, number);
+    try writer.writeByte(' ');
+    try style.paint(writer, DIM, "|");
+    try writer.print(" {s}\n", .{text});
+
+    try writer.writeByteNTimes(' ', number.len + 1);
+    try style.paint(writer, DIM, "|");
+    try writer.writeByte(' ');
+    try write_padding(writer, text[0 .. column - 1]);
+
+    const word = word_at(text[column - 1 ..]);
+    try style.begin(writer, RED);
+    try writer.writeByteNTimes('^', std.math.max(1, unicode.display_width(word, .{})));
+    try style.end(writer);
+    try writer.writeByte('\n');
+}
+
+/// The `line`th line of `source`, without its line ending.
+fn line_of(source: []const u8, line: usize) ?[]const u8 {
+    if (line == 0) return null;
+
+    var lines = std.mem.split(u8, source, "\n");
+    var idx: usize = 1;
+    while (lines.next()) |text| : (idx += 1) {
+        if (idx == line) return std.mem.trimRight(u8, text, "\r");
+    }
+    return null;
+}
+
+/// Whitespace as wide as `before` is shown, tabs kept as tabs, so that
+/// whatever's written next lines up with what follows `before` in the row
+/// above.
+fn write_padding(writer: anytype, before: []const u8) !void {
+    var segments = std.mem.split(u8, before, "\t");
+    var first = true;
+    while (segments.next()) |segment| {
+        if (!first) try writer.writeByte('\t');
+        first = false;
+        try writer.writeByteNTimes(' ', unicode.display_width(segment, .{}));
+    }
+}
+
+fn word_at(text: []const u8) []const u8 {
+    var len: usize = 0;
+    while (len < text.len and !lexer.is_inline_space(text[len])) len += 1;
+    return text[0..len];
+}
+
+test "write_snippet" {
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    const source = "1 2 +\r\n\t\"é\" bogus 4\n";
+    try write_snippet(out.writer(), .{}, source, 2, 7);
+    try expectEqualStrings("2 | \t\"é\" bogus 4\n  | \t    ^^^^^\n", out.items);
+
+    out.clearRetainingCapacity();
+    try write_snippet(out.writer(), .{ .color = true }, source, 1, 5);
+    try expectEqualStrings(
+        "\x1b[2m1\x1b[0m \x1b[2m|\x1b[0m 1 2 +\n" ++
+            "  \x1b[2m|\x1b[0m     \x1b[1;31m^\x1b[0m\n",
+        out.items,
+    );
+
+    out.clearRetainingCapacity();
+    try write_snippet(out.writer(), .{}, source, 9, 1);
+    try write_snippet(out.writer(), .{}, source, 1, 40);
+    try expectEqualStrings("", out.items);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,121 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const helpers = @import("./helpers.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+// How source files are read, whatever editor wrote them. Sources are UTF-8,
+// and only UTF-8: a leading byte order mark (as some Windows editors insist
+// on writing) is dropped, and UTF-16 is an UnsupportedEncoding rather than
+// a parse error three bytes in. CRLF line breaks become LF, everywhere
+// (Strings included), as eval splits words on LF alone, and so that a source
+// means the same thing however it was checked out. Lone CRs are left be.
+
+pub const UTF8_BOM = "\xEF\xBB\xBF";
+pub const UTF16_LE_BOM = "\xFF\xFE";
+pub const UTF16_BE_BOM = "\xFE\xFF";
+
+pub const Encoding = enum {
+    Utf8,
+    Utf16LittleEndian,
+    Utf16BigEndian,
+
+    pub fn name(self: Encoding) []const u8 {
+        return switch (self) {
+            .Utf8 => "UTF-8",
+            .Utf16LittleEndian => "UTF-16LE",
+            .Utf16BigEndian => "UTF-16BE",
+        };
+    }
+};
+
+/// What `source` appears to be written in: UTF-16 if it starts with either
+/// of its byte order marks, or, lacking one, if exactly one of its first two
+/// bytes is NUL (as ASCII is in UTF-16, and never is in a gale source), and
+/// UTF-8 otherwise. This is a guess, not validation: UTF-8 that isn't valid
+/// is left for whatever reads it to trip over.
+pub fn detect(source: []const u8) Encoding {
+    if (std.mem.startsWith(u8, source, UTF16_LE_BOM)) return .Utf16LittleEndian;
+    if (std.mem.startsWith(u8, source, UTF16_BE_BOM)) return .Utf16BigEndian;
+    if (source.len >= 2 and (source[0] == 0) != (source[1] == 0)) {
+        return if (source[1] == 0) .Utf16LittleEndian else .Utf16BigEndian;
+    }
+    return .Utf8;
+}
+
+/// How long `source`'s UTF-8 byte order mark is: 0 if it has none.
+pub fn bom_len(source: []const u8) usize {
+    return if (std.mem.startsWith(u8, source, UTF8_BOM)) UTF8_BOM.len else 0;
+}
+
+/// Fail with UnsupportedEncoding unless `source` is (so far as detect can
+/// tell) UTF-8.
+pub fn check(source: []const u8) InternalError!void {
+    if (detect(source) != .Utf8) return InternalError.UnsupportedEncoding;
+}
+
+/// `source` without its byte order mark and with LF for every CRLF, which
+/// the caller owns, or null if it has neither (and so is fine as it is).
+/// Anything but UTF-8 is an UnsupportedEncoding.
+pub fn normalize(alloc: Allocator, source: []const u8) !?[]u8 {
+    try check(source);
+
+    const body = source[bom_len(source)..];
+    const crlfs = std.mem.count(u8, body, "\r\n");
+    if (crlfs == 0 and body.len == source.len) return null;
+
+    const normalized = try alloc.alloc(u8, body.len - crlfs);
+    var len: usize = 0;
+    for (body) |chr, idx| {
+        if (chr == '\r' and idx + 1 < body.len and body[idx + 1] == helpers.CHAR_NEWLINE) continue;
+        normalized[len] = chr;
+        len += 1;
+    }
+    return normalized;
+}
+
+test "detect" {
+    try expectEqual(Encoding.Utf8, detect("1 2 @ADD"));
+    try expectEqual(Encoding.Utf8, detect(UTF8_BOM ++ "1"));
+    try expectEqual(Encoding.Utf8, detect(""));
+    try expectEqual(Encoding.Utf16LittleEndian, detect(UTF16_LE_BOM ++ "1\x00"));
+    try expectEqual(Encoding.Utf16BigEndian, detect(UTF16_BE_BOM ++ "\x001"));
+    try expectEqual(Encoding.Utf16LittleEndian, detect("1\x00 \x00"));
+    try expectEqual(Encoding.Utf16BigEndian, detect("\x001\x00 "));
+}
+
+test "normalize" {
+    try expect((try normalize(testAllocator, "1 2\n@ADD\r")) == null);
+    try expectError(InternalError.UnsupportedEncoding, normalize(testAllocator, UTF16_LE_BOM ++ "1\x00"));
+
+    const normalized = (try normalize(testAllocator, UTF8_BOM ++ "1 \"a\r\nb\"\r\n\r@ADD\r\n")).?;
+    defer testAllocator.free(normalized);
+    try expectEqualStrings("1 \"a\nb\"\n\r@ADD\n", normalized);
+
+    const bom_only = (try normalize(testAllocator, UTF8_BOM)).?;
+    defer testAllocator.free(bom_only);
+    try expectEqualStrings("", bom_only);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -51,6 +51,12 @@ pub const StackManipulationError = error{
 /// and the bottom element of a "higher" stack in a tight loop or some such). I
 /// don't yet have benchmarking data to prove this hypothesis, however.
 ///
+/// Stacks can be snapshotted in O(1) time (see `snapshot`), which is what
+/// lets things like a REPL roll back a failed line without cloning every
+/// Object (and thus every Rc handle) on the stack. Segments are shared between
+/// the live stack and any outstanding snapshots, and are copied (one segment
+/// at a time, and only once they're actually written to) on write.
+///
 /// Not thread-safe. Use channels or something.
 // TODO:
 // - Docs
@@ -88,6 +94,30 @@ pub const Stack = struct {
     next_idx: usize,
     contents: [STACK_SIZE]?Object,
 
+    /// The number of claims on this segment *beyond* the first one, which is
+    /// usually the live stack itself. Each outstanding Snapshot holds a
+    /// claim, as does each segment (in any chain) whose .prev points here. A
+    /// segment with any extra claims must never be written to: see
+    /// `ensure_exclusive`.
+    ///
+    /// Note that .next is meaningless on shared segments, since it can only
+    /// point to one of the (potentially many) segments sitting atop this one.
+    shares: usize,
+
+    /// A frozen, O(1) view of a Stack as it was when `Stack.snapshot` was
+    /// called. Snapshots must eventually be either handed to `Stack.restore`
+    /// or thrown away with `discard`, otherwise the segments they hold claims
+    /// on (and the Objects within) will leak.
+    pub const Snapshot = struct {
+        top: *Stack,
+
+        /// Drop this snapshot's claims, freeing any segments (and Objects)
+        /// which nothing else is still using.
+        pub fn discard(self: Snapshot) void {
+            self.top.release();
+        }
+    };
+
     pub fn init(alloc: Allocator, prev: ?*Stack) !*Self {
         var stack = try alloc.create(Self);
         stack.* = .{
@@ -96,6 +126,7 @@ pub const Stack = struct {
             .next = null,
             .next_idx = 0,
             .contents = .{null} ** STACK_SIZE,
+            .shares = 0,
         };
         return stack;
     }
@@ -112,6 +143,144 @@ pub const Stack = struct {
         self.alloc.destroy(self);
     }
 
+    /// Drop this chain's claim on this segment and on every segment beneath
+    /// it, walking top-down and freeing whatever is no longer used by anyone
+    /// else. The walk stops at the first segment which is still claimed
+    /// elsewhere (say, by a Snapshot), since its claimants still need it and
+    /// everything beneath it.
+    ///
+    /// This must be called on the terminal segment of a chain (as with
+    /// anything else mutating a Stack), since segments above this one are not
+    /// visited.
+    pub fn release(self: *Self) void {
+        var current: ?*Self = self;
+
+        while (current) |segment| {
+            if (segment.shares > 0) {
+                segment.shares -= 1;
+                return;
+            }
+
+            const prev = segment.prev;
+
+            while (segment.next_idx > 0) {
+                segment.next_idx -= 1;
+
+                if (segment.contents[segment.next_idx]) |*obj| {
+                    obj.deinit(segment.alloc);
+                }
+
+                segment.contents[segment.next_idx] = null;
+            }
+
+            segment.alloc.destroy(segment);
+            current = prev;
+        }
+    }
+
+    /// The total number of Objects on this Stack, counting this segment and
+    /// every segment beneath it. This walks the segments, so it's O(segments)
+    /// rather than O(1), but segments are large.
+    pub fn depth(self: *Self) usize {
+        var total: usize = 0;
+        var current: ?*Self = self;
+
+        while (current) |segment| {
+            total += segment.next_idx;
+            current = segment.prev;
+        }
+
+        return total;
+    }
+
+    test "depth" {
+        const stack = try Self.init(testAllocator, null);
+        try expectEqual(@as(usize, 0), stack.depth());
+
+        var target = try stack.do_push_uint(1);
+        var i: usize = 0;
+        while (i < STACK_SIZE) : (i += 1) target = try target.do_push_uint(i);
+        try expect(target != stack);
+        try expectEqual(@as(usize, STACK_SIZE + 1), target.depth());
+
+        target.release();
+    }
+
+    /// The Object `n` below the top of this (terminal) Stack, so 0 is the top
+    /// itself, or null if the Stack isn't that deep. This is O(segments), and
+    /// meant for instrumentation rather than anything hot.
+    pub fn nth_from_top(self: *Self, n: usize) ?*const Object {
+        var remaining = n;
+        var current: ?*Self = self;
+
+        while (current) |segment| : (current = segment.prev) {
+            if (remaining < segment.next_idx) return &segment.contents[segment.next_idx - 1 - remaining].?;
+            remaining -= segment.next_idx;
+        }
+
+        return null;
+    }
+
+    test "nth_from_top" {
+        const stack = try Self.init(testAllocator, null);
+        var target = try stack.do_push_uint(0);
+        var i: usize = 1;
+        while (i <= STACK_SIZE) : (i += 1) target = try target.do_push_uint(i);
+
+        try expectEqual(@as(usize, STACK_SIZE), target.nth_from_top(0).?.UnsignedInt);
+        try expectEqual(@as(usize, 0), target.nth_from_top(STACK_SIZE).?.UnsignedInt);
+        try expect(target.nth_from_top(STACK_SIZE + 1) == null);
+
+        target.release();
+    }
+
+    /// Take an O(1) snapshot of this Stack, which must be the terminal
+    /// segment. No Objects are copied (nor are their refcounts touched): the
+    /// snapshot simply holds a claim on the current top segment, and
+    /// future writes to the live stack will copy segments as necessary (see
+    /// `ensure_exclusive`), leaving the snapshot's view untouched.
+    pub fn snapshot(self: *Self) StackManipulationError!Snapshot {
+        try self.non_terminal_stack_guard();
+        self.shares += 1;
+        return Snapshot{ .top = self };
+    }
+
+    /// Throw away this (live, terminal) Stack in favour of the state captured
+    /// in `snap`, returning the Stack to perform future operations on. This
+    /// consumes the snapshot: take another one if you need to roll back to
+    /// the same point again later.
+    pub fn restore(self: *Self, snap: Snapshot) *Self {
+        self.release();
+        return snap.top;
+    }
+
+    /// Copy-on-write support for `snapshot`: if anything other than the
+    /// caller still holds a claim on this segment, clone it (taking new
+    /// references to its Objects along the way) so the caller has a segment
+    /// it may freely write to, and leave the original untouched for everyone
+    /// else. Returns self when no copy is necessary, which is by far the most
+    /// common case.
+    fn ensure_exclusive(self: *Self) !*Self {
+        if (self.shares == 0) return self;
+
+        // .prev is deliberately left unset until we can no longer fail, so
+        // that the errdefer'd release can't wrongly free segments beneath us
+        // that we never took a claim on.
+        var clone = try Self.init(self.alloc, null);
+        errdefer clone.release();
+
+        for (self.contents[0..self.next_idx]) |obj| {
+            clone.contents[clone.next_idx] = try obj.?.ref();
+            clone.next_idx += 1;
+        }
+
+        clone.prev = self.prev;
+        if (self.prev) |prev| prev.shares += 1;
+        self.shares -= 1;
+
+        return clone;
+    }
+
     // TODO: docs about stack jumping behavior here
     // TODO: see if this should just be the mainline deinit() function instead
     // or if they can otherwise be merged
@@ -354,19 +523,33 @@ pub const Stack = struct {
     pub fn do_pop(self: *Self) !Types.PopSingle {
         if (self.next_idx == 0) {
             if (self.prev) |prev| {
-                self.deinit();
+                // If a snapshot still needs this (empty) segment, we can't
+                // throw it away: instead, the live chain takes its own claim on
+                // the segment beneath, which is about to become the top.
+                if (self.shares > 0) {
+                    self.shares -= 1;
+                    prev.shares += 1;
+                } else {
+                    // This segment's claim on the one beneath passes to the
+                    // live chain, so prev.shares stays as it is: dropping it
+                    // here would leave a snapshot's segment writable.
+                    self.deinit();
+                }
+
                 return prev.do_pop();
             }
 
             return StackManipulationError.Underflow;
         }
 
-        self.next_idx -= 1;
-        if (self.contents[self.next_idx]) |obj| {
-            self.contents[self.next_idx] = null;
+        const target = try self.ensure_exclusive();
+
+        target.next_idx -= 1;
+        if (target.contents[target.next_idx]) |obj| {
+            target.contents[target.next_idx] = null;
             return Types.PopSingle{
                 .item = obj,
-                .now_top_stack = self,
+                .now_top_stack = target,
             };
         }
 
@@ -387,6 +570,41 @@ pub const Stack = struct {
         try expectError(StackManipulationError.Underflow, target.do_pop());
     }
 
+    test "do_pop: through a shared segment" {
+        const bottom = try Self.init(testAllocator, null);
+        var target = bottom;
+        var i: usize = 0;
+        while (i <= STACK_SIZE) : (i += 1) target = try target.do_push_uint(i);
+
+        // Popping past a copied segment while the snapshot lives copies the
+        // segment beneath too, leaving the snapshot's view alone.
+        const snap = try target.snapshot();
+        target = try target.do_push_uint(99);
+        i = 0;
+        while (i < 3) : (i += 1) target = (try target.do_pop()).now_top_stack;
+        try expect(target != bottom);
+        try expectEqual(@as(usize, STACK_SIZE - 1), target.depth());
+        try expectEqual(@as(usize, STACK_SIZE + 1), snap.top.depth());
+        try expectEqual(@as(usize, STACK_SIZE - 1), snap.top.nth_from_top(1).?.UnsignedInt);
+        snap.discard();
+        target.release();
+
+        // Once the snapshot's gone, nothing but the live chain holds the
+        // segment beneath, so popping into it copies nothing.
+        const bottom_again = try Self.init(testAllocator, null);
+        target = bottom_again;
+        i = 0;
+        while (i <= STACK_SIZE) : (i += 1) target = try target.do_push_uint(i);
+        const gone = try target.snapshot();
+        target = try target.do_push_uint(99);
+        gone.discard();
+        try expectEqual(@as(usize, 0), bottom_again.shares);
+        i = 0;
+        while (i < 3) : (i += 1) target = (try target.do_pop()).now_top_stack;
+        try expect(target == bottom_again);
+        target.release();
+    }
+
     /// Remove the top two items off of this stack and return them, along with
     /// a pointer to which Stack object to perform future operations on. If
     /// this is the bottom Stack and there aren't at least two Objects
@@ -464,7 +682,10 @@ pub const Stack = struct {
     /// the object ended up on.
     pub fn do_push(self: *Self, obj: Object) !*Self {
         try self.non_terminal_stack_guard();
-        const target = try self.expand_to_fit(1) orelse self;
+        // TODO: when self is both shared and full, this copies a segment only
+        // to immediately grow past it, which is wasteful.
+        const exclusive = try self.ensure_exclusive();
+        const target = try exclusive.expand_to_fit(1) orelse exclusive;
         target.contents[target.next_idx] = try obj.ref();
         target.next_idx += 1;
         return target;
@@ -591,7 +812,10 @@ pub const Stack = struct {
         try expectError(StackManipulationError.Underflow, target.do_pop());
     }
 
-    pub fn do_swap(self: *Self) StackManipulationError!void {
+    /// Swap the top two Objects of this Stack, returning a pointer to which
+    /// Stack object to perform future operations on (which, thanks to
+    /// copy-on-write snapshots, may not be self).
+    pub fn do_swap(self: *Self) !*Self {
         try self.non_terminal_stack_guard();
         return try @call(
             .{ .modifier = .always_inline },
@@ -600,7 +824,7 @@ pub const Stack = struct {
         );
     }
 
-    pub fn do_swap_no_really_even_on_inner_stacks(self: *Self) StackManipulationError!void {
+    pub fn do_swap_no_really_even_on_inner_stacks(self: *Self) !*Self {
         if (self.next_idx < 2 and self.prev == null) {
             return StackManipulationError.Underflow;
         }
@@ -609,13 +833,23 @@ pub const Stack = struct {
             unreachable;
         }
 
-        const near_obj = &self.contents[self.next_idx - 1];
-        const far_obj = if (self.next_idx > 1)
-            &self.contents[self.next_idx - 2]
+        const target = try self.ensure_exclusive();
+
+        // Swapping across a segment boundary writes to the segment beneath us
+        // too, so it needs to be ours to write to.
+        if (target.next_idx < 2) {
+            target.prev = try target.prev.?.ensure_exclusive();
+        }
+
+        const near_obj = &target.contents[target.next_idx - 1];
+        const far_obj = if (target.next_idx > 1)
+            &target.contents[target.next_idx - 2]
         else
-            &self.prev.?.contents[self.prev.?.next_idx - 1];
+            &target.prev.?.contents[target.prev.?.next_idx - 1];
 
         std.mem.swap(?Object, near_obj, far_obj);
+
+        return target;
     }
 
     test "do_swap: single stack" {
@@ -624,7 +858,7 @@ pub const Stack = struct {
 
         var target = try stack.do_push_uint(1);
         target = try target.do_push_uint(2);
-        try target.do_swap();
+        target = try target.do_swap();
 
         const top_two = try target.do_pop_pair();
         try expectEqual(@as(usize, 2), top_two.far.UnsignedInt);
@@ -655,12 +889,52 @@ pub const Stack = struct {
             baseStack.do_swap(),
         );
 
-        try newStack.do_swap();
-        const top_two = try newStack.do_pop_pair();
+        const swapped = try newStack.do_swap();
+        const top_two = try swapped.do_pop_pair();
         try expectEqual(@as(usize, 2), top_two.far.UnsignedInt);
         try expectEqual(@as(usize, 1), top_two.near.UnsignedInt);
     }
 
+    test "snapshot: writes after a snapshot don't disturb it" {
+        const stack = try Self.init(testAllocator, null);
+
+        var target = try stack.do_push_uint(1);
+        target = try target.do_push_uint(2);
+
+        const snap = try target.snapshot();
+
+        // Mutating the live stack must copy the shared segment, rather than
+        // scribbling over the snapshot's view of the world.
+        target = try target.do_drop();
+        target = try target.do_push_uint(3);
+        try expect(target != snap.top);
+        try expectEqual(@as(usize, 3), (try target.do_peek()).UnsignedInt);
+        try expectEqual(@as(usize, 2), snap.top.contents[1].?.UnsignedInt);
+
+        // Rolling back throws the live copy away and hands the snapshot's
+        // segment back to us, untouched.
+        target = target.restore(snap);
+        try expectEqual(@as(usize, 2), (try target.do_peek()).UnsignedInt);
+
+        target = try target.do_drop();
+        target = try target.do_drop();
+        target.deinit_guard_for_empty();
+    }
+
+    test "snapshot: discarding an untouched snapshot leaves the stack alone" {
+        const stack = try Self.init(testAllocator, null);
+        defer stack.deinit_guard_for_empty();
+
+        var target = try stack.do_push_uint(1);
+        const snap = try target.snapshot();
+        snap.discard();
+
+        // No copy should have been necessary, since nothing was ever written
+        // while the snapshot was alive.
+        target = try target.do_drop();
+        try expectEqual(stack, target);
+    }
+
     /// Remove the top item off of this stack. Return a pointer to which Stack
     /// object to perform future operations on. If this is the bottom Stack and
     /// there are no contents remaining, an Underflow is raised. If this is not
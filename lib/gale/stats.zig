@@ -0,0 +1,117 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+
+/// What a Runtime has been up to since it was made, or since its last
+/// reset_stats, for performance work to have numbers to stand on. See
+/// Runtime.stats, and @INTROSPECT-STATS.
+pub const Stats = struct {
+    /// Words run, literals included, as Limits.max_words_executed counts
+    /// them, but across every eval rather than per budget.
+    words_executed: usize = 0,
+    /// Objects onto and off of the Stack, whichever of the stack_push_*,
+    /// stack_pop*, and stack_wrangle families put them there or took them
+    /// off. Swaps count for neither.
+    pushes: usize = 0,
+    pops: usize = 0,
+    /// CallSites resolved from their cache, and those which had to find
+    /// their word afresh (see Runtime.resolve_call_site).
+    call_site_hits: usize = 0,
+    call_site_misses: usize = 0,
+    /// Allocations made through the Runtime's allocator, and how many bytes
+    /// they asked for between them, if Options.count_allocations asked for
+    /// them to be counted. Zero otherwise.
+    allocations: usize = 0,
+    allocated_bytes: usize = 0,
+};
+
+/// An Allocator forwarding everything to `parent`, counting allocations as
+/// they go by. The counts are atomic, so this is as threadsafe as `parent`
+/// is. It lives on the heap, so that the Allocator it hands out stays valid
+/// however its owner moves about.
+pub const CountingAllocator = struct {
+    const Self = @This();
+
+    parent: Allocator,
+    allocations: std.atomic.Atomic(usize),
+    allocated_bytes: std.atomic.Atomic(usize),
+
+    /// Allocated from `parent`. Hand it back to `destroy` once nothing is
+    /// left to free through it.
+    pub fn create(parent: Allocator) !*Self {
+        const self = try parent.create(Self);
+        self.* = .{
+            .parent = parent,
+            .allocations = std.atomic.Atomic(usize).init(0),
+            .allocated_bytes = std.atomic.Atomic(usize).init(0),
+        };
+        return self;
+    }
+
+    pub fn destroy(self: *Self) void {
+        const parent = self.parent;
+        parent.destroy(self);
+    }
+
+    pub fn allocator(self: *Self) Allocator {
+        return Allocator.init(self, alloc, resize, free);
+    }
+
+    pub fn reset(self: *Self) void {
+        self.allocations.store(0, .Monotonic);
+        self.allocated_bytes.store(0, .Monotonic);
+    }
+
+    fn alloc(self: *Self, len: usize, ptr_align: u29, len_align: u29, ret_addr: usize) Allocator.Error![]u8 {
+        const allocated = try self.parent.rawAlloc(len, ptr_align, len_align, ret_addr);
+        _ = self.allocations.fetchAdd(1, .Monotonic);
+        _ = self.allocated_bytes.fetchAdd(allocated.len, .Monotonic);
+        return allocated;
+    }
+
+    fn resize(self: *Self, buf: []u8, buf_align: u29, new_len: usize, len_align: u29, ret_addr: usize) ?usize {
+        const resized = self.parent.rawResize(buf, buf_align, new_len, len_align, ret_addr) orelse return null;
+        // Growing in place asks for more bytes as surely as allocating does.
+        if (resized > buf.len) _ = self.allocated_bytes.fetchAdd(resized - buf.len, .Monotonic);
+        return resized;
+    }
+
+    fn free(self: *Self, buf: []u8, buf_align: u29, ret_addr: usize) void {
+        self.parent.rawFree(buf, buf_align, ret_addr);
+    }
+};
+
+test "CountingAllocator" {
+    const counter = try CountingAllocator.create(testAllocator);
+    defer counter.destroy();
+    const alloc = counter.allocator();
+
+    const bytes = try alloc.alloc(u8, 16);
+    alloc.free(bytes);
+    const more = try alloc.alloc(u8, 8);
+    defer alloc.free(more);
+
+    try expectEqual(@as(usize, 2), counter.allocations.load(.Monotonic));
+    try expectEqual(@as(usize, 24), counter.allocated_bytes.load(.Monotonic));
+
+    counter.reset();
+    try expectEqual(@as(usize, 0), counter.allocations.load(.Monotonic));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
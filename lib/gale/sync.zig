@@ -0,0 +1,46 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+
+const helpers = @import("./helpers.zig");
+const rc = @import("./rc.zig");
+
+/// Whether Objects (and the containers holding them, like Channels and
+/// Cells) can be shared between host threads, each with its own Runtime.
+/// This follows refcount atomicity (see rc.zig), as there's no sense sharing
+/// a container whose contents can't also be shared, but also needs an OS to
+/// provide the blocking primitives.
+pub const THREADED: bool = rc.RC_THREAD_SAFE and helpers.HOSTED;
+
+/// A real mutex when THREADED, and nothing at all otherwise.
+pub const Mutex = if (THREADED) std.Thread.Mutex else struct {
+    pub fn lock(_: *@This()) void {}
+    pub fn unlock(_: *@This()) void {}
+};
+
+/// A real condition variable when THREADED. Otherwise, nothing could ever
+/// wake a waiter, so waiting is a bug.
+pub const Condition = if (THREADED) std.Thread.Condition else struct {
+    pub fn broadcast(_: *@This()) void {}
+    pub fn wait(_: *@This(), _: *Mutex) void {
+        @panic("nothing can ever wake a waiter in an unthreaded build");
+    }
+    pub fn timedWait(_: *@This(), _: *Mutex, _: u64) error{Timeout}!void {
+        @panic("nothing can ever wake a waiter in an unthreaded build");
+    }
+};
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,343 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const Lexer = @import("./lexer.zig").Lexer;
+const Token = @import("./lexer.zig").Token;
+const vocabulary = @import("./vocabulary.zig");
+const VOCABULARY_SEPARATOR = vocabulary.VOCABULARY_SEPARATOR;
+
+// An index of where things are defined across source files, in the tags
+// formats editors have understood since long before LSP: ctags' `tags` files
+// (vi and friends) and etags' `TAGS` files (Emacs).
+//
+// Besides words defined with @DEFINE (`... :name @DEFINE`, and likewise for
+// @CONSTANT and @VALUE), this follows the definition syntax sketched out in
+// sketches/ and init.gale, which the runtime can't yet evaluate:
+//
+//     : name ... ;        a word
+//     :@ name word ;      a word, hoisted from another
+//     $ Shape ... ;       a shape, whose members ($: and $>) are words named
+//                         Shape/member
+//
+// Names like Vocabulary/word also index the vocabulary they belong to, at its
+// first definition in each file.
+
+pub const Kind = enum {
+    Word,
+    Shape,
+    Member,
+    Vocabulary,
+
+    /// The single letter ctags uses to tell kinds apart.
+    pub fn letter(self: Kind) u8 {
+        return switch (self) {
+            .Word => 'w',
+            .Shape => 's',
+            .Member => 'm',
+            .Vocabulary => 'v',
+        };
+    }
+};
+
+/// Words introducing a definition, named by the word after them.
+const DEFINERS = .{
+    .{ ":", Kind.Word },
+    .{ ":@", Kind.Word },
+    .{ "$", Kind.Shape },
+    .{ "$:", Kind.Member },
+    .{ "$>", Kind.Member },
+};
+
+/// Words opening a block closed by `;`, besides DEFINERS: shapes'
+/// declarations of what they fulfill.
+const OPENERS = .{"$."};
+const CLOSER = ";";
+
+/// Each defines the word named by the Symbol before it. See nucleus_words.zig,
+/// and prelude.gale for the lowercase names.
+const DEFINE_WORDS = [_][]const u8{ "@DEFINE", "@CONSTANT", "@VALUE", "constant", "value" };
+
+fn is_define_word(name: []const u8) bool {
+    for (DEFINE_WORDS) |define_word| {
+        if (std.mem.eql(u8, name, define_word)) return true;
+    }
+    return false;
+}
+
+pub const Tag = struct {
+    name: []const u8,
+    kind: Kind,
+    /// 1-based, as with Tokens.
+    line: usize,
+    column: usize,
+    /// Where the line the name was found on starts in the source.
+    line_offset: usize,
+    /// The source, from the start of that line through the end of the name.
+    text: []const u8,
+};
+
+/// Every Tag in one source, in the order they were found.
+pub const Tags = struct {
+    const Self = @This();
+
+    arena: std.heap.ArenaAllocator,
+    items: []Tag,
+
+    pub fn deinit(self: *Self) void {
+        self.arena.deinit();
+    }
+};
+
+/// Index `source`. Unterminated Strings and block comments are a ValueError,
+/// as they are to the Lexer.
+pub fn scan(alloc: Allocator, source: []const u8) !Tags {
+    var arena = std.heap.ArenaAllocator.init(alloc);
+    errdefer arena.deinit();
+
+    var scanner = Scanner{
+        .alloc = arena.allocator(),
+        .source = source,
+        .found = std.ArrayList(Tag).init(arena.allocator()),
+        .vocabularies = std.StringHashMap(void).init(arena.allocator()),
+    };
+
+    // How many `;`-terminated blocks we're within, and, if one of them is a
+    // shape, its name and the depth it was opened at.
+    var depth: usize = 0;
+    var shape: ?struct { name: []const u8, depth: usize } = null;
+    // The kind of definition whose name is the next word, if any.
+    var pending: ?Kind = null;
+    // The word before this one, for @DEFINE to take its name from.
+    var previous: ?Token = null;
+
+    var lexer = Lexer.init(source);
+    while (try lexer.next()) |token| {
+        if (token.kind != .Word) continue;
+        defer previous = token;
+
+        if (pending) |kind| {
+            pending = null;
+
+            const name = if (kind == .Member and shape != null)
+                try std.fmt.allocPrint(scanner.alloc, "{s}{c}{s}", .{ shape.?.name, VOCABULARY_SEPARATOR, token.raw })
+            else
+                token.raw;
+
+            try scanner.add(token, name, kind);
+            if (kind == .Shape) shape = .{ .name = token.raw, .depth = depth - 1 };
+            continue;
+        }
+
+        if (is_define_word(token.raw)) {
+            const symbol = previous orelse continue;
+            if (symbol.raw.len > 1 and symbol.raw[0] == ':') {
+                try scanner.add(symbol, symbol.raw[1..], .Word);
+            }
+            continue;
+        }
+
+        if (std.mem.eql(u8, token.raw, CLOSER)) {
+            // Stray closers are the linter's problem, not ours.
+            if (depth > 0) depth -= 1;
+            if (shape) |current| {
+                if (depth == current.depth) shape = null;
+            }
+            continue;
+        }
+
+        inline for (DEFINERS) |definer| {
+            if (std.mem.eql(u8, token.raw, definer[0])) {
+                pending = definer[1];
+                depth += 1;
+            }
+        }
+        inline for (OPENERS) |opener| {
+            if (std.mem.eql(u8, token.raw, opener)) depth += 1;
+        }
+    }
+
+    return Tags{ .arena = arena, .items = scanner.found.toOwnedSlice() };
+}
+
+const Scanner = struct {
+    const Self = @This();
+
+    alloc: Allocator,
+    source: []const u8,
+    found: std.ArrayList(Tag),
+    /// Those which have been indexed already.
+    vocabularies: std.StringHashMap(void),
+
+    /// Index `name`, found at `token`, and its vocabulary, if it's the first
+    /// word from it.
+    fn add(self: *Self, token: Token, name: []const u8, kind: Kind) !void {
+        const line_offset = token.offset - (token.column - 1);
+        const tag = Tag{
+            .name = name,
+            .kind = kind,
+            .line = token.line,
+            .column = token.column,
+            .line_offset = line_offset,
+            .text = self.source[line_offset .. token.offset + token.raw.len],
+        };
+        try self.found.append(tag);
+
+        const vocab_name = vocabulary.vocabulary_of(name) orelse return;
+        const seen = try self.vocabularies.getOrPut(vocab_name);
+        if (seen.found_existing) return;
+
+        var vocab_tag = tag;
+        vocab_tag.name = vocab_name;
+        vocab_tag.kind = .Vocabulary;
+        try self.found.append(vocab_tag);
+    }
+};
+
+/// The tags found in one file, for the writers below.
+pub const File = struct {
+    path: []const u8,
+    tags: []const Tag,
+};
+
+const Entry = struct {
+    path: []const u8,
+    tag: *const Tag,
+
+    fn less_than(_: void, lhs: Entry, rhs: Entry) bool {
+        return switch (std.mem.order(u8, lhs.tag.name, rhs.tag.name)) {
+            .lt => true,
+            .gt => false,
+            .eq => switch (std.mem.order(u8, lhs.path, rhs.path)) {
+                .lt => true,
+                .gt => false,
+                .eq => lhs.tag.line < rhs.tag.line,
+            },
+        };
+    }
+};
+
+/// A ctags `tags` file indexing every one of `files`, sorted by name (as
+/// editors expect to binary search it), addressing each tag by line number.
+pub fn write_ctags(alloc: Allocator, writer: anytype, files: []const File) !void {
+    var entries = std.ArrayList(Entry).init(alloc);
+    defer entries.deinit();
+
+    for (files) |file| {
+        for (file.tags) |*tag| try entries.append(.{ .path = file.path, .tag = tag });
+    }
+    std.sort.sort(Entry, entries.items, {}, Entry.less_than);
+
+    try writer.writeAll("!_TAG_FILE_FORMAT\t2\t/extended format/\n");
+    try writer.writeAll("!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n");
+    try writer.writeAll("!_TAG_PROGRAM_NAME\tgale\t//\n");
+
+    for (entries.items) |entry| {
+        try writer.print("{s}\t{s}\t{d};\"\t{c}\n", .{
+            entry.tag.name,
+            entry.path,
+            entry.tag.line,
+            entry.tag.kind.letter(),
+        });
+    }
+}
+
+/// An etags `TAGS` file indexing every one of `files`, in the order given.
+pub fn write_etags(alloc: Allocator, writer: anytype, files: []const File) !void {
+    var section = std.ArrayList(u8).init(alloc);
+    defer section.deinit();
+
+    for (files) |file| {
+        section.clearRetainingCapacity();
+        for (file.tags) |tag| {
+            try section.writer().print("{s}\x7f{s}\x01{d},{d}\n", .{
+                tag.text,
+                tag.name,
+                tag.line,
+                tag.line_offset,
+            });
+        }
+
+        try writer.print("\x0c\n{s},{d}\n", .{ file.path, section.items.len });
+        try writer.writeAll(section.items);
+    }
+}
+
+const EXAMPLE =
+    \\{{ Something printable. }}
+    \\$ Printable
+    \\    $: repr ( Self <- String ) ;
+    \\;
+    \\
+    \\: greet "hi" println ;
+    \\:@ Printable/show Printable/repr ;
+    \\&@DUP :twin @DEFINE
+    \\3.14159 :pi constant
+;
+
+test "scan" {
+    var tags = try scan(testAllocator, EXAMPLE);
+    defer tags.deinit();
+
+    const expected = [_]struct { name: []const u8, kind: Kind, line: usize, column: usize }{
+        .{ .name = "Printable", .kind = .Shape, .line = 2, .column = 3 },
+        .{ .name = "Printable/repr", .kind = .Member, .line = 3, .column = 8 },
+        .{ .name = "Printable", .kind = .Vocabulary, .line = 3, .column = 8 },
+        .{ .name = "greet", .kind = .Word, .line = 6, .column = 3 },
+        .{ .name = "Printable/show", .kind = .Word, .line = 7, .column = 4 },
+        .{ .name = "twin", .kind = .Word, .line = 8, .column = 7 },
+        .{ .name = "pi", .kind = .Word, .line = 9, .column = 9 },
+    };
+
+    try expectEqual(expected.len, tags.items.len);
+    for (expected) |want, idx| {
+        const tag = tags.items[idx];
+        try expectEqualStrings(want.name, tag.name);
+        try expectEqual(want.kind, tag.kind);
+        try expectEqual(want.line, tag.line);
+        try expectEqual(want.column, tag.column);
+    }
+    try expectEqualStrings("    $: repr", tags.items[1].text);
+}
+
+test "write_ctags and write_etags" {
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    var tags = try scan(testAllocator, ": b ;\n: a ;\n");
+    defer tags.deinit();
+    const files = [_]File{.{ .path = "x.gale", .tags = tags.items }};
+
+    try write_ctags(testAllocator, out.writer(), &files);
+    try expectEqualStrings(
+        "!_TAG_FILE_FORMAT\t2\t/extended format/\n" ++
+            "!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n" ++
+            "!_TAG_PROGRAM_NAME\tgale\t//\n" ++
+            "a\tx.gale\t2;\"\tw\n" ++
+            "b\tx.gale\t1;\"\tw\n",
+        out.items,
+    );
+
+    out.clearRetainingCapacity();
+    try write_etags(testAllocator, out.writer(), &files);
+    try expectEqualStrings("\x0c\nx.gale,20\n: b\x7fb\x011,0\n: a\x7fa\x012,6\n", out.items);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,102 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+
+const Object = @import("./object.zig").Object;
+const Stack = @import("./stack.zig").Stack;
+const Types = @import("./types.zig");
+
+/// One word's worth of in-progress execution. Rather than recursing on the
+/// Zig stack for each nested word, the Runtime keeps an explicit stack of
+/// these, which is what allows a Task to be paused (say, by @YIELD) halfway
+/// through a compound word and resumed later.
+///
+/// Frames don't hold references to their words: whoever asked for the word
+/// to be run (the dictionary, an Object on the Stack, a Task's entrypoint)
+/// must keep it alive until the frame is done with it.
+pub const Frame = struct {
+    word: *Types.HeapedWord,
+    /// For Compound words, the index of the next inner word to run. For
+    /// CallSites, 0 until the target word has been entered, then 1. Unused by
+    /// everything else, which runs in a single step.
+    next_step: usize = 0,
+};
+
+pub const FrameStack = std.ArrayList(Frame);
+
+/// A cooperatively-scheduled green thread, with its own Stack and Frames.
+/// Tasks only ever run when someone else waits on them (see Runtime.join_task
+/// and @YIELD), and only ever stop running when they finish, fail, or
+/// @YIELD themselves. There's no preemption, and no OS threads are involved.
+pub const Task = struct {
+    const Self = @This();
+
+    pub const State = enum {
+        /// Waiting for its next slice.
+        Runnable,
+        /// Currently swapped into the Runtime, either directly running or
+        /// somewhere up the (Zig) call chain from whatever is.
+        Running,
+        /// Ran to completion: anything left on its Stack is its result.
+        Done,
+        /// Hit an error, which is stashed in `failure`.
+        Failed,
+    };
+
+    id: usize,
+    state: State,
+    /// Whether the entrypoint has been entered yet, which happens on the
+    /// Task's first slice rather than at spawn time, so that limits and
+    /// observers see it happen in the Task's own context.
+    started: bool,
+    stack: *Stack,
+    frames: FrameStack,
+    /// A Word Object, to which we hold a reference.
+    entrypoint: Object,
+    failure: ?anyerror,
+
+    /// Takes ownership of `entrypoint`'s reference on success.
+    pub fn init(alloc: Allocator, id: usize, entrypoint: Object) !*Self {
+        const task = try alloc.create(Self);
+        errdefer alloc.destroy(task);
+
+        task.* = .{
+            .id = id,
+            .state = .Runnable,
+            .started = false,
+            .stack = try Stack.init(alloc, null),
+            .frames = FrameStack.init(alloc),
+            .entrypoint = entrypoint,
+            .failure = null,
+        };
+
+        return task;
+    }
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        self.stack.release();
+        self.frames.deinit();
+        self.entrypoint.deinit(alloc);
+        alloc.destroy(self);
+    }
+};
+
+/// Tasks by id, in spawn order, which is also the round-robin order they're
+/// scheduled in.
+pub const TaskMap = std.AutoArrayHashMap(usize, *Task);
+
+test {
+    std.testing.refAllDecls(@This());
+}
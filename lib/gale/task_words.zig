@@ -0,0 +1,498 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const sync = @import("./sync.zig");
+
+const Cell = @import("./cell.zig").Cell;
+const Channel = @import("./channel.zig").Channel;
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+/// Every task (and channel, and cell) word, alongside the name it's defined under by
+/// `install`.
+pub const ALL = .{
+    .{ "@BCHAN", BCHAN },
+    .{ "@CELL", CELL },
+    .{ "@CELL-ADD", CELL_ADD },
+    .{ "@CELL-CAS", CELL_CAS },
+    .{ "@CELL-GET", CELL_GET },
+    .{ "@CELL-SET", CELL_SET },
+    .{ "@CHAN", CHAN },
+    .{ "@CLOSE", CLOSE },
+    .{ "@JOIN", JOIN },
+    .{ "@RECV", RECV },
+    .{ "@SEND", SEND },
+    .{ "@SPAWN", SPAWN },
+    .{ "@YIELD", YIELD },
+};
+
+/// Define every task word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @SPAWN ( Word <- UnsignedInt )
+///
+/// Schedule a Word to be run as a Task, on a Stack of its own, returning an
+/// id to @JOIN it by later. The Word won't start until this Task (or the
+/// main program) @YIELDs or @JOINs.
+pub fn SPAWN(runtime: *Runtime) anyerror!void {
+    // Checked before popping so that the Word stays put if it isn't one.
+    if ((try runtime.stack_peek()).* != .Word) return InternalError.TypeError;
+
+    var entrypoint = try runtime.stack_pop();
+    const id = runtime.spawn_task(entrypoint) catch |err| {
+        runtime.release_heaped_object_reference(&entrypoint);
+        return err;
+    };
+
+    try runtime.stack_push_uint(id);
+}
+
+/// @YIELD ( -> )
+///
+/// From within a Task, pause until the next time it's scheduled. From the
+/// main program, give every Runnable Task a slice, and then carry on.
+pub fn YIELD(runtime: *Runtime) anyerror!void {
+    if (runtime.current_task == null) {
+        _ = try runtime.run_task_slices();
+    } else {
+        runtime.yield_requested = true;
+    }
+}
+
+/// @JOIN ( UnsignedInt <- ... )
+///
+/// Wait for the Task with the given id to finish, running it (and any other
+/// Tasks) as needed, and then put everything it left on its Stack onto ours.
+/// If the Task failed, its error is raised here instead.
+pub fn JOIN(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+
+    // The id stays put until the Task is finished, in case we're suspended
+    // (and thus run again from scratch) in the meantime.
+    const id = peeked.UnsignedInt;
+    try runtime.wait_for_task(id);
+
+    _ = try runtime.stack_pop();
+    try runtime.join_task(id);
+}
+
+/// @CHAN ( <- Channel )
+///
+/// Create an unbounded Channel.
+pub fn CHAN(runtime: *Runtime) anyerror!void {
+    try runtime.stack_push_channel(try runtime.new_channel(null));
+}
+
+/// @BCHAN ( UnsignedInt <- Channel )
+///
+/// Create a Channel which can hold at most the given number of Objects (at
+/// least one) before senders must wait.
+pub fn BCHAN(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+    if (peeked.UnsignedInt == 0) return InternalError.ValueError;
+
+    const capacity = (try runtime.stack_pop()).UnsignedInt;
+    try runtime.stack_push_channel(try runtime.new_channel(capacity));
+}
+
+/// @SEND ( Channel @1 <- )
+///
+/// Queue an Object onto a Channel, waiting for room if it's bounded and
+/// full. Sending on a closed Channel raises InternalError.ChannelClosed.
+pub fn SEND(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (far.* != .Channel) return InternalError.TypeError;
+
+    const chan = &far.Channel.value.?;
+
+    // The Channel gets a reference of its own, so that the Stack can be left
+    // untouched until the send has actually happened.
+    var value = try peek.near.ref();
+
+    while (true) {
+        const result = chan.try_send(value) catch |err| {
+            runtime.release_heaped_object_reference(&value);
+            return err;
+        };
+
+        switch (result) {
+            .Sent => break,
+            .Full => wait_for_channel(runtime, chan, .Send) catch |err| {
+                runtime.release_heaped_object_reference(&value);
+                return err;
+            },
+            .Closed => {
+                runtime.release_heaped_object_reference(&value);
+                return InternalError.ChannelClosed;
+            },
+        }
+    }
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @RECV ( Channel <- @1 Boolean )
+///
+/// Take the oldest Object off of a Channel, waiting for one if there are
+/// none, followed by true. Once the Channel is closed and drained, this
+/// instead leaves only false.
+pub fn RECV(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Channel) return InternalError.TypeError;
+
+    const chan = &peeked.Channel.value.?;
+
+    while (true) switch (chan.try_receive()) {
+        .Received => |obj| {
+            var received = obj;
+            defer runtime.release_heaped_object_reference(&received);
+
+            // This may well be the last reference to the Channel, so we're
+            // done with `chan` from here on out.
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_object(received);
+            try runtime.stack_push_bool(true);
+            return;
+        },
+        .Closed => {
+            try runtime.stack_wrangle(.DropTopObject);
+            try runtime.stack_push_bool(false);
+            return;
+        },
+        .Empty => try wait_for_channel(runtime, chan, .Receive),
+    };
+}
+
+/// @CLOSE ( Channel <- )
+///
+/// Close a Channel. Receivers can still drain whatever was already sent.
+pub fn CLOSE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Channel) return InternalError.TypeError;
+
+    peeked.Channel.value.?.close();
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @CELL ( @1 <- Cell )
+///
+/// Box an Object up into a Cell, which can be shared among Tasks (and, with
+/// it, changes to its contents). See cell.zig.
+pub fn CELL(runtime: *Runtime) anyerror!void {
+    var value = try runtime.stack_pop();
+    const heaped = runtime.new_cell(value) catch |err| {
+        runtime.release_heaped_object_reference(&value);
+        return err;
+    };
+
+    try runtime.stack_push_object(.{ .Cell = heaped });
+}
+
+/// @CELL-GET ( Cell <- @1 )
+pub fn CELL_GET(runtime: *Runtime) anyerror!void {
+    var value = try (try peek_cell(runtime, 0)).load();
+    defer runtime.release_heaped_object_reference(&value);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(value);
+}
+
+/// @CELL-SET ( Cell @1 <- )
+pub fn CELL_SET(runtime: *Runtime) anyerror!void {
+    const heaped = try peek_cell(runtime, 1);
+    var old = heaped.swap(try (try runtime.stack_peek()).ref());
+    runtime.release_heaped_object_reference(&old);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @CELL-CAS ( Cell @2 @1 <- Boolean )
+///
+/// If the Cell holds something equal (see Object.eql) to @2, replace it with
+/// @1, all in one go, so that nobody else can sneak a change in in between.
+/// Returns whether the swap happened.
+pub fn CELL_CAS(runtime: *Runtime) anyerror!void {
+    const heaped = try peek_cell(runtime, 2);
+    const peek = try runtime.stack_peek_pair();
+
+    var new = try peek.near.ref();
+    const swapped = if (heaped.compare_and_swap(peek.far.?.*, new)) |_old| swapped: {
+        var old = _old;
+        runtime.release_heaped_object_reference(&old);
+        break :swapped true;
+    } else unswapped: {
+        runtime.release_heaped_object_reference(&new);
+        break :unswapped false;
+    };
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_bool(swapped);
+}
+
+/// @CELL-ADD ( Cell Int <- Int )
+///
+/// Add to the (integer) contents of a Cell, returning what it held before.
+/// See Cell.fetch_add.
+pub fn CELL_ADD(runtime: *Runtime) anyerror!void {
+    const heaped = try peek_cell(runtime, 1);
+    const old = try heaped.fetch_add((try runtime.stack_peek()).*);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(old);
+}
+
+/// Find the Cell `depth` Objects below the top of the Stack (so 0 is the top
+/// itself), raising a TypeError if it isn't one.
+fn peek_cell(runtime: *Runtime, depth: usize) !*Cell {
+    const peek = try runtime.stack_peek_trio();
+    const found = switch (depth) {
+        0 => peek.near,
+        1 => peek.far orelse return StackManipulationError.Underflow,
+        2 => peek.farther orelse return StackManipulationError.Underflow,
+        else => unreachable,
+    };
+
+    if (found.* != .Cell) return InternalError.TypeError;
+    return &found.Cell.value.?;
+}
+
+/// How long wait_for_channel blocks at a time before checking for an
+/// interrupt (see Runtime.interrupt), such as Ctrl-C.
+const INTERRUPT_POLL_NS = 50 * std.time.ns_per_ms;
+
+/// Let other Tasks run (see Runtime.yield_to_others) in the hopes they'll
+/// do something about `chan`, or, if there are none, block on the Channel
+/// itself if another thread has it (see Channel.share), checking for
+/// interrupts as we go. Without another thread, or in unthreaded builds,
+/// nobody could ever come along, and so that's a deadlock.
+fn wait_for_channel(runtime: *Runtime, chan: *Channel, readiness: Channel.Readiness) !void {
+    if (try runtime.yield_to_others()) return;
+    if (comptime !sync.THREADED) return InternalError.Deadlock;
+    if (!chan.is_shared()) return InternalError.Deadlock;
+
+    chan.wait_until(readiness, INTERRUPT_POLL_NS);
+    if (runtime.interrupt_pending()) {
+        runtime.clear_interrupt();
+        return InternalError.Interrupted;
+    }
+}
+
+/// Builds a compound word out of dictionary words, as there's not yet a way
+/// to do so from gale itself.
+fn test_compound(rt: *Runtime, names: []const []const u8) !*Types.HeapedWord {
+    const inner = try rt.alloc.alloc(*Types.HeapedWord, names.len);
+    for (names) |name, idx| inner[idx] = (try rt.find_word(name)).?;
+    return try test_compound_of(rt, inner);
+}
+
+/// As test_compound, but from words in hand. Takes ownership of `inner`.
+fn test_compound_of(rt: *Runtime, inner: []*Types.HeapedWord) !*Types.HeapedWord {
+    // Compound words hold a reference to each of their inner words.
+    for (inner) |word| try word.increment();
+    return try rt.word_from_compound_impl(inner, null);
+}
+
+/// Pops the top Object into a word which pushes it back, via @LIT, which
+/// is how Tasks (with their empty Stacks) are handed their arguments. The
+/// caller gets the Stack's reference to the word.
+fn test_pop_lit(rt: *Runtime) !*Types.HeapedWord {
+    try rt.eval("@LIT");
+    return (try rt.stack_pop()).Word;
+}
+
+test "tasks interleave at @YIELD" {
+    const Log = struct {
+        fn a(context: ?*anyopaque, _: *Runtime) anyerror!void {
+            const log = @ptrCast(*std.ArrayList(u8), @alignCast(@alignOf(std.ArrayList(u8)), context.?));
+            try log.append('a');
+        }
+
+        fn b(context: ?*anyopaque, _: *Runtime) anyerror!void {
+            const log = @ptrCast(*std.ArrayList(u8), @alignCast(@alignOf(std.ArrayList(u8)), context.?));
+            try log.append('b');
+        }
+    };
+
+    var log = std.ArrayList(u8).init(testAllocator);
+    defer log.deinit();
+
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try rt.define_host_closure_word(null, "mark-a", &log, &Log.a);
+    try rt.define_host_closure_word(null, "mark-b", &log, &Log.b);
+
+    const worker = try test_compound(&rt, &.{ "mark-a", "@YIELD", "mark-b" });
+    try rt.stack_push_raw_word(worker);
+    try rt.eval("@DUP @SPAWN @SWAP @SPAWN");
+
+    // Nothing happens until someone waits on a Task...
+    try expectEqual(@as(usize, 0), log.items.len);
+
+    // ...at which point every Task gets a turn, round-robin, and the first
+    // one isn't allowed to finish before the second has started.
+    try rt.eval("@JOIN @JOIN");
+    try expectEqualStrings("aabb", log.items);
+}
+
+test "@JOIN moves results and raises failures" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    // Each Task has its own Stack: the 1 we push here is not visible to the
+    // @DUP (which thus underflows) in the second Task.
+    try rt.eval("1 &@DROP @SPAWN &@DUP @SPAWN");
+    try expectError(StackManipulationError.Underflow, rt.eval("@JOIN"));
+    // The first Task is still around, and fails the same way.
+    try expectError(StackManipulationError.Underflow, rt.eval("@JOIN"));
+    // Which leaves the 1, which is no (longer a) Task at all.
+    try expectError(InternalError.ValueError, rt.eval("@JOIN"));
+
+    try expectError(InternalError.TypeError, rt.eval("1.5 @SPAWN"));
+    _ = try rt.stack_pop();
+}
+
+test "channels carry Objects between tasks" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    // A consumer Task which receives once, from a Channel it's handed.
+    try rt.eval("@CHAN @DUP");
+    const lit = try test_pop_lit(&rt);
+    const inner = try testAllocator.alloc(*Types.HeapedWord, 2);
+    inner[0] = lit;
+    inner[1] = (try rt.find_word("@RECV")).?;
+    try rt.stack_push_raw_word(try test_compound_of(&rt, inner));
+    // The consumer holds its own reference to the @LIT word now.
+    _ = lit.decrement();
+    try rt.eval("@SPAWN");
+
+    // Let the consumer get as far as blocking on the (still empty) Channel:
+    // it gets suspended, rather than failing or deadlocking.
+    try rt.eval("@YIELD @SWAP 42 @SEND");
+
+    try rt.eval("@JOIN");
+    try expect((try rt.stack_pop()).Boolean);
+    try expectEqual(@as(usize, 42), (try rt.stack_pop()).UnsignedInt);
+
+    // With nobody left to send, receiving from an empty Channel could never
+    // finish (short of another thread coming along).
+    try rt.eval("@CHAN");
+    try expectError(InternalError.Deadlock, rt.eval("@RECV"));
+    try rt.eval("@DROP");
+
+    try rt.eval("1 @BCHAN @DUP 1 @SEND @DUP @CLOSE @DUP");
+    try expectError(InternalError.ChannelClosed, rt.eval("2 @SEND"));
+    try rt.eval("@DROP @DROP @DUP @RECV");
+    try expect((try rt.stack_pop()).Boolean);
+    try expectEqual(@as(usize, 1), (try rt.stack_pop()).UnsignedInt);
+    try rt.eval("@RECV");
+    try expect(!(try rt.stack_pop()).Boolean);
+}
+
+test "Channels other threads share are waited on, until interrupted" {
+    if (comptime !sync.THREADED) return error.SkipZigTest;
+
+    const Elsewhere = struct {
+        fn send(chan: *Channel) void {
+            std.time.sleep(10 * std.time.ns_per_ms);
+            _ = chan.try_send(.{ .UnsignedInt = 7 }) catch unreachable;
+            chan.unshare();
+        }
+
+        fn interrupt(rt: *Runtime) void {
+            std.time.sleep(10 * std.time.ns_per_ms);
+            rt.interrupt();
+        }
+    };
+
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try rt.eval("@CHAN @DUP @DUP");
+    const chan = &(try rt.stack_peek()).Channel.value.?;
+
+    chan.share();
+    const sender = try std.Thread.spawn(.{}, Elsewhere.send, .{chan});
+    try rt.eval("@RECV");
+    sender.join();
+    try expect((try rt.stack_pop()).Boolean);
+    try expectEqual(@as(usize, 7), (try rt.stack_pop()).UnsignedInt);
+
+    // Nothing will ever be sent this time, but Ctrl-C (or the like) gets us
+    // out regardless.
+    chan.share();
+    const interrupter = try std.Thread.spawn(.{}, Elsewhere.interrupt, .{&rt});
+    try expectError(InternalError.Interrupted, rt.eval("@RECV"));
+    interrupter.join();
+    chan.unshare();
+    try rt.eval("@DROP @DROP");
+}
+
+test "cells" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try rt.eval("1 @CELL @DUP @DUP 2 3 @CELL-CAS");
+    try expect(!(try rt.stack_pop()).Boolean);
+    try rt.eval("1 3 @CELL-CAS");
+    try expect((try rt.stack_pop()).Boolean);
+
+    try rt.eval("@DUP 4 @CELL-ADD");
+    try expectEqual(@as(usize, 3), (try rt.stack_pop()).UnsignedInt);
+
+    try rt.eval("@DUP \"seven\" @CELL-SET @CELL-GET");
+    var seven = try rt.stack_pop();
+    defer rt.release_heaped_object_reference(&seven);
+    try expectEqualStrings("seven", seven.String.value.?);
+}
+
+test "@JOIN of a Running Task deadlocks" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    // A Task which, once it knows its own id (the 1 pushed into its Stack,
+    // below), tries to wait on itself.
+    const worker = try test_compound(&rt, &.{"@JOIN"});
+    try rt.stack_push_raw_word(worker);
+    try rt.eval("@SPAWN");
+
+    const task = rt.tasks.get(1).?;
+    task.stack = try task.stack.do_push_uint(1);
+
+    try expectError(InternalError.Deadlock, rt.eval("@JOIN"));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -15,17 +15,104 @@ test "gale library test suite" {
     const std = @import("std");
     std.testing.refAllDecls(@This());
 
+    _ = @import("./arithmetic_words.zig");
+    _ = @import("./array_words.zig");
+    _ = @import("./bitwise_words.zig");
+    _ = @import("./bundle.zig");
+    _ = @import("./calendar.zig");
+    _ = @import("./capabilities.zig");
+    _ = @import("./cell.zig");
+    _ = @import("./channel.zig");
+    _ = @import("./conditional.zig");
+    _ = @import("./conversion.zig");
+    _ = @import("./coverage.zig");
+    _ = @import("./csv.zig");
+    _ = @import("./csv_words.zig");
+    _ = @import("./debugger.zig");
+    _ = @import("./digest_words.zig");
+    _ = @import("./docstrings.zig");
+    _ = @import("./encoding_words.zig");
+    _ = @import("./env_words.zig");
+    _ = @import("./event_log.zig");
+    _ = @import("./features.zig");
+    _ = @import("./format_words.zig");
+    _ = @import("./formatter.zig");
+    _ = @import("./fs_words.zig");
     _ = @import("./gale.zig");
+    _ = @import("./hashbang.zig");
     _ = @import("./helpers.zig");
+    _ = @import("./http.zig");
+    _ = @import("./http_words.zig");
+    _ = @import("./image.zig");
     _ = @import("./internal_error.zig");
+    _ = @import("./introspection_words.zig");
+    _ = @import("./io.zig");
+    _ = @import("./io_words.zig");
+    _ = @import("./iter_words.zig");
+    _ = @import("./json.zig");
+    _ = @import("./json_words.zig");
+    _ = @import("./lexer.zig");
+    _ = @import("./library_path.zig");
+    _ = @import("./line_cursor.zig");
+    _ = @import("./linter.zig");
+    _ = @import("./locals_words.zig");
+    _ = @import("./map.zig");
+    _ = @import("./map_words.zig");
+    _ = @import("./math_words.zig");
+    _ = @import("./module.zig");
+    _ = @import("./module_words.zig");
+    _ = @import("./msgpack.zig");
+    _ = @import("./msgpack_words.zig");
+    _ = @import("./net.zig");
+    _ = @import("./net_words.zig");
+    _ = @import("./notebook.zig");
     _ = @import("./nucleus_words.zig");
     _ = @import("./object.zig");
+    _ = @import("./observer.zig");
+    _ = @import("./package.zig");
+    _ = @import("./parallel_words.zig");
     _ = @import("./parsed_word.zig");
+    _ = @import("./pipeline.zig");
+    _ = @import("./path_words.zig");
+    _ = @import("./prelude.zig");
+    _ = @import("./proc_words.zig");
+    _ = @import("./project.zig");
+    _ = @import("./random_words.zig");
     _ = @import("./rc.zig");
+    _ = @import("./recording.zig");
+    _ = @import("./regex.zig");
+    _ = @import("./regex_words.zig");
     _ = @import("./runtime.zig");
+    _ = @import("./semantic_tokens.zig");
+    _ = @import("./set.zig");
+    _ = @import("./set_words.zig");
+    _ = @import("./sexp.zig");
+    _ = @import("./sexp_words.zig");
+    _ = @import("./snippet.zig");
     _ = @import("./shape.zig");
+    _ = @import("./source_encoding.zig");
     _ = @import("./stack.zig");
+    _ = @import("./stats.zig");
+    _ = @import("./sync.zig");
+    _ = @import("./tags.zig");
+    _ = @import("./task.zig");
+    _ = @import("./task_words.zig");
+    _ = @import("./test_runner.zig");
+    _ = @import("./test_words.zig");
+    _ = @import("./thunk_words.zig");
+    _ = @import("./time_words.zig");
+    _ = @import("./toml.zig");
+    _ = @import("./toml_words.zig");
+    _ = @import("./trace_words.zig");
+    _ = @import("./tracer.zig");
     _ = @import("./types.zig");
+    _ = @import("./unicode.zig");
+    _ = @import("./unicode_words.zig");
+    _ = @import("./variant_words.zig");
+    _ = @import("./version.zig");
+    _ = @import("./visualizer.zig");
+    _ = @import("./vocabulary.zig");
+    _ = @import("./well_known_entities.zig");
     _ = @import("./word.zig");
     _ = @import("./word_list.zig");
     _ = @import("./word_map.zig");
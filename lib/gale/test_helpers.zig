@@ -11,6 +11,8 @@
 // OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
 // PERFORMANCE OF THIS SOFTWARE.
 
+const std = @import("std");
+
 const Runtime = @import("./runtime.zig").Runtime;
 
 pub fn push_one(runtime: *Runtime) anyerror!void {
@@ -20,3 +22,19 @@ pub fn push_one(runtime: *Runtime) anyerror!void {
 pub fn push_two(runtime: *Runtime) anyerror!void {
     try runtime.stack_push_uint(2);
 }
+
+/// Bytes the lexer and parser treat specially, weighted heavily enough that
+/// fill_arbitrary's output hits their edge cases rather than only ever
+/// producing nonsense words.
+const INTERESTING_BYTES = " \t\r\n\"{}/:&,.+-0123456789@a#!";
+
+/// Fill `buf` with arbitrary bytes, for fuzzing; about half of them drawn
+/// from INTERESTING_BYTES.
+pub fn fill_arbitrary(random: std.rand.Random, buf: []u8) void {
+    for (buf) |*byte| {
+        byte.* = if (random.boolean())
+            INTERESTING_BYTES[random.uintLessThan(usize, INTERESTING_BYTES.len)]
+        else
+            random.int(u8);
+    }
+}
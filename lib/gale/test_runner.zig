@@ -0,0 +1,176 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const Stack = @import("./stack.zig").Stack;
+
+// Tests written in gale: @TEST registers them (see test_words.zig) as
+// source is loaded, and `run` (or `gale test`) runs every one registered,
+// each on a fresh Stack. A test passes if its body finishes without error,
+// and the assertion words fail it with an InternalError.AssertionFailed,
+// having first left a description of what went wrong behind for the report.
+
+pub const TestCase = struct {
+    /// Owned.
+    name: []u8,
+    /// A Word to run, or a String of source to eval. We hold a reference.
+    body: Object,
+};
+
+/// Every test registered in a Runtime, and whatever the last assertion to
+/// fail had to say about it.
+pub const Registry = struct {
+    const Self = @This();
+
+    cases: std.ArrayList(TestCase),
+    /// Owned. Set by `fail`, and taken by `run`.
+    failure: ?[]u8,
+
+    pub fn init(alloc: Allocator) Self {
+        return .{ .cases = std.ArrayList(TestCase).init(alloc), .failure = null };
+    }
+
+    pub fn deinit(self: *Self, rt: *Runtime) void {
+        for (self.cases.items) |*case| {
+            rt.alloc.free(case.name);
+            rt.release_heaped_object_reference(&case.body);
+        }
+        self.cases.deinit();
+        self.clear_failure(rt.alloc);
+    }
+
+    /// Register a test, taking over the reference to `body`, which must be
+    /// a Word or a String.
+    pub fn add(self: *Self, alloc: Allocator, name: []const u8, body: Object) !void {
+        if (body != .Word and body != .String) return InternalError.TypeError;
+
+        const owned = try alloc.dupe(u8, name);
+        errdefer alloc.free(owned);
+        try self.cases.append(.{ .name = owned, .body = body });
+    }
+
+    /// Record why an assertion failed, and then fail with
+    /// InternalError.AssertionFailed, as the assertion should.
+    pub fn fail(self: *Self, alloc: Allocator, comptime fmt: []const u8, args: anytype) !void {
+        self.clear_failure(alloc);
+        self.failure = try std.fmt.allocPrint(alloc, fmt, args);
+        return InternalError.AssertionFailed;
+    }
+
+    fn clear_failure(self: *Self, alloc: Allocator) void {
+        if (self.failure) |failure| alloc.free(failure);
+        self.failure = null;
+    }
+};
+
+pub const Summary = struct {
+    passed: usize = 0,
+    failed: usize = 0,
+};
+
+/// Run every test registered in `rt`, in registration order, reporting
+/// each (and, for failures, why) to `writer`, and then a tally.
+pub fn run(rt: *Runtime, writer: anytype) !Summary {
+    var summary = Summary{};
+
+    for (rt.tests.cases.items) |case| {
+        if (run_one(rt, case)) |_| {
+            summary.passed += 1;
+            try writer.print("ok {s}\n", .{case.name});
+        } else |err| {
+            summary.failed += 1;
+            try writer.print("FAIL {s}: {s}\n", .{ case.name, @errorName(err) });
+
+            if (rt.tests.failure) |failure| {
+                var lines = std.mem.split(u8, failure, "\n");
+                while (lines.next()) |line| try writer.print("    {s}\n", .{line});
+            }
+        }
+
+        rt.tests.clear_failure(rt.alloc);
+    }
+
+    try writer.print("{d} passed, {d} failed\n", .{ summary.passed, summary.failed });
+    return summary;
+}
+
+fn run_one(rt: *Runtime, case: TestCase) !void {
+    const outer = rt.stack;
+    rt.stack = try Stack.init(rt.alloc, null);
+    defer {
+        rt.stack.release();
+        rt.stack = outer;
+    }
+
+    switch (case.body) {
+        .Word => |word| try rt.run_word(word),
+        .String => |source| try rt.eval(source.value.?),
+        else => unreachable,
+    }
+}
+
+/// Write `count` Objects from the top of `stack`, starting `skip` Objects
+/// down (so as to leave out the assertion's own operands), bottom to top
+/// after their count, as the assertion words describe the Stack.
+pub fn write_stack(writer: anytype, stack: *Stack, skip: usize, count: usize) !void {
+    try writer.print("<{d}>", .{count});
+
+    var idx = count;
+    while (idx > 0) {
+        idx -= 1;
+        try writer.print(" {}", .{stack.nth_from_top(skip + idx).?.*});
+    }
+}
+
+test "run" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try rt.eval(
+        \\"passes" "1 2 @SWAP 1 @ASSERT-EQ 2 @ASSERT-EQ" @TEST
+        \\"fails" "1 2 @ASSERT-EQ" @TEST
+        \\"errors" "@DROP" @TEST
+    );
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    const summary = try run(&rt, out.writer());
+    try expectEqual(@as(usize, 1), summary.passed);
+    try expectEqual(@as(usize, 2), summary.failed);
+    try expectEqualStrings(
+        \\ok passes
+        \\FAIL fails: AssertionFailed
+        \\    expected 2
+        \\      actual 1
+        \\FAIL errors: Underflow
+        \\1 passed, 2 failed
+        \\
+    , out.items);
+
+    // Tests don't touch the Stack they're run from.
+    try expectEqual(@as(usize, 0), rt.stack.depth());
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
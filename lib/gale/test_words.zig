@@ -0,0 +1,145 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const test_runner = @import("./test_runner.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every testing word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@ASSERT", ASSERT },
+    .{ "@ASSERT-EQ", ASSERT_EQ },
+    .{ "@ASSERT-STACK", ASSERT_STACK },
+    .{ "@TEST", TEST },
+};
+
+/// Define every testing word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @TEST ( String @1 -> )
+///
+/// Register a test, named by the String, for the test runner (see
+/// test_runner.zig) to run later on a Stack of its own. The body is either a
+/// Word to run or a String of source to eval.
+pub fn TEST(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (far.* != .String or (peek.near.* != .Word and peek.near.* != .String)) return InternalError.TypeError;
+
+    var body = try peek.near.ref();
+    runtime.tests.add(runtime.alloc, far.String.value.?, body) catch |err| {
+        runtime.release_heaped_object_reference(&body);
+        return err;
+    };
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @ASSERT ( Boolean -> )
+///
+/// Fail with an AssertionFailed unless the Boolean is true.
+pub fn ASSERT(runtime: *Runtime) anyerror!void {
+    const top = try runtime.stack_peek();
+    if (top.* != .Boolean) return InternalError.TypeError;
+
+    if (!top.Boolean) return runtime.tests.fail(runtime.alloc, "expected true", .{});
+    _ = try runtime.stack_pop();
+}
+
+/// @ASSERT-EQ ( @2 @1 -> )
+///
+/// Fail with an AssertionFailed unless the further Object (what was
+/// computed) equals the nearer one (what was expected). Objects of
+/// different kinds are never equal.
+pub fn ASSERT_EQ(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const actual = peek.far orelse return StackManipulationError.Underflow;
+    const expected = peek.near;
+
+    if (!actual.eql(expected.*)) {
+        return runtime.tests.fail(runtime.alloc, "expected {}\n  actual {}", .{ expected.*, actual.* });
+    }
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+/// @ASSERT-STACK ( Array -> )
+///
+/// Fail with an AssertionFailed unless everything else on the Stack is
+/// exactly the Array's members, bottom to top. Unlike the other assertions,
+/// this consumes only the Array: what it checked stays put.
+pub fn ASSERT_STACK(runtime: *Runtime) anyerror!void {
+    const top = try runtime.stack_peek();
+    if (top.* != .Array) return InternalError.TypeError;
+
+    const members = top.Array.value.?.items;
+    const depth = runtime.stack.depth() - 1;
+
+    var matches = members.len == depth;
+    for (members) |member, idx| {
+        if (!matches) break;
+        matches = member.eql(runtime.stack.nth_from_top(depth - idx).?.*);
+    }
+
+    if (!matches) {
+        var description = std.ArrayList(u8).init(runtime.alloc);
+        defer description.deinit();
+        const writer = description.writer();
+
+        try writer.print("expected <{d}>", .{members.len});
+        for (members) |member| try writer.print(" {}", .{member});
+        try writer.writeAll("\n  actual ");
+        try test_runner.write_stack(writer, runtime.stack, 1, depth);
+
+        return runtime.tests.fail(runtime.alloc, "{s}", .{description.items});
+    }
+
+    try runtime.stack_wrangle(.DropTopObject);
+}
+
+test "@ASSERT, @ASSERT-EQ, and @ASSERT-STACK" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    // @EQ leaves its operands be, which @ASSERT-EQ then checks over again.
+    try runtime.eval("1 1 @EQ @ASSERT @ASSERT-EQ \"a\" \"a\" @ASSERT-EQ");
+    try expectEqual(@as(usize, 0), runtime.stack.depth());
+
+    try expectError(InternalError.AssertionFailed, runtime.eval("1 :one @ASSERT-EQ"));
+    try expectEqualStrings("expected :one\n  actual 1", runtime.tests.failure.?);
+    try runtime.eval("@DROP @DROP");
+
+    try runtime.eval("1 2 \"[1, 2]\" @JSON-PARSE @ASSERT-STACK");
+    try expectError(InternalError.AssertionFailed, runtime.eval("\"[2, 1]\" @JSON-PARSE @ASSERT-STACK"));
+    try expectEqualStrings("expected <2> 2 1\n  actual <2> 1 2", runtime.tests.failure.?);
+
+    var array = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&array);
+    _ = try runtime.stack_pop_pair();
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
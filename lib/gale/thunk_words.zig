@@ -0,0 +1,181 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const Cell = @import("./cell.zig").Cell;
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+// Thunks: words whose (single) result is worked out at most once, and only
+// once someone asks for it. A thunk is a Cell (see cell.zig) holding a
+// variant (see variant_words.zig) saying how far along it is:
+//
+// - `[ :delayed Word ]`, until first forced.
+// - `[ :forcing ]`, while the Word runs. Forcing a thunk from within its own
+//   Word is a ValueError, rather than running forever.
+// - `[ :forced @1 ]`, once the Word has left @1 behind.
+//
+// A Word failing (or leaving anything other than exactly one Object behind)
+// leaves its thunk delayed, to be tried again next time.
+
+pub const DELAYED_TAG = "delayed";
+pub const FORCING_TAG = "forcing";
+pub const FORCED_TAG = "forced";
+
+/// Every thunk word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@DELAY", DELAY },
+    .{ "@FORCE", FORCE },
+};
+
+/// Define every thunk word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @DELAY ( Word <- Cell )
+///
+/// A thunk which, once forced, runs the Word.
+pub fn DELAY(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Word) return InternalError.TypeError;
+
+    var state = try new_state(runtime, DELAYED_TAG, peeked.*);
+    const heaped = runtime.new_cell(state) catch |err| {
+        runtime.release_heaped_object_reference(&state);
+        return err;
+    };
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(.{ .Cell = heaped });
+}
+
+/// @FORCE ( Cell <- @1 )
+///
+/// The thunk's result, running its Word for it first if nobody has yet.
+/// Cells which aren't thunks are a TypeError.
+pub fn FORCE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .Cell) return InternalError.TypeError;
+    const thunk = &peeked.Cell.value.?;
+
+    var state = try thunk.load();
+    defer runtime.release_heaped_object_reference(&state);
+    const members = try members_of(state);
+    const tag = members[0].Symbol.value.?;
+
+    if (std.mem.eql(u8, FORCED_TAG, tag)) {
+        var result = try members[1].ref();
+        defer runtime.release_heaped_object_reference(&result);
+        try runtime.stack_wrangle(.DropTopObject);
+        try runtime.stack_push_object(result);
+        return;
+    }
+
+    if (std.mem.eql(u8, FORCING_TAG, tag)) return InternalError.ValueError;
+
+    // Held on to while the Word runs, so that nothing it does can take the
+    // thunk out from under us.
+    var held = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&held);
+
+    try replace_state(runtime, thunk, try new_state(runtime, FORCING_TAG, null));
+    const depth = runtime.stack.depth();
+
+    runtime.run_word(members[1].Word) catch |err| {
+        try replace_state(runtime, thunk, try state.ref());
+        return err;
+    };
+
+    if (runtime.stack.depth() != depth + 1) {
+        try replace_state(runtime, thunk, try state.ref());
+        return InternalError.ValueError;
+    }
+
+    try replace_state(runtime, thunk, try new_state(runtime, FORCED_TAG, (try runtime.stack_peek()).*));
+}
+
+/// A state variant tagged `tag`, carrying `payload` (to which a new
+/// reference is taken) if given, with a reference of its own.
+fn new_state(runtime: *Runtime, tag: []const u8, payload: ?Object) !Object {
+    const variant = try runtime.new_array(2);
+    errdefer runtime.release_array(variant);
+
+    const symbol = Object{ .Symbol = (try runtime.get_or_put_symbol(tag)).value_ptr };
+    variant.value.?.appendAssumeCapacity(try symbol.ref());
+    if (payload) |obj| variant.value.?.appendAssumeCapacity(try obj.ref());
+
+    variant.increment() catch unreachable;
+    return Object{ .Array = variant };
+}
+
+/// Put `state` (whose reference the thunk takes over) in the thunk.
+fn replace_state(runtime: *Runtime, thunk: *Cell, state: Object) !void {
+    var old = thunk.swap(state);
+    runtime.release_heaped_object_reference(&old);
+}
+
+/// The members of a thunk's state, checked over.
+fn members_of(state: Object) ![]Object {
+    if (state != .Array) return InternalError.TypeError;
+    const members = state.Array.value.?.items;
+    if (members.len == 0 or members[0] != .Symbol) return InternalError.TypeError;
+
+    const tag = members[0].Symbol.value.?;
+    const expected: usize = if (std.mem.eql(u8, FORCING_TAG, tag)) 1 else 2;
+    if (members.len != expected) return InternalError.TypeError;
+    if (std.mem.eql(u8, DELAYED_TAG, tag) and members[1] != .Word) return InternalError.TypeError;
+    if (!std.mem.eql(u8, DELAYED_TAG, tag) and !std.mem.eql(u8, FORCING_TAG, tag) and !std.mem.eql(u8, FORCED_TAG, tag)) {
+        return InternalError.TypeError;
+    }
+
+    return members;
+}
+
+test "@DELAY and @FORCE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    // A Word counting how many times it's been run.
+    try runtime.eval("0 @CELL :runs @CONSTANT");
+    try runtime.eval("&runs 1 @LIT @COMPOSE &@CELL-ADD @COMPOSE @DELAY");
+    try runtime.eval("@DUP @FORCE @SWAP @DUP @FORCE @SWAP");
+    try runtime.eval("@CELL-GET");
+    var state = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&state);
+    try expectEqualStrings(FORCED_TAG, state.Array.value.?.items[0].Symbol.value.?);
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 0), try runtime.stack_pop_value(usize));
+
+    try runtime.eval("runs @CELL-GET");
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+
+    try expectError(InternalError.ValueError, runtime.eval("6 @LIT @DUP @COMPOSE @DELAY @FORCE"));
+    try runtime.eval("@DROP @DROP");
+    try expectError(InternalError.TypeError, runtime.eval("4 @FORCE"));
+    try expectError(InternalError.TypeError, runtime.eval("@CELL @FORCE"));
+    try runtime.eval("@DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
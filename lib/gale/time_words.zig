@@ -0,0 +1,171 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const calendar = @import("./calendar.zig");
+const helpers = @import("./helpers.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+
+/// Every time word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@ELAPSED", ELAPSED },
+    .{ "@NOW", NOW },
+    .{ "@SLEEP-MS", SLEEP_MS },
+    .{ "@TIME-FORMAT", TIME_FORMAT },
+    .{ "@TIME-PARSE", TIME_PARSE },
+    .{ "@UNIX-TIME", UNIX_TIME },
+};
+
+/// Define every time word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @NOW ( <- UnsignedInt )
+///
+/// Read the monotonic clock: nanoseconds since the Runtime was created. This
+/// never goes backwards (unlike the wall clock), but also means nothing
+/// outside of this Runtime, so it's good for marks to hand to @ELAPSED and
+/// little else. Raises InternalError.Unimplemented on systems without such a
+/// clock.
+pub fn NOW(runtime: *Runtime) anyerror!void {
+    const ns = try runtime.read_monotonic_clock();
+    try runtime.stack_push_uint(std.math.cast(usize, ns) orelse return InternalError.ValueError);
+}
+
+/// @ELAPSED ( UnsignedInt UnsignedInt <- UnsignedInt )
+///
+/// How many nanoseconds passed between two @NOW marks, the earlier of which
+/// is further down the stack. Marks in the wrong order are a ValueError.
+pub fn ELAPSED(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt or far.* != .UnsignedInt) return InternalError.TypeError;
+
+    const later = peek.near.UnsignedInt;
+    const earlier = far.UnsignedInt;
+    if (later < earlier) return InternalError.ValueError;
+
+    _ = try runtime.stack_pop_pair();
+    try runtime.stack_push_uint(later - earlier);
+}
+
+/// @SLEEP-MS ( UnsignedInt <- )
+///
+/// Do nothing for (at least) the given number of milliseconds. This blocks
+/// the whole Runtime, Tasks included, and can't be interrupted (see
+/// Runtime.interrupt) until it's done.
+// TODO: let Tasks get a slice in while we wait, rather than blocking them.
+pub fn SLEEP_MS(runtime: *Runtime) anyerror!void {
+    if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+    const ns = std.math.mul(u64, peeked.UnsignedInt, std.time.ns_per_ms) catch return InternalError.ValueError;
+
+    _ = try runtime.stack_pop();
+    std.time.sleep(ns);
+}
+
+/// @UNIX-TIME ( <- SignedInt )
+///
+/// Read the wall clock: seconds since 1970-01-01T00:00:00Z, not counting
+/// leap seconds. Unlike @NOW, this can jump around (say, when the system's
+/// clock is corrected), but means the same thing everywhere. Raises
+/// InternalError.Unimplemented on systems without a wall clock.
+pub fn UNIX_TIME(runtime: *Runtime) anyerror!void {
+    const seconds = @divFloor(try runtime.read_wall_clock_ms(), std.time.ms_per_s);
+    try runtime.stack_push_sint(std.math.cast(isize, seconds) orelse return InternalError.ValueError);
+}
+
+/// @TIME-FORMAT ( SignedInt String <- String )
+///
+/// Write out a @UNIX-TIME timestamp (in UTC) according to a pattern like
+/// `"%Y-%m-%d"`. See calendar.zig for what patterns can contain.
+pub fn TIME_FORMAT(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .SignedInt) return InternalError.TypeError;
+
+    var formatted = std.ArrayList(u8).init(runtime.alloc);
+    defer formatted.deinit();
+    try calendar.format(formatted.writer(), far.SignedInt, peek.near.String.value.?);
+    const str = try runtime.get_or_put_string(formatted.items);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    _ = try runtime.stack_pop();
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+/// @TIME-PARSE ( String String <- SignedInt )
+///
+/// The inverse of @TIME-FORMAT: the timestamp that the text (further down
+/// the Stack) describes according to the pattern (on top). Text which
+/// doesn't match the pattern is a ValueError.
+pub fn TIME_PARSE(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .String) return InternalError.TypeError;
+
+    const timestamp = try calendar.parse(far.String.value.?, peek.near.String.value.?);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_sint(std.math.cast(isize, timestamp) orelse return InternalError.ValueError);
+}
+
+test "@NOW, @SLEEP-MS, and @ELAPSED" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("@NOW 2 @SLEEP-MS @NOW @ELAPSED");
+    try expect((try runtime.stack_pop_value(usize)) >= 2 * std.time.ns_per_ms);
+
+    // Later marks are higher up the Stack, not further down.
+    try runtime.eval("2 1");
+    try expectError(InternalError.ValueError, runtime.eval("@ELAPSED"));
+    _ = try runtime.stack_pop_pair();
+
+    try expectError(InternalError.TypeError, runtime.eval("-1 @SLEEP-MS"));
+    _ = try runtime.stack_pop();
+}
+
+test "@TIME-FORMAT and @TIME-PARSE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("+1700000000 \"%Y-%m-%dT%H:%M:%S\" @TIME-FORMAT");
+    var formatted = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&formatted);
+    try expectEqualStrings("2023-11-14T22:13:20", formatted.String.value.?);
+
+    try runtime.eval("\"2023-11-14\" \"%Y-%m-%d\" @TIME-PARSE");
+    try expectEqual(@as(isize, 1699920000), try runtime.stack_pop_value(isize));
+
+    try runtime.eval("@UNIX-TIME");
+    try expect((try runtime.stack_pop_value(isize)) > 1700000000);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
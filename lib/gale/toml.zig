@@ -0,0 +1,582 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const Types = @import("./types.zig");
+
+// Reading TOML (https://toml.io/en/v1.0.0) documents into Objects. Tables
+// (inline or otherwise) become Maps, arrays (including arrays of tables)
+// become Arrays, and everything else maps as JSON's equivalents do (see
+// json.zig): non-negative integers are UnsignedInts, negative ones are
+// SignedInts, and so on. TOML's dates and times have no Object of their
+// own, and so are Strings of exactly the text they were written as.
+//
+// We're a touch more lenient than the spec in a couple of corners: tables
+// may be re-opened by a second [header], and [[headers]] may extend arrays of
+// inline tables. Anything else it disallows (defining a key twice, say) is a
+// ValueError, as is anything which isn't TOML at all.
+
+/// How deeply arrays and inline tables may nest before we give up with a
+/// LimitExceeded rather than risk running out of (Zig) stack.
+// TODO: configurable in build.zig
+pub const MAX_NESTING = 512;
+
+/// Parse `text` as a TOML document, producing a Map. As with anything fresh
+/// off the heap, the result holds no references yet.
+pub fn parse(rt: *Runtime, text: []const u8) !Object {
+    const root = try rt.new_map();
+    errdefer rt.release_map(root);
+
+    var parser = Parser{ .rt = rt, .text = text, .pos = 0, .current = root, .root = root };
+    try parser.parse_document();
+
+    return Object{ .Map = root };
+}
+
+const Key = std.ArrayList([]u8);
+
+const Parser = struct {
+    const Self = @This();
+
+    rt: *Runtime,
+    text: []const u8,
+    pos: usize,
+    root: *Types.HeapedMap,
+    /// Whichever table the most recent [header] opened.
+    current: *Types.HeapedMap,
+
+    fn parse_document(self: *Self) !void {
+        while (true) {
+            self.skip_blank_lines();
+            if (self.at_end()) return;
+
+            if (self.peek() == '[') {
+                try self.parse_header();
+            } else {
+                try self.parse_key_value(self.current, 0);
+            }
+            try self.expect_line_end();
+        }
+    }
+
+    fn parse_header(self: *Self) !void {
+        self.pos += 1;
+        const is_array = self.eat('[');
+
+        var key = Key.init(self.rt.alloc);
+        defer free_key(self.rt.alloc, &key);
+        try self.parse_key(&key);
+
+        if (!self.eat(']')) return InternalError.ValueError;
+        if (is_array and !self.eat(']')) return InternalError.ValueError;
+
+        const parents = key.items[0 .. key.items.len - 1];
+        const last = key.items[key.items.len - 1];
+
+        var table = self.root;
+        for (parents) |segment| table = try self.descend(table, segment);
+
+        if (!is_array) {
+            self.current = try self.descend(table, last);
+            return;
+        }
+
+        const array = if (table.value.?.get(last)) |existing| switch (existing) {
+            .Array => |arr| arr,
+            else => return InternalError.ValueError,
+        } else created: {
+            const fresh = try self.rt.new_array(1);
+            try self.adopt(table, last, .{ .Array = fresh });
+            break :created fresh;
+        };
+
+        const element = try self.rt.new_map();
+        var element_obj = try (Object{ .Map = element }).ref();
+        errdefer element_obj.deinit(self.rt.alloc);
+        try array.value.?.append(element_obj);
+        self.current = element;
+    }
+
+    fn parse_key_value(self: *Self, table: *Types.HeapedMap, depth: usize) !void {
+        var key = Key.init(self.rt.alloc);
+        defer free_key(self.rt.alloc, &key);
+        try self.parse_key(&key);
+
+        self.skip_whitespace();
+        if (!self.eat('=')) return InternalError.ValueError;
+        self.skip_whitespace();
+
+        var value = try (try self.parse_value(depth)).ref();
+        // Ownership passes to the table only once it's been filed away.
+        var filed = false;
+        defer if (!filed) value.deinit(self.rt.alloc);
+
+        var target = table;
+        for (key.items[0 .. key.items.len - 1]) |segment| target = try self.descend(target, segment);
+
+        const last = key.items[key.items.len - 1];
+        if (target.value.?.get(last) != null) return InternalError.ValueError;
+        try target.value.?.put(self.rt.alloc, last, value);
+        filed = true;
+    }
+
+    /// The table filed under `segment` in `table`, creating it if need be.
+    /// For arrays of tables, that's the most recently added one.
+    fn descend(self: *Self, table: *Types.HeapedMap, segment: []const u8) !*Types.HeapedMap {
+        if (table.value.?.get(segment)) |existing| switch (existing) {
+            .Map => |inner| return inner,
+            .Array => |arr| {
+                const items = arr.value.?.items;
+                if (items.len == 0 or items[items.len - 1] != .Map) return InternalError.ValueError;
+                return items[items.len - 1].Map;
+            },
+            else => return InternalError.ValueError,
+        };
+
+        const fresh = try self.rt.new_map();
+        try self.adopt(table, segment, .{ .Map = fresh });
+        return fresh;
+    }
+
+    /// File a fresh Array or Map into `table`.
+    fn adopt(self: *Self, table: *Types.HeapedMap, segment: []const u8, fresh: Object) !void {
+        var obj = try fresh.ref();
+        errdefer obj.deinit(self.rt.alloc);
+        try table.value.?.put(self.rt.alloc, segment, obj);
+    }
+
+    fn parse_key(self: *Self, key: *Key) !void {
+        while (true) {
+            self.skip_whitespace();
+            if (self.at_end()) return InternalError.ValueError;
+
+            const segment = switch (self.peek()) {
+                '"' => try self.parse_basic_string(),
+                '\'' => try self.parse_literal_string(),
+                else => try self.rt.alloc.dupe(u8, try self.take_bare_key()),
+            };
+            key.append(segment) catch |err| {
+                self.rt.alloc.free(segment);
+                return err;
+            };
+
+            self.skip_whitespace();
+            if (!self.eat('.')) return;
+        }
+    }
+
+    fn take_bare_key(self: *Self) ![]const u8 {
+        const start = self.pos;
+        while (!self.at_end()) : (self.pos += 1) {
+            const chr = self.peek();
+            if (!(std.ascii.isAlNum(chr) or chr == '_' or chr == '-')) break;
+        }
+
+        if (self.pos == start) return InternalError.ValueError;
+        return self.text[start..self.pos];
+    }
+
+    fn parse_value(self: *Self, depth: usize) anyerror!Object {
+        if (depth > MAX_NESTING) return InternalError.LimitExceeded;
+        if (self.at_end()) return InternalError.ValueError;
+
+        switch (self.peek()) {
+            '"', '\'' => {
+                const bytes = if (self.peek() == '"')
+                    try self.parse_basic_string()
+                else
+                    try self.parse_literal_string();
+                const str = self.rt.new_string_owned(bytes) catch |err| {
+                    self.rt.alloc.free(bytes);
+                    return err;
+                };
+                return Object{ .String = str };
+            },
+            '[' => return try self.parse_array(depth),
+            '{' => return try self.parse_inline_table(depth),
+            else => return try self.parse_scalar(),
+        }
+    }
+
+    fn parse_array(self: *Self, depth: usize) !Object {
+        self.pos += 1;
+
+        const array = try self.rt.new_array(0);
+        errdefer self.rt.release_array(array);
+
+        while (true) {
+            self.skip_blank_lines();
+            if (self.eat(']')) break;
+
+            var member = try (try self.parse_value(depth + 1)).ref();
+            errdefer member.deinit(self.rt.alloc);
+            try array.value.?.append(member);
+
+            self.skip_blank_lines();
+            if (self.eat(']')) break;
+            if (!self.eat(',')) return InternalError.ValueError;
+        }
+
+        return Object{ .Array = array };
+    }
+
+    fn parse_inline_table(self: *Self, depth: usize) !Object {
+        self.pos += 1;
+
+        const table = try self.rt.new_map();
+        errdefer self.rt.release_map(table);
+
+        self.skip_whitespace();
+        if (self.eat('}')) return Object{ .Map = table };
+
+        while (true) {
+            try self.parse_key_value(table, depth + 1);
+
+            self.skip_whitespace();
+            if (self.eat('}')) break;
+            if (!self.eat(',')) return InternalError.ValueError;
+        }
+
+        return Object{ .Map = table };
+    }
+
+    fn parse_scalar(self: *Self) !Object {
+        const start = self.pos;
+        while (!self.at_end()) : (self.pos += 1) {
+            switch (self.peek()) {
+                ',', ']', '}', '#', ' ', '\t', '\r', '\n' => break,
+                else => {},
+            }
+        }
+
+        // Date-times may be written with a space between the date and
+        // the time, rather than a T.
+        if (self.pos - start == 10 and self.text[start + 4] == '-' and self.pos + 3 < self.text.len and
+            self.text[self.pos] == ' ' and std.ascii.isDigit(self.text[self.pos + 1]) and self.text[self.pos + 3] == ':')
+        {
+            self.pos += 1;
+            while (!self.at_end()) : (self.pos += 1) {
+                switch (self.peek()) {
+                    ',', ']', '}', '#', ' ', '\t', '\r', '\n' => break,
+                    else => {},
+                }
+            }
+        }
+
+        const token = self.text[start..self.pos];
+        if (token.len == 0) return InternalError.ValueError;
+
+        if (std.mem.eql(u8, token, "true")) return Object{ .Boolean = true };
+        if (std.mem.eql(u8, token, "false")) return Object{ .Boolean = false };
+        if (is_date_time(token)) return Object{ .String = (try self.rt.get_or_put_string(token)).value_ptr };
+
+        return try parse_number(token);
+    }
+
+    fn parse_basic_string(self: *Self) ![]u8 {
+        const multiline = std.mem.startsWith(u8, self.text[self.pos..], "\"\"\"");
+        self.pos += if (multiline) @as(usize, 3) else 1;
+        if (multiline) self.skip_one_newline();
+
+        var out = std.ArrayList(u8).init(self.rt.alloc);
+        errdefer out.deinit();
+
+        while (true) {
+            if (self.at_end()) return InternalError.ValueError;
+            const chr = self.peek();
+
+            if (chr == '"') {
+                if (!multiline) {
+                    self.pos += 1;
+                    break;
+                }
+                if (std.mem.startsWith(u8, self.text[self.pos..], "\"\"\"")) {
+                    // Up to two quotes may sit right before the closing three.
+                    var quotes: usize = 3;
+                    while (quotes < 5 and self.pos + quotes < self.text.len and self.text[self.pos + quotes] == '"') quotes += 1;
+                    try out.appendNTimes('"', quotes - 3);
+                    self.pos += quotes;
+                    break;
+                }
+            }
+
+            if (chr == '\n' and !multiline) return InternalError.ValueError;
+
+            if (chr != '\\') {
+                try out.append(chr);
+                self.pos += 1;
+                continue;
+            }
+
+            self.pos += 1;
+            if (self.at_end()) return InternalError.ValueError;
+            const escaped = self.peek();
+            self.pos += 1;
+
+            switch (escaped) {
+                'b' => try out.append(0x08),
+                't' => try out.append('\t'),
+                'n' => try out.append('\n'),
+                'f' => try out.append(0x0C),
+                'r' => try out.append('\r'),
+                '"' => try out.append('"'),
+                '\\' => try out.append('\\'),
+                'u', 'U' => {
+                    const len: usize = if (escaped == 'u') 4 else 8;
+                    if (self.pos + len > self.text.len) return InternalError.ValueError;
+                    const codepoint = std.fmt.parseInt(u21, self.text[self.pos .. self.pos + len], 16) catch return InternalError.ValueError;
+                    self.pos += len;
+
+                    var buf: [4]u8 = undefined;
+                    const encoded_len = std.unicode.utf8Encode(codepoint, &buf) catch return InternalError.ValueError;
+                    try out.appendSlice(buf[0..encoded_len]);
+                },
+                // A backslash ending a line in a multi-line string eats all
+                // the whitespace (newlines included) after it.
+                ' ', '\t', '\r', '\n' => {
+                    if (!multiline) return InternalError.ValueError;
+                    self.pos -= 1;
+                    self.skip_whitespace();
+                    if (self.at_end() or (self.peek() != '\n' and self.peek() != '\r')) return InternalError.ValueError;
+                    while (!self.at_end() and std.ascii.isWhitespace(self.peek())) self.pos += 1;
+                },
+                else => return InternalError.ValueError,
+            }
+        }
+
+        return out.toOwnedSlice();
+    }
+
+    fn parse_literal_string(self: *Self) ![]u8 {
+        const multiline = std.mem.startsWith(u8, self.text[self.pos..], "'''");
+        self.pos += if (multiline) @as(usize, 3) else 1;
+        if (multiline) self.skip_one_newline();
+
+        const start = self.pos;
+        if (!multiline) {
+            const end = std.mem.indexOfAnyPos(u8, self.text, start, "'\n") orelse return InternalError.ValueError;
+            if (self.text[end] != '\'') return InternalError.ValueError;
+            self.pos = end + 1;
+            return try self.rt.alloc.dupe(u8, self.text[start..end]);
+        }
+
+        var end = std.mem.indexOfPos(u8, self.text, start, "'''") orelse return InternalError.ValueError;
+        // As with basic strings, up to two quotes may precede the closing
+        // three.
+        var extra: usize = 0;
+        while (extra < 2 and end + 3 + extra < self.text.len and self.text[end + 3 + extra] == '\'') extra += 1;
+        end += extra;
+        self.pos = end + 3;
+        return try self.rt.alloc.dupe(u8, self.text[start..end]);
+    }
+
+    fn skip_one_newline(self: *Self) void {
+        if (std.mem.startsWith(u8, self.text[self.pos..], "\r\n")) {
+            self.pos += 2;
+        } else if (std.mem.startsWith(u8, self.text[self.pos..], "\n")) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_whitespace(self: *Self) void {
+        while (!self.at_end() and (self.peek() == ' ' or self.peek() == '\t')) self.pos += 1;
+    }
+
+    /// Whitespace, newlines, and comments: everything that can sit between
+    /// the elements of an array, or between top-level lines.
+    fn skip_blank_lines(self: *Self) void {
+        while (!self.at_end()) {
+            switch (self.peek()) {
+                ' ', '\t', '\r', '\n' => self.pos += 1,
+                '#' => self.skip_comment(),
+                else => return,
+            }
+        }
+    }
+
+    fn skip_comment(self: *Self) void {
+        while (!self.at_end() and self.peek() != '\n') self.pos += 1;
+    }
+
+    fn expect_line_end(self: *Self) !void {
+        self.skip_whitespace();
+        if (!self.at_end() and self.peek() == '#') self.skip_comment();
+        if (self.at_end()) return;
+        if (self.eat('\n')) return;
+        if (std.mem.startsWith(u8, self.text[self.pos..], "\r\n")) {
+            self.pos += 2;
+            return;
+        }
+
+        return InternalError.ValueError;
+    }
+
+    fn at_end(self: *Self) bool {
+        return self.pos >= self.text.len;
+    }
+
+    fn peek(self: *Self) u8 {
+        return self.text[self.pos];
+    }
+
+    fn eat(self: *Self, chr: u8) bool {
+        if (self.at_end() or self.peek() != chr) return false;
+        self.pos += 1;
+        return true;
+    }
+};
+
+fn free_key(alloc: Allocator, key: *Key) void {
+    for (key.items) |segment| alloc.free(segment);
+    key.deinit();
+}
+
+/// Whether `token` looks like one of TOML's dates or times (1979-05-27,
+/// 07:32:00, or some combination). We don't validate them any further than
+/// that, since we don't interpret them either.
+fn is_date_time(token: []const u8) bool {
+    if (token.len >= 10 and std.ascii.isDigit(token[0]) and token[4] == '-' and token[7] == '-') return true;
+    if (token.len >= 8 and std.ascii.isDigit(token[0]) and token[2] == ':' and token[5] == ':') return true;
+    return false;
+}
+
+fn parse_number(token: []const u8) !Object {
+    var buf: [128]u8 = undefined;
+    if (token.len > buf.len) return InternalError.ValueError;
+
+    // Underscores may only sit between two digits.
+    var len: usize = 0;
+    for (token) |chr, idx| {
+        if (chr == '_') {
+            if (idx == 0 or idx == token.len - 1) return InternalError.ValueError;
+            if (!std.ascii.isXDigit(token[idx - 1]) or !std.ascii.isXDigit(token[idx + 1])) return InternalError.ValueError;
+            continue;
+        }
+        buf[len] = chr;
+        len += 1;
+    }
+    const digits = buf[0..len];
+
+    const unsigned = if (digits[0] == '+' or digits[0] == '-') digits[1..] else digits;
+    const negative = digits[0] == '-';
+
+    if (std.mem.eql(u8, unsigned, "inf")) return Object{ .Float = if (negative) -std.math.inf(f64) else std.math.inf(f64) };
+    if (std.mem.eql(u8, unsigned, "nan")) return Object{ .Float = std.math.nan(f64) };
+
+    if (unsigned.len > 2 and unsigned[0] == '0' and std.mem.indexOfScalar(u8, "xob", unsigned[1]) != null) {
+        // Prefixed integers may not be signed.
+        if (unsigned.len != digits.len) return InternalError.ValueError;
+        const radix: u8 = switch (unsigned[1]) {
+            'x' => 16,
+            'o' => 8,
+            else => 2,
+        };
+        const value = std.fmt.parseInt(u64, unsigned[2..], radix) catch return InternalError.ValueError;
+        return Object{ .UnsignedInt = std.math.cast(usize, value) orelse return InternalError.ValueError };
+    }
+
+    if (unsigned.len == 0 or !std.ascii.isDigit(unsigned[0])) return InternalError.ValueError;
+    // No leading zeros, save for 0 itself (and 0.5 and such).
+    if (unsigned.len > 1 and unsigned[0] == '0' and std.ascii.isDigit(unsigned[1])) return InternalError.ValueError;
+
+    if (std.mem.indexOfAny(u8, unsigned, ".eE") != null) {
+        return Object{ .Float = std.fmt.parseFloat(f64, digits) catch return InternalError.ValueError };
+    }
+
+    const value = std.fmt.parseInt(i64, digits, 10) catch return InternalError.ValueError;
+    return if (value < 0)
+        Object{ .SignedInt = std.math.cast(isize, value) orelse return InternalError.ValueError }
+    else
+        Object{ .UnsignedInt = std.math.cast(usize, value) orelse return InternalError.ValueError };
+}
+
+test "parse" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    const doc =
+        \\# A comment.
+        \\title = "TOML \"Example\"" # Another.
+        \\lit = 'C:\Users'
+        \\ints = [ 1_000, -17, 0xff, ]
+        \\float = 6.25e-1
+        \\when = 1979-05-27 07:32:00Z
+        \\multi = """
+        \\one \
+        \\   two"""
+        \\
+        \\[owner]
+        \\"full name" = "Tom"
+        \\dotted.key = true
+        \\
+        \\[[fruits]]
+        \\name = "apple"
+        \\
+        \\[[fruits]]
+        \\name = "banana"
+        \\color = { hue = "yellow", rgb = [255, 255, 0] }
+    ;
+
+    var obj = try (try parse(&rt, doc)).ref();
+    defer rt.release_heaped_object_reference(&obj);
+    const root = &obj.Map.value.?;
+
+    try expectEqualStrings("TOML \"Example\"", root.get("title").?.String.value.?);
+    try expectEqualStrings("C:\\Users", root.get("lit").?.String.value.?);
+    try expectEqual(@as(usize, 1000), root.get("ints").?.Array.value.?.items[0].UnsignedInt);
+    try expectEqual(@as(isize, -17), root.get("ints").?.Array.value.?.items[1].SignedInt);
+    try expectEqual(@as(usize, 255), root.get("ints").?.Array.value.?.items[2].UnsignedInt);
+    try expectEqual(@as(f64, 0.625), root.get("float").?.Float);
+    try expectEqualStrings("1979-05-27 07:32:00Z", root.get("when").?.String.value.?);
+    try expectEqualStrings("one two", root.get("multi").?.String.value.?);
+
+    const owner = &root.get("owner").?.Map.value.?;
+    try expectEqualStrings("Tom", owner.get("full name").?.String.value.?);
+    try expect(owner.get("dotted").?.Map.value.?.get("key").?.Boolean);
+
+    const fruits = root.get("fruits").?.Array.value.?.items;
+    try expectEqual(@as(usize, 2), fruits.len);
+    try expectEqualStrings("banana", fruits[1].Map.value.?.get("name").?.String.value.?);
+    const color = &fruits[1].Map.value.?.get("color").?.Map.value.?;
+    try expectEqualStrings("yellow", color.get("hue").?.String.value.?);
+}
+
+test "parse: malformed" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    try expectError(InternalError.ValueError, parse(&rt, "a = 1\na = 2"));
+    try expectError(InternalError.ValueError, parse(&rt, "a = \"unterminated"));
+    try expectError(InternalError.ValueError, parse(&rt, "a = 1 b = 2"));
+    try expectError(InternalError.ValueError, parse(&rt, "a = 01"));
+    try expectError(InternalError.ValueError, parse(&rt, "a = 1__0"));
+    try expectError(InternalError.ValueError, parse(&rt, "[a"));
+    try expectError(InternalError.ValueError, parse(&rt, "= 1"));
+
+    const deep = "a = " ++ "[" ** (MAX_NESTING + 2) ++ "]" ** (MAX_NESTING + 2);
+    try expectError(InternalError.LimitExceeded, parse(&rt, deep));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,68 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const toml = @import("./toml.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+
+/// Every TOML word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@TOML-PARSE", TOML_PARSE },
+};
+
+/// Define every TOML word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @TOML-PARSE ( String <- Map )
+///
+/// The document the String holds, as TOML: tables become Maps and arrays
+/// become Arrays. See toml.zig for the finer points. Malformed TOML is a
+/// ValueError.
+pub fn TOML_PARSE(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+
+    const parsed = try toml.parse(runtime, peeked.String.value.?);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_object(parsed);
+}
+
+test "@TOML-PARSE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"answer=42\" @TOML-PARSE");
+    var parsed = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&parsed);
+    try expectEqual(@as(usize, 42), parsed.Map.value.?.get("answer").?.UnsignedInt);
+
+    try expectError(InternalError.ValueError, runtime.eval("\"answer=\" @TOML-PARSE"));
+    var text = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&text);
+
+    try expectError(InternalError.TypeError, runtime.eval("1 @TOML-PARSE"));
+    _ = try runtime.stack_pop();
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
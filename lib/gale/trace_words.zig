@@ -0,0 +1,84 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const io = @import("./io.zig");
+
+const Runtime = @import("./runtime.zig").Runtime;
+const Tracer = @import("./tracer.zig").Tracer;
+
+/// Every tracing word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@TRACE-OFF", TRACE_OFF },
+    .{ "@TRACE-ON", TRACE_ON },
+};
+
+/// Define every tracing word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @TRACE-ON ( <- )
+///
+/// Log every word run from here on out to stderr (see tracer.zig for the
+/// format), starting with the end of this one. Tracing twice over is no
+/// different from tracing once.
+pub fn TRACE_ON(runtime: *Runtime) anyerror!void {
+    if (runtime.tracer != null) return;
+
+    const tracer = try runtime.alloc.create(Tracer);
+    errdefer runtime.alloc.destroy(tracer);
+    tracer.* = Tracer.init(runtime.stderr);
+
+    try runtime.add_observer(tracer.observer());
+    runtime.tracer = tracer;
+}
+
+/// @TRACE-OFF ( <- )
+///
+/// Stop whatever @TRACE-ON started, after logging the start of this word.
+/// Tracers installed by the host are left alone.
+pub fn TRACE_OFF(runtime: *Runtime) anyerror!void {
+    const tracer = runtime.tracer orelse return;
+
+    runtime.remove_observer(tracer.observer());
+    runtime.alloc.destroy(tracer);
+    runtime.tracer = null;
+}
+
+test "TRACE_ON and TRACE_OFF" {
+    var log = std.ArrayList(u8).init(testAllocator);
+    defer log.deinit();
+
+    var runtime = try Runtime.init_with_options(testAllocator, .{ .stderr = io.buffer_output(&log) });
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("@TRACE-ON @TRACE-ON 1 2 @SWAP @TRACE-OFF @DROP @DROP");
+    try expectEqualStrings(
+        \\< @TRACE-ON <0>
+        \\> @TRACE-ON <0>
+        \\< @TRACE-ON <0>
+        \\> @SWAP <2> 1 2
+        \\< @SWAP <2> 2 1
+        \\> @TRACE-OFF <2> 2 1
+        \\
+    , log.items);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
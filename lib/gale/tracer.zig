@@ -0,0 +1,153 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const io = @import("./io.zig");
+
+const Observer = @import("./observer.zig").Observer;
+const ObserverEvent = @import("./observer.zig").Event;
+const OutputHandle = io.OutputHandle;
+const Runtime = @import("./runtime.zig").Runtime;
+const Stack = @import("./stack.zig").Stack;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+/// How many Objects from the top of the Stack each summary shows.
+pub const SUMMARY_DEPTH = 4;
+
+/// An Observer logging every word run to `output`, one line as each starts
+/// (`>`) and another as it ends (`<`), each followed by a summary of the
+/// top of the Stack at the time (see write_summary). Lines are indented
+/// two spaces per word the traced one is nested within, and failures get a
+/// `!` line of their own for each word they unwind.
+///
+/// Install it with Runtime.add_observer(tracer.observer()). As with any
+/// Observer, the Tracer must outlive its installation.
+pub const Tracer = struct {
+    const Self = @This();
+
+    output: OutputHandle,
+
+    pub fn init(output: OutputHandle) Self {
+        return .{ .output = output };
+    }
+
+    pub fn observer(self: *Self) Observer {
+        return .{ .context = self, .on_event = &on_event };
+    }
+
+    fn on_event(context: ?*anyopaque, rt: *Runtime, event: ObserverEvent) void {
+        const self = @ptrCast(*Self, @alignCast(@alignOf(Self), context.?));
+        // Failing to write a trace is no reason to fail whatever's traced.
+        self.trace(rt, event) catch {};
+    }
+
+    fn trace(self: *Self, rt: *Runtime, event: ObserverEvent) !void {
+        const writer = self.output.writer();
+
+        // Frames are pushed before WordStart and popped before everything
+        // else, so the word in question is always the innermost (or just
+        // gone) one.
+        switch (event) {
+            .WordStart => |word| {
+                try writer.writeByteNTimes(' ', 2 * (rt.frames.items.len - 1));
+                try writer.print("> {s} ", .{describe(rt, word)});
+            },
+            .WordEnd => |word| {
+                try writer.writeByteNTimes(' ', 2 * rt.frames.items.len);
+                try writer.print("< {s} ", .{describe(rt, word)});
+            },
+            .Error => |err| {
+                try writer.writeByteNTimes(' ', 2 * rt.frames.items.len);
+                try writer.print("! {s}\n", .{@errorName(err)});
+                return;
+            },
+            .Push, .Pop, .Defined, .VocabularyCreated, .Loaded => return,
+        }
+
+        try write_summary(writer, rt.stack);
+        try writer.writeByte('\n');
+    }
+};
+
+/// `word`'s name, or failing that (for anonymous words), what kind of word
+/// it is.
+pub fn describe(rt: *Runtime, word: *Types.HeapedWord) []const u8 {
+    if (rt.name_of_word(word)) |name| return name;
+
+    const inner = word.value orelse return "<empty>";
+    return switch (inner.impl) {
+        .CallSite => unreachable,
+        .Compound => "<compound>",
+        .HeapLit => "<literal>",
+        .HostClosure => "<host closure>",
+        .Primitive => "<primitive>",
+    };
+}
+
+/// The depth of `stack` between angle brackets, then up to SUMMARY_DEPTH
+/// Objects from its top, bottom to top, with `...` standing in for the rest.
+pub fn write_summary(writer: anytype, stack: *Stack) !void {
+    const depth = stack.depth();
+    try writer.print("<{d}>", .{depth});
+    if (depth > SUMMARY_DEPTH) try writer.writeAll(" ...");
+
+    var idx = std.math.min(depth, SUMMARY_DEPTH);
+    while (idx > 0) {
+        idx -= 1;
+        try writer.print(" {}", .{stack.nth_from_top(idx).?.*});
+    }
+}
+
+test "Tracer" {
+    var log = std.ArrayList(u8).init(testAllocator);
+    defer log.deinit();
+
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var tracer = Tracer.init(io.buffer_output(&log));
+    try rt.add_observer(tracer.observer());
+
+    const inner = try rt.alloc.alloc(*Types.HeapedWord, 2);
+    inner[0] = (try rt.find_word("@DUP")).?;
+    inner[1] = (try rt.find_word("@DROP")).?;
+    for (inner) |word| try word.increment();
+    try rt.define_word("dupdrop", try rt.word_from_compound_impl(inner, null));
+
+    try rt.eval("1 2 3 4 5 dupdrop");
+    try expectEqualStrings(
+        \\> dupdrop <5> ... 2 3 4 5
+        \\  > @DUP <5> ... 2 3 4 5
+        \\  < @DUP <6> ... 3 4 5 5
+        \\  > @DROP <6> ... 3 4 5 5
+        \\  < @DROP <5> ... 2 3 4 5
+        \\< dupdrop <5> ... 2 3 4 5
+        \\
+    , log.items);
+
+    log.clearRetainingCapacity();
+    try rt.eval("@DROP @DROP @DROP @DROP @DROP");
+    log.clearRetainingCapacity();
+    try expectError(StackManipulationError.Underflow, rt.eval("@DROP"));
+    try expectEqualStrings("> @DROP <0>\n! Underflow\n", log.items);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
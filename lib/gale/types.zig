@@ -13,8 +13,12 @@
 
 const std = @import("std");
 
+const Cell = @import("./cell.zig").Cell;
+const Channel = @import("./channel.zig").Channel;
+const Map = @import("./map.zig").Map;
 const Object = @import("./object.zig").Object;
 const Rc = @import("./rc.zig").Rc;
+const Set = @import("./set.zig").Set;
 const Stack = @import("./stack.zig").Stack;
 const Word = @import("./word.zig").Word;
 
@@ -25,7 +29,11 @@ test {
 pub const ObjectArray = std.ArrayList(Object);
 
 pub const HeapedArray = Rc(ObjectArray);
+pub const HeapedCell = Rc(Cell);
+pub const HeapedChannel = Rc(Channel);
+pub const HeapedMap = Rc(Map);
 pub const HeapedOpaque = Rc([]u8);
+pub const HeapedSet = Rc(Set);
 pub const HeapedString = Rc([]u8);
 pub const HeapedSymbol = Rc([]u8);
 pub const HeapedWord = Rc(Word);
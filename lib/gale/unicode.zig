@@ -0,0 +1,452 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+// What counts as a "character", and how characters compare regardless of
+// case, for the words in unicode_words.zig and for anything else (the parser,
+// diagnostics) that needs to agree with them.
+//
+// There's no Unicode Character Database in here: both grapheme segmentation
+// and case folding work from hand-kept tables of the ranges that matter most,
+// rather than the full property data. Graphemes follow the shape of UAX #29's
+// extended grapheme clusters (CR LF, combining marks, variation selectors,
+// emoji modifiers and ZWJ sequences, and regional indicator pairs), but scripts
+// whose clusters depend on finer properties (Hangul syllable sequences, Indic
+// conjuncts) may split where a full implementation wouldn't. Case folding
+// covers Latin, Greek, Cyrillic, Armenian, and fullwidth Latin, and leaves
+// everything else as-is.
+// TODO: generate proper tables from the UCD at build time.
+//
+// Invalid UTF-8 is tolerated throughout: each offending byte stands alone as
+// its own "codepoint" (and so grapheme), rather than being an error.
+
+const ZWJ = 0x200D;
+
+const Decoded = struct {
+    cp: u21,
+    len: usize,
+};
+
+fn decode_at(text: []const u8, pos: usize) Decoded {
+    const as_byte = Decoded{ .cp = text[pos], .len = 1 };
+    const len = std.unicode.utf8ByteSequenceLength(text[pos]) catch return as_byte;
+    if (pos + len > text.len) return as_byte;
+    const cp = std.unicode.utf8Decode(text[pos .. pos + len]) catch return as_byte;
+    return Decoded{ .cp = cp, .len = len };
+}
+
+const Range = struct { lo: u21, hi: u21 };
+
+fn in_ranges(cp: u21, ranges: []const Range) bool {
+    for (ranges) |range| {
+        if (cp >= range.lo and cp <= range.hi) return true;
+    }
+    return false;
+}
+
+/// Codepoints which attach to whatever precedes them: combining marks,
+/// variation selectors, emoji modifiers, tags, and the like.
+const EXTEND_RANGES = [_]Range{
+    .{ .lo = 0x0300, .hi = 0x036F },
+    .{ .lo = 0x0483, .hi = 0x0489 },
+    .{ .lo = 0x0591, .hi = 0x05BD },
+    .{ .lo = 0x05BF, .hi = 0x05C7 },
+    .{ .lo = 0x0610, .hi = 0x061A },
+    .{ .lo = 0x064B, .hi = 0x065F },
+    .{ .lo = 0x0670, .hi = 0x0670 },
+    .{ .lo = 0x06D6, .hi = 0x06DC },
+    .{ .lo = 0x06DF, .hi = 0x06E4 },
+    .{ .lo = 0x0900, .hi = 0x0903 },
+    .{ .lo = 0x093A, .hi = 0x094F },
+    .{ .lo = 0x0951, .hi = 0x0957 },
+    .{ .lo = 0x0E31, .hi = 0x0E31 },
+    .{ .lo = 0x0E34, .hi = 0x0E3A },
+    .{ .lo = 0x0E47, .hi = 0x0E4E },
+    .{ .lo = 0x1AB0, .hi = 0x1AFF },
+    .{ .lo = 0x1DC0, .hi = 0x1DFF },
+    .{ .lo = 0x200C, .hi = 0x200C },
+    .{ .lo = 0x20D0, .hi = 0x20FF },
+    .{ .lo = 0xFE00, .hi = 0xFE0F },
+    .{ .lo = 0xFE20, .hi = 0xFE2F },
+    .{ .lo = 0x1F3FB, .hi = 0x1F3FF },
+    .{ .lo = 0xE0020, .hi = 0xE007F },
+    .{ .lo = 0xE0100, .hi = 0xE01EF },
+};
+
+/// Roughly Extended_Pictographic: the things ZWJ sequences are built of.
+const PICTOGRAPHIC_RANGES = [_]Range{
+    .{ .lo = 0x00A9, .hi = 0x00A9 },
+    .{ .lo = 0x00AE, .hi = 0x00AE },
+    .{ .lo = 0x203C, .hi = 0x203C },
+    .{ .lo = 0x2049, .hi = 0x2049 },
+    .{ .lo = 0x2122, .hi = 0x2122 },
+    .{ .lo = 0x2139, .hi = 0x2139 },
+    .{ .lo = 0x2194, .hi = 0x21AA },
+    .{ .lo = 0x231A, .hi = 0x23FF },
+    .{ .lo = 0x24C2, .hi = 0x24C2 },
+    .{ .lo = 0x25AA, .hi = 0x25FE },
+    .{ .lo = 0x2600, .hi = 0x27BF },
+    .{ .lo = 0x2934, .hi = 0x2935 },
+    .{ .lo = 0x2B05, .hi = 0x2B55 },
+    .{ .lo = 0x3030, .hi = 0x3030 },
+    .{ .lo = 0x303D, .hi = 0x303D },
+    .{ .lo = 0x3297, .hi = 0x3299 },
+    .{ .lo = 0x1F000, .hi = 0x1F1E5 },
+    .{ .lo = 0x1F200, .hi = 0x1F3FA },
+    .{ .lo = 0x1F400, .hi = 0x1FAFF },
+};
+
+const WHITESPACE_RANGES = [_]Range{
+    .{ .lo = 0x0009, .hi = 0x000D },
+    .{ .lo = 0x0020, .hi = 0x0020 },
+    .{ .lo = 0x0085, .hi = 0x0085 },
+    .{ .lo = 0x00A0, .hi = 0x00A0 },
+    .{ .lo = 0x1680, .hi = 0x1680 },
+    .{ .lo = 0x2000, .hi = 0x200A },
+    .{ .lo = 0x2028, .hi = 0x2029 },
+    .{ .lo = 0x202F, .hi = 0x202F },
+    .{ .lo = 0x205F, .hi = 0x205F },
+    .{ .lo = 0x3000, .hi = 0x3000 },
+};
+
+/// Roughly East_Asian_Width Wide and Fullwidth, less the emoji, which
+/// display_width treats as wide (or not) by their presentation.
+const WIDE_RANGES = [_]Range{
+    .{ .lo = 0x1100, .hi = 0x115F },
+    .{ .lo = 0x2E80, .hi = 0x303E },
+    .{ .lo = 0x3041, .hi = 0x33FF },
+    .{ .lo = 0x3400, .hi = 0x4DBF },
+    .{ .lo = 0x4E00, .hi = 0x9FFF },
+    .{ .lo = 0xA000, .hi = 0xA4CF },
+    .{ .lo = 0xAC00, .hi = 0xD7A3 },
+    .{ .lo = 0xF900, .hi = 0xFAFF },
+    .{ .lo = 0xFE30, .hi = 0xFE4F },
+    .{ .lo = 0xFF00, .hi = 0xFF60 },
+    .{ .lo = 0xFFE0, .hi = 0xFFE6 },
+    .{ .lo = 0x20000, .hi = 0x2FFFD },
+    .{ .lo = 0x30000, .hi = 0x3FFFD },
+};
+
+fn is_control(cp: u21) bool {
+    return cp < 0x20 or (cp >= 0x7F and cp <= 0x9F) or cp == 0x2028 or cp == 0x2029;
+}
+
+fn is_extend(cp: u21) bool {
+    return in_ranges(cp, &EXTEND_RANGES);
+}
+
+fn is_pictographic(cp: u21) bool {
+    return in_ranges(cp, &PICTOGRAPHIC_RANGES);
+}
+
+fn is_regional_indicator(cp: u21) bool {
+    return cp >= 0x1F1E6 and cp <= 0x1F1FF;
+}
+
+/// Whether `cp` has the Unicode White_Space property.
+pub fn is_whitespace(cp: u21) bool {
+    return in_ranges(cp, &WHITESPACE_RANGES);
+}
+
+/// Walks the grapheme clusters of a string, front to back.
+pub const GraphemeIterator = struct {
+    const Self = @This();
+
+    text: []const u8,
+    pos: usize = 0,
+
+    pub fn init(text: []const u8) Self {
+        return Self{ .text = text };
+    }
+
+    pub fn next(self: *Self) ?[]const u8 {
+        if (self.pos >= self.text.len) return null;
+
+        const start = self.pos;
+        const first = decode_at(self.text, self.pos);
+        self.pos += first.len;
+
+        if (is_control(first.cp)) {
+            if (first.cp == '\r' and self.pos < self.text.len and self.text[self.pos] == '\n') self.pos += 1;
+            return self.text[start..self.pos];
+        }
+
+        const emoji = is_pictographic(first.cp);
+        var prev = first.cp;
+        var regional_count: usize = if (is_regional_indicator(first.cp)) 1 else 0;
+
+        while (self.pos < self.text.len) {
+            const cur = decode_at(self.text, self.pos);
+
+            const joins = if (is_control(cur.cp))
+                false
+            else if (is_extend(cur.cp) or cur.cp == ZWJ)
+                true
+            else if (prev == ZWJ and emoji and is_pictographic(cur.cp))
+                true
+            else if (is_regional_indicator(cur.cp) and regional_count % 2 == 1)
+                true
+            else
+                false;
+            if (!joins) break;
+
+            if (is_regional_indicator(cur.cp)) regional_count += 1;
+            prev = cur.cp;
+            self.pos += cur.len;
+        }
+
+        return self.text[start..self.pos];
+    }
+};
+
+/// How many grapheme clusters `text` holds.
+pub fn grapheme_count(text: []const u8) usize {
+    var iter = GraphemeIterator.init(text);
+    var count: usize = 0;
+    while (iter.next() != null) count += 1;
+    return count;
+}
+
+/// The byte offset at which grapheme `index` of `text` starts, or
+/// `text.len` for the index just past the last one. Null if `text` has
+/// fewer graphemes than that.
+pub fn grapheme_offset(text: []const u8, index: usize) ?usize {
+    var iter = GraphemeIterator.init(text);
+    var seen: usize = 0;
+    while (seen < index) : (seen += 1) {
+        _ = iter.next() orelse return null;
+    }
+    return iter.pos;
+}
+
+/// Where `find` found something, both as a byte offset and as a grapheme
+/// index.
+pub const Found = struct {
+    offset: usize,
+    index: usize,
+};
+
+/// The first occurrence of `needle` in `text` starting at or after byte
+/// offset `from`, counting only those which start and end between
+/// graphemes: "e" isn't in "e\u{301}", its accent and all being one grapheme.
+/// The empty needle is found at the first boundary it can be.
+pub fn find(text: []const u8, needle: []const u8, from: usize) ?Found {
+    var iter = GraphemeIterator.init(text);
+    var index: usize = 0;
+    while (true) : (index += 1) {
+        const at = iter.pos;
+        if (at >= from and std.mem.startsWith(u8, text[at..], needle) and ends_grapheme(iter, at + needle.len)) {
+            return Found{ .offset = at, .index = index };
+        }
+        _ = iter.next() orelse return null;
+    }
+}
+
+fn ends_grapheme(from: GraphemeIterator, offset: usize) bool {
+    var iter = from;
+    while (iter.pos < offset) _ = iter.next();
+    return iter.pos == offset;
+}
+
+/// How display_width counts columns, so that diagnostics can agree with
+/// whichever editor or terminal shows them.
+pub const ColumnPolicy = struct {
+    /// Tabs advance to the next multiple of this many columns.
+    tab_width: usize = 8,
+    /// Whether East Asian Wide and Fullwidth characters, and emoji, take two
+    /// columns, as most terminals (but not every editor) show them.
+    east_asian_width: bool = false,
+};
+
+/// How many columns `text` (a line, or the start of one) takes up when
+/// shown, under `policy`. Graphemes take one column each, tabs and wide
+/// graphemes aside.
+pub fn display_width(text: []const u8, policy: ColumnPolicy) usize {
+    var width: usize = 0;
+    var iter = GraphemeIterator.init(text);
+    while (iter.next()) |grapheme| {
+        if (grapheme[0] == '\t' and policy.tab_width > 0) {
+            width += policy.tab_width - width % policy.tab_width;
+        } else if (policy.east_asian_width and is_wide(decode_at(grapheme, 0).cp)) {
+            width += 2;
+        } else {
+            width += 1;
+        }
+    }
+    return width;
+}
+
+fn is_wide(cp: u21) bool {
+    // Regional indicators pair up into flags, which are two columns wide
+    // as a pair, as are emoji other than the text-presentation defaults.
+    return in_ranges(cp, &WIDE_RANGES) or is_regional_indicator(cp) or (cp >= 0x1F300 and is_pictographic(cp));
+}
+
+/// `text` with leading and trailing whitespace removed.
+pub fn trim(text: []const u8) []const u8 {
+    var start: usize = 0;
+    while (start < text.len) {
+        const decoded = decode_at(text, start);
+        if (!is_whitespace(decoded.cp)) break;
+        start += decoded.len;
+    }
+
+    // Walking backwards through UTF-8 means finding each codepoint's lead
+    // byte first.
+    var end = text.len;
+    while (end > start) {
+        var lead = end - 1;
+        while (lead > start and end - lead < 4 and text[lead] & 0xC0 == 0x80) lead -= 1;
+        const decoded = decode_at(text, lead);
+        if (lead + decoded.len != end or !is_whitespace(decoded.cp)) break;
+        end = lead;
+    }
+
+    return text[start..end];
+}
+
+/// Append `text`, case folded (so that strings differing only in case fold
+/// to the same thing), to `out`.
+pub fn append_folded(out: *std.ArrayList(u8), text: []const u8) !void {
+    var pos: usize = 0;
+    while (pos < text.len) {
+        const decoded = decode_at(text, pos);
+        pos += decoded.len;
+
+        // Folding invalid UTF-8 leaves it be, rather than "fixing" it.
+        if (decoded.len == 1 and decoded.cp >= 0x80) {
+            try out.append(@intCast(u8, decoded.cp));
+            continue;
+        }
+
+        const folded = fold(decoded.cp);
+        for (folded.cps[0..folded.len]) |cp| {
+            var buf: [4]u8 = undefined;
+            const len = std.unicode.utf8Encode(cp, &buf) catch unreachable;
+            try out.appendSlice(buf[0..len]);
+        }
+    }
+}
+
+const Folded = struct {
+    cps: [2]u21,
+    len: usize,
+
+    fn one(cp: u21) Folded {
+        return .{ .cps = .{ cp, 0 }, .len = 1 };
+    }
+
+    fn two(first: u21, second: u21) Folded {
+        return .{ .cps = .{ first, second }, .len = 2 };
+    }
+};
+
+/// The full case folding (per CaseFolding.txt's C and F mappings) of `cp`,
+/// for the scripts we know about.
+fn fold(cp: u21) Folded {
+    return switch (cp) {
+        'A'...'Z' => Folded.one(cp + 32),
+        0x00B5 => Folded.one(0x03BC),
+        0x00C0...0x00D6, 0x00D8...0x00DE => Folded.one(cp + 32),
+        0x00DF, 0x1E9E => Folded.two('s', 's'),
+        0x0130 => Folded.two('i', 0x0307),
+        0x0149 => Folded.two(0x02BC, 'n'),
+        0x0178 => Folded.one(0x00FF),
+        0x017F => Folded.one('s'),
+        // Latin Extended-A is (mostly) upper- and lowercase pairs, with the
+        // uppercase letter first.
+        0x0100...0x012F, 0x0132...0x0137, 0x014A...0x0177 => Folded.one(cp | 1),
+        0x0139...0x0148, 0x0179...0x017E => Folded.one(if (cp & 1 == 1) cp + 1 else cp),
+        0x0386 => Folded.one(0x03AC),
+        0x0388...0x038A => Folded.one(cp + 37),
+        0x038C => Folded.one(0x03CC),
+        0x038E...0x038F => Folded.one(cp + 63),
+        0x0391...0x03A1, 0x03A3...0x03AB => Folded.one(cp + 32),
+        0x03C2 => Folded.one(0x03C3),
+        0x0400...0x040F => Folded.one(cp + 80),
+        0x0410...0x042F => Folded.one(cp + 32),
+        0x0460...0x0481, 0x048A...0x04BF => Folded.one(cp | 1),
+        0x0531...0x0556 => Folded.one(cp + 48),
+        0xFF21...0xFF3A => Folded.one(cp + 32),
+        else => Folded.one(cp),
+    };
+}
+
+fn expect_graphemes(text: []const u8, expected: []const []const u8) !void {
+    var iter = GraphemeIterator.init(text);
+    for (expected) |want| try expectEqualStrings(want, iter.next().?);
+    try expect(iter.next() == null);
+}
+
+test "graphemes" {
+    try expect_graphemes("ab", &.{ "a", "b" });
+    try expect_graphemes("e\u{301}x", &.{ "e\u{301}", "x" });
+    try expect_graphemes("\r\n\n", &.{ "\r\n", "\n" });
+    // A family (ZWJ sequence), then a flag (regional indicator pair), then
+    // a waving hand with a skin tone modifier.
+    try expect_graphemes(
+        "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{1F1EC}\u{1F1E7}\u{1F1EB}\u{1F44B}\u{1F3FD}",
+        &.{ "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}", "\u{1F1EC}\u{1F1E7}", "\u{1F1EB}", "\u{1F44B}\u{1F3FD}" },
+    );
+    try expect_graphemes("a\xffb", &.{ "a", "\xff", "b" });
+
+    try expectEqual(@as(usize, 3), grapheme_count("🐸☕e\u{301}"));
+    try expectEqual(@as(?usize, 7), grapheme_offset("🐸☕e\u{301}", 2));
+    try expectEqual(@as(?usize, 10), grapheme_offset("🐸☕e\u{301}", 3));
+    try expectEqual(@as(?usize, null), grapheme_offset("🐸☕e\u{301}", 4));
+}
+
+test "find" {
+    try expectEqual(@as(?Found, Found{ .offset = 4, .index = 1 }), find("🐸☕🐸", "☕", 0));
+    try expectEqual(@as(?Found, Found{ .offset = 7, .index = 2 }), find("🐸☕🐸", "🐸", 1));
+    try expectEqual(@as(?Found, null), find("ae\u{301}", "e", 0));
+    try expectEqual(@as(?Found, Found{ .offset = 3, .index = 1 }), find("e\u{301}e", "e", 0));
+    try expectEqual(@as(?Found, Found{ .offset = 0, .index = 0 }), find("ab", "", 0));
+    try expectEqual(@as(?Found, Found{ .offset = 2, .index = 2 }), find("ab", "", 2));
+    try expectEqual(@as(?Found, null), find("ab", "abc", 0));
+}
+
+test "display_width" {
+    try expectEqual(@as(usize, 5), display_width("a\u{0301}bcde", .{}));
+    try expectEqual(@as(usize, 9), display_width("\tx", .{}));
+    try expectEqual(@as(usize, 8), display_width("ab\t", .{}));
+    try expectEqual(@as(usize, 5), display_width("ab\tx", .{ .tab_width = 4 }));
+    try expectEqual(@as(usize, 3), display_width("日本x", .{}));
+    try expectEqual(@as(usize, 5), display_width("日本x", .{ .east_asian_width = true }));
+    try expectEqual(@as(usize, 4), display_width("🐸🇯🇵", .{ .east_asian_width = true }));
+    try expectEqual(@as(usize, 1), display_width("©", .{ .east_asian_width = true }));
+}
+
+test "trim" {
+    try expectEqualStrings("a b", trim(" \t\u{3000}a b\u{A0}\n"));
+    try expectEqualStrings("", trim("\u{2003} "));
+    try expectEqualStrings("é", trim("é"));
+}
+
+test "append_folded" {
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+
+    try append_folded(&out, "Straße ΣΑΣ Привет Ĳ Ÿ 🐸");
+    try expectEqualStrings("strasse σασ привет ĳ ÿ 🐸", out.items);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
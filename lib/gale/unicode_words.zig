@@ -0,0 +1,399 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const unicode = @import("./unicode.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+// Strings as sequences of grapheme clusters (what a reader would call
+// characters), rather than bytes. See unicode.zig for what that means, and
+// where it falls short of the full Unicode rules.
+
+/// Every Unicode word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@CASE-FOLD", CASE_FOLD },
+    .{ "@GRAPHEME-COUNT", GRAPHEME_COUNT },
+    .{ "@GRAPHEME-SLICE", GRAPHEME_SLICE },
+    .{ "@STRING-CONCAT", STRING_CONCAT },
+    .{ "@STRING-CONTAINS?", STRING_CONTAINS_P },
+    .{ "@STRING-ENDS-WITH?", STRING_ENDS_WITH_P },
+    .{ "@STRING-INDEX-OF", STRING_INDEX_OF },
+    .{ "@STRING-JOIN", STRING_JOIN },
+    .{ "@STRING-REPEAT", STRING_REPEAT },
+    .{ "@STRING-REPLACE", STRING_REPLACE },
+    .{ "@STRING-SPLIT", STRING_SPLIT },
+    .{ "@STRING-STARTS-WITH?", STRING_STARTS_WITH_P },
+    .{ "@TRIM", TRIM },
+};
+
+/// Define every Unicode word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @GRAPHEME-COUNT ( String <- UnsignedInt )
+///
+/// How many grapheme clusters the String holds: "e" followed by a combining
+/// acute accent is one, as is a flag or a family emoji, for all that each is
+/// several codepoints.
+pub fn GRAPHEME_COUNT(runtime: *Runtime) anyerror!void {
+    const text = try peek_string(runtime);
+    const count = unicode.grapheme_count(text);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_uint(count);
+}
+
+/// @GRAPHEME-SLICE ( String UnsignedInt UnsignedInt <- String )
+///
+/// The graphemes of the String from the first index (inclusive) up to the
+/// second (exclusive), counting from zero. Indices past the end of the
+/// String, or a start after the end, are a ValueError.
+pub fn GRAPHEME_SLICE(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_trio();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    const mid = peek.mid orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt or mid.* != .UnsignedInt or far.* != .String) return InternalError.TypeError;
+
+    const text = far.String.value.?;
+    const first = mid.UnsignedInt;
+    const last = peek.near.UnsignedInt;
+    if (first > last) return InternalError.ValueError;
+
+    const from = unicode.grapheme_offset(text, first) orelse return InternalError.ValueError;
+    const to = from + (unicode.grapheme_offset(text[from..], last - first) orelse return InternalError.ValueError);
+    const str = try runtime.get_or_put_string(text[from..to]);
+
+    _ = try runtime.stack_pop();
+    _ = try runtime.stack_pop();
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+/// @CASE-FOLD ( String <- String )
+///
+/// The String, case folded: Strings which differ only in case fold to the
+/// same thing (so "Straße" and "STRASSE" both become "strasse"), making this
+/// the thing to compare with when case shouldn't matter. It isn't meant for
+/// display, for which lowercasing proper is what's wanted.
+pub fn CASE_FOLD(runtime: *Runtime) anyerror!void {
+    const text = try peek_string(runtime);
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+    try unicode.append_folded(&out, text);
+
+    try replace_top(runtime, 1, out.items);
+}
+
+/// @TRIM ( String <- String )
+///
+/// The String without any leading or trailing whitespace (per Unicode's
+/// White_Space property, so no-break and ideographic spaces count too).
+pub fn TRIM(runtime: *Runtime) anyerror!void {
+    const text = try peek_string(runtime);
+    try replace_top(runtime, 1, unicode.trim(text));
+}
+
+/// @STRING-SPLIT ( String String <- Array )
+///
+/// The pieces of the further String between occurrences of the nearer one,
+/// as an Array of Strings. Splitting by the empty String splits into
+/// graphemes.
+pub fn STRING_SPLIT(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .String) return InternalError.TypeError;
+
+    const text = far.String.value.?;
+    const separator = peek.near.String.value.?;
+
+    const pieces = try runtime.new_array(0);
+    errdefer runtime.release_array(pieces);
+
+    if (separator.len == 0) {
+        var iter = unicode.GraphemeIterator.init(text);
+        while (iter.next()) |grapheme| try append_string(runtime, pieces, grapheme);
+    } else {
+        var iter = std.mem.split(u8, text, separator);
+        while (iter.next()) |piece| try append_string(runtime, pieces, piece);
+    }
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_array(pieces);
+}
+
+/// @STRING-JOIN ( Array String <- String )
+///
+/// The Strings in the Array, end to end with the String between each (not
+/// to be confused with @JOIN, which is for Tasks). The Array holding
+/// anything other than Strings is a TypeError.
+pub fn STRING_JOIN(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .Array) return InternalError.TypeError;
+
+    const separator = peek.near.String.value.?;
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+    for (far.Array.value.?.items) |member, idx| {
+        if (member != .String) return InternalError.TypeError;
+        if (idx > 0) try out.appendSlice(separator);
+        try out.appendSlice(member.String.value.?);
+    }
+
+    try replace_top(runtime, 2, out.items);
+}
+
+/// @STRING-CONCAT ( String String <- String )
+///
+/// The further String followed by the nearer one.
+pub fn STRING_CONCAT(runtime: *Runtime) anyerror!void {
+    const args = try peek_string_pair(runtime);
+    const joined = try std.mem.concat(runtime.alloc, u8, &.{ args.far, args.near });
+    defer runtime.alloc.free(joined);
+
+    try replace_top(runtime, 2, joined);
+}
+
+// Searching within a String (the further one) for another (the nearer one),
+// as the next few words do, only finds what starts and ends between
+// graphemes (see unicode.find): "e" isn't in "e\u{301}", though its bytes
+// are.
+
+/// @STRING-CONTAINS? ( String String <- Boolean )
+pub fn STRING_CONTAINS_P(runtime: *Runtime) anyerror!void {
+    const args = try peek_string_pair(runtime);
+    const found = unicode.find(args.far, args.near, 0) != null;
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_bool(found);
+}
+
+/// @STRING-STARTS-WITH? ( String String <- Boolean )
+pub fn STRING_STARTS_WITH_P(runtime: *Runtime) anyerror!void {
+    const args = try peek_string_pair(runtime);
+    const found = if (unicode.find(args.far, args.near, 0)) |it| it.offset == 0 else false;
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_bool(found);
+}
+
+/// @STRING-ENDS-WITH? ( String String <- Boolean )
+pub fn STRING_ENDS_WITH_P(runtime: *Runtime) anyerror!void {
+    const args = try peek_string_pair(runtime);
+    const found = std.mem.endsWith(u8, args.far, args.near) and
+        unicode.find(args.far, args.near, args.far.len - args.near.len) != null;
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_bool(found);
+}
+
+/// @STRING-INDEX-OF ( String String <- UnsignedInt | Symbol )
+///
+/// Where the nearer String first turns up in the further one, counting in
+/// graphemes from zero (so, as @GRAPHEME-SLICE counts), or `:null` (see
+/// conversion.NULL_SYMBOL) if it doesn't.
+pub fn STRING_INDEX_OF(runtime: *Runtime) anyerror!void {
+    const args = try peek_string_pair(runtime);
+    const found = unicode.find(args.far, args.near, 0);
+
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_value(if (found) |it| @as(?usize, it.index) else null);
+}
+
+/// @STRING-REPLACE ( String String String <- String )
+///
+/// The furthest String with every occurrence of the middle one replaced by
+/// the nearest, from left to right. Replacing the empty String is a
+/// ValueError.
+pub fn STRING_REPLACE(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_trio();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    const mid = peek.mid orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or mid.* != .String or far.* != .String) return InternalError.TypeError;
+
+    const text = far.String.value.?;
+    const needle = mid.String.value.?;
+    const replacement = peek.near.String.value.?;
+    if (needle.len == 0) return InternalError.ValueError;
+
+    var out = std.ArrayList(u8).init(runtime.alloc);
+    defer out.deinit();
+
+    var rest: usize = 0;
+    while (unicode.find(text, needle, rest)) |found| {
+        try out.appendSlice(text[rest..found.offset]);
+        try out.appendSlice(replacement);
+        rest = found.offset + needle.len;
+    }
+    try out.appendSlice(text[rest..]);
+
+    try replace_top(runtime, 3, out.items);
+}
+
+/// @STRING-REPEAT ( String UnsignedInt <- String )
+///
+/// The String, that many times over, end to end.
+pub fn STRING_REPEAT(runtime: *Runtime) anyerror!void {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .UnsignedInt or far.* != .String) return InternalError.TypeError;
+
+    const text = far.String.value.?;
+    const times = peek.near.UnsignedInt;
+    const len = std.math.mul(usize, text.len, times) catch return error.OutOfMemory;
+
+    var out = try std.ArrayList(u8).initCapacity(runtime.alloc, len);
+    defer out.deinit();
+    var done: usize = 0;
+    while (done < times) : (done += 1) out.appendSliceAssumeCapacity(text);
+
+    try replace_top(runtime, 2, out.items);
+}
+
+fn peek_string_pair(runtime: *Runtime) !struct { far: []const u8, near: []const u8 } {
+    const peek = try runtime.stack_peek_pair();
+    const far = peek.far orelse return StackManipulationError.Underflow;
+    if (peek.near.* != .String or far.* != .String) return InternalError.TypeError;
+    return .{ .far = far.String.value.?, .near = peek.near.String.value.? };
+}
+
+fn peek_string(runtime: *Runtime) ![]const u8 {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .String) return InternalError.TypeError;
+    return peeked.String.value.?;
+}
+
+fn append_string(runtime: *Runtime, target: *Types.HeapedArray, bytes: []const u8) !void {
+    var obj = try (Object{ .String = (try runtime.get_or_put_string(bytes)).value_ptr }).ref();
+    errdefer obj.deinit(runtime.alloc);
+    try target.value.?.append(obj);
+}
+
+/// Drop `consumed` Objects, then push a String holding a copy of `bytes`
+/// (which may well borrow from what's being dropped).
+fn replace_top(runtime: *Runtime, consumed: usize, bytes: []const u8) !void {
+    const str = try runtime.get_or_put_string(bytes);
+
+    var dropped: usize = 0;
+    while (dropped < consumed) : (dropped += 1) try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_string(str.value_ptr);
+}
+
+fn expect_string_result(runtime: *Runtime, source: []const u8, expected: []const u8) !void {
+    try runtime.eval(source);
+    var result = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&result);
+    try expectEqualStrings(expected, result.String.value.?);
+}
+
+test "@GRAPHEME-COUNT and @GRAPHEME-SLICE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"🐸☕e\u{301}\" @GRAPHEME-COUNT");
+    try expectEqual(@as(usize, 3), (try runtime.stack_pop()).UnsignedInt);
+
+    try expect_string_result(&runtime, "\"🐸☕e\u{301}!\" 1 3 @GRAPHEME-SLICE", "☕e\u{301}");
+
+    try expectError(InternalError.ValueError, runtime.eval("\"ab\" 1 3 @GRAPHEME-SLICE"));
+    _ = try runtime.stack_pop();
+    _ = try runtime.stack_pop();
+    var text = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&text);
+}
+
+test "@CASE-FOLD and @TRIM" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_string_result(&runtime, "\"Straße\" @CASE-FOLD", "strasse");
+
+    try expect_string_result(&runtime, "\"\u{A0} hi\t\" @TRIM", "hi");
+}
+
+test "@STRING-SPLIT and @STRING-JOIN" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_string_result(&runtime, "\"a,b,,c\" \",\" @STRING-SPLIT \"-\" @STRING-JOIN", "a-b--c");
+    try expect_string_result(&runtime, "\"e\u{301}🐸\" \"\" @STRING-SPLIT \"|\" @STRING-JOIN", "e\u{301}|🐸");
+
+    try expectError(InternalError.TypeError, runtime.eval("\"a\" \",\" @STRING-SPLIT 1 @STRING-JOIN"));
+    _ = try runtime.stack_pop();
+    var pieces = try runtime.stack_pop();
+    runtime.release_heaped_object_reference(&pieces);
+}
+
+test "@STRING-CONCAT, @STRING-REPEAT, and @STRING-REPLACE" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try expect_string_result(&runtime, "\"🐸\" \"☕\" @STRING-CONCAT", "🐸☕");
+    try expect_string_result(&runtime, "\"ab\" 3 @STRING-REPEAT", "ababab");
+    try expect_string_result(&runtime, "\"ab\" 0 @STRING-REPEAT", "");
+    try expect_string_result(&runtime, "\"a-b--c\" \"-\" \"+\" @STRING-REPLACE", "a+b++c");
+    try expect_string_result(&runtime, "\"aaa\" \"aa\" \"b\" @STRING-REPLACE", "ba");
+    try expect_string_result(&runtime, "\"e\u{301}e\" \"e\" \"x\" @STRING-REPLACE", "e\u{301}x");
+
+    try expectError(InternalError.ValueError, runtime.eval("\"ab\" \"\" \"x\" @STRING-REPLACE"));
+    try runtime.eval("@DROP @DROP @DROP");
+    try expectError(InternalError.TypeError, runtime.eval("\"ab\" 1 @STRING-CONCAT"));
+    try runtime.eval("@DROP @DROP");
+}
+
+test "searching Strings" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"🐸☕🐸\" \"🐸\" @STRING-CONTAINS?");
+    try expect((try runtime.stack_pop()).Boolean);
+    try runtime.eval("\"ae\u{301}\" \"e\" @STRING-CONTAINS?");
+    try expect(!(try runtime.stack_pop()).Boolean);
+    try runtime.eval("\"hello\" \"he\" @STRING-STARTS-WITH?");
+    try expect((try runtime.stack_pop()).Boolean);
+    try runtime.eval("\"e\u{301}\" \"e\" @STRING-STARTS-WITH?");
+    try expect(!(try runtime.stack_pop()).Boolean);
+    try runtime.eval("\"hello\" \"llo\" @STRING-ENDS-WITH?");
+    try expect((try runtime.stack_pop()).Boolean);
+    try runtime.eval("\"lo\" \"hello\" @STRING-ENDS-WITH?");
+    try expect(!(try runtime.stack_pop()).Boolean);
+
+    try runtime.eval("\"🐸☕🐸\" \"☕\" @STRING-INDEX-OF");
+    try expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+    try runtime.eval("\"🐸☕🐸\" \"x\" @STRING-INDEX-OF");
+    try expectEqual(@as(?usize, null), try runtime.stack_pop_value(?usize));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,204 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+const expectEqualStrings = std.testing.expectEqualStrings;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Object = @import("./object.zig").Object;
+const Runtime = @import("./runtime.zig").Runtime;
+const StackManipulationError = @import("./stack.zig").StackManipulationError;
+const Types = @import("./types.zig");
+
+// Variants: the values of sum shapes, at least until Shapes can declare sums
+// of their own. A variant is an Array whose first member is a Symbol, its
+// tag, naming which of its shape's variants it is, and whose remaining
+// members are its payload:
+//
+//     5 :some 1 @VARIANT   // [ :some 5 ]
+//     :none 0 @VARIANT     // [ :none ]
+//
+// @MATCH takes one apart again, running whichever word goes with its tag.
+
+/// The tag of the @MATCH arm run when no other arm's tag matches.
+pub const ELSE_TAG = "else";
+
+/// Every variant word, alongside the name it's defined under by `install`.
+pub const ALL = .{
+    .{ "@MATCH", MATCH },
+    .{ "@VARIANT", VARIANT },
+};
+
+/// Define every variant word into `runtime`'s dictionary.
+pub fn install(runtime: *Runtime) !void {
+    try runtime.install_primitives(ALL);
+}
+
+/// @VARIANT ( @n ... @1 Symbol UnsignedInt <- Array )
+///
+/// A variant tagged with the Symbol, carrying the UnsignedInt many values
+/// beneath it (@n first) as its payload.
+pub fn VARIANT(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+    const count = peeked.UnsignedInt;
+
+    const depth = runtime.stack.depth();
+    if (depth < 2 or depth - 2 < count) return StackManipulationError.Underflow;
+    const tag = runtime.stack.nth_from_top(1).?;
+    if (tag.* != .Symbol) return InternalError.TypeError;
+
+    const variant = try runtime.new_array(count + 1);
+    errdefer runtime.release_array(variant);
+    const members = &variant.value.?;
+    members.appendAssumeCapacity(try tag.ref());
+    var idx: usize = 0;
+    while (idx < count) : (idx += 1) {
+        members.appendAssumeCapacity(try runtime.stack.nth_from_top(count + 1 - idx).?.ref());
+    }
+
+    idx = 0;
+    while (idx < count + 2) : (idx += 1) try runtime.stack_wrangle(.DropTopObject);
+    try runtime.stack_push_array(variant);
+}
+
+/// @MATCH ( Array Symbol Word ... Symbol Word UnsignedInt -> * )
+///
+/// Run the Word of the first of the UnsignedInt many arms (each a Symbol and
+/// a Word, in the order written) whose Symbol is the variant's tag, with the
+/// variant's payload pushed in its place:
+///
+///     opt :some &@DUP :none 0 @LIT 2 @MATCH
+///
+/// An arm tagged `:else` (see ELSE_TAG), which must come last, matches any
+/// variant, and is handed the variant whole. Matching nothing, with no such
+/// arm to fall back on, is a ValueError, as is tagging two arms alike: what
+/// can't yet be checked by Shapes is checked here, every time.
+pub fn MATCH(runtime: *Runtime) anyerror!void {
+    const peeked = try runtime.stack_peek();
+    if (peeked.* != .UnsignedInt) return InternalError.TypeError;
+    const arms = peeked.UnsignedInt;
+
+    const depth = runtime.stack.depth();
+    if (depth < 2 or (depth - 2) / 2 < arms) return StackManipulationError.Underflow;
+    const variant = runtime.stack.nth_from_top(2 * arms + 1).?;
+    const tag = try tag_of(variant.*);
+
+    var chosen: ?*const Object = null;
+    var destructure = false;
+    var idx: usize = 0;
+    while (idx < arms) : (idx += 1) {
+        const arm_tag = runtime.stack.nth_from_top(2 * (arms - idx)).?;
+        const arm_word = runtime.stack.nth_from_top(2 * (arms - idx) - 1).?;
+        if (arm_tag.* != .Symbol or arm_word.* != .Word) return InternalError.TypeError;
+
+        // Symbols are interned, so alike tags are one and the same.
+        var earlier: usize = 0;
+        while (earlier < idx) : (earlier += 1) {
+            if (runtime.stack.nth_from_top(2 * (arms - earlier)).?.Symbol == arm_tag.Symbol) return InternalError.ValueError;
+        }
+
+        const is_else = std.mem.eql(u8, ELSE_TAG, arm_tag.Symbol.value.?);
+        if (is_else and idx + 1 != arms) return InternalError.ValueError;
+
+        if (chosen == null and (arm_tag.Symbol == tag or is_else)) {
+            chosen = arm_word;
+            destructure = arm_tag.Symbol == tag;
+        }
+    }
+
+    const found = chosen orelse return InternalError.ValueError;
+
+    // Held on to until they're pushed (or run), lest dropping the arms and
+    // the variant take them with them.
+    var word = try found.ref();
+    defer runtime.release_heaped_object_reference(&word);
+    var subject = try variant.ref();
+    defer runtime.release_heaped_object_reference(&subject);
+
+    idx = 0;
+    while (idx < 2 * arms + 2) : (idx += 1) try runtime.stack_wrangle(.DropTopObject);
+
+    if (destructure) {
+        for (subject.Array.value.?.items[1..]) |member| try runtime.stack_push_object(member);
+    } else {
+        try runtime.stack_push_object(subject);
+    }
+
+    try runtime.run_word(word.Word);
+}
+
+/// The tag of `variant`, if it's a variant at all (TypeError otherwise).
+fn tag_of(variant: Object) !*Types.HeapedSymbol {
+    if (variant != .Array) return InternalError.TypeError;
+    const members = variant.Array.value.?.items;
+    if (members.len == 0 or members[0] != .Symbol) return InternalError.TypeError;
+    return members[0].Symbol;
+}
+
+test "@VARIANT" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("\"five\" 5 :some 2 @VARIANT");
+    var variant = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&variant);
+    const members = variant.Array.value.?.items;
+    try expectEqual(@as(usize, 3), members.len);
+    try expectEqualStrings("some", members[0].Symbol.value.?);
+    try expectEqualStrings("five", members[1].String.value.?);
+    try expectEqual(@as(usize, 5), members[2].UnsignedInt);
+
+    try expectError(StackManipulationError.Underflow, runtime.eval(":some 1 @VARIANT"));
+    try expectError(InternalError.TypeError, runtime.eval("@DROP 2 1 @VARIANT"));
+    try runtime.eval("@DROP @DROP @DROP");
+}
+
+test "@MATCH" {
+    var runtime = try Runtime.init(testAllocator);
+    defer runtime.deinit_guard_for_empty_stack();
+
+    try runtime.eval("5 :some 1 @VARIANT :none 0 @LIT :some &@DUP 2 @MATCH");
+    try expectEqual(@as(usize, 2), runtime.stack.depth());
+    try expectEqual(@as(usize, 5), try runtime.stack_pop_value(usize));
+    try expectEqual(@as(usize, 5), try runtime.stack_pop_value(usize));
+
+    try runtime.eval(":none 0 @VARIANT :some &@DUP :none 7 @LIT 2 @MATCH");
+    try expectEqual(@as(usize, 1), runtime.stack.depth());
+    try expectEqual(@as(usize, 7), try runtime.stack_pop_value(usize));
+
+    // The else arm gets the variant itself, not its payload.
+    try runtime.eval("3 :other 1 @VARIANT :some &@DUP :else &@DUP 2 @MATCH");
+    try expectEqual(@as(usize, 2), runtime.stack.depth());
+    var whole = try runtime.stack_pop();
+    defer runtime.release_heaped_object_reference(&whole);
+    try expectEqualStrings("other", whole.Array.value.?.items[0].Symbol.value.?);
+    try runtime.eval("@DROP");
+
+    try expectError(InternalError.ValueError, runtime.eval(":none 0 @VARIANT :some &@DUP 1 @MATCH"));
+    try runtime.eval("@DROP @DROP @DROP @DROP");
+    try expectError(InternalError.ValueError, runtime.eval(":none 0 @VARIANT :else &@DUP :none &@DUP 2 @MATCH"));
+    try runtime.eval("@DROP @DROP @DROP @DROP @DROP @DROP");
+    try expectError(InternalError.ValueError, runtime.eval(":none 0 @VARIANT :none &@DUP :none &@DUP 2 @MATCH"));
+    try runtime.eval("@DROP @DROP @DROP @DROP @DROP @DROP");
+    try expectError(InternalError.TypeError, runtime.eval("4 :some &@DUP 1 @MATCH"));
+    try runtime.eval("@DROP @DROP @DROP @DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
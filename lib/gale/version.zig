@@ -0,0 +1,292 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expect = std.testing.expect;
+const expectEqual = std.testing.expectEqual;
+const expectError = std.testing.expectError;
+
+const InternalError = @import("./internal_error.zig").InternalError;
+
+// Vocabulary versions (see Runtime.declare_version) and the ranges imports
+// may demand of them (see Runtime.require_version). Versions are plain
+// MAJOR.MINOR.PATCH, with none of semver's pre-release or build suffixes.
+// Ranges are any number of space-separated comparators, all of which must
+// hold:
+//
+//     1.2.3    exactly 1.2.3
+//     1.2      any 1.2.x, as `~1.2.0`
+//     1        any 1.x.y, as `^1.0.0`
+//     ^1.2.3   1.2.3 up to (but not including) 2.0.0, or for 0.x versions,
+//              up to the next minor version: ^0.2.3 is below 0.3.0
+//     ~1.2.3   1.2.3 up to 1.3.0
+//     >=1.2.3, >1.2.3, <=1.2.3, <1.2.3, =1.2.3
+//     *        anything at all
+//
+// Comparators which leave out parts (`>=1.2`, say) take them as 0.
+//
+// Since every range is a single interval, two ranges which overlap at all
+// can always be met by one version, and so can any number of ranges which
+// overlap pairwise.
+
+pub const Version = std.SemanticVersion;
+
+/// Parse `text` as a (complete) MAJOR.MINOR.PATCH version.
+pub fn parse_version(text: []const u8) !Version {
+    const partial = try parse_partial(text);
+    if (partial.parts < 3) return InternalError.ValueError;
+    return partial.version;
+}
+
+const Partial = struct {
+    version: Version,
+    /// How many of MAJOR, MINOR, and PATCH were given.
+    parts: u2,
+};
+
+fn parse_partial(text: []const u8) !Partial {
+    var version = Version{ .major = 0, .minor = 0, .patch = 0 };
+    var parts: u2 = 0;
+
+    var numbers = std.mem.split(u8, text, ".");
+    while (numbers.next()) |number| : (parts += 1) {
+        if (parts == 3 or number.len == 0) return InternalError.ValueError;
+        // No signs, and no leading zeroes, so each version has one spelling.
+        if (number[0] < '0' or number[0] > '9') return InternalError.ValueError;
+        if (number.len > 1 and number[0] == '0') return InternalError.ValueError;
+
+        const value = std.fmt.parseUnsigned(usize, number, 10) catch return InternalError.ValueError;
+        switch (parts) {
+            0 => version.major = value,
+            1 => version.minor = value,
+            else => version.patch = value,
+        }
+    }
+
+    if (parts == 0) return InternalError.ValueError;
+    return Partial{ .version = version, .parts = parts };
+}
+
+pub const Bound = struct {
+    version: Version,
+    inclusive: bool,
+};
+
+/// The versions between `min` and `max`, either of which may be missing, for
+/// "no bound at all".
+pub const Range = struct {
+    const Self = @This();
+
+    min: ?Bound = null,
+    max: ?Bound = null,
+
+    /// Anything at all.
+    pub const ANY = Self{};
+
+    pub fn contains(self: Self, version: Version) bool {
+        if (self.min) |min| switch (version.order(min.version)) {
+            .lt => return false,
+            .eq => if (!min.inclusive) return false,
+            .gt => {},
+        };
+        if (self.max) |max| switch (version.order(max.version)) {
+            .gt => return false,
+            .eq => if (!max.inclusive) return false,
+            .lt => {},
+        };
+        return true;
+    }
+
+    /// The versions in both ranges, or null if there aren't any.
+    pub fn intersect(self: Self, other: Self) ?Self {
+        const both = Self{
+            .min = tighter(self.min, other.min, .gt),
+            .max = tighter(self.max, other.max, .lt),
+        };
+
+        const min = both.min orelse return both;
+        const max = both.max orelse return both;
+        return switch (min.version.order(max.version)) {
+            .lt => both,
+            .eq => if (min.inclusive and max.inclusive) both else null,
+            .gt => null,
+        };
+    }
+
+    /// Whichever of `a` and `b` admits less, where `outward` is the order in
+    /// which a bound's version admits less (.gt for minimums, .lt for
+    /// maximums).
+    fn tighter(a: ?Bound, b: ?Bound, outward: std.math.Order) ?Bound {
+        const left = a orelse return b;
+        const right = b orelse return a;
+        const order = left.version.order(right.version);
+        if (order == outward) return left;
+        if (order == .eq) return if (left.inclusive) right else left;
+        return right;
+    }
+};
+
+/// Parse `text` as a range (see the top of this file).
+pub fn parse_range(text: []const u8) !Range {
+    var range = Range.ANY;
+    var comparators = std.mem.tokenize(u8, text, " ");
+    var any_comparators = false;
+
+    while (comparators.next()) |comparator| {
+        any_comparators = true;
+        range = range.intersect(try parse_comparator(comparator)) orelse return InternalError.ValueError;
+    }
+
+    if (!any_comparators) return InternalError.ValueError;
+    return range;
+}
+
+fn parse_comparator(text: []const u8) !Range {
+    if (std.mem.eql(u8, text, "*")) return Range.ANY;
+
+    inline for (.{
+        .{ ">=", true, true },
+        .{ "<=", false, true },
+        .{ ">", true, false },
+        .{ "<", false, false },
+    }) |op| {
+        if (std.mem.startsWith(u8, text, op[0])) {
+            const bound = Bound{
+                .version = (try parse_partial(text[op[0].len..])).version,
+                .inclusive = op[2],
+            };
+            return if (op[1]) Range{ .min = bound } else Range{ .max = bound };
+        }
+    }
+
+    if (text[0] == '=') return exactly((try parse_partial(text[1..])).version);
+    if (text[0] == '^') return caret((try parse_partial(text[1..])).version);
+    if (text[0] == '~') return tilde((try parse_partial(text[1..])).version);
+
+    const partial = try parse_partial(text);
+    return switch (partial.parts) {
+        3 => exactly(partial.version),
+        2 => tilde(partial.version),
+        else => caret(partial.version),
+    };
+}
+
+fn exactly(version: Version) Range {
+    const bound = Bound{ .version = version, .inclusive = true };
+    return Range{ .min = bound, .max = bound };
+}
+
+fn caret(version: Version) Range {
+    const below = if (version.major > 0)
+        Version{ .major = version.major + 1, .minor = 0, .patch = 0 }
+    else if (version.minor > 0)
+        Version{ .major = 0, .minor = version.minor + 1, .patch = 0 }
+    else
+        Version{ .major = 0, .minor = 0, .patch = version.patch + 1 };
+    return up_to(version, below);
+}
+
+fn tilde(version: Version) Range {
+    return up_to(version, Version{ .major = version.major, .minor = version.minor + 1, .patch = 0 });
+}
+
+fn up_to(version: Version, below: Version) Range {
+    return Range{
+        .min = Bound{ .version = version, .inclusive = true },
+        .max = Bound{ .version = below, .inclusive = false },
+    };
+}
+
+/// A Runtime's bookkeeping for one vocabulary, all of it owned by the
+/// Runtime. See Runtime.declare_version and Runtime.require_version.
+pub const Versioning = struct {
+    const Self = @This();
+
+    declared: ?Version = null,
+    demands: std.ArrayListUnmanaged(Demand) = .{},
+
+    pub fn deinit(self: *Self, alloc: Allocator) void {
+        for (self.demands.items) |demand| {
+            alloc.free(demand.text);
+            alloc.free(demand.by);
+        }
+        self.demands.deinit(alloc);
+    }
+};
+
+pub const Demand = struct {
+    range: Range,
+    /// As the range was written, for error messages.
+    text: []u8,
+    /// The vocabulary (or TOP_LEVEL) which demanded it.
+    by: []u8,
+};
+
+/// Who's responsible for demands made outside of any module.
+pub const TOP_LEVEL = "the top level";
+
+fn v(text: []const u8) Version {
+    return parse_version(text) catch unreachable;
+}
+
+test "parse_version" {
+    try expectEqual(Version{ .major = 1, .minor = 12, .patch = 0 }, try parse_version("1.12.0"));
+    try expectError(InternalError.ValueError, parse_version("1.2"));
+    try expectError(InternalError.ValueError, parse_version("1.2.3.4"));
+    try expectError(InternalError.ValueError, parse_version("1.02.3"));
+    try expectError(InternalError.ValueError, parse_version("1.2.3-beta"));
+    try expectError(InternalError.ValueError, parse_version(""));
+}
+
+test "parse_range" {
+    const caret_range = try parse_range("^1.2.3");
+    try expect(caret_range.contains(v("1.2.3")));
+    try expect(caret_range.contains(v("1.9.0")));
+    try expect(!caret_range.contains(v("2.0.0")));
+    try expect(!caret_range.contains(v("1.2.2")));
+
+    try expect(!(try parse_range("^0.2.3")).contains(v("0.3.0")));
+    try expect(!(try parse_range("~1.2.3")).contains(v("1.3.0")));
+    try expect((try parse_range("1.2")).contains(v("1.2.9")));
+    try expect(!(try parse_range("1")).contains(v("2.0.0")));
+    try expect((try parse_range("1.2.3")).contains(v("1.2.3")));
+    try expect(!(try parse_range("1.2.3")).contains(v("1.2.4")));
+    try expect((try parse_range("*")).contains(v("0.0.1")));
+
+    const both = try parse_range(">=1.2 <1.5");
+    try expect(both.contains(v("1.4.9")));
+    try expect(!both.contains(v("1.5.0")));
+    try expect(!(try parse_range(">1.2.0")).contains(v("1.2.0")));
+
+    try expectError(InternalError.ValueError, parse_range(""));
+    try expectError(InternalError.ValueError, parse_range(">=2 <1"));
+    try expectError(InternalError.ValueError, parse_range("^x"));
+}
+
+test "Range.intersect" {
+    const one = try parse_range("^1.2");
+    try expect(one.intersect(try parse_range("^2")) == null);
+    try expect(one.intersect(try parse_range("<1.2.0")) == null);
+    try expect(one.intersect(try parse_range("<=1.2.0")) != null);
+
+    const overlap = one.intersect(try parse_range(">=1.4")).?;
+    try expect(overlap.contains(v("1.4.0")));
+    try expect(!overlap.contains(v("1.3.0")));
+    try expect(!overlap.contains(v("2.0.0")));
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
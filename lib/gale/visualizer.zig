@@ -0,0 +1,216 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const Allocator = std.mem.Allocator;
+const testAllocator: Allocator = std.testing.allocator;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const helpers = @import("./helpers.zig");
+const io = @import("./io.zig");
+const tracer = @import("./tracer.zig");
+
+const Observer = @import("./observer.zig").Observer;
+const ObserverEvent = @import("./observer.zig").Event;
+const OutputHandle = io.OutputHandle;
+const Runtime = @import("./runtime.zig").Runtime;
+
+// A full-screen view of a Runtime as it runs: the Stack, the words being run
+// (the return stack, as Forth would have it), the words most recently run,
+// and what's most recently been defined, all redrawn as each word starts and
+// ends. Watching `2 3 + 4 *` shuffle the Stack about one word at a time
+// teaches more than any amount of explaining what it does.
+//
+// Drawing is done with plain ANSI escapes, which any terminal of the last few
+// decades understands, so there's nothing to link against.
+
+/// How many of the most recently run (and defined) words are shown.
+pub const RECENT_LEN = 8;
+/// Names longer than this are cut short among the recent words.
+pub const MAX_NAME_LEN = 48;
+
+/// Switch to (and back from) the alternate screen, as full-screen programs
+/// do, so that the scrollback is left as it was.
+const ENTER = "\x1b[?1049h";
+const LEAVE = "\x1b[?1049l";
+/// Move to the top left, then clear everything.
+const CLEAR = "\x1b[H\x1b[2J";
+
+/// The last RECENT_LEN names pushed, oldest first to go.
+const NameRing = struct {
+    const Self = @This();
+    const Name = std.BoundedArray(u8, MAX_NAME_LEN);
+
+    names: [RECENT_LEN]Name = undefined,
+    len: usize = 0,
+    /// Where the next name goes.
+    next: usize = 0,
+
+    fn push(self: *Self, name: []const u8) void {
+        self.names[self.next] = Name.fromSlice(name[0..std.math.min(name.len, MAX_NAME_LEN)]) catch unreachable;
+        self.next = (self.next + 1) % RECENT_LEN;
+        self.len = std.math.min(self.len + 1, RECENT_LEN);
+    }
+
+    /// The `n`th most recent name, from 0.
+    fn nth_latest(self: *const Self, n: usize) []const u8 {
+        return self.names[(self.next + RECENT_LEN - 1 - n) % RECENT_LEN].constSlice();
+    }
+};
+
+/// An Observer drawing a Runtime to `output` (which had better be a
+/// terminal) as it runs. Install it with Runtime.add_observer, and (as with
+/// any Observer) keep it around until it's removed. Between `enter` and
+/// `leave`, it has the whole screen to itself.
+pub const Visualizer = struct {
+    const Self = @This();
+
+    output: OutputHandle,
+    /// How many rows each of the Stack and the words being run get: deeper
+    /// ones are summed up in a row of their own.
+    rows: usize = 10,
+    /// How long to dwell on each word's end, so that it can be followed by
+    /// eye. Zero draws as fast as the words run.
+    step_delay_ms: u64 = 0,
+    recent_words: NameRing = .{},
+    recent_definitions: NameRing = .{},
+
+    pub fn init(output: OutputHandle) Self {
+        return .{ .output = output };
+    }
+
+    pub fn observer(self: *Self) Observer {
+        return .{ .context = self, .on_event = &on_event };
+    }
+
+    /// Take over the screen, until `leave`.
+    pub fn enter(self: *Self) !void {
+        try self.output.writer().writeAll(ENTER);
+    }
+
+    pub fn leave(self: *Self) !void {
+        try self.output.writer().writeAll(LEAVE);
+    }
+
+    fn on_event(context: ?*anyopaque, rt: *Runtime, event: ObserverEvent) void {
+        const self = @ptrCast(*Self, @alignCast(@alignOf(Self), context.?));
+
+        switch (event) {
+            .WordStart => {},
+            .WordEnd => |word| self.recent_words.push(tracer.describe(rt, word)),
+            .Defined => |definition| self.recent_definitions.push(definition.name),
+            .Push, .Pop, .Error, .VocabularyCreated, .Loaded => return,
+        }
+
+        // Failing to draw is no reason to fail whatever's drawn.
+        self.draw(rt) catch return;
+
+        if (comptime helpers.HOSTED) {
+            if (event == .WordEnd and self.step_delay_ms > 0) std.time.sleep(self.step_delay_ms * std.time.ns_per_ms);
+        }
+    }
+
+    /// Redraw the whole screen as `rt` stands now. Not everything worth
+    /// seeing is an event worth redrawing on (pushing a literal, say), so
+    /// hosts should also draw after evaluating each line of input.
+    pub fn draw(self: *Self, rt: *Runtime) !void {
+        var buffered = std.io.bufferedWriter(self.output.writer());
+        try self.render(rt, buffered.writer());
+        try buffered.flush();
+    }
+
+    pub fn render(self: *const Self, rt: *Runtime, writer: anytype) !void {
+        try writer.writeAll(CLEAR);
+
+        const depth = rt.stack.depth();
+        try writer.print("Stack <{d}>, top first:\n", .{depth});
+        var idx: usize = 0;
+        while (idx < std.math.min(depth, self.rows)) : (idx += 1) {
+            try writer.print("  {}\n", .{rt.stack.nth_from_top(idx).?.*});
+        }
+        if (depth > self.rows) try writer.print("  ... and {d} more\n", .{depth - self.rows});
+
+        // CallSites are just on their way to the word they call, which has
+        // (or is about to have) a frame of its own.
+        var running: usize = 0;
+        for (rt.frames.items) |frame| {
+            if (frame.word.value.?.impl != .CallSite) running += 1;
+        }
+
+        try writer.print("\nRunning <{d}>, innermost first:\n", .{running});
+        var shown: usize = 0;
+        idx = rt.frames.items.len;
+        while (idx > 0 and shown < self.rows) {
+            idx -= 1;
+            const word = rt.frames.items[idx].word;
+            if (word.value.?.impl == .CallSite) continue;
+
+            try writer.print("  {s}\n", .{tracer.describe(rt, word)});
+            shown += 1;
+        }
+        if (running > shown) try writer.print("  ... and {d} more\n", .{running - shown});
+
+        try writer.writeAll("\nRecently run, latest first:\n");
+        try write_names(writer, &self.recent_words);
+
+        try writer.print("\nDictionary <{d}>, latest definitions first:\n", .{rt.dictionary.count()});
+        try write_names(writer, &self.recent_definitions);
+    }
+};
+
+fn write_names(writer: anytype, ring: *const NameRing) !void {
+    var idx: usize = 0;
+    while (idx < ring.len) : (idx += 1) try writer.print("  {s}\n", .{ring.nth_latest(idx)});
+}
+
+test "Visualizer" {
+    var rt = try Runtime.init(testAllocator);
+    defer rt.deinit_guard_for_empty_stack();
+
+    var visualizer = Visualizer.init(io.DISCARD);
+    visualizer.rows = 2;
+    try rt.add_observer(visualizer.observer());
+
+    try rt.eval("&@DUP :twin @DEFINE 1 2 @SWAP twin");
+
+    var out = std.ArrayList(u8).init(testAllocator);
+    defer out.deinit();
+    try visualizer.render(&rt, out.writer());
+
+    var expected = std.ArrayList(u8).init(testAllocator);
+    defer expected.deinit();
+    try expected.writer().print(
+        \\{s}Stack <3>, top first:
+        \\  1
+        \\  1
+        \\  ... and 1 more
+        \\
+        \\Running <0>, innermost first:
+        \\
+        \\Recently run, latest first:
+        \\  {s}
+        \\  @SWAP
+        \\  @DEFINE
+        \\
+        \\Dictionary <{d}>, latest definitions first:
+        \\  twin
+        \\
+    , .{ CLEAR, tracer.describe(&rt, (try rt.find_word("twin")).?), rt.dictionary.count() });
+    try expectEqualStrings(expected.items, out.items);
+
+    try rt.eval("@DROP @DROP @DROP");
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
@@ -0,0 +1,99 @@
+// Copyright (C) 2023 Josh Klar aka "klardotsh" <josh@klar.sh>
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES WITH
+// REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY SPECIAL, DIRECT,
+// INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM
+// LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR
+// OTHER TORTIOUS ACTION, ARISING OUT OF OR IN CONNECTION WITH THE USE OR
+// PERFORMANCE OF THIS SOFTWARE.
+
+const std = @import("std");
+const expect = std.testing.expect;
+const expectEqualStrings = std.testing.expectEqualStrings;
+
+const helpers = @import("./helpers.zig");
+
+const InternalError = @import("./internal_error.zig").InternalError;
+const Runtime = @import("./runtime.zig").Runtime;
+
+/// Words belonging to a vocabulary are named `vocabulary/word`, for example
+/// `Equatable/eq`. The dictionary itself is flat: this is purely a naming
+/// convention, but it's one the Runtime leans on to figure out which (if any)
+/// lazy vocabulary to materialize when a lookup misses.
+pub const VOCABULARY_SEPARATOR = '/';
+
+// TODO: configurable in build.zig
+pub const MAX_VOCABULARY_FILE_SIZE = 16 * 1024 * 1024;
+
+/// Host-provided vocabulary loaders are handed back whatever `context` they
+/// were registered with, alongside the Runtime to define words into.
+pub const LoaderFn = fn (context: ?*anyopaque, rt: *Runtime) anyerror!void;
+
+/// A vocabulary that has been registered with a Runtime, but whose words
+/// won't be defined until something first looks one of them up. This keeps
+/// large standard libraries from costing anything (startup time or RAM) for
+/// scripts that never touch them.
+pub const LazyVocabulary = struct {
+    const Self = @This();
+
+    pub const Source = union(enum) {
+        /// Call a host function to define the vocabulary's words.
+        Loader: struct {
+            context: ?*anyopaque,
+            load: *const LoaderFn,
+        },
+        /// Evaluate a gale source file (relative to the current working
+        /// directory) into the Runtime. The Runtime owns this slice once
+        /// registered. Unavailable on freestanding targets.
+        Path: []const u8,
+    };
+
+    source: Source,
+
+    /// Set *before* loading begins, so that a vocabulary which (directly or
+    /// otherwise) looks up one of its own words mid-load doesn't recurse
+    /// forever, and reset if loading fails so that it can be retried.
+    loaded: bool = false,
+
+    /// Define this vocabulary's words into `rt`. This doesn't touch `loaded`
+    /// (and doesn't take self by reference), since loaders are free to
+    /// register further vocabularies, which may well move us in memory.
+    pub fn load_into(source: Source, rt: *Runtime) anyerror!void {
+        switch (source) {
+            .Loader => |loader| try loader.load(loader.context, rt),
+            .Path => |path| {
+                // No filesystem, no files. Loader sources work fine, though.
+                if (comptime !helpers.HOSTED) return InternalError.Unimplemented;
+
+                const contents = try std.fs.cwd().readFileAlloc(rt.alloc, path, MAX_VOCABULARY_FILE_SIZE);
+                defer rt.alloc.free(contents);
+                try rt.eval_source(path, contents);
+            },
+        }
+    }
+};
+
+/// Return the vocabulary portion of a word name like `vocabulary/word`, or
+/// null if the word isn't namespaced. Words *starting* with the separator
+/// (say, `/`, which someone will inevitably want for division) are not
+/// considered namespaced.
+pub fn vocabulary_of(name: []const u8) ?[]const u8 {
+    const idx = std.mem.indexOfScalar(u8, name, VOCABULARY_SEPARATOR) orelse return null;
+    if (idx == 0) return null;
+    return name[0..idx];
+}
+
+test "vocabulary_of" {
+    try expectEqualStrings("Equatable", vocabulary_of("Equatable/eq").?);
+    try expect(vocabulary_of("eq") == null);
+    try expect(vocabulary_of("/") == null);
+    try expect(vocabulary_of("/2") == null);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
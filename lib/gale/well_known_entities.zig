@@ -48,12 +48,49 @@ pub const WellKnownSignature = enum(u8) {
     NullarySingleUnboundedWordSignature,
 };
 
-pub fn shape_storage() WellKnownShapeStorage {
-    return .{
-        // This explicit cast avoids an error at array fill time:
-        // error: expected type '@TypeOf(undefined)', found 'shape.Shape'
-        @as(Shape, undefined),
-    } ** @typeInfo(WellKnownShapeStorage).Array.len;
+/// The well-known Shapes, fully built at compile time, for Runtime.init to
+/// copy into place wholesale rather than constructing each in turn. The rest
+/// of a Runtime (its primitives and Prelude) is still built as it starts,
+/// though see Runtime.Options.prelude_image for skipping the Prelude.
+pub const SHAPE_IMAGE: WellKnownShapeStorage = build_shape_image();
+
+fn build_shape_image() WellKnownShapeStorage {
+    var image: WellKnownShapeStorage = undefined;
+
+    for (image) |*it, idx| {
+        it.* = Shape.new_containing_primitive(.Unbounded, primitive_for(@intToEnum(WellKnownShape, idx)));
+    }
+
+    return image;
+}
+
+fn primitive_for(shape: WellKnownShape) Shape.Primitives {
+    return switch (shape) {
+        .UnboundedArray => .Array,
+        .UnboundedBoolean => .Boolean,
+        .UnboundedString, .UnboundedSymbol => .CharSlice,
+        .UnboundedUnsignedInt => .UnsignedInt,
+        .UnboundedSignedInt => .SignedInt,
+        .UnboundedFloat => .Float,
+        .UnboundedWord => .Word,
+        .UnboundedWordSignature => .WordSignature,
+    };
+}
+
+/// Which well-known Shape each well-known (NullarySingle) signature places
+/// onto the Stack.
+fn shape_for(signature: WellKnownSignature) WellKnownShape {
+    return switch (signature) {
+        .NullarySingleUnboundedArray => .UnboundedArray,
+        .NullarySingleUnboundedBoolean => .UnboundedBoolean,
+        .NullarySingleUnboundedString => .UnboundedString,
+        .NullarySingleUnboundedSymbol => .UnboundedSymbol,
+        .NullarySingleUnboundedUnsignedInt => .UnboundedUnsignedInt,
+        .NullarySingleUnboundedSignedInt => .UnboundedSignedInt,
+        .NullarySingleUnboundedFloat => .UnboundedFloat,
+        .NullarySingleUnboundedWord => .UnboundedWord,
+        .NullarySingleUnboundedWordSignature => .UnboundedWordSignature,
+    };
 }
 
 pub fn signature_storage() WellKnownSignatureStorage {
@@ -64,72 +101,35 @@ pub fn signature_storage() WellKnownSignatureStorage {
     } ** @typeInfo(WellKnownSignatureStorage).Array.len;
 }
 
+/// Load the build-time Shape image into the Runtime and intern a signature
+/// for each well-known Shape. The Shapes must already live at a stable
+/// address (Runtime heap-allocates them), since the interned signatures
+/// point into them.
 pub fn populate(rt: *Runtime) !void {
-    for (rt.well_known_shapes) |*it, idx| {
-        switch (@intToEnum(WellKnownShape, idx)) {
-            .UnboundedArray => {
-                it.* = Shape.new_containing_primitive(.Unbounded, .Array);
-                var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = it });
-                stored.value_ptr.* = {};
-                rt.well_known_signatures[@enumToInt(WellKnownSignature.NullarySingleUnboundedArray)] =
-                    stored.key_ptr;
-            },
-            .UnboundedBoolean => {
-                it.* = Shape.new_containing_primitive(.Unbounded, .Boolean);
-                var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = it });
-                stored.value_ptr.* = {};
-                rt.well_known_signatures[@enumToInt(WellKnownSignature.NullarySingleUnboundedBoolean)] =
-                    stored.key_ptr;
-            },
-            .UnboundedString => {
-                it.* = Shape.new_containing_primitive(.Unbounded, .CharSlice);
-                var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = it });
-                stored.value_ptr.* = {};
-                rt.well_known_signatures[@enumToInt(WellKnownSignature.NullarySingleUnboundedString)] =
-                    stored.key_ptr;
-            },
-            .UnboundedSymbol => {
-                it.* = Shape.new_containing_primitive(.Unbounded, .CharSlice);
-                var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = it });
-                stored.value_ptr.* = {};
-                rt.well_known_signatures[@enumToInt(WellKnownSignature.NullarySingleUnboundedSymbol)] =
-                    stored.key_ptr;
-            },
-            .UnboundedUnsignedInt => {
-                it.* = Shape.new_containing_primitive(.Unbounded, .UnsignedInt);
-                var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = it });
-                stored.value_ptr.* = {};
-                rt.well_known_signatures[@enumToInt(WellKnownSignature.NullarySingleUnboundedUnsignedInt)] =
-                    stored.key_ptr;
-            },
-            .UnboundedSignedInt => {
-                it.* = Shape.new_containing_primitive(.Unbounded, .SignedInt);
-                var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = it });
-                stored.value_ptr.* = {};
-                rt.well_known_signatures[@enumToInt(WellKnownSignature.NullarySingleUnboundedSignedInt)] =
-                    stored.key_ptr;
-            },
-            .UnboundedFloat => {
-                it.* = Shape.new_containing_primitive(.Unbounded, .Float);
-                var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = it });
-                stored.value_ptr.* = {};
-                rt.well_known_signatures[@enumToInt(WellKnownSignature.NullarySingleUnboundedFloat)] =
-                    stored.key_ptr;
-            },
-            .UnboundedWord => {
-                it.* = Shape.new_containing_primitive(.Unbounded, .Word);
-                var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = it });
-                stored.value_ptr.* = {};
-                rt.well_known_signatures[@enumToInt(WellKnownSignature.NullarySingleUnboundedWord)] =
-                    stored.key_ptr;
-            },
-            .UnboundedWordSignature => {
-                it.* = Shape.new_containing_primitive(.Unbounded, .WordSignature);
-                var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = it });
-                stored.value_ptr.* = {};
-                rt.well_known_signatures[@enumToInt(WellKnownSignature.NullarySingleUnboundedWordSignature)] =
-                    stored.key_ptr;
-            },
-        }
+    rt.well_known_shapes.* = SHAPE_IMAGE;
+
+    for (rt.well_known_signatures) |*it, idx| {
+        const shape = rt.get_well_known_shape(shape_for(@intToEnum(WellKnownSignature, idx)));
+        var stored = try rt.signatures.getOrPut(WordSignature{ .NullarySingle = shape });
+        stored.value_ptr.* = {};
+        it.* = stored.key_ptr;
     }
 }
+
+test "populate: loads the shape image and points signatures into it" {
+    var rt = try Runtime.init(std.testing.allocator);
+    defer rt.deinit();
+
+    const float_shape = rt.get_well_known_shape(.UnboundedFloat);
+    try std.testing.expect(std.meta.eql(
+        SHAPE_IMAGE[@enumToInt(WellKnownShape.UnboundedFloat)].contents,
+        float_shape.contents,
+    ));
+
+    const float_sig = rt.get_well_known_word_signature(.NullarySingleUnboundedFloat);
+    try std.testing.expectEqual(float_shape, float_sig.NullarySingle);
+}
+
+test {
+    std.testing.refAllDecls(@This());
+}
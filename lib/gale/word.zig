@@ -22,6 +22,44 @@ const WordSignature = @import("./word_signature.zig").WordSignature;
 // TODO: handle stack juggling
 pub const PrimitiveWord = fn (*Runtime) anyerror!void;
 
+/// A late-bound reference to a word by name, which caches whatever the name
+/// last resolved to. The cache is only trusted while the Runtime's
+/// dictionary_generation still matches the one recorded here, which only
+/// changes when an existing name is redefined, so repeated calls through a
+/// call site skip the dictionary entirely in the common case. `&name` makes
+/// one, so compound words built in gale (`&a &b @COMPOSE`) call through a
+/// call site per step, as do those built by Runtime.word_from_call_sites.
+///
+/// Being late-bound, a call site can name a word which doesn't exist yet, so
+/// long as it does by the time it's run, which is all a word needs to call
+/// itself (`&@DROP &drain @COMPOSE :drain @DEFINE`), or for two words to
+/// call each other.
+pub const CallSite = struct {
+    /// Always an interned Symbol, to which we hold a reference.
+    name: *Types.HeapedSymbol,
+    /// We hold no reference to this: the dictionary does, until whatever
+    /// replaces it bumps the generation, and a reference here would keep
+    /// words calling themselves alive forever.
+    cached: ?*Types.HeapedWord = null,
+    generation: usize = 0,
+    /// The Runtime.id of the Runtime this call site was created in. Only
+    /// that Runtime may write to the cache: others (those using it as a
+    /// frozen base) resolve the name fresh each time.
+    owner_id: usize = 0,
+};
+
+/// Host closures are primitives which carry a pointer's worth of host-owned
+/// state with them, for embedders whose words need more context than the
+/// Runtime itself (a database handle, an application struct, whatever). gale
+/// never frees (or even looks at) `context`: its lifetime is the host's
+/// problem, and it must outlive every Runtime the closure is defined into.
+pub const HostClosureFn = fn (context: ?*anyopaque, *Runtime) anyerror!void;
+pub const HostClosure = struct {
+    context: ?*anyopaque,
+    call: *const HostClosureFn,
+};
+
+pub const CallSiteImplementation = *CallSite;
 pub const CompoundImplementation = []*Types.HeapedWord;
 pub const HeapLitImplementation = *Object;
 pub const PrimitiveImplementation = *const PrimitiveWord;
@@ -29,10 +67,18 @@ pub const WordImplementation = union(enum) {
     Primitive: PrimitiveImplementation,
     Compound: CompoundImplementation,
     HeapLit: HeapLitImplementation,
+    CallSite: CallSiteImplementation,
+    HostClosure: HostClosure,
 };
 
 pub const Flags = packed struct {
     hidden: bool,
+    /// Set on the literals @CONSTANT and @VALUE define, which name a value
+    /// rather than any code.
+    data: bool,
+    /// Set (alongside data) on the literals @VALUE defines, whose value @TO
+    /// can change.
+    mutable: bool,
 };
 
 // TODO: Docs.
@@ -91,7 +137,7 @@ pub const Word = struct {
 
     pub fn new_untagged(impl: WordImplementation, sig: ?SignatureState) Self {
         return Self{
-            .flags = .{ .hidden = false },
+            .flags = .{ .hidden = false, .data = false, .mutable = false },
             .tags = [_]u8{0} ** TAG_ARRAY_SIZE,
             .impl = impl,
             .signature = sig,
@@ -119,11 +165,26 @@ pub const Word = struct {
         return new_untagged(.{ .Primitive = impl }, sig);
     }
 
+    pub fn new_host_closure_untagged(
+        impl: HostClosure,
+        sig: ?SignatureState,
+    ) Self {
+        return new_untagged(.{ .HostClosure = impl }, sig);
+    }
+
+    pub fn new_call_site_untagged(
+        impl: CallSiteImplementation,
+        sig: ?SignatureState,
+    ) Self {
+        return new_untagged(.{ .CallSite = impl }, sig);
+    }
+
     pub fn deinit(self: *Self, alloc: std.mem.Allocator) void {
         return switch (self.impl) {
             // There's no necessary action to deinit a primitive: they live in
-            // the data sector of the binary anyway and can't be freed.
-            .Primitive => {},
+            // the data sector of the binary anyway and can't be freed. Host
+            // closures' contexts are owned by the host, not us.
+            .Primitive, .HostClosure => {},
             // Defer to the heaped object's teardown process for its underlying
             // memory as appropriate, and then destroy the Rc that holds that
             // Object.
@@ -153,6 +214,10 @@ pub const Word = struct {
                 // Finally, destroy the compound slice itself.
                 alloc.free(compound);
             },
+            .CallSite => |site| {
+                _ = site.name.decrement_and_prune(.FreeInnerDestroySelf, alloc);
+                alloc.destroy(site);
+            },
         };
     }
 };
@@ -16,24 +16,76 @@ const std = @import("std");
 const Types = @import("./types.zig");
 const WordList = @import("./word_list.zig").WordList;
 
-const SymbolContext = struct {
-    const Self = @This();
-
-    pub fn hash(_: Self, s: *Types.HeapedSymbol) u64 {
-        return std.hash_map.hashString(s.value.?);
-    }
-    pub fn eql(_: Self, a: *Types.HeapedSymbol, b: *Types.HeapedSymbol) bool {
-        return std.hash_map.eqlString(a.value.?, b.value.?);
-    }
-};
-
-// TODO: Docs.
-pub const WordMap = std.HashMap(
-    *Types.HeapedSymbol,
-    WordList,
-    SymbolContext,
-    std.hash_map.default_max_load_percentage,
-);
+/// The hash function used for dictionary and symbol pool lookups, which
+/// dominate interpretation time. Any type exposing a Wyhash-like
+/// `hash(seed: u64, input: []const u8) u64` will do.
+// TODO: configurable in build.zig
+pub const DefaultHasher = std.hash.Wyhash;
+
+/// Since every Symbol naming a word is interned by the Runtime (see
+/// Runtime.get_or_put_symbol), a Symbol's address is as good as its contents
+/// for identity purposes. Hashing the address rather than the string means
+/// the (arbitrarily long) name is only ever hashed once, when it's first
+/// interned, and equality checks become a single pointer comparison.
+///
+/// The flip side: keys *must* come from the Runtime's symbol pool, otherwise
+/// two Symbols with identical contents will name two different WordLists.
+pub fn SymbolContext(comptime Hasher: type) type {
+    return struct {
+        const Self = @This();
+
+        pub fn hash(_: Self, s: *Types.HeapedSymbol) u64 {
+            const addr = @ptrToInt(s);
+            return Hasher.hash(0, std.mem.asBytes(&addr));
+        }
+        pub fn eql(_: Self, a: *Types.HeapedSymbol, b: *Types.HeapedSymbol) bool {
+            return a == b;
+        }
+    };
+}
+
+/// String-keyed hashing context for anything wanting to share the
+/// dictionary's Hasher, namely the Runtime's symbol pool.
+pub fn StringContext(comptime Hasher: type) type {
+    return struct {
+        const Self = @This();
+
+        pub fn hash(_: Self, s: []const u8) u64 {
+            return Hasher.hash(0, s);
+        }
+        pub fn eql(_: Self, a: []const u8, b: []const u8) bool {
+            return std.mem.eql(u8, a, b);
+        }
+    };
+}
+
+/// A mapping of interned Symbols to the list of Words defined under that
+/// name, using an arbitrary Hasher. Most folks want WordMap.
+pub fn WordMapWithHasher(comptime Hasher: type) type {
+    return std.HashMap(
+        *Types.HeapedSymbol,
+        WordList,
+        SymbolContext(Hasher),
+        std.hash_map.default_max_load_percentage,
+    );
+}
+
+pub const WordMap = WordMapWithHasher(DefaultHasher);
+
+test "WordMap: keys by interned identity" {
+    var name_a = "foo".*;
+    var name_b = "foo".*;
+    var sym_a = Types.HeapedSymbol.init(name_a[0..]);
+    var sym_b = Types.HeapedSymbol.init(name_b[0..]);
+
+    var map = WordMap.init(std.testing.allocator);
+    defer map.deinit();
+
+    try map.put(&sym_a, WordList.init(std.testing.allocator));
+    try std.testing.expect(map.contains(&sym_a));
+    // Same contents, different (non-interned) Symbol: not the same key.
+    try std.testing.expect(!map.contains(&sym_b));
+}
 
 test {
     std.testing.refAllDecls(@This());
@@ -17,6 +17,7 @@ const testAllocator: Allocator = std.testing.allocator;
 const expect = std.testing.expect;
 const expectEqual = std.testing.expectEqual;
 const expectError = std.testing.expectError;
+const expectFmt = std.testing.expectFmt;
 
 const InternalError = @import("./internal_error.zig").InternalError;
 const Types = @import("./types.zig");
@@ -125,6 +126,65 @@ pub const WordSignature = union(enum) {
         };
     }
 
+    /// The stack effect notation used atop each primitive's docs: `( taken
+    /// -> left )` for words which replace what they're given, `( expected <-
+    /// added )` for those which leave it be, and `!!!` for words which never
+    /// return.
+    pub fn format(
+        self: Self,
+        comptime fmt: []const u8,
+        options: std.fmt.FormatOptions,
+        writer: anytype,
+    ) @TypeOf(writer).Error!void {
+        _ = fmt;
+        _ = options;
+
+        switch (self) {
+            .SideEffectary => try write_effect(writer, &.{}, "->", &.{}, false),
+            .Nullary => |gives| try write_effect(writer, &.{}, "<-", gives, false),
+            .NullarySingle => |gives| try write_effect(writer, &.{}, "<-", &.{gives}, false),
+            .NullaryTerminal => try write_effect(writer, &.{}, "->", &.{}, true),
+            .PurelyConsuming => |takes| try write_effect(writer, takes, "->", &.{}, false),
+            .ConsumingTerminal => |takes| try write_effect(writer, takes, "->", &.{}, true),
+            .PurelyAdditive => |sig| try write_effect(writer, sig.expects, "<-", sig.gives, false),
+            .Mutative => |sig| try write_effect(writer, sig.before, "->", sig.after, false),
+        }
+    }
+
+    fn write_effect(
+        writer: anytype,
+        left: []const *Shape,
+        arrow: []const u8,
+        right: []const *Shape,
+        terminal: bool,
+    ) @TypeOf(writer).Error!void {
+        try writer.writeByte('(');
+        for (left) |shape| try write_shape(writer, shape);
+        try writer.print(" {s}", .{arrow});
+        for (right) |shape| try write_shape(writer, shape);
+        if (terminal) try writer.writeAll(" !!!");
+        try writer.writeAll(" )");
+    }
+
+    /// A space, then `shape`'s name, if it has one, or its contents.
+    fn write_shape(writer: anytype, shape: *const Shape) @TypeOf(writer).Error!void {
+        try writer.writeByte(' ');
+        if (shape.given_name) |given_name| return writer.writeAll(given_name.value.?);
+
+        switch (shape.contents) {
+            .Empty => try writer.writeAll("Empty"),
+            .CatchAll => |idx| try writer.print("@{d}", .{idx}),
+            .Primitive => |primitive| {
+                const tag = switch (primitive) {
+                    .Bounded => |inner| @tagName(inner),
+                    .Unbounded => |inner| @tagName(inner),
+                };
+                // It's a String everywhere gale code can see it.
+                try writer.writeAll(if (std.mem.eql(u8, tag, "CharSlice")) "String" else tag);
+            },
+        }
+    }
+
     const IncompatibilitySidedness = enum {
         Left,
         Right,
@@ -788,6 +848,20 @@ pub const WordSignature = union(enum) {
         // TODO: test *why* these words aren't compatible
         try expect(!word1.compatible_with(&word2).as_bool_lossy());
     }
+    test "format" {
+        var catchall_shape = Shape.new_containing_catchall(1);
+        var string_shape = Shape.new_containing_primitive(.Unbounded, .CharSlice);
+        var boolean_shape = Shape.new_containing_primitive(.Unbounded, .Boolean);
+
+        var takes = [_]*Shape{ &catchall_shape, &string_shape };
+        var gives = [_]*Shape{&boolean_shape};
+
+        try expectFmt("( -> )", "{}", .{Self.SideEffectary});
+        try expectFmt("( <- Boolean )", "{}", .{Self{ .NullarySingle = &boolean_shape }});
+        try expectFmt("( @1 String -> !!! )", "{}", .{Self{ .ConsumingTerminal = &takes }});
+        try expectFmt("( @1 String <- Boolean )", "{}", .{Self{ .PurelyAdditive = .{ .expects = &takes, .gives = &gives } }});
+        try expectFmt("( @1 String -> Boolean )", "{}", .{Self{ .Mutative = .{ .before = &takes, .after = &gives } }});
+    }
 };
 
 test {
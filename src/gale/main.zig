@@ -12,10 +12,1484 @@
 // PERFORMANCE OF THIS SOFTWARE.
 
 const std = @import("std");
+const builtin = @import("builtin");
 const gale = @import("gale");
+const build_options = @import("build_options");
 
+const PROMPT = "> ";
+
+// TODO: configurable in build.zig
+const MAX_LINE_LEN = 64 * 1024;
+const MAX_SOURCE_LEN = 16 * 1024 * 1024;
+// Recordings (see lib/gale/recording.zig) hold all of stdin, so can get
+// rather bigger than sources.
+const MAX_RECORDING_LEN = 256 * 1024 * 1024;
+
+/// What libgale compiles in (see lib/gale/features.zig), as `zig build -D...`
+/// would have it.
+pub const gale_features = gale.Features{
+    .readline = build_options.readline,
+    .regex = build_options.regex,
+    .json = build_options.json,
+    .net = build_options.net,
+    .crypto = build_options.crypto,
+};
+
+/// `gale NAME ...` runs these (each returning an exit status) instead of the
+/// REPL.
+const SUBCOMMANDS = .{
+    .{ "bundle", make_bundle },
+    .{ "fmt", fmt },
+    .{ "kernel", kernel },
+    .{ "lint", lint },
+    .{ "pkg", pkg },
+    .{ "run", run },
+    .{ "tags", make_tags },
+    .{ "test", run_tests },
+};
+
+// The CLI is deliberately a thin consumer of libgale's public API (see
+// lib/gale/gale.zig): if it needs something that isn't exported there, that's
+// a sign the embedding API is missing something.
 pub fn main() anyerror!void {
     var gpa = std.heap.GeneralPurposeAllocator(.{}){};
-    const runtime = try gale.Runtime.init(gpa.allocator());
-    std.debug.print("{any}\n", .{runtime});
+    defer _ = gpa.deinit();
+
+    const args = try std.process.argsAlloc(gpa.allocator());
+    defer std.process.argsFree(gpa.allocator(), args);
+
+    // Executables made by `gale bundle --binary` run their bundle, taking
+    // every argument as a flag to it, and are nothing more.
+    if (embedded_bundle(gpa.allocator()) catch null) |text| {
+        defer gpa.allocator().free(text);
+        const status = run_bundled(gpa.allocator(), text, args[1..]) catch |err| blk: {
+            std.debug.print("{s}: {}\n", .{ args[0], painted(err, stderr_style()) });
+            break :blk 2;
+        };
+        if (status != 0) std.process.exit(status);
+        return;
+    }
+
+    inline for (SUBCOMMANDS) |subcommand| {
+        if (args.len > 1 and std.mem.eql(u8, args[1], subcommand[0])) {
+            const status = subcommand[1](gpa.allocator(), args[2..]) catch |err| blk: {
+                std.debug.print("gale {s}: {}\n", .{ subcommand[0], painted(err, stderr_style()) });
+                break :blk 2;
+            };
+            if (status != 0) std.process.exit(status);
+            return;
+        }
+    }
+
+    const options = parse_flags(args[1..]) catch |err| {
+        std.debug.print("gale: {}\n", .{painted(err, stderr_style())});
+        std.process.exit(2);
+    };
+
+    const lib_path = try library_path_for(gpa.allocator(), options.lib_dirs.constSlice());
+    defer gale.library_path.free(gpa.allocator(), lib_path);
+
+    var runtime = try runtime_for(gpa.allocator(), &options, lib_path, .Live);
+    defer runtime.deinit();
+
+    var trace_file: ?std.fs.File = null;
+    defer if (trace_file) |file| file.close();
+    var tracer: gale.Tracer = undefined;
+
+    if (options.trace) |trace| {
+        tracer = gale.Tracer.init(switch (trace) {
+            .Stderr => gale.io.stderr(),
+            .File => |path| blk: {
+                trace_file = try std.fs.cwd().createFile(path, .{});
+                break :blk gale.io.file_output(&trace_file.?);
+            },
+        });
+        try runtime.add_observer(tracer.observer());
+    }
+
+    var event_log_file: ?std.fs.File = null;
+    defer if (event_log_file) |file| file.close();
+    var event_log: gale.EventLog = undefined;
+
+    if (options.event_log) |destination| {
+        event_log = gale.EventLog.init(.{ .Output = switch (destination) {
+            .Stderr => gale.io.stderr(),
+            // Appended to, so that a log outlives the runs it's logging.
+            .File => |path| blk: {
+                event_log_file = try std.fs.cwd().createFile(path, .{ .truncate = false });
+                try event_log_file.?.seekFromEnd(0);
+                break :blk gale.io.file_output(&event_log_file.?);
+            },
+        } });
+        try runtime.add_observer(event_log.observer());
+    }
+
+    if (options.image) |path| load_image(gpa.allocator(), &runtime, path) catch |err| {
+        std.debug.print("gale: {s}: {}\n", .{ path, painted(err, stderr_style()) });
+        std.process.exit(2);
+    };
+
+    var visualizer: gale.Visualizer = undefined;
+    if (options.visualize) |step_delay_ms| {
+        visualizer = gale.Visualizer.init(gale.io.stdout());
+        visualizer.step_delay_ms = step_delay_ms;
+        try runtime.add_observer(visualizer.observer());
+        try visualizer.enter();
+    }
+    defer if (options.visualize != null) visualizer.leave() catch {};
+
+    try repl(gpa.allocator(), &runtime, if (options.visualize != null) &visualizer else null);
+}
+
+/// Pick up where the image at `path` left off, unless there's nothing there
+/// yet, in which case there's nothing to pick up.
+fn load_image(alloc: std.mem.Allocator, runtime: *gale.Runtime, path: []const u8) !void {
+    const bytes = std.fs.cwd().readFileAlloc(alloc, path, MAX_SOURCE_LEN) catch |err| switch (err) {
+        error.FileNotFound => return,
+        else => return err,
+    };
+    defer alloc.free(bytes);
+
+    try gale.image.load(runtime, bytes);
+}
+
+/// The source at `path`, which the caller owns, failing with
+/// UnsupportedEncoding (and saying as much, with what it looks like instead)
+/// if it isn't UTF-8. See lib/gale/source_encoding.zig.
+fn read_source(alloc: std.mem.Allocator, path: []const u8) ![]u8 {
+    const source = try std.fs.cwd().readFileAlloc(alloc, path, MAX_SOURCE_LEN);
+    errdefer alloc.free(source);
+
+    gale.source_encoding.check(source) catch |err| {
+        const found = gale.source_encoding.detect(source).name();
+        std.debug.print("gale: {s} looks like {s}: gale only reads UTF-8, so re-save it as that\n", .{ path, found });
+        return err;
+    };
+    return source;
+}
+
+/// A Runtime as `options` would have it, searching `lib_path` (which must
+/// outlive it) for modules, and recording or replaying as `recording` says.
+fn runtime_for(
+    alloc: std.mem.Allocator,
+    options: *const CliOptions,
+    lib_path: []const []const u8,
+    recording: gale.recording.Source,
+) !gale.Runtime {
+    return try gale.Runtime.init_with_options(alloc, .{
+        .capabilities = options.granted,
+        .library_path = lib_path,
+        .flags = options.flags.constSlice(),
+        .recording = recording,
+    });
+}
+
+/// The REPL's library path: `lib_dirs`, then the roots of the dependencies
+/// of the package in the working directory (if it is one), then the rest of
+/// gale.library_path.from_environment's usual suspects.
+fn library_path_for(alloc: std.mem.Allocator, lib_dirs: []const []const u8) ![][]u8 {
+    var manifest = gale.package.read_manifest(alloc, ".") catch |err| switch (err) {
+        error.FileNotFound => return try gale.library_path.from_environment(alloc, lib_dirs),
+        else => return err,
+    };
+    defer manifest.deinit();
+
+    const roots = try gale.package.roots(alloc, &manifest, ".", gale.package.DEFAULT_STORE);
+    defer gale.library_path.free(alloc, roots);
+
+    const dirs = try std.mem.concat(alloc, []const u8, &.{ lib_dirs, roots });
+    defer alloc.free(dirs);
+    return try gale.library_path.from_environment(alloc, dirs);
+}
+
+/// Read lines from stdin and evaluate them, one at a time, until EOF. Prompts
+/// (and cheery acknowledgements) are only shown to humans: piped input gets
+/// error messages alone. With a `visualizer`, the screen is redrawn after
+/// each line, too.
+fn repl(alloc: std.mem.Allocator, runtime: *gale.Runtime, visualizer: ?*gale.Visualizer) !void {
+    const stdin = std.io.getStdIn();
+    const stdout = std.io.getStdOut().writer();
+    const interactive = stdin.isTty();
+    const style = style_for(std.io.getStdOut());
+
+    var buffered = std.io.bufferedReader(stdin.reader());
+    const input = buffered.reader();
+
+    // Without readline, there's no debugger to speak of, and Ctrl-C does
+    // whatever it does to any other process.
+    if (comptime gale_features.readline) install_sigint_handler(runtime);
+
+    var debugger = if (comptime gale_features.readline) gale.Debugger.init(runtime) else {};
+    defer if (comptime gale_features.readline) debugger.deinit();
+
+    var session = Session.init(alloc);
+    defer session.deinit();
+
+    // Drawn before anything's said about how the line went, which would
+    // otherwise be drawn right over.
+    if (visualizer) |it| try it.draw(runtime);
+
+    while (true) {
+        if (interactive) try stdout.writeAll(PROMPT);
+
+        const line = (try input.readUntilDelimiterOrEofAlloc(alloc, '\n', MAX_LINE_LEN)) orelse break;
+        defer alloc.free(line);
+        idle_interrupts.store(0, .Release);
+        runtime.clear_load_trace();
+
+        const trimmed = std.mem.trimRight(u8, line, "\r");
+        const save_path = save_command_path(trimmed);
+        const is_debug_command = gale_features.readline and trimmed.len > 0 and trimmed[0] == DEBUG_PREFIX;
+        const handled = if (save_path) |path|
+            session.save(path)
+        else if (is_debug_command)
+            debug_command(&debugger, trimmed[1..], stdout)
+        else
+            eval_line(runtime, trimmed);
+        if (visualizer) |it| try it.draw(runtime);
+
+        handled catch |err| {
+            try stdout.print("error: {}\n", .{painted(err, style)});
+            try write_load_trace(runtime, stdout, style);
+            continue;
+        };
+
+        if (save_path == null and !is_debug_command) try session.append(trimmed);
+
+        if (interactive) try stdout.writeAll("ok\n");
+    }
+
+    if (interactive) try stdout.writeAll("\n");
+}
+
+/// Where a failed @LOAD (or other eval_source) failed, if it was one, one
+/// source per line, innermost first, after why versions didn't match up or
+/// everywhere a file that couldn't be found was looked for, if either is why.
+/// The innermost of these sources gets its failing line shown too, if it can
+/// still be read.
+fn write_load_trace(runtime: *gale.Runtime, writer: anytype, style: gale.snippet.Style) !void {
+    if (runtime.version_conflict) |conflict| try writer.print("  {s}\n", .{conflict});
+    for (runtime.resolution_attempts.items) |path| {
+        try writer.print("  tried {s}\n", .{path});
+    }
+    for (runtime.load_trace.items) |position, idx| {
+        try writer.writeAll("  at ");
+        try style.begin(writer, gale.snippet.BOLD);
+        try writer.print("{s}:{d}:{d}", .{ position.path, position.line, position.column });
+        try style.end(writer);
+        try writer.writeByte('\n');
+        if (idx == 0) write_position_snippet(runtime.alloc, writer, style, position) catch {};
+    }
+}
+
+/// `position`'s line of its source, read afresh (and normalised, as
+/// eval_source does, so that columns still line up), with carets under the
+/// word there. Best effort: sources can change or vanish (or, bundled, never
+/// have been files at all) between being loaded and failing.
+fn write_position_snippet(alloc: std.mem.Allocator, writer: anytype, style: gale.snippet.Style, position: gale.Runtime.SourcePosition) !void {
+    const raw = try std.fs.cwd().readFileAlloc(alloc, position.path, MAX_SOURCE_LEN);
+    defer alloc.free(raw);
+    const normalized = try gale.source_encoding.normalize(alloc, raw);
+    defer if (normalized) |it| alloc.free(it);
+
+    try gale.snippet.write_snippet(writer, style, normalized orelse raw, position.line, position.column);
+}
+
+/// How diagnostics written to `file` should look: coloured on terminals,
+/// unless NO_COLOR (see https://no-color.org) is set to anything at all.
+fn style_for(file: std.fs.File) gale.snippet.Style {
+    if (builtin.os.tag != .windows and std.os.getenv("NO_COLOR") != null) return .{};
+    return .{ .color = file.isTty() };
+}
+
+fn stderr_style() gale.snippet.Style {
+    return style_for(std.io.getStdErr());
+}
+
+/// `err`'s name, in bold red when `style` has colour, for `{}` in error
+/// messages.
+fn painted(err: anyerror, style: gale.snippet.Style) PaintedError {
+    return .{ .err = err, .style = style };
+}
+
+const PaintedError = struct {
+    err: anyerror,
+    style: gale.snippet.Style,
+
+    pub fn format(self: PaintedError, comptime _: []const u8, _: std.fmt.FormatOptions, writer: anytype) !void {
+        try self.style.paint(writer, gale.snippet.RED, @errorName(self.err));
+    }
+};
+
+/// Evaluate one line of REPL input atomically, as far as the Stack is
+/// concerned: if anything goes wrong (Ctrl-C included), the Stack is rolled
+/// back to how it was before the line began.
+fn eval_line(runtime: *gale.Runtime, line: []const u8) !void {
+    const snap = try runtime.stack_snapshot();
+
+    evaluating.store(true, .Release);
+    defer {
+        evaluating.store(false, .Release);
+        // An interrupt which lands after the last word of the line has no
+        // business hitting the first word of the next one.
+        runtime.clear_interrupt();
+    }
+
+    runtime.eval(line) catch |err| {
+        runtime.stack_restore(snap);
+        return err;
+    };
+
+    snap.discard();
+}
+
+test "eval_line: rolls back on failure" {
+    var runtime = try gale.Runtime.init(std.testing.allocator);
+    defer runtime.deinit();
+
+    try eval_line(&runtime, "1");
+    try std.testing.expectError(gale.InternalError.UnknownWord, eval_line(&runtime, "2 3 bogus"));
+
+    runtime.interrupt();
+    try std.testing.expectError(gale.InternalError.Interrupted, eval_line(&runtime, "4 @DUP"));
+
+    try std.testing.expectEqual(@as(usize, 1), runtime.stack.depth());
+    try std.testing.expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+}
+
+/// `#save PATH` writes out the session so far as a script. Unlike the
+/// debugger's commands, this is around with or without readline.
+const SAVE_COMMAND = "#save";
+
+/// The PATH of a `#save PATH` line (empty if it's missing), or null if
+/// `line` is anything else.
+fn save_command_path(line: []const u8) ?[]const u8 {
+    if (!std.mem.startsWith(u8, line, SAVE_COMMAND)) return null;
+
+    const rest = line[SAVE_COMMAND.len..];
+    if (rest.len > 0 and rest[0] != ' ' and rest[0] != '\t') return null;
+    return std.mem.trim(u8, rest, " \t");
+}
+
+/// The lines of a REPL session which were evaluated without failing, for
+/// #save to write out as a script, so that what was worked out at the prompt
+/// needn't be copied out of the terminal by hand. Failed lines left the
+/// Stack just as it was (see eval_line), so leaving them out changes nothing
+/// about what the script does, and commands (#save included) aren't gale at
+/// all.
+const Session = struct {
+    const Self = @This();
+
+    alloc: std.mem.Allocator,
+    lines: std.ArrayList([]u8),
+
+    fn init(alloc: std.mem.Allocator) Self {
+        return .{ .alloc = alloc, .lines = std.ArrayList([]u8).init(alloc) };
+    }
+
+    fn deinit(self: *Self) void {
+        for (self.lines.items) |line| self.alloc.free(line);
+        self.lines.deinit();
+    }
+
+    /// Remember `line`, unless it's blank.
+    fn append(self: *Self, line: []const u8) !void {
+        if (std.mem.trim(u8, line, " \t").len == 0) return;
+
+        const owned = try self.alloc.dupe(u8, line);
+        errdefer self.alloc.free(owned);
+        try self.lines.append(owned);
+    }
+
+    fn write(self: Self, writer: anytype) !void {
+        try writer.writeAll("// Saved from a gale REPL session.\n");
+        for (self.lines.items) |line| try writer.print("{s}\n", .{line});
+    }
+
+    /// Write the session to `path`, overwriting whatever's there.
+    fn save(self: Self, path: []const u8) !void {
+        if (path.len == 0) return CliError.UnknownArgument;
+
+        var file = try std.fs.cwd().createFile(path, .{});
+        defer file.close();
+
+        var buffered = std.io.bufferedWriter(file.writer());
+        try self.write(buffered.writer());
+        try buffered.flush();
+    }
+};
+
+test "Session" {
+    var session = Session.init(std.testing.allocator);
+    defer session.deinit();
+
+    try session.append("1 2 +");
+    try session.append("   ");
+    try session.append("&@DUP :twin @DEFINE");
+
+    var out = std.ArrayList(u8).init(std.testing.allocator);
+    defer out.deinit();
+    try session.write(out.writer());
+    try std.testing.expectEqualStrings(
+        \\// Saved from a gale REPL session.
+        \\1 2 +
+        \\&@DUP :twin @DEFINE
+        \\
+    , out.items);
+
+    try std.testing.expectError(CliError.UnknownArgument, session.save(""));
+
+    try std.testing.expectEqualStrings("work.gale", save_command_path("#save  work.gale").?);
+    try std.testing.expectEqualStrings("", save_command_path("#save").?);
+    try std.testing.expect(save_command_path("#saved") == null);
+    try std.testing.expect(save_command_path("1 2 +") == null);
+}
+
+/// Lines starting with this are debugger commands, not gale source.
+const DEBUG_PREFIX = '#';
+
+const DEBUG_HELP =
+    \\debugger commands:
+    \\  #break NAME     stop before any word named NAME runs
+    \\  #unbreak NAME   stop stopping there
+    \\  #debug SOURCE   run SOURCE until a breakpoint (or the end)
+    \\  #step           run one step of whatever's paused
+    \\  #continue       run until the next breakpoint (or the end)
+    \\  #stack          show the stack, bottom to top
+    \\  #abort          throw away whatever's paused
+    \\  #save PATH      write this session's lines that worked to PATH, as a script
+    \\while paused, anything else is evaluated as usual, stack and all
+    \\
+;
+
+/// Run one debugger command (everything after the DEBUG_PREFIX) against
+/// `debugger`. Unlike eval_line, nothing is rolled back on failure: a failed
+/// word is worth seeing the aftermath of.
+fn debug_command(debugger: *gale.Debugger, command_line: []const u8, out: anytype) !void {
+    var words = std.mem.tokenize(u8, command_line, " \t");
+    const command = words.next() orelse "";
+    const argument = std.mem.trim(u8, words.rest(), " \t");
+
+    evaluating.store(true, .Release);
+    defer {
+        evaluating.store(false, .Release);
+        debugger.rt.clear_interrupt();
+    }
+
+    if (std.mem.eql(u8, command, "break") and argument.len > 0) {
+        try debugger.add_breakpoint(argument);
+    } else if (std.mem.eql(u8, command, "unbreak") and argument.len > 0) {
+        if (!debugger.remove_breakpoint(argument)) try out.print("no breakpoint on {s}\n", .{argument});
+    } else if (std.mem.eql(u8, command, "debug") and argument.len > 0) {
+        if (debugger.running()) debugger.abandon();
+        try debugger.begin_source(argument);
+        try report_stop(debugger, try debugger.run_until_break(), out);
+    } else if (std.mem.eql(u8, command, "step")) {
+        const step = try debugger.step();
+        try out.print("{s}: ", .{@tagName(step)});
+        try debugger.write_stack(out);
+        try out.writeByte('\n');
+    } else if (std.mem.eql(u8, command, "continue")) {
+        try report_stop(debugger, try debugger.run_until_break(), out);
+    } else if (std.mem.eql(u8, command, "stack")) {
+        try debugger.write_stack(out);
+        try out.writeByte('\n');
+    } else if (std.mem.eql(u8, command, "abort")) {
+        debugger.abandon();
+    } else {
+        try out.writeAll(DEBUG_HELP);
+    }
+}
+
+fn report_stop(debugger: *gale.Debugger, stop: gale.Debugger.Stop, out: anytype) !void {
+    switch (stop) {
+        .Finished => try out.writeAll("finished: "),
+        .Breakpoint => |name| try out.print("break at {s}: ", .{name}),
+    }
+
+    try debugger.write_stack(out);
+    try out.writeByte('\n');
+}
+
+test "debug_command" {
+    var runtime = try gale.Runtime.init(std.testing.allocator);
+    defer runtime.deinit();
+
+    var debugger = gale.Debugger.init(&runtime);
+    defer debugger.deinit();
+
+    var out = std.ArrayList(u8).init(std.testing.allocator);
+    defer out.deinit();
+
+    try debug_command(&debugger, "break @SWAP", out.writer());
+    try debug_command(&debugger, "debug 1 2 @SWAP", out.writer());
+    try debug_command(&debugger, "continue", out.writer());
+    try std.testing.expectEqualStrings("break at @SWAP: <2> 1 2\nfinished: <2> 2 1\n", out.items);
+
+    try std.testing.expectEqual(@as(usize, 1), try runtime.stack_pop_value(usize));
+    try std.testing.expectEqual(@as(usize, 2), try runtime.stack_pop_value(usize));
+}
+
+// SIGINT handling. Signal handlers can't be handed any context, so this is
+// stashed globally, and only ever touched atomically (or, for repl_runtime,
+// written once before the handler is installed).
+var repl_runtime: ?*gale.Runtime = null;
+var evaluating = std.atomic.Atomic(bool).init(false);
+var idle_interrupts = std.atomic.Atomic(u8).init(0);
+
+/// Not yet on Windows, where Ctrl-C still kills the REPL outright.
+fn install_sigint_handler(runtime: *gale.Runtime) void {
+    repl_runtime = runtime;
+
+    // TODO: SetConsoleCtrlHandler, for Windows.
+    if (comptime builtin.os.tag == .windows) return;
+
+    var action = std.os.Sigaction{
+        .handler = .{ .handler = &handle_sigint },
+        .mask = std.os.empty_sigset,
+        // Reads from the prompt (or from words like @READ-LINE) carry on
+        // waiting rather than failing with EINTR.
+        .flags = std.os.SA.RESTART,
+    };
+    std.os.sigaction(std.os.SIG.INT, &action, null);
+}
+
+/// The first Ctrl-C during evaluation interrupts it (see
+/// Runtime.interrupt), unwinding back to the prompt. A second one before the
+/// first has been noticed (say, while a primitive is stuck waiting on I/O)
+/// exits, as do two in a row at an idle prompt.
+fn handle_sigint(_: c_int) callconv(.C) void {
+    const runtime = repl_runtime orelse return;
+
+    if (evaluating.load(.Acquire)) {
+        if (!runtime.interrupt_pending()) return runtime.interrupt();
+        std.os.exit(130);
+    }
+
+    if (idle_interrupts.fetchAdd(1, .AcqRel) > 0) std.os.exit(130);
+
+    // Only async-signal-safe calls from here on out, which rules out
+    // anything buffered.
+    const hint = "\n(press Ctrl-C again to exit)\n" ++ PROMPT;
+    _ = std.os.write(std.io.getStdErr().handle, hint) catch {};
+}
+
+const CliError = error{ UnknownArgument, TooManyArguments };
+
+const FmtOptions = struct {
+    /// Report files which aren't formatted, rather than rewriting them.
+    check: bool = false,
+    /// Files to format. With none, stdin is formatted to stdout.
+    paths: []const []const u8,
+};
+
+fn parse_fmt_flags(args: []const []const u8) CliError!FmtOptions {
+    var options = FmtOptions{ .paths = args };
+
+    if (args.len > 0 and std.mem.eql(u8, args[0], "--check")) {
+        options.check = true;
+        options.paths = args[1..];
+    }
+
+    for (options.paths) |path| {
+        if (std.mem.startsWith(u8, path, "--")) return CliError.UnknownArgument;
+    }
+
+    return options;
+}
+
+test "parse_fmt_flags" {
+    const plain = try parse_fmt_flags(&.{ "a.gale", "b.gale" });
+    try std.testing.expect(!plain.check);
+    try std.testing.expectEqual(@as(usize, 2), plain.paths.len);
+
+    const check = try parse_fmt_flags(&.{ "--check", "a.gale" });
+    try std.testing.expect(check.check);
+    try std.testing.expectEqualStrings("a.gale", check.paths[0]);
+
+    try std.testing.expectError(CliError.UnknownArgument, parse_fmt_flags(&.{ "a.gale", "--in-place" }));
+}
+
+/// `gale fmt [--check] [FILES...]`: rewrite each file in gale's canonical
+/// layout (see lib/gale/formatter.zig), or with --check, list those which
+/// would change without touching them. Returns the exit status: 1 if --check
+/// found anything, 2 if any file couldn't be formatted at all.
+fn fmt(alloc: std.mem.Allocator, args: []const []const u8) !u8 {
+    const options = try parse_fmt_flags(args);
+    const stdout = std.io.getStdOut().writer();
+
+    if (options.paths.len == 0) {
+        const source = try std.io.getStdIn().reader().readAllAlloc(alloc, MAX_SOURCE_LEN);
+        defer alloc.free(source);
+
+        const formatted = try gale.formatter.format(alloc, source);
+        defer alloc.free(formatted);
+
+        if (options.check) return @boolToInt(!std.mem.eql(u8, source, formatted));
+        try stdout.writeAll(formatted);
+        return 0;
+    }
+
+    var status: u8 = 0;
+    for (options.paths) |path| {
+        const source = try read_source(alloc, path);
+        defer alloc.free(source);
+
+        const formatted = gale.formatter.format(alloc, source) catch |err| {
+            std.debug.print("gale fmt: {s}: {}\n", .{ path, painted(err, stderr_style()) });
+            status = 2;
+            continue;
+        };
+        defer alloc.free(formatted);
+
+        if (std.mem.eql(u8, source, formatted)) continue;
+
+        if (options.check) {
+            try stdout.print("{s}\n", .{path});
+            status = std.math.max(status, 1);
+        } else {
+            try std.fs.cwd().writeFile(path, formatted);
+        }
+    }
+
+    return status;
+}
+
+const TRACE_FLAG = "--trace";
+const LOG_EVENTS_FLAG = "--log-events";
+const LIB_FLAG = "--lib=";
+const MAX_LIB_FLAGS = 32;
+const FLAG_FLAG = "--flag=";
+const MAX_FLAG_FLAGS = 32;
+const IMAGE_FLAG = "--image=";
+const VISUALIZE_FLAG = "--visualize";
+const RECORD_FLAG = "--record=";
+const REPLAY_FLAG = "--replay=";
+
+const CliOptions = struct {
+    /// Folded together from any number of --allow-* flags. Nothing is
+    /// granted by default.
+    granted: gale.Capabilities = gale.capabilities.NONE,
+    /// Set by --trace (to stderr) or --trace=PATH. See gale.Tracer.
+    trace: ?Destination = null,
+    /// Set by --log-events (to stderr) or --log-events=PATH. See
+    /// gale.EventLog.
+    event_log: ?Destination = null,
+    /// From each --lib=DIR, searched (in order) ahead of GLUUMY_PATH and the
+    /// defaults. See gale.library_path.
+    lib_dirs: std.BoundedArray([]const u8, MAX_LIB_FLAGS) = .{},
+    /// From each --flag=NAME, for `#if-flag:NAME`. See gale.conditional.
+    flags: std.BoundedArray([]const u8, MAX_FLAG_FLAGS) = .{},
+    /// From --image=PATH: an image to pick up where it left off, if it
+    /// exists yet, which @SAVE-IMAGE can save back to. See gale.image.
+    image: ?[]const u8 = null,
+    /// Set by --visualize, or --visualize=MS to dwell MS milliseconds on
+    /// each word: the REPL draws the runtime full-screen as it goes. See
+    /// gale.Visualizer.
+    visualize: ?u64 = null,
+    /// From --record=PATH or --replay=PATH: where `gale run` should write
+    /// down what its script reads from the world, or read it back from, to
+    /// run just as a recorded run did. See gale.recording.
+    recording: ?Recording = null,
+
+    const Recording = union(enum) {
+        Record: []const u8,
+        Replay: []const u8,
+    };
+
+    const Destination = union(enum) {
+        Stderr,
+        File: []const u8,
+    };
+};
+
+fn parse_flags(args: []const []const u8) CliError!CliOptions {
+    var options = CliOptions{};
+
+    for (args) |arg| {
+        if (std.mem.eql(u8, arg, TRACE_FLAG)) {
+            options.trace = .Stderr;
+        } else if (std.mem.startsWith(u8, arg, TRACE_FLAG ++ "=")) {
+            options.trace = .{ .File = arg[TRACE_FLAG.len + 1 ..] };
+        } else if (std.mem.eql(u8, arg, LOG_EVENTS_FLAG)) {
+            options.event_log = .Stderr;
+        } else if (std.mem.startsWith(u8, arg, LOG_EVENTS_FLAG ++ "=")) {
+            options.event_log = .{ .File = arg[LOG_EVENTS_FLAG.len + 1 ..] };
+        } else if (std.mem.startsWith(u8, arg, LIB_FLAG)) {
+            options.lib_dirs.append(arg[LIB_FLAG.len..]) catch return CliError.TooManyArguments;
+        } else if (std.mem.startsWith(u8, arg, FLAG_FLAG)) {
+            options.flags.append(arg[FLAG_FLAG.len..]) catch return CliError.TooManyArguments;
+        } else if (std.mem.startsWith(u8, arg, IMAGE_FLAG)) {
+            options.image = arg[IMAGE_FLAG.len..];
+        } else if (std.mem.eql(u8, arg, VISUALIZE_FLAG)) {
+            options.visualize = 0;
+        } else if (std.mem.startsWith(u8, arg, VISUALIZE_FLAG ++ "=")) {
+            options.visualize = std.fmt.parseInt(u64, arg[VISUALIZE_FLAG.len + 1 ..], 10) catch return CliError.UnknownArgument;
+        } else if (std.mem.startsWith(u8, arg, RECORD_FLAG)) {
+            options.recording = .{ .Record = arg[RECORD_FLAG.len..] };
+        } else if (std.mem.startsWith(u8, arg, REPLAY_FLAG)) {
+            options.recording = .{ .Replay = arg[REPLAY_FLAG.len..] };
+        } else {
+            const flag_grants = gale.capabilities.from_flag(arg) orelse return CliError.UnknownArgument;
+            options.granted.setUnion(flag_grants);
+        }
+    }
+
+    return options;
+}
+
+test "parse_flags" {
+    const options = try parse_flags(&.{ "--allow-read", "--allow-net" });
+    try std.testing.expect(options.granted.contains(.Read));
+    try std.testing.expect(options.granted.contains(.Net));
+    try std.testing.expect(!options.granted.contains(.Write));
+    try std.testing.expect(options.trace == null);
+
+    try std.testing.expect((try parse_flags(&.{"--trace"})).trace.? == .Stderr);
+    try std.testing.expectEqualStrings("t.log", (try parse_flags(&.{"--trace=t.log"})).trace.?.File);
+    try std.testing.expect((try parse_flags(&.{"--log-events"})).event_log.? == .Stderr);
+    try std.testing.expectEqualStrings("e.log", (try parse_flags(&.{"--log-events=e.log"})).event_log.?.File);
+
+    const with_libs = try parse_flags(&.{ "--lib=vendor", "--allow-read", "--lib=/opt/gale" });
+    const libs = with_libs.lib_dirs.constSlice();
+    try std.testing.expectEqual(@as(usize, 2), libs.len);
+    try std.testing.expectEqualStrings("vendor", libs[0]);
+    try std.testing.expectEqualStrings("/opt/gale", libs[1]);
+
+    const with_flags = try parse_flags(&.{"--flag=debug"});
+    try std.testing.expectEqualStrings("debug", with_flags.flags.constSlice()[0]);
+    try std.testing.expect(with_flags.image == null);
+
+    const with_image = try parse_flags(&.{"--image=work.image"});
+    try std.testing.expectEqualStrings("work.image", with_image.image.?);
+    try std.testing.expect(with_image.recording == null);
+    try std.testing.expect(with_image.visualize == null);
+
+    try std.testing.expectEqual(@as(?u64, 0), (try parse_flags(&.{"--visualize"})).visualize);
+    try std.testing.expectEqual(@as(?u64, 250), (try parse_flags(&.{"--visualize=250"})).visualize);
+    try std.testing.expectError(CliError.UnknownArgument, parse_flags(&.{"--visualize=slowly"}));
+
+    try std.testing.expectEqualStrings("bug.trace", (try parse_flags(&.{"--record=bug.trace"})).recording.?.Record);
+    try std.testing.expectEqualStrings("bug.trace", (try parse_flags(&.{"--replay=bug.trace"})).recording.?.Replay);
+
+    try std.testing.expectError(CliError.UnknownArgument, parse_flags(&.{"--yolo"}));
+}
+
+const LintOptions = struct {
+    rules: gale.linter.Rules = gale.linter.ALL_RULES,
+    /// One JSON object per line, rather than one line of prose, per
+    /// Diagnostic.
+    json: bool = false,
+    /// How columns are counted in what's reported.
+    columns: gale.unicode.ColumnPolicy = .{},
+    paths: []const []const u8,
+};
+
+/// Rules (see linter.zig) are named as they're reported, and passed
+/// comma-separated: --only=unknown-word,underflow checks just those, and
+/// --disable=shadowed-name everything but. Columns are counted as an editor
+/// would show them, with --tab-width=N (8 by default) and, with
+/// --east-asian-width, wide characters taking two.
+fn parse_lint_flags(args: []const []const u8) CliError!LintOptions {
+    var options = LintOptions{ .paths = args };
+
+    var idx: usize = 0;
+    while (idx < args.len and std.mem.startsWith(u8, args[idx], "--")) : (idx += 1) {
+        const arg = args[idx];
+
+        if (std.mem.eql(u8, arg, "--json")) {
+            options.json = true;
+        } else if (std.mem.startsWith(u8, arg, "--only=")) {
+            options.rules = try parse_rules(arg["--only=".len..]);
+        } else if (std.mem.startsWith(u8, arg, "--disable=")) {
+            var disabled = try parse_rules(arg["--disable=".len..]);
+            var disabled_iter = disabled.iterator();
+            while (disabled_iter.next()) |rule| options.rules.remove(rule);
+        } else if (std.mem.startsWith(u8, arg, "--tab-width=")) {
+            options.columns.tab_width = std.fmt.parseInt(usize, arg["--tab-width=".len..], 10) catch return CliError.UnknownArgument;
+        } else if (std.mem.eql(u8, arg, "--east-asian-width")) {
+            options.columns.east_asian_width = true;
+        } else {
+            return CliError.UnknownArgument;
+        }
+    }
+
+    options.paths = args[idx..];
+    return options;
+}
+
+fn parse_rules(list: []const u8) CliError!gale.linter.Rules {
+    var rules = gale.linter.Rules.initEmpty();
+
+    var names = std.mem.tokenize(u8, list, ",");
+    while (names.next()) |name| rules.insert(gale.linter.Rule.from_name(name) orelse return CliError.UnknownArgument);
+
+    return rules;
+}
+
+test "parse_lint_flags" {
+    const Rule = gale.linter.Rule;
+
+    const plain = try parse_lint_flags(&.{"a.gale"});
+    try std.testing.expect(!plain.json);
+    try std.testing.expect(plain.rules.contains(.Underflow));
+
+    const picky = try parse_lint_flags(&.{ "--json", "--disable=underflow,shadowed-name", "a.gale" });
+    try std.testing.expect(picky.json);
+    try std.testing.expect(!picky.rules.contains(Rule.Underflow));
+    try std.testing.expect(!picky.rules.contains(Rule.ShadowedName));
+    try std.testing.expect(picky.rules.contains(Rule.UnknownWord));
+    try std.testing.expectEqualStrings("a.gale", picky.paths[0]);
+
+    const only = try parse_lint_flags(&.{"--only=unknown-word"});
+    try std.testing.expectEqual(@as(usize, 1), only.rules.count());
+
+    const wide = try parse_lint_flags(&.{ "--tab-width=4", "--east-asian-width", "a.gale" });
+    try std.testing.expectEqual(@as(usize, 4), wide.columns.tab_width);
+    try std.testing.expect(wide.columns.east_asian_width);
+
+    try std.testing.expectError(CliError.UnknownArgument, parse_lint_flags(&.{"--only=nope"}));
+    try std.testing.expectError(CliError.UnknownArgument, parse_lint_flags(&.{"--tab-width=wide"}));
+}
+
+/// `gale lint [--json] [--only=RULES | --disable=RULES] [--tab-width=N]
+/// [--east-asian-width] FILES...`: report
+/// whatever the linter finds in each file, judging words against a Runtime
+/// with every primitive group installed. Returns the exit status: 1 if
+/// anything was found, 2 if any file couldn't be linted at all.
+fn lint(alloc: std.mem.Allocator, args: []const []const u8) !u8 {
+    const options = try parse_lint_flags(args);
+    const stdout = std.io.getStdOut().writer();
+
+    var runtime = try gale.Runtime.init(alloc);
+    defer runtime.deinit();
+
+    var status: u8 = 0;
+    for (options.paths) |path| {
+        const source = try read_source(alloc, path);
+        defer alloc.free(source);
+
+        const found = gale.linter.lint(alloc, &runtime, source, options.rules) catch |err| {
+            std.debug.print("gale lint: {s}: {}\n", .{ path, painted(err, stderr_style()) });
+            status = 2;
+            continue;
+        };
+        defer alloc.free(found);
+
+        if (found.len > 0) status = std.math.max(status, 1);
+
+        for (found) |diagnostic| {
+            const severity = diagnostic.rule.severity().name();
+            const columns = diagnostic.columns(source, options.columns);
+
+            if (options.json) {
+                try std.json.stringify(.{
+                    .path = path,
+                    .line = diagnostic.line,
+                    .column = columns.display,
+                    .grapheme_column = columns.grapheme,
+                    .byte_column = diagnostic.column,
+                    .rule = diagnostic.rule.name(),
+                    .severity = severity,
+                    .word = diagnostic.word,
+                    .message = diagnostic.message,
+                }, .{}, stdout);
+                try stdout.writeByte('\n');
+            } else {
+                try stdout.print("{s}:{d}:{d}: {s}: {s} {s} [{s}]\n", .{
+                    path,
+                    diagnostic.line,
+                    columns.display,
+                    severity,
+                    diagnostic.word,
+                    diagnostic.message,
+                    diagnostic.rule.name(),
+                });
+            }
+        }
+    }
+
+    return status;
+}
+
+const COVERAGE_FLAG = "--coverage";
+
+const TestOptions = struct {
+    coverage: bool = false,
+    paths: []const []const u8 = &.{},
+};
+
+fn parse_test_flags(args: []const []const u8) CliError!TestOptions {
+    var options = TestOptions{};
+    var idx: usize = 0;
+
+    while (idx < args.len and std.mem.startsWith(u8, args[idx], "--")) : (idx += 1) {
+        if (!std.mem.eql(u8, args[idx], COVERAGE_FLAG)) return CliError.UnknownArgument;
+        options.coverage = true;
+    }
+
+    options.paths = args[idx..];
+    return options;
+}
+
+test "parse_test_flags" {
+    const plain = try parse_test_flags(&.{"a.gale"});
+    try std.testing.expect(!plain.coverage);
+    try std.testing.expectEqualStrings("a.gale", plain.paths[0]);
+
+    const covered = try parse_test_flags(&.{ "--coverage", "a.gale", "b.gale" });
+    try std.testing.expect(covered.coverage);
+    try std.testing.expectEqual(@as(usize, 2), covered.paths.len);
+
+    try std.testing.expectError(CliError.UnknownArgument, parse_test_flags(&.{"--nope"}));
+}
+
+/// `gale test [--coverage] FILES...`: load each file into one Runtime, then
+/// run every test they registered with @TEST, each against a fresh Stack,
+/// optionally followed by a report of which defined words the tests ran.
+/// Returns the exit status: 1 if any test failed, 2 if any file couldn't be
+/// loaded at all.
+fn run_tests(alloc: std.mem.Allocator, args: []const []const u8) !u8 {
+    const options = try parse_test_flags(args);
+    const stdout = std.io.getStdOut().writer();
+
+    var runtime = try gale.Runtime.init(alloc);
+    defer runtime.deinit();
+
+    // Only the tests themselves are measured, not the loading of their files.
+    var coverage = gale.Coverage.init(alloc);
+    defer coverage.deinit(&runtime);
+
+    for (options.paths) |path| {
+        const source = try read_source(alloc, path);
+        defer alloc.free(source);
+
+        runtime.eval_source(path, source) catch |err| {
+            std.debug.print("gale test: {s}: {}\n", .{ path, painted(err, stderr_style()) });
+            try write_load_trace(&runtime, std.io.getStdErr().writer(), stderr_style());
+            return 2;
+        };
+    }
+
+    if (options.coverage) try runtime.add_observer(coverage.observer());
+    const summary = try gale.test_runner.run(&runtime, stdout);
+    if (options.coverage) try coverage.write_report(&runtime, stdout);
+
+    return if (summary.failed > 0) 1 else 0;
+}
+
+const PkgOptions = struct {
+    command: Command,
+    /// Where git dependencies are fetched to, relative to the manifest.
+    store: []const u8 = gale.package.DEFAULT_STORE,
+
+    const Command = enum {
+        /// Fetch every dependency not already in the store.
+        fetch,
+        /// Print the root of each dependency, a line each, as they'd be
+        /// searched for modules.
+        path,
+    };
+};
+
+fn parse_pkg_flags(args: []const []const u8) CliError!PkgOptions {
+    if (args.len == 0) return CliError.UnknownArgument;
+    var options = PkgOptions{
+        .command = std.meta.stringToEnum(PkgOptions.Command, args[0]) orelse return CliError.UnknownArgument,
+    };
+
+    for (args[1..]) |arg| {
+        if (!std.mem.startsWith(u8, arg, "--store=")) return CliError.UnknownArgument;
+        options.store = arg["--store=".len..];
+    }
+
+    return options;
+}
+
+test "parse_pkg_flags" {
+    const fetch = try parse_pkg_flags(&.{"fetch"});
+    try std.testing.expect(fetch.command == .fetch);
+    try std.testing.expectEqualStrings(gale.package.DEFAULT_STORE, fetch.store);
+
+    const path = try parse_pkg_flags(&.{ "path", "--store=vendor" });
+    try std.testing.expect(path.command == .path);
+    try std.testing.expectEqualStrings("vendor", path.store);
+
+    try std.testing.expectError(CliError.UnknownArgument, parse_pkg_flags(&.{}));
+    try std.testing.expectError(CliError.UnknownArgument, parse_pkg_flags(&.{"install"}));
+}
+
+/// `gale pkg fetch|path [--store=DIR]`: fetch the dependencies named by the
+/// package manifest (see lib/gale/package.zig) in the working directory, or
+/// list where they live. The REPL searches those same places for @LOAD and
+/// @IMPORT. Returns the exit status: 2 if anything couldn't be fetched.
+fn pkg(alloc: std.mem.Allocator, args: []const []const u8) !u8 {
+    const options = try parse_pkg_flags(args);
+    const stdout = std.io.getStdOut().writer();
+
+    var manifest = try gale.package.read_manifest(alloc, ".");
+    defer manifest.deinit();
+
+    switch (options.command) {
+        .fetch => gale.package.fetch(alloc, &manifest, ".", options.store, .{}, stdout) catch |err| {
+            std.debug.print("gale pkg: {s}: {}\n", .{ manifest.name, painted(err, stderr_style()) });
+            return 2;
+        },
+        .path => {
+            const roots = try gale.package.roots(alloc, &manifest, ".", options.store);
+            defer gale.library_path.free(alloc, roots);
+            for (roots) |root| try stdout.print("{s}\n", .{root});
+        },
+    }
+
+    return 0;
+}
+
+const RunOptions = struct {
+    cli: CliOptions,
+    /// A script, or a bundle made by `gale bundle`.
+    path: []const u8,
+};
+
+fn parse_run_flags(args: []const []const u8) CliError!RunOptions {
+    if (args.len == 0) return CliError.UnknownArgument;
+    if (std.mem.startsWith(u8, args[args.len - 1], "--")) return CliError.UnknownArgument;
+
+    return RunOptions{
+        .cli = try parse_flags(args[0 .. args.len - 1]),
+        .path = args[args.len - 1],
+    };
+}
+
+test "parse_run_flags" {
+    const plain = try parse_run_flags(&.{"main.gale"});
+    try std.testing.expectEqualStrings("main.gale", plain.path);
+
+    const flagged = try parse_run_flags(&.{ "--allow-read", "--flag=debug", "app.glm" });
+    try std.testing.expect(flagged.cli.granted.contains(.Read));
+    try std.testing.expectEqualStrings("app.glm", flagged.path);
+
+    try std.testing.expectError(CliError.UnknownArgument, parse_run_flags(&.{}));
+    try std.testing.expectError(CliError.UnknownArgument, parse_run_flags(&.{"--allow-read"}));
+    try std.testing.expectError(CliError.UnknownArgument, parse_run_flags(&.{ "a.gale", "b.gale" }));
+}
+
+/// Take up whichever of gale.hashbang.FLAG_PREFIXES `hashbang` gives, after
+/// those from the command line.
+fn honor_hashbang(options: *CliOptions, hashbang: gale.hashbang.Hashbang) CliError!void {
+    var args = hashbang.arguments();
+    while (gale.hashbang.Hashbang.next_gale_flag(&args)) |arg| {
+        const parsed = try parse_flags(&.{arg});
+        options.lib_dirs.appendSlice(parsed.lib_dirs.constSlice()) catch return CliError.TooManyArguments;
+        options.flags.appendSlice(parsed.flags.constSlice()) catch return CliError.TooManyArguments;
+    }
+}
+
+test "honor_hashbang" {
+    var options = try parse_flags(&.{"--flag=cli"});
+    try honor_hashbang(&options, gale.hashbang.parse("#!/usr/bin/env -S gale run --allow-net --flag=debug --lib=vendor").?);
+
+    try std.testing.expect(!options.granted.contains(.Net));
+    try std.testing.expectEqualStrings("cli", options.flags.constSlice()[0]);
+    try std.testing.expectEqualStrings("debug", options.flags.constSlice()[1]);
+    try std.testing.expectEqualStrings("vendor", options.lib_dirs.constSlice()[0]);
+}
+
+/// `gale run [FLAGS] PATH`: evaluate a script, or a bundle (see
+/// lib/gale/bundle.zig), with the REPL's flags, and exit. A script's
+/// hashbang can add --lib= and --flag= flags of its own. With
+/// --record=PATH, whatever the script reads from the world is written down
+/// to PATH, and with --replay=PATH, read back from there instead, so that a
+/// failing run can be rerun exactly. Returns the exit status: 2 if it
+/// failed.
+fn run(alloc: std.mem.Allocator, args: []const []const u8) !u8 {
+    var options = try parse_run_flags(args);
+
+    const source = try read_source(alloc, options.path);
+    defer alloc.free(source);
+
+    if (gale.hashbang.parse(source[gale.source_encoding.bom_len(source)..])) |hashbang| {
+        honor_hashbang(&options.cli, hashbang) catch |err| {
+            std.debug.print("gale run: {s}: hashbang: {}\n", .{ options.path, painted(err, stderr_style()) });
+            return 2;
+        };
+    }
+
+    const lib_path = try library_path_for(alloc, options.cli.lib_dirs.constSlice());
+    defer gale.library_path.free(alloc, lib_path);
+
+    var recording_file: ?std.fs.File = null;
+    defer if (recording_file) |file| file.close();
+    var recorder: gale.recording.Recorder = undefined;
+    var replay: ?gale.recording.Replay = null;
+    defer if (replay) |*it| it.deinit();
+
+    const recording: gale.recording.Source = if (options.cli.recording) |wanted| switch (wanted) {
+        .Record => |path| blk: {
+            recording_file = try std.fs.cwd().createFile(path, .{});
+            recorder = gale.recording.Recorder.init(gale.io.file_output(&recording_file.?));
+            break :blk .{ .Record = &recorder };
+        },
+        .Replay => |path| blk: {
+            const trace = try std.fs.cwd().readFileAlloc(alloc, path, MAX_RECORDING_LEN);
+            defer alloc.free(trace);
+            replay = try gale.recording.Replay.init(alloc, trace);
+            break :blk .{ .Replay = &replay.? };
+        },
+    } else .Live;
+
+    var runtime = try runtime_for(alloc, &options.cli, lib_path, recording);
+    defer runtime.deinit();
+
+    const evaluated: anyerror!void = if (std.mem.startsWith(u8, source, gale.bundle.MAGIC))
+        runtime.eval_bundle(source)
+    else
+        runtime.eval_source(options.path, source);
+
+    if (replay) |*it| {
+        if (!it.finished()) std.debug.print("gale run: {s}: some of the recording went unreplayed, so this run went differently\n", .{options.path});
+    }
+
+    evaluated catch |err| {
+        std.debug.print("gale run: {s}: {}\n", .{ options.path, painted(err, stderr_style()) });
+        try write_load_trace(&runtime, std.io.getStdErr().writer(), stderr_style());
+        return 2;
+    };
+
+    return 0;
+}
+
+/// Run `text`, a bundle tacked onto this very executable, with the REPL's
+/// `args`, but nothing from the library path: everything it needs should be
+/// bundled.
+fn run_bundled(alloc: std.mem.Allocator, text: []const u8, args: []const []const u8) !u8 {
+    const options = try parse_flags(args);
+
+    var runtime = try runtime_for(alloc, &options, &.{}, .Live);
+    defer runtime.deinit();
+
+    runtime.eval_bundle(text) catch |err| {
+        std.debug.print("error: {}\n", .{painted(err, stderr_style())});
+        try write_load_trace(&runtime, std.io.getStdErr().writer(), stderr_style());
+        return 2;
+    };
+
+    return 0;
+}
+
+/// The bundle at the end of this executable (see gale.bundle.TRAILER), if
+/// there is one.
+fn embedded_bundle(alloc: std.mem.Allocator) !?[]u8 {
+    const exe_path = try std.fs.selfExePathAlloc(alloc);
+    defer alloc.free(exe_path);
+
+    const file = try std.fs.openFileAbsolute(exe_path, .{});
+    defer file.close();
+
+    const size = (try file.stat()).size;
+    if (size < gale.bundle.TRAILER_LEN) return null;
+
+    var trailer: [gale.bundle.TRAILER_LEN]u8 = undefined;
+    if (try file.preadAll(&trailer, size - trailer.len) != trailer.len) return null;
+    const len = gale.bundle.length_from_trailer(&trailer) orelse return null;
+    if (len > size - trailer.len) return null;
+
+    const text = try alloc.alloc(u8, @intCast(usize, len));
+    errdefer alloc.free(text);
+    if (try file.preadAll(text, size - trailer.len - len) != text.len) return error.EndOfStream;
+    return text;
+}
+
+const BundleOptions = struct {
+    /// Copy this executable, with the bundle tacked on the end, rather than
+    /// writing the bundle alone.
+    binary: bool = false,
+    /// Defaults to the entry script's name, less its extension (and plus
+    /// .glm, unless it's a binary), in the working directory. `-` is stdout,
+    /// for bundles alone.
+    output: ?[]const u8 = null,
+    /// As for the REPL's --lib=DIR.
+    lib_dirs: std.BoundedArray([]const u8, MAX_LIB_FLAGS) = .{},
+    entry: []const u8 = "",
+};
+
+fn parse_bundle_flags(args: []const []const u8) CliError!BundleOptions {
+    var options = BundleOptions{};
+    var idx: usize = 0;
+
+    while (idx < args.len and std.mem.startsWith(u8, args[idx], "--")) : (idx += 1) {
+        const arg = args[idx];
+        if (std.mem.eql(u8, arg, "--binary")) {
+            options.binary = true;
+        } else if (std.mem.startsWith(u8, arg, "--output=")) {
+            options.output = arg["--output=".len..];
+        } else if (std.mem.startsWith(u8, arg, LIB_FLAG)) {
+            options.lib_dirs.append(arg[LIB_FLAG.len..]) catch return CliError.TooManyArguments;
+        } else {
+            return CliError.UnknownArgument;
+        }
+    }
+
+    if (idx == args.len) return CliError.UnknownArgument;
+    if (idx + 1 < args.len) return CliError.TooManyArguments;
+    options.entry = args[idx];
+    return options;
+}
+
+test "parse_bundle_flags" {
+    const plain = try parse_bundle_flags(&.{"main.gale"});
+    try std.testing.expect(!plain.binary);
+    try std.testing.expect(plain.output == null);
+    try std.testing.expectEqualStrings("main.gale", plain.entry);
+
+    const binary = try parse_bundle_flags(&.{ "--binary", "--lib=vendor", "--output=app", "main.gale" });
+    try std.testing.expect(binary.binary);
+    try std.testing.expectEqualStrings("app", binary.output.?);
+    try std.testing.expectEqualStrings("vendor", binary.lib_dirs.constSlice()[0]);
+
+    try std.testing.expectError(CliError.UnknownArgument, parse_bundle_flags(&.{}));
+    try std.testing.expectError(CliError.UnknownArgument, parse_bundle_flags(&.{"--static"}));
+    try std.testing.expectError(CliError.TooManyArguments, parse_bundle_flags(&.{ "a.gale", "b.gale" }));
+}
+
+/// `gale bundle [--binary] [--output=PATH] [--lib=DIR...] ENTRY`: gather an
+/// entry script and everything it imports or loads (see
+/// lib/gale/bundle.zig) into one .glm file, for `gale run`, or into a copy of
+/// this executable which runs nothing else. Returns the exit status: 2 if
+/// anything couldn't be found.
+fn make_bundle(alloc: std.mem.Allocator, args: []const []const u8) !u8 {
+    const options = try parse_bundle_flags(args);
+
+    const lib_path = try library_path_for(alloc, options.lib_dirs.constSlice());
+    defer gale.library_path.free(alloc, lib_path);
+
+    var runtime = try gale.Runtime.init_with_options(alloc, .{ .library_path = lib_path });
+    defer runtime.deinit();
+
+    var collected = gale.bundle.collect(alloc, &runtime, options.entry) catch |err| {
+        std.debug.print("gale bundle: {s}: {}\n", .{ options.entry, painted(err, stderr_style()) });
+        try write_load_trace(&runtime, std.io.getStdErr().writer(), stderr_style());
+        return 2;
+    };
+    defer collected.deinit();
+
+    var text = std.ArrayList(u8).init(alloc);
+    defer text.deinit();
+    try gale.bundle.write(text.writer(), collected.files.items, collected.entry);
+
+    const stem = std.fs.path.stem(options.entry);
+    const default_output = try std.mem.concat(alloc, u8, &.{ stem, if (options.binary) "" else ".glm" });
+    defer alloc.free(default_output);
+    const output = options.output orelse default_output;
+
+    if (!options.binary) {
+        if (std.mem.eql(u8, output, "-")) {
+            try std.io.getStdOut().writeAll(text.items);
+        } else {
+            try std.fs.cwd().writeFile(output, text.items);
+        }
+        return 0;
+    }
+
+    // The copy keeps this executable's mode, and so stays executable.
+    const exe_path = try std.fs.selfExePathAlloc(alloc);
+    defer alloc.free(exe_path);
+    try std.fs.cwd().copyFile(exe_path, std.fs.cwd(), output, .{});
+
+    const file = try std.fs.cwd().openFile(output, .{ .mode = .write_only });
+    defer file.close();
+    try file.seekFromEnd(0);
+    try file.writeAll(text.items);
+    try file.writeAll(&gale.bundle.trailer_for(text.items.len));
+
+    return 0;
+}
+
+/// `gale kernel [FLAGS]`: answer evaluation requests from stdin on stdout,
+/// a line of JSON apiece, for notebooks and the like (see
+/// lib/gale/notebook.zig), with the REPL's flags, until stdin runs dry.
+fn kernel(alloc: std.mem.Allocator, args: []const []const u8) !u8 {
+    const options = try parse_flags(args);
+
+    const lib_path = try library_path_for(alloc, options.lib_dirs.constSlice());
+    defer gale.library_path.free(alloc, lib_path);
+
+    var notebook_kernel = gale.notebook.Kernel.init(alloc);
+    defer notebook_kernel.deinit();
+
+    var runtime = try runtime_for(alloc, &options, lib_path, .Live);
+    defer runtime.deinit();
+    runtime.stdout = notebook_kernel.output();
+
+    var input = std.io.bufferedReader(std.io.getStdIn().reader());
+    var output = std.io.bufferedWriter(std.io.getStdOut().writer());
+
+    while (try input.reader().readUntilDelimiterOrEofAlloc(alloc, '\n', MAX_SOURCE_LEN)) |line| {
+        defer alloc.free(line);
+        try notebook_kernel.handle(&runtime, std.mem.trimRight(u8, line, "\r"), output.writer());
+        try output.flush();
+    }
+
+    return 0;
+}
+
+const TagsOptions = struct {
+    /// Write an Emacs-style TAGS file, rather than a vi-style tags file.
+    etags: bool = false,
+    /// Write every file's semantic tokens (see
+    /// lib/gale/semantic_tokens.zig), one JSON object per line, rather than
+    /// an index.
+    tokens: bool = false,
+    /// Defaults to tags or TAGS, as befits the format, in the working
+    /// directory, or to stdout for --tokens. `-` is stdout.
+    output: ?[]const u8 = null,
+    /// Files to index, and directories to search for .gale files to index.
+    paths: []const []const u8 = &.{},
+};
+
+fn parse_tags_flags(args: []const []const u8) CliError!TagsOptions {
+    var options = TagsOptions{};
+    var idx: usize = 0;
+
+    while (idx < args.len and std.mem.startsWith(u8, args[idx], "--")) : (idx += 1) {
+        const arg = args[idx];
+        if (std.mem.eql(u8, arg, "--etags")) {
+            options.etags = true;
+        } else if (std.mem.eql(u8, arg, "--tokens")) {
+            options.tokens = true;
+        } else if (std.mem.startsWith(u8, arg, "--output=")) {
+            options.output = arg["--output=".len..];
+        } else {
+            return CliError.UnknownArgument;
+        }
+    }
+
+    options.paths = args[idx..];
+    return options;
+}
+
+test "parse_tags_flags" {
+    const plain = try parse_tags_flags(&.{"src"});
+    try std.testing.expect(!plain.etags);
+    try std.testing.expect(plain.output == null);
+    try std.testing.expectEqualStrings("src", plain.paths[0]);
+
+    const emacs = try parse_tags_flags(&.{ "--etags", "--output=-", "a.gale" });
+    try std.testing.expect(emacs.etags);
+    try std.testing.expectEqualStrings("-", emacs.output.?);
+    try std.testing.expect(!emacs.tokens);
+
+    try std.testing.expect((try parse_tags_flags(&.{ "--tokens", "a.gale" })).tokens);
+
+    try std.testing.expectError(CliError.UnknownArgument, parse_tags_flags(&.{"--ctags"}));
+}
+
+/// `gale tags [--etags | --tokens] [--output=PATH] PATHS...`: index the
+/// words, shapes, and vocabularies defined in each file (and each .gale file
+/// within each directory) for editors' jump-to-definition, or with --tokens,
+/// list what each span of them is for syntax highlighting. Returns the exit
+/// status: 2 if any file couldn't be indexed, though the rest still are.
+fn make_tags(alloc: std.mem.Allocator, args: []const []const u8) !u8 {
+    const options = try parse_tags_flags(args);
+
+    var paths = std.ArrayList([]u8).init(alloc);
+    defer {
+        for (paths.items) |path| alloc.free(path);
+        paths.deinit();
+    }
+    for (options.paths) |path| try collect_sources(alloc, &paths, path);
+
+    var sources = std.ArrayList(gale.project.Source).init(alloc);
+    defer {
+        for (sources.items) |source| alloc.free(source.text);
+        sources.deinit();
+    }
+    for (paths.items) |path| {
+        const text = try read_source(alloc, path);
+        errdefer alloc.free(text);
+        try sources.append(.{ .path = path, .text = text });
+    }
+
+    if (options.tokens) return write_semantic_tokens(alloc, options.output orelse "-", sources.items);
+
+    var project = try gale.project.scan(alloc, sources.items);
+    defer project.deinit();
+
+    for (project.failures) |failure| {
+        std.debug.print("gale tags: {s}: {}\n", .{ failure.path, painted(failure.err, stderr_style()) });
+    }
+    const status: u8 = if (project.failures.len > 0) 2 else 0;
+
+    const output = options.output orelse if (options.etags) "TAGS" else "tags";
+    const to_stdout = std.mem.eql(u8, output, "-");
+
+    var file = if (to_stdout) std.io.getStdOut() else try std.fs.cwd().createFile(output, .{});
+    defer if (!to_stdout) file.close();
+
+    var buffered = std.io.bufferedWriter(file.writer());
+    if (options.etags) {
+        try gale.tags.write_etags(alloc, buffered.writer(), project.files);
+    } else {
+        try gale.tags.write_ctags(alloc, buffered.writer(), project.files);
+    }
+    try buffered.flush();
+
+    return status;
+}
+
+fn write_semantic_tokens(alloc: std.mem.Allocator, output: []const u8, sources: []const gale.project.Source) !u8 {
+    const to_stdout = std.mem.eql(u8, output, "-");
+    var file = if (to_stdout) std.io.getStdOut() else try std.fs.cwd().createFile(output, .{});
+    defer if (!to_stdout) file.close();
+
+    var buffered = std.io.bufferedWriter(file.writer());
+    var status: u8 = 0;
+
+    for (sources) |source| {
+        const tokens = gale.semantic_tokens.classify(alloc, source.text) catch |err| {
+            std.debug.print("gale tags: {s}: {}\n", .{ source.path, painted(err, stderr_style()) });
+            status = 2;
+            continue;
+        };
+        defer alloc.free(tokens);
+
+        for (tokens) |token| {
+            try std.json.stringify(.{
+                .path = source.path,
+                .line = token.line,
+                .column = token.column,
+                .length = token.len,
+                .category = token.category.name(),
+            }, .{}, buffered.writer());
+            try buffered.writer().writeByte('\n');
+        }
+    }
+
+    try buffered.flush();
+    return status;
+}
+
+/// Append `path` to `paths` if it's a file, or every .gale file beneath it if
+/// it's a directory. Appended paths are owned by the caller.
+fn collect_sources(alloc: std.mem.Allocator, paths: *std.ArrayList([]u8), path: []const u8) !void {
+    var dir = std.fs.cwd().openIterableDir(path, .{}) catch |err| switch (err) {
+        error.NotDir => {
+            const owned = try alloc.dupe(u8, path);
+            errdefer alloc.free(owned);
+            return paths.append(owned);
+        },
+        else => return err,
+    };
+    defer dir.close();
+
+    var walker = try dir.walk(alloc);
+    defer walker.deinit();
+
+    while (try walker.next()) |entry| {
+        if (entry.kind != .File or !std.mem.endsWith(u8, entry.basename, ".gale")) continue;
+
+        const joined = try std.fs.path.join(alloc, &.{ path, entry.path });
+        errdefer alloc.free(joined);
+        try paths.append(joined);
+    }
 }
@@ -12,6 +12,32 @@
 // PERFORMANCE OF THIS SOFTWARE.
 
 const std = @import("std");
+const gale = @import("gale");
+
+const testAllocator = std.testing.allocator;
+const expectEqual = std.testing.expectEqual;
+
+// Only the public embedding API is used here, so these double as a check that
+// gale.zig exports enough to do useful things from outside the library.
+test "embedding: host-defined words are callable from source" {
+    const Host = struct {
+        fn forty_two(rt: *gale.Runtime) anyerror!void {
+            try rt.stack_push_uint(42);
+        }
+    };
+
+    var rt = try gale.Runtime.init(testAllocator);
+    defer rt.deinit();
+
+    try rt.define_word("forty-two", try rt.word_from_primitive_impl(&Host.forty_two, null));
+    try rt.eval("forty-two");
+
+    var result: gale.Object = try rt.stack_pop();
+    defer rt.release_heaped_object_reference(&result);
+    try expectEqual(@as(usize, 42), result.UnsignedInt);
+
+    try std.testing.expectError(gale.InternalError.UnknownWord, rt.eval("forty-three"));
+}
 
 test {
     std.testing.refAllDecls(@This());